@@ -0,0 +1,149 @@
+/*! Aliasing of sequence names between naming styles.
+
+The same sequences are named differently by different providers. UCSC names chromosomes `chr1`
+and `chrM`, Ensembl names them `1` and `MT`, while GenBank and RefSeq use their sequence
+accessions. Aliases translate sequence names of any style into a single target style, using the
+names in an NCBI assembly report when given, and otherwise falling back to built-in rules for the
+UCSC and Ensembl chromosome names.
+
+Aliases are applied to gene and transcript streams, or to genes and transcripts before they are
+written, through the `aliases` method of `SeqRewrite`.
+*/
+use std::collections::HashMap;
+use std::error::Error;
+use std::io::{self, BufRead, BufReader};
+use std::path::Path;
+
+use utils::open_path;
+
+
+quick_error! {
+    /// Errors that occur when reading sequence aliases.
+    #[derive(Debug)]
+    pub enum AliasError {
+        /// Occurs when an assembly report line can not be parsed.
+        InvalidReportLine(line: String) {
+            description("invalid assembly report line")
+            display(self_) -> ("{}: '{}'", self_.description(), line)
+        }
+        /// Errors propagated from reading the underlying stream.
+        Io(err: io::Error) {
+            description(err.description())
+            from()
+            cause(err)
+        }
+    }
+}
+
+/// Sequence naming styles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NameStyle {
+    /// UCSC names, such as `chr1`, `chrM`, and `chrUn_KI270302v1`.
+    Ucsc,
+    /// Ensembl names, such as `1`, `MT`, and `KI270302.1`.
+    Ensembl,
+    /// GenBank accessions, such as `CM000663.2`.
+    GenBank,
+    /// RefSeq accessions, such as `NC_000001.11`.
+    RefSeq,
+}
+
+/// Value used for absent names in NCBI assembly reports.
+const REPORT_NA: &'static str = "na";
+
+/// Sequence name aliases for a target naming style.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SeqAliases {
+    style: NameStyle,
+    names: HashMap<String, String>,
+}
+
+impl SeqAliases {
+
+    /// Creates aliases to the given naming style, using only the built-in rules.
+    ///
+    /// The built-in rules translate between the UCSC and Ensembl names of numbered, sex, and
+    /// mitochondrial chromosomes. All other names are kept as-is.
+    pub fn new(style: NameStyle) -> Self {
+        SeqAliases {
+            style: style,
+            names: HashMap::new(),
+        }
+    }
+
+    /// Creates aliases to the given naming style from an NCBI assembly report.
+    ///
+    /// All names of each sequence in the report, including its UCSC-style name, are aliased to
+    /// its name in the given style. The Ensembl name of a sequence is its sequence name for
+    /// assembled molecules, and its GenBank accession otherwise. Names missing from the report
+    /// fall back to the built-in rules.
+    pub fn from_assembly_report<R: io::Read>(in_reader: R, style: NameStyle) -> ::Result<Self> {
+        let mut aliases = SeqAliases::new(style);
+        for line in BufReader::new(in_reader).lines() {
+            let line = line.map_err(|e| ::Error::from(AliasError::from(e)))?;
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let cols = line.split('\t').map(|col| col.trim()).collect::<Vec<&str>>();
+            if cols.len() < 10 {
+                return Err(::Error::from(AliasError::InvalidReportLine(line.clone())));
+            }
+            let ensembl = if cols[1] == "assembled-molecule" { cols[0] } else { cols[4] };
+            let target = match style {
+                NameStyle::Ucsc => cols[9],
+                NameStyle::Ensembl => ensembl,
+                NameStyle::GenBank => cols[4],
+                NameStyle::RefSeq => cols[6],
+            };
+            if target == REPORT_NA {
+                continue;
+            }
+            for name in [cols[0], ensembl, cols[4], cols[6], cols[9]].iter() {
+                if *name != REPORT_NA {
+                    let _ = aliases.insert(*name, target);
+                }
+            }
+        }
+        Ok(aliases)
+    }
+
+    /// Creates aliases to the given naming style from an NCBI assembly report file, which may
+    /// be gzip-compressed.
+    pub fn from_assembly_report_path<P>(path: P, style: NameStyle) -> ::Result<Self>
+        where P: AsRef<Path>
+    {
+        let file = open_path(path).map_err(|e| ::Error::from(AliasError::from(e)))?;
+        SeqAliases::from_assembly_report(file, style)
+    }
+
+    /// Returns the target naming style.
+    pub fn style(&self) -> NameStyle {
+        self.style
+    }
+
+    /// Aliases the given name to the given target name.
+    pub fn insert<S, T>(&mut self, alias: S, name: T) -> &mut Self
+        where S: Into<String>, T: Into<String>
+    {
+        let _ = self.names.insert(alias.into(), name.into());
+        self
+    }
+
+    /// Returns the name of the given sequence in the target naming style.
+    pub fn resolve(&self, seq_name: &str) -> String {
+        if let Some(name) = self.names.get(seq_name) {
+            return name.clone();
+        }
+        let bare = if seq_name.starts_with("chr") { &seq_name[3..] } else { seq_name };
+        let is_chrom = bare == "X" || bare == "Y" ||
+            (!bare.is_empty() && bare.chars().all(|c| c.is_digit(10)));
+        let is_mito = bare == "M" || bare == "MT";
+        match self.style {
+            NameStyle::Ucsc if is_mito => "chrM".to_owned(),
+            NameStyle::Ucsc if is_chrom => format!("chr{}", bare),
+            NameStyle::Ensembl if is_mito => "MT".to_owned(),
+            NameStyle::Ensembl if is_chrom => bare.to_owned(),
+            _ => seq_name.to_owned(),
+        }
+    }
+}
@@ -0,0 +1,677 @@
+/*! Genome-wide container of genes with overlap queries, and adapters over gene streams.
+
+*/
+use std::cmp::{max, min, Ordering};
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+use std::vec;
+
+use bio::data_structures::interval_tree::IntervalTree;
+use bio::utils::Interval;
+
+use {BoundedGenes, BoundsChecker, DuplicatePolicy, Exon, FlankSide, Gene, GeneFeature,
+     GeneFeatureKind, ModelError, SeqDict, ParYGenes, ParYPolicy, SeqRewrite, RewrittenGenes,
+     Strand, Transcript, TranscriptFeature};
+use model::suffixed_id;
+
+
+/// Index of the genes in a single sequence.
+///
+/// Genes are sorted by their start coordinates, and their intervals are stored in an interval
+/// tree keyed to their positions in the sorted genes. Overlap queries take O(log n + k) time
+/// for k hits, regardless of how long the genes are. Since the tree does not support nearest
+/// neighbor queries, the genes are also indexed by their end coordinates. Transcription start
+/// sites are stored separately as sorted tuples of the site coordinate, the gene index, and the
+/// transcript index within the gene.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde_impl", derive(Serialize, Deserialize))]
+struct SeqIndex {
+    genes: Vec<Gene>,
+    tree: IntervalTree<u64, usize>,
+    by_end: Vec<(u64, usize)>,
+    tss: Vec<(u64, usize, usize)>,
+}
+
+impl SeqIndex {
+
+    /// Creates an index of the given genes.
+    fn new(mut genes: Vec<Gene>) -> Self {
+        genes.sort_by_key(|gx| (gx.start(), gx.end()));
+        let mut tree = IntervalTree::new();
+        let mut by_end = Vec::with_capacity(genes.len());
+        let mut tss = Vec::new();
+        for (gidx, gx) in genes.iter().enumerate() {
+            tree.insert(gx.start()..gx.end(), gidx);
+            by_end.push((gx.end(), gidx));
+            for (tidx, trx) in gx.transcripts().values().enumerate() {
+                tss.push((tss_coord(trx), gidx, tidx));
+            }
+        }
+        by_end.sort();
+        tss.sort();
+        SeqIndex {
+            genes: genes,
+            tree: tree,
+            by_end: by_end,
+            tss: tss,
+        }
+    }
+
+    /// Returns the indices of the genes overlapping the given region, in ascending order.
+    fn overlapping_indices(&self, start: u64, end: u64) -> Vec<usize> {
+        let mut idxs = self.tree.find(start..end)
+            .map(|entry| *entry.data())
+            .collect::<Vec<usize>>();
+        idxs.sort();
+        idxs
+    }
+
+    /// Returns the gene nearest to the given coordinate, along with its signed distance.
+    fn nearest_gene(&self, pos: u64) -> Option<(&Gene, i64)> {
+        // Of the genes overlapping the coordinate, the one reaching furthest is picked.
+        let overlapping = self.overlapping_indices(pos, pos + 1).into_iter()
+            .max_by_key(|&gidx| (self.genes[gidx].end(), gidx));
+        if let Some(gidx) = overlapping {
+            let gx = &self.genes[gidx];
+            return Some((gx, signed_distance(gx, pos)));
+        }
+
+        // Otherwise, the nearest gene on the left side is the one that ends last before the
+        // coordinate, and the nearest gene on the right side is the first one after it.
+        let upper = self.by_end
+            .binary_search_by(|&(end, _)| {
+                if end <= pos { Ordering::Less } else { Ordering::Greater }
+            })
+            .unwrap_or_else(|idx| idx);
+        let left = if upper > 0 { Some(&self.genes[self.by_end[upper - 1].1]) } else { None };
+        let first_after = self.genes
+            .binary_search_by(|gx| {
+                if gx.start() <= pos { Ordering::Less } else { Ordering::Greater }
+            })
+            .unwrap_or_else(|idx| idx);
+        let right = self.genes.get(first_after);
+
+        let nearest = match (left, right) {
+            (Some(lgx), Some(rgx)) => {
+                if pos + 1 - lgx.end() <= rgx.start() - pos { lgx } else { rgx }
+            },
+            (Some(lgx), None) => lgx,
+            (None, Some(rgx)) => rgx,
+            (None, None) => return None,
+        };
+        Some((nearest, signed_distance(nearest, pos)))
+    }
+
+    /// Returns the transcript whose transcription start site is nearest to the given
+    /// coordinate, along with its signed distance.
+    fn nearest_tss(&self, pos: u64) -> Option<(&Transcript, i64)> {
+        let upper = self.tss
+            .binary_search_by(|&(coord, _, _)| {
+                if coord <= pos { Ordering::Less } else { Ordering::Greater }
+            })
+            .unwrap_or_else(|idx| idx);
+
+        let left = if upper > 0 { self.tss.get(upper - 1) } else { None };
+        let right = self.tss.get(upper);
+        let &(coord, gidx, tidx) = match (left, right) {
+            (Some(l), Some(r)) => if pos - l.0 <= r.0 - pos { l } else { r },
+            (Some(l), None) => l,
+            (None, Some(r)) => r,
+            (None, None) => return None,
+        };
+
+        self.genes[gidx].transcripts().values().nth(tidx)
+            .map(|trx| (trx, oriented_distance(trx.strand(), coord, pos)))
+    }
+
+    /// Returns all genes overlapping the given region, sorted by their start coordinates.
+    fn overlapping(&self, start: u64, end: u64) -> Vec<&Gene> {
+        self.overlapping_indices(start, end).into_iter()
+            .map(|gidx| &self.genes[gidx])
+            .collect()
+    }
+}
+
+/// Container of genes from an entire genome, indexed per sequence for overlap queries.
+///
+/// All query regions are zero-based, half-open ranges, consistent with the coordinates of the
+/// gene, transcript, and exon models. A feature overlaps a region if they share at least one
+/// base.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde_impl", derive(Serialize, Deserialize))]
+pub struct GenomeAnnotation {
+    seqs: HashMap<String, SeqIndex>,
+    num_genes: usize,
+    // sequence names and indices of the genes of each gene name
+    names: HashMap<String, Vec<(String, usize)>>,
+    // sequence name and index of the first gene of each gene identifier
+    ids: HashMap<String, (String, usize)>,
+}
+
+impl GenomeAnnotation {
+
+    /// Creates an annotation from the given genes.
+    pub fn from_genes<I>(genes: I) -> Self
+        where I: IntoIterator<Item=Gene>
+    {
+        let mut seq_genes: HashMap<String, Vec<Gene>> = HashMap::new();
+        let mut num_genes = 0;
+        for gx in genes {
+            num_genes += 1;
+            seq_genes.entry(gx.seq_name().to_owned())
+                .or_insert_with(Vec::new)
+                .push(gx);
+        }
+        let seqs = seq_genes.into_iter()
+            .map(|(seq_name, genes)| (seq_name, SeqIndex::new(genes)))
+            .collect::<HashMap<String, SeqIndex>>();
+        let mut names: HashMap<String, Vec<(String, usize)>> = HashMap::new();
+        let mut ids: HashMap<String, (String, usize)> = HashMap::new();
+        for (seq_name, index) in seqs.iter() {
+            for (gidx, gx) in index.genes.iter().enumerate() {
+                if let Some(id) = gx.id() {
+                    let loc = ids.entry(id.to_owned()).or_insert_with(|| (seq_name.clone(), gidx));
+                    if (seq_name, gidx) < (&loc.0, loc.1) {
+                        *loc = (seq_name.clone(), gidx);
+                    }
+                }
+                if let Some(name) = gx.name().or_else(|| gx.id()) {
+                    names.entry(name.to_owned())
+                        .or_insert_with(Vec::new)
+                        .push((seq_name.clone(), gidx));
+                }
+            }
+        }
+        for (_, locs) in names.iter_mut() {
+            locs.sort();
+        }
+        GenomeAnnotation {
+            seqs: seqs,
+            num_genes: num_genes,
+            names: names,
+            ids: ids,
+        }
+    }
+
+    /// Creates an annotation from the given stream of gene results, such as the genes streams
+    /// of the refFlat or genePred readers.
+    ///
+    /// The first error encountered in the stream is returned.
+    pub fn from_genes_stream<I>(genes: I) -> ::Result<Self>
+        where I: IntoIterator<Item=::Result<Gene>>
+    {
+        let mut collected = Vec::new();
+        for result in genes {
+            collected.push(result?);
+        }
+        Ok(Self::from_genes(collected))
+    }
+
+    /// Returns the number of genes in the annotation.
+    pub fn len(&self) -> usize {
+        self.num_genes
+    }
+
+    /// Returns whether the annotation contains no genes.
+    pub fn is_empty(&self) -> bool {
+        self.num_genes == 0
+    }
+
+    /// Returns the names of all sequences with at least one gene, sorted lexicographically.
+    pub fn seq_names(&self) -> Vec<&str> {
+        let mut names = self.seqs.keys().map(|k| k.as_str()).collect::<Vec<&str>>();
+        names.sort();
+        names
+    }
+
+    /// Returns all genes of the given sequence, sorted by their start coordinates.
+    pub fn genes(&self, seq_name: &str) -> &[Gene] {
+        self.seqs.get(seq_name)
+            .map(|index| index.genes.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// Returns the gene with the given identifier.
+    ///
+    /// Of genes sharing the same identifier, the first by sequence name and start coordinate
+    /// is returned.
+    pub fn gene(&self, id: &str) -> Option<&Gene> {
+        self.ids.get(id).map(|&(ref seq_name, gidx)| &self.seqs[seq_name].genes[gidx])
+    }
+
+    /// Returns all genes with the given name, such as `TP53`, sorted by their sequence names
+    /// and start coordinates.
+    ///
+    /// Genes without names, such as those read from refFlat files, are looked up by their
+    /// identifiers instead.
+    pub fn genes_by_name(&self, name: &str) -> Vec<&Gene> {
+        self.names.get(name)
+            .map(|locs| {
+                locs.iter()
+                    .map(|&(ref seq_name, gidx)| &self.seqs[seq_name].genes[gidx])
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Returns all genes overlapping the given region, sorted by their start coordinates.
+    pub fn genes_at(&self, seq_name: &str, region: Range<u64>) -> Vec<&Gene> {
+        match self.seqs.get(seq_name) {
+            Some(index) if region.start < region.end =>
+                index.overlapping(region.start, region.end),
+            _ => vec![],
+        }
+    }
+
+    /// Returns all transcripts overlapping the given region.
+    ///
+    /// Transcripts are ordered by the start coordinates of their genes, and then by their
+    /// order within each gene.
+    pub fn transcripts_at(&self, seq_name: &str, region: Range<u64>) -> Vec<&Transcript> {
+        let (start, end) = (region.start, region.end);
+        self.genes_at(seq_name, region).into_iter()
+            .flat_map(|gx| gx.transcripts().values())
+            .filter(|trx| trx.start() < end && trx.end() > start)
+            .collect()
+    }
+
+    /// Returns all exons overlapping the given region.
+    ///
+    /// Exons are ordered by their transcripts, following the order of `transcripts_at`. Exons
+    /// shared by multiple transcripts are returned once for each transcript.
+    pub fn exons_at(&self, seq_name: &str, region: Range<u64>) -> Vec<&Exon> {
+        let (start, end) = (region.start, region.end);
+        self.transcripts_at(seq_name, region).into_iter()
+            .flat_map(|trx| trx.exons().iter())
+            .filter(|exn| exn.start() < end && exn.end() > start)
+            .collect()
+    }
+
+    /// Returns the gene nearest to the given zero-based coordinate, along with the signed
+    /// distance from the gene to the coordinate.
+    ///
+    /// The distance is zero if the gene overlaps the coordinate. Otherwise, it is negative if
+    /// the coordinate lies upstream of the gene and positive if it lies downstream, relative to
+    /// the strand of the gene. Genes with unknown strands are treated as forward-stranded. Ties
+    /// are resolved in favor of the gene with the smaller coordinates.
+    pub fn nearest_gene(&self, seq_name: &str, pos: u64) -> Option<(&Gene, i64)> {
+        self.seqs.get(seq_name).and_then(|index| index.nearest_gene(pos))
+    }
+
+    /// Returns the transcript whose transcription start site is nearest to the given
+    /// zero-based coordinate, along with the signed distance from the site to the coordinate.
+    ///
+    /// The transcription start site is the 5'-most base of the transcript. The distance is
+    /// negative if the coordinate lies upstream of the site and positive if it lies downstream,
+    /// relative to the strand of the transcript. Transcripts with unknown strands are treated
+    /// as forward-stranded. Ties are resolved in favor of the site with the smaller coordinate.
+    pub fn nearest_tss(&self, seq_name: &str, pos: u64) -> Option<(&Transcript, i64)> {
+        self.seqs.get(seq_name).and_then(|index| index.nearest_tss(pos))
+    }
+}
+
+/// Iterator over the promoters of all transcripts in a stream of genes.
+///
+/// Each item contains a transcript along with its promoter, as created by the `promoter` method
+/// of `Transcript`. Errors in the underlying gene stream are passed through as-is.
+pub struct Promoters<I> {
+    genes: I,
+    upstream: u64,
+    downstream: u64,
+    pending: vec::IntoIter<Transcript>,
+}
+
+impl<I> Promoters<I> where I: Iterator<Item=::Result<Gene>> {
+
+    /// Creates an iterator over the promoters of the transcripts in the given genes.
+    pub fn new<T>(genes: T, upstream: u64, downstream: u64) -> Self
+        where T: IntoIterator<Item=::Result<Gene>, IntoIter=I>
+    {
+        Promoters {
+            genes: genes.into_iter(),
+            upstream: upstream,
+            downstream: downstream,
+            pending: Vec::new().into_iter(),
+        }
+    }
+}
+
+impl<I> Iterator for Promoters<I> where I: Iterator<Item=::Result<Gene>> {
+
+    type Item = ::Result<(Transcript, TranscriptFeature)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(trx) = self.pending.next() {
+                let promoter = trx.promoter(self.upstream, self.downstream);
+                return Some(Ok((trx, promoter)));
+            }
+            match self.genes.next() {
+                Some(Ok(gx)) => {
+                    self.pending = gx.take_transcripts().into_iter()
+                        .map(|(_, trx)| trx)
+                        .collect::<Vec<Transcript>>()
+                        .into_iter();
+                },
+                Some(Err(err)) => return Some(Err(err)),
+                None => return None,
+            }
+        }
+    }
+}
+
+/// Iterator over the flanking regions of all genes in a stream of genes.
+///
+/// Each item contains a gene along with its flanking region, as created by the `flank` method
+/// of `Gene`. Errors in the underlying gene stream are passed through as-is.
+pub struct Flanks<I> {
+    genes: I,
+    size: u64,
+    side: FlankSide,
+}
+
+impl<I> Flanks<I> where I: Iterator<Item=::Result<Gene>> {
+
+    /// Creates an iterator over the flanking regions of the given genes.
+    pub fn new<T>(genes: T, size: u64, side: FlankSide) -> Self
+        where T: IntoIterator<Item=::Result<Gene>, IntoIter=I>
+    {
+        Flanks {
+            genes: genes.into_iter(),
+            size: size,
+            side: side,
+        }
+    }
+}
+
+impl<I> Iterator for Flanks<I> where I: Iterator<Item=::Result<Gene>> {
+
+    type Item = ::Result<(Gene, GeneFeature)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (size, side) = (self.size, self.side);
+        self.genes.next()
+            .map(|result| result.map(|gx| {
+                let flank = gx.flank(size, side);
+                (gx, flank)
+            }))
+    }
+}
+
+/// Iterator over the genes of a stream whose biotypes are among the given ones.
+///
+/// Biotypes are determined by the `biotype` method of `Gene`, so genes without any biotype
+/// attribute are always skipped. Errors in the underlying gene stream are passed through as-is.
+pub struct BiotypeFilter<I> {
+    genes: I,
+    biotypes: Vec<String>,
+}
+
+impl<I> BiotypeFilter<I> where I: Iterator<Item=::Result<Gene>> {
+
+    /// Creates an iterator over the genes with any of the given biotypes.
+    pub fn new<T, B>(genes: T, biotypes: &[B]) -> Self
+        where T: IntoIterator<Item=::Result<Gene>, IntoIter=I>, B: AsRef<str>
+    {
+        BiotypeFilter {
+            genes: genes.into_iter(),
+            biotypes: biotypes.iter().map(|bt| bt.as_ref().to_owned()).collect(),
+        }
+    }
+}
+
+impl<I> Iterator for BiotypeFilter<I> where I: Iterator<Item=::Result<Gene>> {
+
+    type Item = ::Result<Gene>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let biotypes = &self.biotypes;
+        self.genes.by_ref()
+            .find(|result| match *result {
+                Ok(ref gx) => gx.biotype()
+                    .map_or(false, |bt| biotypes.iter().any(|b| b == bt.as_str())),
+                Err(_) => true,
+            })
+    }
+}
+
+/// Iterator over the genes of a stream that lie on any of the given sequences.
+///
+/// Errors in the underlying gene stream are passed through as-is.
+pub struct SeqNameFilter<I> {
+    genes: I,
+    seq_names: Vec<String>,
+}
+
+impl<I> SeqNameFilter<I> where I: Iterator<Item=::Result<Gene>> {
+
+    /// Creates an iterator over the genes on any of the given sequences.
+    pub fn new<T, S>(genes: T, seq_names: &[S]) -> Self
+        where T: IntoIterator<Item=::Result<Gene>, IntoIter=I>, S: AsRef<str>
+    {
+        SeqNameFilter {
+            genes: genes.into_iter(),
+            seq_names: seq_names.iter().map(|sn| sn.as_ref().to_owned()).collect(),
+        }
+    }
+}
+
+impl<I> Iterator for SeqNameFilter<I> where I: Iterator<Item=::Result<Gene>> {
+
+    type Item = ::Result<Gene>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let seq_names = &self.seq_names;
+        self.genes.by_ref()
+            .find(|result| match *result {
+                Ok(ref gx) => seq_names.iter().any(|sn| sn == gx.seq_name()),
+                Err(_) => true,
+            })
+    }
+}
+
+/// Iterator over genes with unique identifiers.
+///
+/// Genes without identifiers are yielded as-is. With the `KeepLast` policy, all genes are read
+/// before the first one is yielded.
+pub struct DedupGeneIds<I> {
+    genes: I,
+    policy: DuplicatePolicy,
+    seen: HashSet<String>,
+    buffered: Option<vec::IntoIter<::Result<Gene>>>,
+}
+
+impl<I> DedupGeneIds<I> where I: Iterator<Item=::Result<Gene>> {
+
+    /// Creates an iterator over the given genes, handling genes with identifiers that were
+    /// already seen using the given policy.
+    pub fn new<T>(genes: T, policy: DuplicatePolicy) -> Self
+        where T: IntoIterator<Item=::Result<Gene>, IntoIter=I>
+    {
+        DedupGeneIds {
+            genes: genes.into_iter(),
+            policy: policy,
+            seen: HashSet::new(),
+            buffered: None,
+        }
+    }
+
+    /// Reads all genes, keeping only the last gene of each identifier at the position of its
+    /// first occurrence.
+    fn buffer_last(&mut self) -> vec::IntoIter<::Result<Gene>> {
+        let mut slots: Vec<::Result<Gene>> = Vec::new();
+        let mut positions: HashMap<String, usize> = HashMap::new();
+        for result in self.genes.by_ref() {
+            let pos = match result {
+                Ok(ref gx) => gx.id().and_then(|id| positions.get(id).cloned()),
+                Err(_) => None,
+            };
+            match pos {
+                Some(pos) => slots[pos] = result,
+                None => {
+                    if let Ok(ref gx) = result {
+                        if let Some(id) = gx.id() {
+                            let _ = positions.insert(id.to_owned(), slots.len());
+                        }
+                    }
+                    slots.push(result);
+                },
+            }
+        }
+        slots.into_iter()
+    }
+}
+
+impl<I> Iterator for DedupGeneIds<I> where I: Iterator<Item=::Result<Gene>> {
+
+    type Item = ::Result<Gene>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.policy == DuplicatePolicy::KeepLast {
+            if self.buffered.is_none() {
+                self.buffered = Some(self.buffer_last());
+            }
+            return self.buffered.as_mut().and_then(|genes| genes.next());
+        }
+        loop {
+            let mut gx = match self.genes.next() {
+                Some(Ok(gx)) => gx,
+                otherwise => return otherwise,
+            };
+            let gid = match gx.id() {
+                Some(id) => id.to_owned(),
+                None => return Some(Ok(gx)),
+            };
+            if self.seen.insert(gid.clone()) {
+                return Some(Ok(gx));
+            }
+            match self.policy {
+                DuplicatePolicy::KeepFirst => continue,
+                DuplicatePolicy::Suffix => {
+                    let new_gid = {
+                        let seen = &self.seen;
+                        suffixed_id(&gid, |id: &str| seen.contains(id))
+                    };
+                    let _ = self.seen.insert(new_gid.clone());
+                    gx.set_id(Some(new_gid));
+                    return Some(Ok(gx));
+                },
+                _ => return Some(Err(::Error::from(ModelError::DuplicateGeneId(gid)))),
+            }
+        }
+    }
+}
+
+/// Extension methods for filtering and rewriting streams of genes.
+///
+/// This is implemented for all iterators over gene results, such as the gene streams of the
+/// refFlat and genePred readers.
+pub trait GeneStreamExt: Iterator<Item=::Result<Gene>> + Sized {
+
+    /// Keeps only the genes with any of the given biotypes.
+    fn filter_biotype<B: AsRef<str>>(self, biotypes: &[B]) -> BiotypeFilter<Self> {
+        BiotypeFilter::new(self, biotypes)
+    }
+
+    /// Keeps only the genes on any of the given sequences.
+    fn filter_seqname<S: AsRef<str>>(self, seq_names: &[S]) -> SeqNameFilter<Self> {
+        SeqNameFilter::new(self, seq_names)
+    }
+
+    /// Handles genes sharing the same identifier, such as genes with the same identifier on
+    /// different sequences, using the given policy.
+    fn dedup_gene_ids(self, policy: DuplicatePolicy) -> DedupGeneIds<Self> {
+        DedupGeneIds::new(self, policy)
+    }
+
+    /// Renames the sequences and shifts the coordinates of the genes using the given rewrite.
+    fn rewrite_seqs(self, rewrite: SeqRewrite) -> RewrittenGenes<Self> {
+        RewrittenGenes::new(self, rewrite)
+    }
+
+    /// Handles the chromosome Y copies of pseudoautosomal genes using the given policy.
+    fn par_y(self, policy: ParYPolicy) -> ParYGenes<Self> {
+        ParYGenes::new(self, policy)
+    }
+
+    /// Flags, clips, or drops the genes that do not fit their sequences, as set in the given
+    /// checker.
+    fn check_bounds(self, checker: BoundsChecker) -> BoundedGenes<Self> {
+        BoundedGenes::new(self, checker)
+    }
+}
+
+impl<I> GeneStreamExt for I where I: Iterator<Item=::Result<Gene>> {}
+
+/// Returns the regions not covered by any of the given genes.
+///
+/// Regions are computed for every sequence of the given dictionary and are returned along with
+/// their sequence names, in the order of the dictionary and then sorted by their coordinates.
+/// Sequences without any genes are returned whole. The given number of bases flanking each
+/// gene on both sides are excluded from the regions as well. Genes on sequences absent from the
+/// dictionary are ignored, since their regions can not be bounded. Errors in the underlying
+/// gene stream are returned as-is.
+pub fn intergenic_regions<T>(genes: T, dict: &SeqDict, flank: u64)
+    -> ::Result<Vec<(String, GeneFeature)>>
+    where T: IntoIterator<Item=::Result<Gene>>
+{
+    let mut gene_coords: HashMap<String, Vec<(u64, u64)>> = HashMap::new();
+    for gx in genes {
+        let gx = gx?;
+        if dict.seq_len(gx.seq_name()).is_none() {
+            continue;
+        }
+        gene_coords.entry(gx.seq_name().to_owned())
+            .or_insert_with(Vec::new)
+            .push((gx.start().saturating_sub(flank), gx.end().saturating_add(flank)));
+    }
+
+    let mut regions = Vec::new();
+    for &(ref seq_name, seq_len) in dict.seqs() {
+        let mut coords = gene_coords.remove(seq_name).unwrap_or_default();
+        coords.sort();
+        let mut cursor = 0;
+        for (start, end) in coords {
+            if start > cursor && cursor < seq_len {
+                let feature = GeneFeature::new(Interval::new(cursor..min(start, seq_len)).unwrap(),
+                                               GeneFeatureKind::Intergenic);
+                regions.push((seq_name.clone(), feature));
+            }
+            cursor = max(cursor, end);
+        }
+        if cursor < seq_len {
+            let feature = GeneFeature::new(Interval::new(cursor..seq_len).unwrap(),
+                                           GeneFeatureKind::Intergenic);
+            regions.push((seq_name.clone(), feature));
+        }
+    }
+    Ok(regions)
+}
+
+/// Returns the coordinate of the transcription start site of the given transcript.
+fn tss_coord(trx: &Transcript) -> u64 {
+    match *trx.strand() {
+        Strand::Reverse => trx.end() - 1,
+        _ => trx.start(),
+    }
+}
+
+/// Returns the strand-aware distance from the given reference coordinate to a coordinate.
+fn oriented_distance(strand: &Strand, reference: u64, pos: u64) -> i64 {
+    let dist = pos as i64 - reference as i64;
+    match *strand {
+        Strand::Reverse => -dist,
+        _ => dist,
+    }
+}
+
+/// Returns the strand-aware distance from the given gene to a coordinate.
+fn signed_distance(gx: &Gene, pos: u64) -> i64 {
+    if pos < gx.start() {
+        oriented_distance(gx.strand(), gx.start(), pos)
+    } else if pos >= gx.end() {
+        oriented_distance(gx.strand(), gx.end() - 1, pos)
+    } else {
+        0
+    }
+}
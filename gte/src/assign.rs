@@ -0,0 +1,268 @@
+/*! Assignment of aligned reads to genes and transcripts.
+
+Reads are assigned following the `union` mode of
+[htseq-count](https://htseq.readthedocs.io/en/latest/count.html): a read hits a gene if any of
+its aligned blocks overlaps any exon of the gene, on a strand allowed by the library
+strandedness. Reads hitting exactly one gene are assigned to it, while reads hitting more than
+one gene are ambiguous. Aligned blocks are split at the skipped regions (`N` operations) of the
+read CIGAR, so spliced reads do not hit genes lying entirely within their introns.
+
+Alignments can be created from CIGAR strings, or directly from BAM/CRAM records of
+[rust-htslib](https://github.com/rust-bio/rust-htslib) when the `htslib` feature is enabled.
+
+*/
+use std::ptr;
+
+#[cfg(feature = "htslib")]
+use rust_htslib::bam;
+
+use {Gene, GenomeAnnotation, Strand, Transcript};
+
+
+quick_error! {
+    /// Errors that occur when creating alignments.
+    #[derive(Debug)]
+    pub enum AssignError {
+        /// Occurs when a CIGAR string can not be parsed.
+        InvalidCigar(cigar: String) {
+            description("invalid CIGAR string")
+            display("invalid CIGAR string: '{}'", cigar)
+        }
+    }
+}
+
+/// Strandedness protocols of sequencing libraries.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Strandedness {
+    /// Reads may come from either strand of their features.
+    Unstranded,
+    /// Single-end reads and first mates come from the same strand as their features (e.g.
+    /// ligation-based protocols).
+    Forward,
+    /// Single-end reads and first mates come from the opposite strand of their features (e.g.
+    /// dUTP-based protocols).
+    Reverse,
+}
+
+impl Default for Strandedness {
+    fn default() -> Self {
+        Strandedness::Unstranded
+    }
+}
+
+/// Aligned read, reduced to the parts used for assigning it to features.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Alignment {
+    seq_name: String,
+    blocks: Vec<(u64, u64)>,
+    reverse: bool,
+    second_mate: bool,
+}
+
+impl Alignment {
+
+    /// Creates a forward-stranded alignment from its sequence name, zero-based leftmost
+    /// coordinate, and CIGAR string.
+    pub fn from_cigar<T>(seq_name: T, pos: u64, cigar: &str) -> ::Result<Alignment>
+        where T: Into<String>
+    {
+        let ops = parse_cigar(cigar)
+            .ok_or_else(|| AssignError::InvalidCigar(cigar.to_owned()))?;
+        Ok(Alignment {
+            seq_name: seq_name.into(),
+            blocks: aligned_blocks(pos, ops),
+            reverse: false,
+            second_mate: false,
+        })
+    }
+
+    /// Sets whether the read is aligned to the reverse strand.
+    pub fn reverse(mut self, reverse: bool) -> Self {
+        self.reverse = reverse;
+        self
+    }
+
+    /// Sets whether the read is the second mate of a pair.
+    pub fn second_mate(mut self, second_mate: bool) -> Self {
+        self.second_mate = second_mate;
+        self
+    }
+
+    /// Returns the sequence name.
+    pub fn seq_name(&self) -> &str {
+        self.seq_name.as_str()
+    }
+
+    /// Returns the zero-based, half-open reference regions covered by the read.
+    ///
+    /// Matches and deletions are covered, while skipped regions separate the blocks.
+    pub fn blocks(&self) -> &[(u64, u64)] {
+        self.blocks.as_slice()
+    }
+
+    /// Returns the strand of the read, after flipping second mates to the strand of their
+    /// fragments.
+    fn fragment_strand(&self) -> Strand {
+        if self.reverse != self.second_mate { Strand::Reverse } else { Strand::Forward }
+    }
+}
+
+#[cfg(feature = "htslib")]
+impl Alignment {
+
+    /// Creates an alignment from a BAM/CRAM record and the header of its file.
+    ///
+    /// `None` is returned for unmapped reads.
+    pub fn from_record(record: &bam::Record, header: &bam::HeaderView) -> Option<Alignment> {
+        if record.is_unmapped() || record.tid() < 0 {
+            return None;
+        }
+        let seq_name = String::from_utf8_lossy(header.tid2name(record.tid() as u32))
+            .into_owned();
+        let ops = record.cigar().iter()
+            .map(|op| (op.char(), op.len() as u64))
+            .collect();
+        Some(Alignment {
+            seq_name: seq_name,
+            blocks: aligned_blocks(record.pos() as u64, ops),
+            reverse: record.is_reverse(),
+            second_mate: record.is_paired() && record.is_last_in_template(),
+        })
+    }
+}
+
+/// Result of assigning a read.
+#[derive(Debug, Clone)]
+pub enum Assignment<'a> {
+    /// The read hits a single gene, along with the transcripts of the gene that it hits.
+    Gene(&'a Gene, Vec<&'a Transcript>),
+    /// The read hits more than one gene, sorted by their start coordinates.
+    Ambiguous(Vec<&'a Gene>),
+    /// The read does not hit any gene.
+    NoFeature,
+}
+
+/// Assigner of reads to the genes of an annotation.
+#[derive(Debug, Clone)]
+pub struct ReadAssigner<'a> {
+    annotation: &'a GenomeAnnotation,
+    strandedness: Strandedness,
+}
+
+impl<'a> ReadAssigner<'a> {
+
+    /// Creates an assigner of unstranded reads to the genes of the given annotation.
+    pub fn new(annotation: &'a GenomeAnnotation) -> Self {
+        ReadAssigner {
+            annotation: annotation,
+            strandedness: Strandedness::default(),
+        }
+    }
+
+    /// Sets the strandedness protocol of the reads.
+    pub fn strandedness(&mut self, strandedness: Strandedness) -> &mut Self {
+        self.strandedness = strandedness;
+        self
+    }
+
+    /// Assigns the given read.
+    pub fn assign(&self, aln: &Alignment) -> Assignment<'a> {
+        let strand = self.feature_strand(aln);
+        let mut genes: Vec<&'a Gene> = Vec::new();
+        let mut transcripts: Vec<&'a Transcript> = Vec::new();
+        for &(start, end) in aln.blocks.iter() {
+            for gx in self.annotation.genes_at(&aln.seq_name, start..end) {
+                if !is_compatible(gx.strand(), strand) {
+                    continue;
+                }
+                for trx in gx.transcripts().values() {
+                    let hit = is_compatible(trx.strand(), strand) &&
+                        trx.exons().iter().any(|exn| exn.start() < end && exn.end() > start);
+                    if !hit || transcripts.iter().any(|&t| ptr::eq(t, trx)) {
+                        continue;
+                    }
+                    transcripts.push(trx);
+                    if !genes.iter().any(|&g| ptr::eq(g, gx)) {
+                        genes.push(gx);
+                    }
+                }
+            }
+        }
+        match genes.len() {
+            0 => Assignment::NoFeature,
+            1 => Assignment::Gene(genes[0], transcripts),
+            _ => {
+                genes.sort_by_key(|gx| (gx.start(), gx.end()));
+                Assignment::Ambiguous(genes)
+            },
+        }
+    }
+
+    /// Returns the strand that features must have to be hit by the given read, or `None` if
+    /// features on both strands can be hit.
+    fn feature_strand(&self, aln: &Alignment) -> Option<Strand> {
+        match self.strandedness {
+            Strandedness::Unstranded => None,
+            Strandedness::Forward => Some(aln.fragment_strand()),
+            Strandedness::Reverse => match aln.fragment_strand() {
+                Strand::Forward => Some(Strand::Reverse),
+                _ => Some(Strand::Forward),
+            },
+        }
+    }
+}
+
+/// Helper function to check whether a feature strand is compatible with the expected strand.
+///
+/// Features with unknown strands are compatible with any read.
+fn is_compatible(feature_strand: &Strand, expected: Option<Strand>) -> bool {
+    match (*feature_strand, expected) {
+        (_, None) | (Strand::Unknown, _) => true,
+        (fs, Some(es)) => fs == es,
+    }
+}
+
+/// Helper function to parse a CIGAR string into its operations and lengths.
+fn parse_cigar(cigar: &str) -> Option<Vec<(char, u64)>> {
+    let mut ops = Vec::new();
+    let mut len_start = 0;
+    for (idx, chr) in cigar.char_indices() {
+        if chr.is_digit(10) {
+            continue;
+        }
+        let len = match cigar[len_start..idx].parse::<u64>() {
+            Ok(len) if "MIDNSHP=X".contains(chr) => len,
+            _ => return None,
+        };
+        ops.push((chr, len));
+        len_start = idx + chr.len_utf8();
+    }
+    if len_start != cigar.len() || ops.is_empty() {
+        return None;
+    }
+    Some(ops)
+}
+
+/// Helper function to compute the reference regions covered by the given CIGAR operations.
+fn aligned_blocks(pos: u64, ops: Vec<(char, u64)>) -> Vec<(u64, u64)> {
+    let mut blocks: Vec<(u64, u64)> = Vec::new();
+    let mut ref_pos = pos;
+    for (op, len) in ops {
+        match op {
+            'M' | 'D' | '=' | 'X' => {
+                let extend = blocks.last().map(|&(_, end)| end == ref_pos).unwrap_or(false);
+                if extend {
+                    let last = blocks.len() - 1;
+                    blocks[last].1 += len;
+                } else {
+                    blocks.push((ref_pos, ref_pos + len));
+                }
+                ref_pos += len;
+            },
+            'N' => ref_pos += len,
+            _ => {},
+        }
+    }
+    blocks.retain(|&(start, end)| start < end);
+    blocks
+}
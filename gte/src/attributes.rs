@@ -0,0 +1,299 @@
+/*! Insertion-ordered storage of feature attributes.
+
+*/
+use std::borrow::Cow;
+use std::iter::FromIterator;
+use std::slice;
+use std::sync::Arc;
+
+use bio::io::gff::GffType;
+#[cfg(feature = "serde_impl")]
+use linked_hash_map::LinkedHashMap;
+use multimap::MultiMap;
+
+use io_gff::parse_raw_attributes;
+
+
+/// Attributes of genes, transcripts, and exons.
+///
+/// Each key may have multiple values. Keys are kept in the order in which they were first
+/// inserted, and values are kept in the order in which they were inserted, so that attributes
+/// read from an input can be written back in their original order.
+///
+/// Features usually have only a handful of attributes, so they are stored in a vector of keys
+/// and values, which is looked up linearly. This avoids the per-feature hash table and linked
+/// list allocations of a map, which dominate the memory usage of large annotations.
+///
+/// Attributes read by GFF readers in lazy mode are kept as their unparsed column until they are
+/// parsed, either explicitly with `parse` or by any of the methods that modify the attributes.
+/// Until then, the methods that read the attributes behave as if there are none. Equality and
+/// serialization always use the parsed attributes.
+#[derive(Debug, Clone, Default)]
+pub struct Attributes {
+    inner: Vec<(String, Vec<String>)>,
+    raw: Option<Box<RawAttributes>>,
+}
+
+/// Helper struct for an unparsed attributes column.
+#[derive(Debug, Clone)]
+struct RawAttributes {
+    column: String,
+    gff3: bool,
+    excluded_keys: Arc<Vec<String>>,
+}
+
+impl RawAttributes {
+
+    /// Parses the column into keys and values, in order of appearance.
+    fn parse(&self) -> Vec<(String, String)> {
+        let gff_type = if self.gff3 { GffType::GFF3 } else { GffType::GTF2 };
+        parse_raw_attributes(&self.column, gff_type, &self.excluded_keys)
+    }
+}
+
+impl Attributes {
+
+    /// Creates an empty attribute store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an attribute store from the given unparsed GFF attributes column.
+    ///
+    /// The given keys, such as those of gene and transcript identifiers, are left out when the
+    /// column is parsed.
+    pub(crate) fn from_raw(column: String, gff_type: GffType, excluded_keys: Arc<Vec<String>>)
+        -> Self
+    {
+        let gff3 = match gff_type {
+            GffType::GFF3 => true,
+            _ => false,
+        };
+        Attributes {
+            inner: Vec::new(),
+            raw: Some(Box::new(RawAttributes {
+                column: column,
+                gff3: gff3,
+                excluded_keys: excluded_keys,
+            })),
+        }
+    }
+
+    /// Returns whether the attributes have been parsed.
+    pub fn is_parsed(&self) -> bool {
+        self.raw.is_none()
+    }
+
+    /// Returns the unparsed attributes column, if the attributes have not been parsed.
+    pub fn raw(&self) -> Option<&str> {
+        self.raw.as_ref().map(|raw| raw.column.as_str())
+    }
+
+    /// Parses the unparsed attributes column, if any.
+    ///
+    /// Parsed values are added after any existing values.
+    pub fn parse(&mut self) {
+        if let Some(raw) = self.raw.take() {
+            for (key, value) in raw.parse() {
+                self.insert(key, value);
+            }
+        }
+    }
+
+    /// Returns the number of distinct keys.
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Returns whether there are no attributes.
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// Returns whether the given key is present.
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.position(key).is_some()
+    }
+
+    /// Returns the first value of the given key.
+    pub fn get(&self, key: &str) -> Option<&String> {
+        self.get_vec(key).and_then(|values| values.first())
+    }
+
+    /// Returns all values of the given key.
+    pub fn get_vec(&self, key: &str) -> Option<&Vec<String>> {
+        self.position(key).map(|idx| &self.inner[idx].1)
+    }
+
+    /// Adds a value to the given key.
+    ///
+    /// New keys are placed after all existing keys, while values of existing keys are appended
+    /// to their previous values.
+    pub fn insert(&mut self, key: String, value: String) {
+        self.parse();
+        match self.position(&key) {
+            Some(idx) => self.inner[idx].1.push(value),
+            None => self.inner.push((key, vec![value])),
+        }
+    }
+
+    /// Removes the given key and returns its values.
+    pub fn remove(&mut self, key: &str) -> Option<Vec<String>> {
+        self.parse();
+        self.position(key).map(|idx| self.inner.remove(idx).1)
+    }
+
+    /// Returns an iterator over the keys and their values, in insertion order.
+    pub fn iter(&self) -> AttributesIter {
+        AttributesIter { inner: self.inner.iter() }
+    }
+
+    /// Returns an iterator over the keys, in insertion order.
+    pub fn keys(&self) -> AttributeKeys {
+        AttributeKeys { inner: self.inner.iter() }
+    }
+
+    /// Shrinks the capacity of the store and of its values as much as possible.
+    pub fn shrink_to_fit(&mut self) {
+        self.parse();
+        self.inner.shrink_to_fit();
+        for &mut (_, ref mut values) in self.inner.iter_mut() {
+            values.shrink_to_fit();
+        }
+    }
+
+    /// Helper method for getting the parsed keys and values, including those of the unparsed
+    /// column.
+    fn entries(&self) -> Cow<[(String, Vec<String>)]> {
+        match self.raw {
+            None => Cow::Borrowed(self.inner.as_slice()),
+            Some(ref raw) => {
+                let mut parsed = Attributes { inner: self.inner.clone(), raw: None };
+                for (key, value) in raw.parse() {
+                    parsed.insert(key, value);
+                }
+                Cow::Owned(parsed.inner)
+            },
+        }
+    }
+
+    /// Helper method for finding the index of the given key.
+    fn position(&self, key: &str) -> Option<usize> {
+        self.inner.iter().position(|&(ref k, _)| k == key)
+    }
+}
+
+/// Iterator over the keys and values of attributes, in insertion order.
+///
+/// This struct is created by the `iter` method of `Attributes`.
+pub struct AttributesIter<'a> {
+    inner: slice::Iter<'a, (String, Vec<String>)>,
+}
+
+impl<'a> Iterator for AttributesIter<'a> {
+
+    type Item = (&'a String, &'a Vec<String>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|&(ref key, ref values)| (key, values))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a> ExactSizeIterator for AttributesIter<'a> {}
+
+/// Iterator over the keys of attributes, in insertion order.
+///
+/// This struct is created by the `keys` method of `Attributes`.
+pub struct AttributeKeys<'a> {
+    inner: slice::Iter<'a, (String, Vec<String>)>,
+}
+
+impl<'a> Iterator for AttributeKeys<'a> {
+
+    type Item = &'a String;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|&(ref key, _)| key)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a> ExactSizeIterator for AttributeKeys<'a> {}
+
+impl PartialEq for Attributes {
+    fn eq(&self, other: &Attributes) -> bool {
+        self.entries() == other.entries()
+    }
+}
+
+impl Eq for Attributes {}
+
+impl FromIterator<(String, String)> for Attributes {
+    fn from_iter<T: IntoIterator<Item=(String, String)>>(iter: T) -> Self {
+        let mut attributes = Attributes::new();
+        for (key, value) in iter {
+            attributes.insert(key, value);
+        }
+        attributes.shrink_to_fit();
+        attributes
+    }
+}
+
+impl<'a> IntoIterator for &'a Attributes {
+    type Item = (&'a String, &'a Vec<String>);
+    type IntoIter = AttributesIter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Converts unordered attributes, ordering them by their keys so that the result is stable.
+impl From<MultiMap<String, String>> for Attributes {
+    fn from(map: MultiMap<String, String>) -> Self {
+        let mut entries = map.into_iter().collect::<Vec<(String, Vec<String>)>>();
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+        Attributes { inner: entries, raw: None }
+    }
+}
+
+/// Attributes are serialized as a map of keys to their values, in insertion order.
+#[cfg(feature = "serde_impl")]
+impl ::serde::Serialize for Attributes {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where S: ::serde::Serializer
+    {
+        serializer.collect_map(self.entries().iter().map(|&(ref key, ref values)| (key, values)))
+    }
+}
+
+#[cfg(feature = "serde_impl")]
+impl<'de> ::serde::Deserialize<'de> for Attributes {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where D: ::serde::Deserializer<'de>
+    {
+        let map: LinkedHashMap<String, Vec<String>> =
+            ::serde::Deserialize::deserialize(deserializer)?;
+        Ok(Attributes { inner: map.into_iter().collect(), raw: None })
+    }
+}
+
+impl From<Attributes> for MultiMap<String, String> {
+    fn from(mut attributes: Attributes) -> Self {
+        attributes.parse();
+        let mut map = MultiMap::new();
+        for (key, values) in attributes.inner {
+            for value in values {
+                map.insert(key.clone(), value);
+            }
+        }
+        map
+    }
+}
@@ -0,0 +1,235 @@
+/*! Reader for bgzip-compressed files.
+
+Bgzip-compressed files consist of a series of gzip members, or blocks, each holding at most
+64 KiB of uncompressed data. Since the blocks can be decompressed independently of one another,
+several of them can be decompressed at once on a pool of worker threads while the data is still
+returned in order. The format is described in the
+[SAM specification](https://samtools.github.io/hts-specs/SAMv1.pdf).
+*/
+use std::cmp;
+use std::collections::VecDeque;
+use std::io::{self, Read};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+
+use flate2::read::DeflateDecoder;
+
+
+/// Length of the fixed part of a block header.
+const HEADER_LEN: usize = 12;
+
+/// Length of the block footer, which holds the CRC32 and the uncompressed size.
+const FOOTER_LEN: usize = 8;
+
+/// Gzip header flag indicating the presence of extra fields.
+const FEXTRA: u8 = 4;
+
+/// Number of blocks read ahead per worker thread.
+const BLOCKS_PER_THREAD: usize = 2;
+
+/// Compressed block data, its expected uncompressed size, and where to send the result.
+type Job = (Vec<u8>, usize, Sender<io::Result<Vec<u8>>>);
+
+/// Returns whether the given bytes start with a bgzip block header.
+pub(crate) fn is_bgzf(bytes: &[u8]) -> bool {
+    bytes.len() >= 16 && bytes[0] == 0x1f && bytes[1] == 0x8b && bytes[3] & FEXTRA != 0
+        && bytes[12] == b'B' && bytes[13] == b'C'
+}
+
+/// Reader that decompresses bgzip-compressed data, optionally on multiple threads.
+///
+/// By default, blocks are decompressed on the calling thread. When more than one thread is
+/// set, worker threads are started when reading starts and blocks are read ahead of the
+/// consumed data, so that decompression does not hold up parsing.
+pub struct BgzfReader<R: Read> {
+    inner: R,
+    threads: Arc<AtomicUsize>,
+    jobs: Option<Sender<Job>>,
+    pending: VecDeque<Receiver<io::Result<Vec<u8>>>>,
+    buf: Vec<u8>,
+    pos: usize,
+    eof: bool,
+}
+
+impl<R: Read> BgzfReader<R> {
+
+    /// Creates a bgzip reader that decompresses data from another reader.
+    pub fn new(in_reader: R) -> Self {
+        BgzfReader::with_threads(in_reader, Arc::new(AtomicUsize::new(1)))
+    }
+
+    /// Creates a bgzip reader whose thread count is shared with its owner.
+    pub(crate) fn with_threads(in_reader: R, threads: Arc<AtomicUsize>) -> Self {
+        BgzfReader {
+            inner: in_reader,
+            threads: threads,
+            jobs: None,
+            pending: VecDeque::new(),
+            buf: Vec::new(),
+            pos: 0,
+            eof: false,
+        }
+    }
+
+    /// Sets the number of threads used for decompression.
+    ///
+    /// Values of 0 are treated as 1. The number of worker threads is fixed once reading
+    /// starts.
+    pub fn threads(&mut self, threads: usize) -> &mut Self {
+        self.threads.store(cmp::max(threads, 1), Ordering::SeqCst);
+        self
+    }
+
+    /// Reads the next compressed block, returning `None` at the end of the input.
+    fn read_block(&mut self) -> io::Result<Option<(Vec<u8>, usize)>> {
+        let mut header = [0u8; HEADER_LEN];
+        let mut filled = 0;
+        while filled < HEADER_LEN {
+            match self.inner.read(&mut header[filled..]) {
+                Ok(0) if filled == 0 => return Ok(None),
+                Ok(0) => return Err(invalid_block("truncated block header")),
+                Ok(n) => filled += n,
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {},
+                Err(e) => return Err(e),
+            }
+        }
+        if header[0] != 0x1f || header[1] != 0x8b || header[2] != 8 || header[3] & FEXTRA == 0 {
+            return Err(invalid_block("invalid block header"));
+        }
+
+        let xlen = le_u16(&header[10..]);
+        let mut extra = vec![0; xlen];
+        self.inner.read_exact(&mut extra)?;
+        let block_len = block_size(&extra)
+            .ok_or_else(|| invalid_block("missing block size field"))?;
+        if block_len < HEADER_LEN + xlen + FOOTER_LEN {
+            return Err(invalid_block("invalid block size"));
+        }
+
+        let mut rest = vec![0; block_len - HEADER_LEN - xlen];
+        self.inner.read_exact(&mut rest)?;
+        let data_len = rest.len() - FOOTER_LEN;
+        let uncompressed_len = le_u32(&rest[data_len + 4..]);
+        rest.truncate(data_len);
+        Ok(Some((rest, uncompressed_len)))
+    }
+
+    /// Starts the given number of worker threads.
+    fn start_workers(&mut self, threads: usize) {
+        let (jobs_tx, jobs_rx) = mpsc::channel::<Job>();
+        let jobs_rx = Arc::new(Mutex::new(jobs_rx));
+        for _ in 0..threads {
+            let jobs_rx = jobs_rx.clone();
+            let _ = thread::spawn(move || work(jobs_rx));
+        }
+        self.jobs = Some(jobs_tx);
+    }
+
+    /// Returns the next decompressed block, returning `None` at the end of the input.
+    fn next_block(&mut self) -> io::Result<Option<Vec<u8>>> {
+        let threads = self.threads.load(Ordering::SeqCst);
+        if threads <= 1 && self.jobs.is_none() {
+            return match self.read_block()? {
+                Some((data, uncompressed_len)) => inflate(&data, uncompressed_len).map(Some),
+                None => Ok(None),
+            };
+        }
+
+        if self.jobs.is_none() {
+            self.start_workers(threads);
+        }
+        while !self.eof && self.pending.len() < threads * BLOCKS_PER_THREAD {
+            match self.read_block()? {
+                Some((data, uncompressed_len)) => {
+                    let (result_tx, result_rx) = mpsc::channel();
+                    if let Some(ref jobs) = self.jobs {
+                        let _ = jobs.send((data, uncompressed_len, result_tx));
+                    }
+                    self.pending.push_back(result_rx);
+                },
+                None => self.eof = true,
+            }
+        }
+
+        match self.pending.pop_front() {
+            Some(result_rx) => {
+                let result = result_rx.recv()
+                    .map_err(|_| io::Error::new(io::ErrorKind::Other,
+                                                "bgzip worker thread stopped"))?;
+                result.map(Some)
+            },
+            None => Ok(None),
+        }
+    }
+}
+
+impl<R: Read> Read for BgzfReader<R> {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        while self.pos == self.buf.len() {
+            match self.next_block()? {
+                Some(block) => {
+                    self.buf = block;
+                    self.pos = 0;
+                },
+                None => return Ok(0),
+            }
+        }
+        let n = cmp::min(out.len(), self.buf.len() - self.pos);
+        out[..n].copy_from_slice(&self.buf[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+/// Decompresses blocks sent by a reader until the reader is dropped.
+fn work(jobs: Arc<Mutex<Receiver<Job>>>) {
+    loop {
+        let job = match jobs.lock() {
+            Ok(guard) => guard.recv(),
+            Err(_) => break,
+        };
+        match job {
+            Ok((data, uncompressed_len, result_tx)) => {
+                let _ = result_tx.send(inflate(&data, uncompressed_len));
+            },
+            Err(_) => break,
+        }
+    }
+}
+
+/// Decompresses the raw deflate data of a block.
+fn inflate(data: &[u8], uncompressed_len: usize) -> io::Result<Vec<u8>> {
+    let mut out = Vec::with_capacity(uncompressed_len);
+    let _ = DeflateDecoder::new(data).read_to_end(&mut out)?;
+    if out.len() != uncompressed_len {
+        return Err(invalid_block("uncompressed block size mismatch"));
+    }
+    Ok(out)
+}
+
+/// Returns the total block size stored in the extra fields of a block header.
+fn block_size(extra: &[u8]) -> Option<usize> {
+    let mut i = 0;
+    while i + 4 <= extra.len() {
+        let field_len = le_u16(&extra[i + 2..]);
+        if extra[i] == b'B' && extra[i + 1] == b'C' && field_len == 2 && i + 6 <= extra.len() {
+            return Some(le_u16(&extra[i + 4..]) + 1);
+        }
+        i += 4 + field_len;
+    }
+    None
+}
+
+fn le_u16(bytes: &[u8]) -> usize {
+    bytes[0] as usize | (bytes[1] as usize) << 8
+}
+
+fn le_u32(bytes: &[u8]) -> usize {
+    le_u16(bytes) | le_u16(&bytes[2..]) << 16
+}
+
+fn invalid_block(reason: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("invalid bgzip block: {}", reason))
+}
@@ -0,0 +1,126 @@
+/*! Biotypes of genes and transcripts.
+
+Biotypes are parsed from the `gene_biotype` and `transcript_biotype` (Ensembl) or `gene_type`
+and `transcript_type` (GENCODE) attributes. The commonly used biotypes have their own variants,
+while any other value is kept as-is in the `Other` variant, so that parsing never fails and the
+original value can always be written back.
+*/
+use std::fmt;
+
+
+/// Biotype of a gene or a transcript.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Biotype {
+    /// `protein_coding`
+    ProteinCoding,
+    /// `lncRNA`
+    LncRna,
+    /// `miRNA`
+    MiRna,
+    /// `misc_RNA`
+    MiscRna,
+    /// `rRNA`
+    RRna,
+    /// `snRNA`
+    SnRna,
+    /// `snoRNA`
+    SnoRna,
+    /// `scaRNA`
+    ScaRna,
+    /// `Mt_rRNA`
+    MtRRna,
+    /// `Mt_tRNA`
+    MtTRna,
+    /// `pseudogene`
+    Pseudogene,
+    /// `processed_pseudogene`
+    ProcessedPseudogene,
+    /// `unprocessed_pseudogene`
+    UnprocessedPseudogene,
+    /// `transcribed_processed_pseudogene`
+    TranscribedProcessedPseudogene,
+    /// `transcribed_unprocessed_pseudogene`
+    TranscribedUnprocessedPseudogene,
+    /// `processed_transcript`
+    ProcessedTranscript,
+    /// `retained_intron`
+    RetainedIntron,
+    /// `nonsense_mediated_decay`
+    NonsenseMediatedDecay,
+    /// `non_stop_decay`
+    NonStopDecay,
+    /// `TEC` (to be experimentally confirmed)
+    Tec,
+    /// Any other biotype, stored as-is.
+    Other(String),
+}
+
+impl Biotype {
+
+    /// Returns the string representation of the biotype, as used in GTF attributes.
+    pub fn as_str(&self) -> &str {
+        match *self {
+            Biotype::ProteinCoding => "protein_coding",
+            Biotype::LncRna => "lncRNA",
+            Biotype::MiRna => "miRNA",
+            Biotype::MiscRna => "misc_RNA",
+            Biotype::RRna => "rRNA",
+            Biotype::SnRna => "snRNA",
+            Biotype::SnoRna => "snoRNA",
+            Biotype::ScaRna => "scaRNA",
+            Biotype::MtRRna => "Mt_rRNA",
+            Biotype::MtTRna => "Mt_tRNA",
+            Biotype::Pseudogene => "pseudogene",
+            Biotype::ProcessedPseudogene => "processed_pseudogene",
+            Biotype::UnprocessedPseudogene => "unprocessed_pseudogene",
+            Biotype::TranscribedProcessedPseudogene => "transcribed_processed_pseudogene",
+            Biotype::TranscribedUnprocessedPseudogene => "transcribed_unprocessed_pseudogene",
+            Biotype::ProcessedTranscript => "processed_transcript",
+            Biotype::RetainedIntron => "retained_intron",
+            Biotype::NonsenseMediatedDecay => "nonsense_mediated_decay",
+            Biotype::NonStopDecay => "non_stop_decay",
+            Biotype::Tec => "TEC",
+            Biotype::Other(ref value) => value.as_str(),
+        }
+    }
+
+    /// Returns whether the biotype is any kind of pseudogene, including those without their
+    /// own variants, such as `polymorphic_pseudogene`.
+    pub fn is_pseudogene(&self) -> bool {
+        self.as_str().ends_with("pseudogene")
+    }
+}
+
+impl<'a> From<&'a str> for Biotype {
+    fn from(value: &'a str) -> Self {
+        match value {
+            "protein_coding" => Biotype::ProteinCoding,
+            "lncRNA" => Biotype::LncRna,
+            "miRNA" => Biotype::MiRna,
+            "misc_RNA" => Biotype::MiscRna,
+            "rRNA" => Biotype::RRna,
+            "snRNA" => Biotype::SnRna,
+            "snoRNA" => Biotype::SnoRna,
+            "scaRNA" => Biotype::ScaRna,
+            "Mt_rRNA" => Biotype::MtRRna,
+            "Mt_tRNA" => Biotype::MtTRna,
+            "pseudogene" => Biotype::Pseudogene,
+            "processed_pseudogene" => Biotype::ProcessedPseudogene,
+            "unprocessed_pseudogene" => Biotype::UnprocessedPseudogene,
+            "transcribed_processed_pseudogene" => Biotype::TranscribedProcessedPseudogene,
+            "transcribed_unprocessed_pseudogene" => Biotype::TranscribedUnprocessedPseudogene,
+            "processed_transcript" => Biotype::ProcessedTranscript,
+            "retained_intron" => Biotype::RetainedIntron,
+            "nonsense_mediated_decay" => Biotype::NonsenseMediatedDecay,
+            "non_stop_decay" => Biotype::NonStopDecay,
+            "TEC" => Biotype::Tec,
+            other => Biotype::Other(other.to_owned()),
+        }
+    }
+}
+
+impl fmt::Display for Biotype {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
@@ -0,0 +1,225 @@
+/*! Validation of genes against the lengths of their sequences.
+
+Annotations made for one assembly are easily mixed up with sequences of another, which leaves
+genes on sequences that do not exist or past the ends of their sequences. These genes are found
+using a `SeqDict`, which may be read from chrom.sizes, FASTA index (`.fai`), or Picard sequence
+dictionary files, and are then flagged, clipped, or dropped depending on the `BoundsAction`.
+*/
+use std::collections::HashMap;
+use std::fmt;
+
+use linked_hash_map::LinkedHashMap;
+
+use {GBuilder, Gene, SeqDict, Transcript, DEF_ID};
+use utils::OptionDeref;
+
+
+/// Actions taken on genes that do not fit their sequences.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BoundsAction {
+    /// Keeps the genes as they are, only reporting them.
+    Flag,
+    /// Clips the genes to the ends of their sequences, dropping transcripts that lie entirely
+    /// past them.
+    Clip,
+    /// Discards the genes.
+    Drop,
+}
+
+impl Default for BoundsAction {
+    fn default() -> Self {
+        BoundsAction::Flag
+    }
+}
+
+/// Kinds of bounds issues.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BoundsIssueKind {
+    /// The sequence of the gene is absent from the dictionary.
+    UnknownSequence,
+    /// The gene extends past the end of its sequence, whose length is given.
+    ExceedsLength(u64),
+}
+
+/// A gene that does not fit its sequence, along with the action taken on it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BoundsIssue {
+    /// Identifier of the gene.
+    pub gene_id: Option<String>,
+    /// Sequence name of the gene.
+    pub seq_name: String,
+    /// End coordinate of the gene, before any clipping.
+    pub end: u64,
+    /// Kind of the issue.
+    pub kind: BoundsIssueKind,
+    /// Action taken on the gene.
+    pub action: BoundsAction,
+}
+
+impl fmt::Display for BoundsIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let gid = self.gene_id.as_deref().unwrap_or(DEF_ID);
+        match self.kind {
+            BoundsIssueKind::UnknownSequence =>
+                write!(f, "gene {} lies on unknown sequence '{}'", gid, self.seq_name)?,
+            BoundsIssueKind::ExceedsLength(len) =>
+                write!(f, "gene {} ends at {}, past the end of '{}' at {}",
+                       gid, self.end, self.seq_name, len)?,
+        }
+        match self.action {
+            BoundsAction::Flag => Ok(()),
+            BoundsAction::Clip => write!(f, " (clipped)"),
+            BoundsAction::Drop => write!(f, " (dropped)"),
+        }
+    }
+}
+
+/// Checker of genes against the lengths of their sequences.
+///
+/// By default, genes on unknown sequences and genes exceeding their sequences are only flagged.
+#[derive(Debug, Clone)]
+pub struct BoundsChecker {
+    lengths: HashMap<String, u64>,
+    unknown_action: BoundsAction,
+    exceeding_action: BoundsAction,
+}
+
+impl BoundsChecker {
+
+    /// Creates a checker using the sequence lengths of the given dictionary.
+    pub fn new(dict: &SeqDict) -> Self {
+        BoundsChecker {
+            lengths: dict.seqs().iter().cloned().collect(),
+            unknown_action: BoundsAction::default(),
+            exceeding_action: BoundsAction::default(),
+        }
+    }
+
+    /// Sets the action taken on genes on unknown sequences.
+    ///
+    /// Since these genes can not be clipped, `Clip` drops them.
+    pub fn unknown_action(&mut self, action: BoundsAction) -> &mut Self {
+        self.unknown_action = match action {
+            BoundsAction::Clip => BoundsAction::Drop,
+            other => other,
+        };
+        self
+    }
+
+    /// Sets the action taken on genes extending past the ends of their sequences.
+    pub fn exceeding_action(&mut self, action: BoundsAction) -> &mut Self {
+        self.exceeding_action = action;
+        self
+    }
+
+    /// Returns the issue of the given gene, if it does not fit its sequence.
+    pub fn check_gene(&self, gene: &Gene) -> Option<BoundsIssue> {
+        let (kind, action) = match self.lengths.get(gene.seq_name()) {
+            None => (BoundsIssueKind::UnknownSequence, self.unknown_action),
+            Some(&len) if gene.end() > len => {
+                // genes starting past the end of their sequences can not be clipped
+                let action = match self.exceeding_action {
+                    BoundsAction::Clip if gene.start() >= len => BoundsAction::Drop,
+                    other => other,
+                };
+                (BoundsIssueKind::ExceedsLength(len), action)
+            },
+            Some(_) => return None,
+        };
+        Some(BoundsIssue {
+            gene_id: gene.id().map(|v| v.to_owned()),
+            seq_name: gene.seq_name().to_owned(),
+            end: gene.end(),
+            kind: kind,
+            action: action,
+        })
+    }
+
+    /// Checks the given gene and takes the configured action on it, returning the gene, if it
+    /// is kept, along with its issue, if any.
+    pub fn apply(&self, gene: Gene) -> ::Result<(Option<Gene>, Option<BoundsIssue>)> {
+        let issue = match self.check_gene(&gene) {
+            Some(issue) => issue,
+            None => return Ok((Some(gene), None)),
+        };
+        match (issue.action, issue.kind) {
+            (BoundsAction::Drop, _) => Ok((None, Some(issue))),
+            (BoundsAction::Clip, BoundsIssueKind::ExceedsLength(len)) => {
+                let clipped = clip_gene(&gene, len)?;
+                Ok((Some(clipped), Some(issue)))
+            },
+            _ => Ok((Some(gene), Some(issue))),
+        }
+    }
+}
+
+/// Returns a copy of the given gene clipped to the given sequence length.
+///
+/// The clipped gene ends with its clipped transcripts, or at the end of the sequence if none of
+/// its transcripts are left.
+fn clip_gene(gene: &Gene, len: u64) -> ::Result<Gene> {
+    let transcripts = gene.transcripts().iter()
+        .filter_map(|(tid, trx)| trx.clip(0, len).map(|trx| (tid.clone(), trx)))
+        .collect::<LinkedHashMap<String, Transcript>>();
+    let end = transcripts.values().map(|trx| trx.end()).max().unwrap_or(len);
+    GBuilder::from_gene(gene)
+        .coord(gene.start(), end)
+        .transcripts(transcripts)
+        .build()
+}
+
+/// Iterator over genes checked against the lengths of their sequences.
+///
+/// Genes are flagged, clipped, or dropped depending on the actions of the checker, and the
+/// issues found so far are available through `issues`. Errors in the underlying gene stream are
+/// passed through as-is.
+pub struct BoundedGenes<I> {
+    genes: I,
+    checker: BoundsChecker,
+    issues: Vec<BoundsIssue>,
+}
+
+impl<I> BoundedGenes<I> where I: Iterator<Item=::Result<Gene>> {
+
+    /// Creates an iterator over the given genes, checked using the given checker.
+    pub fn new<T>(genes: T, checker: BoundsChecker) -> Self
+        where T: IntoIterator<Item=::Result<Gene>, IntoIter=I>
+    {
+        BoundedGenes {
+            genes: genes.into_iter(),
+            checker: checker,
+            issues: Vec::new(),
+        }
+    }
+
+    /// Returns the issues of the genes yielded or dropped so far.
+    pub fn issues(&self) -> &[BoundsIssue] {
+        self.issues.as_slice()
+    }
+}
+
+impl<I> Iterator for BoundedGenes<I> where I: Iterator<Item=::Result<Gene>> {
+
+    type Item = ::Result<Gene>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let gx = match self.genes.next() {
+                Some(Ok(gx)) => gx,
+                otherwise => return otherwise,
+            };
+            match self.checker.apply(gx) {
+                Ok((gx, issue)) => {
+                    if let Some(issue) = issue {
+                        self.issues.push(issue);
+                    }
+                    match gx {
+                        Some(gx) => return Some(Ok(gx)),
+                        None => continue,
+                    }
+                },
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
@@ -0,0 +1,127 @@
+/*! Binary caches of parsed annotations.
+
+Parsing large annotation files may take a while. Genes and annotations can instead be saved once
+into a compact binary cache, which is much faster to load in subsequent runs. Caches start with
+a magic string and a format version, so that incompatible caches are rejected instead of being
+misread. Saving and loading caches requires the `cache` feature.
+
+*/
+use std::error::Error;
+use std::io;
+#[cfg(feature = "cache")]
+use std::fs;
+#[cfg(feature = "cache")]
+use std::io::{BufReader, BufWriter, Read, Write};
+#[cfg(feature = "cache")]
+use std::path::Path;
+
+#[cfg(feature = "cache")]
+use bincode::{self, Infinite};
+#[cfg(feature = "cache")]
+use serde::Serialize;
+#[cfg(feature = "cache")]
+use serde::de::DeserializeOwned;
+
+
+/// Magic bytes at the start of cache files.
+pub const CACHE_MAGIC: &'static [u8; 4] = b"GTEC";
+
+/// Version of the cache format written by this crate.
+///
+/// This must be incremented whenever the serialized form of any cached type changes.
+pub const CACHE_VERSION: u32 = 7;
+
+quick_error! {
+    /// Errors that occur when saving or loading caches.
+    #[derive(Debug)]
+    pub enum CacheError {
+        /// Occurs when the input does not start with the cache magic bytes.
+        InvalidHeader {
+            description("input is not a gte cache")
+        }
+        /// Occurs when the cache was written with an unsupported format version.
+        UnsupportedVersion(version: u32) {
+            description("unsupported cache format version")
+            display(self_) -> ("{}: {}, expected: {}", self_.description(), version, CACHE_VERSION)
+        }
+        /// Occurs when the cache contents can not be encoded or decoded.
+        Encoding(msg: String) {
+            description("invalid cache contents")
+            display(self_) -> ("{}: {}", self_.description(), msg)
+        }
+        /// Errors propagated from reading or writing the underlying cache.
+        Io(err: io::Error) {
+            description(err.description())
+            from()
+            cause(err)
+        }
+    }
+}
+
+/// Writes the given value as a cache, preceded by the cache header.
+#[cfg(feature = "cache")]
+pub fn write_cache<W, T>(writer: &mut W, value: &T) -> ::Result<()>
+    where W: Write, T: Serialize
+{
+    writer.write_all(CACHE_MAGIC)
+        .and_then(|_| writer.write_all(&version_bytes(CACHE_VERSION)))
+        .map_err(|e| ::Error::from(CacheError::from(e)))?;
+    bincode::serialize_into(writer, value, Infinite)
+        .map_err(|e| ::Error::from(CacheError::Encoding(e.to_string())))
+}
+
+/// Reads a value from a cache, after checking the cache header.
+#[cfg(feature = "cache")]
+pub fn read_cache<R, T>(reader: &mut R) -> ::Result<T>
+    where R: Read, T: DeserializeOwned
+{
+    let mut header = [0u8; 8];
+    reader.read_exact(&mut header)
+        .map_err(|e| match e.kind() {
+            io::ErrorKind::UnexpectedEof => ::Error::from(CacheError::InvalidHeader),
+            _ => ::Error::from(CacheError::from(e)),
+        })?;
+    if &header[..4] != &CACHE_MAGIC[..] {
+        return Err(::Error::from(CacheError::InvalidHeader));
+    }
+    let version = version_from_bytes(&header[4..]);
+    if version != CACHE_VERSION {
+        return Err(::Error::from(CacheError::UnsupportedVersion(version)));
+    }
+    bincode::deserialize_from(reader, Infinite)
+        .map_err(|e| ::Error::from(CacheError::Encoding(e.to_string())))
+}
+
+/// Saves the given value, such as a `GenomeAnnotation` or a vector of genes, into a cache file.
+#[cfg(feature = "cache")]
+pub fn save_cache<P, T>(path: P, value: &T) -> ::Result<()>
+    where P: AsRef<Path>, T: Serialize
+{
+    let file = fs::File::create(path).map_err(|e| ::Error::from(CacheError::from(e)))?;
+    let mut writer = BufWriter::new(file);
+    write_cache(&mut writer, value)?;
+    writer.flush().map_err(|e| ::Error::from(CacheError::from(e)))
+}
+
+/// Loads a value, such as a `GenomeAnnotation` or a vector of genes, from a cache file.
+#[cfg(feature = "cache")]
+pub fn load_cache<P, T>(path: P) -> ::Result<T>
+    where P: AsRef<Path>, T: DeserializeOwned
+{
+    let file = fs::File::open(path).map_err(|e| ::Error::from(CacheError::from(e)))?;
+    read_cache(&mut BufReader::new(file))
+}
+
+/// Helper function for encoding the format version as little-endian bytes.
+#[cfg(feature = "cache")]
+#[inline]
+fn version_bytes(version: u32) -> [u8; 4] {
+    [version as u8, (version >> 8) as u8, (version >> 16) as u8, (version >> 24) as u8]
+}
+
+/// Helper function for decoding the format version from little-endian bytes.
+#[cfg(feature = "cache")]
+#[inline]
+fn version_from_bytes(bytes: &[u8]) -> u32 {
+    bytes.iter().rev().fold(0, |acc, &b| (acc << 8) | b as u32)
+}
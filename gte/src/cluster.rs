@@ -0,0 +1,211 @@
+/*! Clustering of transcripts into synthetic genes.
+
+Some annotations, such as those assembled from RNA-seq reads, contain transcripts without any
+gene identifiers. These can be grouped into genes the way `cuffmerge` and `stringtie --merge`
+do: transcripts on the same sequence and strand are clustered together when their exons
+overlap, directly or through other transcripts of the cluster. Each cluster becomes a gene
+whose identifier is generated from a prefix and a running number (e.g. `GENE.1`, `GENE.2`).
+
+Transcripts with unknown strands are clustered only with each other. Any gene identifiers the
+transcripts already have are replaced with the generated ones, and are kept in their
+`ref_gene_id` attribute.
+*/
+use std::cmp::max;
+use std::collections::HashMap;
+use std::vec;
+
+use {Gene, GBuilder, Strand, Transcript, DEF_ID};
+use linked_hash_map::LinkedHashMap;
+use model::suffixed_id;
+
+
+/// Default prefix of generated gene identifiers.
+pub const DEF_CLUSTER_PREFIX: &'static str = "GENE";
+
+/// Attribute of clustered transcripts holding their original gene identifiers.
+pub const REF_GENE_ID_ATTR: &'static str = "ref_gene_id";
+
+/// Iterator over genes created by clustering transcripts.
+///
+/// Since clusters can only be completed once all transcripts of their sequences are known, the
+/// underlying transcript stream is read in full on the first call to `next`. Genes are yielded
+/// in order of first appearance of their sequences in the stream, and then sorted by their
+/// coordinates. The first error in the underlying stream is returned as-is, after which the
+/// iterator is exhausted.
+pub struct ClusteredGenes<I> {
+    transcripts: Option<I>,
+    id_prefix: String,
+    genes: vec::IntoIter<Gene>,
+}
+
+impl<I> ClusteredGenes<I> where I: Iterator<Item=::Result<Transcript>> {
+
+    /// Creates an iterator over genes clustered from the given transcripts, with identifiers
+    /// generated using the given prefix.
+    pub fn new<T, S>(transcripts: T, id_prefix: S) -> Self
+        where T: IntoIterator<Item=::Result<Transcript>, IntoIter=I>, S: Into<String>
+    {
+        ClusteredGenes {
+            transcripts: Some(transcripts.into_iter()),
+            id_prefix: id_prefix.into(),
+            genes: Vec::new().into_iter(),
+        }
+    }
+
+    /// Reads all transcripts and clusters them into genes.
+    fn cluster(&self, transcripts: I) -> ::Result<Vec<Gene>> {
+        let mut group_idxs = HashMap::new();
+        let mut groups: Vec<Vec<Transcript>> = Vec::new();
+        for trx in transcripts {
+            let trx = trx?;
+            let key = (trx.seq_name().to_owned(), strand_char(trx.strand()));
+            let idx = *group_idxs.entry(key).or_insert(groups.len());
+            if idx == groups.len() {
+                groups.push(Vec::new());
+            }
+            groups[idx].push(trx);
+        }
+
+        let mut genes = Vec::new();
+        let mut seq_order: Vec<String> = Vec::new();
+        for mut group in groups {
+            group.sort_by_key(|trx| (trx.start(), trx.end()));
+            let seq_name = group[0].seq_name().to_owned();
+            let seq_idx = match seq_order.iter().position(|name| *name == seq_name) {
+                Some(idx) => idx,
+                None => {
+                    seq_order.push(seq_name);
+                    seq_order.len() - 1
+                },
+            };
+            for cluster in cluster_group(group) {
+                let start = cluster.iter().map(|trx| trx.start()).min().unwrap();
+                genes.push((seq_idx, start, cluster));
+            }
+        }
+        genes.sort_by_key(|&(seq_idx, start, _)| (seq_idx, start));
+
+        let mut built = Vec::with_capacity(genes.len());
+        for (num, (_, _, cluster)) in genes.into_iter().enumerate() {
+            let gid = format!("{}.{}", self.id_prefix, num + 1);
+            built.push(cluster_to_gene(gid, cluster)?);
+        }
+        Ok(built)
+    }
+}
+
+impl<I> Iterator for ClusteredGenes<I> where I: Iterator<Item=::Result<Transcript>> {
+
+    type Item = ::Result<Gene>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(transcripts) = self.transcripts.take() {
+            match self.cluster(transcripts) {
+                Ok(genes) => self.genes = genes.into_iter(),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+        self.genes.next().map(Ok)
+    }
+}
+
+/// Returns the character representation of the given strand.
+fn strand_char(strand: &Strand) -> char {
+    match *strand {
+        Strand::Forward => '+',
+        Strand::Reverse => '-',
+        Strand::Unknown => '.',
+    }
+}
+
+/// Returns whether any exons of the given transcripts overlap.
+fn exons_overlap(a: &Transcript, b: &Transcript) -> bool {
+    let (aexs, bexs) = (a.exons(), b.exons());
+    let (mut i, mut j) = (0, 0);
+    while i < aexs.len() && j < bexs.len() {
+        if aexs[i].start() < bexs[j].end() && bexs[j].start() < aexs[i].end() {
+            return true;
+        }
+        if aexs[i].end() <= bexs[j].end() {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    false
+}
+
+/// Returns the root of the given index in the union-find parents.
+fn find_root(parents: &mut [usize], mut idx: usize) -> usize {
+    while parents[idx] != idx {
+        parents[idx] = parents[parents[idx]];
+        idx = parents[idx];
+    }
+    idx
+}
+
+/// Clusters the given transcripts, which lie on the same sequence and strand and are sorted by
+/// their coordinates, by the overlap of their exons.
+fn cluster_group(group: Vec<Transcript>) -> Vec<Vec<Transcript>> {
+    let mut parents = (0..group.len()).collect::<Vec<usize>>();
+    // only transcripts within the same locus, i.e. with overlapping spans, can share exons
+    let mut locus_start = 0;
+    let mut locus_end = 0;
+    for idx in 0..group.len() {
+        if group[idx].start() >= locus_end {
+            locus_start = idx;
+            locus_end = group[idx].end();
+        } else {
+            locus_end = max(locus_end, group[idx].end());
+        }
+        for prev in locus_start..idx {
+            if exons_overlap(&group[prev], &group[idx]) {
+                let (root_prev, root_cur) =
+                    (find_root(&mut parents, prev), find_root(&mut parents, idx));
+                parents[root_cur] = root_prev;
+            }
+        }
+    }
+
+    let mut cluster_idxs = HashMap::new();
+    let mut clusters: Vec<Vec<Transcript>> = Vec::new();
+    for (idx, trx) in group.into_iter().enumerate() {
+        let root = find_root(&mut parents, idx);
+        let cidx = *cluster_idxs.entry(root).or_insert(clusters.len());
+        if cidx == clusters.len() {
+            clusters.push(Vec::new());
+        }
+        clusters[cidx].push(trx);
+    }
+    clusters
+}
+
+/// Creates a gene with the given identifier from the given clustered transcripts.
+fn cluster_to_gene(gid: String, cluster: Vec<Transcript>) -> ::Result<Gene> {
+    let seq_name = cluster[0].seq_name().to_owned();
+    let strand = *cluster[0].strand();
+    let start = cluster.iter().map(|trx| trx.start()).min().unwrap();
+    let end = cluster.iter().map(|trx| trx.end()).max().unwrap();
+
+    let mut transcripts = LinkedHashMap::new();
+    for mut trx in cluster {
+        let ref_gid = trx.gene_id().map(|id| id.to_owned());
+        if let Some(ref_gid) = ref_gid {
+            trx.attributes_mut().insert(REF_GENE_ID_ATTR.to_owned(), ref_gid);
+        }
+        trx.set_gene_id(Some(gid.as_str()));
+        let tid = trx.id().unwrap_or(DEF_ID).to_owned();
+        let tid = if transcripts.contains_key(&tid) {
+            suffixed_id(&tid, |candidate| transcripts.contains_key(candidate))
+        } else {
+            tid
+        };
+        let _ = transcripts.insert(tid, trx);
+    }
+
+    GBuilder::new(seq_name, start, end)
+        .id(gid)
+        .strand(strand)
+        .transcripts(transcripts)
+        .build()
+}
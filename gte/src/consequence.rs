@@ -0,0 +1,221 @@
+/*! Classification of the consequences of small variants on transcripts.
+
+Variants are mapped onto the transcripts that they overlap and classified using the exon and
+coding features of each transcript. Coding changes of substitutions are determined by
+translating the affected codons of the reference and the altered coding sequences, which are
+read from a sequence source such as an indexed FASTA file.
+
+*/
+use std::cmp::{max, min};
+
+use {CodonTable, GenomeAnnotation, SequenceSource, Strand, Transcript};
+use sequence::complement;
+
+
+/// Number of intronic bases at each end of an intron that are considered splice sites.
+const SPLICE_SITE_LEN: u64 = 2;
+
+/// Small variant, such as a single VCF record with one alternate allele.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Variant {
+    seq_name: String,
+    pos: u64,
+    ref_allele: Vec<u8>,
+    alt_allele: Vec<u8>,
+}
+
+impl Variant {
+
+    /// Creates a variant from its sequence name, zero-based position, and its reference and
+    /// alternate alleles.
+    ///
+    /// Note that VCF positions are one-based, so they must be decremented by one.
+    pub fn new<T>(seq_name: T, pos: u64, ref_allele: &[u8], alt_allele: &[u8]) -> Variant
+        where T: Into<String>
+    {
+        Variant {
+            seq_name: seq_name.into(),
+            pos: pos,
+            ref_allele: ref_allele.to_vec(),
+            alt_allele: alt_allele.to_vec(),
+        }
+    }
+
+    /// Returns the sequence name.
+    pub fn seq_name(&self) -> &str {
+        self.seq_name.as_str()
+    }
+
+    /// Returns the zero-based position.
+    pub fn pos(&self) -> u64 {
+        self.pos
+    }
+
+    /// Returns the reference allele.
+    pub fn ref_allele(&self) -> &[u8] {
+        self.ref_allele.as_slice()
+    }
+
+    /// Returns the alternate allele.
+    pub fn alt_allele(&self) -> &[u8] {
+        self.alt_allele.as_slice()
+    }
+
+    /// Returns the zero-based, half-open region covered by the reference allele.
+    ///
+    /// Variants with empty reference alleles cover the single base at their positions.
+    fn span(&self) -> (u64, u64) {
+        (self.pos, self.pos + max(self.ref_allele.len() as u64, 1))
+    }
+}
+
+/// Consequences of variants on transcripts.
+///
+/// Consequences are declared from the most to the least severe, so that more severe
+/// consequences compare as smaller values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Consequence {
+    /// Variant overlapping the first or last two bases of an intron.
+    SpliceSite,
+    /// Substitution that changes an amino acid codon into a stop codon.
+    Nonsense,
+    /// Substitution that changes a stop codon into an amino acid codon.
+    StopLost,
+    /// Insertion or deletion in the coding region whose length is not a multiple of three.
+    Frameshift,
+    /// Insertion or deletion in the coding region whose length is a multiple of three.
+    InframeIndel,
+    /// Substitution that changes the encoded amino acids.
+    Missense,
+    /// Substitution in the coding region that does not change the encoded amino acids.
+    Synonymous,
+    /// Variant in the 5' untranslated region.
+    FivePrimeUtr,
+    /// Variant in the 3' untranslated region.
+    ThreePrimeUtr,
+    /// Variant in an exon of a non-coding transcript.
+    NonCodingExon,
+    /// Variant in an intron, outside of its splice sites.
+    Intronic,
+    /// Variant outside of any transcript.
+    Intergenic,
+}
+
+impl Transcript {
+
+    /// Returns the consequence of the given variant on the transcript.
+    ///
+    /// Variants on other sequences or outside of the transcript are `Intergenic`. The
+    /// consequences of insertions and deletions in the coding region are determined by their
+    /// lengths alone, while those of substitutions are determined by translating the affected
+    /// codons with the given codon table. Substitutions affecting more than one codon are
+    /// assigned the most severe consequence among them.
+    ///
+    /// Transcripts with unknown strands are treated as forward-stranded.
+    pub fn variant_consequence<S>(&self, variant: &Variant, source: &mut S, table: &CodonTable)
+        -> ::Result<Consequence>
+        where S: SequenceSource
+    {
+        let (start, end) = variant.span();
+        if variant.seq_name() != self.seq_name() || end <= self.start() || start >= self.end() {
+            return Ok(Consequence::Intergenic);
+        }
+        let in_splice_site = self.introns().any(|intron| {
+            let (istart, iend) = (intron.start(), intron.end());
+            let site_len = min(SPLICE_SITE_LEN, iend - istart);
+            (start < istart + site_len && end > istart) || (start < iend && end > iend - site_len)
+        });
+        if in_splice_site {
+            return Ok(Consequence::SpliceSite);
+        }
+        if !self.exons().iter().any(|exn| exn.start() < end && exn.end() > start) {
+            return Ok(Consequence::Intronic);
+        }
+
+        let (coding_start, coding_end) = match self.coding_coord(true) {
+            Some(coord) => coord,
+            None => return Ok(Consequence::NonCodingExon),
+        };
+        if end <= coding_start || start >= coding_end {
+            let upstream = (end <= coding_start) != (*self.strand() == Strand::Reverse);
+            let utr =
+                if upstream { Consequence::FivePrimeUtr } else { Consequence::ThreePrimeUtr };
+            return Ok(utr);
+        }
+
+        let (ref_len, alt_len) = (variant.ref_allele.len(), variant.alt_allele.len());
+        if ref_len != alt_len {
+            let diff = if ref_len > alt_len { ref_len - alt_len } else { alt_len - ref_len };
+            let indel =
+                if diff % 3 == 0 { Consequence::InframeIndel } else { Consequence::Frameshift };
+            return Ok(indel);
+        }
+        self.substitution_consequence(variant, source, table)
+    }
+
+    /// Returns the consequence of the given substitution, which overlaps the coding region.
+    fn substitution_consequence<S>(&self, variant: &Variant, source: &mut S, table: &CodonTable)
+        -> ::Result<Consequence>
+        where S: SequenceSource
+    {
+        let ref_cds = self.cds_sequence(source, true)?;
+        let mut alt_cds = ref_cds.clone();
+        let reverse = *self.strand() == Strand::Reverse;
+        let phase = self.coding_phase() as usize;
+
+        let mut codon_starts = Vec::new();
+        for (offset, &base) in variant.alt_allele.iter().enumerate() {
+            let cpos = match self.genomic_to_cds(variant.pos + offset as u64, true) {
+                Some(cpos) => cpos as usize,
+                None => continue,
+            };
+            alt_cds[cpos] = if reverse { complement(base) } else { base };
+            if cpos >= phase {
+                codon_starts.push(cpos - (cpos - phase) % 3);
+            }
+        }
+        codon_starts.sort();
+        codon_starts.dedup();
+
+        let mut consequence = Consequence::Synonymous;
+        for cstart in codon_starts.into_iter().filter(|&cs| cs + 3 <= ref_cds.len()) {
+            let ref_aa = table.translate_codon(&ref_cds[cstart..cstart + 3]);
+            let alt_aa = table.translate_codon(&alt_cds[cstart..cstart + 3]);
+            let codon_csq =
+                if ref_aa == alt_aa {
+                    Consequence::Synonymous
+                } else if alt_aa == b'*' {
+                    Consequence::Nonsense
+                } else if ref_aa == b'*' {
+                    Consequence::StopLost
+                } else {
+                    Consequence::Missense
+                };
+            consequence = min(consequence, codon_csq);
+        }
+        Ok(consequence)
+    }
+}
+
+impl GenomeAnnotation {
+
+    /// Returns the consequences of the given variant on all transcripts that it overlaps.
+    ///
+    /// Transcripts are ordered as in `transcripts_at`. A single `Intergenic` consequence with
+    /// no transcript is returned if the variant does not overlap any transcript.
+    pub fn variant_consequences<S>(&self, variant: &Variant, source: &mut S, table: &CodonTable)
+        -> ::Result<Vec<(Option<&Transcript>, Consequence)>>
+        where S: SequenceSource
+    {
+        let (start, end) = variant.span();
+        let transcripts = self.transcripts_at(variant.seq_name(), start..end);
+        if transcripts.is_empty() {
+            return Ok(vec![(None, Consequence::Intergenic)]);
+        }
+        let mut consequences = Vec::with_capacity(transcripts.len());
+        for trx in transcripts {
+            consequences.push((Some(trx), trx.variant_consequence(variant, source, table)?));
+        }
+        Ok(consequences)
+    }
+}
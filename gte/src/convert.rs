@@ -0,0 +1,182 @@
+/*! Conversion of annotation files between formats in a single call.
+
+`convert` reads an annotation file in any of the formats detected by `AnnotationReader`, and
+writes its genes to a file in the format given by `ConvertOptions` or indicated by the output
+extension, optionally filtering and sorting the genes on the way:
+
+```no_run
+use gte::SeqOrder;
+use gte::convert::{convert, ConvertOptions};
+
+let opts = ConvertOptions::new()
+    .filter(|gene| gene.seq_name() != "chrM")
+    .sort(SeqOrder::Karyotype);
+let num_genes = convert("genes.gtf.gz", "genes.refFlat", &opts).unwrap();
+```
+
+For inputs and outputs other than files, such as standard streams, the steps are also available
+separately as `create_writer` and `write_genes`.
+*/
+use std::error::Error;
+use std::fs;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use {Gene, GffType};
+use io_auto::{AnnotationFormat, AnnotationReader, ReadAnnotation, WriteAnnotation};
+use io_bed::Writer as BedWriter;
+use io_genepred::Writer as GenePredWriter;
+use io_gff::Writer as GffWriter;
+use io_refflat::Writer as RefFlatWriter;
+use sort::{SeqOrder, sorted_genes};
+
+
+quick_error! {
+    /// Errors that occur when converting annotation files.
+    #[derive(Debug)]
+    pub enum ConvertError {
+        /// Occurs when no output format is given and the output extension is not recognized.
+        UnknownOutputFormat(path: String) {
+            description("unknown output format")
+            display(self_) -> ("{}: {}", self_.description(), path)
+        }
+        /// Errors propagated from creating the output file.
+        Io(err: io::Error) {
+            description(err.description())
+            from()
+            cause(err)
+        }
+    }
+}
+
+/// Options of annotation file conversions.
+#[derive(Default)]
+pub struct ConvertOptions {
+    input_format: Option<AnnotationFormat>,
+    output_format: Option<AnnotationFormat>,
+    seq_order: Option<SeqOrder>,
+    filter: Option<Box<Fn(&Gene) -> bool>>,
+    strip_id_versions: bool,
+}
+
+impl ConvertOptions {
+
+    /// Creates options that write all genes in their input order, with the input format
+    /// detected and the output format indicated by the output extension.
+    pub fn new() -> Self {
+        ConvertOptions::default()
+    }
+
+    /// Sets the input format, skipping its detection.
+    pub fn input_format(mut self, format: AnnotationFormat) -> Self {
+        self.input_format = Some(format);
+        self
+    }
+
+    /// Sets the output format, regardless of the output extension.
+    pub fn output_format(mut self, format: AnnotationFormat) -> Self {
+        self.output_format = Some(format);
+        self
+    }
+
+    /// Sets the genes to be sorted by their sequence names, in the given order, and their
+    /// coordinates.
+    ///
+    /// All genes are kept in memory before they are written.
+    pub fn sort(mut self, order: SeqOrder) -> Self {
+        self.seq_order = Some(order);
+        self
+    }
+
+    /// Sets whether the version suffixes of gene and transcript identifiers are stripped before
+    /// the genes are filtered and written.
+    pub fn strip_id_versions(mut self, strip_id_versions: bool) -> Self {
+        self.strip_id_versions = strip_id_versions;
+        self
+    }
+
+    /// Sets the predicate of the genes to write.
+    pub fn filter<F>(mut self, predicate: F) -> Self
+        where F: Fn(&Gene) -> bool + 'static
+    {
+        self.filter = Some(Box::new(predicate));
+        self
+    }
+}
+
+/// Converts the annotation file at the given input path into a file at the given output path,
+/// returning the number of genes written.
+///
+/// The input may be gzip or bgzip-compressed, and its format, unless given, is detected as in
+/// `AnnotationReader::open`. The output is always uncompressed.
+pub fn convert<P, Q>(input_path: P, output_path: Q, opts: &ConvertOptions) -> ::Result<usize>
+    where P: AsRef<Path>, Q: AsRef<Path>
+{
+    let format = match opts.output_format {
+        Some(format) => format,
+        None => AnnotationFormat::from_extension(&output_path)
+            .ok_or_else(|| {
+                let path = output_path.as_ref().to_string_lossy().into_owned();
+                ::Error::from(ConvertError::UnknownOutputFormat(path))
+            })?,
+    };
+    let mut reader = match opts.input_format {
+        Some(input_format) => AnnotationReader::open_as(input_path, input_format)?,
+        None => AnnotationReader::open(input_path)?,
+    };
+    let file = fs::File::create(output_path)
+        .map_err(|e| ::Error::from(ConvertError::from(e)))?;
+    let mut writer = create_writer(BufWriter::new(file), format);
+    write_genes(&mut reader, &mut *writer, opts)
+}
+
+/// Writes the genes of the given reader to the given writer, filtering and sorting them as set
+/// in the given options, and returns the number of genes written.
+///
+/// The input and output formats of the options are ignored.
+pub fn write_genes<R, W>(reader: &mut R, writer: &mut W, opts: &ConvertOptions) -> ::Result<usize>
+    where R: ReadAnnotation + ?Sized, W: WriteAnnotation + ?Sized
+{
+    let strip_id_versions = opts.strip_id_versions;
+    let genes = reader.read_genes()?
+        .map(|result| result.map(|mut gene| {
+            if strip_id_versions {
+                gene.strip_id_versions();
+            }
+            gene
+        }))
+        .filter(|result| match (result, &opts.filter) {
+            (&Ok(ref gene), &Some(ref predicate)) => predicate(gene),
+            _ => true,
+        });
+
+    let mut num_genes = 0;
+    match opts.seq_order {
+        Some(ref order) => {
+            for gene in sorted_genes(genes, order)? {
+                writer.write_gene(&gene)?;
+                num_genes += 1;
+            }
+        },
+        None => {
+            for gene in genes {
+                writer.write_gene(&gene?)?;
+                num_genes += 1;
+            }
+        },
+    }
+    Ok(num_genes)
+}
+
+/// Creates a writer of the given format from another writer.
+pub fn create_writer<W>(out_writer: W, format: AnnotationFormat) -> Box<WriteAnnotation>
+    where W: Write + 'static
+{
+    match format {
+        AnnotationFormat::RefFlat => Box::new(RefFlatWriter::from_writer(out_writer)),
+        AnnotationFormat::GenePred => Box::new(GenePredWriter::from_writer(out_writer)),
+        AnnotationFormat::Gtf => Box::new(GffWriter::from_writer(out_writer, GffType::GTF2)),
+        AnnotationFormat::Gff3 => Box::new(GffWriter::from_writer(out_writer, GffType::GFF3)),
+        AnnotationFormat::Bed12 => Box::new(BedWriter::from_writer(out_writer)),
+    }
+}
@@ -0,0 +1,156 @@
+/*! Detection of transcripts with identical exon chains.
+
+Transcripts are duplicates of each other if they lie on the same sequence and strand and their
+exons have the same coordinates. Optionally, the start of the first exon and the end of the
+last exon may be ignored, so that transcripts that differ only in the extent of their UTRs are
+also duplicates. Single-exon transcripts are always compared by their full coordinates.
+
+The first transcript of each set of duplicates, in input order, is kept. Its duplicates are
+either removed, with their identifiers recorded in the `merged_transcript_ids` attribute of the
+kept transcript, or kept and flagged with the identifier of the kept transcript in their
+`duplicate_of` attribute.
+*/
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+
+use {Gene, Strand, Transcript};
+
+
+/// Attribute of kept transcripts listing the identifiers of their merged duplicates.
+pub const MERGED_IDS_ATTR: &'static str = "merged_transcript_ids";
+
+/// Attribute of flagged transcripts holding the identifier of the transcript they duplicate.
+pub const DUPLICATE_OF_ATTR: &'static str = "duplicate_of";
+
+/// Actions taken on duplicate transcripts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DuplicateAction {
+    /// Removes duplicates, recording their identifiers in the kept transcript.
+    Merge,
+    /// Keeps duplicates, recording the identifier of the kept transcript in each of them.
+    Flag,
+}
+
+impl Default for DuplicateAction {
+    fn default() -> Self {
+        DuplicateAction::Merge
+    }
+}
+
+/// Deduplicator of transcripts with identical exon chains.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Deduplicator {
+    ignore_utr_ends: bool,
+    across_genes: bool,
+    action: DuplicateAction,
+}
+
+impl Deduplicator {
+
+    /// Creates a deduplicator that merges transcripts of the same gene with identical exons.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets whether the start of the first exon and the end of the last exon are ignored when
+    /// comparing multi-exon transcripts.
+    pub fn ignore_utr_ends(&mut self, ignore_utr_ends: bool) -> &mut Self {
+        self.ignore_utr_ends = ignore_utr_ends;
+        self
+    }
+
+    /// Sets whether transcripts of different genes are compared by `dedup_genes`.
+    ///
+    /// Genes left without transcripts after merging are removed.
+    pub fn across_genes(&mut self, across_genes: bool) -> &mut Self {
+        self.across_genes = across_genes;
+        self
+    }
+
+    /// Sets the action taken on duplicates.
+    pub fn action(&mut self, action: DuplicateAction) -> &mut Self {
+        self.action = action;
+        self
+    }
+
+    /// Deduplicates the transcripts of the given gene.
+    ///
+    /// The coordinates of the gene are kept as they are, even if merging removes its outermost
+    /// transcripts.
+    pub fn dedup_gene(&self, gene: Gene) -> Gene {
+        let mut genes = self.dedup(vec![gene], false);
+        genes.pop().expect("a single gene is never removed")
+    }
+
+    /// Deduplicates the transcripts of the given genes, within each gene or, if set, across
+    /// all of them.
+    pub fn dedup_genes(&self, genes: Vec<Gene>) -> Vec<Gene> {
+        self.dedup(genes, self.across_genes)
+    }
+
+    fn dedup(&self, mut genes: Vec<Gene>, across_genes: bool) -> Vec<Gene> {
+        // (gene index, transcript ID) of duplicates and of the transcripts they duplicate
+        let mut duplicates = Vec::new();
+        {
+            let mut firsts: HashMap<_, (usize, String)> = HashMap::new();
+            for (gidx, gene) in genes.iter().enumerate() {
+                for (tid, transcript) in gene.transcripts().iter() {
+                    let scope = if across_genes { 0 } else { gidx };
+                    let key = (scope, self.exon_chain(transcript));
+                    match firsts.entry(key) {
+                        Entry::Occupied(entry) =>
+                            duplicates.push(((gidx, tid.clone()), entry.get().clone())),
+                        Entry::Vacant(entry) => {
+                            let _ = entry.insert((gidx, tid.clone()));
+                        },
+                    }
+                }
+            }
+        }
+        if duplicates.is_empty() {
+            return genes;
+        }
+
+        let had_transcripts = genes.iter()
+            .map(|gx| !gx.transcripts().is_empty())
+            .collect::<Vec<bool>>();
+        for ((dup_gidx, dup_tid), (kept_gidx, kept_tid)) in duplicates {
+            match self.action {
+                DuplicateAction::Merge => {
+                    let _ = genes[dup_gidx].transcripts_mut().remove(&dup_tid);
+                    if let Some(kept) = genes[kept_gidx].transcripts_mut().get_mut(&kept_tid) {
+                        kept.attributes_mut().insert(MERGED_IDS_ATTR.to_owned(), dup_tid);
+                    }
+                },
+                DuplicateAction::Flag => {
+                    if let Some(dup) = genes[dup_gidx].transcripts_mut().get_mut(&dup_tid) {
+                        dup.attributes_mut().insert(DUPLICATE_OF_ATTR.to_owned(), kept_tid);
+                    }
+                },
+            }
+        }
+        genes.into_iter()
+            .zip(had_transcripts.into_iter())
+            .filter(|&(ref gx, had)| !had || !gx.transcripts().is_empty())
+            .map(|(gx, _)| gx)
+            .collect()
+    }
+
+    /// Returns the sequence name, strand, and exon coordinates that identify the structure of
+    /// the given transcript.
+    fn exon_chain(&self, transcript: &Transcript) -> (String, char, Vec<u64>) {
+        let mut coords = transcript.exons().iter()
+            .flat_map(|exn| vec![exn.start(), exn.end()])
+            .collect::<Vec<u64>>();
+        if self.ignore_utr_ends && coords.len() > 2 {
+            let _ = coords.pop();
+            let _ = coords.remove(0);
+        }
+        let strand = match transcript.strand() {
+            &Strand::Forward => '+',
+            &Strand::Reverse => '-',
+            &Strand::Unknown => '.',
+        };
+        (transcript.seq_name().to_owned(), strand, coords)
+    }
+}
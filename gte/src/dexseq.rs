@@ -0,0 +1,185 @@
+/*! Flattening of genes into DEXSeq exon counting bins.
+
+[DEXSeq](https://bioconductor.org/packages/DEXSeq) tests for differential exon usage by counting
+reads in disjoint bins, called exonic parts. The exons of all transcripts of a gene are split at
+every exon boundary, so that each exonic part is covered by the same set of transcripts along its
+whole length. Exonic parts are numbered genome-wise, starting from one.
+
+The writer emits the flattened GFF produced by the `dexseq_prepare_annotation.py` script: one
+`aggregate_gene` row per gene, followed by one `exonic_part` row per exonic part. Unlike the
+script, genes that overlap each other are not aggregated into a single gene.
+*/
+use std::error::Error;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use std::str;
+
+use {Gene, Strand};
+
+
+/// Value of the source column of the written rows.
+const DEXSEQ_SOURCE: &'static str = "dexseq_prepare_annotation.py";
+
+quick_error! {
+    /// Errors that occur when writing DEXSeq flattened annotations.
+    #[derive(Debug)]
+    pub enum DexSeqError {
+        /// Occurs when the written gene has no identifier.
+        MissingGeneId(seq_name: String, start: u64, end: u64) {
+            description("gene has no identifier")
+            display(self_) -> ("{}: {}:{}-{}", self_.description(), seq_name, start, end)
+        }
+        /// Errors propagated from writing the underlying stream.
+        Io(err: io::Error) {
+            description(err.description())
+            from()
+            cause(err)
+        }
+    }
+}
+
+/// Disjoint exonic region of a gene, covered by the same transcripts along its whole length.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExonicPart {
+    number: usize,
+    start: u64,
+    end: u64,
+    transcript_ids: Vec<String>,
+}
+
+impl ExonicPart {
+
+    /// Returns the one-based, genome-wise number of the exonic part within its gene.
+    pub fn number(&self) -> usize {
+        self.number
+    }
+
+    /// Returns the genome-wise 5'-most coordinate of the exonic part.
+    pub fn start(&self) -> u64 {
+        self.start
+    }
+
+    /// Returns the genome-wise 3'-most coordinate of the exonic part.
+    pub fn end(&self) -> u64 {
+        self.end
+    }
+
+    /// Returns the identifiers of the transcripts covering the exonic part.
+    ///
+    /// The identifiers are in the order of the transcripts in their gene.
+    pub fn transcript_ids(&self) -> &[String] {
+        self.transcript_ids.as_slice()
+    }
+}
+
+impl Gene {
+
+    /// Returns the exonic parts of the gene, sorted by their coordinates.
+    ///
+    /// The exons of all transcripts are split at every exon start and end coordinate of the
+    /// gene. Regions not covered by any exon are not included.
+    pub fn exonic_parts(&self) -> Vec<ExonicPart> {
+        let exons = self.transcripts().iter()
+            .flat_map(|(tid, trx)| {
+                trx.exons().iter().map(move |exn| (exn.start(), exn.end(), tid))
+            })
+            .collect::<Vec<(u64, u64, &String)>>();
+
+        let mut bounds = exons.iter()
+            .flat_map(|&(start, end, _)| vec![start, end])
+            .collect::<Vec<u64>>();
+        bounds.sort();
+        bounds.dedup();
+
+        let mut parts = Vec::new();
+        for pair in bounds.windows(2) {
+            let (start, end) = (pair[0], pair[1]);
+            let mut transcript_ids: Vec<String> = Vec::new();
+            for &(_, _, tid) in exons.iter().filter(|&&(s, e, _)| s <= start && end <= e) {
+                if !transcript_ids.contains(tid) {
+                    transcript_ids.push(tid.clone());
+                }
+            }
+            if !transcript_ids.is_empty() {
+                parts.push(ExonicPart {
+                    number: parts.len() + 1,
+                    start: start,
+                    end: end,
+                    transcript_ids: transcript_ids,
+                });
+            }
+        }
+        parts
+    }
+}
+
+/// DEXSeq flattened annotation writer.
+pub struct Writer<W: Write> {
+    inner: W,
+}
+
+impl<W: Write> Writer<W> {
+
+    /// Creates a DEXSeq flattened annotation writer from another writer.
+    pub fn from_writer(in_writer: W) -> Writer<W> {
+        Writer {
+            inner: in_writer,
+        }
+    }
+
+    /// Writes the aggregate gene row and the exonic part rows of the given gene.
+    ///
+    /// Genes without exons are written as an aggregate gene row only.
+    pub fn write_gene(&mut self, gene: &Gene) -> ::Result<()> {
+        let gid = gene.id()
+            .ok_or_else(|| DexSeqError::MissingGeneId(
+                gene.seq_name().to_owned(), gene.start(), gene.end()))?;
+        let strand_char = match gene.strand() {
+            &Strand::Forward => '+',
+            &Strand::Reverse => '-',
+            &Strand::Unknown => '.',
+        };
+        writeln!(self.inner, "{}\t{}\taggregate_gene\t{}\t{}\t.\t{}\t.\tgene_id \"{}\"",
+                 gene.seq_name(), DEXSEQ_SOURCE, gene.start() + 1, gene.end(), strand_char, gid)
+            .map_err(|e| ::Error::from(DexSeqError::from(e)))?;
+        for part in gene.exonic_parts() {
+            writeln!(self.inner,
+                     "{}\t{}\texonic_part\t{}\t{}\t.\t{}\t.\t\
+                      transcripts \"{}\"; exonic_part_number \"{:03}\"; gene_id \"{}\"",
+                     gene.seq_name(), DEXSEQ_SOURCE, part.start + 1, part.end, strand_char,
+                     part.transcript_ids.join("+"), part.number, gid)
+                .map_err(|e| ::Error::from(DexSeqError::from(e)))?;
+        }
+        Ok(())
+    }
+
+    /// Flushes the underlying writer.
+    pub fn flush(&mut self) -> ::Result<()> {
+        self.inner.flush().map_err(|e| ::Error::from(DexSeqError::from(e)))
+    }
+}
+
+impl Writer<fs::File> {
+
+    /// Creates a DEXSeq flattened annotation writer that writes to the given path.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let f = fs::File::create(path)?;
+        Ok(Writer::from_writer(f))
+    }
+}
+
+impl Writer<Vec<u8>> {
+
+    /// Creates a DEXSeq flattened annotation writer that writes to an in-memory buffer.
+    ///
+    /// The initial capacity of the buffer is 64 KiB.
+    pub fn from_memory() -> Writer<Vec<u8>> {
+        Writer::from_writer(Vec::with_capacity(1024 * 64))
+    }
+
+    /// Returns the values of the in-memory buffer as a string.
+    pub fn as_string(&self) -> &str {
+        str::from_utf8(&self.inner).expect("valid UTF-8 flattened annotation")
+    }
+}
@@ -0,0 +1,430 @@
+/*! Comparison of two versions of a gene annotation.
+
+`AnnotationDiff` matches the genes of an old and a new annotation by their identifiers, and
+their transcripts by their identifiers within the matched genes. Each gene and transcript is
+reported as added, removed, changed, or unchanged, with changed features classified by their
+structural changes, such as gained exons or a changed coding region. Transcripts that move to a
+gene with another identifier are reported as removed from one gene and added to the other.
+
+The non-unchanged features of a diff can be written as TSV rows with `DiffWriter`. With the
+`serde_impl` feature enabled, the diff types also implement serde's `Serialize` and
+`Deserialize` traits.
+*/
+use std::collections::HashSet;
+use std::error::Error;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
+use itertools::Itertools;
+use linked_hash_map::LinkedHashMap;
+
+use {Exon, Gene, Transcript};
+use model::strand_ord;
+
+
+quick_error! {
+    /// Errors that occur when comparing annotations or writing their differences.
+    #[derive(Debug)]
+    pub enum DiffError {
+        /// Occurs when a compared gene has no identifier.
+        MissingGeneId(seq_name: String, start: u64, end: u64) {
+            description("gene has no identifier")
+            display(self_) -> ("{} at {}:{}-{}", self_.description(), seq_name, start, end)
+        }
+        /// Occurs when a gene identifier occurs more than once in an annotation.
+        DuplicateGeneId(id: String) {
+            description("duplicate gene identifier")
+            display(self_) -> ("{}: '{}'", self_.description(), id)
+        }
+        /// Errors propagated from writing the underlying stream.
+        Io(err: io::Error) {
+            description(err.description())
+            from()
+            cause(err)
+        }
+    }
+}
+
+/// Status of a feature in the new annotation relative to the old one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde_impl", derive(Serialize, Deserialize))]
+pub enum DiffStatus {
+    /// The feature occurs only in the new annotation.
+    Added,
+    /// The feature occurs only in the old annotation.
+    Removed,
+    /// The feature occurs in both annotations, with differences.
+    Changed,
+    /// The feature occurs in both annotations, without differences.
+    Unchanged,
+}
+
+impl DiffStatus {
+
+    /// Returns the name of the status as written in reports.
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            DiffStatus::Added => "added",
+            DiffStatus::Removed => "removed",
+            DiffStatus::Changed => "changed",
+            DiffStatus::Unchanged => "unchanged",
+        }
+    }
+}
+
+/// Structural changes of a feature between two annotations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde_impl", derive(Serialize, Deserialize))]
+pub enum StructuralChange {
+    /// The feature moved to another sequence or strand.
+    LocationChanged,
+    /// The start or the end of the feature moved.
+    BoundaryMoved,
+    /// The transcript has exons that do not overlap any of its old exons.
+    ExonGained,
+    /// The transcript lost exons that do not overlap any of its new exons.
+    ExonLost,
+    /// The transcript has exons that overlap old exons with other coordinates.
+    ExonBoundaryMoved,
+    /// The coding region of the transcript changed, including its gain or loss.
+    CdsChanged,
+    /// The biotype of the feature changed.
+    BiotypeChanged,
+}
+
+impl StructuralChange {
+
+    /// Returns the name of the change as written in reports.
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            StructuralChange::LocationChanged => "location_changed",
+            StructuralChange::BoundaryMoved => "boundary_moved",
+            StructuralChange::ExonGained => "exon_gained",
+            StructuralChange::ExonLost => "exon_lost",
+            StructuralChange::ExonBoundaryMoved => "exon_boundary_moved",
+            StructuralChange::CdsChanged => "cds_changed",
+            StructuralChange::BiotypeChanged => "biotype_changed",
+        }
+    }
+}
+
+/// Differences of a transcript between two annotations.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde_impl", derive(Serialize, Deserialize))]
+pub struct TranscriptDiff {
+    /// Identifier of the transcript.
+    pub transcript_id: String,
+    /// Identifier of the gene of the transcript.
+    pub gene_id: String,
+    /// Status of the transcript.
+    pub status: DiffStatus,
+    /// Structural changes of the transcript, empty unless it is changed.
+    pub changes: Vec<StructuralChange>,
+}
+
+/// Differences of a gene and its transcripts between two annotations.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde_impl", derive(Serialize, Deserialize))]
+pub struct GeneDiff {
+    /// Identifier of the gene.
+    pub gene_id: String,
+    /// Status of the gene.
+    ///
+    /// Genes are changed if they have structural changes or if any of their transcripts is not
+    /// unchanged.
+    pub status: DiffStatus,
+    /// Structural changes of the gene itself.
+    pub changes: Vec<StructuralChange>,
+    /// Differences of the transcripts of the gene, old transcripts first.
+    pub transcripts: Vec<TranscriptDiff>,
+}
+
+/// Options of annotation comparisons.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DiffOptions {
+    ignore_id_versions: bool,
+}
+
+impl DiffOptions {
+
+    /// Creates options that match features by their full identifiers.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets whether features are matched by their identifiers without version suffixes, so
+    /// that, e.g., `ENST00000367770.8` and `ENST00000367770.9` are the same transcript.
+    ///
+    /// The identifiers of the differences are then reported without their versions.
+    pub fn ignore_id_versions(&mut self, ignore_id_versions: bool) -> &mut Self {
+        self.ignore_id_versions = ignore_id_versions;
+        self
+    }
+}
+
+/// Differences between two annotations.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde_impl", derive(Serialize, Deserialize))]
+pub struct AnnotationDiff {
+    genes: Vec<GeneDiff>,
+}
+
+impl AnnotationDiff {
+
+    /// Compares the given old and new genes.
+    ///
+    /// All new genes are kept in memory while the old genes are compared one at a time. The
+    /// differences of old genes come first, in their input order, followed by the added genes.
+    pub fn from_genes<I, J>(old_genes: I, new_genes: J) -> ::Result<Self>
+        where I: IntoIterator<Item=::Result<Gene>>, J: IntoIterator<Item=::Result<Gene>>
+    {
+        AnnotationDiff::from_genes_with(old_genes, new_genes, &DiffOptions::default())
+    }
+
+    /// Compares the given old and new genes using the given options.
+    pub fn from_genes_with<I, J>(old_genes: I, new_genes: J, opts: &DiffOptions)
+        -> ::Result<Self>
+        where I: IntoIterator<Item=::Result<Gene>>, J: IntoIterator<Item=::Result<Gene>>
+    {
+        let prepare = |result: ::Result<Gene>| result.map(|mut gene| {
+            if opts.ignore_id_versions {
+                gene.strip_id_versions();
+            }
+            gene
+        });
+
+        let mut news = LinkedHashMap::new();
+        for gene in new_genes {
+            let gene = prepare(gene)?;
+            let id = gene_id(&gene)?;
+            if news.insert(id.clone(), gene).is_some() {
+                return Err(::Error::from(DiffError::DuplicateGeneId(id)));
+            }
+        }
+
+        let mut genes = Vec::new();
+        let mut seen = HashSet::new();
+        for gene in old_genes {
+            let gene = prepare(gene)?;
+            let id = gene_id(&gene)?;
+            if !seen.insert(id.clone()) {
+                return Err(::Error::from(DiffError::DuplicateGeneId(id)));
+            }
+            match news.remove(&id) {
+                Some(new_gene) => genes.push(diff_gene(id, &gene, &new_gene)),
+                None => genes.push(single_gene_diff(id, &gene, DiffStatus::Removed)),
+            }
+        }
+        for (id, gene) in news {
+            genes.push(single_gene_diff(id, &gene, DiffStatus::Added));
+        }
+
+        Ok(AnnotationDiff { genes: genes })
+    }
+
+    /// Returns the differences of all compared genes.
+    pub fn genes(&self) -> &[GeneDiff] {
+        &self.genes
+    }
+
+    /// Returns an iterator over the differences of all compared transcripts.
+    pub fn transcripts<'a>(&'a self) -> Box<Iterator<Item=&'a TranscriptDiff> + 'a> {
+        Box::new(self.genes.iter().flat_map(|gd| gd.transcripts.iter()))
+    }
+
+    /// Returns the number of genes with the given status.
+    pub fn num_genes(&self, status: DiffStatus) -> usize {
+        self.genes.iter().filter(|gd| gd.status == status).count()
+    }
+
+    /// Returns the number of transcripts with the given status.
+    pub fn num_transcripts(&self, status: DiffStatus) -> usize {
+        self.transcripts().filter(|td| td.status == status).count()
+    }
+
+    /// Returns the number of changed transcripts with the given structural change.
+    pub fn num_transcript_changes(&self, change: StructuralChange) -> usize {
+        self.transcripts().filter(|td| td.changes.contains(&change)).count()
+    }
+}
+
+/// TSV writer of annotation differences.
+pub struct DiffWriter<W: Write> {
+    inner: W,
+}
+
+impl<W: Write> DiffWriter<W> {
+
+    /// Creates a diff writer from another writer.
+    pub fn from_writer(in_writer: W) -> DiffWriter<W> {
+        DiffWriter { inner: in_writer }
+    }
+
+    /// Writes the header line.
+    pub fn write_header(&mut self) -> ::Result<()> {
+        writeln!(self.inner, "feature\tid\tgene_id\tstatus\tchanges")
+            .map_err(|e| ::Error::from(DiffError::from(e)))
+    }
+
+    /// Writes the rows of the given gene and of its transcripts, skipping unchanged ones.
+    pub fn write_gene_diff(&mut self, diff: &GeneDiff) -> ::Result<()> {
+        if diff.status != DiffStatus::Unchanged {
+            self.write_row("gene", &diff.gene_id, &diff.gene_id, diff.status, &diff.changes)?;
+        }
+        for td in diff.transcripts.iter().filter(|td| td.status != DiffStatus::Unchanged) {
+            self.write_row("transcript", &td.transcript_id, &td.gene_id, td.status,
+                           &td.changes)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the header line and the rows of all genes and transcripts of the given diff,
+    /// skipping unchanged ones.
+    pub fn write_diff(&mut self, diff: &AnnotationDiff) -> ::Result<()> {
+        self.write_header()?;
+        for gd in diff.genes() {
+            self.write_gene_diff(gd)?;
+        }
+        Ok(())
+    }
+
+    /// Flushes the underlying writer.
+    pub fn flush(&mut self) -> ::Result<()> {
+        self.inner.flush().map_err(|e| ::Error::from(DiffError::from(e)))
+    }
+
+    fn write_row(&mut self, feature: &str, id: &str, gene_id: &str, status: DiffStatus,
+                 changes: &[StructuralChange]) -> ::Result<()>
+    {
+        let changes = if changes.is_empty() {
+            ".".to_owned()
+        } else {
+            changes.iter().map(|c| c.as_str()).join(",")
+        };
+        writeln!(self.inner, "{}\t{}\t{}\t{}\t{}", feature, id, gene_id, status.as_str(), changes)
+            .map_err(|e| ::Error::from(DiffError::from(e)))
+    }
+}
+
+impl DiffWriter<fs::File> {
+
+    /// Creates a diff writer that writes to the given path.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let f = fs::File::create(path)?;
+        Ok(DiffWriter::from_writer(f))
+    }
+}
+
+fn gene_id(gene: &Gene) -> ::Result<String> {
+    gene.id()
+        .map(|id| id.to_owned())
+        .ok_or_else(|| {
+            let err = DiffError::MissingGeneId(gene.seq_name().to_owned(), gene.start(),
+                                               gene.end());
+            ::Error::from(err)
+        })
+}
+
+/// Creates the diff of a gene that occurs in only one of the annotations.
+fn single_gene_diff(id: String, gene: &Gene, status: DiffStatus) -> GeneDiff {
+    let transcripts = gene.transcripts().keys()
+        .map(|tid| TranscriptDiff {
+            transcript_id: tid.clone(),
+            gene_id: id.clone(),
+            status: status,
+            changes: vec![],
+        })
+        .collect();
+    GeneDiff { gene_id: id, status: status, changes: vec![], transcripts: transcripts }
+}
+
+fn diff_gene(id: String, old: &Gene, new: &Gene) -> GeneDiff {
+    let mut changes = vec![];
+    if old.seq_name() != new.seq_name() || strand_ord(old.strand()) != strand_ord(new.strand()) {
+        changes.push(StructuralChange::LocationChanged);
+    } else if old.start() != new.start() || old.end() != new.end() {
+        changes.push(StructuralChange::BoundaryMoved);
+    }
+    if old.biotype() != new.biotype() {
+        changes.push(StructuralChange::BiotypeChanged);
+    }
+
+    let mut transcripts = vec![];
+    for (tid, old_trx) in old.transcripts().iter() {
+        let (status, trx_changes) = match new.transcripts().get(tid) {
+            Some(new_trx) => {
+                let trx_changes = diff_transcript(old_trx, new_trx);
+                if trx_changes.is_empty() {
+                    (DiffStatus::Unchanged, trx_changes)
+                } else {
+                    (DiffStatus::Changed, trx_changes)
+                }
+            },
+            None => (DiffStatus::Removed, vec![]),
+        };
+        transcripts.push(TranscriptDiff {
+            transcript_id: tid.clone(),
+            gene_id: id.clone(),
+            status: status,
+            changes: trx_changes,
+        });
+    }
+    for tid in new.transcripts().keys().filter(|tid| !old.transcripts().contains_key(*tid)) {
+        transcripts.push(TranscriptDiff {
+            transcript_id: tid.clone(),
+            gene_id: id.clone(),
+            status: DiffStatus::Added,
+            changes: vec![],
+        });
+    }
+
+    let status =
+        if changes.is_empty() && transcripts.iter().all(|td| td.status == DiffStatus::Unchanged) {
+            DiffStatus::Unchanged
+        } else {
+            DiffStatus::Changed
+        };
+    GeneDiff { gene_id: id, status: status, changes: changes, transcripts: transcripts }
+}
+
+fn diff_transcript(old: &Transcript, new: &Transcript) -> Vec<StructuralChange> {
+    let mut changes = vec![];
+    if old.seq_name() != new.seq_name() || strand_ord(old.strand()) != strand_ord(new.strand()) {
+        changes.push(StructuralChange::LocationChanged);
+        return changes;
+    }
+    if old.start() != new.start() || old.end() != new.end() {
+        changes.push(StructuralChange::BoundaryMoved);
+    }
+    if new.exons().iter().any(|exn| !overlaps_any(exn, old.exons())) {
+        changes.push(StructuralChange::ExonGained);
+    }
+    if old.exons().iter().any(|exn| !overlaps_any(exn, new.exons())) {
+        changes.push(StructuralChange::ExonLost);
+    }
+    if new.exons().iter().any(|exn| moved_within(exn, old.exons())) {
+        changes.push(StructuralChange::ExonBoundaryMoved);
+    }
+    if old.coding_segments(true) != new.coding_segments(true) {
+        changes.push(StructuralChange::CdsChanged);
+    }
+    if old.biotype() != new.biotype() {
+        changes.push(StructuralChange::BiotypeChanged);
+    }
+    changes
+}
+
+fn overlaps_any(exon: &Exon, exons: &[Exon]) -> bool {
+    exons.iter().any(|other| exon.start() < other.end() && other.start() < exon.end())
+}
+
+/// Returns whether the given exon overlaps any of the given exons without having the same
+/// coordinates as all of them.
+fn moved_within(exon: &Exon, exons: &[Exon]) -> bool {
+    let mut overlapping = exons.iter()
+        .filter(|other| exon.start() < other.end() && other.start() < exon.end())
+        .peekable();
+    overlapping.peek().is_some()
+        && overlapping.any(|other| other.start() != exon.start() || other.end() != exon.end())
+}
@@ -0,0 +1,434 @@
+/*! External merge sort of annotation records.
+
+Inputs that do not fit in memory can be sorted by reading them in chunks, sorting each chunk in
+memory, and spilling it to a temporary file. The sorted chunks are then merged back into a single
+stream of records, so that only one record per chunk is kept in memory at any time.
+
+Records are sorted by their sequence names, using one of the orderings of `SeqOrder`, and then by
+their start and end coordinates. Records with equal keys keep their input order.
+*/
+use std::cmp::Ordering;
+use std::env;
+use std::error::Error;
+use std::fs::{self, OpenOptions};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering, ATOMIC_USIZE_INIT};
+use std::time::{SystemTime, UNIX_EPOCH};
+use std::vec;
+
+use itertools::Itertools;
+
+use {RefFlatRecord, SeqOrder};
+
+
+/// Default number of records held in memory before they are spilled to a temporary file.
+const DEF_CHUNK_SIZE: usize = 100_000;
+
+/// Counter for creating unique temporary file names within the process.
+static SPILL_COUNTER: AtomicUsize = ATOMIC_USIZE_INIT;
+
+quick_error! {
+    /// Errors that occur when sorting records externally.
+    #[derive(Debug)]
+    pub enum ExtSortError {
+        /// Occurs when a record line can not be parsed.
+        InvalidLine(line: String) {
+            description("invalid record line")
+            display(self_) -> ("{}: '{}'", self_.description(), line)
+        }
+        /// Errors propagated from reading or writing the temporary files.
+        Io(err: io::Error) {
+            description(err.description())
+            from()
+            cause(err)
+        }
+    }
+}
+
+/// Records that can be sorted externally.
+///
+/// Records are spilled to temporary files as single lines of text, which must not contain any
+/// newline characters.
+pub trait SortRecord: Sized {
+
+    /// Returns the sequence name of the record.
+    fn seq_name(&self) -> &str;
+
+    /// Returns the start coordinate of the record.
+    fn start(&self) -> u64;
+
+    /// Returns the end coordinate of the record.
+    fn end(&self) -> u64;
+
+    /// Returns the record as a single line of text, without the trailing newline.
+    fn to_line(&self) -> String;
+
+    /// Parses a record from a line created by `to_line`.
+    fn from_line(line: &str) -> ::Result<Self>;
+}
+
+impl SortRecord for RefFlatRecord {
+
+    fn seq_name(&self) -> &str {
+        self.seq_name()
+    }
+
+    fn start(&self) -> u64 {
+        self.transcript_start()
+    }
+
+    fn end(&self) -> u64 {
+        self.transcript_end()
+    }
+
+    fn to_line(&self) -> String {
+        let mut exon_starts = self.exon_starts().iter().join(",");
+        exon_starts.push(',');
+        let mut exon_ends = self.exon_ends().iter().join(",");
+        exon_ends.push(',');
+        format!("{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+                self.gene_id(), self.transcript_id(), self.seq_name(), self.strand(),
+                self.transcript_start(), self.transcript_end(),
+                self.coding_start(), self.coding_end(), self.num_exons(),
+                exon_starts, exon_ends)
+    }
+
+    fn from_line(line: &str) -> ::Result<Self> {
+        let invalid = || ::Error::from(ExtSortError::InvalidLine(line.to_owned()));
+        let cols = line.split('\t').collect::<Vec<&str>>();
+        if cols.len() != 11 {
+            return Err(invalid());
+        }
+        let coord = |raw: &str| u64::from_str(raw).map_err(|_| invalid());
+        let strand = cols[3].chars().next().ok_or_else(&invalid)?;
+        let num_exons = usize::from_str(cols[8]).map_err(|_| invalid())?;
+        RefFlatRecord::try_from_row(
+            (cols[0].to_owned(), cols[1].to_owned(), cols[2].to_owned(), strand,
+             coord(cols[4])?, coord(cols[5])?, coord(cols[6])?, coord(cols[7])?, num_exons,
+             cols[9].to_owned(), cols[10].to_owned()))
+    }
+}
+
+/// Raw GFF line, along with the values used for sorting it.
+///
+/// The line is kept as-is, so that sorted lines can be written out directly or read by a
+/// `GffReader`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GffLine {
+    line: String,
+    seq_name: String,
+    start: u64,
+    end: u64,
+}
+
+impl GffLine {
+
+    /// Parses the given GFF line.
+    ///
+    /// Only the sequence name and coordinate columns are parsed. The coordinates are converted
+    /// to be zero-based and half-open.
+    pub fn parse<T>(line: T) -> ::Result<Self>
+        where T: Into<String>
+    {
+        let line = line.into();
+        let (seq_name, start, end) = {
+            let cols = line.split('\t').collect::<Vec<&str>>();
+            let coord = |idx: usize| cols.get(idx).and_then(|raw| u64::from_str(raw).ok());
+            match (cols.len(), coord(3), coord(4)) {
+                (9, Some(start), Some(end)) if start > 0 => (cols[0].to_owned(), start - 1, end),
+                _ => return Err(::Error::from(ExtSortError::InvalidLine(line.clone()))),
+            }
+        };
+        Ok(GffLine {
+            line: line,
+            seq_name: seq_name,
+            start: start,
+            end: end,
+        })
+    }
+
+    /// Returns the line, without the trailing newline.
+    pub fn as_str(&self) -> &str {
+        self.line.as_str()
+    }
+
+    /// Consumes the record, returning the line without the trailing newline.
+    pub fn into_string(self) -> String {
+        self.line
+    }
+}
+
+impl SortRecord for GffLine {
+
+    fn seq_name(&self) -> &str {
+        self.seq_name.as_str()
+    }
+
+    fn start(&self) -> u64 {
+        self.start
+    }
+
+    fn end(&self) -> u64 {
+        self.end
+    }
+
+    fn to_line(&self) -> String {
+        self.line.clone()
+    }
+
+    fn from_line(line: &str) -> ::Result<Self> {
+        GffLine::parse(line)
+    }
+}
+
+/// Reads the lines of a GFF file as `GffLine`s.
+///
+/// Empty lines, comments, and directives are skipped. Reading stops at the start of the
+/// `##FASTA` section of GFF3 files.
+pub fn gff_lines<R: BufRead>(in_reader: R) -> GffLines<R> {
+    GffLines { inner: in_reader.lines(), done: false }
+}
+
+/// Iterator over the lines of a GFF file.
+pub struct GffLines<R: BufRead> {
+    inner: io::Lines<R>,
+    done: bool,
+}
+
+impl<R: BufRead> Iterator for GffLines<R> {
+
+    type Item = ::Result<GffLine>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        loop {
+            let line = match self.inner.next() {
+                Some(Ok(line)) => line,
+                Some(Err(err)) => return Some(Err(::Error::from(ExtSortError::from(err)))),
+                None => return None,
+            };
+            if line.starts_with("##FASTA") || line.starts_with('>') {
+                self.done = true;
+                return None;
+            }
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            return Some(GffLine::parse(line));
+        }
+    }
+}
+
+/// External merge sorter of records.
+#[derive(Debug, Clone)]
+pub struct ExternalSorter {
+    order: SeqOrder,
+    chunk_size: usize,
+    tmp_dir: PathBuf,
+}
+
+impl ExternalSorter {
+
+    /// Creates a sorter with the default sequence ordering and chunk size, which spills to the
+    /// system temporary directory.
+    pub fn new() -> Self {
+        ExternalSorter {
+            order: SeqOrder::default(),
+            chunk_size: DEF_CHUNK_SIZE,
+            tmp_dir: env::temp_dir(),
+        }
+    }
+
+    /// Sets the ordering of sequence names.
+    pub fn seq_order(&mut self, order: SeqOrder) -> &mut Self {
+        self.order = order;
+        self
+    }
+
+    /// Sets the maximum number of records held in memory before they are spilled.
+    ///
+    /// Chunk sizes of zero are treated as one.
+    pub fn chunk_size(&mut self, chunk_size: usize) -> &mut Self {
+        self.chunk_size = if chunk_size == 0 { 1 } else { chunk_size };
+        self
+    }
+
+    /// Sets the directory in which temporary files are created.
+    pub fn tmp_dir<T>(&mut self, tmp_dir: T) -> &mut Self
+        where T: Into<PathBuf>
+    {
+        self.tmp_dir = tmp_dir.into();
+        self
+    }
+
+    /// Sorts the given records.
+    ///
+    /// If all records fit in a single chunk, they are sorted in memory without creating any
+    /// temporary files. The first error of the input, if any, is returned instead.
+    pub fn sort<T, I>(&self, records: I) -> ::Result<SortedRecords<T>>
+        where T: SortRecord, I: IntoIterator<Item=::Result<T>>
+    {
+        let mut sorted = SortedRecords {
+            order: self.order.clone(),
+            memory: Vec::new().into_iter(),
+            chunks: Vec::new(),
+            paths: Vec::new(),
+        };
+        let mut buf = Vec::with_capacity(self.chunk_size);
+        for record in records {
+            buf.push(record?);
+            if buf.len() >= self.chunk_size {
+                self.spill(&mut buf, &mut sorted)?;
+            }
+        }
+        if sorted.paths.is_empty() {
+            sort_records(&mut buf, &self.order);
+            sorted.memory = buf.into_iter();
+        } else if !buf.is_empty() {
+            self.spill(&mut buf, &mut sorted)?;
+        }
+        Ok(sorted)
+    }
+
+    /// Sorts the given records and writes them to a temporary file, which is added to the
+    /// chunks to merge.
+    fn spill<T: SortRecord>(&self, buf: &mut Vec<T>, sorted: &mut SortedRecords<T>)
+        -> ::Result<()>
+    {
+        sort_records(buf, &self.order);
+        let (path, file) = create_spill_file(&self.tmp_dir)
+            .map_err(|e| ::Error::from(ExtSortError::from(e)))?;
+        sorted.paths.push(path.clone());
+        {
+            let mut writer = BufWriter::new(file);
+            for record in buf.drain(..) {
+                writer.write_all(record.to_line().as_bytes())
+                    .and_then(|_| writer.write_all(b"\n"))
+                    .map_err(|e| ::Error::from(ExtSortError::from(e)))?;
+            }
+            writer.flush().map_err(|e| ::Error::from(ExtSortError::from(e)))?;
+        }
+        let reader = fs::File::open(&path)
+            .map_err(|e| ::Error::from(ExtSortError::from(e)))?;
+        let mut chunk = Chunk { lines: BufReader::new(reader).lines(), head: None };
+        chunk.advance()?;
+        sorted.chunks.push(chunk);
+        Ok(())
+    }
+}
+
+impl Default for ExternalSorter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Sorted chunk of records spilled to a temporary file.
+struct Chunk<T> {
+    lines: io::Lines<BufReader<fs::File>>,
+    head: Option<T>,
+}
+
+impl<T: SortRecord> Chunk<T> {
+
+    /// Reads the next record of the chunk, returning the previous one.
+    fn advance(&mut self) -> ::Result<Option<T>> {
+        let next = match self.lines.next() {
+            Some(line) => {
+                let line = line.map_err(|e| ::Error::from(ExtSortError::from(e)))?;
+                Some(T::from_line(&line)?)
+            },
+            None => None,
+        };
+        Ok(mem::replace(&mut self.head, next))
+    }
+}
+
+/// Iterator over sorted records.
+///
+/// The temporary files are removed when the iterator is dropped.
+pub struct SortedRecords<T> {
+    order: SeqOrder,
+    memory: vec::IntoIter<T>,
+    chunks: Vec<Chunk<T>>,
+    paths: Vec<PathBuf>,
+}
+
+impl<T> SortedRecords<T> {
+
+    /// Returns the number of temporary files used for sorting.
+    pub fn num_spills(&self) -> usize {
+        self.paths.len()
+    }
+}
+
+impl<T: SortRecord> Iterator for SortedRecords<T> {
+
+    type Item = ::Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.chunks.is_empty() {
+            return self.memory.next().map(Ok);
+        }
+        let mut min_idx: Option<usize> = None;
+        for (idx, chunk) in self.chunks.iter().enumerate() {
+            let head = match chunk.head {
+                Some(ref rec) => rec,
+                None => continue,
+            };
+            min_idx = match min_idx {
+                Some(cur) => {
+                    let cur_head = self.chunks[cur].head.as_ref().unwrap();
+                    if compare_records(head, cur_head, &self.order) == Ordering::Less {
+                        Some(idx)
+                    } else {
+                        Some(cur)
+                    }
+                },
+                None => Some(idx),
+            };
+        }
+        min_idx.map(|idx| self.chunks[idx].advance().map(|rec| rec.unwrap()))
+    }
+}
+
+impl<T> Drop for SortedRecords<T> {
+    fn drop(&mut self) {
+        self.chunks.clear();
+        for path in self.paths.iter() {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+/// Compares the given records by their sequence names and coordinates.
+fn compare_records<T: SortRecord>(a: &T, b: &T, order: &SeqOrder) -> Ordering {
+    order.compare(a.seq_name(), b.seq_name())
+        .then_with(|| (a.start(), a.end()).cmp(&(b.start(), b.end())))
+}
+
+/// Sorts the given records in memory.
+fn sort_records<T: SortRecord>(records: &mut [T], order: &SeqOrder) {
+    records.sort_by(|a, b| compare_records(a, b, order))
+}
+
+/// Creates a new, uniquely-named temporary file in the given directory.
+pub(crate) fn create_spill_file(tmp_dir: &Path) -> io::Result<(PathBuf, fs::File)> {
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or(0);
+    loop {
+        let count = SPILL_COUNTER.fetch_add(1, AtomicOrdering::SeqCst);
+        let path = tmp_dir.join(format!("gte-extsort-{}-{}.tmp", nanos, count));
+        match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(file) => return Ok((path, file)),
+            Err(ref err) if err.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(err) => return Err(err),
+        }
+    }
+}
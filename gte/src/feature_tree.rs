@@ -0,0 +1,329 @@
+/*! Generic trees of GFF3 features.
+
+GFF3 files link their features through `ID` and `Parent` attributes, forming hierarchies that
+may be arbitrarily deep and may contain feature types other than genes, transcripts, and exons,
+such as pseudogenes with nested parts, regulatory regions, or miRNAs nested within their primary
+transcripts. A `FeatureTree` keeps this hierarchy as-is, and its nodes can be converted into the
+`Gene` and `Transcript` models where they have the required exon or CDS children.
+
+*/
+use std::cmp::{max, min};
+use std::collections::HashMap;
+
+use bio::utils::Interval;
+use linked_hash_map::LinkedHashMap;
+
+use {Attributes, Coord, GBuilder, Gene, GffError, Strand, TBuilder, Transcript, DEF_ID};
+use ops::merged_coords;
+
+
+/// Feature type of exons.
+const EXON_STR: &'static str = "exon";
+
+/// Feature type of coding regions.
+const CDS_STR: &'static str = "CDS";
+
+/// Feature type of start codons.
+const START_CODON_STR: &'static str = "start_codon";
+
+/// Feature type of stop codons.
+const STOP_CODON_STR: &'static str = "stop_codon";
+
+/// Feature of a GFF3 file, along with all of its child features.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeatureNode {
+    seq_name: String,
+    start: u64,
+    end: u64,
+    strand: Strand,
+    feature_type: String,
+    id: Option<String>,
+    source: Option<String>,
+    score: Option<f64>,
+    frame: Option<u8>,
+    attributes: Attributes,
+    children: Vec<FeatureNode>,
+}
+
+impl FeatureNode {
+
+    /// Creates a node without children from the values of a GFF3 row.
+    ///
+    /// Coordinates are zero-based and half-open, and the attributes exclude the `ID` and
+    /// `Parent` keys.
+    pub(crate) fn new(
+        seq_name: String,
+        coord: Coord<u64>,
+        strand: Strand,
+        feature_type: String,
+        id: Option<String>,
+        source: Option<String>,
+        score: Option<f64>,
+        frame: Option<u8>,
+        attributes: Attributes,
+    ) -> FeatureNode {
+        FeatureNode {
+            seq_name: seq_name,
+            start: coord.0,
+            end: coord.1,
+            strand: strand,
+            feature_type: feature_type,
+            id: id,
+            source: source,
+            score: score,
+            frame: frame,
+            attributes: attributes,
+            children: Vec::new(),
+        }
+    }
+
+    /// Returns the sequence name.
+    pub fn seq_name(&self) -> &str {
+        self.seq_name.as_str()
+    }
+
+    /// Returns the 0-based start coordinate.
+    pub fn start(&self) -> u64 {
+        self.start
+    }
+
+    /// Returns the 0-based, exclusive end coordinate.
+    pub fn end(&self) -> u64 {
+        self.end
+    }
+
+    /// Returns the strand.
+    pub fn strand(&self) -> &Strand {
+        &self.strand
+    }
+
+    /// Returns the feature type, as in the third column of its row.
+    pub fn feature_type(&self) -> &str {
+        self.feature_type.as_str()
+    }
+
+    /// Returns the identifier, taken from the `ID` attribute.
+    pub fn id(&self) -> Option<&str> {
+        self.id.as_ref().map(|v| v.as_str())
+    }
+
+    /// Returns the source, if known.
+    pub fn source(&self) -> Option<&str> {
+        self.source.as_ref().map(|v| v.as_str())
+    }
+
+    /// Returns the score, if known.
+    pub fn score(&self) -> Option<f64> {
+        self.score
+    }
+
+    /// Returns the frame, if known.
+    pub fn frame(&self) -> Option<u8> {
+        self.frame
+    }
+
+    /// Returns the attributes, excluding the `ID` and `Parent` keys.
+    pub fn attributes(&self) -> &Attributes {
+        &self.attributes
+    }
+
+    /// Returns the child features, in the order of their rows.
+    pub fn children(&self) -> &[FeatureNode] {
+        self.children.as_slice()
+    }
+
+    /// Returns the first node with the given identifier among this node and its descendants,
+    /// searched depth-first.
+    pub fn find(&self, id: &str) -> Option<&FeatureNode> {
+        if self.id() == Some(id) {
+            return Some(self);
+        }
+        self.children.iter().filter_map(|child| child.find(id)).next()
+    }
+
+    /// Returns whether the node can be converted into a transcript, that is whether it has
+    /// any exon or CDS children.
+    pub fn is_transcript(&self) -> bool {
+        self.children.iter()
+            .any(|child| child.feature_type == EXON_STR || child.feature_type == CDS_STR)
+    }
+
+    /// Returns whether the node can be converted into a gene, that is whether it or any of its
+    /// children can be converted into a transcript.
+    pub fn is_gene(&self) -> bool {
+        self.is_transcript() || self.children.iter().any(|child| child.is_transcript())
+    }
+
+    /// Converts the node into a transcript, using its exon, CDS, and codon children.
+    ///
+    /// Nodes without exon children take their exons from their CDS children. The coding
+    /// region spans all CDS and codon children. If there are no stop codon children, the
+    /// coding region includes the stop codon only if `cds_incl_stop` is set, as in RefSeq GFF3
+    /// files. The attributes, source, and score of the node are kept.
+    pub fn to_transcript(&self, gene_id: Option<&str>, cds_incl_stop: bool)
+        -> ::Result<Transcript>
+    {
+        let children_coords = |types: &[&str]| -> Vec<Coord<u64>> {
+            merged_coords(self.children.iter()
+                .filter(|child| types.contains(&child.feature_type.as_str()))
+                .map(|child| Interval::new(child.start..child.end).unwrap()))
+        };
+        let mut exon_coords = children_coords(&[EXON_STR]);
+        if exon_coords.is_empty() {
+            exon_coords = children_coords(&[CDS_STR]);
+        }
+        let coding_coord = children_coords(&[CDS_STR, START_CODON_STR, STOP_CODON_STR]).iter()
+            .fold(None, |acc, &(start, end)| match acc {
+                Some((a, b)) => Some((min(a, start), max(b, end))),
+                None => Some((start, end)),
+            });
+        let has_stop = self.children.iter().any(|child| child.feature_type == STOP_CODON_STR);
+
+        let mut builder = TBuilder::new(self.seq_name.clone(), self.start, self.end)
+            .strand(self.strand)
+            .attributes(self.attributes.clone())
+            .coords(exon_coords, coding_coord)
+            .coding_incl_stop(has_stop || cds_incl_stop);
+        if let Some(ref id) = self.id {
+            builder = builder.id(id.clone());
+        }
+        if let Some(gid) = gene_id {
+            builder = builder.gene_id(gid);
+        }
+        if let Some(ref source) = self.source {
+            builder = builder.source(source.clone());
+        }
+        if let Some(score) = self.score {
+            builder = builder.score(score);
+        }
+        builder.build()
+    }
+
+    /// Converts the node into a gene, whose transcripts are created from its children that
+    /// can be converted into transcripts, as done by `to_transcript`.
+    ///
+    /// Nodes with their own exon or CDS children, such as pseudogenes whose exons lie directly
+    /// under their gene rows, are converted into genes with one transcript created from the
+    /// node itself. Deeper descendants, such as miRNAs nested within their primary
+    /// transcripts, are not converted.
+    pub fn to_gene(&self, cds_incl_stop: bool) -> ::Result<Gene> {
+        let mut transcripts = LinkedHashMap::new();
+        if self.is_transcript() {
+            let trx = self.to_transcript(self.id(), cds_incl_stop)?;
+            let _ = transcripts.insert(self.id().unwrap_or(DEF_ID).to_owned(), trx);
+        }
+        for child in self.children.iter().filter(|child| child.is_transcript()) {
+            let trx = child.to_transcript(self.id(), cds_incl_stop)?;
+            let _ = transcripts.insert(child.id().unwrap_or(DEF_ID).to_owned(), trx);
+        }
+
+        let mut builder = GBuilder::new(self.seq_name.clone(), self.start, self.end)
+            .strand(self.strand)
+            .attributes(self.attributes.clone())
+            .transcripts(transcripts)
+            .extend_bounds(true);
+        if let Some(ref id) = self.id {
+            builder = builder.id(id.clone());
+        }
+        if let Some(ref source) = self.source {
+            builder = builder.source(source.clone());
+        }
+        if let Some(score) = self.score {
+            builder = builder.score(score);
+        }
+        builder.build()
+    }
+}
+
+/// Tree of the features of a GFF3 file, linked through their `ID` and `Parent` attributes.
+///
+/// Features whose `Parent` attributes do not refer to any feature of the file are roots of the
+/// tree. Features with multiple parents are included under each of them, and features that
+/// share the same identifier, such as the rows of a discontinuous CDS, are kept as separate
+/// nodes, with children attached to the first of them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeatureTree {
+    roots: Vec<FeatureNode>,
+}
+
+impl FeatureTree {
+
+    /// Creates a tree from the given nodes and the identifiers of their parents, in the order
+    /// of their rows.
+    pub(crate) fn from_nodes(nodes: Vec<(FeatureNode, Vec<String>)>) -> Result<Self, GffError> {
+        let mut index = HashMap::new();
+        for (idx, &(ref node, _)) in nodes.iter().enumerate() {
+            if let Some(ref id) = node.id {
+                let _ = index.entry(id.clone()).or_insert(idx);
+            }
+        }
+
+        let mut children = vec![Vec::new(); nodes.len()];
+        let mut root_idxs = Vec::new();
+        for (idx, &(_, ref parents)) in nodes.iter().enumerate() {
+            let parent_idxs = parents.iter()
+                .filter_map(|parent| index.get(parent))
+                .collect::<Vec<&usize>>();
+            if parent_idxs.is_empty() {
+                root_idxs.push(idx);
+            }
+            for &pidx in parent_idxs {
+                children[pidx].push(idx);
+            }
+        }
+
+        let nodes = nodes.into_iter().map(|(node, _)| node).collect::<Vec<FeatureNode>>();
+        let mut visited = vec![false; nodes.len()];
+        let mut roots = Vec::with_capacity(root_idxs.len());
+        for idx in root_idxs {
+            roots.push(build_node(idx, &nodes, &children, &mut Vec::new(), &mut visited)?);
+        }
+        // features that are only reachable from each other form parent cycles
+        if let Some(idx) = visited.iter().position(|&v| !v) {
+            return Err(GffError::ParentCycle(nodes[idx].id.clone()));
+        }
+        Ok(FeatureTree { roots: roots })
+    }
+
+    /// Returns the root features, in the order of their rows.
+    pub fn roots(&self) -> &[FeatureNode] {
+        self.roots.as_slice()
+    }
+
+    /// Returns the first feature with the given identifier, searched depth-first from the
+    /// roots.
+    pub fn find(&self, id: &str) -> Option<&FeatureNode> {
+        self.roots.iter().filter_map(|root| root.find(id)).next()
+    }
+
+    /// Converts all roots that can be converted into genes, as done by `FeatureNode::to_gene`.
+    pub fn genes(&self, cds_incl_stop: bool) -> Vec<::Result<Gene>> {
+        self.roots.iter()
+            .filter(|root| root.is_gene())
+            .map(|root| root.to_gene(cds_incl_stop))
+            .collect()
+    }
+}
+
+/// Helper function for creating the node at the given index along with its descendants.
+///
+/// The indices of the ancestors of the node are kept in `path` to detect parent cycles.
+fn build_node(
+    idx: usize,
+    nodes: &[FeatureNode],
+    children: &[Vec<usize>],
+    path: &mut Vec<usize>,
+    visited: &mut [bool],
+) -> Result<FeatureNode, GffError> {
+    if path.contains(&idx) {
+        return Err(GffError::ParentCycle(nodes[idx].id.clone()));
+    }
+    visited[idx] = true;
+    path.push(idx);
+    let mut node = nodes[idx].clone();
+    for &cidx in children[idx].iter() {
+        node.children.push(build_node(cidx, nodes, children, path, visited)?);
+    }
+    let _ = path.pop();
+    Ok(node)
+}
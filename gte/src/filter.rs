@@ -0,0 +1,204 @@
+/*! Filtering of genes and transcripts by their attributes and coordinates.
+
+A filter consists of criteria on biotypes, sequence names, genomic regions, and tags. A feature
+passes a criterion if it matches any of its values, and passes the filter if it passes all of
+its criteria, so that a filter without any criteria passes all features. Filters are applied
+lazily to gene and transcript streams.
+*/
+use std::error::Error;
+
+use {Biotype, Gene, Transcript};
+
+
+quick_error! {
+    /// Errors that occur when creating filters.
+    #[derive(Debug)]
+    pub enum FilterError {
+        /// Occurs when a region string can not be parsed.
+        InvalidRegion(region: String) {
+            description("invalid region")
+            display(self_) -> ("{}: '{}'", self_.description(), region)
+        }
+    }
+}
+
+/// Filter of genes and transcripts.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FeatureFilter {
+    biotypes: Vec<Biotype>,
+    seq_names: Vec<String>,
+    regions: Vec<(String, u64, u64)>,
+    tags: Vec<String>,
+}
+
+impl FeatureFilter {
+
+    /// Creates a filter without any criteria, which passes all features.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the given biotype to the biotype criterion.
+    ///
+    /// Genes are matched by their own biotypes and transcripts by theirs.
+    pub fn biotype<B: Into<Biotype>>(&mut self, biotype: B) -> &mut Self {
+        self.biotypes.push(biotype.into());
+        self
+    }
+
+    /// Adds the given sequence name to the sequence name criterion.
+    pub fn seq_name<S: Into<String>>(&mut self, seq_name: S) -> &mut Self {
+        self.seq_names.push(seq_name.into());
+        self
+    }
+
+    /// Adds the given zero-based, half-open region to the region criterion.
+    ///
+    /// Features pass the criterion if they overlap any of its regions.
+    pub fn region<S: Into<String>>(&mut self, seq_name: S, start: u64, end: u64) -> &mut Self {
+        self.regions.push((seq_name.into(), start, end));
+        self
+    }
+
+    /// Adds the given tag, such as `basic` or `CCDS`, to the tag criterion.
+    ///
+    /// Genes pass the criterion if any of their transcripts has any of its tags.
+    pub fn tag<S: Into<String>>(&mut self, tag: S) -> &mut Self {
+        self.tags.push(tag.into());
+        self
+    }
+
+    /// Returns whether the given gene passes the filter.
+    pub fn matches_gene(&self, gene: &Gene) -> bool {
+        self.matches_location(gene.seq_name(), gene.start(), gene.end())
+            && (self.biotypes.is_empty()
+                || gene.biotype().map_or(false, |bt| self.biotypes.contains(&bt)))
+            && (self.tags.is_empty()
+                || gene.transcripts().values().any(|trx| self.matches_tags(trx)))
+    }
+
+    /// Returns whether the given transcript passes the filter.
+    pub fn matches_transcript(&self, transcript: &Transcript) -> bool {
+        self.matches_location(transcript.seq_name(), transcript.start(), transcript.end())
+            && (self.biotypes.is_empty()
+                || transcript.biotype().map_or(false, |bt| self.biotypes.contains(&bt)))
+            && (self.tags.is_empty() || self.matches_tags(transcript))
+    }
+
+    fn matches_location(&self, seq_name: &str, start: u64, end: u64) -> bool {
+        (self.seq_names.is_empty() || self.seq_names.iter().any(|name| name == seq_name))
+            && (self.regions.is_empty()
+                || self.regions.iter().any(|&(ref name, rstart, rend)| {
+                    name == seq_name && start < rend && rstart < end
+                }))
+    }
+
+    fn matches_tags(&self, transcript: &Transcript) -> bool {
+        self.tags.iter().any(|tag| transcript.has_tag(tag))
+    }
+}
+
+/// Parses a region string of the form `seq:start-end`, with one-based, inclusive coordinates
+/// that may contain thousands separators, into a sequence name and zero-based, half-open
+/// coordinates.
+///
+/// A region consisting of only a sequence name spans the whole sequence.
+pub fn parse_region(region: &str) -> ::Result<(String, u64, u64)> {
+    let invalid = || ::Error::from(FilterError::InvalidRegion(region.to_owned()));
+    let (seq_name, range) = match region.rfind(':') {
+        Some(idx) => (&region[..idx], Some(&region[idx + 1..])),
+        None => (region, None),
+    };
+    if seq_name.is_empty() {
+        return Err(invalid());
+    }
+    let range = match range {
+        Some(range) => range,
+        None => return Ok((seq_name.to_owned(), 0, u64::max_value())),
+    };
+    let parse = |value: &str| value.replace(',', "").parse::<u64>().ok();
+    let mut bounds = range.splitn(2, '-');
+    match (bounds.next().and_then(&parse), bounds.next().and_then(&parse)) {
+        (Some(start), Some(end)) if start > 0 && start <= end =>
+            Ok((seq_name.to_owned(), start - 1, end)),
+        _ => Err(invalid()),
+    }
+}
+
+/// Iterator over the genes of a stream that pass a filter.
+///
+/// Errors in the underlying gene stream are passed through as-is.
+pub struct FilteredGenes<I> {
+    genes: I,
+    filter: FeatureFilter,
+}
+
+impl<I> FilteredGenes<I> where I: Iterator<Item=::Result<Gene>> {
+
+    /// Creates an iterator over the given genes that pass the given filter.
+    pub fn new<T>(genes: T, filter: FeatureFilter) -> Self
+        where T: IntoIterator<Item=::Result<Gene>, IntoIter=I>
+    {
+        FilteredGenes {
+            genes: genes.into_iter(),
+            filter: filter,
+        }
+    }
+}
+
+impl<I> Iterator for FilteredGenes<I> where I: Iterator<Item=::Result<Gene>> {
+
+    type Item = ::Result<Gene>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.genes.next() {
+                Some(Ok(gx)) => {
+                    if self.filter.matches_gene(&gx) {
+                        return Some(Ok(gx));
+                    }
+                },
+                other => return other,
+            }
+        }
+    }
+}
+
+/// Iterator over the transcripts of a stream that pass a filter.
+///
+/// Errors in the underlying transcript stream are passed through as-is.
+pub struct FilteredTranscripts<I> {
+    transcripts: I,
+    filter: FeatureFilter,
+}
+
+impl<I> FilteredTranscripts<I> where I: Iterator<Item=::Result<Transcript>> {
+
+    /// Creates an iterator over the given transcripts that pass the given filter.
+    pub fn new<T>(transcripts: T, filter: FeatureFilter) -> Self
+        where T: IntoIterator<Item=::Result<Transcript>, IntoIter=I>
+    {
+        FilteredTranscripts {
+            transcripts: transcripts.into_iter(),
+            filter: filter,
+        }
+    }
+}
+
+impl<I> Iterator for FilteredTranscripts<I> where I: Iterator<Item=::Result<Transcript>> {
+
+    type Item = ::Result<Transcript>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.transcripts.next() {
+                Some(Ok(trx)) => {
+                    if self.filter.matches_transcript(&trx) {
+                        return Some(Ok(trx));
+                    }
+                },
+                other => return other,
+            }
+        }
+    }
+}
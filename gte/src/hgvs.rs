@@ -0,0 +1,133 @@
+/*! HGVS-style notations of positions on transcripts.
+
+Genomic positions are expressed relative to a transcript using the `c.` (coding), `n.`
+(non-coding), and `p.` (protein) notations of the
+[HGVS nomenclature](https://varnomen.hgvs.org/bg-material/numbering/). Only positions are
+formatted, not full variant descriptions.
+
+In the `c.` notation, the first base of the start codon is `c.1`, bases upstream of it are
+numbered `c.-1`, `c.-2`, and so on, and bases downstream of the stop codon are numbered `c.*1`,
+`c.*2`, and so on. Intronic positions are expressed as offsets from the nearest exonic base,
+e.g. `c.88+2` or `c.89-3`, with the middle base of an odd-length intron taking the `+` offset.
+
+*/
+use {CodonTable, SequenceSource, Strand, Transcript};
+
+
+impl Transcript {
+
+    /// Returns the `c.` notation of the given genomic position.
+    ///
+    /// The value is `None` if the transcript has no coding region or if the position lies
+    /// outside of the transcript.
+    pub fn hgvs_c(&self, pos: u64) -> Option<String> {
+        let (first, last) = match self.coding_bounds() {
+            Some(bounds) => bounds,
+            None => return None,
+        };
+        self.hgvs_offset_notation(pos, |tpos| {
+            if tpos < first {
+                format!("-{}", first - tpos)
+            } else if tpos > last {
+                format!("*{}", tpos - last)
+            } else {
+                format!("{}", tpos - first + 1)
+            }
+        }).map(|value| format!("c.{}", value))
+    }
+
+    /// Returns the `n.` notation of the given genomic position.
+    ///
+    /// The value is `None` if the position lies outside of the transcript.
+    pub fn hgvs_n(&self, pos: u64) -> Option<String> {
+        self.hgvs_offset_notation(pos, |tpos| format!("{}", tpos + 1))
+            .map(|value| format!("n.{}", value))
+    }
+
+    /// Returns the `p.` notation of the amino acid encoded by the codon containing the given
+    /// genomic position, e.g. `p.Met1`.
+    ///
+    /// The amino acid is written in its three-letter code, translated using the given codon
+    /// table. The value is `None` if the position does not lie in a complete codon of the
+    /// coding region, including the stop codon.
+    pub fn hgvs_p<S: SequenceSource>(&self, pos: u64, source: &mut S, table: &CodonTable)
+        -> ::Result<Option<String>>
+    {
+        let aa_idx = match self.genomic_to_protein(pos, true) {
+            Some(idx) => idx as usize,
+            None => return Ok(None),
+        };
+        let cds = self.cds_sequence(source, true)?;
+        let cstart = self.coding_phase() as usize + aa_idx * 3;
+        if cstart + 3 > cds.len() {
+            return Ok(None);
+        }
+        let codon = &cds[cstart..cstart + 3];
+        let aa =
+            if aa_idx == 0 && self.coding_phase() == 0 && table.is_start(codon) {
+                b'M'
+            } else {
+                table.translate_codon(codon)
+            };
+        Ok(Some(format!("p.{}{}", three_letter_code(aa), aa_idx + 1)))
+    }
+
+    /// Returns the zero-based transcript positions of the first and last bases of the coding
+    /// region, including the stop codon.
+    fn coding_bounds(&self) -> Option<(u64, u64)> {
+        self.coding_coord(true).and_then(|(start, end)| {
+            let (first, last) = match *self.strand() {
+                Strand::Reverse => (end - 1, start),
+                _ => (start, end - 1),
+            };
+            match (self.genomic_to_transcript(first), self.genomic_to_transcript(last)) {
+                (Some(f), Some(l)) => Some((f, l)),
+                _ => None,
+            }
+        })
+    }
+
+    /// Returns the notation of the given genomic position, without its prefix.
+    ///
+    /// Exonic positions are formatted with the given function, which accepts zero-based
+    /// transcript positions. Intronic positions are formatted as offsets from the nearest
+    /// exonic base.
+    fn hgvs_offset_notation<F>(&self, pos: u64, fmt_exonic: F) -> Option<String>
+        where F: Fn(u64) -> String
+    {
+        if let Some(tpos) = self.genomic_to_transcript(pos) {
+            return Some(fmt_exonic(tpos));
+        }
+        let intron = match self.introns().find(|ntr| ntr.start() <= pos && pos < ntr.end()) {
+            Some(intron) => intron,
+            None => return None,
+        };
+        // Exonic bases flanking the intron in the transcript direction, along with the
+        // distances of the position from each of them.
+        let (upstream, up_dist, downstream, down_dist) = match *self.strand() {
+            Strand::Reverse =>
+                (intron.end(), intron.end() - pos, intron.start() - 1, pos - intron.start() + 1),
+            _ =>
+                (intron.start() - 1, pos - intron.start() + 1, intron.end(), intron.end() - pos),
+        };
+        if up_dist <= down_dist {
+            self.genomic_to_transcript(upstream)
+                .map(|tpos| format!("{}+{}", fmt_exonic(tpos), up_dist))
+        } else {
+            self.genomic_to_transcript(downstream)
+                .map(|tpos| format!("{}-{}", fmt_exonic(tpos), down_dist))
+        }
+    }
+}
+
+/// Helper function to return the three-letter code of the given one-letter amino acid code.
+fn three_letter_code(aa: u8) -> &'static str {
+    match aa {
+        b'A' => "Ala", b'R' => "Arg", b'N' => "Asn", b'D' => "Asp", b'C' => "Cys",
+        b'Q' => "Gln", b'E' => "Glu", b'G' => "Gly", b'H' => "His", b'I' => "Ile",
+        b'L' => "Leu", b'K' => "Lys", b'M' => "Met", b'F' => "Phe", b'P' => "Pro",
+        b'S' => "Ser", b'T' => "Thr", b'W' => "Trp", b'Y' => "Tyr", b'V' => "Val",
+        b'*' => "Ter",
+        _ => "Xaa",
+    }
+}
@@ -0,0 +1,200 @@
+/*! Generation of identifiers for anonymous genes and transcripts.
+
+Some inputs, such as BED12 rows named `.`, contain transcripts and genes without identifiers,
+which formats like refFlat and GFF3 require. An `IdGenerator` assigns identifiers to these
+features, using one of two schemes:
+
+* `IdScheme::Hash` creates identifiers from a hash of the sequence name, the strand, and the
+  exon coordinates of the features, so that the same feature always gets the same identifier,
+  regardless of the input order.
+* `IdScheme::Counter` numbers the features in the order they are given.
+
+Gene identifiers are derived from the exon coordinates of all their transcripts, so that a
+transcript without a gene gets the same gene identifier as a gene consisting of only that
+transcript.
+*/
+use std::mem;
+
+use linked_hash_map::LinkedHashMap;
+
+use {Gene, Strand, Transcript};
+use model::suffixed_id;
+
+
+/// Default prefix of generated gene identifiers.
+pub const DEF_GENE_ID_PREFIX: &'static str = "GENE_";
+
+/// Default prefix of generated transcript identifiers.
+pub const DEF_TRANSCRIPT_ID_PREFIX: &'static str = "TRX_";
+
+/// Schemes for generating identifiers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IdScheme {
+    /// Hashes the sequence name, strand, and exon coordinates of the feature.
+    Hash,
+    /// Numbers the features in order.
+    Counter,
+}
+
+impl Default for IdScheme {
+    fn default() -> Self {
+        IdScheme::Hash
+    }
+}
+
+/// Generator of identifiers for genes and transcripts without any.
+#[derive(Debug, Clone)]
+pub struct IdGenerator {
+    scheme: IdScheme,
+    gene_prefix: String,
+    transcript_prefix: String,
+    num_genes: u64,
+    num_transcripts: u64,
+}
+
+impl Default for IdGenerator {
+    fn default() -> Self {
+        IdGenerator::new(IdScheme::default())
+    }
+}
+
+impl IdGenerator {
+
+    /// Creates a generator using the given scheme.
+    pub fn new(scheme: IdScheme) -> Self {
+        IdGenerator {
+            scheme: scheme,
+            gene_prefix: DEF_GENE_ID_PREFIX.to_owned(),
+            transcript_prefix: DEF_TRANSCRIPT_ID_PREFIX.to_owned(),
+            num_genes: 0,
+            num_transcripts: 0,
+        }
+    }
+
+    /// Sets the prefix of generated gene identifiers.
+    pub fn gene_prefix<T>(&mut self, prefix: T) -> &mut Self
+        where T: Into<String>
+    {
+        self.gene_prefix = prefix.into();
+        self
+    }
+
+    /// Sets the prefix of generated transcript identifiers.
+    pub fn transcript_prefix<T>(&mut self, prefix: T) -> &mut Self
+        where T: Into<String>
+    {
+        self.transcript_prefix = prefix.into();
+        self
+    }
+
+    /// Returns the scheme used by the generator.
+    pub fn scheme(&self) -> IdScheme {
+        self.scheme
+    }
+
+    /// Generates an identifier for the given transcript.
+    pub fn transcript_id(&mut self, transcript: &Transcript) -> String {
+        match self.scheme {
+            IdScheme::Hash =>
+                format!("{}{:016x}", self.transcript_prefix, hash_features(&[transcript])),
+            IdScheme::Counter => {
+                self.num_transcripts += 1;
+                format!("{}{}", self.transcript_prefix, self.num_transcripts)
+            },
+        }
+    }
+
+    /// Generates an identifier for the given gene.
+    pub fn gene_id(&mut self, gene: &Gene) -> String {
+        let transcripts = gene.transcripts().values().collect::<Vec<&Transcript>>();
+        self.gene_id_of(&transcripts)
+    }
+
+    /// Generates an identifier for a gene consisting of the given transcripts.
+    fn gene_id_of(&mut self, transcripts: &[&Transcript]) -> String {
+        match self.scheme {
+            IdScheme::Hash =>
+                format!("{}{:016x}", self.gene_prefix, hash_features(transcripts)),
+            IdScheme::Counter => {
+                self.num_genes += 1;
+                format!("{}{}", self.gene_prefix, self.num_genes)
+            },
+        }
+    }
+
+    /// Sets the identifier and the gene identifier of the given transcript, if it has none.
+    pub fn fill_transcript(&mut self, transcript: &mut Transcript) {
+        if transcript.id().is_none() {
+            let tid = self.transcript_id(transcript);
+            transcript.set_id(Some(tid));
+        }
+        if transcript.gene_id().is_none() {
+            let gid = self.gene_id_of(&[&*transcript]);
+            transcript.set_gene_id(Some(gid));
+        }
+    }
+
+    /// Sets the identifiers of the given gene and its transcripts, if they have none.
+    ///
+    /// Transcripts without gene identifiers take that of the gene. Transcripts are keyed by
+    /// their new identifiers within the gene, with colliding identifiers suffixed.
+    pub fn fill_gene(&mut self, gene: &mut Gene) {
+        if gene.id().is_none() {
+            let gid = self.gene_id(gene);
+            gene.set_id(Some(gid));
+        }
+        if gene.transcripts().values().all(|trx| trx.id().is_some()) {
+            return;
+        }
+        let gid = gene.id().map(|id| id.to_owned());
+        let transcripts = mem::replace(gene.transcripts_mut(), LinkedHashMap::new());
+        let mut filled = LinkedHashMap::new();
+        for (key, mut trx) in transcripts {
+            let key = if trx.id().is_some() {
+                key
+            } else {
+                let tid = self.transcript_id(&trx);
+                trx.set_id(Some(tid.as_str()));
+                tid
+            };
+            if trx.gene_id().is_none() {
+                trx.set_gene_id(gid.clone());
+            }
+            let key = if filled.contains_key(&key) {
+                suffixed_id(&key, |candidate| filled.contains_key(candidate))
+            } else {
+                key
+            };
+            let _ = filled.insert(key, trx);
+        }
+        *gene.transcripts_mut() = filled;
+    }
+}
+
+/// Returns a stable 64-bit FNV-1a hash of the sequence names, strands, and sorted exon
+/// coordinates of the given transcripts.
+///
+/// Unlike the hasher of the standard library, this hash is guaranteed to remain the same
+/// across releases, so that generated identifiers are reproducible.
+fn hash_features(transcripts: &[&Transcript]) -> u64 {
+    let mut chains = transcripts.iter()
+        .map(|trx| {
+            let strand = match *trx.strand() {
+                Strand::Forward => '+',
+                Strand::Reverse => '-',
+                Strand::Unknown => '.',
+            };
+            let exons = trx.exons().iter()
+                .map(|exn| format!("{}-{}", exn.start(), exn.end()))
+                .collect::<Vec<String>>();
+            format!("{}\t{}\t{}", trx.seq_name(), strand, exons.join(","))
+        })
+        .collect::<Vec<String>>();
+    chains.sort();
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in chains.join("\n").bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
@@ -0,0 +1,160 @@
+/*! Mapping between gene identifiers, gene names, and transcript identifiers.
+
+`IdMap` collects the identifiers and names of the genes of an annotation, along with the
+identifiers of their transcripts, and looks them up in any direction, for example the genes of
+a name or the gene of a transcript. The mapping can be written as a TSV table with one row per
+transcript.
+*/
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
+use linked_hash_map::LinkedHashMap;
+
+use Gene;
+
+
+quick_error! {
+    /// Errors that occur when writing identifier mappings.
+    #[derive(Debug)]
+    pub enum IdMapError {
+        /// Errors propagated from writing the underlying stream.
+        Io(err: io::Error) {
+            description(err.description())
+            from()
+            cause(err)
+        }
+    }
+}
+
+/// Names and transcripts of a gene in an identifier mapping.
+#[derive(Debug, Clone, Default, PartialEq)]
+struct GeneEntry {
+    name: Option<String>,
+    transcript_ids: Vec<String>,
+}
+
+/// Mapping between gene identifiers, gene names, and transcript identifiers.
+///
+/// Genes without identifiers are not included in the mapping.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IdMap {
+    genes: LinkedHashMap<String, GeneEntry>,
+    names: HashMap<String, Vec<String>>,
+    transcripts: HashMap<String, String>,
+}
+
+impl IdMap {
+
+    /// Creates an empty mapping.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a mapping of the given genes.
+    ///
+    /// The first error encountered in the stream is returned.
+    pub fn from_genes<I>(genes: I) -> ::Result<Self>
+        where I: IntoIterator<Item=::Result<Gene>>
+    {
+        let mut map = IdMap::new();
+        for gene in genes {
+            map.add_gene(&gene?);
+        }
+        Ok(map)
+    }
+
+    /// Adds the given gene and its transcripts to the mapping.
+    ///
+    /// Transcripts of genes that were added before are appended to them.
+    pub fn add_gene(&mut self, gene: &Gene) {
+        let gid = match gene.id() {
+            Some(id) => id.to_owned(),
+            None => return,
+        };
+        if !self.genes.contains_key(&gid) {
+            let name = gene.name().map(|name| name.to_owned());
+            if let Some(ref name) = name {
+                self.names.entry(name.clone()).or_insert_with(Vec::new).push(gid.clone());
+            }
+            let entry = GeneEntry { name: name, transcript_ids: vec![] };
+            let _ = self.genes.insert(gid.clone(), entry);
+        }
+        let entry = self.genes.get_mut(&gid).expect("an added gene");
+        for tid in gene.transcripts().keys() {
+            if self.transcripts.insert(tid.clone(), gid.clone()).is_none() {
+                entry.transcript_ids.push(tid.clone());
+            }
+        }
+    }
+
+    /// Returns the number of genes in the mapping.
+    pub fn len(&self) -> usize {
+        self.genes.len()
+    }
+
+    /// Returns whether the mapping contains no genes.
+    pub fn is_empty(&self) -> bool {
+        self.genes.is_empty()
+    }
+
+    /// Returns the identifiers of all genes, in order of addition.
+    pub fn gene_ids(&self) -> Vec<&str> {
+        self.genes.keys().map(|gid| gid.as_str()).collect()
+    }
+
+    /// Returns the name of the gene with the given identifier.
+    pub fn gene_name(&self, gene_id: &str) -> Option<&str> {
+        self.genes.get(gene_id).and_then(|entry| entry.name.as_ref().map(|n| n.as_str()))
+    }
+
+    /// Returns the identifiers of the genes with the given name, in order of addition.
+    pub fn genes_by_name(&self, name: &str) -> Vec<&str> {
+        self.names.get(name)
+            .map(|gids| gids.iter().map(|gid| gid.as_str()).collect())
+            .unwrap_or_default()
+    }
+
+    /// Returns the identifiers of the transcripts of the gene with the given identifier.
+    pub fn transcript_ids(&self, gene_id: &str) -> Vec<&str> {
+        self.genes.get(gene_id)
+            .map(|entry| entry.transcript_ids.iter().map(|tid| tid.as_str()).collect())
+            .unwrap_or_default()
+    }
+
+    /// Returns the identifier of the gene of the transcript with the given identifier.
+    pub fn gene_of_transcript(&self, transcript_id: &str) -> Option<&str> {
+        self.transcripts.get(transcript_id).map(|gid| gid.as_str())
+    }
+
+    /// Writes the mapping as a TSV table with a header line and one row per transcript, with
+    /// the `gene_id`, `gene_name`, and `transcript_id` columns.
+    ///
+    /// Genes without transcripts are written in a single row, with missing values written as
+    /// `.`.
+    pub fn write_tsv<W: Write>(&self, mut writer: W) -> ::Result<()> {
+        self.write_rows(&mut writer).map_err(|e| ::Error::from(IdMapError::from(e)))
+    }
+
+    /// Writes the mapping as a TSV table to the given path.
+    pub fn write_tsv_file<P: AsRef<Path>>(&self, path: P) -> ::Result<()> {
+        let file = fs::File::create(path).map_err(|e| ::Error::from(IdMapError::from(e)))?;
+        self.write_tsv(io::BufWriter::new(file))
+    }
+
+    fn write_rows<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writeln!(writer, "gene_id\tgene_name\ttranscript_id")?;
+        for (gid, entry) in self.genes.iter() {
+            let name = entry.name.as_ref().map_or(".", |n| n.as_str());
+            if entry.transcript_ids.is_empty() {
+                writeln!(writer, "{}\t{}\t.", gid, name)?;
+            }
+            for tid in entry.transcript_ids.iter() {
+                writeln!(writer, "{}\t{}\t{}", gid, name, tid)?;
+            }
+        }
+        writer.flush()
+    }
+}
@@ -0,0 +1,368 @@
+/*! Asynchronous readers for refFlat and GFF files.
+
+These readers read from `tokio_io::AsyncRead` sources and yield their results as `futures`
+streams, so that they can be polled from an event loop without blocking it. RefFlat records are
+parsed as soon as their lines are read. The features of GFF transcripts may be spread over the
+entire file, so the GFF reader collects its whole input before yielding any transcript.
+*/
+use std::io::{self, Cursor};
+use std::mem;
+
+use futures::{Async, Poll, Stream};
+use tokio_io::AsyncRead;
+
+use {DuplicatePolicy, Gene, GffType, StrandPolicy, Transcript};
+use io_gff::{GffError, GffGenes, GffTranscripts, Reader as GffReader};
+use io_refflat::{records_to_gene, RefFlatError, RefFlatRecord, RefFlatRecordRef};
+
+
+/// Number of bytes read from the source at a time.
+const CHUNK_LEN: usize = 8192;
+
+/// Line reader over an asynchronous source.
+struct Lines<R> {
+    inner: R,
+    buf: Vec<u8>,
+    scanned: usize,
+    eof: bool,
+}
+
+impl<R: AsyncRead> Lines<R> {
+
+    fn new(inner: R) -> Self {
+        Lines { inner: inner, buf: Vec::new(), scanned: 0, eof: false }
+    }
+
+    /// Polls for the next line, without its line terminator.
+    fn poll_line(&mut self) -> Poll<Option<String>, io::Error> {
+        loop {
+            let newline = self.buf[self.scanned..].iter().position(|&b| b == b'\n');
+            if let Some(pos) = newline {
+                let line = self.buf.drain(..self.scanned + pos + 1).collect::<Vec<u8>>();
+                self.scanned = 0;
+                return to_line(line).map(|line| Async::Ready(Some(line)));
+            }
+            self.scanned = self.buf.len();
+            if self.eof {
+                if self.buf.is_empty() {
+                    return Ok(Async::Ready(None));
+                }
+                self.scanned = 0;
+                let line = mem::replace(&mut self.buf, Vec::new());
+                return to_line(line).map(|line| Async::Ready(Some(line)));
+            }
+            let mut chunk = [0u8; CHUNK_LEN];
+            match self.inner.read(&mut chunk) {
+                Ok(0) => self.eof = true,
+                Ok(n) => self.buf.extend_from_slice(&chunk[..n]),
+                Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => return Ok(Async::NotReady),
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {},
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Polls until the entire source has been read, returning its contents.
+    fn poll_all(&mut self) -> Poll<Vec<u8>, io::Error> {
+        let mut chunk = [0u8; CHUNK_LEN];
+        while !self.eof {
+            match self.inner.read(&mut chunk) {
+                Ok(0) => self.eof = true,
+                Ok(n) => self.buf.extend_from_slice(&chunk[..n]),
+                Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => return Ok(Async::NotReady),
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {},
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(Async::Ready(mem::replace(&mut self.buf, Vec::new())))
+    }
+}
+
+/// Converts the given bytes into a line, removing its line terminator.
+fn to_line(mut bytes: Vec<u8>) -> io::Result<String> {
+    if bytes.last() == Some(&b'\n') {
+        let _ = bytes.pop();
+        if bytes.last() == Some(&b'\r') {
+            let _ = bytes.pop();
+        }
+    }
+    String::from_utf8(bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Asynchronous refFlat reader.
+pub struct AsyncRefFlatReader<R> {
+    lines: Lines<R>,
+    duplicate_policy: DuplicatePolicy,
+}
+
+impl<R: AsyncRead> AsyncRefFlatReader<R> {
+
+    /// Creates an asynchronous refFlat reader from another reader.
+    pub fn from_reader(in_reader: R) -> Self {
+        AsyncRefFlatReader {
+            lines: Lines::new(in_reader),
+            duplicate_policy: DuplicatePolicy::default(),
+        }
+    }
+
+    /// Sets how transcripts with duplicate identifiers in the same gene are handled, as in
+    /// `RefFlatReader::duplicate_transcripts`.
+    pub fn duplicate_transcripts(mut self, policy: DuplicatePolicy) -> Self {
+        self.duplicate_policy = policy;
+        self
+    }
+
+    /// Creates a stream of records.
+    pub fn records(self) -> AsyncRefFlatRecords<R> {
+        AsyncRefFlatRecords { lines: self.lines, num_lines: 0 }
+    }
+
+    /// Creates a stream of transcripts.
+    pub fn transcripts(self) -> AsyncRefFlatTranscripts<R> {
+        AsyncRefFlatTranscripts { inner: self.records() }
+    }
+
+    /// Creates a stream of genes.
+    ///
+    /// As with `RefFlatReader::genes_stream`, consecutive records are grouped based on their
+    /// gene identifiers into genes.
+    pub fn genes(self) -> AsyncRefFlatGenes<R> {
+        let policy = self.duplicate_policy;
+        AsyncRefFlatGenes { inner: self.records(), group: Vec::new(), policy: policy }
+    }
+}
+
+/// Stream of refFlat records.
+pub struct AsyncRefFlatRecords<R> {
+    lines: Lines<R>,
+    num_lines: u64,
+}
+
+impl<R: AsyncRead> Stream for AsyncRefFlatRecords<R> {
+
+    type Item = RefFlatRecord;
+    type Error = ::Error;
+
+    fn poll(&mut self) -> Poll<Option<RefFlatRecord>, ::Error> {
+        loop {
+            let line = match self.lines.poll_line() {
+                Ok(Async::Ready(Some(line))) => line,
+                Ok(Async::Ready(None)) => return Ok(Async::Ready(None)),
+                Ok(Async::NotReady) => return Ok(Async::NotReady),
+                Err(err) => return Err(::Error::from(RefFlatError::from(err))),
+            };
+            self.num_lines += 1;
+            if line.is_empty() {
+                continue;
+            }
+            return RefFlatRecordRef::from_line(&line)
+                .and_then(|rec| rec.to_record())
+                .map(|rec| Async::Ready(Some(rec)))
+                .map_err(|err| ::Error::at_line(self.num_lines, err));
+        }
+    }
+}
+
+/// Stream of transcripts created from refFlat records.
+pub struct AsyncRefFlatTranscripts<R> {
+    inner: AsyncRefFlatRecords<R>,
+}
+
+impl<R: AsyncRead> Stream for AsyncRefFlatTranscripts<R> {
+
+    type Item = Transcript;
+    type Error = ::Error;
+
+    fn poll(&mut self) -> Poll<Option<Transcript>, ::Error> {
+        match self.inner.poll()? {
+            Async::Ready(Some(record)) =>
+                record.into_transcript().map(|trx| Async::Ready(Some(trx))),
+            Async::Ready(None) => Ok(Async::Ready(None)),
+            Async::NotReady => Ok(Async::NotReady),
+        }
+    }
+}
+
+/// Stream of genes created from consecutive refFlat records.
+pub struct AsyncRefFlatGenes<R> {
+    inner: AsyncRefFlatRecords<R>,
+    group: Vec<RefFlatRecord>,
+    policy: DuplicatePolicy,
+}
+
+impl<R: AsyncRead> AsyncRefFlatGenes<R> {
+
+    /// Creates a gene from the given records, which share their gene identifiers, sequence
+    /// names, and strands.
+    fn group_to_gene(&self, group: Vec<RefFlatRecord>) -> ::Result<Gene> {
+        let (gid, seq_name, strand_char) = {
+            let first = &group[0];
+            (first.gene_id().to_owned(), first.seq_name().to_owned(), first.strand())
+        };
+        records_to_gene(gid, seq_name, strand_char, group.into_iter().map(Ok), self.policy)
+    }
+}
+
+impl<R: AsyncRead> Stream for AsyncRefFlatGenes<R> {
+
+    type Item = Gene;
+    type Error = ::Error;
+
+    fn poll(&mut self) -> Poll<Option<Gene>, ::Error> {
+        loop {
+            let record = match self.inner.poll()? {
+                Async::Ready(Some(record)) => record,
+                Async::Ready(None) if self.group.is_empty() => return Ok(Async::Ready(None)),
+                Async::Ready(None) => {
+                    let group = mem::replace(&mut self.group, Vec::new());
+                    return self.group_to_gene(group).map(|gx| Async::Ready(Some(gx)));
+                },
+                Async::NotReady => return Ok(Async::NotReady),
+            };
+            let same_gene = match self.group.first() {
+                Some(first) => first.gene_id() == record.gene_id()
+                    && first.seq_name() == record.seq_name()
+                    && first.strand() == record.strand(),
+                None => true,
+            };
+            if same_gene {
+                self.group.push(record);
+            } else {
+                let group = mem::replace(&mut self.group, vec![record]);
+                return self.group_to_gene(group).map(|gx| Async::Ready(Some(gx)));
+            }
+        }
+    }
+}
+
+/// Asynchronous GFF reader.
+///
+/// The input is read entirely before it is parsed, since the features of a transcript may be
+/// interspersed with the features of other transcripts.
+pub struct AsyncGffReader<R> {
+    lines: Lines<R>,
+    gff_type: GffType,
+    keep_attributes: bool,
+    lenient: bool,
+    strand_policy: StrandPolicy,
+}
+
+impl<R: AsyncRead> AsyncGffReader<R> {
+
+    /// Creates an asynchronous GFF reader of the given variant from another reader.
+    pub fn from_reader(in_reader: R, gff_type: GffType) -> Self {
+        AsyncGffReader {
+            lines: Lines::new(in_reader),
+            gff_type: gff_type,
+            keep_attributes: false,
+            lenient: false,
+            strand_policy: StrandPolicy::DefaultUnknown,
+        }
+    }
+
+    /// Sets whether the attributes of the rows are kept, as in `GffReader::keep_attributes`.
+    pub fn keep_attributes(mut self, keep_attributes: bool) -> Self {
+        self.keep_attributes = keep_attributes;
+        self
+    }
+
+    /// Sets whether invalid rows are skipped, as in `GffReader::lenient`.
+    pub fn lenient(mut self, lenient: bool) -> Self {
+        self.lenient = lenient;
+        self
+    }
+
+    /// Sets how rows without strands are handled, as in `GffReader::strand_policy`.
+    pub fn strand_policy(mut self, policy: StrandPolicy) -> Self {
+        self.strand_policy = policy;
+        self
+    }
+
+    /// Creates a stream of transcripts.
+    pub fn transcripts(self) -> AsyncGffTranscripts<R> {
+        AsyncGffTranscripts { reader: Some(self), inner: None }
+    }
+
+    /// Creates a stream of genes.
+    pub fn genes(self) -> AsyncGffGenes<R> {
+        AsyncGffGenes { reader: Some(self), inner: None }
+    }
+
+    /// Creates a synchronous reader over the given contents with the settings of this reader.
+    fn sync_reader(&self, contents: Vec<u8>) -> GffReader<Cursor<Vec<u8>>> {
+        let mut reader = GffReader::from_reader(Cursor::new(contents), self.gff_type.clone());
+        let _ = reader.keep_attributes(self.keep_attributes)
+            .lenient(self.lenient)
+            .strand_policy(self.strand_policy);
+        reader
+    }
+
+    /// Polls until the entire input has been read, returning a synchronous reader over it.
+    fn poll_sync_reader(&mut self) -> Poll<GffReader<Cursor<Vec<u8>>>, ::Error> {
+        match self.lines.poll_all() {
+            Ok(Async::Ready(contents)) => Ok(Async::Ready(self.sync_reader(contents))),
+            Ok(Async::NotReady) => Ok(Async::NotReady),
+            Err(err) => Err(::Error::from(GffError::from(err))),
+        }
+    }
+}
+
+/// Stream of transcripts created from GFF rows.
+pub struct AsyncGffTranscripts<R> {
+    reader: Option<AsyncGffReader<R>>,
+    inner: Option<GffTranscripts>,
+}
+
+impl<R: AsyncRead> Stream for AsyncGffTranscripts<R> {
+
+    type Item = Transcript;
+    type Error = ::Error;
+
+    fn poll(&mut self) -> Poll<Option<Transcript>, ::Error> {
+        if self.inner.is_none() {
+            let transcripts = match self.reader {
+                Some(ref mut reader) => match reader.poll_sync_reader()? {
+                    Async::Ready(mut sync_reader) => sync_reader.transcripts()?,
+                    Async::NotReady => return Ok(Async::NotReady),
+                },
+                None => return Ok(Async::Ready(None)),
+            };
+            self.reader = None;
+            self.inner = Some(transcripts);
+        }
+        match self.inner.as_mut().and_then(|inner| inner.next()) {
+            Some(result) => result.map(|trx| Async::Ready(Some(trx))),
+            None => Ok(Async::Ready(None)),
+        }
+    }
+}
+
+/// Stream of genes created from GFF rows.
+pub struct AsyncGffGenes<R> {
+    reader: Option<AsyncGffReader<R>>,
+    inner: Option<GffGenes>,
+}
+
+impl<R: AsyncRead> Stream for AsyncGffGenes<R> {
+
+    type Item = Gene;
+    type Error = ::Error;
+
+    fn poll(&mut self) -> Poll<Option<Gene>, ::Error> {
+        if self.inner.is_none() {
+            let genes = match self.reader {
+                Some(ref mut reader) => match reader.poll_sync_reader()? {
+                    Async::Ready(mut sync_reader) => sync_reader.genes()?,
+                    Async::NotReady => return Ok(Async::NotReady),
+                },
+                None => return Ok(Async::Ready(None)),
+            };
+            self.reader = None;
+            self.inner = Some(genes);
+        }
+        match self.inner.as_mut().and_then(|inner| inner.next()) {
+            Some(result) => result.map(|gx| Async::Ready(Some(gx))),
+            None => Ok(Async::Ready(None)),
+        }
+    }
+}
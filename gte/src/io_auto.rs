@@ -0,0 +1,431 @@
+/*! Reader of annotation files whose format is detected automatically, and format-agnostic
+conversion between annotation formats.
+
+`AnnotationReader::open` detects whether a file is in the refFlat, genePred, GTF, GFF3, or BED12
+format from its first data row, falling back to its extension when the row is inconclusive.
+The readers of all formats implement `ReadAnnotation`, which yields transcripts and genes as
+boxed iterators, so that tools can be written without regard to the input format. Likewise, the
+writers of the refFlat, genePred, GTF, GFF3, and BED12 formats implement `WriteAnnotation`, and
+`convert` copies the genes of any reader into any writer.
+*/
+use std::error::Error;
+use std::fmt;
+use std::io::{self, Cursor, Read};
+use std::path::Path;
+
+use {Gene, GffType, IdGenerator, Transcript};
+use io_bed::{Reader as BedReader, Writer as BedWriter, transcript_to_gene};
+use io_genepred::{Reader as GenePredReader, Writer as GenePredWriter};
+use io_gff::{Reader as GffReader, Writer as GffWriter};
+use io_refflat::{Reader as RefFlatReader, Writer as RefFlatWriter};
+use utils::open_path;
+
+
+/// Number of bytes read from the start of a file for detecting its format.
+const HEAD_LEN: u64 = 1 << 16;
+
+quick_error! {
+    /// Errors that occur when detecting the format of annotation files.
+    #[derive(Debug)]
+    pub enum DetectError {
+        /// Occurs when the format can be determined from neither the contents nor the
+        /// extension of a file.
+        UnknownFormat(path: String) {
+            description("unknown annotation format")
+            display(self_) -> ("{}: {}", self_.description(), path)
+        }
+        /// Errors propagated from reading the file.
+        Io(err: io::Error) {
+            description(err.description())
+            from()
+            cause(err)
+        }
+    }
+}
+
+/// Annotation file formats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnnotationFormat {
+    /// The refFlat format.
+    RefFlat,
+    /// The genePred format, with or without the extended columns.
+    GenePred,
+    /// The GTF format.
+    Gtf,
+    /// The GFF3 format.
+    Gff3,
+    /// The BED12 format.
+    Bed12,
+}
+
+impl AnnotationFormat {
+
+    /// Returns the format indicated by the extension of the given path, ignoring any
+    /// compression extension.
+    pub fn from_extension<P: AsRef<Path>>(path: P) -> Option<Self> {
+        let name = match path.as_ref().file_name().and_then(|name| name.to_str()) {
+            Some(name) => name.to_lowercase(),
+            None => return None,
+        };
+        let name = name.trim_right_matches(".gz").trim_right_matches(".bgz");
+        let ext = match name.rfind('.') {
+            Some(idx) => &name[idx + 1..],
+            None => return None,
+        };
+        match ext {
+            "refflat" => Some(AnnotationFormat::RefFlat),
+            "genepred" | "gp" => Some(AnnotationFormat::GenePred),
+            "gtf" | "gtf2" | "gff2" => Some(AnnotationFormat::Gtf),
+            "gff3" | "gff" => Some(AnnotationFormat::Gff3),
+            "bed" | "bed12" => Some(AnnotationFormat::Bed12),
+            _ => None,
+        }
+    }
+
+    /// Returns the format indicated by the given start of a file, if it is conclusive.
+    ///
+    /// The format is detected from the `##gff-version` directive, or from the number and the
+    /// values of the columns of the first data row.
+    pub fn from_contents(head: &str) -> Option<Self> {
+        for line in head.lines() {
+            let line = line.trim_right_matches('\r');
+            if line.starts_with("##gff-version") {
+                return match line.split_whitespace().nth(1) {
+                    Some(version) if version.starts_with('3') => Some(AnnotationFormat::Gff3),
+                    Some(_) => Some(AnnotationFormat::Gtf),
+                    None => None,
+                };
+            }
+            if line.is_empty() || line.starts_with('#') || line.starts_with("track")
+                || line.starts_with("browser")
+            {
+                continue;
+            }
+            return detect_row(&line.split('\t').collect::<Vec<&str>>());
+        }
+        None
+    }
+}
+
+impl fmt::Display for AnnotationFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match *self {
+            AnnotationFormat::RefFlat => "refFlat",
+            AnnotationFormat::GenePred => "genePred",
+            AnnotationFormat::Gtf => "GTF",
+            AnnotationFormat::Gff3 => "GFF3",
+            AnnotationFormat::Bed12 => "BED12",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Returns the format of the given data row columns, if they are conclusive.
+fn detect_row(cols: &[&str]) -> Option<AnnotationFormat> {
+    let is_num = |idx: usize| cols[idx].parse::<u64>().is_ok();
+    let is_strand = |idx: usize| cols[idx] == "+" || cols[idx] == "-" || cols[idx] == ".";
+    match cols.len() {
+        9 if is_num(3) && is_num(4) && is_strand(6) => {
+            let attrs = cols[8];
+            if attrs.contains('"') || !attrs.contains('=') {
+                Some(AnnotationFormat::Gtf)
+            } else {
+                Some(AnnotationFormat::Gff3)
+            }
+        },
+        11 if is_strand(3) && (4..9).all(&is_num) => Some(AnnotationFormat::RefFlat),
+        10 | 15 if is_strand(2) && (3..8).all(&is_num) => Some(AnnotationFormat::GenePred),
+        12 if is_num(1) && is_num(2) && is_strand(5) && is_num(9) =>
+            Some(AnnotationFormat::Bed12),
+        _ => None,
+    }
+}
+
+/// Readers yielding transcripts and genes regardless of their input format.
+pub trait ReadAnnotation {
+
+    /// Creates an iterator of transcripts.
+    fn read_transcripts<'a>(&'a mut self)
+        -> ::Result<Box<Iterator<Item=::Result<Transcript>> + 'a>>;
+
+    /// Creates an iterator of genes.
+    fn read_genes<'a>(&'a mut self) -> ::Result<Box<Iterator<Item=::Result<Gene>> + 'a>>;
+}
+
+impl<R: Read> ReadAnnotation for RefFlatReader<R> {
+
+    fn read_transcripts<'a>(&'a mut self)
+        -> ::Result<Box<Iterator<Item=::Result<Transcript>> + 'a>>
+    {
+        Ok(Box::new(self.transcripts_stream()))
+    }
+
+    /// Creates an iterator of genes, grouping records regardless of their order.
+    fn read_genes<'a>(&'a mut self) -> ::Result<Box<Iterator<Item=::Result<Gene>> + 'a>> {
+        let genes = self.genes_unsorted()?;
+        Ok(Box::new(genes))
+    }
+}
+
+impl<R: Read> ReadAnnotation for GenePredReader<R> {
+
+    fn read_transcripts<'a>(&'a mut self)
+        -> ::Result<Box<Iterator<Item=::Result<Transcript>> + 'a>>
+    {
+        Ok(Box::new(self.transcripts_stream()))
+    }
+
+    /// Creates an iterator of genes, grouping consecutive records.
+    fn read_genes<'a>(&'a mut self) -> ::Result<Box<Iterator<Item=::Result<Gene>> + 'a>> {
+        Ok(Box::new(self.genes_stream()))
+    }
+}
+
+impl<R: Read> ReadAnnotation for GffReader<R> {
+
+    fn read_transcripts<'a>(&'a mut self)
+        -> ::Result<Box<Iterator<Item=::Result<Transcript>> + 'a>>
+    {
+        let transcripts = self.transcripts()?;
+        Ok(Box::new(transcripts))
+    }
+
+    fn read_genes<'a>(&'a mut self) -> ::Result<Box<Iterator<Item=::Result<Gene>> + 'a>> {
+        let genes = self.genes()?;
+        Ok(Box::new(genes))
+    }
+}
+
+impl<R: Read> ReadAnnotation for BedReader<R> {
+
+    fn read_transcripts<'a>(&'a mut self)
+        -> ::Result<Box<Iterator<Item=::Result<Transcript>> + 'a>>
+    {
+        Ok(Box::new(self.transcripts()))
+    }
+
+    fn read_genes<'a>(&'a mut self) -> ::Result<Box<Iterator<Item=::Result<Gene>> + 'a>> {
+        Ok(Box::new(self.genes()))
+    }
+}
+
+/// Reader of annotation files in any of the supported formats.
+pub struct AnnotationReader {
+    format: AnnotationFormat,
+    inner: Box<ReadAnnotation>,
+    strip_id_versions: bool,
+    id_generator: Option<IdGenerator>,
+}
+
+impl AnnotationReader {
+
+    /// Opens the file at the given path, which may be gzip or bgzip-compressed, detecting its
+    /// format.
+    pub fn open<P: AsRef<Path>>(path: P) -> ::Result<Self> {
+        let mut file = open_path(&path).map_err(detect_io_error)?;
+        let mut head = Vec::new();
+        let _ = (&mut file).take(HEAD_LEN).read_to_end(&mut head).map_err(detect_io_error)?;
+        let format = AnnotationFormat::from_contents(&String::from_utf8_lossy(&head))
+            .or_else(|| AnnotationFormat::from_extension(&path))
+            .ok_or_else(|| {
+                let path = path.as_ref().to_string_lossy().into_owned();
+                ::Error::from(DetectError::UnknownFormat(path))
+            })?;
+        let input: Box<Read> = Box::new(Cursor::new(head).chain(file));
+        Ok(AnnotationReader::from_reader(input, format))
+    }
+
+    /// Opens the file at the given path, which may be gzip or bgzip-compressed, as a file of the
+    /// given format.
+    pub fn open_as<P: AsRef<Path>>(path: P, format: AnnotationFormat) -> ::Result<Self> {
+        let file = open_path(path).map_err(detect_io_error)?;
+        Ok(AnnotationReader::from_reader(file, format))
+    }
+
+    /// Creates a reader of the given format from another reader.
+    ///
+    /// GTF and GFF3 inputs are read with their attributes kept, so that biotypes, tags, and
+    /// other attribute values are available regardless of the input format.
+    pub fn from_reader<R: Read + 'static>(in_reader: R, format: AnnotationFormat) -> Self {
+        let inner: Box<ReadAnnotation> = match format {
+            AnnotationFormat::RefFlat => Box::new(RefFlatReader::from_reader(in_reader)),
+            AnnotationFormat::GenePred => Box::new(GenePredReader::from_reader(in_reader)),
+            AnnotationFormat::Gtf => Box::new(gff_reader(in_reader, GffType::GTF2)),
+            AnnotationFormat::Gff3 => Box::new(gff_reader(in_reader, GffType::GFF3)),
+            AnnotationFormat::Bed12 => Box::new(BedReader::from_reader(in_reader)),
+        };
+        AnnotationReader {
+            format: format,
+            inner: inner,
+            strip_id_versions: false,
+            id_generator: None,
+        }
+    }
+
+    /// Returns the format of the input.
+    pub fn format(&self) -> AnnotationFormat {
+        self.format
+    }
+
+    /// Sets whether the version suffixes of gene and transcript identifiers are stripped, as
+    /// in `strip_id_version`, or retained.
+    pub fn strip_id_versions(&mut self, strip_id_versions: bool) -> &mut Self {
+        self.strip_id_versions = strip_id_versions;
+        self
+    }
+
+    /// Sets the generator used for the identifiers of transcripts and genes without any.
+    ///
+    /// Identifiers are generated after version suffixes are stripped, if set.
+    pub fn id_generator(&mut self, generator: IdGenerator) -> &mut Self {
+        self.id_generator = Some(generator);
+        self
+    }
+
+    /// Creates an iterator of transcripts.
+    pub fn transcripts<'a>(&'a mut self)
+        -> ::Result<Box<Iterator<Item=::Result<Transcript>> + 'a>>
+    {
+        let strip_id_versions = self.strip_id_versions;
+        let generator = &mut self.id_generator;
+        let transcripts = self.inner.read_transcripts()?;
+        if !strip_id_versions && generator.is_none() {
+            return Ok(transcripts);
+        }
+        Ok(Box::new(transcripts.map(move |result| result.map(|mut trx| {
+            if strip_id_versions {
+                trx.strip_id_versions();
+            }
+            if let Some(ref mut generator) = *generator {
+                generator.fill_transcript(&mut trx);
+            }
+            trx
+        }))))
+    }
+
+    /// Creates an iterator of genes.
+    ///
+    /// Genes are created from refFlat, GTF, and GFF3 rows regardless of their order, while
+    /// genePred rows are grouped into genes only when they are consecutive. Each BED12 row is
+    /// returned as a gene of its own.
+    pub fn genes<'a>(&'a mut self) -> ::Result<Box<Iterator<Item=::Result<Gene>> + 'a>> {
+        if self.format == AnnotationFormat::Bed12 && self.id_generator.is_some() {
+            // BED12 genes are named after their transcripts, so these are filled in first
+            let transcripts = self.transcripts()?;
+            Ok(Box::new(transcripts.map(|result| result.and_then(transcript_to_gene))))
+        } else {
+            self.filled_genes()
+        }
+    }
+
+    /// Creates an iterator of genes with their identifiers stripped or generated, as set.
+    fn filled_genes<'a>(&'a mut self) -> ::Result<Box<Iterator<Item=::Result<Gene>> + 'a>> {
+        let strip_id_versions = self.strip_id_versions;
+        let generator = &mut self.id_generator;
+        let genes = self.inner.read_genes()?;
+        if !strip_id_versions && generator.is_none() {
+            return Ok(genes);
+        }
+        Ok(Box::new(genes.map(move |result| result.map(|mut gx| {
+            if strip_id_versions {
+                gx.strip_id_versions();
+            }
+            if let Some(ref mut generator) = *generator {
+                generator.fill_gene(&mut gx);
+            }
+            gx
+        }))))
+    }
+}
+
+impl ReadAnnotation for AnnotationReader {
+
+    fn read_transcripts<'a>(&'a mut self)
+        -> ::Result<Box<Iterator<Item=::Result<Transcript>> + 'a>>
+    {
+        self.transcripts()
+    }
+
+    fn read_genes<'a>(&'a mut self) -> ::Result<Box<Iterator<Item=::Result<Gene>> + 'a>> {
+        self.genes()
+    }
+}
+
+/// Writers accepting transcripts and genes regardless of their output format.
+pub trait WriteAnnotation {
+
+    /// Writes the given transcript.
+    fn write_transcript(&mut self, transcript: &Transcript) -> ::Result<()>;
+
+    /// Writes the given gene.
+    fn write_gene(&mut self, gene: &Gene) -> ::Result<()>;
+}
+
+impl<W: io::Write> WriteAnnotation for RefFlatWriter<W> {
+
+    fn write_transcript(&mut self, transcript: &Transcript) -> ::Result<()> {
+        RefFlatWriter::write_transcript(self, transcript)
+    }
+
+    fn write_gene(&mut self, gene: &Gene) -> ::Result<()> {
+        RefFlatWriter::write_gene(self, gene)
+    }
+}
+
+impl<W: io::Write> WriteAnnotation for GenePredWriter<W> {
+
+    fn write_transcript(&mut self, transcript: &Transcript) -> ::Result<()> {
+        GenePredWriter::write_transcript(self, transcript)
+    }
+
+    fn write_gene(&mut self, gene: &Gene) -> ::Result<()> {
+        GenePredWriter::write_gene(self, gene)
+    }
+}
+
+impl<W: io::Write> WriteAnnotation for GffWriter<W> {
+
+    fn write_transcript(&mut self, transcript: &Transcript) -> ::Result<()> {
+        GffWriter::write_transcript(self, transcript)
+    }
+
+    fn write_gene(&mut self, gene: &Gene) -> ::Result<()> {
+        GffWriter::write_gene(self, gene)
+    }
+}
+
+impl<W: io::Write> WriteAnnotation for BedWriter<W> {
+
+    fn write_transcript(&mut self, transcript: &Transcript) -> ::Result<()> {
+        BedWriter::write_transcript(self, transcript)
+    }
+
+    fn write_gene(&mut self, gene: &Gene) -> ::Result<()> {
+        BedWriter::write_gene(self, gene)
+    }
+}
+
+/// Writes all genes of the given reader to the given writer, returning the number of genes
+/// written.
+///
+/// Conversion stops at the first error of either the reader or the writer.
+pub fn convert<R, W>(reader: &mut R, writer: &mut W) -> ::Result<usize>
+    where R: ReadAnnotation + ?Sized, W: WriteAnnotation + ?Sized
+{
+    let mut num_genes = 0;
+    for gene in reader.read_genes()? {
+        writer.write_gene(&gene?)?;
+        num_genes += 1;
+    }
+    Ok(num_genes)
+}
+
+fn gff_reader<R: Read>(in_reader: R, gff_type: GffType) -> GffReader<R> {
+    let mut reader = GffReader::from_reader(in_reader, gff_type);
+    let _ = reader.keep_attributes(true);
+    reader
+}
+
+fn detect_io_error(err: io::Error) -> ::Error {
+    ::Error::from(DetectError::from(err))
+}
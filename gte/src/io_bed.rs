@@ -0,0 +1,319 @@
+/*! Reader and writer for the BED12 format.
+
+BED12 rows describe single transcripts, with their exons given as blocks relative to the
+transcript start and their coding regions given by the thick start and end columns. The format
+is described in the
+[UCSC Genome Browser FAQ](https://genome.ucsc.edu/FAQ/FAQformat.html#format1).
+
+BED12 rows have no gene identifiers, so each transcript is returned as a gene of its own, which
+has the name of the transcript as its identifier. Rows named `.` can be given generated
+identifiers with an `IdGenerator`. Conversely, genes are written as one row per
+transcript, and their identifiers are not kept.
+*/
+use std::convert::AsRef;
+use std::error::Error;
+use std::fs;
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::Path;
+
+use itertools::Itertools;
+use linked_hash_map::LinkedHashMap;
+
+use {Coord, GBuilder, Gene, IdGenerator, Strand, Transcript, TBuilder, DEF_ID};
+use utils::{OptionDeref, open_path};
+
+
+/// Number of columns of BED12 rows.
+const NUM_COLS: usize = 12;
+
+quick_error! {
+    /// Errors that occur when reading or writing BED12 files.
+    #[derive(Debug)]
+    pub enum BedError {
+        /// Occurs when a row does not have 12 columns.
+        ColumnCount(count: usize) {
+            description("unexpected number of columns")
+            display(self_) -> ("{}: expected {}, found {}", self_.description(), NUM_COLS, count)
+        }
+        /// Occurs when a column value can not be parsed.
+        InvalidColumn(field: &'static str, value: String) {
+            description("invalid column value")
+            display(self_) -> ("{}: {}, value: {}", self_.description(), field, value)
+        }
+        /// Occurs when the block count does not match the number of block sizes or starts.
+        BlockCountMismatch(name: Option<String>) {
+            description("number of blocks and number of block coordinates are not equal")
+            display(self_) -> ("{}, name: {}",
+                               self_.description(), name.as_deref().unwrap_or(DEF_ID))
+        }
+        /// Errors propagated from reading the input.
+        Io(err: io::Error) {
+            description(err.description())
+            from()
+            cause(err)
+        }
+    }
+}
+
+/// BED12 reader.
+pub struct Reader<R: io::Read> {
+    inner: BufReader<R>,
+    buf: String,
+    num_lines: u64,
+    id_generator: Option<IdGenerator>,
+}
+
+impl<R: io::Read> Reader<R> {
+
+    /// Creates a BED12 reader from another reader.
+    pub fn from_reader(in_reader: R) -> Self {
+        Reader {
+            inner: BufReader::new(in_reader),
+            buf: String::new(),
+            num_lines: 0,
+            id_generator: None,
+        }
+    }
+
+    /// Sets the generator used for the identifiers of transcripts without names, and of their
+    /// genes.
+    ///
+    /// By default, such transcripts have no identifiers, and their genes are identified as
+    /// `<unknown>`.
+    pub fn id_generator(&mut self, generator: IdGenerator) -> &mut Self {
+        self.id_generator = Some(generator);
+        self
+    }
+
+    /// Creates an iterator of transcripts.
+    ///
+    /// Empty lines, comment lines, and `track` and `browser` lines are skipped.
+    pub fn transcripts(&mut self) -> BedTranscripts<R> {
+        BedTranscripts { reader: self }
+    }
+
+    /// Creates an iterator of genes, each consisting of a single transcript.
+    pub fn genes(&mut self) -> BedGenes<R> {
+        BedGenes { inner: self.transcripts() }
+    }
+
+    /// Reads the next transcript, returning `None` at the end of the input.
+    fn read_transcript(&mut self) -> Option<::Result<Transcript>> {
+        loop {
+            self.buf.clear();
+            match self.inner.read_line(&mut self.buf) {
+                Ok(0) => return None,
+                Ok(_) => self.num_lines += 1,
+                Err(err) => return Some(Err(::Error::from(BedError::from(err)))),
+            }
+            let line = self.buf.trim_right_matches(|c| c == '\n' || c == '\r');
+            if line.is_empty() || line.starts_with('#') || line.starts_with("track")
+                || line.starts_with("browser")
+            {
+                continue;
+            }
+            let num_lines = self.num_lines;
+            let mut trx = match parse_row(line) {
+                Ok(trx) => trx,
+                Err(err) => return Some(Err(::Error::at_line(num_lines, err))),
+            };
+            if let Some(ref mut generator) = self.id_generator {
+                generator.fill_transcript(&mut trx);
+            }
+            return Some(Ok(trx));
+        }
+    }
+}
+
+impl Reader<fs::File> {
+
+    /// Creates a BED12 reader that reads from the given path.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        fs::File::open(path).map(Reader::from_reader)
+    }
+}
+
+impl Reader<Box<io::Read>> {
+
+    /// Creates a BED12 reader that reads from the given path, which may be gzip or
+    /// bgzip-compressed.
+    ///
+    /// Compression is detected from the contents of the file, regardless of its extension.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        open_path(path).map(Reader::from_reader)
+    }
+}
+
+/// Iterator over transcripts created from BED12 rows.
+pub struct BedTranscripts<'a, R: 'a> where R: io::Read {
+    reader: &'a mut Reader<R>,
+}
+
+impl<'a, R> Iterator for BedTranscripts<'a, R> where R: io::Read {
+
+    type Item = ::Result<Transcript>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.reader.read_transcript()
+    }
+}
+
+/// Iterator over single-transcript genes created from BED12 rows.
+pub struct BedGenes<'a, R: 'a> where R: io::Read {
+    inner: BedTranscripts<'a, R>,
+}
+
+impl<'a, R> Iterator for BedGenes<'a, R> where R: io::Read {
+
+    type Item = ::Result<Gene>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|result| result.and_then(transcript_to_gene))
+    }
+}
+
+/// BED12 writer.
+pub struct Writer<W: Write> {
+    inner: W,
+}
+
+impl<W: Write> Writer<W> {
+
+    /// Creates a BED12 writer from another writer.
+    pub fn from_writer(in_writer: W) -> Writer<W> {
+        Writer { inner: in_writer }
+    }
+
+    /// Writes the given transcript as a single row.
+    ///
+    /// The coding region is written with its stop codon, and the thick start and end columns
+    /// of noncoding transcripts are both set to the transcript end. Transcripts without
+    /// identifiers are named `.`. Scores are rounded to the
+    /// nearest integer, with `0` written for transcripts without scores.
+    pub fn write_transcript(&mut self, transcript: &Transcript) -> ::Result<()> {
+        let start = transcript.start();
+        let strand_char = match transcript.strand() {
+            &Strand::Forward => '+',
+            &Strand::Reverse => '-',
+            &Strand::Unknown => '.',
+        };
+        let (thick_start, thick_end) = transcript.coding_coord(true)
+            .unwrap_or((transcript.end(), transcript.end()));
+        let score = transcript.score().map_or(0, |score| score.round() as i64);
+        let mut sizes = transcript.exons().iter().map(|exn| exn.end() - exn.start()).join(",");
+        sizes.push(',');
+        let mut offsets = transcript.exons().iter().map(|exn| exn.start() - start).join(",");
+        offsets.push(',');
+
+        writeln!(self.inner, "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t0\t{}\t{}\t{}",
+                 transcript.seq_name(), start, transcript.end(), transcript.id().unwrap_or("."),
+                 score, strand_char, thick_start, thick_end, transcript.exons().len(),
+                 sizes, offsets)
+            .map_err(|e| ::Error::from(BedError::from(e)))
+    }
+
+    /// Writes the given gene as multiple rows.
+    pub fn write_gene(&mut self, gene: &Gene) -> ::Result<()> {
+        for transcript in gene.transcripts().values() {
+            self.write_transcript(transcript)?;
+        }
+        Ok(())
+    }
+
+    /// Flushes the underlying writer.
+    pub fn flush(&mut self) -> ::Result<()> {
+        self.inner.flush().map_err(|e| ::Error::from(BedError::from(e)))
+    }
+}
+
+impl Writer<fs::File> {
+
+    /// Creates a BED12 writer that writes to the given path.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let f = fs::File::create(path)?;
+        Ok(Writer::from_writer(f))
+    }
+}
+
+/// Creates a transcript from the given BED12 row.
+fn parse_row(line: &str) -> ::Result<Transcript> {
+    let cols = line.split('\t').collect::<Vec<&str>>();
+    if cols.len() != NUM_COLS {
+        return Err(::Error::from(BedError::ColumnCount(cols.len())));
+    }
+    let start = parse_num(cols[1], "chromStart")?;
+    let end = parse_num(cols[2], "chromEnd")?;
+    let thick_start = parse_num(cols[6], "thickStart")?;
+    let thick_end = parse_num(cols[7], "thickEnd")?;
+    let block_count = parse_num(cols[9], "blockCount")? as usize;
+    let sizes = parse_list(cols[10], "blockSizes")?;
+    let offsets = parse_list(cols[11], "blockStarts")?;
+    let name = cols[3];
+    if sizes.len() != block_count || offsets.len() != block_count {
+        let name = if name.is_empty() { None } else { Some(name.to_owned()) };
+        return Err(::Error::from(BedError::BlockCountMismatch(name)));
+    }
+
+    let exon_coords = offsets.iter().zip(sizes.iter())
+        .map(|(&offset, &size)| (start + offset, start + offset + size))
+        .collect::<Vec<Coord<u64>>>();
+    let coding_coord =
+        if thick_start == thick_end {
+            None
+        } else {
+            Some((thick_start, thick_end))
+        };
+    let strand_char = match cols[5] {
+        "+" => '+',
+        "-" => '-',
+        "." => '.',
+        other => {
+            let err = BedError::InvalidColumn("strand", other.to_owned());
+            return Err(::Error::from(err));
+        },
+    };
+
+    let mut builder = TBuilder::new(cols[0], start, end)
+        .strand_char(strand_char)
+        .coords(exon_coords, coding_coord)
+        .coding_incl_stop(true);
+    if !name.is_empty() && name != "." {
+        builder = builder.id(name);
+    }
+    if let Ok(score) = cols[4].parse::<f64>() {
+        builder = builder.score(score);
+    }
+    builder.build()
+}
+
+/// Creates a gene consisting of the given transcript, identified by the gene identifier of the
+/// transcript if it has one, or by its own identifier otherwise.
+pub(crate) fn transcript_to_gene(transcript: Transcript) -> ::Result<Gene> {
+    let mut transcript = transcript;
+    let tid = transcript.id().unwrap_or(DEF_ID).to_owned();
+    let gid = transcript.gene_id().map(|id| id.to_owned()).unwrap_or_else(|| tid.clone());
+    if transcript.gene_id().is_none() {
+        transcript.set_gene_id(Some(gid.clone()));
+    }
+    let (start, end) = (transcript.start(), transcript.end());
+    let (seq_name, strand) = (transcript.seq_name().to_owned(), *transcript.strand());
+    let mut transcripts = LinkedHashMap::new();
+    let _ = transcripts.insert(tid, transcript);
+    GBuilder::new(seq_name, start, end)
+        .id(gid)
+        .strand(strand)
+        .transcripts(transcripts)
+        .build()
+}
+
+fn parse_num(value: &str, field: &'static str) -> ::Result<u64> {
+    value.parse::<u64>()
+        .map_err(|_| ::Error::from(BedError::InvalidColumn(field, value.to_owned())))
+}
+
+fn parse_list(value: &str, field: &'static str) -> ::Result<Vec<u64>> {
+    value.split(',')
+        .filter(|item| !item.is_empty())
+        .map(|item| parse_num(item, field))
+        .collect()
+}
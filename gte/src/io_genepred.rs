@@ -0,0 +1,1017 @@
+/*! Reader and writer for the genePred format and its extended variant.
+
+The genePred format is a transcript-oriented format used throughout the UCSC genome browser
+tooling. Each transcript is denoted in a single line with ten columns. The extended variant
+(genePredExt) appends five more columns: a score, a secondary name (usually the gene name or
+identifier), the completeness status of the coding region start and end, and the reading frame
+of each exon.
+
+A minimum specification of the columns can be found on
+[this page](https://genome.ucsc.edu/FAQ/FAQformat.html#format9).
+*/
+use std::cmp::{max, min};
+use std::convert::AsRef;
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::num::ParseIntError;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use csv;
+use itertools::{GroupBy, Group, Itertools};
+use linked_hash_map::LinkedHashMap;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use {Coord, DuplicatePolicy, Gene, GBuilder, ParseWarning, Strand, Transcript, TBuilder, DEF_ID,
+     INIT_COORD};
+use ExonFeatureKind::CDS;
+use model::insert_transcript;
+use utils::{OptionDeref, open_path_threads, update_seq_name};
+
+
+/// Number of columns in a genePred row.
+const NUM_COLS: usize = 10;
+
+/// Number of columns in an extended genePred row.
+const NUM_COLS_EXT: usize = 15;
+
+/// Exon frame value for exons without any coding region.
+const NO_FRAME: i8 = -1;
+
+quick_error! {
+    /// Errors that occur when reading or writing genePred files.
+    #[derive(Debug)]
+    pub enum GenePredError {
+        /// Occurs when a row has neither the genePred nor the extended genePred column count.
+        InvalidColumnCount(num_cols: usize) {
+            description("unexpected number of columns")
+            display(self_) -> ("{}: expected {} or {}, found {}",
+                               self_.description(), NUM_COLS, NUM_COLS_EXT, num_cols)
+        }
+        /// Occurs when the value of the number of exons column, the number of exon start
+        /// coordinates, the number of exon end coordinates, and/or the number of exon frames
+        /// are not the same.
+        ExonCountMismatch(tid: Option<String>) {
+            description("number of exons and number of exon coordinates are not equal")
+            display(self_) -> ("{}, transcript ID: {}",
+                               self_.description(), tid.as_deref().unwrap_or(DEF_ID))
+        }
+        /// Indicates a duplicate transcript identifier with the same gene identifier.
+        DuplicateTranscriptId(gid: Option<String>) {
+            description("gene has multiple transcripts with the same identifier")
+            display(self_) -> ("{}, gene ID: {}",
+                               self_.description(), gid.as_deref().unwrap_or(DEF_ID))
+        }
+        /// Occurs when genes are requested from records without gene identifiers.
+        MissingGeneId {
+            description("gene identifier column has no value")
+        }
+        /// Occurs when the transcript identifier column is empty.
+        MissingTranscriptId {
+            description("transcript identifier column has no value")
+        }
+        /// Occurs when the strand column does not contain exactly one character.
+        InvalidStrand(tid: Option<String>) {
+            description("strand column must contain exactly one character")
+            display(self_) -> ("{}, transcript ID: {}",
+                               self_.description(), tid.as_deref().unwrap_or(DEF_ID))
+        }
+        /// Occurs when any of the numeric columns is not a valid integer value.
+        InvalidNumber(err: ParseIntError, field: &'static str, tid: Option<String>) {
+            description(err.description())
+            display(self_) -> ("{}, field: {}, transcript ID: {}",
+                               self_.description(), field, tid.as_deref().unwrap_or(DEF_ID))
+            cause(err)
+        }
+        /// Occurs when the coding region status column has an unknown value.
+        InvalidCdsStat(value: String) {
+            description("invalid coding region completeness status")
+            display(self_) -> ("{}: '{}'", self_.description(), value)
+        }
+        /// Errors propagated from the underlying `csv` crate.
+        Csv(err: csv::Error) {
+            description(err.description())
+            from()
+            cause(err)
+        }
+    }
+}
+
+/// Completeness status of a coding region end in extended genePred records.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde_impl", derive(Serialize, Deserialize))]
+pub enum CdsStat {
+    /// No coding region is defined.
+    None,
+    /// Completeness is unknown.
+    Unknown,
+    /// The coding region end is incomplete.
+    Incomplete,
+    /// The coding region end is complete.
+    Complete,
+}
+
+impl CdsStat {
+
+    /// Returns the string representation of the status, as used in genePred files.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            &CdsStat::None => "none",
+            &CdsStat::Unknown => "unk",
+            &CdsStat::Incomplete => "incmpl",
+            &CdsStat::Complete => "cmpl",
+        }
+    }
+}
+
+impl FromStr for CdsStat {
+
+    type Err = GenePredError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(CdsStat::None),
+            "unk" => Ok(CdsStat::Unknown),
+            "incmpl" => Ok(CdsStat::Incomplete),
+            "cmpl" => Ok(CdsStat::Complete),
+            otherwise => Err(GenePredError::InvalidCdsStat(otherwise.to_owned())),
+        }
+    }
+}
+
+impl fmt::Display for CdsStat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Values of the additional columns present in extended genePred records.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde_impl", derive(Serialize, Deserialize))]
+pub struct GenePredExt {
+    score: i64,
+    name2: String,
+    cds_start_stat: CdsStat,
+    cds_end_stat: CdsStat,
+    exon_frames: Vec<i8>,
+}
+
+impl GenePredExt {
+
+    /// Creates the extended values from their components.
+    pub fn new<T>(score: i64, name2: T, cds_start_stat: CdsStat, cds_end_stat: CdsStat,
+                  exon_frames: Vec<i8>) -> Self
+        where T: Into<String>
+    {
+        GenePredExt {
+            score: score,
+            name2: name2.into(),
+            cds_start_stat: cds_start_stat,
+            cds_end_stat: cds_end_stat,
+            exon_frames: exon_frames,
+        }
+    }
+
+    /// Returns the score.
+    pub fn score(&self) -> i64 {
+        self.score
+    }
+
+    /// Returns the secondary name, which is usually the gene identifier.
+    pub fn name2(&self) -> &str {
+        self.name2.as_str()
+    }
+
+    /// Returns the completeness status of the genome-wise 5'-most coding region end.
+    pub fn cds_start_stat(&self) -> CdsStat {
+        self.cds_start_stat
+    }
+
+    /// Returns the completeness status of the genome-wise 3'-most coding region end.
+    pub fn cds_end_stat(&self) -> CdsStat {
+        self.cds_end_stat
+    }
+
+    /// Returns a slice of the exon frames.
+    ///
+    /// Each value is the position within its codon of the first coding base of the exon, in
+    /// the transcript direction, or `-1` if the exon has no coding region.
+    pub fn exon_frames(&self) -> &[i8] {
+        self.exon_frames.as_slice()
+    }
+}
+
+/// GenePred record type.
+///
+/// This type represents the information present in a genePred record. Records read from
+/// extended genePred files additionally carry a `GenePredExt` value.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde_impl", derive(Serialize, Deserialize))]
+pub struct GenePredRecord {
+    transcript_id: String,
+    seq_name: String,
+    strand: char,
+    transcript_start: u64,
+    transcript_end: u64,
+    coding_start: u64,
+    coding_end: u64,
+    exon_starts: Vec<u64>,
+    exon_ends: Vec<u64>,
+    ext: Option<GenePredExt>,
+}
+
+impl GenePredRecord {
+
+    /// Returns the transcript identifier.
+    pub fn transcript_id(&self) -> &str {
+        self.transcript_id.as_str()
+    }
+
+    /// Sets the transcript identifier.
+    pub fn set_transcript_id<T>(&mut self, transcript_id: T)
+        where T: Into<String>
+    {
+        self.transcript_id = transcript_id.into();
+    }
+
+    /// Returns the gene identifier.
+    ///
+    /// This is the secondary name column of extended records, if it is nonempty.
+    pub fn gene_id(&self) -> Option<&str> {
+        self.ext.as_ref()
+            .map(|ext| ext.name2())
+            .and_then(|name2| if name2.is_empty() { None } else { Some(name2) })
+    }
+
+    /// Returns the sequence name.
+    pub fn seq_name(&self) -> &str {
+        self.seq_name.as_str()
+    }
+
+    /// Sets the sequence name.
+    pub fn set_seq_name<T>(&mut self, seq_name: T)
+        where T: Into<String>
+    {
+        self.seq_name = seq_name.into();
+    }
+
+    /// Returns the strand.
+    pub fn strand(&self) -> char {
+        self.strand
+    }
+
+    /// Returns the genome-wise 5'-most transcript coordinate of the record.
+    pub fn transcript_start(&self) -> u64 {
+        self.transcript_start
+    }
+
+    /// Returns the genome-wise 3'-most transcript coordinate of the record.
+    pub fn transcript_end(&self) -> u64 {
+        self.transcript_end
+    }
+
+    /// Returns the genome-wise 5'-most coding region coordinate of the record.
+    ///
+    /// This includes the stop codon coordinate on minus strand records.
+    pub fn coding_start(&self) -> u64 {
+        self.coding_start
+    }
+
+    /// Returns the genome-wise 3'-most coding region coordinate of the record.
+    ///
+    /// This includes the stop codon coordinate on plus strand records.
+    pub fn coding_end(&self) -> u64 {
+        self.coding_end
+    }
+
+    /// Returns the number of exons contained within the record.
+    pub fn num_exons(&self) -> usize {
+        self.exon_starts.len() // must be the same as exon_ends
+    }
+
+    /// Returns a slice of the genome-wise 5'-most coordinates of exons in the record.
+    pub fn exon_starts(&self) -> &[u64] {
+        self.exon_starts.as_slice()
+    }
+
+    /// Returns a slice of the genome-wise 3'-most coordinates of the exons in the record.
+    pub fn exon_ends(&self) -> &[u64] {
+        self.exon_ends.as_slice()
+    }
+
+    /// Returns the extended column values, if the record is an extended record.
+    pub fn ext(&self) -> Option<&GenePredExt> {
+        self.ext.as_ref()
+    }
+
+    /// Sets the extended column values and returns the old value.
+    ///
+    /// An error type will be returned if the number of exon frames differ from the number of
+    /// exons.
+    pub fn set_ext(&mut self, ext: Option<GenePredExt>) -> ::Result<Option<GenePredExt>> {
+        if let Some(ref e) = ext {
+            if e.exon_frames.len() != self.num_exons() {
+                let tid = self.transcript_id.clone();
+                return Err(::Error::from(GenePredError::ExonCountMismatch(Some(tid))));
+            }
+        }
+        Ok(::std::mem::replace(&mut self.ext, ext))
+    }
+
+    /// Creates a record from the string values of a genePred or an extended genePred row.
+    pub fn try_from_fields(fields: Vec<String>) -> ::Result<Self> {
+
+        let num_cols = fields.len();
+        if num_cols != NUM_COLS && num_cols != NUM_COLS_EXT {
+            return Err(::Error::from(GenePredError::InvalidColumnCount(num_cols)));
+        }
+
+        let mut fields = fields.into_iter();
+        // The unwraps are safe here since we have checked the number of fields.
+        let transcript_id = fields.next().unwrap();
+        let seq_name = fields.next().unwrap();
+
+        let tid_owned = transcript_id.clone();
+        let tid = Some(tid_owned.as_str());
+        let raw_strand = fields.next().unwrap();
+        let mut strand_chars = raw_strand.chars();
+        let strand = match (strand_chars.next(), strand_chars.next()) {
+            (Some(c), None) => c,
+            _ => return Err(::Error::from(GenePredError::InvalidStrand(
+                tid.map(|v| v.to_owned())))),
+        };
+
+        let transcript_start = parse_num::<u64>(&fields.next().unwrap(), "txStart", tid)?;
+        let transcript_end = parse_num::<u64>(&fields.next().unwrap(), "txEnd", tid)?;
+        let coding_start = parse_num::<u64>(&fields.next().unwrap(), "cdsStart", tid)?;
+        let coding_end = parse_num::<u64>(&fields.next().unwrap(), "cdsEnd", tid)?;
+        let num_exons = parse_num::<usize>(&fields.next().unwrap(), "exonCount", tid)?;
+        let exon_starts = parse_list::<u64>(&fields.next().unwrap(), "exonStarts", tid)?;
+        let exon_ends = parse_list::<u64>(&fields.next().unwrap(), "exonEnds", tid)?;
+
+        if exon_starts.len() != num_exons || exon_starts.len() != exon_ends.len() {
+            let err = GenePredError::ExonCountMismatch(tid.map(|v| v.to_owned()));
+            return Err(::Error::from(err));
+        }
+
+        let ext =
+            if num_cols == NUM_COLS_EXT {
+                let score = parse_num::<i64>(&fields.next().unwrap(), "score", tid)?;
+                let name2 = fields.next().unwrap();
+                let cds_start_stat = CdsStat::from_str(&fields.next().unwrap())?;
+                let cds_end_stat = CdsStat::from_str(&fields.next().unwrap())?;
+                let exon_frames = parse_list::<i8>(&fields.next().unwrap(), "exonFrames", tid)?;
+                if exon_frames.len() != num_exons {
+                    let err = GenePredError::ExonCountMismatch(tid.map(|v| v.to_owned()));
+                    return Err(::Error::from(err));
+                }
+                Some(GenePredExt::new(score, name2, cds_start_stat, cds_end_stat, exon_frames))
+            } else {
+                None
+            };
+
+        Ok(GenePredRecord {
+            transcript_id: transcript_id,
+            seq_name: seq_name,
+            strand: strand,
+            transcript_start: transcript_start,
+            transcript_end: transcript_end,
+            coding_start: coding_start,
+            coding_end: coding_end,
+            exon_starts: exon_starts,
+            exon_ends: exon_ends,
+            ext: ext,
+        })
+    }
+
+    /// Transforms the record into a transcript.
+    ///
+    /// The gene identifier of the transcript is set from the secondary name column of
+    /// extended records.
+    pub fn into_transcript(self) -> ::Result<Transcript> {
+
+        if self.transcript_id.is_empty() {
+            return Err(::Error::from(GenePredError::MissingTranscriptId));
+        }
+        let coding_interval =
+            if self.coding_start == self.coding_end {
+                None
+            } else {
+                Some((self.coding_start, self.coding_end))
+            };
+        let gene_id = self.gene_id().map(|gid| gid.to_owned());
+        // the status columns are genome-wise, so their ends are swapped on minus strand records
+        let incomplete = self.ext.as_ref()
+            .map(|ext| {
+                let start = ext.cds_start_stat == CdsStat::Incomplete;
+                let end = ext.cds_end_stat == CdsStat::Incomplete;
+                if self.strand == '-' { (end, start) } else { (start, end) }
+            })
+            .unwrap_or((false, false));
+
+        let exon_coords = self.exon_starts.into_iter().zip(self.exon_ends.into_iter())
+            .collect::<Vec<Coord<u64>>>();
+
+        let btrx = TBuilder::new(self.seq_name, self.transcript_start, self.transcript_end)
+            .id(self.transcript_id)
+            .strand_char(self.strand)
+            .coords(exon_coords, coding_interval)
+            .coding_incl_stop(true)
+            .cds_5p_incomplete(incomplete.0)
+            .cds_3p_incomplete(incomplete.1);
+
+        match gene_id {
+            Some(gid) => btrx.gene_id(gid).build(),
+            None => btrx.build(),
+        }
+    }
+}
+
+/// Helper function for parsing numeric column values.
+#[inline]
+fn parse_num<T>(raw: &str, field: &'static str, tid: Option<&str>) -> Result<T, GenePredError>
+    where T: FromStr<Err=ParseIntError>
+{
+    T::from_str(raw)
+        .map_err(|e| GenePredError::InvalidNumber(e, field, tid.map(|v| v.to_owned())))
+}
+
+/// Helper function for parsing comma-separated numeric column values.
+#[inline]
+fn parse_list<T>(raw: &str, field: &'static str, tid: Option<&str>)
+    -> Result<Vec<T>, GenePredError>
+    where T: FromStr<Err=ParseIntError>
+{
+    let trimmed = raw.trim_matches(',');
+    if trimmed.is_empty() {
+        return Ok(vec![]);
+    }
+    let mut res = vec![];
+    for item in trimmed.split(',') {
+        res.push(parse_num(item, field, tid)?);
+    }
+    Ok(res)
+}
+
+/// GenePred reader.
+///
+/// The reader accepts both genePred and extended genePred rows.
+pub struct Reader<R: io::Read> {
+    inner: csv::Reader<R>,
+    seq_name_prefix: Option<String>,
+    seq_name_lstrip: Option<String>,
+    lenient: bool,
+    duplicate_policy: DuplicatePolicy,
+    num_lines: u64,
+    warnings: Vec<ParseWarning>,
+    threads: Arc<AtomicUsize>,
+}
+
+impl<R: io::Read> Reader<R> {
+
+    /// Creates a genePred reader from another reader.
+    pub fn from_reader(in_reader: R) -> Reader<R> {
+        Reader {
+            inner: csv::Reader::from_reader(in_reader)
+                .delimiter(b'\t')
+                .has_headers(false)
+                .flexible(true),
+            seq_name_prefix: None,
+            seq_name_lstrip: None,
+            lenient: false,
+            duplicate_policy: DuplicatePolicy::default(),
+            num_lines: 0,
+            warnings: Vec::new(),
+            threads: Arc::new(AtomicUsize::new(1)),
+        }
+    }
+
+    /// Sets the reader to add the given prefix to all sequence names.
+    pub fn seq_name_prefix<T>(&mut self, prefix: T) -> &mut Self
+        where T: Into<String>
+    {
+        self.seq_name_prefix = Some(prefix.into());
+        self
+    }
+
+    /// Sets the reader to trim the given string from all sequence names if present at the
+    /// beginning.
+    pub fn seq_name_lstrip<T>(&mut self, lstrip: T) -> &mut Self
+        where T: Into<String>
+    {
+        self.seq_name_lstrip = Some(lstrip.into());
+        self
+    }
+
+    /// Sets the number of threads used for decompressing bgzip-compressed input.
+    ///
+    /// This only has an effect on readers created with `from_path` from bgzip-compressed files,
+    /// and must be set before any record is read. By default, input is decompressed on the
+    /// calling thread.
+    pub fn threads(&mut self, threads: usize) -> &mut Self {
+        self.threads.store(max(threads, 1), Ordering::SeqCst);
+        self
+    }
+
+    /// Sets the reader to skip malformed records instead of yielding errors.
+    ///
+    /// Each skipped record is stored as a warning, which can be retrieved using the `warnings`
+    /// method. Transcript streams also skip records that can not be turned into transcripts,
+    /// while gene streams only skip records that can not be parsed.
+    pub fn lenient(&mut self, lenient: bool) -> &mut Self {
+        self.lenient = lenient;
+        self
+    }
+
+    /// Sets how gene streams handle transcripts with the same identifier within a gene.
+    ///
+    /// By default, such transcripts result in an error.
+    pub fn duplicate_transcripts(&mut self, policy: DuplicatePolicy) -> &mut Self {
+        self.duplicate_policy = policy;
+        self
+    }
+
+    /// Returns the warnings of the records skipped in lenient mode.
+    pub fn warnings(&self) -> &[ParseWarning] {
+        self.warnings.as_slice()
+    }
+
+    /// Creates an iterator of genePred records.
+    pub fn records_stream(&mut self) -> GenePredRecordsStream<R> {
+        GenePredRecordsStream {
+            inner: self.inner.records(),
+            seq_name_prefix: self.seq_name_prefix.as_deref(),
+            seq_name_lstrip: self.seq_name_lstrip.as_deref(),
+            lenient: self.lenient,
+            num_lines: &mut self.num_lines,
+            warnings: &mut self.warnings,
+        }
+    }
+
+    /// Creates an iterator of transcripts.
+    pub fn transcripts_stream(&mut self) -> GenePredTranscriptsStream<R> {
+        GenePredTranscriptsStream {
+            inner: self.records_stream()
+        }
+    }
+
+    /// Creates an iterator of genes.
+    ///
+    /// This iterator groups consecutive records based on their gene identifiers into genes.
+    /// Since only extended records have gene identifiers, any non-extended record will result
+    /// in an error.
+    pub fn genes_stream(&mut self) -> GenePredGenesStream<R> {
+        let policy = self.duplicate_policy;
+        GenePredGenesStream {
+            inner: self.records_stream()
+                .group_by(GenePredGenesStream::<R>::group_func),
+            policy: policy,
+        }
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<R: io::Read> Reader<R> {
+
+    /// Reads all genes, creating their transcripts in parallel.
+    ///
+    /// Records are read and grouped into genes sequentially, as in `genes_stream`, after which
+    /// the transcripts and genes of all groups are created across multiple threads. The genes
+    /// are returned in the same order as they appear in the input.
+    pub fn par_genes(&mut self) -> Vec<::Result<Gene>> {
+        let mut groups: Vec<::Result<((Option<String>, String, char), Vec<GenePredRecord>)>> =
+            Vec::new();
+        for result in self.records_stream() {
+            let record = match result {
+                Ok(rec) => rec,
+                Err(err) => {
+                    groups.push(Err(err));
+                    continue;
+                },
+            };
+            let gid = record.gene_id().map(|gid| gid.to_owned());
+            let key = (gid, record.seq_name.clone(), record.strand);
+            if let Some(&mut Ok((ref group_key, ref mut records))) = groups.last_mut() {
+                if *group_key == key {
+                    records.push(record);
+                    continue;
+                }
+            }
+            groups.push(Ok((key, vec![record])));
+        }
+        let policy = self.duplicate_policy;
+        groups.into_par_iter()
+            .map(|group| group.and_then(|((gid, seq_name, strand_char), records)| {
+                match gid {
+                    Some(gid) => records_to_gene(gid, seq_name, strand_char,
+                                                 records.into_iter().map(Ok), policy),
+                    None => Err(::Error::from(GenePredError::MissingGeneId)),
+                }
+            }))
+            .collect()
+    }
+}
+
+impl Reader<fs::File> {
+
+    /// Creates a genePred reader that reads from the given path.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        fs::File::open(path).map(Reader::from_reader)
+    }
+}
+
+impl Reader<Box<io::Read>> {
+
+    /// Creates a genePred reader that reads from the given path, which may be gzip or
+    /// bgzip-compressed.
+    ///
+    /// Compression is detected from the contents of the file, regardless of its extension.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let threads = Arc::new(AtomicUsize::new(1));
+        open_path_threads(path, threads.clone()).map(|file| {
+            let mut reader = Reader::from_reader(file);
+            reader.threads = threads;
+            reader
+        })
+    }
+}
+
+/// Iterator over genePred records.
+pub struct GenePredRecordsStream<'a, R: 'a> where R: io::Read {
+    inner: csv::StringRecords<'a, R>,
+    seq_name_prefix: Option<&'a str>,
+    seq_name_lstrip: Option<&'a str>,
+    lenient: bool,
+    num_lines: &'a mut u64,
+    warnings: &'a mut Vec<ParseWarning>,
+}
+
+impl<'a, R> GenePredRecordsStream<'a, R> where R: io::Read {
+
+    /// Stores the given error as a warning of the last read line if the stream is lenient, or
+    /// returns it with the line number otherwise.
+    fn skip_or_yield<T>(&mut self, err: ::Error) -> Option<::Result<T>> {
+        if self.lenient {
+            self.warnings.push(ParseWarning::new(*self.num_lines, err));
+            None
+        } else {
+            Some(Err(::Error::at_line(*self.num_lines, err)))
+        }
+    }
+}
+
+impl<'a, R> Iterator for GenePredRecordsStream<'a, R> where R: io::Read {
+
+    type Item = ::Result<GenePredRecord>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let lstrip = self.seq_name_lstrip.map(|v| (v, v.len()));
+        let prefix = self.seq_name_prefix;
+        loop {
+            let result = match self.inner.next() {
+                Some(fields) => fields
+                    .or_else(|err| Err(::Error::from(GenePredError::from(err))))
+                    .and_then(GenePredRecord::try_from_fields)
+                    .map(|mut rec| {
+                        update_seq_name(&mut rec.seq_name, prefix, lstrip);
+                        rec
+                    }),
+                None => return None,
+            };
+            *self.num_lines += 1;
+            match result {
+                Ok(record) => return Some(Ok(record)),
+                Err(err) => {
+                    let yielded = self.skip_or_yield(err);
+                    if yielded.is_some() {
+                        return yielded;
+                    }
+                },
+            }
+        }
+    }
+}
+
+/// Iterator over transcripts created from genePred records.
+pub struct GenePredTranscriptsStream<'a, R: 'a> where R: io::Read {
+    inner: GenePredRecordsStream<'a, R>,
+}
+
+impl<'a, R> Iterator for GenePredTranscriptsStream<'a, R> where R: io::Read {
+
+    type Item = ::Result<Transcript>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let result = match self.inner.next() {
+                Some(record) => record.and_then(|rec| rec.into_transcript()),
+                None => return None,
+            };
+            match result {
+                Ok(transcript) => return Some(Ok(transcript)),
+                Err(err) => {
+                    let yielded = self.inner.skip_or_yield(err);
+                    if yielded.is_some() {
+                        return yielded;
+                    }
+                },
+            }
+        }
+    }
+}
+
+/// The type used for grouping records into genes.
+///
+/// The tuple elements represent gene identifier, sequence name, and strand. The gene
+/// identifier is `None` for non-extended records.
+type GroupKey = Option<(Option<String>, String, char)>;
+
+/// The type of the function used for creating record-grouping keys for genes.
+type GroupFunc = fn(&::Result<GenePredRecord>) -> GroupKey;
+
+/// The type of the grouped records for creating genes.
+type GroupedRecords<'a, 'b, R> = Group<'b, GroupKey, GenePredRecordsStream<'a, R>, GroupFunc>;
+
+/// Iterator over genes created from extended genePred records.
+pub struct GenePredGenesStream<'a, R: 'a> where R: io::Read, {
+    inner: GroupBy<GroupKey, GenePredRecordsStream<'a, R>, GroupFunc>,
+    policy: DuplicatePolicy,
+}
+
+impl<'a, R> GenePredGenesStream<'a, R> where R: io::Read {
+
+    /// Creates the group key from the given genePred record result.
+    fn group_func(result: &::Result<GenePredRecord>) -> GroupKey {
+        result.as_ref().ok()
+            .map(|ref rec| (rec.gene_id().map(|gid| gid.to_owned()),
+                            rec.seq_name.clone(), rec.strand))
+    }
+
+    /// Creates genes from the given grouped records.
+    fn group_to_gene<'b>(group: (GroupKey, GroupedRecords<'a, 'b, R>), policy: DuplicatePolicy)
+        -> ::Result<Gene>
+    {
+        let (group_key, records) = group;
+        match group_key {
+
+            None => Err(records.filter_map(|x| x.err()).next().unwrap()),
+
+            Some((None, _, _)) => Err(::Error::from(GenePredError::MissingGeneId)),
+
+            Some((Some(gid), seq_name, strand_char)) =>
+                records_to_gene(gid, seq_name, strand_char, records, policy),
+        }
+    }
+}
+
+impl<'a, R> Iterator for GenePredGenesStream<'a, R> where R: io::Read {
+
+    type Item = ::Result<Gene>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let policy = self.policy;
+        self.inner.into_iter().next().map(|group| Self::group_to_gene(group, policy))
+    }
+}
+
+/// Creates a gene from the given records, which must all have the same gene identifier,
+/// sequence name, and strand.
+fn records_to_gene<I>(
+    gid: String,
+    seq_name: String,
+    strand_char: char,
+    records: I,
+    policy: DuplicatePolicy,
+) -> ::Result<Gene>
+    where I: IntoIterator<Item=::Result<GenePredRecord>>
+{
+    let mut transcripts = LinkedHashMap::new();
+    for record in records {
+        let transcript = record.and_then(|rec| rec.into_transcript())?;
+        let tid = transcript.id().map(|id| id.to_owned())
+            .ok_or(::Error::from(GenePredError::MissingTranscriptId))?;
+        if !insert_transcript(&mut transcripts, tid, transcript, policy) {
+            let err = GenePredError::DuplicateTranscriptId(Some(gid));
+            return Err(::Error::from(err));
+        }
+    }
+    let (gene_start, gene_end) = transcripts.values()
+        .fold(INIT_COORD, |(start, end), trx| (min(start, trx.start()), max(end, trx.end())));
+    GBuilder::new(seq_name, gene_start, gene_end)
+        .id(gid)
+        .strand_char(strand_char)
+        .transcripts(transcripts)
+        .transcript_coding_incl_stop(true)
+        .build()
+}
+
+/// GenePred writer.
+///
+/// By default, the writer writes non-extended genePred rows.
+pub struct Writer<W: io::Write> {
+    inner: csv::Writer<W>,
+    extended: bool,
+    coding_incl_stop: bool,
+}
+
+impl<W: io::Write> Writer<W> {
+
+    /// Creates a genePred writer from another writer.
+    pub fn from_writer(in_writer: W) -> Writer<W> {
+        Writer {
+            inner: csv::Writer::from_writer(in_writer)
+                .delimiter(b'\t')
+                .quote_style(csv::QuoteStyle::Never),
+            extended: false,
+            coding_incl_stop: true,
+        }
+    }
+
+    /// Sets the writer to write extended genePred rows.
+    pub fn extended(&mut self, extended: bool) -> &mut Self {
+        self.extended = extended;
+        self
+    }
+
+    /// Sets whether the coding region of written transcripts includes the stop codon.
+    ///
+    /// The default is `true`, following the UCSC convention. Transcripts with unknown strands
+    /// are always written with their stop codons, since the end of their coding regions can
+    /// not be determined.
+    pub fn coding_incl_stop(&mut self, coding_incl_stop: bool) -> &mut Self {
+        self.coding_incl_stop = coding_incl_stop;
+        self
+    }
+
+    /// Writes the given record.
+    ///
+    /// When writing extended rows, records without extended values are written with a zero
+    /// score, an empty secondary name, and completeness statuses and exon frames computed
+    /// from their coordinates.
+    pub fn write_record(&mut self, record: &GenePredRecord) -> ::Result<()> {
+        let mut fields = vec![
+            record.transcript_id.clone(), record.seq_name.clone(), record.strand.to_string(),
+            record.transcript_start.to_string(), record.transcript_end.to_string(),
+            record.coding_start.to_string(), record.coding_end.to_string(),
+            record.num_exons().to_string(),
+            join_list(record.exon_starts.iter()), join_list(record.exon_ends.iter())];
+
+        if self.extended {
+            match record.ext {
+                Some(ref ext) => {
+                    fields.push(ext.score.to_string());
+                    fields.push(ext.name2.clone());
+                    fields.push(ext.cds_start_stat.to_string());
+                    fields.push(ext.cds_end_stat.to_string());
+                    fields.push(join_list(ext.exon_frames.iter()));
+                },
+                None => {
+                    let transcript = record.clone().into_transcript()?;
+                    let (start_stat, end_stat, exon_frames) = transcript.genepred_frames_field();
+                    fields.push("0".to_owned());
+                    fields.push(String::new());
+                    fields.push(start_stat.to_string());
+                    fields.push(end_stat.to_string());
+                    fields.push(exon_frames);
+                },
+            }
+        }
+
+        self.inner
+            .write(fields.into_iter())
+            .map_err(|e| ::Error::from(GenePredError::from(e)))
+    }
+
+    /// Writes the given transcript as a single row.
+    ///
+    /// When writing extended rows, the gene identifier of the transcript is used as the
+    /// secondary name and the exon frames are computed from the transcript's coding features.
+    pub fn write_transcript(&mut self, transcript: &Transcript) -> ::Result<()> {
+        let transcript_name = transcript.id()
+            .ok_or(::Error::GenePred(GenePredError::MissingTranscriptId))?;
+        let strand_char = match transcript.strand() {
+            &Strand::Forward => '+',
+            &Strand::Reverse => '-',
+            &Strand::Unknown => '.',
+        };
+
+        let (coding_start, coding_end) = transcript.coding_coord(self.coding_incl_stop)
+            .or_else(|| transcript.coding_coord(true))
+            .unwrap_or((transcript.end(), transcript.end()));
+
+        let mut fields = vec![
+            transcript_name.to_owned(), transcript.seq_name().to_owned(),
+            strand_char.to_string(),
+            transcript.start().to_string(), transcript.end().to_string(),
+            coding_start.to_string(), coding_end.to_string(),
+            transcript.exons().len().to_string(),
+            join_list(transcript.exons().iter().map(|exon| exon.start())),
+            join_list(transcript.exons().iter().map(|exon| exon.end()))];
+
+        if self.extended {
+            let (start_stat, end_stat, exon_frames) = transcript.genepred_frames_field();
+            fields.push("0".to_owned());
+            fields.push(transcript.gene_id().unwrap_or("").to_owned());
+            fields.push(start_stat.to_string());
+            fields.push(end_stat.to_string());
+            fields.push(exon_frames);
+        }
+
+        self.inner
+            .write(fields.into_iter())
+            .map_err(|e| ::Error::from(GenePredError::from(e)))
+    }
+
+    /// Writes the given gene as multiple rows.
+    pub fn write_gene(&mut self, gene: &Gene) -> ::Result<()> {
+        for transcript in gene.transcripts().values() {
+            self.write_transcript(&transcript)?;
+        }
+        Ok(())
+    }
+}
+
+/// Helper function for creating comma-separated column values.
+#[inline]
+fn join_list<T, I>(items: I) -> String
+    where T: ToString, I: Iterator<Item=T>
+{
+    let mut joined = items.map(|item| item.to_string()).join(",");
+    joined.push(',');
+    joined
+}
+
+impl Transcript {
+
+    /// Returns the genome-wise start and end coding region statuses and the string value of the
+    /// exon frames column.
+    ///
+    /// The exon frames are derived from the frames of the CDS features of each exon, which are
+    /// computed from the CDS coordinates using `fill_coding_frames` if unknown.
+    fn genepred_frames_field(&self) -> (CdsStat, CdsStat, String) {
+        let filled;
+        let trx =
+            if self.has_unknown_frames() {
+                let mut copy = self.clone();
+                copy.fill_coding_frames();
+                filled = copy;
+                &filled
+            } else {
+                self
+            };
+        let exon_frames = trx.exons().iter()
+            .map(|exon| {
+                exon.features().iter()
+                    .filter_map(|fx| match fx.kind() {
+                        &CDS { frame: Some(f) } => Some(((3 - f) % 3) as i8),
+                        _ => None,
+                    })
+                    .next()
+                    .unwrap_or(NO_FRAME)
+            });
+        let stat = |incomplete| {
+            if self.coding_coord(true).is_none() {
+                CdsStat::None
+            } else if incomplete {
+                CdsStat::Incomplete
+            } else {
+                CdsStat::Complete
+            }
+        };
+        let (stat_5p, stat_3p) = (stat(self.cds_5p_incomplete()), stat(self.cds_3p_incomplete()));
+        let (start_stat, end_stat) = match *self.strand() {
+            Strand::Reverse => (stat_3p, stat_5p),
+            _ => (stat_5p, stat_3p),
+        };
+        (start_stat, end_stat, join_list(exon_frames))
+    }
+}
+
+impl Writer<fs::File> {
+
+    /// Creates a genePred writer that writes to the given path.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let f = fs::File::create(path)?;
+        Ok(Writer::from_writer(f))
+    }
+}
+
+impl Writer<Vec<u8>> {
+
+    /// Creates a genePred writer that writes to an in-memory buffer.
+    ///
+    /// The initial capacity of the buffer is 64 KiB.
+    pub fn from_memory() -> Writer<Vec<u8>> {
+        Writer::from_writer(Vec::with_capacity(1024 * 64))
+    }
+
+    /// Returns the values of the in-memory buffer as a string.
+    pub fn as_string(&mut self) -> &str {
+        self.inner.as_string()
+    }
+}
@@ -1,4 +1,4 @@
-/*! Reader for GFF format variants.
+/*! Reader and writer for GFF format variants.
 
 The GFF format is a feature-oriented format that is commonly used to store gene annotation data.
 
@@ -6,24 +6,44 @@ An unofficial specification of the formats can be found [here](http://mblab.wust
 or [here](http://www.ensembl.org/info/website/upload/gff.html).
 
 The reader provided by this module is based on a modified version of the GFF reader provided by
-the [rust-bio](https://github.com/rust-bio/rust-bio) library.
+the [rust-bio](https://github.com/rust-bio/rust-bio) library, whose writer is used for writing
+the records created from genes and transcripts.
 */
 use std::cmp::{max, min};
+use std::collections::{HashMap, VecDeque};
 use std::convert::AsRef;
 use std::error::Error;
-use std::io;
+use std::io::{self, BufRead};
 use std::fs;
-use std::path::Path;
+use std::iter;
+use std::mem;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::vec;
 
 use bio::io::gff::{self, GffType};
+use bio::utils::Interval;
 use itertools::{GroupBy, Group, Itertools};
+use linked_hash_map::LinkedHashMap;
 use multimap::MultiMap;
-use regex::{Error as RegexError, Regex};
-
-use {Coord, Exon, ExonFeatureKind as EFK, Gene, Strand, TBuilder, Transcript,
-     RawTrxCoords, INIT_START, INIT_END, INIT_COORD, DEF_ID};
-use utils::{OptionDeref, update_seq_name};
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+use regex::{self, Error as RegexError, Regex};
+
+use {Attributes, Coord, Exon, ExonFeatureKind as EFK, Gene, GBuilder, ParseWarning, SeqName,
+     SeqNamePool, Strand, StrandPolicy, TBuilder, Transcript, RawTrxCoords, INIT_START, INIT_END,
+     INIT_COORD, DEF_ID};
+use feature_tree::{FeatureNode, FeatureTree};
+use model::{is_readthrough_tag, strand_ord};
+use regulatory::{RegulatoryFeature, RegulatoryKind};
+use ops::merged_coords;
+use progress::{CancelToken, Cancellation, Observer, Tracker};
+use tabix::{self, TabixError, TabixIndex};
+use utils::{OptionDeref, open_path_threads, update_seq_name};
+#[cfg(feature = "remote")]
+use utils::open_url;
 
 
 /// Name for gene features.
@@ -59,6 +79,31 @@ const GENE_ID_STR: &'static str = "gene_id";
 /// Name for attribute key of transcript identifiers.
 const TRANSCRIPT_ID_STR: &'static str = "transcript_id";
 
+/// Name for attribute key of exon numbers.
+const EXON_NUMBER_STR: &'static str = "exon_number";
+
+/// Feature types of NCBI RefSeq GFF3 rows that are read as transcripts in RefSeq mode.
+const REFSEQ_TRANSCRIPT_TYPES: &'static [&'static str] = &[
+    "mRNA", "ncRNA", "lnc_RNA", "antisense_RNA", "rRNA", "tRNA", "snRNA", "snoRNA", "scRNA",
+    "miRNA", "primary_transcript", "guide_RNA", "RNase_MRP_RNA", "RNase_P_RNA",
+    "telomerase_RNA", "vault_RNA", "Y_RNA", "transcript",
+];
+
+/// Attribute key of gene names in NCBI RefSeq GFF3 files.
+const REFSEQ_GENE_STR: &'static str = "gene";
+
+/// Attribute key of GFF3 feature identifiers.
+const ID_STR: &'static str = "ID";
+
+/// Attribute key of GFF3 parent feature identifiers.
+const PARENT_STR: &'static str = "Parent";
+
+/// Attribute key of locus tags in prokaryotic GFF3 files.
+const LOCUS_TAG_STR: &'static str = "locus_tag";
+
+/// Prefix of the identifiers of NCBI RefSeq GFF3 transcript rows.
+const REFSEQ_RNA_PREFIX: &'static str = "rna-";
+
 /// Value for columns that are undefined, as a string.
 const UNK_STR: &'static str = ".";
 
@@ -87,9 +132,9 @@ quick_error! {
             display(self_) -> ("{}, transcript ID: {}",
                                self_.description(), tid.as_deref().unwrap_or(DEF_ID))
         }
-        /// Occurs when an expected transcript feature record is not found.
+        /// Occurs when neither transcript nor exon feature records of a transcript are found.
         MissingTranscript(tid: Option<String>) {
-            description("no 'transcript' feature present")
+            description("no 'transcript' or 'exon' feature present")
             display(self_) -> ("{}, transcript ID: {}",
                                self_.description(), tid.as_deref().unwrap_or(DEF_ID))
         }
@@ -122,6 +167,35 @@ quick_error! {
             display(self_) -> ("{}, transcript ID: {}",
                                self_.description(), tid.as_deref().unwrap_or(DEF_ID))
         }
+        /// Occurs when the `exon_number` attribute of an exon to write differs from its number
+        /// in the transcript direction.
+        InvalidExonNumber(tid: Option<String>, number: String) {
+            description("'exon_number' attribute does not match exon position")
+            display(self_) -> ("{}: '{}', transcript ID: {}",
+                               self_.description(), number, tid.as_deref().unwrap_or(DEF_ID))
+        }
+        /// Occurs when features are their own ancestors through their `Parent` attributes.
+        ParentCycle(id: Option<String>) {
+            description("feature is its own ancestor")
+            display(self_) -> ("{}, feature ID: {}",
+                               self_.description(), id.as_deref().unwrap_or(DEF_ID))
+        }
+        /// Occurs when a row does not have the expected number of columns.
+        ColumnCount(count: usize) {
+            description("gff row does not have 9 columns")
+            display(self_) -> ("{}: {} columns found", self_.description(), count)
+        }
+        /// Occurs when a column value can not be parsed.
+        InvalidColumn(field: &'static str, value: String) {
+            description("invalid gff column value")
+            display(self_) -> ("{}, field: {}, value: '{}'", self_.description(), field, value)
+        }
+        /// Errors propagated from reading the underlying stream directly.
+        Io(err: io::Error) {
+            description(err.description())
+            from()
+            cause(err)
+        }
         /// Occurs when an unsupported GFF variant is used.
         UnsupportedGffType {
             description("unsupported gff type")
@@ -144,12 +218,23 @@ quick_error! {
 
 /// GFF reader.
 pub struct Reader<R: io::Read> {
-    inner: gff::Reader<R>,
-    gene_id_attr: String,
-    transcript_id_attr: String,
+    inner: gff::Reader<FeatureRows<R>>,
+    directives: Arc<Mutex<Directives>>,
+    line_numbers: LineNumbers,
+    gene_id_attrs: Vec<String>,
+    transcript_id_attrs: Vec<String>,
     seq_name_prefix: Option<String>,
     seq_name_lstrip: Option<String>,
     loose_codons: bool,
+    refseq: bool,
+    prokaryotic: bool,
+    keep_attributes: bool,
+    lazy_attributes: bool,
+    lenient: bool,
+    strand_policy: StrandPolicy,
+    warnings: Vec<ParseWarning>,
+    threads: Arc<AtomicUsize>,
+    tracker: Tracker,
     pub(crate) gff_type: GffType,
 }
 
@@ -157,13 +242,29 @@ impl<R: io::Read> Reader<R> {
 
     /// Creates a GFF reader of the given variant from another reader.
     pub fn from_reader(in_reader: R, gff_type: GffType) -> Reader<R> {
+        let directives = Arc::new(Mutex::new(Directives::default()));
+        let bytes = Arc::new(AtomicUsize::new(0));
+        let line_numbers = Arc::new(Mutex::new(VecDeque::new()));
+        let rows = FeatureRows::new(in_reader, directives.clone(), bytes.clone(),
+                                    line_numbers.clone());
         Reader {
-            inner: gff::Reader::new(in_reader, gff_type),
-            gene_id_attr: GENE_ID_STR.to_owned(),
-            transcript_id_attr: TRANSCRIPT_ID_STR.to_owned(),
+            inner: gff::Reader::new(rows, gff_type),
+            directives: directives,
+            line_numbers: line_numbers,
+            gene_id_attrs: vec![GENE_ID_STR.to_owned()],
+            transcript_id_attrs: vec![TRANSCRIPT_ID_STR.to_owned()],
             seq_name_prefix: None,
             seq_name_lstrip: None,
             loose_codons: false,
+            refseq: false,
+            prokaryotic: false,
+            keep_attributes: false,
+            lazy_attributes: false,
+            lenient: false,
+            strand_policy: StrandPolicy::DefaultUnknown,
+            warnings: Vec::new(),
+            threads: Arc::new(AtomicUsize::new(1)),
+            tracker: Tracker::new(bytes),
             gff_type: gff_type.clone(),
         }
     }
@@ -172,7 +273,19 @@ impl<R: io::Read> Reader<R> {
     pub fn gene_id_attr<T>(&mut self, gene_id_attr: T) -> &mut Self
         where T: Into<String>
     {
-        self.gene_id_attr = gene_id_attr.into();
+        self.gene_id_attrs = vec![gene_id_attr.into()];
+        self
+    }
+
+    /// Sets the reader to use the first present of the given attribute keys for getting gene
+    /// identifiers.
+    ///
+    /// This allows reading annotations that identify genes with keys other than `gene_id`, such
+    /// as `gene`, `locus_tag`, or `Name`, including annotations that mix several of them.
+    pub fn gene_id_attrs<T>(&mut self, gene_id_attrs: &[T]) -> &mut Self
+        where T: AsRef<str>
+    {
+        self.gene_id_attrs = gene_id_attrs.iter().map(|v| v.as_ref().to_owned()).collect();
         self
     }
 
@@ -180,8 +293,18 @@ impl<R: io::Read> Reader<R> {
     pub fn transcript_id_attr<T>(&mut self, transcript_id_attr: T) -> &mut Self
         where T: Into<String>
     {
-        self.transcript_id_attr = transcript_id_attr.into();
-        self
+        self.transcript_id_attrs = vec![transcript_id_attr.into()];
+        self
+    }
+
+    /// Sets the reader to use the first present of the given attribute keys for getting
+    /// transcript identifiers.
+    pub fn transcript_id_attrs<T>(&mut self, transcript_id_attrs: &[T]) -> &mut Self
+        where T: AsRef<str>
+    {
+        self.transcript_id_attrs =
+            transcript_id_attrs.iter().map(|v| v.as_ref().to_owned()).collect();
+        self
     }
 
     /// Sets the reader to add the given prefix to all sequence names.
@@ -201,75 +324,1093 @@ impl<R: io::Read> Reader<R> {
         self
     }
 
-    /// Sets the reader to use CDS coordinates when start and/or stop codons for transcripts
-    /// can not be found.
-    pub fn loose_codons(&mut self, loose_codons: bool) -> &mut Self {
-        self.loose_codons = loose_codons;
-        self
+    /// Sets the number of threads used for decompressing bgzip-compressed input.
+    ///
+    /// This only has an effect on readers created with `from_path` from bgzip-compressed files,
+    /// and must be set before any record is read. By default, input is decompressed on the
+    /// calling thread.
+    pub fn threads(&mut self, threads: usize) -> &mut Self {
+        self.threads.store(max(threads, 1), Ordering::SeqCst);
+        self
+    }
+
+    /// Sets the reader to use CDS coordinates when start and/or stop codons for transcripts
+    /// can not be found.
+    pub fn loose_codons(&mut self, loose_codons: bool) -> &mut Self {
+        self.loose_codons = loose_codons;
+        self
+    }
+
+    /// Sets the reader to assemble transcripts from the hierarchies of NCBI RefSeq GFF3 files.
+    ///
+    /// In RefSeq mode, rows of RNA subtypes such as `mRNA`, `ncRNA`, `lnc_RNA`, or `tRNA` are
+    /// read as transcript rows, and exon and CDS rows are grouped into the transcripts named by
+    /// their `Parent` attributes. Transcript identifiers are taken from the `ID` attribute of
+    /// the RNA rows, without their `rna-` prefixes, and gene identifiers are taken from the
+    /// `gene` attribute present on all rows. Since RefSeq files have no start or stop codon
+    /// rows, CDS rows are taken to include the stop codon. Composite source columns, such as
+    /// `BestRefSeq%2CGnomon`, are percent-decoded.
+    ///
+    /// The mode is only supported for GFF3 files, and overrides the gene and transcript
+    /// identifier attributes of the reader.
+    pub fn refseq(&mut self, refseq: bool) -> &mut Self {
+        self.refseq = refseq;
+        self
+    }
+
+    /// Sets the reader to create transcripts from the CDS rows of prokaryotic GFF3 files, such
+    /// as those created by Prokka or the NCBI PGAP, which have no transcript level.
+    ///
+    /// In prokaryotic mode, each CDS, identified by its `ID` attribute, is read as a
+    /// single-exon transcript whose exon and coding region span the CDS, including its stop
+    /// codon. Gene identifiers are taken from the `locus_tag` attribute. CDS rows sharing the
+    /// same identifier, such as those of programmed frameshifts, are read as the exons of one
+    /// transcript. All other rows are skipped, and the source, score, and attributes of CDS rows
+    /// are kept as those of the transcripts if the reader is set to keep attributes.
+    ///
+    /// The mode is only supported for GFF3 files. It overrides RefSeq mode, as well as the gene
+    /// and transcript identifier attributes of the reader.
+    pub fn prokaryotic(&mut self, prokaryotic: bool) -> &mut Self {
+        self.prokaryotic = prokaryotic;
+        self
+    }
+
+    /// Sets the reader to keep the source, score, and attributes of transcript and exon rows.
+    ///
+    /// Kept values are stored as attributes of the created transcripts and exons, with the
+    /// source and score columns stored under the `source` and `score` keys. This allows the
+    /// records created by their `into_gff_records` methods to retain the values of the input
+    /// rows. Gene and transcript identifier attributes are not kept, since they are always
+    /// written from the model identifiers.
+    ///
+    /// Regardless of this setting, the source and score columns of transcript and exon rows are
+    /// always available through the `source` and `score` methods of the created models.
+    pub fn keep_attributes(&mut self, keep_attributes: bool) -> &mut Self {
+        self.keep_attributes = keep_attributes;
+        self
+    }
+
+    /// Sets the reader to keep the attributes of rows unparsed until they are first used.
+    ///
+    /// This only has an effect if `keep_attributes` is set. The attributes columns are then stored
+    /// as-is, and only parsed when the `parse` method or any of the modifying methods of the
+    /// created `Attributes` is called, such as when writing GFF records. This speeds up reading
+    /// inputs whose attributes are mostly not used. The source and score columns are not stored
+    /// as attributes in this mode.
+    pub fn lazy_attributes(&mut self, lazy_attributes: bool) -> &mut Self {
+        self.lazy_attributes = lazy_attributes;
+        self
+    }
+
+    /// Sets the reader to skip malformed rows instead of returning errors.
+    ///
+    /// Each skipped row is stored as a warning, which can be retrieved using the `warnings`
+    /// method. Rows are considered malformed if they can not be parsed or if they lack the
+    /// gene or transcript identifier attributes.
+    pub fn lenient(&mut self, lenient: bool) -> &mut Self {
+        self.lenient = lenient;
+        self
+    }
+
+    /// Sets the policy for rows whose strand columns are `.`.
+    ///
+    /// By default, such rows are read as having unknown strands. With `StrandPolicy::Require`,
+    /// they are considered malformed instead.
+    pub fn strand_policy(&mut self, policy: StrandPolicy) -> &mut Self {
+        self.strand_policy = policy;
+        self
+    }
+
+    /// Sets the observer notified of the progress of reading rows.
+    ///
+    /// Since transcripts are only created after all rows are read, the observer is notified
+    /// while `transcripts` or `genes` reads the input.
+    pub fn observer<O>(&mut self, observer: O) -> &mut Self
+        where O: Observer + Send + 'static
+    {
+        self.tracker.set_observer(Box::new(observer));
+        self
+    }
+
+    /// Sets the token for cancelling reading.
+    ///
+    /// The token is checked before every row is read, and before every transcript is created.
+    pub fn cancel_token(&mut self, token: CancelToken) -> &mut Self {
+        self.tracker.set_cancel_token(token);
+        self
+    }
+
+    /// Returns the warnings of the rows skipped in lenient mode.
+    pub fn warnings(&self) -> &[ParseWarning] {
+        self.warnings.as_slice()
+    }
+
+    /// Sets the reader to store the sequences of the `##FASTA` section of GFF3 files.
+    ///
+    /// The section is skipped by default, since it is not required for creating transcripts.
+    /// Stored sequences can be retrieved using the `take_sequences` method.
+    pub fn read_fasta(&mut self, read_fasta: bool) -> &mut Self {
+        lock_directives(&self.directives).read_fasta = read_fasta;
+        self
+    }
+
+    /// Returns the regions of the `##sequence-region` directives read so far.
+    ///
+    /// Each region is returned as its sequence name along with its zero-based, half-open
+    /// coordinates. Sequence names are updated using the prefix and left-strip settings of
+    /// the reader, as with the sequence names of transcripts.
+    pub fn sequence_regions(&self) -> Vec<(String, Range<u64>)> {
+        lock_directives(&self.directives).sequence_regions.iter()
+            .map(|&(ref name, ref region)| (self.updated_seq_name(name.clone()), region.clone()))
+            .collect()
+    }
+
+    /// Takes the sequences of the `##FASTA` section, keyed by their sequence names.
+    ///
+    /// Sequences are only stored if the reader is set to do so using `read_fasta`, and only
+    /// once all rows have been read, for example by `transcripts`. The returned map can be used
+    /// as a `SequenceSource` for extracting the sequences of the read transcripts.
+    pub fn take_sequences(&mut self) -> HashMap<String, Vec<u8>> {
+        let sequences = mem::replace(&mut lock_directives(&self.directives).sequences,
+                                     HashMap::new());
+        sequences.into_iter()
+            .map(|(name, seq)| (self.updated_seq_name(name), seq))
+            .collect()
+    }
+
+    /// Updates the given sequence name using the prefix and left-strip settings.
+    fn updated_seq_name(&self, mut name: String) -> String {
+        update_seq_name(&mut name, self.seq_name_prefix.as_deref(),
+                        self.seq_name_lstrip.as_deref().map(|v| (v, v.len())));
+        name
+    }
+
+    /// Creates an iterator of transcripts.
+    ///
+    /// This iterator reads all GFF records into memory first, before sorting and grouping them
+    /// into transcripts. This is because features of a transcript may be interspersed with
+    /// features from another transcript.
+    ///
+    /// Transcripts without transcript rows, such as those in GTF files created by StringTie or
+    /// Cufflinks, span from the start of their first exon to the end of their last exon.
+    pub fn transcripts(&mut self) -> ::Result<GffTranscripts> {
+        let parser = PartParser::new(self)?;
+        let lenient = self.lenient;
+
+        let mut parts = Vec::new();
+        let mut warnings = Vec::new();
+        {
+            let tracker = &mut self.tracker;
+            let rows = GffRawRows::new(self.inner.raw_rows(), self.line_numbers.clone());
+            for (line, result) in rows {
+                if !tracker.proceed()? {
+                    break;
+                }
+                push_part(parser.parse(result), line, lenient, &mut parts, &mut warnings)?;
+                tracker.record();
+            }
+        }
+        self.warnings.extend(warnings);
+
+        Ok(self.group_parts(parts))
+    }
+
+    /// Creates an iterator of genes.
+    ///
+    /// Transcripts are created as in `transcripts`, and then grouped by their gene identifiers,
+    /// sequence names, and strands into genes. Gene rows are not required, since the gene
+    /// coordinates are computed from the coordinates of their transcripts.
+    pub fn genes(&mut self) -> ::Result<GffGenes> {
+        self.transcripts().map(|transcripts| GffGenes { inner: transcripts.peekable() })
+    }
+
+    /// Creates an iterator of the regulatory features of the file, such as the enhancers and
+    /// promoters of the Ensembl Regulatory Build.
+    ///
+    /// Rows of other feature types are skipped. Sequence names are updated using the prefix and
+    /// left-strip settings of the reader.
+    pub fn regulatory_features(&mut self) -> GffRegulatoryFeatures<R> {
+        GffRegulatoryFeatures {
+            prefix: self.seq_name_prefix.clone(),
+            lstrip: self.seq_name_lstrip.clone(),
+            gff_type: self.gff_type,
+            rows: self.raw_rows_stream(),
+        }
+    }
+
+    /// Reads all rows of a GFF3 file into a tree of features linked by their `ID` and `Parent`
+    /// attributes.
+    ///
+    /// Unlike `transcripts`, all feature types are kept, regardless of the identifier and
+    /// assembly settings of the reader. Sequence names are updated using the prefix and
+    /// left-strip settings of the reader. An error is returned for other GFF variants, or if
+    /// the `Parent` attributes of the features form a cycle.
+    pub fn feature_tree(&mut self) -> ::Result<FeatureTree> {
+        match self.gff_type {
+            GffType::GFF3 => {},
+            _ => return Err(::Error::from(GffError::UnsupportedGffType)),
+        }
+        let mut rows = Vec::new();
+        for (line, result) in self.raw_rows_stream() {
+            rows.push(result.map_err(|e| ::Error::at_line(line, e))?);
+        }
+
+        let nodes = rows.into_iter()
+            .map(|row| {
+                let mut id = None;
+                let mut parents = Vec::new();
+                let mut attribs = Attributes::new();
+                for (key, value) in parse_attributes(&row.8, GffType::GFF3) {
+                    if key == ID_STR {
+                        id = id.or(Some(value));
+                    } else if key == PARENT_STR {
+                        parents.push(value);
+                    } else {
+                        attribs.insert(key, value);
+                    }
+                }
+                let node = FeatureNode::new(
+                    self.updated_seq_name(row.0),
+                    (row.3 - 1, row.4),
+                    Strand::from_char(&row.6).unwrap(),
+                    row.2,
+                    id,
+                    if row.1 != UNK_STR { Some(row.1) } else { None },
+                    row.5.parse::<f64>().ok(),
+                    parse_frame(&row.7),
+                    attribs);
+                (node, parents)
+            })
+            .collect();
+        FeatureTree::from_nodes(nodes).map_err(::Error::from)
+    }
+
+    /// Sorts the given transcript parts and groups them into transcripts.
+    fn group_parts(&self, mut parts: Vec<TrxPart>) -> GffTranscripts {
+        parts.sort_by_key(|ref elem| elem.sort_key());
+        GffTranscripts {
+            groups: parts.into_iter().group_by(TrxPart::transcript_group_key),
+            assembly: self.assembly(),
+            seq_names: SeqNamePool::new(),
+            cancellation: self.tracker.cancellation(),
+        }
+    }
+
+    /// Returns the settings for creating transcripts from grouped rows.
+    fn assembly(&self) -> Assembly {
+        Assembly {
+            loose_codons: self.loose_codons,
+            cds_incl_stop: self.refseq || self.prokaryotic,
+            cds_exons: self.prokaryotic,
+        }
+    }
+
+    /// Creates an iterator of GFF rows, along with their line numbers.
+    pub(crate) fn raw_rows_stream(&mut self) -> GffRawRows<R> {
+        GffRawRows::new(self.inner.raw_rows(), self.line_numbers.clone())
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<R: io::Read> Reader<R> {
+
+    /// Reads all transcripts, parsing rows and creating transcripts in parallel.
+    ///
+    /// Rows are read sequentially, after which their attributes are parsed across multiple
+    /// threads. The parsed rows are then grouped into transcripts as in `transcripts`, and the
+    /// transcripts are created across multiple threads. The transcripts are returned in the same
+    /// order as the `transcripts` iterator yields them.
+    pub fn par_transcripts(&mut self) -> ::Result<Vec<::Result<Transcript>>> {
+        let (groups, assembly) = self.par_groups()?;
+        Ok(groups.into_par_iter()
+            .map(|(key, seq_name, tps)| group_to_transcript(key, seq_name, tps, assembly))
+            .collect())
+    }
+
+    /// Reads all genes, creating their transcripts and genes in parallel.
+    ///
+    /// Rows are parsed and grouped into transcripts as in `par_transcripts`, after which the
+    /// transcripts of each gene are created across multiple threads. The genes are returned in
+    /// the same order as the `genes` iterator yields them.
+    pub fn par_genes(&mut self) -> ::Result<Vec<::Result<Gene>>> {
+        let (groups, assembly) = self.par_groups()?;
+        let mut gene_groups: Vec<Vec<(TrxGroupKey, SeqName, Vec<TrxPart>)>> = Vec::new();
+        for group in groups {
+            if let Some(gene_group) = gene_groups.last_mut() {
+                let same_gene = {
+                    let (ref key, _, _) = gene_group[0];
+                    let (ref cur_key, _, _) = group;
+                    key.0 == cur_key.0 && key.2 == cur_key.2 && key.3 == cur_key.3
+                };
+                if same_gene {
+                    gene_group.push(group);
+                    continue;
+                }
+            }
+            gene_groups.push(vec![group]);
+        }
+
+        let genes = gene_groups.into_par_iter()
+            .map(|gene_group| {
+                let mut transcripts = gene_group.into_iter()
+                    .map(|(key, seq_name, tps)| group_to_transcript(key, seq_name, tps, assembly))
+                    .peekable();
+                let mut genes = Vec::new();
+                while let Some(gene) = next_gene(&mut transcripts) {
+                    genes.push(gene);
+                }
+                genes
+            })
+            .collect::<Vec<Vec<::Result<Gene>>>>();
+        Ok(genes.into_iter().flat_map(|genes| genes).collect())
+    }
+
+    /// Parses all rows in parallel and groups them into the rows of each transcript.
+    fn par_groups(&mut self)
+        -> ::Result<(Vec<(TrxGroupKey, SeqName, Vec<TrxPart>)>, Assembly)>
+    {
+        let parser = PartParser::new(self)?;
+        let lenient = self.lenient;
+
+        let rows = self.raw_rows_stream().collect::<Vec<(u64, ::Result<gff::RawRow>)>>();
+        let parsed = rows.into_par_iter()
+            .map(|(line, result)| (line, parser.parse(result)))
+            .collect::<Vec<(u64, ::Result<Option<TrxPart>>)>>();
+
+        let mut parts = Vec::new();
+        let mut warnings = Vec::new();
+        for (line, part) in parsed {
+            push_part(part, line, lenient, &mut parts, &mut warnings)?;
+        }
+        self.warnings.extend(warnings);
+
+        let assembly = self.assembly();
+        let mut transcripts = self.group_parts(parts);
+        let seq_names = &mut transcripts.seq_names;
+        let groups = transcripts.groups.into_iter()
+            .map(|(key, tps)| {
+                let seq_name = seq_names.intern(&key.2);
+                (key, seq_name, tps.collect::<Vec<TrxPart>>())
+            })
+            .collect::<Vec<(TrxGroupKey, SeqName, Vec<TrxPart>)>>();
+        Ok((groups, assembly))
+    }
+}
+
+impl Reader<fs::File> {
+
+    /// Creates a GFF reader that reads from the given path.
+    pub fn from_file<P: AsRef<Path>>(path: P, gff_type: GffType) -> io::Result<Self> {
+        fs::File::open(path).map(|file| Reader::from_reader(file, gff_type))
+    }
+}
+
+impl Reader<Box<io::Read>> {
+
+    /// Creates a GFF reader that reads from the given path, which may be gzip or
+    /// bgzip-compressed.
+    ///
+    /// Compression is detected from the contents of the file, regardless of its extension.
+    pub fn from_path<P: AsRef<Path>>(path: P, gff_type: GffType) -> io::Result<Self> {
+        let threads = Arc::new(AtomicUsize::new(1));
+        open_path_threads(path, threads.clone()).map(|file| {
+            let mut reader = Reader::from_reader(file, gff_type);
+            reader.threads = threads;
+            reader
+        })
+    }
+
+    /// Creates a GFF reader that reads from the given HTTP URL, which may point to a gzip or
+    /// bgzip-compressed file.
+    ///
+    /// The file is streamed as it is read, with compression detected as in `from_path`.
+    #[cfg(feature = "remote")]
+    pub fn from_url(url: &str, gff_type: GffType) -> io::Result<Self> {
+        open_url(url).map(|file| Reader::from_reader(file, gff_type))
+    }
+}
+
+/// GFF reader for bgzip-compressed files with tabix indices.
+///
+/// Only the rows overlapping a queried region are read, instead of the entire file. The file
+/// must be sorted by sequence name and start coordinate, as required for tabix indexing.
+pub struct IndexedReader {
+    source: Source,
+    index: TabixIndex,
+    gff_type: GffType,
+}
+
+/// Location of the file read by an indexed reader.
+enum Source {
+    Path(PathBuf),
+    #[cfg(feature = "remote")]
+    Url(String),
+}
+
+impl IndexedReader {
+
+    /// Creates an indexed reader of the given bgzip-compressed file.
+    ///
+    /// The tabix index is read from the same path with an added `.tbi` extension.
+    pub fn from_path<P: AsRef<Path>>(path: P, gff_type: GffType) -> ::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let mut index_path = path.clone().into_os_string();
+        index_path.push(".tbi");
+        let index = TabixIndex::from_path(&index_path)?;
+        Ok(IndexedReader {
+            source: Source::Path(path),
+            index: index,
+            gff_type: gff_type,
+        })
+    }
+
+    /// Creates an indexed reader of the bgzip-compressed file at the given URL.
+    ///
+    /// The tabix index is read from the same URL with an added `.tbi` extension. Rows are read
+    /// using HTTP range requests, so that only the parts of the file starting from the queried
+    /// regions are downloaded.
+    #[cfg(feature = "remote")]
+    pub fn from_url(url: &str, gff_type: GffType) -> ::Result<Self> {
+        let index = TabixIndex::from_url(&format!("{}.tbi", url))?;
+        Ok(IndexedReader {
+            source: Source::Url(url.to_owned()),
+            index: index,
+            gff_type: gff_type,
+        })
+    }
+
+    /// Creates a GFF reader of the rows overlapping the given zero-based, half-open region.
+    ///
+    /// The region is extended until it covers all overlapping rows, so that rows of features
+    /// that span past the region, such as the exons of an overlapping transcript, are included.
+    /// The returned reader can be configured like any other GFF reader before its transcripts
+    /// are created.
+    pub fn fetch(&mut self, seq_name: &str, region: Range<u64>)
+        -> ::Result<Reader<io::Cursor<Vec<u8>>>>
+    {
+        let mut coord = (region.start, region.end);
+        loop {
+            let (rows, span) = self.fetch_rows(seq_name, coord)?;
+            if span == coord {
+                return Ok(Reader::from_reader(io::Cursor::new(rows), self.gff_type));
+            }
+            coord = span;
+        }
+    }
+
+    /// Returns all transcripts overlapping the given zero-based, half-open region.
+    pub fn query(&mut self, seq_name: &str, region: Range<u64>) -> ::Result<Vec<Transcript>> {
+        let (start, end) = (region.start, region.end);
+        let mut reader = self.fetch(seq_name, region)?;
+        let mut transcripts = Vec::new();
+        for result in reader.transcripts()? {
+            let transcript = result?;
+            if transcript.start() < end && transcript.end() > start {
+                transcripts.push(transcript);
+            }
+        }
+        Ok(transcripts)
+    }
+
+    /// Reads the rows overlapping the given region, returning them along with their span.
+    ///
+    /// The returned span always covers the given region. Rows whose coordinates can not be
+    /// parsed are included as-is, so that their errors are reported by the GFF reader.
+    fn fetch_rows(&self, seq_name: &str, coord: Coord<u64>) -> ::Result<(Vec<u8>, Coord<u64>)> {
+        let (start, end) = coord;
+        let mut rows = Vec::new();
+        let mut span = coord;
+        let voffset = match self.index.start_offset(seq_name, start, end) {
+            Some(voffset) => voffset,
+            None => return Ok((rows, span)),
+        };
+
+        let opened = match self.source {
+            Source::Path(ref path) => tabix::open_at(path, voffset)?,
+            #[cfg(feature = "remote")]
+            Source::Url(ref url) => tabix::open_url_at(url, voffset)?,
+        };
+        for line in opened.lines() {
+            let line = line.map_err(|e| ::Error::from(TabixError::from(e)))?;
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut cols = line.split('\t');
+            if cols.next() != Some(seq_name) {
+                break;
+            }
+            let row_start = cols.nth(2).and_then(|v| v.parse::<u64>().ok());
+            let row_end = cols.next().and_then(|v| v.parse::<u64>().ok());
+            match (row_start, row_end) {
+                (Some(rstart), _) if rstart > 0 && rstart - 1 >= end => break,
+                (Some(rstart), Some(rend)) if rstart > 0 => {
+                    if rend <= start {
+                        continue;
+                    }
+                    span = (min(span.0, rstart - 1), max(span.1, rend));
+                },
+                _ => {},
+            }
+            rows.extend_from_slice(line.as_bytes());
+            rows.push(b'\n');
+        }
+        Ok((rows, span))
+    }
+}
+
+/// Helper struct for parsing GFF rows into transcript parts.
+struct PartParser {
+    gid_regexes: Vec<Regex>,
+    tid_regexes: Vec<Regex>,
+    parent_regexes: Option<Vec<Regex>>,
+    prokaryotic: bool,
+    id_attrs: Arc<Vec<String>>,
+    prefix: Option<String>,
+    lstrip: Option<String>,
+    keep_attributes: bool,
+    lazy_attributes: bool,
+    strand_policy: StrandPolicy,
+    gff_type: GffType,
+}
+
+impl PartParser {
+
+    /// Creates a parser using the identifier attributes and sequence name settings of the given
+    /// reader.
+    ///
+    /// In RefSeq mode, transcript rows are identified by their `ID` attributes and the other
+    /// rows by their `Parent` attributes, which are kept separately. In prokaryotic mode, CDS
+    /// rows are identified by their `ID` attributes.
+    fn new<R: io::Read>(reader: &Reader<R>) -> ::Result<Self> {
+        if reader.refseq || reader.prokaryotic {
+            match reader.gff_type {
+                GffType::GFF3 => {},
+                _ => return Err(::Error::from(GffError::UnsupportedGffType)),
+            }
+        }
+        let (gid_attrs, tid_attrs, parent_attrs) =
+            if reader.prokaryotic {
+                (vec![LOCUS_TAG_STR.to_owned()], vec![ID_STR.to_owned()], Vec::new())
+            } else if reader.refseq {
+                (vec![REFSEQ_GENE_STR.to_owned()], vec![ID_STR.to_owned()],
+                 vec![PARENT_STR.to_owned()])
+            } else {
+                (reader.gene_id_attrs.clone(), reader.transcript_id_attrs.clone(), Vec::new())
+            };
+        let parent_regexes =
+            if parent_attrs.is_empty() {
+                None
+            } else {
+                Some(make_gff_id_regexes(&parent_attrs, reader.gff_type)?)
+            };
+        Ok(PartParser {
+            gid_regexes: make_gff_id_regexes(&gid_attrs, reader.gff_type)?,
+            tid_regexes: make_gff_id_regexes(&tid_attrs, reader.gff_type)?,
+            parent_regexes: parent_regexes,
+            prokaryotic: reader.prokaryotic,
+            id_attrs: Arc::new(
+                gid_attrs.into_iter().chain(tid_attrs).chain(parent_attrs).collect()),
+            prefix: reader.seq_name_prefix.clone(),
+            lstrip: reader.seq_name_lstrip.clone(),
+            keep_attributes: reader.keep_attributes,
+            lazy_attributes: reader.lazy_attributes,
+            strand_policy: reader.strand_policy,
+            gff_type: reader.gff_type,
+        })
+    }
+
+    /// Parses the given row into a transcript part, if the row is of a transcript feature.
+    fn parse(&self, result: ::Result<gff::RawRow>) -> ::Result<Option<TrxPart>> {
+        result.and_then(|mut row| {
+            update_seq_name(&mut row.0, self.prefix.as_deref(),
+                            self.lstrip.as_deref().map(|v| (v, v.len())));
+            let parent_regexes = match self.parent_regexes {
+                Some(ref regexes) => {
+                    row.1 = gff3_unescape(&row.1);
+                    if REFSEQ_TRANSCRIPT_TYPES.contains(&row.2.as_str()) {
+                        row.2 = TRANSCRIPT_STR.to_owned();
+                        None
+                    } else {
+                        Some(regexes.as_slice())
+                    }
+                },
+                None => None,
+            };
+            let tid_regexes = parent_regexes.unwrap_or(self.tid_regexes.as_slice());
+            if self.prokaryotic && row.2 != CDS_STR {
+                return Ok(None);
+            }
+            match row.2.as_str() {
+                TRANSCRIPT_STR | EXON_STR => {
+                    let attributes =
+                        if self.keep_attributes { Some(self.attributes(&row)) } else { None };
+                    let readthrough = has_readthrough_tag(&row.8, self.gff_type);
+                    TrxPart::try_from_row(row, &self.gid_regexes, tid_regexes, self.gff_type)
+                        .map_err(::Error::from)
+                        .and_then(|tp| self.finish_part(tp))
+                        .map(|mut tp| {
+                            tp.attributes = attributes;
+                            tp.readthrough = readthrough;
+                            Some(tp)
+                        })
+                },
+                CDS_STR | START_CODON_STR | STOP_CODON_STR => {
+                    let attributes =
+                        if self.prokaryotic && self.keep_attributes {
+                            Some(self.attributes(&row))
+                        } else {
+                            None
+                        };
+                    TrxPart::try_from_row(row, &self.gid_regexes, tid_regexes, self.gff_type)
+                        .map_err(::Error::from)
+                        .and_then(|tp| self.finish_part(tp))
+                        .map(|mut tp| {
+                            tp.attributes = attributes;
+                            Some(tp)
+                        })
+                },
+                _ => Ok(None),
+            }
+        })
+    }
+
+    /// Returns the attributes of the given row, which are left unparsed in lazy mode.
+    fn attributes(&self, row: &gff::RawRow) -> Attributes {
+        if self.lazy_attributes {
+            Attributes::from_raw(row.8.clone(), self.gff_type, self.id_attrs.clone())
+        } else {
+            row_attributes(row, self.gff_type, &self.id_attrs).into_iter().collect()
+        }
+    }
+
+    /// Sets the strand of the given transcript part using the strand policy, if its strand is
+    /// unknown, and removes the RefSeq prefix of its transcript identifier in RefSeq mode.
+    fn finish_part(&self, mut tp: TrxPart) -> ::Result<TrxPart> {
+        if tp.strand.is_unknown() {
+            tp.strand = self.strand_policy.default_strand().map_err(::Error::from)?;
+        }
+        if self.parent_regexes.is_some() && tp.transcript_id.starts_with(REFSEQ_RNA_PREFIX) {
+            tp.transcript_id = tp.transcript_id[REFSEQ_RNA_PREFIX.len()..].to_owned();
+        }
+        Ok(tp)
+    }
+}
+
+/// Helper function for collecting the transcript part parsed from the given line.
+///
+/// Errors are stored as warnings if `lenient` is set to `true`, or returned with the line
+/// number otherwise.
+fn push_part(
+    part: ::Result<Option<TrxPart>>,
+    line: u64,
+    lenient: bool,
+    parts: &mut Vec<TrxPart>,
+    warnings: &mut Vec<ParseWarning>,
+) -> ::Result<()> {
+    match part {
+        Ok(Some(tp)) => parts.push(tp),
+        Ok(None) => {},
+        Err(err) => {
+            if lenient {
+                warnings.push(ParseWarning::new(line, err));
+            } else {
+                return Err(::Error::at_line(line, err));
+            }
+        },
+    }
+    Ok(())
+}
+
+/// Borrowed GFF record.
+///
+/// This type refers to the columns of a feature row without copying them, and is created by
+/// the `read_record` method of `Scanner`. It is meant for scanning or filtering files without
+/// the cost of creating owned records or models for every row. Attributes are only parsed when
+/// requested.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GffRecordRef<'a> {
+    seq_name: &'a str,
+    source: &'a str,
+    feature_type: &'a str,
+    start: u64,
+    end: u64,
+    score: Option<f64>,
+    strand: Strand,
+    frame: Option<u8>,
+    attributes: &'a str,
+    gff3: bool,
+}
+
+impl<'a> GffRecordRef<'a> {
+
+    /// Creates a record from a feature row of the given GFF variant, without its line
+    /// terminator.
+    pub fn from_line(line: &'a str, gff_type: GffType) -> ::Result<Self> {
+        let mut cols = [""; 9];
+        let mut count = 0;
+        for col in line.split('\t') {
+            if count < cols.len() {
+                cols[count] = col;
+            }
+            count += 1;
+        }
+        if count != cols.len() {
+            return Err(::Error::from(GffError::ColumnCount(count)));
+        }
+        let parse_coord = |field: &'static str, value: &str| {
+            value.parse::<u64>()
+                .map_err(|_| ::Error::from(GffError::InvalidColumn(field, value.to_owned())))
+        };
+        let start = parse_coord("start", cols[3])?;
+        let end = parse_coord("end", cols[4])?;
+        if start == 0 || start > end {
+            return Err(::Error::from(GffError::InvalidColumn("start", cols[3].to_owned())));
+        }
+        let strand = match cols[6] {
+            "+" => Strand::Forward,
+            "-" => Strand::Reverse,
+            "." | "?" => Strand::Unknown,
+            other => return Err(::Error::from(
+                GffError::InvalidColumn("strand", other.to_owned()))),
+        };
+        let score = match cols[5] {
+            UNK_STR => None,
+            value => Some(value.parse::<f64>().map_err(|_| ::Error::from(
+                GffError::InvalidColumn("score", value.to_owned())))?),
+        };
+        Ok(GffRecordRef {
+            seq_name: cols[0],
+            source: cols[1],
+            feature_type: cols[2],
+            start: start - 1,
+            end: end,
+            score: score,
+            strand: strand,
+            frame: parse_frame(cols[7]),
+            attributes: cols[8],
+            gff3: match gff_type {
+                GffType::GFF3 => true,
+                _ => false,
+            },
+        })
+    }
+
+    /// Returns the sequence name.
+    pub fn seq_name(&self) -> &'a str {
+        self.seq_name
+    }
+
+    /// Returns the source column.
+    pub fn source(&self) -> &'a str {
+        self.source
+    }
+
+    /// Returns the feature type.
+    pub fn feature_type(&self) -> &'a str {
+        self.feature_type
+    }
+
+    /// Returns the 0-based start coordinate.
+    pub fn start(&self) -> u64 {
+        self.start
+    }
+
+    /// Returns the 0-based, exclusive end coordinate.
+    pub fn end(&self) -> u64 {
+        self.end
+    }
+
+    /// Returns the score, if known.
+    pub fn score(&self) -> Option<f64> {
+        self.score
+    }
+
+    /// Returns the strand.
+    pub fn strand(&self) -> Strand {
+        self.strand
+    }
+
+    /// Returns the frame, if known.
+    pub fn frame(&self) -> Option<u8> {
+        self.frame
+    }
+
+    /// Returns the unparsed attributes column.
+    pub fn raw_attributes(&self) -> &'a str {
+        self.attributes
+    }
+
+    /// Parses and returns the attributes, in order of appearance.
+    pub fn attributes(&self) -> Attributes {
+        let gff_type = if self.gff3 { GffType::GFF3 } else { GffType::GTF2 };
+        parse_attributes(self.attributes, gff_type).into_iter().collect()
+    }
+}
+
+/// Scanner over the feature rows of a GFF file, yielding borrowed records.
+///
+/// Unlike `Reader`, the scanner reuses one line buffer for all rows, so reading a record does
+/// not allocate. Empty lines, comments, and directives are skipped, and scanning stops at the
+/// start of the `##FASTA` section.
+pub struct Scanner<R: io::Read> {
+    inner: io::BufReader<R>,
+    buf: String,
+    num_lines: u64,
+    done: bool,
+    gff_type: GffType,
+}
+
+impl<R: io::Read> Scanner<R> {
+
+    /// Creates a scanner of the given GFF variant from another reader.
+    pub fn from_reader(in_reader: R, gff_type: GffType) -> Self {
+        Scanner {
+            inner: io::BufReader::new(in_reader),
+            buf: String::new(),
+            num_lines: 0,
+            done: false,
+            gff_type: gff_type,
+        }
+    }
+
+    /// Reads the next feature row into the internal buffer and returns its record, or `None`
+    /// if there are no more feature rows.
+    ///
+    /// The returned record borrows the buffer, so it must be dropped before the next call.
+    pub fn read_record(&mut self) -> Option<::Result<GffRecordRef>> {
+        loop {
+            if self.done {
+                return None;
+            }
+            self.buf.clear();
+            match self.inner.read_line(&mut self.buf) {
+                Ok(0) => self.done = true,
+                Ok(_) => self.num_lines += 1,
+                Err(err) => return Some(Err(::Error::from(GffError::from(err)))),
+            }
+            let line = self.buf.trim_right_matches(|c: char| c == '\n' || c == '\r');
+            if line.starts_with("##FASTA") || line.starts_with('>') {
+                self.done = true;
+            } else if !line.is_empty() && !line.starts_with('#') {
+                break;
+            }
+        }
+        let (num_lines, gff_type) = (self.num_lines, self.gff_type);
+        let line = self.buf.trim_right_matches(|c: char| c == '\n' || c == '\r');
+        Some(GffRecordRef::from_line(line, gff_type)
+            .map_err(|err| ::Error::at_line(num_lines, err)))
+    }
+}
+
+impl Scanner<fs::File> {
+
+    /// Creates a GFF scanner of the given variant that reads from the given path.
+    pub fn from_file<P: AsRef<Path>>(path: P, gff_type: GffType) -> io::Result<Self> {
+        fs::File::open(path).map(|file| Scanner::from_reader(file, gff_type))
+    }
+}
+
+/// Helper struct for the values of GFF3 directives, shared by a reader and its input.
+#[derive(Debug, Default)]
+struct Directives {
+    read_fasta: bool,
+    sequence_regions: Vec<(String, Range<u64>)>,
+    sequences: HashMap<String, Vec<u8>>,
+}
+
+/// Helper function for locking shared directives, regardless of whether the lock is poisoned.
+fn lock_directives(directives: &Arc<Mutex<Directives>>) -> MutexGuard<Directives> {
+    directives.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// The type used for sharing the line numbers of the feature rows read by `FeatureRows`.
+///
+/// Line numbers are queued as rows are read, and taken by `GffRawRows` as the rows are parsed.
+type LineNumbers = Arc<Mutex<VecDeque<u64>>>;
+
+/// Helper function for locking shared line numbers, regardless of whether the lock is poisoned.
+fn lock_line_numbers(line_numbers: &LineNumbers) -> MutexGuard<VecDeque<u64>> {
+    line_numbers.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Helper struct for reading only the feature rows of a GFF file.
+///
+/// Empty lines, comments, and directives are removed from the underlying reader, with the
+/// regions of `##sequence-region` directives stored in the shared directives. Reading stops at
+/// the start of the `##FASTA` section, marked either by a `##FASTA` line or by a line starting
+/// with `>`, whose sequences are then stored if requested. The line numbers of the feature rows
+/// in the underlying reader are queued, so that errors refer to the lines of the file.
+struct FeatureRows<R: io::Read> {
+    inner: io::BufReader<R>,
+    line: Vec<u8>,
+    pos: usize,
+    done: bool,
+    num_lines: u64,
+    directives: Arc<Mutex<Directives>>,
+    bytes: Arc<AtomicUsize>,
+    line_numbers: LineNumbers,
+}
+
+impl<R: io::Read> FeatureRows<R> {
+
+    /// Creates the feature rows of the given reader, counting the bytes read with the given
+    /// counter and queueing the line numbers of the rows.
+    fn new(
+        in_reader: R,
+        directives: Arc<Mutex<Directives>>,
+        bytes: Arc<AtomicUsize>,
+        line_numbers: LineNumbers,
+    ) -> Self {
+        FeatureRows {
+            inner: io::BufReader::new(in_reader),
+            line: Vec::new(),
+            pos: 0,
+            done: false,
+            num_lines: 0,
+            directives: directives,
+            bytes: bytes,
+            line_numbers: line_numbers,
+        }
+    }
+
+    /// Reads the next feature row into the line buffer, returning `false` if none is left.
+    fn fill_line(&mut self) -> io::Result<bool> {
+        loop {
+            self.line.clear();
+            self.pos = 0;
+            let len = if self.done { 0 } else { self.inner.read_until(b'\n', &mut self.line)? };
+            let _ = self.bytes.fetch_add(len, Ordering::SeqCst);
+            if len == 0 {
+                self.done = true;
+                return Ok(false);
+            }
+            self.num_lines += 1;
+            if self.line.starts_with(b"##FASTA") || self.line.starts_with(b">") {
+                self.done = true;
+                self.read_fasta()?;
+                return Ok(false);
+            }
+            if self.line.starts_with(b"##sequence-region") {
+                self.push_sequence_region();
+            } else if !self.line.starts_with(b"#") && !trim_line_end(&self.line).is_empty() {
+                lock_line_numbers(&self.line_numbers).push_back(self.num_lines);
+                return Ok(true);
+            }
+        }
     }
 
-    /// Creates an iterator of transcripts.
+    /// Stores the region of the `##sequence-region` directive in the line buffer.
     ///
-    /// This iterator reads all GFF records into memory first, before sorting and grouping them
-    /// into transcripts. This is because features of a transcript may be interspersed with
-    /// features from another transcript.
-    pub fn transcripts(&mut self) -> ::Result<GffTranscripts> {
-
-        let gid_regex = make_gff_id_regex(self.gene_id_attr.as_str(), self.gff_type)?;
-        let tid_regex = make_gff_id_regex(self.transcript_id_attr.as_str(), self.gff_type)?;
-        let prefix = self.seq_name_prefix.clone();
-        let lstrip = self.seq_name_lstrip.clone();
-
-        let mut parts = Vec::new();
-        for result in self.raw_rows_stream() {
-            let mut row = result.map_err(::Error::from)?;
-            update_seq_name(&mut row.0, prefix.as_deref(),
-                            lstrip.as_deref().map(|v| (v, v.len())));
-            match row.2.as_str() {
-                TRANSCRIPT_STR | EXON_STR | CDS_STR | START_CODON_STR | STOP_CODON_STR => {
-                    let rf = TrxPart::try_from_row(row, &gid_regex, &tid_regex)
-                        .map_err(::Error::from)?;
-                    parts.push(rf);
-                },
-                _ => {},
+    /// Directives with missing or invalid coordinates are ignored.
+    fn push_sequence_region(&self) {
+        let line = String::from_utf8_lossy(&self.line);
+        let mut cols = line.split_whitespace().skip(1);
+        let name = cols.next();
+        let start = cols.next().and_then(|v| v.parse::<u64>().ok());
+        let end = cols.next().and_then(|v| v.parse::<u64>().ok());
+        if let (Some(name), Some(start), Some(end)) = (name, start, end) {
+            if start > 0 && start <= end {
+                lock_directives(&self.directives).sequence_regions
+                    .push((name.to_owned(), start - 1..end));
             }
         }
-        parts.sort_by_key(|ref elem| elem.sort_key());
-
-        Ok(GffTranscripts {
-            groups: parts.into_iter().group_by(TrxPart::transcript_group_key),
-            loose_codons: self.loose_codons,
-        })
     }
 
-    /// Creates an iterator of GFF rows.
-    pub(crate) fn raw_rows_stream(&mut self) -> GffRawRows<R> {
-        GffRawRows {
-            inner: self.inner.raw_rows()
+    /// Reads the sequences of the `##FASTA` section, if they are to be stored.
+    ///
+    /// The line buffer is expected to contain the first line of the section.
+    fn read_fasta(&mut self) -> io::Result<()> {
+        let mut directives = lock_directives(&self.directives);
+        let mut line = mem::replace(&mut self.line, Vec::new());
+        if !directives.read_fasta {
+            return Ok(());
+        }
+        if line.starts_with(b"##") {
+            line.clear();
+        }
+
+        let mut current: Option<(String, Vec<u8>)> = None;
+        loop {
+            if line.starts_with(b">") {
+                if let Some((name, seq)) = current.take() {
+                    let _ = directives.sequences.insert(name, seq);
+                }
+                let name = String::from_utf8_lossy(&line[1..]).split_whitespace()
+                    .next().unwrap_or("").to_owned();
+                current = Some((name, Vec::new()));
+            } else {
+                let seq_line = trim_line_end(&line);
+                if !seq_line.is_empty() {
+                    match current {
+                        Some((_, ref mut seq)) => seq.extend_from_slice(seq_line),
+                        None => return Err(io::Error::new(io::ErrorKind::InvalidData,
+                                                          "FASTA sequence without header")),
+                    }
+                }
+            }
+            line.clear();
+            if self.inner.read_until(b'\n', &mut line)? == 0 {
+                break;
+            }
         }
+        if let Some((name, seq)) = current {
+            let _ = directives.sequences.insert(name, seq);
+        }
+        Ok(())
     }
 }
 
-impl Reader<fs::File> {
+impl<R: io::Read> io::Read for FeatureRows<R> {
 
-    /// Creates a refFlat reader that reads from the given path.
-    pub fn from_file<P: AsRef<Path>>(path: P, gff_type: GffType) -> io::Result<Self> {
-        fs::File::open(path).map(|file| Reader::from_reader(file, gff_type))
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.pos >= self.line.len() && !self.fill_line()? {
+            return Ok(0);
+        }
+        let num_read = min(buf.len(), self.line.len() - self.pos);
+        buf[..num_read].copy_from_slice(&self.line[self.pos..self.pos + num_read]);
+        self.pos += num_read;
+        Ok(num_read)
     }
 }
 
-/// Iterator over GFF rows.
+/// Helper function for removing trailing whitespace and line endings from the given line.
+fn trim_line_end(line: &[u8]) -> &[u8] {
+    let end = line.iter()
+        .rposition(|&b| b != b'\n' && b != b'\r' && b != b' ' && b != b'\t')
+        .map(|idx| idx + 1)
+        .unwrap_or(0);
+    &line[..end]
+}
+
+/// Iterator over GFF rows, along with their line numbers.
 pub(crate) struct GffRawRows<'a, R: 'a> where R: io::Read {
-    inner: gff::RawRows<'a, R>,
+    inner: gff::RawRows<'a, FeatureRows<R>>,
+    line_numbers: LineNumbers,
+    num_lines: u64,
+}
+
+impl<'a, R> GffRawRows<'a, R> where R: io::Read {
+
+    /// Creates an iterator of the given rows, taking their line numbers from the given queue.
+    fn new(inner: gff::RawRows<'a, FeatureRows<R>>, line_numbers: LineNumbers) -> Self {
+        GffRawRows { inner: inner, line_numbers: line_numbers, num_lines: 0 }
+    }
 }
 
 impl<'a, R> Iterator for GffRawRows<'a, R> where R: io::Read {
 
-    type Item = ::Result<gff::RawRow>;
+    type Item = (u64, ::Result<gff::RawRow>);
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.inner.next()
-            .map(|row| row.map_err(|e| ::Error::from(GffError::from(e))))
+        let row = match self.inner.next() {
+            Some(row) => row.map_err(|e| ::Error::from(GffError::from(e))),
+            None => return None,
+        };
+        // rows that fail to be read may not have queued line numbers
+        if let Some(line) = lock_line_numbers(&self.line_numbers).pop_front() {
+            self.num_lines = line;
+        }
+        Some((self.num_lines, row))
     }
 }
 
@@ -286,6 +1427,12 @@ struct TrxPart {
     strand: Strand,
     transcript_id: String,
     gene_id: String,
+    source: Option<String>,
+    score: Option<f64>,
+    frame: Option<u8>,
+    partial: (bool, bool),
+    readthrough: bool,
+    attributes: Option<Attributes>,
 }
 
 /// The type used for sorting GFF records.
@@ -297,22 +1444,25 @@ type TrxSortKey = (String, String, String, u64, u64, u8);
 impl TrxPart {
 
     /// Creates a `TrxPart` from the given GFF row and the gene and transcript identifier regexes.
+    ///
+    /// The regexes are tried in order, and the first match is used as the identifier.
     fn try_from_row(
         row: gff::RawRow,
-        gx_regex: &Regex,
-        trx_regex: &Regex,
+        gx_regexes: &[Regex],
+        trx_regexes: &[Regex],
+        gff_type: GffType,
     ) -> Result<Self, GffError> {
 
-        let gx_id = gx_regex.captures(&row.8)
-            .and_then(|cap| cap.name("value"))
-            .map(|v| v.as_str().to_owned())
+        let gx_id = capture_id(gx_regexes, &row.8, gff_type)
             .ok_or(GffError::MissingGeneId)?;
 
-        let trx_id = trx_regex.captures(&row.8)
-            .and_then(|cap| cap.name("value"))
-            .map(|v| v.as_str().to_owned())
+        let trx_id = capture_id(trx_regexes, &row.8, gff_type)
             .ok_or(GffError::MissingTranscriptId)?;
 
+        let source = if row.1 != UNK_STR { Some(row.1) } else { None };
+        let score = row.5.parse::<f64>().ok();
+        let partial = parse_partial(&row.8, gff_type);
+
         Ok(TrxPart {
             feature: row.2,
             chrom: row.0,
@@ -320,6 +1470,12 @@ impl TrxPart {
             strand: Strand::from_char(&row.6).unwrap(),
             transcript_id: trx_id,
             gene_id: gx_id,
+            source: source,
+            score: score,
+            frame: parse_frame(&row.7),
+            partial: partial,
+            readthrough: false,
+            attributes: None,
         })
     }
 
@@ -331,16 +1487,12 @@ impl TrxPart {
 
     /// Returns a tuple of grouping key.
     fn transcript_group_key(&self) -> TrxGroupKey {
-        (self.gene_id.clone(), self.transcript_id.clone(), self.chrom.clone(), self.strand)
+        (self.gene_id.clone(), self.transcript_id.clone(), self.chrom.clone(), self.strand_ord())
     }
 
     /// Returns the u8 value for distinguishing strands.
     fn strand_ord(&self) -> u8 {
-        match &self.strand {
-            &Strand::Unknown => 0,
-            &Strand::Forward => 1,
-            &Strand::Reverse => 2,
-        }
+        strand_ord(&self.strand)
     }
 }
 
@@ -396,17 +1548,32 @@ impl TrxCoords {
     }
 
     /// Returns coordinates required to create a transcript.
+    ///
+    /// The genome-wise 5' and 3' ends of the CDS that are marked as open in `open_ends` do not
+    /// require codons, and are taken from the CDS coordinates instead.
     fn resolve<'a>(
         self,
         strand: Strand,
         loose_codons: bool,
+        open_ends: (bool, bool),
         tid: Option<&'a str>
     ) -> Result<RawTrxCoords, GffError> {
 
-        let trx_coord = self.trx_coord
-            .ok_or(GffError::MissingTranscript(tid.map(|v| v.to_owned())))?;
+        // transcripts without their own rows, as in exon-only GTF files, span their exons
+        let trx_coord = match self.trx_coord {
+            Some(coord) => coord,
+            None if !self.exon_coords.is_empty() => self.exon_coords.iter()
+                .fold(INIT_COORD, |(start, end), &(a, b)| (min(start, a), max(end, b))),
+            None => return Err(GffError::MissingTranscript(tid.map(|v| v.to_owned()))),
+        };
+
+        let cds_coord = self.cds_coord;
+        let codon_5 = self.codon_5
+            .or_else(|| if open_ends.0 { cds_coord.map(|c| c.0) } else { None });
+        let codon_3 = self.codon_3
+            .or_else(|| if open_ends.1 { cds_coord.map(|c| c.1) } else { None });
 
-        let coding_coord = match (self.codon_5, self.codon_3) {
+        let coding_coord = match (codon_5, codon_3) {
             // common case: stop and start codon defined
             (Some(c5), Some(c3)) => Some((c5, c3)),
             // expected case: no stop and start codon defined
@@ -434,10 +1601,10 @@ impl TrxCoords {
                 let cdsc = self.cds_coord
                     .ok_or(GffError::OrphanCodon(tid.map(|v| v.to_owned())))?;
                 match strand {
-                    Strand::Forward if end == cdsc.1 => {
+                    Strand::Forward if end == cdsc.1 && !open_ends.1 => {
                         return Err(GffError::StopCodonInCds(tid.map(|v| v.to_owned())));
                     },
-                    Strand::Reverse if start > cdsc.0 => {
+                    Strand::Reverse if start > cdsc.0 && !open_ends.0 => {
                         return Err(GffError::StopCodonInCds(tid.map(|v| v.to_owned())));
                     },
                     _ => {},
@@ -452,13 +1619,27 @@ impl TrxCoords {
 /// Iterator over transcripts created from GFF records.
 pub struct GffTranscripts {
     groups: GroupBy<TrxGroupKey, vec::IntoIter<TrxPart>, TrxGroupFunc>,
+    assembly: Assembly,
+    seq_names: SeqNamePool,
+    cancellation: Cancellation,
+}
+
+/// Helper struct for the settings of creating transcripts from grouped rows.
+#[derive(Debug, Clone, Copy)]
+struct Assembly {
+    /// Whether CDS coordinates are used when start and/or stop codons can not be found.
     loose_codons: bool,
+    /// Whether CDS rows include the stop codon of transcripts without codon rows.
+    cds_incl_stop: bool,
+    /// Whether CDS rows are used as exons of transcripts without exon rows.
+    cds_exons: bool,
 }
 
 /// The type used for grouping records into transcripts.
 ///
 /// The tuple elements represent gene identifier, transcript identifier, sequence name, and strand.
-type TrxGroupKey = (String, String, String, Strand);
+/// Strands are stored as their `u8` values, since unknown strands are not equal to each other.
+type TrxGroupKey = (String, String, String, u8);
 
 /// The type of the function used for creating record-grouping keys for transcripts.
 type TrxGroupFunc = fn(&TrxPart) -> TrxGroupKey;
@@ -471,64 +1652,575 @@ impl Iterator for GffTranscripts {
     type Item = ::Result<Transcript>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let group_to_transcript = |(key, tps): (TrxGroupKey, TrxGroup)| {
-            let (gid, tid, chrom, strand) = key;
-            let mut tc = TrxCoords::default();
-
-            for tp in tps {
-                match (tp.feature.as_str(), strand) {
-                    (TRANSCRIPT_STR, _) => {
-                        tc.set_trx_coord(tp.coord)
-                            .map_err(::Error::from)?;
-                    },
-                    (EXON_STR, _) => {
-                        tc.add_exon_coord(tp.coord);
-                    },
-                    (CDS_STR, _) => {
-                        tc.include_cds_coord(tp.coord);
-                    },
-                    (START_CODON_STR, Strand::Forward) | (STOP_CODON_STR, Strand::Reverse) => {
-                        tc.include_codon_5(tp.coord.0);
-                    },
-                    (STOP_CODON_STR, Strand::Forward) | (START_CODON_STR, Strand::Reverse) => {
-                        tc.include_codon_3(tp.coord.1);
-                    },
-                    _ => {},
+        match self.cancellation.proceed() {
+            Ok(true) => {},
+            Ok(false) => return None,
+            Err(err) => return Some(Err(err)),
+        }
+        let assembly = self.assembly;
+        let seq_names = &mut self.seq_names;
+        self.groups.into_iter()
+            .map(|(key, tps): (TrxGroupKey, TrxGroup)| {
+                let seq_name = seq_names.intern(&key.2);
+                group_to_transcript(key, seq_name, tps, assembly)
+            })
+            .next()
+    }
+}
+
+/// Iterator over regulatory features created from GFF records.
+pub struct GffRegulatoryFeatures<'a, R: 'a> where R: io::Read {
+    rows: GffRawRows<'a, R>,
+    prefix: Option<String>,
+    lstrip: Option<String>,
+    gff_type: GffType,
+}
+
+impl<'a, R> Iterator for GffRegulatoryFeatures<'a, R> where R: io::Read {
+
+    type Item = ::Result<RegulatoryFeature>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let mut row = match self.rows.next() {
+                Some((_, Ok(row))) => row,
+                Some((line, Err(err))) => return Some(Err(::Error::at_line(line, err))),
+                None => return None,
+            };
+            let kind = match RegulatoryKind::from_feature_type(&row.2) {
+                Some(kind) => kind,
+                None => continue,
+            };
+            update_seq_name(&mut row.0, self.prefix.as_deref(),
+                            self.lstrip.as_deref().map(|v| (v, v.len())));
+
+            // Ensembl identifiers are prefixed with their feature types
+            let id_prefix = format!("{}:", row.2);
+            let mut id = None;
+            let mut attribs = Attributes::new();
+            for (key, value) in parse_attributes(&row.8, self.gff_type) {
+                if key == ID_STR && id.is_none() {
+                    id = Some(
+                        if value.starts_with(id_prefix.as_str()) {
+                            value[id_prefix.len()..].to_owned()
+                        } else {
+                            value
+                        });
+                } else {
+                    attribs.insert(key, value);
                 }
             }
+            let source = if row.1 != UNK_STR { Some(row.1) } else { None };
+            return Some(Ok(RegulatoryFeature::new(
+                row.0, (row.3 - 1, row.4), Strand::from_char(&row.6).unwrap(), kind, id,
+                source, row.5.parse::<f64>().ok(), attribs)));
+        }
+    }
+}
 
-            let ((trx_start, trx_end), exn_coords, coding_coord) =
-                tc.resolve(strand, self.loose_codons, Some(tid.as_str()))
-                    .map_err(::Error::from)?;
+/// Iterator over genes created from GFF records.
+pub struct GffGenes {
+    inner: iter::Peekable<GffTranscripts>,
+}
 
-            TBuilder::new(chrom, trx_start, trx_end)
-                .id(tid)
-                .gene_id(gid)
-                .strand(strand)
-                .coords(exn_coords, coding_coord)
-                .coding_incl_stop(true)
-                .build()
+impl Iterator for GffGenes {
+
+    type Item = ::Result<Gene>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        next_gene(&mut self.inner)
+    }
+}
+
+/// Creates a gene from the next transcripts of the given iterator that have the same gene
+/// identifier, sequence name, and strand.
+fn next_gene<I>(transcripts: &mut iter::Peekable<I>) -> Option<::Result<Gene>>
+    where I: Iterator<Item=::Result<Transcript>>
+{
+    let first = match transcripts.next() {
+        Some(Ok(trx)) => trx,
+        Some(Err(err)) => return Some(Err(err)),
+        None => return None,
+    };
+    let key = (first.gene_id().map(|v| v.to_owned()), first.seq_name().to_owned(),
+               *first.strand());
+    let mut gene_transcripts = vec![first];
+    loop {
+        let same_gene = match transcripts.peek() {
+            Some(&Ok(ref trx)) =>
+                trx.gene_id() == key.0.as_deref() && trx.seq_name() == key.1
+                    && strand_ord(trx.strand()) == strand_ord(&key.2),
+            _ => false,
         };
+        if !same_gene {
+            break;
+        }
+        if let Some(Ok(trx)) = transcripts.next() {
+            gene_transcripts.push(trx);
+        }
+    }
+    Some(transcripts_to_gene(key.0, key.1, key.2, gene_transcripts))
+}
+
+/// Creates a gene from the given transcripts, which must all have the same gene identifier,
+/// sequence name, and strand.
+fn transcripts_to_gene(
+    gid: Option<String>,
+    seq_name: String,
+    strand: Strand,
+    transcripts: Vec<Transcript>,
+) -> ::Result<Gene> {
+    let gid = gid.ok_or(::Error::from(GffError::MissingGeneId))?;
+    let (start, end) = transcripts.iter()
+        .fold(INIT_COORD, |(start, end), trx| (min(start, trx.start()), max(end, trx.end())));
+    let trxs = transcripts.into_iter()
+        .map(|trx| (trx.id().unwrap_or(DEF_ID).to_owned(), trx))
+        .collect::<LinkedHashMap<String, Transcript>>();
+    GBuilder::new(seq_name, start, end)
+        .id(gid)
+        .strand(strand)
+        .transcripts(trxs)
+        .build()
+}
+
+/// Creates a transcript from the given grouped transcript parts.
+///
+/// If CDS rows include the stop codon, as in RefSeq and prokaryotic GFF3 files, transcripts
+/// without codon rows take their coding regions from their CDS rows.
+fn group_to_transcript<I>(
+    key: TrxGroupKey,
+    seq_name: SeqName,
+    tps: I,
+    assembly: Assembly,
+) -> ::Result<Transcript>
+    where I: IntoIterator<Item=TrxPart>
+{
+    let (gid, tid, _, strand) = key;
+    let strand = match strand {
+        1 => Strand::Forward,
+        2 => Strand::Reverse,
+        _ => Strand::Unknown,
+    };
+    let mut tc = TrxCoords::default();
+    let mut trx_attribs = None;
+    let mut trx_source_score = (None, None);
+    let mut exon_attribs = Vec::new();
+    let mut exon_source_scores = Vec::new();
+    let mut cds_frames = Vec::new();
+    let mut cds_coords = Vec::new();
+    let mut partial = (false, false);
+    let mut readthrough = false;
+
+    for mut tp in tps {
+        let attribs = tp.attributes.take();
+        if tp.feature == TRANSCRIPT_STR || tp.feature == CDS_STR {
+            partial = (partial.0 || tp.partial.0, partial.1 || tp.partial.1);
+        }
+        readthrough = readthrough || tp.readthrough;
+        match (tp.feature.as_str(), strand) {
+            (TRANSCRIPT_STR, _) => {
+                tc.set_trx_coord(tp.coord)
+                    .map_err(::Error::from)?;
+                trx_attribs = attribs;
+                trx_source_score = (tp.source, tp.score);
+            },
+            (EXON_STR, _) => {
+                tc.add_exon_coord(tp.coord);
+                if let Some(values) = attribs {
+                    exon_attribs.push((tp.coord, values));
+                }
+                if tp.source.is_some() || tp.score.is_some() {
+                    exon_source_scores.push((tp.coord, tp.source, tp.score));
+                }
+            },
+            (CDS_STR, _) => {
+                tc.include_cds_coord(tp.coord);
+                if let Some(frame) = tp.frame {
+                    cds_frames.push((tp.coord, frame));
+                }
+                // CDS rows only have attributes when they stand for whole transcripts
+                if let (Some(values), true) = (attribs, trx_attribs.is_none()) {
+                    trx_attribs = Some(values);
+                    trx_source_score = (tp.source, tp.score);
+                }
+                cds_coords.push(tp.coord);
+            },
+            (START_CODON_STR, Strand::Forward) | (STOP_CODON_STR, Strand::Reverse) => {
+                tc.include_codon_5(tp.coord.0);
+            },
+            (STOP_CODON_STR, Strand::Forward) | (START_CODON_STR, Strand::Reverse) => {
+                tc.include_codon_3(tp.coord.1);
+            },
+            _ => {},
+        }
+    }
+
+    // partial CDS ends are only taken as incomplete if they have no codon rows
+    let has_cds = tc.cds_coord.is_some();
+    let open_ends = (has_cds && partial.0 && tc.codon_5.is_none(),
+                     has_cds && partial.1 && tc.codon_3.is_none());
+    let (open_5p, open_3p) =
+        if strand == Strand::Reverse { (open_ends.1, open_ends.0) } else { open_ends };
+
+    if assembly.cds_exons && tc.exon_coords.is_empty() {
+        // overlapping CDS rows, as in programmed frameshifts, are merged into one exon
+        tc.exon_coords = merged_coords(cds_coords.into_iter()
+            .map(|(start, end)| Interval::new(start..end).unwrap()));
+    }
+    let cds_incl_stop =
+        assembly.cds_incl_stop && tc.codon_5.is_none() && tc.codon_3.is_none();
+    if let (true, Some((cds_start, cds_end))) = (cds_incl_stop, tc.cds_coord) {
+        tc.include_codon_5(cds_start);
+        tc.include_codon_3(cds_end);
+    }
+
+    let loose_codons = assembly.loose_codons || cds_incl_stop;
+    let ((trx_start, trx_end), exn_coords, coding_coord) =
+        tc.resolve(strand, loose_codons, open_ends, Some(tid.as_str()))
+            .map_err(::Error::from)?;
+
+    let mut transcript = TBuilder::new(seq_name, trx_start, trx_end)
+        .id(tid)
+        .gene_id(gid)
+        .strand(strand)
+        .coords(exn_coords, coding_coord)
+        .coding_incl_stop(true)
+        .cds_5p_incomplete(open_5p)
+        .cds_3p_incomplete(open_3p)
+        .stop_readthrough(readthrough)
+        .build()?;
+
+    if let Some(values) = trx_attribs {
+        let _ = transcript.set_attributes(values);
+    }
+    transcript.set_source(trx_source_score.0);
+    transcript.set_score(trx_source_score.1);
+    for exon in transcript.exons_mut() {
+        let coord = (exon.start(), exon.end());
+        if let Some(idx) = exon_attribs.iter().position(|&(c, _)| c == coord) {
+            let values = exon_attribs.swap_remove(idx).1;
+            let _ = exon.set_attributes(values);
+        }
+        if let Some(idx) = exon_source_scores.iter().position(|&(c, _, _)| c == coord) {
+            let (_, source, score) = exon_source_scores.swap_remove(idx);
+            exon.set_source(source);
+            exon.set_score(score);
+        }
+        // frames of the CDS rows take precedence over the ones computed from the coordinates
+        for fx in exon.features_mut() {
+            if let EFK::CDS { .. } = *fx.kind() {
+                let coord = (fx.start(), fx.end());
+                if let Some(&(_, frame)) = cds_frames.iter().find(|&&(c, _)| c == coord) {
+                    fx.set_kind(EFK::CDS { frame: Some(frame) });
+                }
+            }
+        }
+    }
+
+    Ok(transcript)
+}
+
+/// Helper function for getting the source, score, and attributes of the given row.
+///
+/// The source and score columns are only included if they have known values. Gene and
+/// transcript identifier attributes, including the given identifier keys, are excluded.
+fn row_attributes(row: &gff::RawRow, gff_type: GffType, id_attrs: &[String])
+    -> Vec<(String, String)>
+{
+    let mut attribs = Vec::new();
+    if row.1 != UNK_STR {
+        attribs.push(("source".to_owned(), row.1.clone()));
+    }
+    if row.5 != UNK_STR {
+        attribs.push(("score".to_owned(), row.5.clone()));
+    }
+    attribs.extend(parse_raw_attributes(&row.8, gff_type, id_attrs));
+    attribs
+}
+
+/// Helper function for parsing the attributes column of a row, excluding gene and transcript
+/// identifier attributes, including the given identifier keys.
+pub(crate) fn parse_raw_attributes(raw: &str, gff_type: GffType, id_attrs: &[String])
+    -> Vec<(String, String)>
+{
+    parse_attributes(raw, gff_type).into_iter()
+        .filter(|&(ref key, _)| {
+            key != GENE_ID_STR && key != TRANSCRIPT_ID_STR && !id_attrs.contains(key)
+        })
+        .collect()
+}
+
+/// Helper function for parsing the attributes column of a row, in order of appearance.
+///
+/// GFF3 keys and values are percent-decoded, while GTF and GFF2 values may contain semicolons
+/// if they are quoted.
+fn parse_attributes(raw: &str, gff_type: GffType) -> Vec<(String, String)> {
+    let mut attribs = Vec::new();
+    match gff_type {
+        GffType::GFF3 => {
+            for item in raw.split(';').map(|item| item.trim()).filter(|item| !item.is_empty()) {
+                let mut kv = item.splitn(2, '=');
+                let key = gff3_unescape(kv.next().unwrap_or("").trim());
+                for value in kv.next().unwrap_or("").split(',') {
+                    attribs.push((key.clone(), gff3_unescape(value)));
+                }
+            }
+        },
+        _ => {
+            for item in split_quoted(raw).into_iter()
+                .map(|item| item.trim())
+                .filter(|item| !item.is_empty())
+            {
+                let (key, value) = match item.find(char::is_whitespace) {
+                    Some(idx) => (&item[..idx], item[idx..].trim()),
+                    None => (item, ""),
+                };
+                let value =
+                    if value.len() >= 2 && value.starts_with('"') && value.ends_with('"') {
+                        &value[1..value.len() - 1]
+                    } else {
+                        value.trim_matches('"')
+                    };
+                attribs.push((key.to_owned(), value.to_owned()));
+            }
+        },
+    }
+    attribs
+}
+
+/// Helper function for getting whether a GFF3 feature is partial at its genome-wise 5' and 3'
+/// ends, using its `partial`, `start_range`, and `end_range` attributes.
+///
+/// Features marked as partial without either range attribute are taken as partial at both ends.
+fn parse_partial(raw: &str, gff_type: GffType) -> (bool, bool) {
+    match gff_type {
+        GffType::GFF3 if raw.contains("partial") => {},
+        _ => return (false, false),
+    }
+    let attribs = parse_attributes(raw, gff_type);
+    if !attribs.iter().any(|&(ref key, ref value)| key == "partial" && value == "true") {
+        return (false, false);
+    }
+    // range values are split on their comma, with open ends written as dots
+    let is_open = |range_key: &str| {
+        attribs.iter().any(|&(ref key, ref value)| key == range_key && value == UNK_STR)
+    };
+    match (is_open("start_range"), is_open("end_range")) {
+        (false, false) => (true, true),
+        ends => ends,
+    }
+}
+
+/// Helper function for checking whether the given attributes column has a `tag` attribute that
+/// marks stop codon readthrough, such as `seleno`.
+fn has_readthrough_tag(raw: &str, gff_type: GffType) -> bool {
+    raw.contains("tag") &&
+        parse_attributes(raw, gff_type).iter()
+            .any(|&(ref key, ref value)| key == "tag" && is_readthrough_tag(value))
+}
+
+/// Helper function for splitting a GTF or GFF2 attributes column on semicolons that are not
+/// within quoted values.
+fn split_quoted(raw: &str) -> Vec<&str> {
+    let mut items = Vec::new();
+    let (mut start, mut quoted) = (0, false);
+    for (idx, chr) in raw.char_indices() {
+        match chr {
+            '"' => quoted = !quoted,
+            ';' if !quoted => {
+                items.push(&raw[start..idx]);
+                start = idx + 1;
+            },
+            _ => {},
+        }
+    }
+    items.push(&raw[start..]);
+    items
+}
 
-        self.groups.into_iter().map(group_to_transcript).next()
+/// Decodes the percent-encoded characters of the given GFF3 column value.
+///
+/// Percent signs that do not start a valid escape sequence are kept as-is.
+pub fn gff3_unescape(value: &str) -> String {
+    if !value.contains('%') {
+        return value.to_owned();
+    }
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut idx = 0;
+    while idx < bytes.len() {
+        if bytes[idx] == b'%' && idx + 2 < bytes.len() {
+            if let (Some(hi), Some(lo)) = (hex_value(bytes[idx + 1]), hex_value(bytes[idx + 2])) {
+                decoded.push(hi << 4 | lo);
+                idx += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[idx]);
+        idx += 1;
+    }
+    String::from_utf8(decoded)
+        .unwrap_or_else(|err| String::from_utf8_lossy(&err.into_bytes()).into_owned())
+}
+
+/// Encodes the characters of the given value that have special meanings in GFF3 columns.
+///
+/// Control characters, including tabs and newlines, and the `%`, `;`, `=`, `&`, and `,`
+/// characters are percent-encoded.
+pub fn gff3_escape(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for chr in value.chars() {
+        match chr {
+            chr if (chr as u32) < 0x20 || "%;=&,\x7f".contains(chr) =>
+                encoded.push_str(&format!("%{:02X}", chr as u32)),
+            chr => encoded.push(chr),
+        }
+    }
+    encoded
+}
+
+/// Returns the value of the given hexadecimal digit.
+fn hex_value(digit: u8) -> Option<u8> {
+    match digit {
+        b'0'...b'9' => Some(digit - b'0'),
+        b'a'...b'f' => Some(digit - b'a' + 10),
+        b'A'...b'F' => Some(digit - b'A' + 10),
+        _ => None,
     }
 }
 
+/// Helper function for getting the first identifier captured by the given regexes.
+///
+/// GFF3 identifiers are percent-decoded.
+fn capture_id(regexes: &[Regex], raw: &str, gff_type: GffType) -> Option<String> {
+    regexes.iter()
+        .filter_map(|re| re.captures(raw))
+        .filter_map(|cap| cap.name("quoted").or_else(|| cap.name("value")))
+        .map(|v| match gff_type {
+            GffType::GFF3 => gff3_unescape(v.as_str()),
+            _ => v.as_str().to_owned(),
+        })
+        .next()
+}
+
+/// Helper function to create regexes for parsing GFF identifiers with the given keys.
+fn make_gff_id_regexes(attr_names: &[String], gff_type: GffType) -> ::Result<Vec<Regex>> {
+    attr_names.iter()
+        .map(|attr_name| make_gff_id_regex(attr_name, gff_type))
+        .collect()
+}
+
 /// Helper function to create regex for parsing GFF identifiers.
+///
+/// The attribute key must appear at the start of the attributes column or after a separator,
+/// so that keys such as `locus_tag` do not match within other keys such as `old_locus_tag`.
+/// Quoted GTF and GFF2 values are captured in the `quoted` group, and may contain semicolons.
 fn make_gff_id_regex(attr_name: &str, gff_type: GffType) -> ::Result<Regex> {
-    let fmts = match gff_type {
-        GffType::GFF2 | GffType::GTF2 => Ok((" ", ";", r#"""#)),
-        GffType::GFF3 => Ok(("=", ",", "")),
-        _ => Err(::Error::from(GffError::UnsupportedGffType)),
+    let key = regex::escape(attr_name);
+    let pat = match gff_type {
+        GffType::GFF2 | GffType::GTF2 =>
+            format!(r#"(?:^|[;\s]){}\s+(?:"(?P<quoted>[^"]*)"|(?P<value>[^;\s"]+))"#, key),
+        GffType::GFF3 => format!(r#"(?:^|;)\s*{}=(?P<value>[^;,\t]+)"#, key),
+        _ => return Err(::Error::from(GffError::UnsupportedGffType)),
     };
-    fmts.and_then(|(delim, term, nest)| {
-        let pat = format!(
-            r#"{attr_name}{delim}{nest}(?P<value>[^{delim}{term}\t]+){nest}{term}?"#,
-            attr_name=attr_name, delim=delim, term=term, nest=nest);
-        Regex::new(&pat)
+    Regex::new(&pat)
+        .map_err(|e| ::Error::from(GffError::from(e)))
+}
+
+/// Stop codon conventions of GFF records created from genes, transcripts, and exons.
+///
+/// By default, CDS records exclude the stop codon and explicit `stop_codon` records are
+/// written, following the Ensembl and GENCODE convention.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GffCodingOptions {
+    cds_incl_stop: bool,
+    stop_codon_records: bool,
+}
+
+impl Default for GffCodingOptions {
+    fn default() -> Self {
+        GffCodingOptions {
+            cds_incl_stop: false,
+            stop_codon_records: true,
+        }
+    }
+}
+
+impl GffCodingOptions {
+
+    /// Creates options with the default conventions.
+    pub fn new() -> Self {
+        GffCodingOptions::default()
+    }
+
+    /// Sets whether CDS records are extended to include the stop codon.
+    ///
+    /// Stop codon parts that are not adjacent to any CDS of their exons, such as those split
+    /// off by an intron, are written as separate CDS records.
+    pub fn cds_incl_stop(mut self, cds_incl_stop: bool) -> Self {
+        self.cds_incl_stop = cds_incl_stop;
+        self
+    }
+
+    /// Sets whether explicit `stop_codon` records are written.
+    pub fn stop_codon_records(mut self, stop_codon_records: bool) -> Self {
+        self.stop_codon_records = stop_codon_records;
+        self
+    }
+}
+
+/// GFF writer.
+pub struct Writer<W: io::Write> {
+    inner: gff::Writer<W>,
+    coding_options: GffCodingOptions,
+}
+
+impl<W: io::Write> Writer<W> {
+
+    /// Creates a GFF writer of the given variant from another writer.
+    pub fn from_writer(in_writer: W, gff_type: GffType) -> Writer<W> {
+        Writer {
+            inner: gff::Writer::new(in_writer, gff_type),
+            coding_options: GffCodingOptions::default(),
+        }
+    }
+
+    /// Sets the stop codon conventions of the records written for transcripts and genes.
+    pub fn coding_options(&mut self, coding_options: GffCodingOptions) -> &mut Self {
+        self.coding_options = coding_options;
+        self
+    }
+
+    /// Writes the given record.
+    pub fn write_record(&mut self, record: &gff::Record) -> ::Result<()> {
+        self.inner.write(record)
             .map_err(|e| ::Error::from(GffError::from(e)))
-    })
+    }
+
+    /// Writes the given transcript as a transcript row followed by the rows of its exons.
+    pub fn write_transcript(&mut self, transcript: &Transcript) -> ::Result<()> {
+        for record in transcript.clone().into_gff_records_with(self.coding_options)? {
+            self.write_record(&record)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the given gene as a gene row followed by the rows of its transcripts.
+    pub fn write_gene(&mut self, gene: &Gene) -> ::Result<()> {
+        for record in gene.clone().into_gff_records_with(self.coding_options)? {
+            self.write_record(&record)?;
+        }
+        Ok(())
+    }
+}
 
+impl Writer<fs::File> {
+
+    /// Creates a GFF writer of the given variant that writes to the given path.
+    pub fn from_file<P: AsRef<Path>>(path: P, gff_type: GffType) -> io::Result<Self> {
+        let f = fs::File::create(path)?;
+        Ok(Writer::from_writer(f, gff_type))
+    }
 }
 
 impl Gene {
@@ -543,15 +2235,22 @@ impl Gene {
 
     // TODO: also handle gene-level features
     /// Transforms the gene into GFF records.
-    pub fn into_gff_records(mut self) -> ::Result<Vec<gff::Record>> {
+    pub fn into_gff_records(self) -> ::Result<Vec<gff::Record>> {
+        self.into_gff_records_with(GffCodingOptions::default())
+    }
+
+    /// Transforms the gene into GFF records, following the given stop codon conventions.
+    pub fn into_gff_records_with(mut self, opts: GffCodingOptions)
+        -> ::Result<Vec<gff::Record>>
+    {
 
-        let mut attribs = self.set_attributes(MultiMap::new());
+        let mut attribs = self.set_attributes(Attributes::new());
 
         self.id()
             .ok_or(GffError::MissingGeneId)
             .map(|gid| attribs.insert(GENE_ID_STR.to_owned(), gid.to_owned()))?;
 
-        let (source, score) = extract_source_score(&mut attribs);
+        let (source, score) = extract_source_score(&mut attribs, self.source(), self.score());
 
         let mut recs = Vec::with_capacity(self.num_records());
 
@@ -561,13 +2260,13 @@ impl Gene {
             .score(score)
             .strand(strand_to_char(&self.strand()))
             .frame(UNK_CHAR)
-            .attributes(attribs)
+            .attributes(MultiMap::from(attribs))
             .build()
             .map_err(|e| ::Error::from(GffError::from(e)))?;
         recs.push(gx_record);
 
         for (_, transcript) in self.take_transcripts() {
-            recs.append(&mut transcript.into_gff_records()?);
+            recs.append(&mut transcript.into_gff_records_with(opts)?);
         }
 
         Ok(recs)
@@ -586,9 +2285,22 @@ impl Transcript {
 
     // TODO: also handle transcript-level features
     /// Transforms the transcript into GFF records.
-    pub fn into_gff_records(mut self) -> ::Result<Vec<gff::Record>> {
+    ///
+    /// The frames of coding features are computed using `fill_coding_frames` if unknown. Exons
+    /// are given `exon_number` attributes numbered from the transcript-wise 5' end, and an error
+    /// is returned if an exon already has a different `exon_number` attribute.
+    pub fn into_gff_records(self) -> ::Result<Vec<gff::Record>> {
+        self.into_gff_records_with(GffCodingOptions::default())
+    }
+
+    /// Transforms the transcript into GFF records, following the given stop codon conventions.
+    pub fn into_gff_records_with(mut self, opts: GffCodingOptions)
+        -> ::Result<Vec<gff::Record>>
+    {
 
-        let mut attribs = self.set_attributes(MultiMap::new());
+        self.fill_coding_frames();
+
+        let mut attribs = self.set_attributes(Attributes::new());
 
         self.gene_id()
             .ok_or(GffError::MissingGeneId)
@@ -598,7 +2310,7 @@ impl Transcript {
             .ok_or(GffError::MissingTranscriptId)
             .map(|tid| attribs.insert(TRANSCRIPT_ID_STR.to_owned(), tid.to_owned()))?;
 
-        let (source, score) = extract_source_score(&mut attribs);
+        let (source, score) = extract_source_score(&mut attribs, self.source(), self.score());
 
         let mut recs = Vec::with_capacity(self.num_records());
 
@@ -608,13 +2320,19 @@ impl Transcript {
             .score(score)
             .strand(strand_to_char(&self.strand()))
             .frame(UNK_CHAR)
-            .attributes(attribs)
+            .attributes(MultiMap::from(attribs))
             .build()
             .map_err(|e| ::Error::from(GffError::from(e)))?;
         recs.push(trx_record);
 
-        for exon in self.take_exons() {
-            recs.append(&mut exon.into_gff_records()?);
+        let reverse = self.strand() == &Strand::Reverse;
+        let tid = self.id().map(|v| v.to_owned());
+        let exons = self.take_exons();
+        let num_exons = exons.len();
+        for (idx, mut exon) in exons.into_iter().enumerate() {
+            let number = if reverse { num_exons - idx } else { idx + 1 };
+            set_exon_number(&mut exon, number, &tid)?;
+            recs.append(&mut exon.into_gff_records_with(opts)?);
         }
 
         Ok(recs)
@@ -642,9 +2360,16 @@ impl EFK {
 impl Exon {
 
     /// Transforms the exon into GFF records.
-    pub fn into_gff_records(mut self) -> ::Result<Vec<gff::Record>> {
+    pub fn into_gff_records(self) -> ::Result<Vec<gff::Record>> {
+        self.into_gff_records_with(GffCodingOptions::default())
+    }
+
+    /// Transforms the exon into GFF records, following the given stop codon conventions.
+    pub fn into_gff_records_with(mut self, opts: GffCodingOptions)
+        -> ::Result<Vec<gff::Record>>
+    {
 
-        let mut attribs = self.set_attributes(MultiMap::new());
+        let mut attribs = self.set_attributes(Attributes::new());
 
         self.gene_id()
             .ok_or(GffError::MissingGeneId)
@@ -654,48 +2379,111 @@ impl Exon {
             .ok_or(GffError::MissingTranscriptId)
             .map(|tid| attribs.insert(TRANSCRIPT_ID_STR.to_owned(), tid.to_owned()))?;
 
-        let (source, score) = extract_source_score(&mut attribs);
+        let (source, score) = extract_source_score(&mut attribs, self.source(), self.score());
 
         let mut recs = Vec::with_capacity(1 + self.features().len());
 
-        for (idx, fx) in self.features().iter().enumerate() {
-            let (feature, frame) = fx.kind().get_feature_frame();
-            let fx_record = gff::RecordBuilder::new(self.seq_name(), fx.start(), fx.end())
+        let exn_record = gff::RecordBuilder::new(self.seq_name(), self.start(), self.end())
+            .source(source.as_str())
+            .feature_type(EXON_STR)
+            .score(score.as_str())
+            .strand(strand_to_char(&self.strand()))
+            .frame(UNK_CHAR)
+            .attributes(MultiMap::from(attribs.clone()))
+            .build()
+            .map_err(|e| ::Error::from(GffError::from(e)))?;
+        recs.push(exn_record);
+
+        for (feature, start, end, frame) in self.gff_feature_rows(opts) {
+            let fx_record = gff::RecordBuilder::new(self.seq_name(), start, end)
                 .source(source.as_str())
                 .feature_type(feature.as_str())
                 .score(score.as_str())
                 .strand(strand_to_char(&self.strand()))
                 .frame(frame)
-                .attributes(attribs.clone())
+                .attributes(MultiMap::from(attribs.clone()))
                 .build()
                 .map_err(|e| ::Error::from(GffError::from(e)))?;
+            recs.push(fx_record);
+        }
+
+        Ok(recs)
+    }
 
-            recs[1 + idx] = fx_record;
+    /// Returns the feature names, coordinates, and frames of the GFF records of the exon
+    /// features, following the given stop codon conventions.
+    fn gff_feature_rows(&self, opts: GffCodingOptions) -> Vec<(String, u64, u64, char)> {
+        let (mut cdss, mut stops): (Vec<Coord<u64>>, Vec<Coord<u64>>) = (vec![], vec![]);
+        for fx in self.features().iter() {
+            match fx.kind() {
+                &EFK::CDS { .. } => cdss.push((fx.start(), fx.end())),
+                &EFK::StopCodon { .. } => stops.push((fx.start(), fx.end())),
+                _ => {},
+            }
         }
+        let adjacent = |a: Coord<u64>, b: Coord<u64>| a.1 == b.0 || b.1 == a.0;
 
-        let exn_record = gff::RecordBuilder::new(self.seq_name(), self.start(), self.end())
-            .source(source.as_str())
-            .feature_type(EXON_STR)
-            .score(score.as_str())
-            .strand(strand_to_char(&self.strand()))
-            .frame(UNK_CHAR)
-            .attributes(attribs)
-            .build()
-            .map_err(|e| ::Error::from(GffError::from(e)))?;
-        recs[0] = exn_record;
+        let mut rows = Vec::with_capacity(self.features().len());
+        for fx in self.features().iter() {
+            let (feature, frame) = fx.kind().get_feature_frame();
+            let (mut start, mut end) = (fx.start(), fx.end());
+            match fx.kind() {
+                &EFK::CDS { .. } if opts.cds_incl_stop => {
+                    let span = (fx.start(), fx.end());
+                    for &(s, e) in stops.iter().filter(|&&stop| adjacent(span, stop)) {
+                        start = min(start, s);
+                        end = max(end, e);
+                    }
+                },
+                &EFK::StopCodon { .. } => {
+                    let merged = cdss.iter().any(|&c| adjacent(c, (start, end)));
+                    if opts.cds_incl_stop && !merged {
+                        rows.push((CDS_STR.to_owned(), start, end, frame));
+                    }
+                    if !opts.stop_codon_records {
+                        continue;
+                    }
+                },
+                _ => {},
+            }
+            rows.push((feature, start, end, frame));
+        }
+        rows
+    }
+}
 
-        Ok(recs)
+/// Helper function to set the `exon_number` attribute of an exon, or to check it if present.
+fn set_exon_number(exon: &mut Exon, number: usize, tid: &Option<String>) -> ::Result<()> {
+    let number = number.to_string();
+    let existing = exon.attributes().get(EXON_NUMBER_STR).cloned();
+    match existing {
+        Some(value) => {
+            if value != number {
+                return Err(::Error::from(GffError::InvalidExonNumber(tid.clone(), value)));
+            }
+        },
+        None => exon.attributes_mut().insert(EXON_NUMBER_STR.to_owned(), number),
     }
+    Ok(())
 }
 
-/// Helper function to extract source and score attributes.
+/// Helper function to extract source and score values.
+///
+/// The given source and score take precedence over the `source` and `score` attributes, which
+/// are always removed from the attributes.
 #[inline(always)]
-fn extract_source_score(attributes: &mut MultiMap<String, String>) -> (String, String) {
-    let source = attributes.remove("source")
-        .and_then(|mut vec| vec.pop())
+fn extract_source_score(
+    attributes: &mut Attributes,
+    source: Option<&str>,
+    score: Option<f64>,
+) -> (String, String) {
+    let source_attr = attributes.remove("source").and_then(|mut vec| vec.pop());
+    let score_attr = attributes.remove("score").and_then(|mut vec| vec.pop());
+    let source = source.map(|v| v.to_owned())
+        .or(source_attr)
         .unwrap_or(UNK_STR.to_owned());
-    let score = attributes.remove("score")
-        .and_then(|mut vec| vec.pop())
+    let score = score.map(|v| v.to_string())
+        .or(score_attr)
         .unwrap_or(UNK_STR.to_owned());
     (source, score)
 }
@@ -710,6 +2498,17 @@ fn strand_to_char(strand: &Strand) -> char {
     }
 }
 
+/// Helper function to parse the frame column of a GFF row.
+#[inline(always)]
+fn parse_frame(frame: &str) -> Option<u8> {
+    match frame {
+        "0" => Some(0),
+        "1" => Some(1),
+        "2" => Some(2),
+        _ => None,
+    }
+}
+
 /// Helper function to create a char given an optional frame.
 #[inline(always)]
 fn frame_to_char(frame: &Option<u8>) -> char {
@@ -0,0 +1,252 @@
+/*! Writer for the Picard interval list format.
+
+The interval list format is used by the
+[picard suite tools](https://broadinstitute.github.io/picard/) for denoting genomic regions, for
+example the ribosomal intervals required by `CollectRnaSeqMetrics`. It consists of a SAM-style
+header containing the sequence dictionary, followed by one line per interval with the sequence
+name, start and end coordinates, strand, and interval name.
+
+Coordinates in interval lists are one-based and fully-closed. The writer converts the zero-based,
+half-open coordinates of `gte` accordingly.
+*/
+use std::collections::HashSet;
+use std::error::Error;
+use std::fs;
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::Path;
+use std::str;
+
+use {Gene, Strand, Transcript};
+use utils::open_path;
+
+
+quick_error! {
+    /// Errors that occur when writing interval lists.
+    #[derive(Debug)]
+    pub enum IntervalListError {
+        /// Occurs when a sequence dictionary line can not be parsed.
+        InvalidDictLine(line: String) {
+            description("invalid sequence dictionary line")
+            display(self_) -> ("{}: '{}'", self_.description(), line)
+        }
+        /// Occurs when an interval is written on a sequence missing from the header.
+        MissingSequence(seq_name: String) {
+            description("sequence not found in interval list header")
+            display(self_) -> ("{}: '{}'", self_.description(), seq_name)
+        }
+        /// Occurs when the written feature has no identifier to use as the interval name.
+        MissingName(seq_name: String, start: u64, end: u64) {
+            description("interval has no name")
+            display(self_) -> ("{}: {}:{}-{}", self_.description(), seq_name, start, end)
+        }
+        /// Errors propagated from reading or writing the underlying streams.
+        Io(err: io::Error) {
+            description(err.description())
+            from()
+            cause(err)
+        }
+    }
+}
+
+/// Sequence dictionary used for interval list headers.
+///
+/// Sequences are kept in the order in which they were added.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SeqDict {
+    seqs: Vec<(String, u64)>,
+}
+
+impl SeqDict {
+
+    /// Creates an empty sequence dictionary.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a sequence dictionary from a chrom.sizes file, a FASTA index (`.fai`) file, or a
+    /// Picard sequence dictionary.
+    ///
+    /// Lines starting with `@SQ` are parsed for their `SN` and `LN` tags, while other lines
+    /// starting with `@` are ignored. All other nonempty lines must start with the sequence name
+    /// and its length, separated by a tab, as in chrom.sizes and FASTA index files. Any further
+    /// columns, such as the offsets of FASTA index files, are ignored.
+    pub fn from_reader<R: io::Read>(in_reader: R) -> ::Result<Self> {
+        let mut dict = SeqDict::new();
+        for line in BufReader::new(in_reader).lines() {
+            let line = line.map_err(|e| ::Error::from(IntervalListError::from(e)))?;
+            if line.is_empty() || (line.starts_with('@') && !line.starts_with("@SQ\t")) {
+                continue;
+            }
+            let entry =
+                if line.starts_with("@SQ\t") {
+                    parse_sq_line(&line)
+                } else {
+                    parse_sizes_line(&line)
+                };
+            match entry {
+                Some((name, len)) => dict.push(name, len),
+                None => return Err(::Error::from(IntervalListError::InvalidDictLine(line))),
+            }
+        }
+        Ok(dict)
+    }
+
+    /// Creates a sequence dictionary from a file, which may be gzip-compressed.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> ::Result<Self> {
+        let file = open_path(path).map_err(|e| ::Error::from(IntervalListError::from(e)))?;
+        SeqDict::from_reader(file)
+    }
+
+    /// Adds a sequence to the dictionary.
+    pub fn push<T: Into<String>>(&mut self, seq_name: T, length: u64) {
+        self.seqs.push((seq_name.into(), length))
+    }
+
+    /// Returns the sequence names and lengths of the dictionary.
+    pub fn seqs(&self) -> &[(String, u64)] {
+        self.seqs.as_slice()
+    }
+
+    /// Returns the length of the given sequence, if present.
+    pub fn seq_len(&self, seq_name: &str) -> Option<u64> {
+        self.seqs.iter()
+            .find(|&&(ref name, _)| name == seq_name)
+            .map(|&(_, len)| len)
+    }
+}
+
+/// Helper function for parsing the name and length of an `@SQ` header line.
+fn parse_sq_line(line: &str) -> Option<(String, u64)> {
+    let mut name = None;
+    let mut len = None;
+    for tag in line.split('\t').skip(1) {
+        if tag.starts_with("SN:") {
+            name = Some(tag[3..].to_owned());
+        } else if tag.starts_with("LN:") {
+            len = tag[3..].parse::<u64>().ok();
+        }
+    }
+    match (name, len) {
+        (Some(name), Some(len)) => Some((name, len)),
+        _ => None,
+    }
+}
+
+/// Helper function for parsing the name and length of a chrom.sizes line.
+fn parse_sizes_line(line: &str) -> Option<(String, u64)> {
+    let mut cols = line.split('\t');
+    match (cols.next(), cols.next().and_then(|len| len.trim().parse::<u64>().ok())) {
+        (Some(name), Some(len)) if !name.is_empty() => Some((name.to_owned(), len)),
+        _ => None,
+    }
+}
+
+/// Interval list writer.
+///
+/// The header must be written with `write_header` before any interval is written. Intervals on
+/// sequences absent from the header are rejected.
+pub struct Writer<W: Write> {
+    inner: W,
+    seq_names: HashSet<String>,
+}
+
+impl<W: Write> Writer<W> {
+
+    /// Creates an interval list writer from another writer.
+    pub fn from_writer(in_writer: W) -> Writer<W> {
+        Writer {
+            inner: in_writer,
+            seq_names: HashSet::new(),
+        }
+    }
+
+    /// Writes the header containing the given sequence dictionary.
+    pub fn write_header(&mut self, dict: &SeqDict) -> ::Result<()> {
+        writeln!(self.inner, "@HD\tVN:1.5\tSO:coordinate")
+            .map_err(|e| ::Error::from(IntervalListError::from(e)))?;
+        for &(ref name, len) in dict.seqs() {
+            writeln!(self.inner, "@SQ\tSN:{}\tLN:{}", name, len)
+                .map_err(|e| ::Error::from(IntervalListError::from(e)))?;
+            let _ = self.seq_names.insert(name.clone());
+        }
+        Ok(())
+    }
+
+    /// Writes a single interval, given its zero-based, half-open coordinates.
+    ///
+    /// Intervals on unknown strands are written on the forward strand, since the format only
+    /// allows `+` and `-` as strand values.
+    pub fn write_interval(&mut self, seq_name: &str, start: u64, end: u64, strand: &Strand,
+                          name: &str) -> ::Result<()>
+    {
+        if !self.seq_names.contains(seq_name) {
+            let err = IntervalListError::MissingSequence(seq_name.to_owned());
+            return Err(::Error::from(err));
+        }
+        let strand_char = match strand {
+            &Strand::Reverse => '-',
+            _ => '+',
+        };
+        writeln!(self.inner, "{}\t{}\t{}\t{}\t{}", seq_name, start + 1, end, strand_char, name)
+            .map_err(|e| ::Error::from(IntervalListError::from(e)))
+    }
+
+    /// Writes the interval of the given transcript, named by its identifier.
+    pub fn write_transcript(&mut self, transcript: &Transcript) -> ::Result<()> {
+        let name = transcript.id()
+            .ok_or_else(|| IntervalListError::MissingName(
+                transcript.seq_name().to_owned(), transcript.start(), transcript.end()))?;
+        self.write_interval(transcript.seq_name(), transcript.start(), transcript.end(),
+                            transcript.strand(), name)
+    }
+
+    /// Writes the interval of the given gene, named by its identifier.
+    pub fn write_gene(&mut self, gene: &Gene) -> ::Result<()> {
+        let name = gene.id()
+            .ok_or_else(|| IntervalListError::MissingName(
+                gene.seq_name().to_owned(), gene.start(), gene.end()))?;
+        self.write_interval(gene.seq_name(), gene.start(), gene.end(), gene.strand(), name)
+    }
+
+    /// Writes the intervals of all exons of the given transcript.
+    ///
+    /// Each exon is named by its own identifier, or by its transcript identifier if it has none.
+    pub fn write_exons(&mut self, transcript: &Transcript) -> ::Result<()> {
+        for exon in transcript.exons() {
+            let name = exon.id().or_else(|| transcript.id())
+                .ok_or_else(|| IntervalListError::MissingName(
+                    exon.seq_name().to_owned(), exon.start(), exon.end()))?;
+            self.write_interval(exon.seq_name(), exon.start(), exon.end(), exon.strand(), name)?;
+        }
+        Ok(())
+    }
+
+    /// Flushes the underlying writer.
+    pub fn flush(&mut self) -> ::Result<()> {
+        self.inner.flush().map_err(|e| ::Error::from(IntervalListError::from(e)))
+    }
+}
+
+impl Writer<fs::File> {
+
+    /// Creates an interval list writer that writes to the given path.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let f = fs::File::create(path)?;
+        Ok(Writer::from_writer(f))
+    }
+}
+
+impl Writer<Vec<u8>> {
+
+    /// Creates an interval list writer that writes to an in-memory buffer.
+    ///
+    /// The initial capacity of the buffer is 64 KiB.
+    pub fn from_memory() -> Writer<Vec<u8>> {
+        Writer::from_writer(Vec::with_capacity(1024 * 64))
+    }
+
+    /// Returns the values of the in-memory buffer as a string.
+    pub fn as_string(&self) -> &str {
+        str::from_utf8(&self.inner).expect("valid UTF-8 interval list")
+    }
+}
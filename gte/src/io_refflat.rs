@@ -10,18 +10,34 @@ A minimum specification of the columns can be found on
 use std::cmp::{max, min};
 use std::convert::AsRef;
 use std::error::Error;
-use std::io;
+use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::env;
+use std::hash::{Hash, Hasher};
+use std::io::{self, BufRead, BufReader, BufWriter, SeekFrom, Write};
 use std::num::ParseIntError;
 use std::fs;
-use std::path::Path;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::vec;
 
 use csv;
 use itertools::{GroupBy, Group, Itertools};
-use linked_hash_map::LinkedHashMap;
+use linked_hash_map::{self, LinkedHashMap};
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
 
-use {Coord, Gene, GBuilder, Strand, Transcript, TBuilder, DEF_ID, INIT_COORD};
-use utils::{OptionDeref, update_seq_name};
+use {Coord, DuplicatePolicy, Gene, GBuilder, ParseWarning, Strand, Transcript, TBuilder, DEF_ID,
+     INIT_COORD};
+use extsort::{create_spill_file, SortRecord};
+use model::insert_transcript;
+use progress::{CancelToken, CountingReader, Observer, Tracker};
+use utils::{OptionDeref, open_path_threads, update_seq_name};
+#[cfg(feature = "remote")]
+use utils::open_url;
 
 
 quick_error! {
@@ -50,18 +66,39 @@ quick_error! {
             description("transcript identifier column has no value")
         }
         /// Occurs when any of the exon start or end coordinates is not a valid u64 value.
-        InvalidExonCoord(err: ParseIntError, tid: Option<String>) {
+        InvalidExonCoord(err: ParseIntError, field: &'static str, tid: Option<String>) {
             description(err.description())
-            display(self_) -> ("{}, transcript ID: {}",
-                               self_.description(), tid.as_deref().unwrap_or(DEF_ID))
+            display(self_) -> ("{}, field: {}, transcript ID: {}",
+                               self_.description(), field, tid.as_deref().unwrap_or(DEF_ID))
             cause(err)
         }
+        /// Occurs when a row does not have the expected number of columns.
+        ColumnCount(count: usize) {
+            description("refFlat row does not have 11 columns")
+            display(self_) -> ("{}: {} columns found", self_.description(), count)
+        }
+        /// Occurs when a column value can not be parsed.
+        InvalidColumn(field: &'static str, value: String) {
+            description("invalid refFlat column value")
+            display(self_) -> ("{}, field: {}, value: '{}'", self_.description(), field, value)
+        }
+        /// Occurs when a line of a refFlat index can not be parsed.
+        InvalidIndexLine(line: String) {
+            description("invalid refFlat index line")
+            display(self_) -> ("{}: '{}'", self_.description(), line)
+        }
         /// Errors propagated from the underlying `csv` crate.
         Csv(err: csv::Error) {
             description(err.description())
             from()
             cause(err)
         }
+        /// Errors propagated from reading or seeking the underlying stream directly.
+        Io(err: io::Error) {
+            description(err.description())
+            from()
+            cause(err)
+        }
     }
 }
 
@@ -97,6 +134,7 @@ pub type RefFlatRow = (String, String, String, char, u64, u64, u64, u64, usize,
 ///   row type.
 /// * The number of exon start and end coordinates are guaranteed to be equal in this type.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde_impl", derive(Serialize, Deserialize))]
 pub struct RefFlatRecord {
     gene_id: String,
     transcript_id: String,
@@ -242,9 +280,9 @@ impl RefFlatRecord {
     /// * the number of exon coordinates and the number of exons column value are not equal
     pub fn try_from_row(row: RefFlatRow) -> ::Result<Self> {
 
-        let exon_starts = Self::parse_coords(row.9.as_str(), row.1.as_str())
+        let exon_starts = Self::parse_coords(row.9.as_str(), "exonStarts", row.1.as_str())
             .map_err(::Error::from)?;
-        let exon_ends = Self::parse_coords(row.10.as_str(), row.1.as_str())
+        let exon_ends = Self::parse_coords(row.10.as_str(), "exonEnds", row.1.as_str())
             .map_err(::Error::from)?;
         if exon_starts.len() != row.8 {
             let err = RefFlatError::ExonCountMismatch(Some(row.1.clone()));
@@ -300,14 +338,17 @@ impl RefFlatRecord {
 
     /// Parses the given raw coordinate string into a vector of u64s.
     ///
-    /// The transcript identifier argument is required for when an error type is returned.
+    /// The field name and transcript identifier arguments are required for when an error type
+    /// is returned.
     #[inline]
-    fn parse_coords(raw_coords: &str, tid: &str) -> Result<Vec<u64>, RefFlatError> {
+    fn parse_coords(raw_coords: &str, field: &'static str, tid: &str)
+        -> Result<Vec<u64>, RefFlatError>
+    {
         let rcoords = raw_coords
             .trim_matches(',')
             .split(',')
             .map(|item| u64::from_str(item)
-                 .map_err(|e| RefFlatError::InvalidExonCoord(e, Some(tid.to_owned()))));
+                 .map_err(|e| RefFlatError::InvalidExonCoord(e, field, Some(tid.to_owned()))));
 
         let mut res = vec![];
         for rcoord in rcoords {
@@ -317,23 +358,220 @@ impl RefFlatRecord {
     }
 }
 
+/// Borrowed refFlat record.
+///
+/// This type refers to the columns of a line without copying them, and is created by the
+/// `read_record` method of `Scanner`. It is meant for scanning or filtering files without the
+/// cost of creating owned records or models for every line. Exon coordinates are only parsed
+/// when requested.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RefFlatRecordRef<'a> {
+    gene_id: &'a str,
+    transcript_id: &'a str,
+    seq_name: &'a str,
+    strand: char,
+    transcript_start: u64,
+    transcript_end: u64,
+    coding_start: u64,
+    coding_end: u64,
+    num_exons: usize,
+    exon_starts: &'a str,
+    exon_ends: &'a str,
+}
+
+impl<'a> RefFlatRecordRef<'a> {
+
+    /// Creates a record from a refFlat line, without its line terminator.
+    pub fn from_line(line: &'a str) -> ::Result<Self> {
+        let mut cols = [""; 11];
+        let mut count = 0;
+        for col in line.split('\t') {
+            if count < cols.len() {
+                cols[count] = col;
+            }
+            count += 1;
+        }
+        if count != cols.len() {
+            return Err(::Error::from(RefFlatError::ColumnCount(count)));
+        }
+        let (strand, num_exons) = (cols[3], cols[8]);
+
+        let mut strand_chars = strand.chars();
+        let strand = match (strand_chars.next(), strand_chars.next()) {
+            (Some(chr), None) => chr,
+            _ => return Err(::Error::from(
+                RefFlatError::InvalidColumn("strand", strand.to_owned()))),
+        };
+        let parse_coord = |field: &'static str, value: &str| {
+            u64::from_str(value)
+                .map_err(|_| ::Error::from(RefFlatError::InvalidColumn(field, value.to_owned())))
+        };
+        Ok(RefFlatRecordRef {
+            gene_id: cols[0],
+            transcript_id: cols[1],
+            seq_name: cols[2],
+            strand: strand,
+            transcript_start: parse_coord("txStart", cols[4])?,
+            transcript_end: parse_coord("txEnd", cols[5])?,
+            coding_start: parse_coord("cdsStart", cols[6])?,
+            coding_end: parse_coord("cdsEnd", cols[7])?,
+            num_exons: usize::from_str(num_exons)
+                .map_err(|_| ::Error::from(
+                    RefFlatError::InvalidColumn("exonCount", num_exons.to_owned())))?,
+            exon_starts: cols[9],
+            exon_ends: cols[10],
+        })
+    }
+
+    /// Returns the gene identifier.
+    pub fn gene_id(&self) -> &'a str {
+        self.gene_id
+    }
+
+    /// Returns the transcript identifier.
+    pub fn transcript_id(&self) -> &'a str {
+        self.transcript_id
+    }
+
+    /// Returns the sequence name.
+    pub fn seq_name(&self) -> &'a str {
+        self.seq_name
+    }
+
+    /// Returns the strand character.
+    pub fn strand(&self) -> char {
+        self.strand
+    }
+
+    /// Returns the 0-based transcript start coordinate.
+    pub fn transcript_start(&self) -> u64 {
+        self.transcript_start
+    }
+
+    /// Returns the 0-based, exclusive transcript end coordinate.
+    pub fn transcript_end(&self) -> u64 {
+        self.transcript_end
+    }
+
+    /// Returns the 0-based coding start coordinate.
+    pub fn coding_start(&self) -> u64 {
+        self.coding_start
+    }
+
+    /// Returns the 0-based, exclusive coding end coordinate.
+    pub fn coding_end(&self) -> u64 {
+        self.coding_end
+    }
+
+    /// Returns the number of exons, as in the number of exons column.
+    pub fn num_exons(&self) -> usize {
+        self.num_exons
+    }
+
+    /// Parses and returns the exon coordinates.
+    pub fn exon_coords(&self) -> ::Result<Vec<Coord<u64>>> {
+        let record = self.to_record()?;
+        Ok(record.exon_starts.into_iter().zip(record.exon_ends).collect())
+    }
+
+    /// Creates an owned record with the values of this record.
+    pub fn to_record(&self) -> ::Result<RefFlatRecord> {
+        RefFlatRecord::try_from_row((
+            self.gene_id.to_owned(), self.transcript_id.to_owned(), self.seq_name.to_owned(),
+            self.strand, self.transcript_start, self.transcript_end, self.coding_start,
+            self.coding_end, self.num_exons, self.exon_starts.to_owned(),
+            self.exon_ends.to_owned()))
+    }
+}
+
+/// Scanner over the lines of a refFlat file, yielding borrowed records.
+///
+/// Unlike `Reader`, the scanner reuses one line buffer for all lines, so reading a record does
+/// not allocate.
+pub struct Scanner<R: io::Read> {
+    inner: BufReader<R>,
+    buf: String,
+    num_lines: u64,
+}
+
+impl<R: io::Read> Scanner<R> {
+
+    /// Creates a scanner from another reader.
+    pub fn from_reader(in_reader: R) -> Self {
+        Scanner {
+            inner: BufReader::new(in_reader),
+            buf: String::new(),
+            num_lines: 0,
+        }
+    }
+
+    /// Reads the next non-empty line into the internal buffer and returns its record, or
+    /// `None` if there are no more lines.
+    ///
+    /// The returned record borrows the buffer, so it must be dropped before the next call.
+    pub fn read_record(&mut self) -> Option<::Result<RefFlatRecordRef>> {
+        loop {
+            self.buf.clear();
+            match self.inner.read_line(&mut self.buf) {
+                Ok(0) => return None,
+                Ok(_) => self.num_lines += 1,
+                Err(err) => return Some(Err(::Error::from(RefFlatError::from(err)))),
+            }
+            if !trim_line_end(&self.buf).is_empty() {
+                break;
+            }
+        }
+        let num_lines = self.num_lines;
+        Some(RefFlatRecordRef::from_line(trim_line_end(&self.buf))
+            .map_err(|err| ::Error::at_line(num_lines, err)))
+    }
+}
+
+impl Scanner<fs::File> {
+
+    /// Creates a refFlat scanner that reads from the given path.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        fs::File::open(path).map(Scanner::from_reader)
+    }
+}
+
+/// Helper function for removing the line terminator of the given line.
+fn trim_line_end(line: &str) -> &str {
+    line.trim_right_matches(|c: char| c == '\n' || c == '\r')
+}
+
 /// RefFlat reader.
 pub struct Reader<R: io::Read> {
-    inner: csv::Reader<R>,
+    inner: csv::Reader<CountingReader<R>>,
     seq_name_prefix: Option<String>,
     seq_name_lstrip: Option<String>,
+    lenient: bool,
+    duplicate_policy: DuplicatePolicy,
+    num_lines: u64,
+    warnings: Vec<ParseWarning>,
+    threads: Arc<AtomicUsize>,
+    tracker: Tracker,
 }
 
 impl<R: io::Read> Reader<R> {
 
     /// Creates a refFlat reader from another reader.
     pub fn from_reader(in_reader: R) -> Reader<R> {
+        let bytes = Arc::new(AtomicUsize::new(0));
         Reader {
-            inner: csv::Reader::from_reader(in_reader)
+            inner: csv::Reader::from_reader(CountingReader::new(in_reader, bytes.clone()))
                 .delimiter(b'\t')
-                .has_headers(false),
+                .has_headers(false)
+                // rows with missing columns fail to decode instead of ending the stream
+                .flexible(true),
             seq_name_prefix: None,
             seq_name_lstrip: None,
+            lenient: false,
+            duplicate_policy: DuplicatePolicy::default(),
+            num_lines: 0,
+            warnings: Vec::new(),
+            threads: Arc::new(AtomicUsize::new(1)),
+            tracker: Tracker::new(bytes),
         }
     }
 
@@ -354,12 +592,66 @@ impl<R: io::Read> Reader<R> {
         self
     }
 
+    /// Sets the number of threads used for decompressing bgzip-compressed input.
+    ///
+    /// This only has an effect on readers created with `from_path` from bgzip-compressed files,
+    /// and must be set before any record is read. By default, input is decompressed on the
+    /// calling thread.
+    pub fn threads(&mut self, threads: usize) -> &mut Self {
+        self.threads.store(max(threads, 1), Ordering::SeqCst);
+        self
+    }
+
+    /// Sets the reader to skip malformed records instead of yielding errors.
+    ///
+    /// Each skipped record is stored as a warning, which can be retrieved using the `warnings`
+    /// method. Transcript streams also skip records that can not be turned into transcripts,
+    /// while gene streams only skip records that can not be parsed.
+    pub fn lenient(&mut self, lenient: bool) -> &mut Self {
+        self.lenient = lenient;
+        self
+    }
+
+    /// Sets how gene streams handle transcripts with the same identifier within a gene.
+    ///
+    /// By default, such transcripts result in an error.
+    pub fn duplicate_transcripts(&mut self, policy: DuplicatePolicy) -> &mut Self {
+        self.duplicate_policy = policy;
+        self
+    }
+
+    /// Returns the warnings of the records skipped in lenient mode.
+    pub fn warnings(&self) -> &[ParseWarning] {
+        self.warnings.as_slice()
+    }
+
+    /// Sets the observer notified of the progress of reading records.
+    pub fn observer<O>(&mut self, observer: O) -> &mut Self
+        where O: Observer + Send + 'static
+    {
+        self.tracker.set_observer(Box::new(observer));
+        self
+    }
+
+    /// Sets the token for cancelling reading.
+    ///
+    /// The token is checked before every record, so cancellation also stops transcript and
+    /// gene iterators.
+    pub fn cancel_token(&mut self, token: CancelToken) -> &mut Self {
+        self.tracker.set_cancel_token(token);
+        self
+    }
+
     /// Creates an iterator of refFlat records.
     pub fn records_stream(&mut self) -> RefFlatRecordsStream<R> {
         RefFlatRecordsStream {
             inner: self.inner.decode(),
             seq_name_prefix: self.seq_name_prefix.as_deref(),
             seq_name_lstrip: self.seq_name_lstrip.as_deref(),
+            lenient: self.lenient,
+            num_lines: &mut self.num_lines,
+            warnings: &mut self.warnings,
+            tracker: &mut self.tracker,
         }
     }
 
@@ -372,12 +664,111 @@ impl<R: io::Read> Reader<R> {
 
     /// Creates an iterator of genes.
     ///
-    /// This iterator groups consecutive records based on their gene identifiers into genes.
+    /// This iterator groups consecutive records based on their gene identifiers into genes. Genes
+    /// whose records are not adjacent in the input are yielded multiple times, once for every
+    /// run of records. Use `genes_unsorted` for such inputs.
     pub fn genes_stream(&mut self) -> RefFlatGenesStream<R> {
+        let policy = self.duplicate_policy;
         RefFlatGenesStream {
             inner: self.records_stream()
                 .group_by(RefFlatGenesStream::<R>::group_func),
+            policy: policy,
+        }
+    }
+
+    /// Reads all records and creates an iterator of genes, regardless of the order of the
+    /// records in the input.
+    ///
+    /// Records are grouped by their gene identifiers, sequence names, and strands in memory
+    /// before any gene is created. Genes are yielded in the order of their first records in the
+    /// input.
+    pub fn genes_unsorted(&mut self) -> ::Result<RefFlatUnsortedGenes> {
+        let mut groups = LinkedHashMap::new();
+        for record in self.records_stream() {
+            push_grouped(&mut groups, record?);
         }
+        Ok(RefFlatUnsortedGenes {
+            groups: groups.into_iter(),
+            partitions: Vec::new().into_iter(),
+            paths: Vec::new(),
+            policy: self.duplicate_policy,
+        })
+    }
+
+    /// Reads all records and creates an iterator of genes, regardless of the order of the
+    /// records in the input, while keeping only a part of the records in memory.
+    ///
+    /// Records are first distributed by their gene identifiers over the given number of
+    /// temporary files, which are then grouped into genes one file at a time. Genes are yielded
+    /// in the order of their first records within each file. The temporary files are created in
+    /// the system temporary directory and removed when the iterator is dropped.
+    pub fn genes_unsorted_bounded(&mut self, num_partitions: usize)
+        -> ::Result<RefFlatUnsortedGenes>
+    {
+        let num_partitions = max(num_partitions, 1);
+        let mut genes = RefFlatUnsortedGenes {
+            groups: LinkedHashMap::new().into_iter(),
+            partitions: Vec::new().into_iter(),
+            paths: Vec::with_capacity(num_partitions),
+            policy: self.duplicate_policy,
+        };
+        let mut writers = Vec::with_capacity(num_partitions);
+        let tmp_dir = env::temp_dir();
+        for _ in 0..num_partitions {
+            let (path, file) = create_spill_file(&tmp_dir)
+                .map_err(|e| ::Error::from(RefFlatError::from(e)))?;
+            genes.paths.push(path);
+            writers.push(BufWriter::new(file));
+        }
+        for record in self.records_stream() {
+            let record = record?;
+            let mut hasher = DefaultHasher::new();
+            record.gene_id.hash(&mut hasher);
+            let writer = &mut writers[(hasher.finish() % num_partitions as u64) as usize];
+            writeln!(writer, "{}", record.to_line())
+                .map_err(|e| ::Error::from(RefFlatError::from(e)))?;
+        }
+        for mut writer in writers {
+            writer.flush().map_err(|e| ::Error::from(RefFlatError::from(e)))?;
+        }
+        genes.partitions = genes.paths.clone().into_iter();
+        Ok(genes)
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<R: io::Read> Reader<R> {
+
+    /// Reads all genes, creating their transcripts in parallel.
+    ///
+    /// Records are read and grouped into genes sequentially, as in `genes_stream`, after which
+    /// the transcripts and genes of all groups are created across multiple threads. The genes
+    /// are returned in the same order as they appear in the input.
+    pub fn par_genes(&mut self) -> Vec<::Result<Gene>> {
+        let mut groups: Vec<::Result<((String, String, char), Vec<RefFlatRecord>)>> = Vec::new();
+        for result in self.records_stream() {
+            let record = match result {
+                Ok(rec) => rec,
+                Err(err) => {
+                    groups.push(Err(err));
+                    continue;
+                },
+            };
+            let key = (record.gene_id.clone(), record.seq_name.clone(), record.strand);
+            if let Some(&mut Ok((ref group_key, ref mut records))) = groups.last_mut() {
+                if *group_key == key {
+                    records.push(record);
+                    continue;
+                }
+            }
+            groups.push(Ok((key, vec![record])));
+        }
+        let policy = self.duplicate_policy;
+        groups.into_par_iter()
+            .map(|group| group.and_then(|((gid, seq_name, strand_char), records)| {
+                records_to_gene(gid, seq_name, strand_char, records.into_iter().map(Ok), policy)
+            }))
+            .collect()
     }
 }
 
@@ -389,11 +780,54 @@ impl Reader<fs::File> {
     }
 }
 
+impl Reader<Box<io::Read>> {
+
+    /// Creates a refFlat reader that reads from the given path, which may be gzip or
+    /// bgzip-compressed.
+    ///
+    /// Compression is detected from the contents of the file, regardless of its extension.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let threads = Arc::new(AtomicUsize::new(1));
+        open_path_threads(path, threads.clone()).map(|file| {
+            let mut reader = Reader::from_reader(file);
+            reader.threads = threads;
+            reader
+        })
+    }
+
+    /// Creates a refFlat reader that reads from the given HTTP URL, which may point to a gzip or
+    /// bgzip-compressed file.
+    ///
+    /// The file is streamed as it is read, with compression detected as in `from_path`.
+    #[cfg(feature = "remote")]
+    pub fn from_url(url: &str) -> io::Result<Self> {
+        open_url(url).map(Reader::from_reader)
+    }
+}
+
 /// Iterator over refFlat records.
 pub struct RefFlatRecordsStream<'a, R: 'a> where R: io::Read {
-    inner: csv::DecodedRecords<'a, R, RefFlatRow>,
+    inner: csv::DecodedRecords<'a, CountingReader<R>, RefFlatRow>,
     seq_name_prefix: Option<&'a str>,
     seq_name_lstrip: Option<&'a str>,
+    lenient: bool,
+    num_lines: &'a mut u64,
+    warnings: &'a mut Vec<ParseWarning>,
+    tracker: &'a mut Tracker,
+}
+
+impl<'a, R> RefFlatRecordsStream<'a, R> where R: io::Read {
+
+    /// Stores the given error as a warning of the last read line if the stream is lenient, or
+    /// returns it with the line number otherwise.
+    fn skip_or_yield<T>(&mut self, err: ::Error) -> Option<::Result<T>> {
+        if self.lenient {
+            self.warnings.push(ParseWarning::new(*self.num_lines, err));
+            None
+        } else {
+            Some(Err(::Error::at_line(*self.num_lines, err)))
+        }
+    }
 }
 
 impl<'a, R> Iterator for RefFlatRecordsStream<'a, R> where R: io::Read {
@@ -403,16 +837,34 @@ impl<'a, R> Iterator for RefFlatRecordsStream<'a, R> where R: io::Read {
     fn next(&mut self) -> Option<Self::Item> {
         let lstrip = self.seq_name_lstrip.map(|v| (v, v.len()));
         let prefix = self.seq_name_prefix;
-        self.inner.next()
-            .map(|row| {
-                row
+        loop {
+            match self.tracker.proceed() {
+                Ok(true) => {},
+                Ok(false) => return None,
+                Err(err) => return Some(Err(err)),
+            }
+            let result = match self.inner.next() {
+                Some(row) => row
                     .or_else(|err| Err(::Error::from(RefFlatError::from(err))))
                     .map(|mut row| {
                         update_seq_name(&mut row.2, prefix, lstrip);
                         row
                     })
-                    .and_then(RefFlatRecord::try_from_row)
-            })
+                    .and_then(RefFlatRecord::try_from_row),
+                None => return None,
+            };
+            *self.num_lines += 1;
+            self.tracker.record();
+            match result {
+                Ok(record) => return Some(Ok(record)),
+                Err(err) => {
+                    let yielded = self.skip_or_yield(err);
+                    if yielded.is_some() {
+                        return yielded;
+                    }
+                },
+            }
+        }
     }
 }
 
@@ -426,8 +878,21 @@ impl<'a, R> Iterator for RefFlatTranscriptsStream<'a, R> where R: io::Read {
     type Item = ::Result<Transcript>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.inner.next()
-            .map(|record| record.and_then(|rec| rec.into_transcript()))
+        loop {
+            let result = match self.inner.next() {
+                Some(record) => record.and_then(|rec| rec.into_transcript()),
+                None => return None,
+            };
+            match result {
+                Ok(transcript) => return Some(Ok(transcript)),
+                Err(err) => {
+                    let yielded = self.inner.skip_or_yield(err);
+                    if yielded.is_some() {
+                        return yielded;
+                    }
+                },
+            }
+        }
     }
 }
 
@@ -445,6 +910,7 @@ type GroupedRecords<'a, 'b, R> = Group<'b, GroupKey, RefFlatRecordsStream<'a, R>
 /// Iterator over genes created from refFlat records.
 pub struct RefFlatGenesStream<'a, R: 'a> where R: io::Read, {
     inner: GroupBy<GroupKey, RefFlatRecordsStream<'a, R>, GroupFunc>,
+    policy: DuplicatePolicy,
 }
 
 impl<'a, R> RefFlatGenesStream<'a, R> where R: io::Read {
@@ -456,34 +922,16 @@ impl<'a, R> RefFlatGenesStream<'a, R> where R: io::Read {
     }
 
     /// Creates genes from the given grouped records.
-    fn group_to_gene<'b>(group: (GroupKey, GroupedRecords<'a, 'b, R>)) -> ::Result<Gene> {
+    fn group_to_gene<'b>(group: (GroupKey, GroupedRecords<'a, 'b, R>), policy: DuplicatePolicy)
+        -> ::Result<Gene>
+    {
         let (group_key, records) = group;
         match group_key {
 
             None => Err(records.filter_map(|x| x.err()).next().unwrap()),
 
-            Some((gid, seq_name, strand_char)) => {
-                let mut transcripts = LinkedHashMap::new();
-                let (mut gene_start, mut gene_end) = INIT_COORD;
-                for record in records {
-                    let transcript = record.and_then(|rec| rec.into_transcript())?;
-                    gene_start = min(gene_start, transcript.start());
-                    gene_end = max(gene_end, transcript.end());
-                    let tid = transcript.id().map(|id| id.to_owned())
-                        .ok_or(::Error::from(RefFlatError::MissingTranscriptId))?;
-                    let existing_trx = transcripts.insert(tid, transcript);
-                    if existing_trx.is_some() {
-                        let err = RefFlatError::DuplicateTranscriptId(Some(gid));
-                        return Err(::Error::from(err));
-                    }
-                }
-                GBuilder::new(seq_name, gene_start, gene_end)
-                    .id(gid)
-                    .strand_char(strand_char)
-                    .transcripts(transcripts)
-                    .transcript_coding_incl_stop(true)
-                    .build()
-            },
+            Some((gid, seq_name, strand_char)) =>
+                records_to_gene(gid, seq_name, strand_char, records, policy),
         }
     }
 }
@@ -493,26 +941,374 @@ impl<'a, R> Iterator for RefFlatGenesStream<'a, R> where R: io::Read {
     type Item = ::Result<Gene>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.inner.into_iter().map(Self::group_to_gene).next()
+        let policy = self.policy;
+        self.inner.into_iter().next().map(|group| Self::group_to_gene(group, policy))
+    }
+}
+
+/// Iterator over genes created from refFlat records in any order.
+///
+/// Any temporary files used for grouping records are removed when the iterator is dropped.
+pub struct RefFlatUnsortedGenes {
+    groups: linked_hash_map::IntoIter<(String, String, char), Vec<RefFlatRecord>>,
+    partitions: vec::IntoIter<PathBuf>,
+    paths: Vec<PathBuf>,
+    policy: DuplicatePolicy,
+}
+
+impl RefFlatUnsortedGenes {
+
+    /// Reads and groups the records of the given temporary file.
+    fn load_partition(&mut self, path: &Path) -> ::Result<()> {
+        let file = fs::File::open(path).map_err(|e| ::Error::from(RefFlatError::from(e)))?;
+        let mut groups = LinkedHashMap::new();
+        for line in BufReader::new(file).lines() {
+            let line = line.map_err(|e| ::Error::from(RefFlatError::from(e)))?;
+            push_grouped(&mut groups, RefFlatRecord::from_line(&line)?);
+        }
+        self.groups = groups.into_iter();
+        Ok(())
+    }
+}
+
+impl Iterator for RefFlatUnsortedGenes {
+
+    type Item = ::Result<Gene>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(((gid, seq_name, strand_char), records)) = self.groups.next() {
+                return Some(records_to_gene(gid, seq_name, strand_char,
+                                            records.into_iter().map(Ok), self.policy));
+            }
+            match self.partitions.next() {
+                Some(path) => {
+                    if let Err(err) = self.load_partition(&path) {
+                        return Some(Err(err));
+                    }
+                },
+                None => return None,
+            }
+        }
+    }
+}
+
+impl Drop for RefFlatUnsortedGenes {
+    fn drop(&mut self) {
+        for path in self.paths.iter() {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+/// Adds the given record to the records of its gene.
+fn push_grouped(
+    groups: &mut LinkedHashMap<(String, String, char), Vec<RefFlatRecord>>,
+    record: RefFlatRecord,
+) {
+    let key = (record.gene_id.clone(), record.seq_name.clone(), record.strand);
+    if let Some(records) = groups.get_mut(&key) {
+        records.push(record);
+        return;
+    }
+    let _ = groups.insert(key, vec![record]);
+}
+
+/// Creates a gene from the given records, which must all have the same gene identifier,
+/// sequence name, and strand.
+pub(crate) fn records_to_gene<I>(
+    gid: String,
+    seq_name: String,
+    strand_char: char,
+    records: I,
+    policy: DuplicatePolicy,
+) -> ::Result<Gene>
+    where I: IntoIterator<Item=::Result<RefFlatRecord>>
+{
+    let mut transcripts = LinkedHashMap::new();
+    for record in records {
+        let transcript = record.and_then(|rec| rec.into_transcript())?;
+        let tid = transcript.id().map(|id| id.to_owned())
+            .ok_or(::Error::from(RefFlatError::MissingTranscriptId))?;
+        if !insert_transcript(&mut transcripts, tid, transcript, policy) {
+            let err = RefFlatError::DuplicateTranscriptId(Some(gid));
+            return Err(::Error::from(err));
+        }
+    }
+    let (gene_start, gene_end) = transcripts.values()
+        .fold(INIT_COORD, |(start, end), trx| (min(start, trx.start()), max(end, trx.end())));
+    GBuilder::new(seq_name, gene_start, gene_end)
+        .id(gid)
+        .strand_char(strand_char)
+        .transcripts(transcripts)
+        .transcript_coding_incl_stop(true)
+        .build()
+}
+
+/// Location of a single refFlat row, along with the values used for finding it.
+#[derive(Debug, Clone, PartialEq)]
+struct IndexEntry {
+    gene_id: String,
+    seq_name: String,
+    start: u64,
+    end: u64,
+    offset: u64,
+    length: u64,
+}
+
+/// Byte-offset index of the rows of a refFlat file.
+///
+/// The index stores the gene identifier, sequence name, transcript coordinates, and byte
+/// location of each row, which allows `IndexedReader` to read genes without reading the entire
+/// file. Indices can be saved and loaded as tab-separated text, so that they only need to be
+/// built once.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Index {
+    entries: Vec<IndexEntry>,
+    genes: HashMap<String, Vec<usize>>,
+}
+
+impl Index {
+
+    /// Builds an index by scanning all rows of the given refFlat stream.
+    pub fn build<R: io::Read>(in_reader: R) -> ::Result<Self> {
+        let mut reader = BufReader::new(in_reader);
+        let mut index = Index::default();
+        let mut offset = 0;
+        let mut buf = Vec::new();
+        loop {
+            buf.clear();
+            let length = reader.read_until(b'\n', &mut buf)
+                .map_err(|e| ::Error::from(RefFlatError::from(e)))? as u64;
+            if length == 0 {
+                break;
+            }
+            let line = String::from_utf8_lossy(&buf);
+            let cols = line.trim_right_matches(|c: char| c == '\n' || c == '\r')
+                .split('\t')
+                .collect::<Vec<&str>>();
+            if cols.len() >= 6 {
+                let coord = (cols[4].parse::<u64>(), cols[5].parse::<u64>());
+                if let (Ok(start), Ok(end)) = coord {
+                    index.push(IndexEntry {
+                        gene_id: cols[0].to_owned(),
+                        seq_name: cols[2].to_owned(),
+                        start: start,
+                        end: end,
+                        offset: offset,
+                        length: length,
+                    });
+                }
+            }
+            offset += length;
+        }
+        Ok(index)
+    }
+
+    /// Loads an index saved with `write`.
+    pub fn from_reader<R: io::Read>(in_reader: R) -> ::Result<Self> {
+        let mut index = Index::default();
+        for line in BufReader::new(in_reader).lines() {
+            let line = line.map_err(|e| ::Error::from(RefFlatError::from(e)))?;
+            if line.is_empty() {
+                continue;
+            }
+            let entry = {
+                let cols = line.split('\t').collect::<Vec<&str>>();
+                if cols.len() != 6 {
+                    None
+                } else {
+                    match (cols[2].parse(), cols[3].parse(), cols[4].parse(), cols[5].parse()) {
+                        (Ok(start), Ok(end), Ok(offset), Ok(length)) => Some(IndexEntry {
+                            gene_id: cols[0].to_owned(),
+                            seq_name: cols[1].to_owned(),
+                            start: start,
+                            end: end,
+                            offset: offset,
+                            length: length,
+                        }),
+                        _ => None,
+                    }
+                }
+            };
+            match entry {
+                Some(entry) => index.push(entry),
+                None => return Err(::Error::from(RefFlatError::InvalidIndexLine(line))),
+            }
+        }
+        Ok(index)
+    }
+
+    /// Saves the index as tab-separated text.
+    ///
+    /// Each line contains the gene identifier, sequence name, transcript start and end
+    /// coordinates, and the byte offset and length of a single row.
+    pub fn write<W: Write>(&self, out: &mut W) -> ::Result<()> {
+        for entry in self.entries.iter() {
+            writeln!(out, "{}\t{}\t{}\t{}\t{}\t{}", entry.gene_id, entry.seq_name,
+                     entry.start, entry.end, entry.offset, entry.length)
+                .map_err(|e| ::Error::from(RefFlatError::from(e)))?;
+        }
+        Ok(())
+    }
+
+    /// Returns the number of indexed rows.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns whether the index has no rows.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns whether the given gene identifier is present in the index.
+    pub fn contains_gene(&self, gene_id: &str) -> bool {
+        self.genes.contains_key(gene_id)
+    }
+
+    /// Adds the given entry.
+    fn push(&mut self, entry: IndexEntry) {
+        self.genes.entry(entry.gene_id.clone()).or_insert_with(Vec::new).push(self.entries.len());
+        self.entries.push(entry);
+    }
+}
+
+/// RefFlat reader for random access through a byte-offset index.
+///
+/// Only the rows of the requested genes are read from the underlying stream.
+pub struct IndexedReader<R: io::Read + io::Seek> {
+    inner: R,
+    index: Index,
+}
+
+impl<R: io::Read + io::Seek> IndexedReader<R> {
+
+    /// Creates an indexed reader from another reader and its index.
+    pub fn new(in_reader: R, index: Index) -> Self {
+        IndexedReader {
+            inner: in_reader,
+            index: index,
+        }
+    }
+
+    /// Creates an indexed reader from another reader, building its index first.
+    pub fn from_reader(mut in_reader: R) -> ::Result<Self> {
+        let index = {
+            let _ = in_reader.seek(SeekFrom::Start(0))
+                .map_err(|e| ::Error::from(RefFlatError::from(e)))?;
+            Index::build(&mut in_reader)?
+        };
+        Ok(IndexedReader::new(in_reader, index))
+    }
+
+    /// Returns the index of the reader.
+    pub fn index(&self) -> &Index {
+        &self.index
+    }
+
+    /// Reads the gene with the given identifier, if present.
+    ///
+    /// If the identifier is used by rows on multiple sequences, only the rows on the sequence of
+    /// the first row are used.
+    pub fn seek_gene(&mut self, gene_id: &str) -> ::Result<Option<Gene>> {
+        let seq_name = match self.index.genes.get(gene_id) {
+            Some(idxs) => self.index.entries[idxs[0]].seq_name.clone(),
+            None => return Ok(None),
+        };
+        self.read_gene(gene_id, &seq_name).map(Some)
+    }
+
+    /// Reads all genes with at least one transcript overlapping the given zero-based,
+    /// half-open region.
+    ///
+    /// Genes are returned in the order of their first overlapping rows in the file.
+    pub fn query(&mut self, seq_name: &str, region: Range<u64>) -> ::Result<Vec<Gene>> {
+        let mut gene_ids: Vec<String> = Vec::new();
+        for entry in self.index.entries.iter() {
+            if entry.seq_name == seq_name && entry.start < region.end && entry.end > region.start
+                && !gene_ids.contains(&entry.gene_id)
+            {
+                gene_ids.push(entry.gene_id.clone());
+            }
+        }
+        let mut genes = Vec::with_capacity(gene_ids.len());
+        for gene_id in gene_ids {
+            genes.push(self.read_gene(&gene_id, seq_name)?);
+        }
+        Ok(genes)
+    }
+
+    /// Reads the rows of the given gene on the given sequence and creates the gene.
+    fn read_gene(&mut self, gene_id: &str, seq_name: &str) -> ::Result<Gene> {
+        let mut buf = Vec::new();
+        let inner = &mut self.inner;
+        if let Some(idxs) = self.index.genes.get(gene_id) {
+            for &idx in idxs.iter() {
+                let entry = &self.index.entries[idx];
+                if entry.seq_name != seq_name {
+                    continue;
+                }
+                let mut chunk = vec![0u8; entry.length as usize];
+                inner.seek(SeekFrom::Start(entry.offset))
+                    .and_then(|_| inner.read_exact(&mut chunk))
+                    .map_err(|e| ::Error::from(RefFlatError::from(e)))?;
+                buf.extend_from_slice(&chunk);
+                if !buf.ends_with(b"\n") {
+                    buf.push(b'\n');
+                }
+            }
+        }
+        let mut reader = Reader::from_reader(buf.as_slice());
+        let mut records = reader.records_stream();
+        let first = match records.next() {
+            Some(result) => result?,
+            None => return Err(::Error::from(RefFlatError::MissingGeneId)),
+        };
+        let (gid, seq, strand_char) =
+            (first.gene_id.clone(), first.seq_name.clone(), first.strand);
+        records_to_gene(gid, seq, strand_char, Some(Ok(first)).into_iter().chain(records),
+                        DuplicatePolicy::default())
+    }
+}
+
+impl IndexedReader<fs::File> {
+
+    /// Creates an indexed reader that reads from the given path, building its index first.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> ::Result<Self> {
+        let file = fs::File::open(path).map_err(|e| ::Error::from(RefFlatError::from(e)))?;
+        IndexedReader::from_reader(file)
     }
 }
 
 /// RefFlat writer.
-pub struct Writer<W: io::Write> {
+pub struct Writer<W: Write> {
     inner: csv::Writer<W>,
+    coding_incl_stop: bool,
 }
 
-impl<W: io::Write> Writer<W> {
+impl<W: Write> Writer<W> {
 
     /// Creates a refFlat writer from another writer.
     pub fn from_writer(in_writer: W) -> Writer<W> {
         Writer {
             inner: csv::Writer::from_writer(in_writer)
                 .delimiter(b'\t')
-                .quote_style(csv::QuoteStyle::Never)
+                .quote_style(csv::QuoteStyle::Never),
+            coding_incl_stop: true,
         }
     }
 
+    /// Sets whether the coding region of written transcripts includes the stop codon.
+    ///
+    /// The default is `true`, following the UCSC convention. Transcripts with unknown strands
+    /// are always written with their stop codons, since the end of their coding regions can
+    /// not be determined.
+    pub fn coding_incl_stop(&mut self, coding_incl_stop: bool) -> &mut Self {
+        self.coding_incl_stop = coding_incl_stop;
+        self
+    }
+
     /// Writes the given row.
     pub fn write(&mut self, row: &RefFlatRow) -> ::Result<()> {
         self.inner
@@ -545,7 +1341,8 @@ impl<W: io::Write> Writer<W> {
             &Strand::Unknown => '.',
         };
 
-        let (coding_start, coding_end) = transcript.coding_coord(true)
+        let (coding_start, coding_end) = transcript.coding_coord(self.coding_incl_stop)
+            .or_else(|| transcript.coding_coord(true))
             .unwrap_or((transcript.end(), transcript.end()));
         let (exon_starts, exon_ends) = transcript.coords_field();
 
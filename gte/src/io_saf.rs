@@ -0,0 +1,131 @@
+/*! Writer for the SAF format.
+
+The simplified annotation format (SAF) is the feature annotation format accepted by
+[featureCounts](http://subread.sourceforge.net/) in addition to GTF. It consists of a header line
+followed by one line per feature with the meta-feature identifier, sequence name, start and end
+coordinates, and strand. Features sharing the same meta-feature identifier, usually the exons of
+a gene, are counted together.
+
+Coordinates in SAF files are one-based and fully-closed. The writer converts the zero-based,
+half-open coordinates of `gte` accordingly.
+*/
+use std::error::Error;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use std::str;
+
+use {Gene, Strand};
+
+
+quick_error! {
+    /// Errors that occur when writing SAF files.
+    #[derive(Debug)]
+    pub enum SafError {
+        /// Occurs when the written gene has no identifier to use as the meta-feature identifier.
+        MissingGeneId(seq_name: String, start: u64, end: u64) {
+            description("gene has no identifier")
+            display(self_) -> ("{}: {}:{}-{}", self_.description(), seq_name, start, end)
+        }
+        /// Errors propagated from writing the underlying stream.
+        Io(err: io::Error) {
+            description(err.description())
+            from()
+            cause(err)
+        }
+    }
+}
+
+/// SAF writer.
+///
+/// By default, the exons of each gene are written as they are, so that exons shared by multiple
+/// transcripts are written more than once. featureCounts counts such duplicates only once per
+/// gene, but the exons can also be merged into their union before writing using `collapse`.
+pub struct Writer<W: Write> {
+    inner: W,
+    collapse: bool,
+}
+
+impl<W: Write> Writer<W> {
+
+    /// Creates a SAF writer from another writer.
+    pub fn from_writer(in_writer: W) -> Writer<W> {
+        Writer {
+            inner: in_writer,
+            collapse: false,
+        }
+    }
+
+    /// Sets whether the exons of each gene are merged into their union before writing.
+    pub fn collapse(&mut self, collapse: bool) -> &mut Self {
+        self.collapse = collapse;
+        self
+    }
+
+    /// Writes the header line.
+    pub fn write_header(&mut self) -> ::Result<()> {
+        writeln!(self.inner, "GeneID\tChr\tStart\tEnd\tStrand")
+            .map_err(|e| ::Error::from(SafError::from(e)))
+    }
+
+    /// Writes a single feature, given its zero-based, half-open coordinates.
+    pub fn write_feature(&mut self, gene_id: &str, seq_name: &str, start: u64, end: u64,
+                         strand: &Strand) -> ::Result<()>
+    {
+        let strand_char = match strand {
+            &Strand::Forward => '+',
+            &Strand::Reverse => '-',
+            &Strand::Unknown => '.',
+        };
+        writeln!(self.inner, "{}\t{}\t{}\t{}\t{}", gene_id, seq_name, start + 1, end, strand_char)
+            .map_err(|e| ::Error::from(SafError::from(e)))
+    }
+
+    /// Writes the exons of the given gene, with the gene identifier as their meta-feature
+    /// identifier.
+    pub fn write_gene(&mut self, gene: &Gene) -> ::Result<()> {
+        let gene_id = gene.id()
+            .ok_or_else(|| SafError::MissingGeneId(
+                gene.seq_name().to_owned(), gene.start(), gene.end()))?;
+        if self.collapse {
+            for iv in gene.exonic_intervals() {
+                self.write_feature(gene_id, gene.seq_name(), iv.start, iv.end, gene.strand())?;
+            }
+        } else {
+            for exon in gene.transcripts().values().flat_map(|trx| trx.exons().iter()) {
+                self.write_feature(gene_id, exon.seq_name(), exon.start(), exon.end(),
+                                   exon.strand())?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Flushes the underlying writer.
+    pub fn flush(&mut self) -> ::Result<()> {
+        self.inner.flush().map_err(|e| ::Error::from(SafError::from(e)))
+    }
+}
+
+impl Writer<fs::File> {
+
+    /// Creates a SAF writer that writes to the given path.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let f = fs::File::create(path)?;
+        Ok(Writer::from_writer(f))
+    }
+}
+
+impl Writer<Vec<u8>> {
+
+    /// Creates a SAF writer that writes to an in-memory buffer.
+    ///
+    /// The initial capacity of the buffer is 64 KiB.
+    pub fn from_memory() -> Writer<Vec<u8>> {
+        Writer::from_writer(Vec::with_capacity(1024 * 64))
+    }
+
+    /// Returns the values of the in-memory buffer as a string.
+    pub fn as_string(&self) -> &str {
+        str::from_utf8(&self.inner).expect("valid UTF-8 SAF")
+    }
+}
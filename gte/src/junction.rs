@@ -0,0 +1,295 @@
+/*! Extraction and writing of splice junctions.
+
+Splice junctions are the introns of transcripts, identified by their sequence names, coordinates,
+and strands. Junctions shared by multiple transcripts are collected once, along with the
+identifiers of all their supporting transcripts.
+
+The collected junctions can be written in the BED format, or in the `SJ.out.tab` format of the
+[STAR](https://github.com/alexdobin/STAR) aligner. Since these are written from annotations and
+not from alignments, the STAR intron motif column is always `0` (non-canonical or unknown) and the
+read count and overhang columns are always `0`.
+*/
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use std::str;
+
+use {Gene, SeqOrder, Strand, Transcript};
+
+
+quick_error! {
+    /// Errors that occur when writing splice junctions.
+    #[derive(Debug)]
+    pub enum JunctionError {
+        /// Errors propagated from writing the underlying stream.
+        Io(err: io::Error) {
+            description(err.description())
+            from()
+            cause(err)
+        }
+    }
+}
+
+/// Splice junction of one or more transcripts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Junction {
+    seq_name: String,
+    start: u64,
+    end: u64,
+    strand: Strand,
+    transcript_ids: Vec<String>,
+}
+
+impl Junction {
+
+    /// Returns the sequence name.
+    pub fn seq_name(&self) -> &str {
+        self.seq_name.as_str()
+    }
+
+    /// Returns the genome-wise 5'-most coordinate of the intron.
+    pub fn start(&self) -> u64 {
+        self.start
+    }
+
+    /// Returns the genome-wise 3'-most coordinate of the intron.
+    pub fn end(&self) -> u64 {
+        self.end
+    }
+
+    /// Returns a reference of the strand.
+    pub fn strand(&self) -> &Strand {
+        &self.strand
+    }
+
+    /// Returns the identifiers of the transcripts containing the junction.
+    pub fn transcript_ids(&self) -> &[String] {
+        self.transcript_ids.as_slice()
+    }
+}
+
+impl Transcript {
+
+    /// Returns the splice junctions of the transcript.
+    ///
+    /// Junctions are created from the introns of the transcript, in the same transcript-wise
+    /// order, and are supported only by the transcript itself.
+    pub fn junctions(&self) -> Vec<Junction> {
+        self.introns()
+            .map(|intron| Junction {
+                seq_name: self.seq_name().to_owned(),
+                start: intron.start(),
+                end: intron.end(),
+                strand: *self.strand(),
+                transcript_ids: self.id().map(|id| vec![id.to_owned()]).unwrap_or_default(),
+            })
+            .collect()
+    }
+}
+
+/// The type used for identifying unique junctions.
+///
+/// The tuple elements represent sequence name, start coordinate, end coordinate, and strand
+/// character.
+type JunctionKey = (String, u64, u64, char);
+
+/// Collection of unique splice junctions.
+#[derive(Debug, Clone, Default)]
+pub struct JunctionSet {
+    junctions: HashMap<JunctionKey, Junction>,
+}
+
+impl JunctionSet {
+
+    /// Creates an empty junction set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a junction set from the transcripts of the given genes.
+    pub fn from_genes<T>(genes: T) -> ::Result<Self>
+        where T: IntoIterator<Item=::Result<Gene>>
+    {
+        let mut set = Self::new();
+        for gene in genes {
+            set.add_gene(&gene?);
+        }
+        Ok(set)
+    }
+
+    /// Creates a junction set from the given transcripts.
+    pub fn from_transcripts<T>(transcripts: T) -> ::Result<Self>
+        where T: IntoIterator<Item=::Result<Transcript>>
+    {
+        let mut set = Self::new();
+        for transcript in transcripts {
+            set.add_transcript(&transcript?);
+        }
+        Ok(set)
+    }
+
+    /// Adds the junctions of the given transcript.
+    ///
+    /// The transcript identifier is added to the supporting transcripts of junctions that are
+    /// already present.
+    pub fn add_transcript(&mut self, transcript: &Transcript) {
+        for junction in transcript.junctions() {
+            let key = (junction.seq_name.clone(), junction.start, junction.end,
+                       strand_char(&junction.strand));
+            let entry = self.junctions.entry(key).or_insert_with(|| Junction {
+                transcript_ids: Vec::new(),
+                ..junction.clone()
+            });
+            for tid in junction.transcript_ids {
+                if !entry.transcript_ids.contains(&tid) {
+                    entry.transcript_ids.push(tid);
+                }
+            }
+        }
+    }
+
+    /// Adds the junctions of all transcripts of the given gene.
+    pub fn add_gene(&mut self, gene: &Gene) {
+        for transcript in gene.transcripts().values() {
+            self.add_transcript(transcript);
+        }
+    }
+
+    /// Returns the number of unique junctions.
+    pub fn len(&self) -> usize {
+        self.junctions.len()
+    }
+
+    /// Returns whether the set contains no junctions.
+    pub fn is_empty(&self) -> bool {
+        self.junctions.is_empty()
+    }
+
+    /// Consumes the set and returns its junctions, sorted by their sequence names using the
+    /// given ordering and then by their coordinates and strands.
+    pub fn into_sorted(self, order: &SeqOrder) -> Vec<Junction> {
+        let mut junctions = self.junctions.into_iter()
+            .map(|(_, junction)| junction)
+            .collect::<Vec<Junction>>();
+        junctions.sort_by(|a, b| {
+            order.compare(&a.seq_name, &b.seq_name)
+                .then_with(|| (a.start, a.end).cmp(&(b.start, b.end)))
+                .then_with(|| strand_char(&a.strand).cmp(&strand_char(&b.strand)))
+        });
+        junctions
+    }
+}
+
+/// Output formats of junction writers.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum JunctionFormat {
+    /// BED6, with the supporting transcript identifiers as the name and their number as the
+    /// score.
+    Bed,
+    /// The `SJ.out.tab` format of the STAR aligner.
+    StarSj,
+}
+
+impl Default for JunctionFormat {
+    fn default() -> Self {
+        JunctionFormat::Bed
+    }
+}
+
+/// Splice junction writer.
+pub struct Writer<W: Write> {
+    inner: W,
+    format: JunctionFormat,
+}
+
+impl<W: Write> Writer<W> {
+
+    /// Creates a junction writer from another writer, writing BED rows by default.
+    pub fn from_writer(in_writer: W) -> Writer<W> {
+        Writer {
+            inner: in_writer,
+            format: JunctionFormat::default(),
+        }
+    }
+
+    /// Sets the output format of the writer.
+    pub fn format(&mut self, format: JunctionFormat) -> &mut Self {
+        self.format = format;
+        self
+    }
+
+    /// Writes a single junction.
+    pub fn write_junction(&mut self, junction: &Junction) -> ::Result<()> {
+        let result = match self.format {
+            JunctionFormat::Bed => {
+                let name =
+                    if junction.transcript_ids.is_empty() {
+                        ".".to_owned()
+                    } else {
+                        junction.transcript_ids.join(",")
+                    };
+                writeln!(self.inner, "{}\t{}\t{}\t{}\t{}\t{}",
+                         junction.seq_name, junction.start, junction.end, name,
+                         junction.transcript_ids.len(), strand_char(&junction.strand))
+            },
+            JunctionFormat::StarSj => {
+                let strand_code = match junction.strand {
+                    Strand::Forward => 1,
+                    Strand::Reverse => 2,
+                    Strand::Unknown => 0,
+                };
+                writeln!(self.inner, "{}\t{}\t{}\t{}\t0\t1\t0\t0\t0",
+                         junction.seq_name, junction.start + 1, junction.end, strand_code)
+            },
+        };
+        result.map_err(|e| ::Error::from(JunctionError::from(e)))
+    }
+
+    /// Writes all junctions of the given set, sorted using the given sequence ordering.
+    pub fn write_set(&mut self, set: JunctionSet, order: &SeqOrder) -> ::Result<()> {
+        for junction in set.into_sorted(order) {
+            self.write_junction(&junction)?;
+        }
+        Ok(())
+    }
+
+    /// Flushes the underlying writer.
+    pub fn flush(&mut self) -> ::Result<()> {
+        self.inner.flush().map_err(|e| ::Error::from(JunctionError::from(e)))
+    }
+}
+
+impl Writer<fs::File> {
+
+    /// Creates a junction writer that writes to the given path.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let f = fs::File::create(path)?;
+        Ok(Writer::from_writer(f))
+    }
+}
+
+impl Writer<Vec<u8>> {
+
+    /// Creates a junction writer that writes to an in-memory buffer.
+    ///
+    /// The initial capacity of the buffer is 64 KiB.
+    pub fn from_memory() -> Writer<Vec<u8>> {
+        Writer::from_writer(Vec::with_capacity(1024 * 64))
+    }
+
+    /// Returns the values of the in-memory buffer as a string.
+    pub fn as_string(&self) -> &str {
+        str::from_utf8(&self.inner).expect("valid UTF-8 junctions")
+    }
+}
+
+/// Helper function to create a char given a strand reference.
+fn strand_char(strand: &Strand) -> char {
+    match *strand {
+        Strand::Forward => '+',
+        Strand::Reverse => '-',
+        Strand::Unknown => '.',
+    }
+}
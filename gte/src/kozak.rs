@@ -0,0 +1,122 @@
+/*! Kozak sequence context of start codons.
+
+The strength of a start codon is commonly assessed by how well its surrounding sequence
+matches the Kozak consensus `gccRccAUGG`, most importantly the purine at position -3 and the
+guanine at position +4, relative to the `A` of the start codon at position +1.
+
+*/
+use {SequenceSource, Strand, Transcript};
+
+
+/// Kozak consensus of the bases upstream of the start codon, from position -6 to -1.
+const KOZAK_UPSTREAM: &'static [u8; 6] = b"GCCRCC";
+
+/// Number of bases upstream of the start codon in a Kozak context.
+const UPSTREAM_LEN: u64 = 6;
+
+/// Number of bases in a Kozak context, from position -6 to +4.
+const CONTEXT_LEN: u64 = 10;
+
+/// Strength of a start codon context, based on the two most important Kozak positions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum KozakStrength {
+    /// Both a purine at position -3 and a guanine at position +4.
+    Strong,
+    /// Either a purine at position -3 or a guanine at position +4, but not both.
+    Adequate,
+    /// Neither a purine at position -3 nor a guanine at position +4.
+    Weak,
+}
+
+/// Sequence context around a start codon, spanning positions -6 to +4.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KozakContext {
+    seq: Vec<u8>,
+}
+
+impl KozakContext {
+
+    /// Creates a context from the given spliced transcript sequence and the zero-based position
+    /// of the first base of a start codon in it.
+    ///
+    /// Positions outside of the sequence are filled with `N`.
+    pub fn from_spliced(spliced: &[u8], start_pos: u64) -> KozakContext {
+        let seq = (0..CONTEXT_LEN)
+            .map(|idx| {
+                if start_pos + idx < UPSTREAM_LEN {
+                    return b'N';
+                }
+                spliced.get((start_pos + idx - UPSTREAM_LEN) as usize)
+                    .map(|&base| uppercase(base))
+                    .unwrap_or(b'N')
+            })
+            .collect();
+        KozakContext { seq: seq }
+    }
+
+    /// Returns the bases of the context, from position -6 to +4.
+    pub fn seq(&self) -> &[u8] {
+        self.seq.as_slice()
+    }
+
+    /// Returns the strength of the context.
+    pub fn strength(&self) -> KozakStrength {
+        let purine_m3 = self.seq[3] == b'A' || self.seq[3] == b'G';
+        let guanine_p4 = self.seq[9] == b'G';
+        match (purine_m3, guanine_p4) {
+            (true, true) => KozakStrength::Strong,
+            (false, false) => KozakStrength::Weak,
+            _ => KozakStrength::Adequate,
+        }
+    }
+
+    /// Returns the number of positions matching the Kozak consensus, from zero to seven.
+    ///
+    /// The matched positions are -6 to -1 and +4, while the start codon itself is not counted.
+    pub fn score(&self) -> u8 {
+        let upstream = self.seq[..6].iter().zip(KOZAK_UPSTREAM.iter())
+            .filter(|&(&base, &consensus)| match consensus {
+                b'R' => base == b'A' || base == b'G',
+                _ => base == consensus,
+            })
+            .count() as u8;
+        upstream + if self.seq[9] == b'G' { 1 } else { 0 }
+    }
+}
+
+impl Transcript {
+
+    /// Returns the Kozak context of the annotated start codon.
+    ///
+    /// The value is `None` if the transcript has no coding region, or if its coding region
+    /// does not start with a complete codon.
+    pub fn kozak_context<S: SequenceSource>(&self, source: &mut S)
+        -> ::Result<Option<KozakContext>>
+    {
+        let first = match (self.coding_coord(true), *self.strand()) {
+            (Some(_), _) if self.coding_phase() != 0 => return Ok(None),
+            (Some((_, end)), Strand::Reverse) => end - 1,
+            (Some((start, _)), _) => start,
+            (None, _) => return Ok(None),
+        };
+        match self.genomic_to_transcript(first) {
+            Some(tpos) => self.kozak_context_at(source, tpos).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the Kozak context of a start codon whose first base lies at the given
+    /// zero-based position along the spliced transcript, such as the start of an `Orf`.
+    pub fn kozak_context_at<S: SequenceSource>(&self, source: &mut S, pos: u64)
+        -> ::Result<KozakContext>
+    {
+        let spliced = self.spliced_sequence(source)?;
+        Ok(KozakContext::from_spliced(&spliced, pos))
+    }
+}
+
+/// Helper function to return the uppercase form of the given nucleotide.
+#[inline]
+fn uppercase(base: u8) -> u8 {
+    if base >= b'a' && base <= b'z' { base - (b'a' - b'A') } else { base }
+}
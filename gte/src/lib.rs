@@ -3,7 +3,19 @@ The `gte` crate provides functionalities for working with genes, transcripts, an
 
 It defines simple structs for genes, transcripts, and exons, along with builders of these
 structures that accept a flexible range of arguments. You can create these structs on your own
-or from formats such as GFF and refFlat which are commonly used for storing gene annotations.
+or from formats such as GFF, refFlat, and genePred which are commonly used for storing gene
+annotations.
+
+With the `serde_impl` feature enabled, the gene, transcript, exon, and record types implement
+serde's `Serialize` and `Deserialize` traits. The `cache` feature additionally enables saving
+and loading these types as binary caches. The `htslib` feature enables assigning BAM/CRAM
+records to genes. The `compact_coords` feature stores the coordinates of exon, transcript, and
+gene features as `u32` values, which roughly halves the memory used by large annotations but
+limits feature coordinates to sequences of up to 4 Gbp. The `async` feature enables reading
+refFlat and GFF files from `tokio` sources as `futures` streams. The `remote` feature enables
+reading files from HTTP URLs, including querying tabix-indexed GFF files using range requests.
+The `sqlite` feature enables writing genes into SQLite stores that can be queried without
+parsing the annotation again.
 
 */
 #![deny(missing_docs,
@@ -12,33 +24,212 @@ or from formats such as GFF and refFlat which are commonly used for storing gene
         unstable_features,
         unused_extern_crates, unused_import_braces, unused_qualifications)]
 #![warn(unused_results)]
-#![recursion_limit="128"]
+#![recursion_limit="256"]
 
+#[cfg(feature = "cache")]
+extern crate bincode;
 extern crate bio;
 extern crate csv;
+extern crate flate2;
+#[cfg(feature = "async")]
+extern crate futures;
 extern crate itertools;
 extern crate linked_hash_map;
 extern crate multimap;
 #[macro_use]
 extern crate quick_error;
+#[cfg(feature = "rayon")]
+extern crate rayon;
+#[cfg(feature = "remote")]
+extern crate reqwest;
+#[cfg(feature = "sqlite")]
+extern crate rusqlite;
+#[cfg(feature = "htslib")]
+extern crate rust_htslib;
 extern crate regex;
+#[cfg(feature = "serde_impl")]
+extern crate serde;
+#[cfg(feature = "serde_impl")]
+#[macro_use]
+extern crate serde_derive;
+#[cfg(feature = "async")]
+extern crate tokio_io;
+
+use std::fmt;
 
 pub use bio::utils::Strand;
 pub use bio::io::gff::GffType;
 
+mod attributes;
+pub use attributes::{AttributeKeys, Attributes, AttributesIter};
+
+mod biotype;
+pub use biotype::Biotype;
+
+mod seq_name;
+pub use seq_name::{SeqName, SeqNamePool};
+
+mod progress;
+pub use progress::{CancelToken, Observer};
+
 mod model;
 pub use model::{Feature, ModelError, FeatureKind,
                 EBuilder, Exon, ExonFeature, ExonFeatureKind,
-                TBuilder, Transcript, TranscriptFeature, TranscriptFeatureKind,
-                GBuilder, Gene, GeneFeature, GeneFeatureKind};
+                TBuilder, Transcript, TranscriptFeature, TranscriptFeatureKind, Introns,
+                ExonsStranded, IntoTranscripts, Transcripts,
+                GBuilder, Gene, GeneFeature, GeneFeatureKind, FlankSide, DuplicatePolicy,
+                StrandPolicy, StrandConflictPolicy, BoundsWarning, strip_id_version};
+
+mod annotation;
+pub use annotation::{GenomeAnnotation, Promoters, Flanks,
+                     BiotypeFilter, SeqNameFilter, DedupGeneIds, GeneStreamExt,
+                     intergenic_regions};
 
 mod io_refflat;
 pub use io_refflat::{Reader as RefFlatReader, Writer as RefFlatWriter,
-                     RefFlatError, RefFlatRow, RefFlatRecord,
-                     RefFlatRecordsStream, RefFlatTranscriptsStream, RefFlatGenesStream};
+                     Index as RefFlatIndex, IndexedReader as RefFlatIndexedReader,
+                     Scanner as RefFlatScanner, RefFlatError, RefFlatRow, RefFlatRecord,
+                     RefFlatRecordRef,
+                     RefFlatRecordsStream, RefFlatTranscriptsStream, RefFlatGenesStream,
+                     RefFlatUnsortedGenes};
 
 mod io_gff;
-pub use io_gff::{Reader as GffReader, GffError, GffTranscripts};
+pub use io_gff::{Reader as GffReader, IndexedReader as GffIndexedReader, GffError,
+                 Writer as GffWriter,
+                 Scanner as GffScanner, GffRecordRef,
+                 GffCodingOptions, GffTranscripts, GffGenes, GffRegulatoryFeatures,
+                 gff3_escape, gff3_unescape};
+
+#[cfg(feature = "async")]
+mod io_async;
+#[cfg(feature = "async")]
+pub use io_async::{AsyncRefFlatReader, AsyncRefFlatRecords, AsyncRefFlatTranscripts,
+                   AsyncRefFlatGenes, AsyncGffReader, AsyncGffTranscripts, AsyncGffGenes};
+
+mod io_bed;
+pub use io_bed::{Reader as BedReader, Writer as BedWriter, BedError, BedTranscripts, BedGenes};
+
+mod io_auto;
+pub use io_auto::{AnnotationReader, AnnotationFormat, ReadAnnotation, WriteAnnotation, DetectError,
+                  convert};
+
+pub mod convert;
+pub use convert::{ConvertOptions, ConvertError};
+
+mod regulatory;
+pub use regulatory::{RegulatoryFeature, RegulatoryKind};
+
+mod feature_tree;
+pub use feature_tree::{FeatureNode, FeatureTree};
+
+mod tabix;
+pub use tabix::TabixError;
+
+mod bgzf;
+pub use bgzf::BgzfReader;
+
+mod sequence;
+pub use sequence::{SequenceSource, SequenceError, CodonTable, reverse_complement};
+
+mod consequence;
+pub use consequence::{Variant, Consequence};
+
+mod hgvs;
+
+mod orf;
+pub use orf::Orf;
+
+mod kozak;
+pub use kozak::{KozakContext, KozakStrength};
+
+mod validate;
+pub use validate::{Validator, Check as ValidationCheck, Issue as ValidationIssue,
+                   Report as ValidationReport};
+
+mod io_intervallist;
+pub use io_intervallist::{Writer as IntervalListWriter, IntervalListError, SeqDict};
+
+mod liftover;
+pub use liftover::{ChainMap, LiftStatus, LiftReport, LiftOverError};
+
+mod alias;
+pub use alias::{SeqAliases, NameStyle, AliasError};
+
+mod rewrite;
+pub use rewrite::{SeqRewrite, RewriteError, RewrittenGenes, RewrittenTranscripts};
+
+mod ops;
+pub use ops::{merge_intervals, intersect_intervals, subtract_intervals, complement_intervals};
+
+mod io_saf;
+pub use io_saf::{Writer as SafWriter, SafError};
+
+mod dexseq;
+pub use dexseq::{ExonicPart, Writer as DexSeqWriter, DexSeqError};
+
+mod assign;
+pub use assign::{Alignment, Assignment, ReadAssigner, Strandedness, AssignError};
+
+mod filter;
+pub use filter::{FeatureFilter, FilterError, FilteredGenes, FilteredTranscripts, parse_region};
+
+mod stats;
+pub use stats::{AnnotationStats, LengthStats, SeqStats, GeneMetrics, TranscriptMetrics,
+                MetricsWriter, StatsError};
+
+mod diff;
+pub use diff::{AnnotationDiff, DiffOptions, GeneDiff, TranscriptDiff, DiffStatus, StructuralChange,
+               DiffWriter, DiffError};
+
+mod panel;
+pub use panel::{PanelBuilder, PanelRegion, PanelRegionKind, PanelError, write_panel_bed,
+                write_panel_bed_file};
+
+mod id_map;
+pub use id_map::{IdMap, IdMapError};
+
+mod id_gen;
+pub use id_gen::{IdGenerator, IdScheme, DEF_GENE_ID_PREFIX, DEF_TRANSCRIPT_ID_PREFIX};
+
+mod par;
+pub use par::{ParYPolicy, ParYGenes, ParYTranscripts, PAR_X_ID_ATTR, is_par_y_id, par_x_id};
+
+mod merge;
+pub use merge::{MergedGenes, MergeOptions, MergeError, SOURCE_ATTR};
+
+mod cluster;
+pub use cluster::{ClusteredGenes, DEF_CLUSTER_PREFIX, REF_GENE_ID_ATTR};
+
+mod dedup;
+pub use dedup::{Deduplicator, DuplicateAction, MERGED_IDS_ATTR, DUPLICATE_OF_ATTR};
+
+mod bounds;
+pub use bounds::{BoundsAction, BoundsChecker, BoundsIssue, BoundsIssueKind, BoundedGenes};
+
+mod sort;
+pub use sort::{SeqOrder, sort_genes, sort_transcripts, sorted_genes};
+
+mod extsort;
+pub use extsort::{ExternalSorter, ExtSortError, SortRecord, SortedRecords, GffLine, GffLines,
+                  gff_lines};
+
+mod junction;
+pub use junction::{Junction, JunctionSet, JunctionFormat, Writer as JunctionWriter, JunctionError};
+
+mod cache;
+pub use cache::{CacheError, CACHE_MAGIC, CACHE_VERSION};
+#[cfg(feature = "cache")]
+pub use cache::{read_cache, write_cache, load_cache, save_cache};
+
+mod store;
+pub use store::StoreError;
+#[cfg(feature = "sqlite")]
+pub use store::GeneStore;
+
+mod io_genepred;
+pub use io_genepred::{Reader as GenePredReader, Writer as GenePredWriter,
+                      GenePredError, GenePredRecord, GenePredExt, CdsStat,
+                      GenePredRecordsStream, GenePredTranscriptsStream, GenePredGenesStream};
 
 
 quick_error! {
@@ -66,6 +257,243 @@ quick_error! {
             from()
             cause(err)
         }
+        /// Errors that occur when reading or writing genePred files.
+        GenePred(err: GenePredError) {
+            description(err.description())
+            display("{}", err)
+            from()
+            cause(err)
+        }
+        /// Errors that occur when extracting sequences.
+        Sequence(err: SequenceError) {
+            description(err.description())
+            display("{}", err)
+            from()
+            cause(err)
+        }
+        /// Errors that occur when reading or writing BED12 files.
+        Bed(err: BedError) {
+            description(err.description())
+            display("{}", err)
+            from()
+            cause(err)
+        }
+        /// Errors that occur when detecting the format of annotation files.
+        Detect(err: DetectError) {
+            description(err.description())
+            display("{}", err)
+            from()
+            cause(err)
+        }
+        /// Errors that occur when converting annotation files.
+        Convert(err: ConvertError) {
+            description(err.description())
+            display("{}", err)
+            from()
+            cause(err)
+        }
+        /// Errors that occur when creating filters.
+        Filter(err: FilterError) {
+            description(err.description())
+            display("{}", err)
+            from()
+            cause(err)
+        }
+        /// Errors that occur when writing metrics.
+        Stats(err: StatsError) {
+            description(err.description())
+            display("{}", err)
+            from()
+            cause(err)
+        }
+        /// Errors that occur when creating or writing panel regions.
+        Panel(err: PanelError) {
+            description(err.description())
+            display("{}", err)
+            from()
+            cause(err)
+        }
+        /// Errors that occur when writing identifier mappings.
+        IdMap(err: IdMapError) {
+            description(err.description())
+            display("{}", err)
+            from()
+            cause(err)
+        }
+        /// Errors that occur when merging annotation sources.
+        Merge(err: MergeError) {
+            description(err.description())
+            display("{}", err)
+            from()
+            cause(err)
+        }
+        /// Errors that occur when comparing annotations.
+        Diff(err: DiffError) {
+            description(err.description())
+            display("{}", err)
+            from()
+            cause(err)
+        }
+        /// Errors that occur when reading tabix indices.
+        Tabix(err: TabixError) {
+            description(err.description())
+            display("{}", err)
+            from()
+            cause(err)
+        }
+        /// Errors that occur when writing interval lists.
+        IntervalList(err: IntervalListError) {
+            description(err.description())
+            display("{}", err)
+            from()
+            cause(err)
+        }
+        /// Errors that occur when reading chain files.
+        LiftOver(err: LiftOverError) {
+            description(err.description())
+            display("{}", err)
+            from()
+            cause(err)
+        }
+        /// Errors that occur when reading sequence aliases.
+        Alias(err: AliasError) {
+            description(err.description())
+            display("{}", err)
+            from()
+            cause(err)
+        }
+        /// Errors that occur when reading rewrite tables.
+        Rewrite(err: RewriteError) {
+            description(err.description())
+            display("{}", err)
+            from()
+            cause(err)
+        }
+        /// Errors that occur when creating alignments.
+        Assign(err: AssignError) {
+            description(err.description())
+            display("{}", err)
+            from()
+            cause(err)
+        }
+        /// Errors that occur when writing SAF files.
+        Saf(err: SafError) {
+            description(err.description())
+            display("{}", err)
+            from()
+            cause(err)
+        }
+        /// Errors that occur when writing DEXSeq flattened annotations.
+        DexSeq(err: DexSeqError) {
+            description(err.description())
+            display("{}", err)
+            from()
+            cause(err)
+        }
+        /// Errors that occur when sorting records externally.
+        ExtSort(err: ExtSortError) {
+            description(err.description())
+            display("{}", err)
+            from()
+            cause(err)
+        }
+        /// Errors that occur when writing splice junctions.
+        Junction(err: JunctionError) {
+            description(err.description())
+            display("{}", err)
+            from()
+            cause(err)
+        }
+        /// Errors that occur when saving or loading caches.
+        Cache(err: CacheError) {
+            description(err.description())
+            display("{}", err)
+            from()
+            cause(err)
+        }
+        /// Errors that occur when writing or querying stores.
+        Store(err: StoreError) {
+            description(err.description())
+            display("{}", err)
+            from()
+            cause(err)
+        }
+        /// Occurs when reading is cancelled using a `CancelToken`.
+        Cancelled {
+            description("reading cancelled")
+        }
+        /// Errors that occur at a specific line of the input.
+        Line(line: u64, err: Box<Error>) {
+            description(err.description())
+            display("line {}: {}", line, err)
+            cause(err)
+        }
+    }
+}
+
+impl Error {
+
+    /// Creates an error that occurs at the given one-based line number of the input.
+    ///
+    /// Errors that already carry a line number are returned as-is.
+    pub fn at_line(line: u64, err: Error) -> Error {
+        match err {
+            Error::Line(..) => err,
+            _ => Error::Line(line, Box::new(err)),
+        }
+    }
+
+    /// Returns the one-based line number of the input in which the error occurs, if known.
+    pub fn line(&self) -> Option<u64> {
+        match *self {
+            Error::Line(line, _) => Some(line),
+            _ => None,
+        }
+    }
+
+    /// Returns the underlying error, without its line context.
+    pub fn inner(&self) -> &Error {
+        match *self {
+            Error::Line(_, ref err) => err,
+            _ => self,
+        }
+    }
+}
+
+/// A non-fatal error encountered by readers in lenient mode.
+///
+/// Readers set to be lenient skip malformed input lines instead of yielding errors, recording
+/// each skipped line as a warning.
+#[derive(Debug)]
+pub struct ParseWarning {
+    line: u64,
+    error: Error,
+}
+
+impl ParseWarning {
+
+    /// Creates a warning for the given line number and error.
+    pub(crate) fn new(line: u64, error: Error) -> Self {
+        ParseWarning {
+            line: line,
+            error: error,
+        }
+    }
+
+    /// Returns the one-based number of the skipped line.
+    pub fn line(&self) -> u64 {
+        self.line
+    }
+
+    /// Returns the error that caused the line to be skipped.
+    pub fn error(&self) -> &Error {
+        &self.error
+    }
+}
+
+impl fmt::Display for ParseWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.error)
     }
 }
 
@@ -104,7 +532,23 @@ const INIT_COORD: (u64, u64) = (INIT_START, INIT_END);
 
 /// Utility functions.
 mod utils {
+    use std::fs;
+    use std::io::{self, BufRead, BufReader, Read};
     use std::ops::Deref;
+    use std::path::Path;
+    use std::sync::Arc;
+    use std::sync::atomic::AtomicUsize;
+
+    use flate2::read::MultiGzDecoder;
+    #[cfg(feature = "remote")]
+    use reqwest::{self, StatusCode};
+    #[cfg(feature = "remote")]
+    use reqwest::header::{ByteRangeSpec, Range};
+
+    use bgzf::{is_bgzf, BgzfReader};
+
+    /// Magic bytes at the start of gzip (and thus also bgzip) files.
+    const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
 
     // taken from: https://stackoverflow.com/q/31233938/243058
     /// Helper trait for dereferencing wrapped option values.
@@ -144,4 +588,130 @@ mod utils {
             }
         }
     }
+
+    /// Helper function for opening files that may be compressed.
+    ///
+    /// Gzip-compressed files, including bgzip-compressed files which consist of multiple gzip
+    /// members, are detected from their magic bytes and decompressed on the fly. Other files are
+    /// read as-is.
+    pub(crate) fn open_path<P: AsRef<Path>>(path: P) -> io::Result<Box<Read>> {
+        open_path_threads(path, Arc::new(AtomicUsize::new(1)))
+    }
+
+    /// Helper function for opening files that may be compressed, decompressing bgzip-compressed
+    /// files with the shared number of threads.
+    ///
+    /// The number of threads is read when reading starts, so it can still be changed after the
+    /// file is opened.
+    pub(crate) fn open_path_threads<P: AsRef<Path>>(
+        path: P,
+        threads: Arc<AtomicUsize>,
+    ) -> io::Result<Box<Read>> {
+        decompress(BufReader::new(fs::File::open(path)?), threads)
+    }
+
+    /// Helper function for opening remote files that may be compressed.
+    ///
+    /// Compression is detected as in `open_path`.
+    #[cfg(feature = "remote")]
+    pub(crate) fn open_url(url: &str) -> io::Result<Box<Read>> {
+        let response = open_url_from(url, 0)?;
+        decompress(BufReader::new(response), Arc::new(AtomicUsize::new(1)))
+    }
+
+    /// Helper function for requesting the contents of a remote file from the given byte offset
+    /// onwards.
+    ///
+    /// The offset is requested using an HTTP range request. Servers that do not support range
+    /// requests send the entire file, in which case the bytes before the offset are skipped.
+    #[cfg(feature = "remote")]
+    pub(crate) fn open_url_from(url: &str, offset: u64) -> io::Result<Box<Read>> {
+        let client = reqwest::Client::new();
+        let mut request = client.get(url);
+        if offset > 0 {
+            let _ = request.header(Range::Bytes(vec![ByteRangeSpec::AllFrom(offset)]));
+        }
+        let mut response = request.send()
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+        let status = response.status();
+        if status == StatusCode::PartialContent {
+            return Ok(Box::new(response));
+        }
+        if !status.is_success() {
+            let msg = format!("unexpected HTTP status {} for {}", status, url);
+            return Err(io::Error::new(io::ErrorKind::Other, msg));
+        }
+        let _ = io::copy(&mut (&mut response).take(offset), &mut io::sink())?;
+        Ok(Box::new(response))
+    }
+
+    /// Helper function for decompressing readers whose contents may be compressed.
+    fn decompress<R: BufRead + 'static>(mut reader: R, threads: Arc<AtomicUsize>)
+        -> io::Result<Box<Read>>
+    {
+        let (is_gzip, is_bgzip) = {
+            let head = reader.fill_buf()?;
+            (head.starts_with(&GZIP_MAGIC), is_bgzf(head))
+        };
+        let opened: Box<Read> =
+            if is_bgzip {
+                Box::new(BgzfReader::with_threads(reader, threads))
+            } else if is_gzip {
+                Box::new(MultiGzDecoder::new(reader)?)
+            } else {
+                Box::new(reader)
+            };
+        Ok(opened)
+    }
+
+    /// Serialization of intervals as pairs of start and end coordinates.
+    #[cfg(feature = "serde_impl")]
+    pub(crate) mod serde_interval {
+        use bio::utils::Interval;
+        use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+        pub fn serialize<S>(interval: &Interval<u64>, serializer: S) -> Result<S::Ok, S::Error>
+            where S: Serializer
+        {
+            (interval.start, interval.end).serialize(serializer)
+        }
+
+        pub fn deserialize<'de, D>(deserializer: D) -> Result<Interval<u64>, D::Error>
+            where D: Deserializer<'de>
+        {
+            let (start, end) = <(u64, u64) as Deserialize>::deserialize(deserializer)?;
+            Interval::new(start..end)
+                .map_err(|_| de::Error::custom(format!("invalid interval: {}-{}", start, end)))
+        }
+    }
+
+    /// Serialization of strands as their GFF characters.
+    #[cfg(feature = "serde_impl")]
+    pub(crate) mod serde_strand {
+        use bio::utils::Strand;
+        use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+        pub fn serialize<S>(strand: &Strand, serializer: S) -> Result<S::Ok, S::Error>
+            where S: Serializer
+        {
+            let chr = match *strand {
+                Strand::Forward => '+',
+                Strand::Reverse => '-',
+                Strand::Unknown => '.',
+            };
+            chr.serialize(serializer)
+        }
+
+        pub fn deserialize<'de, D>(deserializer: D) -> Result<Strand, D::Error>
+            where D: Deserializer<'de>
+        {
+            match <char as Deserialize>::deserialize(deserializer)? {
+                '+' => Ok(Strand::Forward),
+                '-' => Ok(Strand::Reverse),
+                '.' => Ok(Strand::Unknown),
+                other => Err(de::Error::invalid_value(de::Unexpected::Char(other),
+                                                      &"one of '+', '-', or '.'")),
+            }
+        }
+    }
 }
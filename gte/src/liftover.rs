@@ -0,0 +1,454 @@
+/*! Lifting of gene and transcript models between assemblies using UCSC chain files.
+
+[Chain files](https://genome.ucsc.edu/goldenPath/help/chain.html) describe alignments between a
+reference assembly, from which models are lifted, and a query assembly, to which models are
+lifted. Each chain consists of ungapped blocks aligning a reference sequence to a query sequence,
+with the query sequence possibly aligned on its reverse strand.
+
+Each transcript is lifted using the single chain that maps most of its exonic bases, so that all
+of its lifted exons lie on the same sequence and strand. Exons are split where the query has
+bases missing from the reference, trimmed where their bases do not map, and dropped if none of
+their bases map. Exon features are only kept if all of their bases map contiguously. The outcome
+for each exon is recorded in a report.
+*/
+use std::cmp::{max, min};
+use std::collections::HashMap;
+use std::error::Error;
+use std::io::{self, BufRead, BufReader};
+use std::mem;
+use std::path::Path;
+
+use bio::utils::Interval;
+use linked_hash_map::LinkedHashMap;
+
+use {Coord, EBuilder, Exon, ExonFeature, Gene, GBuilder, Strand, TBuilder, Transcript};
+use utils::open_path;
+
+
+quick_error! {
+    /// Errors that occur when reading chain files.
+    #[derive(Debug)]
+    pub enum LiftOverError {
+        /// Occurs when a chain file line can not be parsed.
+        InvalidChainLine(line: String) {
+            description("invalid chain file line")
+            display(self_) -> ("{}: '{}'", self_.description(), line)
+        }
+        /// Errors propagated from reading the underlying stream.
+        Io(err: io::Error) {
+            description(err.description())
+            from()
+            cause(err)
+        }
+    }
+}
+
+/// Ungapped alignment block of a chain.
+#[derive(Debug, Clone, PartialEq)]
+struct Block {
+    ref_start: u64,
+    query_start: u64,
+    size: u64,
+}
+
+/// Alignment chain between a reference and a query sequence.
+///
+/// Query coordinates of the blocks are on the aligned strand of the query sequence.
+#[derive(Debug, Clone, PartialEq)]
+struct Chain {
+    ref_start: u64,
+    ref_end: u64,
+    query_name: String,
+    query_size: u64,
+    query_reverse: bool,
+    blocks: Vec<Block>,
+}
+
+impl Chain {
+
+    /// Returns the genome-wise sorted query segments of the given reference region.
+    ///
+    /// Segments that are adjacent in the query are merged into one.
+    fn lift(&self, start: u64, end: u64) -> Vec<Coord<u64>> {
+        let mut segments: Vec<Coord<u64>> = Vec::new();
+        if end <= self.ref_start || start >= self.ref_end {
+            return segments;
+        }
+        for block in self.blocks.iter() {
+            let block_end = block.ref_start + block.size;
+            if block_end <= start {
+                continue;
+            }
+            if block.ref_start >= end {
+                break;
+            }
+            let (ov_start, ov_end) = (max(start, block.ref_start), min(end, block_end));
+            let query_start = block.query_start + (ov_start - block.ref_start);
+            let query_end = query_start + (ov_end - ov_start);
+            if let Some(last) = segments.last_mut() {
+                if last.1 == query_start {
+                    last.1 = query_end;
+                    continue;
+                }
+            }
+            segments.push((query_start, query_end));
+        }
+        if self.query_reverse {
+            segments = segments.into_iter()
+                .rev()
+                .map(|(start, end)| (self.query_size - end, self.query_size - start))
+                .collect();
+        }
+        segments
+    }
+
+    /// Returns the number of bases of the given reference region that map to the query.
+    fn mapped_len(&self, start: u64, end: u64) -> u64 {
+        self.lift(start, end).iter().map(|&(s, e)| e - s).sum()
+    }
+}
+
+/// Outcomes of lifting an exon.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LiftStatus {
+    /// All bases of the exon map to a single interval.
+    Mapped,
+    /// The mapped bases of the exon are split into the given number of intervals, or some of its
+    /// bases do not map.
+    Partial(usize),
+    /// None of the bases of the exon map.
+    Dropped,
+}
+
+/// Lift report of a single transcript.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LiftReport {
+    transcript_id: Option<String>,
+    exons: Vec<LiftStatus>,
+    dropped_features: usize,
+    lifted: bool,
+}
+
+impl LiftReport {
+
+    /// Returns the identifier of the lifted transcript.
+    pub fn transcript_id(&self) -> Option<&str> {
+        self.transcript_id.as_ref().map(|v| v.as_str())
+    }
+
+    /// Returns the outcomes of lifting each exon, in the order of the input exons.
+    pub fn exon_statuses(&self) -> &[LiftStatus] {
+        self.exons.as_slice()
+    }
+
+    /// Returns the number of exon features that were not kept.
+    pub fn dropped_features(&self) -> usize {
+        self.dropped_features
+    }
+
+    /// Returns whether the transcript was lifted.
+    pub fn is_lifted(&self) -> bool {
+        self.lifted
+    }
+
+    /// Returns whether the transcript was lifted with all of its exons and features intact.
+    pub fn is_complete(&self) -> bool {
+        self.lifted && self.dropped_features == 0 &&
+            self.exons.iter().all(|status| *status == LiftStatus::Mapped)
+    }
+}
+
+/// Chains of a chain file, keyed by their reference sequence names.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ChainMap {
+    chains: HashMap<String, Vec<Chain>>,
+}
+
+impl ChainMap {
+
+    /// Creates a chain map from a chain file.
+    pub fn from_reader<R: io::Read>(in_reader: R) -> ::Result<Self> {
+        let mut map = ChainMap::default();
+        let mut current: Option<(String, Chain)> = None;
+        let (mut ref_pos, mut query_pos) = (0, 0);
+        for line in BufReader::new(in_reader).lines() {
+            let line = line.map_err(|e| ::Error::from(LiftOverError::from(e)))?;
+            let fields = line.split_whitespace().collect::<Vec<&str>>();
+            if fields.is_empty() || fields[0].starts_with('#') {
+                continue;
+            }
+            if fields[0] == "chain" {
+                let (ref_name, chain, query_start) = parse_chain_header(&fields)
+                    .ok_or_else(|| LiftOverError::InvalidChainLine(line.clone()))?;
+                ref_pos = chain.ref_start;
+                query_pos = query_start;
+                if let Some((prev_name, prev_chain)) =
+                    mem::replace(&mut current, Some((ref_name, chain)))
+                {
+                    map.push(prev_name, prev_chain);
+                }
+                continue;
+            }
+            let (size, ref_gap, query_gap) = match parse_block_line(&fields) {
+                Some(nums) if current.is_some() => nums,
+                _ => return Err(::Error::from(LiftOverError::InvalidChainLine(line.clone()))),
+            };
+            if let Some((_, ref mut chain)) = current {
+                chain.blocks.push(Block {
+                    ref_start: ref_pos,
+                    query_start: query_pos,
+                    size: size,
+                });
+            }
+            ref_pos += size + ref_gap;
+            query_pos += size + query_gap;
+        }
+        if let Some((ref_name, chain)) = current {
+            map.push(ref_name, chain);
+        }
+        Ok(map)
+    }
+
+    /// Creates a chain map from a chain file, which may be gzip-compressed.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> ::Result<Self> {
+        let file = open_path(path).map_err(|e| ::Error::from(LiftOverError::from(e)))?;
+        ChainMap::from_reader(file)
+    }
+
+    /// Returns the number of chains.
+    pub fn len(&self) -> usize {
+        self.chains.values().map(|chains| chains.len()).sum()
+    }
+
+    /// Returns whether the map contains no chains.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Lifts the given transcript, returning the lifted transcript along with its report.
+    ///
+    /// `None` is returned in place of the transcript if none of its exons map.
+    pub fn lift_transcript(&self, transcript: &Transcript)
+        -> ::Result<(Option<Transcript>, LiftReport)>
+    {
+        let mut report = LiftReport {
+            transcript_id: transcript.id().map(|v| v.to_owned()),
+            exons: vec![LiftStatus::Dropped; transcript.exons().len()],
+            dropped_features: transcript.exons().iter().map(|exn| exn.features().len()).sum(),
+            lifted: false,
+        };
+        let chain = match self.best_chain(transcript) {
+            Some(chain) => chain,
+            None => return Ok((None, report)),
+        };
+        let strand = match (*transcript.strand(), chain.query_reverse) {
+            (Strand::Forward, true) => Strand::Reverse,
+            (Strand::Reverse, true) => Strand::Forward,
+            (other, _) => other,
+        };
+
+        let mut exons = Vec::new();
+        report.dropped_features = 0;
+        for (idx, exon) in transcript.exons().iter().enumerate() {
+            let segments = chain.lift(exon.start(), exon.end());
+            let mapped = segments.iter().map(|&(s, e)| e - s).sum::<u64>();
+            report.exons[idx] = match segments.len() {
+                0 => LiftStatus::Dropped,
+                1 if mapped == exon.span() => LiftStatus::Mapped,
+                n => LiftStatus::Partial(n),
+            };
+
+            let mut features = exon.features().iter()
+                .filter_map(|fx| lift_feature(chain, fx))
+                .collect::<Vec<ExonFeature>>();
+            report.dropped_features += exon.features().len() - features.len();
+            features.sort_by_key(|fx| (fx.start(), fx.end()));
+
+            for (start, end) in segments {
+                let (inner, outer): (Vec<ExonFeature>, Vec<ExonFeature>) = features.into_iter()
+                    .partition(|fx| fx.start() >= start && fx.end() <= end);
+                features = outer;
+                exons.push(lifted_exon(exon, &chain.query_name, strand, start, end, inner)?);
+            }
+        }
+        if exons.is_empty() {
+            return Ok((None, report));
+        }
+        exons.sort_by_key(|exn| (exn.start(), exn.end()));
+
+        let start = exons.iter().map(|exn| exn.start()).min().unwrap();
+        let end = exons.iter().map(|exn| exn.end()).max().unwrap();
+        let mut builder = TBuilder::new(chain.query_name.as_str(), start, end)
+            .strand(strand)
+            .attributes(transcript.attributes().clone())
+            .exons(exons);
+        if let Some(id) = transcript.id() {
+            builder = builder.id(id);
+        }
+        if let Some(gene_id) = transcript.gene_id() {
+            builder = builder.gene_id(gene_id);
+        }
+        if let Some(source) = transcript.source() {
+            builder = builder.source(source);
+        }
+        if let Some(score) = transcript.score() {
+            builder = builder.score(score);
+        }
+        report.lifted = true;
+        Ok((Some(builder.build()?), report))
+    }
+
+    /// Lifts the given gene, returning the lifted gene along with the reports of its
+    /// transcripts.
+    ///
+    /// The sequence and strand of the gene are set by its first lifted transcript. Transcripts
+    /// that are lifted to other sequences or strands are dropped and reported as not lifted.
+    /// `None` is returned in place of the gene if none of its transcripts are lifted.
+    pub fn lift_gene(&self, gene: &Gene) -> ::Result<(Option<Gene>, Vec<LiftReport>)> {
+        let mut reports = Vec::with_capacity(gene.transcripts().len());
+        let mut transcripts = LinkedHashMap::new();
+        let mut location: Option<(String, Strand)> = None;
+        for (key, transcript) in gene.transcripts().iter() {
+            let (lifted, mut report) = self.lift_transcript(transcript)?;
+            if let Some(trx) = lifted {
+                let trx_location = (trx.seq_name().to_owned(), *trx.strand());
+                if location.is_none() {
+                    location = Some(trx_location.clone());
+                }
+                if location.as_ref() == Some(&trx_location) {
+                    let _ = transcripts.insert(key.clone(), trx);
+                } else {
+                    report.lifted = false;
+                }
+            }
+            reports.push(report);
+        }
+        let (seq_name, strand) = match location {
+            Some(location) => location,
+            None => return Ok((None, reports)),
+        };
+
+        let start = transcripts.values().map(|trx| trx.start()).min().unwrap();
+        let end = transcripts.values().map(|trx| trx.end()).max().unwrap();
+        let mut builder = GBuilder::new(seq_name, start, end)
+            .strand(strand)
+            .attributes(gene.attributes().clone())
+            .transcripts(transcripts);
+        if let Some(id) = gene.id() {
+            builder = builder.id(id);
+        }
+        if let Some(source) = gene.source() {
+            builder = builder.source(source);
+        }
+        if let Some(score) = gene.score() {
+            builder = builder.score(score);
+        }
+        Ok((Some(builder.build()?), reports))
+    }
+
+    /// Adds a chain of the given reference sequence.
+    fn push(&mut self, ref_name: String, chain: Chain) {
+        self.chains.entry(ref_name).or_insert_with(Vec::new).push(chain)
+    }
+
+    /// Returns the chain that maps most of the exonic bases of the given transcript.
+    ///
+    /// Ties are resolved in favor of the chain that comes first in the chain file.
+    fn best_chain(&self, transcript: &Transcript) -> Option<&Chain> {
+        let chains = match self.chains.get(transcript.seq_name()) {
+            Some(chains) => chains,
+            None => return None,
+        };
+        let mut best: Option<(&Chain, u64)> = None;
+        for chain in chains.iter() {
+            let mapped = transcript.exons().iter()
+                .map(|exn| chain.mapped_len(exn.start(), exn.end()))
+                .sum::<u64>();
+            if mapped > best.map(|(_, len)| len).unwrap_or(0) {
+                best = Some((chain, mapped));
+            }
+        }
+        best.map(|(chain, _)| chain)
+    }
+}
+
+/// Helper function to parse the header line of a chain.
+///
+/// Returns the reference sequence name, the chain without any blocks, and the query start
+/// coordinate of the chain on its aligned strand.
+fn parse_chain_header(fields: &[&str]) -> Option<(String, Chain, u64)> {
+    if fields.len() < 12 {
+        return None;
+    }
+    let num = |idx: usize| fields[idx].parse::<u64>().ok();
+    let query_reverse = match fields[9] {
+        "+" => false,
+        "-" => true,
+        _ => return None,
+    };
+    match (num(5), num(6), num(8), num(10)) {
+        (Some(ref_start), Some(ref_end), Some(query_size), Some(query_start)) => {
+            let chain = Chain {
+                ref_start: ref_start,
+                ref_end: ref_end,
+                query_name: fields[7].to_owned(),
+                query_size: query_size,
+                query_reverse: query_reverse,
+                blocks: Vec::new(),
+            };
+            Some((fields[2].to_owned(), chain, query_start))
+        },
+        _ => None,
+    }
+}
+
+/// Helper function to parse an alignment data line of a chain.
+///
+/// Returns the block size and the gaps following the block in the reference and the query. The
+/// gaps are zero for the last block of a chain.
+fn parse_block_line(fields: &[&str]) -> Option<(u64, u64, u64)> {
+    let nums = fields.iter()
+        .map(|field| field.parse::<u64>().ok())
+        .collect::<Option<Vec<u64>>>();
+    match nums {
+        Some(ref nums) if nums.len() == 1 => Some((nums[0], 0, 0)),
+        Some(ref nums) if nums.len() == 3 => Some((nums[0], nums[1], nums[2])),
+        _ => None,
+    }
+}
+
+/// Helper function to lift an exon feature whose bases all map contiguously.
+fn lift_feature(chain: &Chain, feature: &ExonFeature) -> Option<ExonFeature> {
+    let segments = chain.lift(feature.start(), feature.end());
+    match segments.first() {
+        Some(&(start, end)) if segments.len() == 1 && end - start == feature.span() =>
+            Some(ExonFeature::new(Interval::new(start..end).unwrap(), feature.kind().clone())),
+        _ => None,
+    }
+}
+
+/// Helper function to create a lifted exon from a segment of the given exon.
+fn lifted_exon(exon: &Exon, seq_name: &str, strand: Strand, start: u64, end: u64,
+               features: Vec<ExonFeature>) -> ::Result<Exon>
+{
+    let mut builder = EBuilder::new(seq_name, start, end)
+        .strand(strand)
+        .attributes(exon.attributes().clone())
+        .features(features);
+    if let Some(id) = exon.id() {
+        builder = builder.id(id);
+    }
+    if let Some(transcript_id) = exon.transcript_id() {
+        builder = builder.transcript_id(transcript_id);
+    }
+    if let Some(gene_id) = exon.gene_id() {
+        builder = builder.gene_id(gene_id);
+    }
+    if let Some(source) = exon.source() {
+        builder = builder.source(source);
+    }
+    if let Some(score) = exon.score() {
+        builder = builder.score(score);
+    }
+    builder.build()
+}
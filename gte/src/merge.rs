@@ -0,0 +1,279 @@
+/*! Merging of gene streams from multiple annotation sources.
+
+`MergedGenes` yields the genes of several named sources one source after another, for example
+a reference annotation followed by custom transgenes. Each gene and transcript is tagged with
+the name of its source in an attribute, `annotation_source` by default.
+
+Gene and transcript identifiers that were already yielded from an earlier source collide, and
+are resolved using a `DuplicatePolicy`. By default, colliding identifiers are renamed by adding
+the name of their source as a suffix (e.g. `GENE1_custom`), falling back to a numeric suffix
+if that identifier is also taken. Identifiers repeated within a single source are yielded as
+they are; those can be handled with `GeneStreamExt::dedup_gene_ids` before merging.
+*/
+use std::collections::{HashMap, VecDeque};
+use std::error::Error;
+use std::mem;
+use std::vec;
+
+use linked_hash_map::LinkedHashMap;
+
+use {DuplicatePolicy, Gene};
+use model::{strip_id_version, suffixed_id};
+
+
+/// Default attribute key of the source names.
+pub const SOURCE_ATTR: &'static str = "annotation_source";
+
+quick_error! {
+    /// Errors that occur when merging annotation sources.
+    #[derive(Debug)]
+    pub enum MergeError {
+        /// Occurs when a gene identifier of a source was already yielded from an earlier source.
+        GeneIdCollision(id: String, source: String) {
+            description("gene identifier collides with an earlier source")
+            display(self_) -> ("{}: '{}' in source '{}'", self_.description(), id, source)
+        }
+        /// Occurs when a transcript identifier of a source was already yielded from an earlier
+        /// source.
+        TranscriptIdCollision(id: String, source: String) {
+            description("transcript identifier collides with an earlier source")
+            display(self_) -> ("{}: '{}' in source '{}'", self_.description(), id, source)
+        }
+    }
+}
+
+/// Options of merging annotation sources.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MergeOptions {
+    policy: DuplicatePolicy,
+    rename_with_source: bool,
+    ignore_id_versions: bool,
+    source_attr: String,
+}
+
+impl Default for MergeOptions {
+    fn default() -> Self {
+        MergeOptions {
+            policy: DuplicatePolicy::Suffix,
+            rename_with_source: true,
+            ignore_id_versions: false,
+            source_attr: SOURCE_ATTR.to_owned(),
+        }
+    }
+}
+
+impl MergeOptions {
+
+    /// Creates options that rename colliding identifiers with the names of their sources.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the policy for identifiers that collide with those of earlier sources.
+    ///
+    /// With `KeepFirst`, colliding genes are discarded whole, while colliding transcripts of
+    /// other genes are discarded from their genes. With `KeepLast`, all genes of all sources
+    /// are read before the first one is yielded, and the genes of later sources take
+    /// precedence.
+    pub fn duplicate_policy(&mut self, policy: DuplicatePolicy) -> &mut Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Sets whether colliding identifiers are suffixed with the names of their sources before
+    /// falling back to numeric suffixes, with the `Suffix` policy.
+    pub fn rename_with_source(&mut self, rename_with_source: bool) -> &mut Self {
+        self.rename_with_source = rename_with_source;
+        self
+    }
+
+    /// Sets whether identifiers collide when they are the same without their version suffixes,
+    /// such as `ENST00000367770.8` and `ENST00000367770.9`.
+    pub fn ignore_id_versions(&mut self, ignore_id_versions: bool) -> &mut Self {
+        self.ignore_id_versions = ignore_id_versions;
+        self
+    }
+
+    /// Sets the attribute key of the source names.
+    pub fn source_attribute<T: Into<String>>(&mut self, key: T) -> &mut Self {
+        self.source_attr = key.into();
+        self
+    }
+}
+
+/// Iterator over the genes of multiple annotation sources.
+///
+/// Errors in the underlying gene streams are passed through as-is.
+pub struct MergedGenes<'a> {
+    sources: VecDeque<(String, Box<Iterator<Item=::Result<Gene>> + 'a>)>,
+    opts: MergeOptions,
+    source_idx: usize,
+    // identifiers already yielded, along with the index of their sources
+    gene_ids: HashMap<String, usize>,
+    transcript_ids: HashMap<String, usize>,
+    buffered: Option<vec::IntoIter<::Result<Gene>>>,
+}
+
+impl<'a> MergedGenes<'a> {
+
+    /// Creates an iterator without any sources, using the given options.
+    pub fn new(opts: MergeOptions) -> Self {
+        MergedGenes {
+            sources: VecDeque::new(),
+            opts: opts,
+            source_idx: 0,
+            gene_ids: HashMap::new(),
+            transcript_ids: HashMap::new(),
+            buffered: None,
+        }
+    }
+
+    /// Adds the genes of a source with the given name after those of the sources added before.
+    pub fn add_source<S, T>(&mut self, name: S, genes: T) -> &mut Self
+        where S: Into<String>, T: IntoIterator<Item=::Result<Gene>>, T::IntoIter: 'a
+    {
+        let genes: Box<Iterator<Item=::Result<Gene>> + 'a> = Box::new(genes.into_iter());
+        self.sources.push_back((name.into(), genes));
+        self
+    }
+
+    /// Reads the genes of all sources, resolving collisions in favor of later sources.
+    fn buffer_last(&mut self) -> vec::IntoIter<::Result<Gene>> {
+        let mut all = Vec::new();
+        for (idx, (name, genes)) in self.sources.drain(..).enumerate() {
+            for result in genes {
+                all.push((idx, name.clone(), result));
+            }
+        }
+        let mut slots = Vec::with_capacity(all.len());
+        for (idx, name, result) in all.into_iter().rev() {
+            let slot = match result {
+                Ok(gx) => {
+                    self.source_idx = idx;
+                    match self.resolve(&name, gx, DuplicatePolicy::KeepFirst) {
+                        Ok(Some(gx)) => Some(Ok(gx)),
+                        Ok(None) => None,
+                        Err(e) => Some(Err(e)),
+                    }
+                },
+                Err(e) => Some(Err(e)),
+            };
+            slots.extend(slot);
+        }
+        slots.reverse();
+        slots.into_iter()
+    }
+
+    /// Tags the given gene with its source and resolves the collisions of its identifiers,
+    /// returning `None` if it is discarded.
+    fn resolve(&mut self, source: &str, mut gene: Gene, policy: DuplicatePolicy)
+        -> ::Result<Option<Gene>>
+    {
+        let source_idx = self.source_idx;
+        let collides = |ids: &HashMap<String, usize>, id: &str| {
+            ids.get(id).map_or(false, |&idx| idx != source_idx)
+        };
+
+        gene.attributes_mut().insert(self.opts.source_attr.clone(), source.to_owned());
+        if let Some(gid) = gene.id().map(|id| id.to_owned()) {
+            if collides(&self.gene_ids, &self.id_key(&gid)) {
+                match policy {
+                    DuplicatePolicy::Suffix => {
+                        let new_gid = self.renamed_id(&gid, source, &self.gene_ids);
+                        gene.set_id(Some(new_gid));
+                    },
+                    DuplicatePolicy::Error => {
+                        let err = MergeError::GeneIdCollision(gid, source.to_owned());
+                        return Err(::Error::from(err));
+                    },
+                    _ => return Ok(None),
+                }
+            }
+        }
+        let gene_key = gene.id().map(|id| self.id_key(id));
+        if let Some(key) = gene_key {
+            let _ = self.gene_ids.entry(key).or_insert(source_idx);
+        }
+
+        let had_transcripts = !gene.transcripts().is_empty();
+        let transcripts = mem::replace(gene.transcripts_mut(), LinkedHashMap::new());
+        for (tid, mut transcript) in transcripts {
+            let mut tid = tid;
+            if collides(&self.transcript_ids, &self.id_key(&tid)) {
+                match policy {
+                    DuplicatePolicy::Suffix => {
+                        tid = self.renamed_id(&tid, source, &self.transcript_ids);
+                        transcript.set_id(Some(tid.clone()));
+                    },
+                    DuplicatePolicy::Error => {
+                        let err = MergeError::TranscriptIdCollision(tid, source.to_owned());
+                        return Err(::Error::from(err));
+                    },
+                    _ => continue,
+                }
+            }
+            let key = self.id_key(&tid);
+            let _ = self.transcript_ids.entry(key).or_insert(source_idx);
+            transcript.attributes_mut()
+                .insert(self.opts.source_attr.clone(), source.to_owned());
+            let _ = gene.transcripts_mut().insert(tid, transcript);
+        }
+        if had_transcripts && gene.transcripts().is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(gene))
+    }
+
+    /// Returns an identifier for the given colliding identifier that is not yet taken.
+    fn renamed_id(&self, id: &str, source: &str, taken: &HashMap<String, usize>) -> String {
+        if self.opts.rename_with_source {
+            let candidate = format!("{}_{}", id, source);
+            if !taken.contains_key(&self.id_key(&candidate)) {
+                return candidate;
+            }
+        }
+        suffixed_id(id, |candidate: &str| taken.contains_key(&self.id_key(candidate)))
+    }
+
+    /// Returns the key of the given identifier in the maps of identifiers already yielded.
+    fn id_key(&self, id: &str) -> String {
+        if self.opts.ignore_id_versions { strip_id_version(id) } else { id.to_owned() }
+    }
+}
+
+impl<'a> Iterator for MergedGenes<'a> {
+
+    type Item = ::Result<Gene>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.opts.policy == DuplicatePolicy::KeepLast {
+            if self.buffered.is_none() {
+                self.buffered = Some(self.buffer_last());
+            }
+            return self.buffered.as_mut().and_then(|genes| genes.next());
+        }
+        loop {
+            let result = match self.sources.front_mut() {
+                Some(&mut (_, ref mut genes)) => genes.next(),
+                None => return None,
+            };
+            let gene = match result {
+                Some(Ok(gx)) => gx,
+                Some(Err(e)) => return Some(Err(e)),
+                None => {
+                    let _ = self.sources.pop_front();
+                    self.source_idx += 1;
+                    continue;
+                },
+            };
+            let source = self.sources.front().map(|&(ref name, _)| name.clone())
+                .expect("a current source");
+            let policy = self.opts.policy;
+            match self.resolve(&source, gene, policy) {
+                Ok(Some(gx)) => return Some(Ok(gx)),
+                Ok(None) => continue,
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
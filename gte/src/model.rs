@@ -1,16 +1,22 @@
 /*! Core gene, transcript, and exon models and builders.
 
 */
-use std::cmp::{max, min};
+use std::cmp::{max, min, Ordering};
+use std::fmt;
+use std::hash::{Hash, Hasher};
 use std::mem;
+use std::ops::Range;
 use std::error::Error;
+use std::slice;
+use std::str::FromStr;
+use std::vec;
 
 use bio::utils::{self as bio_utils, Interval, IntervalError};
 use bio::utils::Strand;
-use linked_hash_map::LinkedHashMap;
-use multimap::MultiMap;
+use linked_hash_map::{self, LinkedHashMap};
 
-use {Coord, RawTrxCoords, DEF_ID, INIT_COORD};
+use {Attributes, Biotype, Coord, RawTrxCoords, SeqName, DEF_ID, INIT_COORD};
+use ops::{merge_intervals, merged_coords};
 use utils::OptionDeref;
 
 use self::ExonFeatureKind::*;
@@ -27,9 +33,14 @@ macro_rules! impl_common {
                 self.seq_name.as_str()
             }
 
+            /// Returns the sequence name, which may share its allocation with other features.
+            pub fn shared_seq_name(&self) -> &SeqName {
+                &self.seq_name
+            }
+
             /// Sets the sequence name.
             pub fn set_seq_name<T>(&mut self, name: T)
-                where T: Into<String>
+                where T: Into<SeqName>
             {
                 self.seq_name = name.into()
             }
@@ -50,20 +61,97 @@ macro_rules! impl_common {
             }
 
             /// Returns a reference of the attributes.
-            pub fn attributes(&self) -> &MultiMap<String, String> {
+            pub fn attributes(&self) -> &Attributes {
                 &self.attributes
             }
 
+            /// Returns all values of the given attribute key, in order of insertion.
+            ///
+            /// Keys that appear multiple times in an input row, such as the `tag` attribute
+            /// in GENCODE GTF files, have all their values returned. An empty slice is returned
+            /// if the key is not present.
+            pub fn attributes_all(&self, key: &str) -> &[String] {
+                self.attributes.get_vec(key)
+                    .map(|values| values.as_slice())
+                    .unwrap_or(&[])
+            }
+
+            /// Returns the first value of the given attribute key, parsed into the given type.
+            ///
+            /// The value is `None` if the key is not present, while an error is returned if its
+            /// value can not be parsed, e.g. `exn.attribute_as::<u32>("exon_number")`.
+            pub fn attribute_as<T: FromStr>(&self, key: &str) -> ::Result<Option<T>> {
+                match self.attributes.get(key) {
+                    Some(value) => value.trim().parse::<T>()
+                        .map(Some)
+                        .map_err(|_| {
+                            let err = ModelError::InvalidAttribute(key.to_owned(), value.clone());
+                            ::Error::from(err)
+                        }),
+                    None => Ok(None),
+                }
+            }
+
+            /// Returns the first value of the given attribute key, parsed as an integer.
+            pub fn attribute_int(&self, key: &str) -> ::Result<Option<i64>> {
+                self.attribute_as::<i64>(key)
+            }
+
+            /// Returns the first value of the given attribute key, parsed as a float.
+            pub fn attribute_float(&self, key: &str) -> ::Result<Option<f64>> {
+                self.attribute_as::<f64>(key)
+            }
+
+            /// Returns the first value of the given attribute key, parsed as a boolean.
+            ///
+            /// The values `true`, `yes`, and `1` are parsed as `true`, and `false`, `no`, and
+            /// `0` as `false`, regardless of their cases.
+            pub fn attribute_bool(&self, key: &str) -> ::Result<Option<bool>> {
+                match self.attributes.get(key) {
+                    Some(value) => match value.trim().to_lowercase().as_str() {
+                        "true" | "yes" | "1" => Ok(Some(true)),
+                        "false" | "no" | "0" => Ok(Some(false)),
+                        _ => {
+                            let err = ModelError::InvalidAttribute(key.to_owned(), value.clone());
+                            Err(::Error::from(err))
+                        },
+                    },
+                    None => Ok(None),
+                }
+            }
+
             /// Returns a mutable reference of the attributes.
-            pub fn attributes_mut(&mut self) -> &mut MultiMap<String, String> {
+            pub fn attributes_mut(&mut self) -> &mut Attributes {
                 &mut self.attributes
             }
 
             /// Sets the attribute and returns the old value.
-            pub fn set_attributes(&mut self, attributes: MultiMap<String, String>)
-                -> MultiMap<String, String>
+            pub fn set_attributes<A>(&mut self, attributes: A) -> Attributes
+                where A: Into<Attributes>
             {
-                mem::replace(&mut self.attributes, attributes)
+                mem::replace(&mut self.attributes, attributes.into())
+            }
+
+            /// Returns the source, such as the program or database that created the feature.
+            pub fn source(&self) -> Option<&str> {
+                self.source.as_deref()
+            }
+
+            /// Sets the source.
+            pub fn set_source<T>(&mut self, source: Option<T>)
+                where T: Into<String>
+            {
+                self.source = source.map(|v| v.into())
+            }
+
+            /// Returns the score.
+            pub fn score(&self) -> Option<f64> {
+                self.score
+            }
+
+            /// Sets the score.
+            pub fn set_score(&mut self, score: Option<f64>) {
+                self.score = score
             }
 
             /// Returns a reference of the interval.
@@ -88,22 +176,98 @@ macro_rules! impl_common {
             }
         }
 
+        /// Models are equal if they have the same sequence names, coordinates, strands, and
+        /// identifiers, regardless of their other values.
+        impl PartialEq for $struct_ty {
+            fn eq(&self, other: &Self) -> bool {
+                self.seq_name == other.seq_name && self.interval.start == other.interval.start
+                    && self.interval.end == other.interval.end
+                    && strand_ord(&self.strand) == strand_ord(&other.strand)
+                    && self.id == other.id
+            }
+        }
+
+        impl Eq for $struct_ty {}
+
+        impl Hash for $struct_ty {
+            fn hash<H: Hasher>(&self, state: &mut H) {
+                self.seq_name.hash(state);
+                self.interval.start.hash(state);
+                self.interval.end.hash(state);
+                strand_ord(&self.strand).hash(state);
+                self.id.hash(state);
+            }
+        }
+
     );
 }
 
+/// Helper function for getting a hashable value of the given strand.
+///
+/// Unlike the strands themselves, the values of unknown strands are equal to each other.
+pub(crate) fn strand_ord(strand: &Strand) -> u8 {
+    match *strand {
+        Strand::Unknown => 0,
+        Strand::Forward => 1,
+        Strand::Reverse => 2,
+    }
+}
+
+/// Type used for storing feature coordinates.
+///
+/// Exon features make up most of the coordinates of a genome annotation, so with the
+/// `compact_coords` feature, their coordinates are stored as `u32` values to halve their memory
+/// usage. This is enough for sequences of up to 4 Gbp, such as all human chromosomes.
+#[cfg(feature = "compact_coords")]
+type FeaturePos = u32;
+
+/// Type used for storing feature coordinates.
+#[cfg(not(feature = "compact_coords"))]
+type FeaturePos = u64;
+
+/// Helper function for converting a coordinate into its stored feature coordinate.
+///
+/// Panics if the coordinate does not fit in the stored type.
+#[cfg(feature = "compact_coords")]
+fn to_feature_pos(value: u64) -> FeaturePos {
+    assert!(value <= u64::from(::std::u32::MAX),
+            "feature coordinate {} does not fit in compact coordinates", value);
+    value as FeaturePos
+}
+
+/// Helper function for converting a coordinate into its stored feature coordinate.
+#[cfg(not(feature = "compact_coords"))]
+fn to_feature_pos(value: u64) -> FeaturePos {
+    value
+}
+
 /// Genomic feature spanning an interval.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde_impl", derive(Serialize, Deserialize))]
 pub struct Feature<K: FeatureKind> {
-    interval: Interval<u64>,
+    #[cfg_attr(feature = "serde_impl", serde(rename = "interval"))]
+    coord: Coord<FeaturePos>,
     kind: K,
 }
 
+impl<K: FeatureKind + Eq> Eq for Feature<K> {}
+
+impl<K: FeatureKind + Hash> Hash for Feature<K> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.coord.hash(state);
+        self.kind.hash(state);
+    }
+}
+
 impl<K: FeatureKind> Feature<K> {
 
     /// Creates a new feature.
+    ///
+    /// With the `compact_coords` feature, this panics if the interval end is larger than
+    /// `u32::MAX`.
     pub fn new(interval: Interval<u64>, kind: K) -> Self {
         Feature {
-            interval: interval,
+            coord: (to_feature_pos(interval.start), to_feature_pos(interval.end)),
             kind: kind,
         }
     }
@@ -113,19 +277,29 @@ impl<K: FeatureKind> Feature<K> {
         &self.kind
     }
 
-    /// Returns a reference of the feature interval.
-    pub fn interval(&self) -> &Interval<u64> {
-        &self.interval
+    /// Sets the feature kind.
+    pub fn set_kind(&mut self, kind: K) {
+        self.kind = kind
+    }
+
+    /// Returns the feature interval.
+    pub fn interval(&self) -> Interval<u64> {
+        Interval::new(self.start()..self.end()).unwrap()
+    }
+
+    /// Sets the feature interval.
+    fn set_interval(&mut self, interval: Interval<u64>) {
+        self.coord = (to_feature_pos(interval.start), to_feature_pos(interval.end));
     }
 
     /// Returns the genome-wise 5'-most coordinate of the feature.
     pub fn start(&self) -> u64 {
-        self.interval.start
+        u64::from(self.coord.0)
     }
 
     /// Returns the genome-wise 3'-most coordinate of the feature.
     pub fn end(&self) -> u64 {
-        self.interval.end
+        u64::from(self.coord.1)
     }
 
     /// Returns the number of bases spanned by the feature interval.
@@ -139,7 +313,8 @@ impl<K: FeatureKind> Feature<K> {
 pub trait FeatureKind {}
 
 /// Possible feature kinds for exons.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde_impl", derive(Serialize, Deserialize))]
 pub enum ExonFeatureKind {
     /// UTR on unknown strands.
     UTR,
@@ -177,14 +352,31 @@ pub enum ExonFeatureKind {
 
 impl FeatureKind for ExonFeatureKind {}
 
+impl ExonFeatureKind {
+
+    /// Returns the frame of CDS, start codon, and stop codon feature kinds.
+    ///
+    /// The frame is the same value as the phase of GFF3 CDS rows. The value is `None` for other
+    /// feature kinds, and for coding feature kinds whose frames are unknown.
+    pub fn frame(&self) -> Option<u8> {
+        match *self {
+            CDS { frame } | StartCodon { frame } | StopCodon { frame } => frame,
+            _ => None,
+        }
+    }
+}
+
 /// Type alias for exon features.
 pub type ExonFeature = Feature<ExonFeatureKind>;
 
 /// Possible feature kinds for transcripts.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde_impl", derive(Serialize, Deserialize))]
 pub enum TranscriptFeatureKind {
     /// An intron.
     Intron,
+    /// A promoter region around the transcription start site.
+    Promoter,
     /// Other features that may exist within transcripts.
     Any(String),
 }
@@ -194,27 +386,300 @@ impl FeatureKind for TranscriptFeatureKind {}
 /// Type alias for transcript features.
 pub type TranscriptFeature = Feature<TranscriptFeatureKind>;
 
-/// Feature kind for genes.
-#[derive(Debug, Clone, PartialEq)]
-pub struct GeneFeatureKind(String);
+/// Possible feature kinds for genes.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde_impl", derive(Serialize, Deserialize))]
+pub enum GeneFeatureKind {
+    /// Flanking region upstream of the gene.
+    UpstreamFlank,
+    /// Flanking region downstream of the gene.
+    DownstreamFlank,
+    /// Region between genes.
+    Intergenic,
+    /// Other features that may be associated with genes.
+    Any(String),
+}
 
 impl FeatureKind for GeneFeatureKind {}
 
 /// Type alias for gene features.
 pub type GeneFeature = Feature<GeneFeatureKind>;
 
+/// Sides of a gene, relative to its strand.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde_impl", derive(Serialize, Deserialize))]
+pub enum FlankSide {
+    /// The side of the gene's 5' end.
+    Upstream,
+    /// The side of the gene's 3' end.
+    Downstream,
+}
+
+/// Policies for handling features that share the same identifier.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DuplicatePolicy {
+    /// Yields an error.
+    Error,
+    /// Keeps the first feature and discards the later ones.
+    KeepFirst,
+    /// Keeps the last feature and discards the earlier ones.
+    KeepLast,
+    /// Keeps all features, adding a numeric suffix (`_2`, `_3`, ...) to the identifiers of the
+    /// later ones.
+    Suffix,
+}
+
+impl Default for DuplicatePolicy {
+    fn default() -> Self {
+        DuplicatePolicy::Error
+    }
+}
+
+/// Policies for handling features whose strands are not specified.
+///
+/// For builders, a strand is unspecified when neither the `strand` nor the `strand_char`
+/// method is invoked. For readers, a strand is unspecified when its column is `.`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StrandPolicy {
+    /// Yields an error.
+    Require,
+    /// Uses `Strand::Unknown`.
+    DefaultUnknown,
+    /// Uses `Strand::Forward`.
+    DefaultForward,
+}
+
+/// Policies for handling genes whose transcripts lie on different strands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StrandConflictPolicy {
+    /// Yields an error.
+    Error,
+    /// Sets the strand of the gene to `Strand::Unknown`, keeping the strands of the transcripts.
+    Unknown,
+    /// Splits the gene into one gene per transcript strand.
+    Split,
+}
+
+impl Default for StrandConflictPolicy {
+    fn default() -> Self {
+        StrandConflictPolicy::Error
+    }
+}
+
+impl Default for StrandPolicy {
+    fn default() -> Self {
+        StrandPolicy::Require
+    }
+}
+
+/// Adjustment of gene bounds made when transcripts extend past their declared gene interval.
+///
+/// These are reported by `GBuilder::build_with_warnings` when automatic bound extension is
+/// enabled.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BoundsWarning {
+    /// Identifier of the gene.
+    pub gene_id: Option<String>,
+    /// Identifiers of the transcripts that extend past the declared gene interval.
+    pub transcript_ids: Vec<String>,
+    /// Declared start and end coordinates of the gene.
+    pub declared: (u64, u64),
+    /// Adjusted start and end coordinates of the gene.
+    pub adjusted: (u64, u64),
+}
+
+impl fmt::Display for BoundsWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "gene bounds extended from {}-{} to {}-{}, gene ID: {}, transcript IDs: {}",
+               self.declared.0, self.declared.1, self.adjusted.0, self.adjusted.1,
+               self.gene_id.as_deref().unwrap_or(DEF_ID), self.transcript_ids.join(","))
+    }
+}
+
+impl StrandPolicy {
+
+    /// Returns the strand to use in place of an unspecified strand.
+    pub(crate) fn default_strand(&self) -> Result<Strand, ModelError> {
+        match *self {
+            StrandPolicy::Require => Err(ModelError::UnspecifiedStrand),
+            StrandPolicy::DefaultUnknown => Ok(Strand::Unknown),
+            StrandPolicy::DefaultForward => Ok(Strand::Forward),
+        }
+    }
+}
+
+/// Helper function for adding a transcript to the transcripts of a gene, following the given
+/// policy if its identifier is already present.
+///
+/// Returns `false` if the identifier is already present and the policy is `Error`.
+pub(crate) fn insert_transcript(
+    transcripts: &mut LinkedHashMap<String, Transcript>,
+    tid: String,
+    mut transcript: Transcript,
+    policy: DuplicatePolicy,
+) -> bool {
+    if !transcripts.contains_key(&tid) {
+        let _ = transcripts.insert(tid, transcript);
+        return true;
+    }
+    match policy {
+        DuplicatePolicy::Error => false,
+        DuplicatePolicy::KeepFirst => true,
+        DuplicatePolicy::KeepLast => {
+            let _ = transcripts.remove(&tid);
+            let _ = transcripts.insert(tid, transcript);
+            true
+        },
+        DuplicatePolicy::Suffix => {
+            let new_tid = suffixed_id(&tid, |id: &str| transcripts.contains_key(id));
+            transcript.set_id(Some(new_tid.clone()));
+            let _ = transcripts.insert(new_tid, transcript);
+            true
+        },
+    }
+}
+
+/// Returns the given identifier without its version suffix, such as `ENST00000367770` for
+/// `ENST00000367770.8`.
+///
+/// The suffix consists of a dot followed by digits at the end of the identifier, or before a
+/// GENCODE `_PAR_Y` suffix, which is kept (e.g. `ENSG00000182378_PAR_Y` for
+/// `ENSG00000182378.14_PAR_Y`). Identifiers without a version suffix are returned as they are.
+pub fn strip_id_version(id: &str) -> String {
+    let (base, par_suffix) = if id.ends_with(PAR_Y_SUFFIX) {
+        (&id[..id.len() - PAR_Y_SUFFIX.len()], PAR_Y_SUFFIX)
+    } else {
+        (id, "")
+    };
+    match base.rfind('.') {
+        Some(idx) if idx > 0 && idx + 1 < base.len()
+                && base[idx + 1..].chars().all(|c| c.is_digit(10)) =>
+            format!("{}{}", &base[..idx], par_suffix),
+        _ => id.to_owned(),
+    }
+}
+
+/// Helper function for creating the first suffixed identifier not yet taken.
+pub(crate) fn suffixed_id<F>(id: &str, mut taken: F) -> String
+    where F: FnMut(&str) -> bool
+{
+    let mut num = 2;
+    loop {
+        let candidate = format!("{}_{}", id, num);
+        if !taken(&candidate) {
+            return candidate;
+        }
+        num += 1;
+    }
+}
+
+/// Helper function to rank transcripts when choosing the canonical transcript of a gene, with
+/// higher ranks being preferred.
+fn canonical_rank(trx: &Transcript) -> (bool, bool, bool, bool, u64) {
+    let spliced_len: u64 = trx.exons().iter().map(|exn| exn.end() - exn.start()).sum();
+    (trx.is_mane_select(), trx.is_ensembl_canonical(), trx.is_mane_plus_clinical(),
+     trx.coding_coord(true).is_some(), spliced_len)
+}
+
+/// Attribute keys of gene biotypes, in order of precedence.
+const GENE_BIOTYPE_KEYS: &'static [&'static str] = &["gene_biotype", "gene_type"];
+
+/// Attribute keys of transcript biotypes, in order of precedence.
+const TRANSCRIPT_BIOTYPE_KEYS: &'static [&'static str] = &["transcript_biotype", "transcript_type"];
+
+/// Attribute key of transcript support levels.
+const TSL_KEY: &'static str = "transcript_support_level";
+
+/// Attribute key of GENCODE annotation levels.
+const LEVEL_KEY: &'static str = "level";
+
+/// Attribute key of tags.
+const TAG_KEY: &'static str = "tag";
+
+/// Attribute key of CCDS identifiers.
+const CCDS_ID_KEY: &'static str = "ccds_id";
+
+/// Attribute keys of database cross-references, in GFF3 and GTF files respectively.
+const XREF_KEYS: &'static [&'static str] = &["Dbxref", "db_xref"];
+
+/// Attribute keys of gene names, in order of preference.
+const GENE_NAME_KEYS: &'static [&'static str] = &["gene_name", "Name", "gene"];
+
+/// Attribute keys of the names of the genes of transcripts, in order of preference.
+const TRANSCRIPT_GENE_NAME_KEYS: &'static [&'static str] = &["gene_name", "gene"];
+
+/// Tag of MANE Select transcripts.
+const MANE_SELECT_TAG: &'static str = "MANE_Select";
+
+/// Tag of MANE Plus Clinical transcripts.
+const MANE_PLUS_CLINICAL_TAG: &'static str = "MANE_Plus_Clinical";
+
+/// Tag of Ensembl canonical transcripts.
+const ENSEMBL_CANONICAL_TAG: &'static str = "Ensembl_canonical";
+
+/// Suffix of the identifiers of GENCODE genes and transcripts in the pseudoautosomal regions of
+/// chromosome Y.
+pub(crate) const PAR_Y_SUFFIX: &'static str = "_PAR_Y";
+
+/// Tags of transcripts whose stop codons are read through.
+const READTHROUGH_TAGS: &'static [&'static str] = &["seleno", "stop_codon_readthrough"];
+
+/// Helper function for checking whether the given tag marks stop codon readthrough.
+pub(crate) fn is_readthrough_tag(tag: &str) -> bool {
+    READTHROUGH_TAGS.contains(&tag)
+}
+
+/// Helper function for parsing the number at the start of an attribute value.
+fn parse_leading_num(value: &str) -> Option<u8> {
+    value.split(|c: char| !c.is_digit(10)).next()
+        .and_then(|digits| digits.parse::<u8>().ok())
+}
+
+/// Helper function for parsing the database cross-references of the given attributes.
+///
+/// Each value is split into its database and accession at its first colon, so that values
+/// such as `HGNC:HGNC:5` are parsed as the `HGNC` database with the `HGNC:5` accession. Values
+/// without a database or an accession are skipped.
+fn find_xrefs<'a>(attributes: &'a Attributes) -> Vec<(&'a str, &'a str)> {
+    XREF_KEYS.iter()
+        .flat_map(|key| attributes.get_vec(*key).map(|values| values.as_slice()).unwrap_or(&[]))
+        .filter_map(|value| {
+            let mut parts = value.trim().splitn(2, ':');
+            match (parts.next(), parts.next()) {
+                (Some(db), Some(acc)) if !db.is_empty() && !acc.is_empty() => Some((db, acc)),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// Helper function for getting the first value of the first present attribute key.
+fn find_attribute<'a>(attributes: &'a Attributes, keys: &[&str]) -> Option<&'a str> {
+    keys.iter()
+        .filter_map(|key| attributes.get(*key))
+        .map(|value| value.as_str())
+        .next()
+}
+
 /// The exon model.
 ///
 /// To create an exon, an `EBuilder` needs to be used.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde_impl", derive(Serialize, Deserialize))]
 pub struct Exon {
-    seq_name: String,
+    seq_name: SeqName,
+    #[cfg_attr(feature = "serde_impl", serde(with = "::utils::serde_interval"))]
     interval: Interval<u64>,
+    #[cfg_attr(feature = "serde_impl", serde(with = "::utils::serde_strand"))]
     strand: Strand,
     id: Option<String>,
     gene_id: Option<String>,
     transcript_id: Option<String>,
-    attributes: MultiMap<String, String>,
+    attributes: Attributes,
+    #[cfg_attr(feature = "serde_impl", serde(default))]
+    source: Option<String>,
+    #[cfg_attr(feature = "serde_impl", serde(default))]
+    score: Option<f64>,
     features: Vec<ExonFeature>,
 }
 
@@ -253,6 +718,11 @@ impl Exon {
         self.gene_id = gene_id.map(|v| v.into())
     }
 
+    /// Parses the attributes of the exon, if they were kept unparsed by a reader.
+    pub fn parse_attributes(&mut self) {
+        self.attributes.parse()
+    }
+
     /// Returns a slice of the features within the exon.
     pub fn features(&self) -> &[ExonFeature] {
         self.features.as_slice()
@@ -263,6 +733,11 @@ impl Exon {
         self.features.as_mut_slice()
     }
 
+    /// Returns an iterator over the features within the exon.
+    pub fn iter(&self) -> slice::Iter<ExonFeature> {
+        self.features.iter()
+    }
+
     /// Sets the exon features and return the old value.
     ///
     /// If the given features is nonempty, the exon interval will also be updated with the
@@ -282,6 +757,18 @@ impl Exon {
             Ok(mem::replace(&mut self.features, features))
         }
     }
+
+    /// Moves the exon and its features to the given sequence, shifting their coordinates by
+    /// the given offset.
+    pub(crate) fn relocate(&mut self, seq_name: &str, offset: i64) -> Result<(), ModelError> {
+        self.interval = shift_interval(&self.interval, offset, &self.seq_name)?;
+        for fx in self.features.iter_mut() {
+            let interval = shift_interval(&fx.interval(), offset, &self.seq_name)?;
+            fx.set_interval(interval);
+        }
+        self.seq_name = seq_name.into();
+        Ok(())
+    }
 }
 
 /// Builder for exons.
@@ -290,15 +777,18 @@ impl Exon {
 /// via its `build` function.
 #[derive(Debug)]
 pub struct EBuilder {
-    seq_name: String,
+    seq_name: SeqName,
     start: u64,
     end: u64,
     strand: Option<Strand>,
     strand_char: Option<char>,
+    strand_policy: StrandPolicy,
     id: Option<String>,
     transcript_id: Option<String>,
     gene_id: Option<String>,
-    attributes: MultiMap<String, String>,
+    attributes: Attributes,
+    source: Option<String>,
+    score: Option<f64>,
     features: Vec<ExonFeature>,
 }
 
@@ -306,7 +796,7 @@ impl EBuilder {
 
     /// Creates a new builder for an exon with the given values.
     pub fn new<T>(seq_name: T, start: u64, end: u64) -> Self
-        where T: Into<String>
+        where T: Into<SeqName>
     {
         EBuilder {
             seq_name: seq_name.into(),
@@ -314,10 +804,13 @@ impl EBuilder {
             end: end,
             strand: None,
             strand_char: None,
+            strand_policy: StrandPolicy::default(),
             id: None,
             transcript_id: None,
             gene_id: None,
-            attributes: MultiMap::new(),
+            attributes: Attributes::new(),
+            source: None,
+            score: None,
             features: Vec::new(),
         }
     }
@@ -345,6 +838,14 @@ impl EBuilder {
         self
     }
 
+    /// Sets the policy for when neither the `strand` nor the `strand_char` method is invoked.
+    ///
+    /// By default, the `build` method returns an error in this case.
+    pub fn strand_policy(mut self, policy: StrandPolicy) -> Self {
+        self.strand_policy = policy;
+        self
+    }
+
     /// Sets the identifier of the exon.
     pub fn id<T>(mut self, id: T) -> Self
         where T: Into<String>
@@ -378,8 +879,24 @@ impl EBuilder {
     }
 
     /// Sets the entire attribute of the exon.
-    pub fn attributes(mut self, attributes: MultiMap<String, String>) -> Self {
-        self.attributes = attributes;
+    pub fn attributes<A>(mut self, attributes: A) -> Self
+        where A: Into<Attributes>
+    {
+        self.attributes = attributes.into();
+        self
+    }
+
+    /// Sets the source of the exon.
+    pub fn source<T>(mut self, source: T) -> Self
+        where T: Into<String>
+    {
+        self.source = Some(source.into());
+        self
+    }
+
+    /// Sets the score of the exon.
+    pub fn score(mut self, score: f64) -> Self {
+        self.score = Some(score);
         self
     }
 
@@ -399,7 +916,7 @@ impl EBuilder {
     pub fn build(self) -> ::Result<Exon> {
         let interval = coord_to_interval(self.start, self.end)
             .map_err(::Error::Model)?;
-        let strand = resolve_strand_input(self.strand, self.strand_char)
+        let strand = resolve_strand_input(self.strand, self.strand_char, self.strand_policy)
             .map_err(::Error::Model)?;
         let feature = Exon {
             seq_name: self.seq_name,
@@ -409,6 +926,8 @@ impl EBuilder {
             transcript_id: self.transcript_id,
             gene_id: self.gene_id,
             attributes: self.attributes,
+            source: self.source,
+            score: self.score,
             features: self.features,
         };
         Ok(feature)
@@ -419,14 +938,27 @@ impl EBuilder {
 ///
 /// To create a transcript, a `TBuilder` needs to be used.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde_impl", derive(Serialize, Deserialize))]
 pub struct Transcript {
-    seq_name: String,
+    seq_name: SeqName,
+    #[cfg_attr(feature = "serde_impl", serde(with = "::utils::serde_interval"))]
     interval: Interval<u64>,
+    #[cfg_attr(feature = "serde_impl", serde(with = "::utils::serde_strand"))]
     strand: Strand,
     id: Option<String>,
     gene_id: Option<String>,
-    attributes: MultiMap<String, String>,
+    attributes: Attributes,
+    #[cfg_attr(feature = "serde_impl", serde(default))]
+    source: Option<String>,
+    #[cfg_attr(feature = "serde_impl", serde(default))]
+    score: Option<f64>,
     exons: Vec<Exon>,
+    #[cfg_attr(feature = "serde_impl", serde(default))]
+    cds_5p_incomplete: bool,
+    #[cfg_attr(feature = "serde_impl", serde(default))]
+    cds_3p_incomplete: bool,
+    #[cfg_attr(feature = "serde_impl", serde(default))]
+    stop_readthrough: bool,
 }
 
 impl_common!(Transcript);
@@ -460,16 +992,352 @@ impl Transcript {
         self.gene_id = gene_id.map(|v| v.into())
     }
 
+    /// Strips the version suffixes from the transcript and gene identifiers of the transcript
+    /// and its exons, as in `strip_id_version`.
+    pub fn strip_id_versions(&mut self) {
+        let tid = self.id.as_ref().map(|id| strip_id_version(id));
+        let gid = self.gene_id.as_ref().map(|id| strip_id_version(id));
+        self.set_id(tid);
+        self.set_gene_id(gid);
+    }
+
+    /// Parses the attributes of the transcript and its exons, if they were kept unparsed by a
+    /// reader.
+    pub fn parse_attributes(&mut self) {
+        self.attributes.parse();
+        for exon in self.exons.iter_mut() {
+            exon.parse_attributes();
+        }
+    }
+
     /// Returns a slice of the transcript's exons.
     pub fn exons(&self) -> &[Exon] {
         self.exons.as_slice()
     }
 
+    /// Returns a mutable slice of the transcript's exons.
+    pub fn exons_mut(&mut self) -> &mut [Exon] {
+        self.exons.as_mut_slice()
+    }
+
+    /// Returns an iterator over the transcript's exons, in ascending coordinate order.
+    pub fn iter(&self) -> slice::Iter<Exon> {
+        self.exons.iter()
+    }
+
+    /// Returns the biotype of the transcript.
+    ///
+    /// The biotype is taken from the `transcript_biotype` (Ensembl) or `transcript_type`
+    /// (GENCODE) attribute, whichever is present first.
+    pub fn biotype(&self) -> Option<Biotype> {
+        find_attribute(&self.attributes, TRANSCRIPT_BIOTYPE_KEYS).map(Biotype::from)
+    }
+
+    /// Returns the transcript support level, from 1 (best supported) to 5.
+    ///
+    /// The level is parsed from the `transcript_support_level` attribute, which may be followed
+    /// by a note as in Ensembl GTF files, e.g. `1 (assigned to previous version 3)`. The value
+    /// is `None` if the attribute is missing or is `NA`.
+    pub fn support_level(&self) -> Option<u8> {
+        self.attributes.get(TSL_KEY).and_then(|value| parse_leading_num(value))
+    }
+
+    /// Returns the GENCODE annotation level: 1 (verified), 2 (manually annotated), or 3
+    /// (automatically annotated).
+    ///
+    /// The level is parsed from the `level` attribute.
+    pub fn level(&self) -> Option<u8> {
+        self.attributes.get(LEVEL_KEY).and_then(|value| parse_leading_num(value))
+    }
+
+    /// Returns all tags of the transcript, such as `basic` or `CCDS`, taken from its `tag`
+    /// attributes.
+    pub fn tags(&self) -> &[String] {
+        self.attributes_all(TAG_KEY)
+    }
+
+    /// Returns whether the transcript has the given tag.
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags().iter().any(|value| value == tag)
+    }
+
+    /// Returns whether the transcript is tagged as MANE Select, the single representative
+    /// transcript of a gene agreed upon by Ensembl and RefSeq.
+    pub fn is_mane_select(&self) -> bool {
+        self.has_tag(MANE_SELECT_TAG)
+    }
+
+    /// Returns whether the transcript is tagged as MANE Plus Clinical, an additional transcript
+    /// needed to report clinically relevant variants.
+    pub fn is_mane_plus_clinical(&self) -> bool {
+        self.has_tag(MANE_PLUS_CLINICAL_TAG)
+    }
+
+    /// Returns whether the transcript is tagged as the Ensembl canonical transcript of its gene.
+    pub fn is_ensembl_canonical(&self) -> bool {
+        self.has_tag(ENSEMBL_CANONICAL_TAG)
+    }
+
+    /// Returns the CCDS identifier of the transcript, taken from its `ccds_id` attribute.
+    pub fn ccds_id(&self) -> Option<&str> {
+        self.attributes.get(CCDS_ID_KEY).map(|value| value.as_str())
+    }
+
+    /// Returns the database cross-references of the transcript as database and accession
+    /// pairs, taken from its `Dbxref` (GFF3) or `db_xref` (GTF) attributes.
+    pub fn xrefs(&self) -> Vec<(&str, &str)> {
+        find_xrefs(&self.attributes)
+    }
+
+    /// Returns the accession of the first cross-reference to the given database.
+    pub fn xref(&self, database: &str) -> Option<&str> {
+        self.xrefs().into_iter().find(|&(db, _)| db == database).map(|(_, acc)| acc)
+    }
+
+    /// Returns whether the coding region is incomplete at its transcript-wise 5' end, so that
+    /// it has no start codon.
+    pub fn cds_5p_incomplete(&self) -> bool {
+        self.cds_5p_incomplete
+    }
+
+    /// Returns whether the coding region is incomplete at its transcript-wise 3' end, so that
+    /// it has no stop codon.
+    pub fn cds_3p_incomplete(&self) -> bool {
+        self.cds_3p_incomplete
+    }
+
+    /// Returns whether stop codons in the coding region are read through, as in selenoproteins
+    /// where internal `UGA` codons encode selenocysteine.
+    ///
+    /// The flag is set from the `seleno` and `stop_codon_readthrough` tags of GENCODE GTF
+    /// files, or explicitly with `set_stop_readthrough`.
+    pub fn stop_readthrough(&self) -> bool {
+        self.stop_readthrough
+    }
+
+    /// Sets whether stop codons in the coding region are read through.
+    pub fn set_stop_readthrough(&mut self, stop_readthrough: bool) {
+        self.stop_readthrough = stop_readthrough;
+    }
+
     /// Consumes the transcript and returns its exons.
     pub fn take_exons(self) -> Vec<Exon> {
         self.exons
     }
 
+    /// Moves the transcript and its exons to the given sequence, shifting their coordinates by
+    /// the given offset.
+    pub(crate) fn relocate(&mut self, seq_name: &str, offset: i64) -> Result<(), ModelError> {
+        self.interval = shift_interval(&self.interval, offset, &self.seq_name)?;
+        for exon in self.exons.iter_mut() {
+            exon.relocate(seq_name, offset)?;
+        }
+        self.seq_name = seq_name.into();
+        Ok(())
+    }
+
+    /// Returns an iterator over the exons of the transcript, from the transcript-wise 5' to
+    /// 3' end.
+    ///
+    /// On reverse strand transcripts the genome-wise 3'-most exon comes first. Transcripts with
+    /// unknown strands are treated as forward-stranded.
+    pub fn exons_stranded(&self) -> ExonsStranded {
+        ExonsStranded {
+            inner: self.exons.iter(),
+            reverse: self.strand == Strand::Reverse,
+        }
+    }
+
+    /// Returns the transcript-wise 5'-most exon.
+    pub fn first_exon(&self) -> Option<&Exon> {
+        self.exons_stranded().next()
+    }
+
+    /// Returns the transcript-wise 3'-most exon.
+    pub fn last_exon(&self) -> Option<&Exon> {
+        self.exons_stranded().next_back()
+    }
+
+    /// Returns the exon with the given one-based number.
+    ///
+    /// Exons are numbered from the transcript-wise 5' end, as in the `exon_number` attribute of
+    /// GTF files, so exon 1 is the genome-wise 3'-most exon on reverse strand transcripts. The
+    /// value is `None` if the number is zero or larger than the number of exons.
+    pub fn exon_number(&self, number: usize) -> Option<&Exon> {
+        if number == 0 {
+            return None;
+        }
+        self.exons_stranded().nth(number - 1)
+    }
+
+    /// Returns the exon that contains the given genomic coordinate.
+    ///
+    /// Exons are looked up with a binary search over their genome-wise sorted coordinates.
+    /// The value is `None` if the coordinate lies outside of all exons.
+    pub fn exon_at(&self, pos: u64) -> Option<&Exon> {
+        let idx = self.exons
+            .binary_search_by(|exon| {
+                if exon.end() <= pos {
+                    Ordering::Less
+                } else if exon.start() > pos {
+                    Ordering::Greater
+                } else {
+                    Ordering::Equal
+                }
+            });
+        idx.ok().map(|idx| &self.exons[idx])
+    }
+
+    /// Returns the genome-wise sorted exons that overlap the given genomic range.
+    ///
+    /// As with `exon_at`, the exons are found with binary searches, so that the slice is
+    /// returned in logarithmic time. The slice is empty if no exon overlaps the range.
+    pub fn exons_in(&self, range: Range<u64>) -> &[Exon] {
+        if range.start >= range.end {
+            return &[];
+        }
+        let first = self.exons
+            .binary_search_by(|exon| {
+                if exon.end() <= range.start { Ordering::Less } else { Ordering::Greater }
+            })
+            .unwrap_or_else(|idx| idx);
+        let last = self.exons
+            .binary_search_by(|exon| {
+                if exon.start() < range.end { Ordering::Less } else { Ordering::Greater }
+            })
+            .unwrap_or_else(|idx| idx);
+        if first < last { &self.exons[first..last] } else { &[] }
+    }
+
+    /// Returns an iterator over the introns of the transcript.
+    ///
+    /// Introns are the gaps between consecutive exons, yielded as transcript features of the
+    /// `Intron` kind. They are ordered from the transcript-wise 5' to 3' end, so on reverse
+    /// strand transcripts the genome-wise 3'-most intron comes first. Adjacent exons with no
+    /// gap between them do not yield any intron, and single-exon transcripts yield none at all.
+    pub fn introns(&self) -> Introns {
+        Introns {
+            exons: self.exons.as_slice(),
+            reverse: self.strand == Strand::Reverse,
+            front: 0,
+            back: self.exons.len().saturating_sub(1),
+        }
+    }
+
+    /// Returns the promoter region of the transcript.
+    ///
+    /// The promoter spans the given number of bases upstream and downstream of the transcription
+    /// start site, which is the transcript-wise 5'-most base. The region is clipped at zero when
+    /// it would extend past the start of the sequence. Transcripts with unknown strands are
+    /// treated as forward-stranded.
+    pub fn promoter(&self, upstream: u64, downstream: u64) -> TranscriptFeature {
+        let (start, end) = match self.strand {
+            Strand::Reverse =>
+                (self.end().saturating_sub(downstream), self.end().saturating_add(upstream)),
+            _ =>
+                (self.start().saturating_sub(upstream), self.start().saturating_add(downstream)),
+        };
+        Feature::new(Interval::new(start..end).unwrap(), TranscriptFeatureKind::Promoter)
+    }
+
+    /// Returns the genome-wise sorted union of the exons of the transcript.
+    ///
+    /// Overlapping or adjacent exons are merged into one interval.
+    pub fn exonic_intervals(&self) -> Vec<Interval<u64>> {
+        merge_intervals(self.exons.iter().map(|exn| exn.interval().clone()))
+    }
+
+    /// Returns a copy of the transcript clipped to the given genomic window.
+    ///
+    /// Exons outside the window are dropped, while exons partially inside it are trimmed along
+    /// with their features. The frames of coding features trimmed at their transcript-wise 5'
+    /// ends are adjusted so that they still mark the same codon boundaries. Start and stop
+    /// codons that are no longer complete are dropped. `None` is returned if no exon overlaps
+    /// the window.
+    pub fn clip(&self, start: u64, end: u64) -> Option<Transcript> {
+        let reverse = self.strand == Strand::Reverse;
+        let exons = self.exons.iter()
+            .filter_map(|exn| clip_exon(exn, start, end, reverse))
+            .collect::<Vec<Exon>>();
+        let trx_start = exons.iter().map(|exn| exn.start()).min();
+        let trx_end = exons.iter().map(|exn| exn.end()).max();
+        match (trx_start, trx_end) {
+            (Some(trx_start), Some(trx_end)) => Some(Transcript {
+                seq_name: self.seq_name.clone(),
+                interval: Interval::new(trx_start..trx_end).unwrap(),
+                strand: self.strand,
+                id: self.id.clone(),
+                gene_id: self.gene_id.clone(),
+                attributes: self.attributes.clone(),
+                source: self.source.clone(),
+                score: self.score,
+                exons: exons,
+                cds_5p_incomplete: self.cds_5p_incomplete,
+                cds_3p_incomplete: self.cds_3p_incomplete,
+                stop_readthrough: self.stop_readthrough,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Returns the intervals of the 5'UTR, in the transcript direction.
+    ///
+    /// The intervals are taken from the `UTR5` features of the exons. Features of the
+    /// unstranded `UTR` kind, such as those read from GENCODE GTF files, are included when they
+    /// lie upstream of the coding region. Transcripts with unknown strands have no 5'UTR.
+    pub fn utr5(&self) -> Vec<Interval<u64>> {
+        self.utr_intervals(true)
+    }
+
+    /// Returns the intervals of the 3'UTR, in the transcript direction.
+    ///
+    /// As with `utr5`, `UTR` features are included when they lie downstream of the coding
+    /// region.
+    pub fn utr3(&self) -> Vec<Interval<u64>> {
+        self.utr_intervals(false)
+    }
+
+    /// Returns the total length of the 5'UTR.
+    pub fn utr5_len(&self) -> u64 {
+        self.utr5().iter().map(|iv| iv.end - iv.start).sum()
+    }
+
+    /// Returns the total length of the 3'UTR.
+    pub fn utr3_len(&self) -> u64 {
+        self.utr3().iter().map(|iv| iv.end - iv.start).sum()
+    }
+
+    /// Returns the intervals of either the 5'UTR or the 3'UTR, in the transcript direction.
+    fn utr_intervals(&self, five_prime: bool) -> Vec<Interval<u64>> {
+        let reverse = match self.strand {
+            Strand::Forward => false,
+            Strand::Reverse => true,
+            Strand::Unknown => return vec![],
+        };
+        let coding = self.coding_coord(true);
+        let mut intervals = self.exons.iter()
+            .flat_map(|exon| exon.features.iter())
+            .filter(|fx| match fx.kind {
+                UTR5 => five_prime,
+                UTR3 => !five_prime,
+                UTR => match coding {
+                    Some((start, end)) => {
+                        let upstream = if reverse { fx.start() >= end } else { fx.end() <= start };
+                        upstream == five_prime
+                    },
+                    None => false,
+                },
+                _ => false,
+            })
+            .map(|fx| fx.interval())
+            .collect::<Vec<Interval<u64>>>();
+        intervals.sort_by_key(|iv| (iv.start, iv.end));
+        if reverse {
+            intervals.reverse();
+        }
+        intervals
+    }
+
     /// Returns the genome-wise 5' and 3'-most coordinate of the coding region.
     ///
     /// The returned coding region coordinates may include the stop codon, depending on the value
@@ -489,6 +1357,129 @@ impl Transcript {
         }
     }
 
+    /// Sets the frames of coding features whose frames are unknown.
+    ///
+    /// Frames are computed in the transcript direction, continuing from the frames of the
+    /// preceding coding features of the same kind, with the transcript-wise 5'-most CDS
+    /// defaulting to frame zero. Features with known frames are left unchanged. Frames can not
+    /// be computed for transcripts with unknown strands.
+    pub fn fill_coding_frames(&mut self) {
+        if !self.has_unknown_frames() {
+            return;
+        }
+        match self.strand {
+            Strand::Forward => set_coding_frames(self.exons.iter_mut(), true),
+            Strand::Reverse => set_coding_frames(self.exons.iter_mut().rev(), true),
+            _ => {},
+        }
+    }
+
+    /// Returns whether any of the coding features has an unknown frame.
+    pub(crate) fn has_unknown_frames(&self) -> bool {
+        self.exons.iter()
+            .flat_map(|exon| exon.features.iter())
+            .any(|fx| match fx.kind {
+                CDS { frame: None } | StartCodon { frame: None } | StopCodon { frame: None } =>
+                    true,
+                _ => false,
+            })
+    }
+
+    /// Returns the position of the given genomic coordinate along the spliced transcript.
+    ///
+    /// The returned position is zero-based and counted from the transcript-wise 5' end, so on
+    /// reverse strand transcripts it increases as the genomic coordinate decreases. The value is
+    /// `None` if the coordinate does not lie in any of the transcript's exons.
+    pub fn genomic_to_transcript(&self, pos: u64) -> Option<u64> {
+        let segments = self.exons.iter()
+            .map(|exon| (exon.start(), exon.end()))
+            .collect();
+        self.project_onto(segments, pos)
+    }
+
+    /// Returns the position of the given genomic coordinate along the coding region.
+    ///
+    /// The returned position is zero-based and counted from the first base of the coding region
+    /// in the transcript direction, i.e. it is the position used in `c.` style notations minus
+    /// one. Whether the stop codon is considered part of the coding region is set by the
+    /// `incl_stop` argument.
+    ///
+    /// The value is `None` if no coding region is defined or if the coordinate does not lie in
+    /// the coding region.
+    pub fn genomic_to_cds(&self, pos: u64, incl_stop: bool) -> Option<u64> {
+        self.project_onto(self.coding_segments(incl_stop), pos)
+    }
+
+    /// Returns the zero-based index of the amino acid encoded by the codon that contains the
+    /// given genomic coordinate.
+    ///
+    /// The frame of the transcript-wise 5'-most CDS is taken into account, so that bases of
+    /// a preceding partial codon in an incomplete coding region yield `None`. Whether the stop
+    /// codon is considered part of the coding region is set by the `incl_stop` argument.
+    pub fn genomic_to_protein(&self, pos: u64, incl_stop: bool) -> Option<u64> {
+        let phase = self.coding_phase() as u64;
+        self.genomic_to_cds(pos, incl_stop)
+            .and_then(|cpos| if cpos >= phase { Some((cpos - phase) / 3) } else { None })
+    }
+
+    /// Returns the frame of the transcript-wise 5'-most CDS, defaulting to zero.
+    pub(crate) fn coding_phase(&self) -> u8 {
+        let mut cds_frames = self.exons.iter()
+            .flat_map(|exon| exon.features.iter())
+            .filter_map(|fx| match fx.kind {
+                CDS { frame } => Some((fx.start(), frame)),
+                _ => None,
+            })
+            .collect::<Vec<(u64, Option<u8>)>>();
+        cds_frames.sort_by_key(|&(start, _)| start);
+        let first = match self.strand {
+            Strand::Reverse => cds_frames.last(),
+            _ => cds_frames.first(),
+        };
+        first.and_then(|&(_, frame)| frame).unwrap_or(0)
+    }
+
+    /// Returns the genome-wise sorted coordinates of the coding features.
+    ///
+    /// The stop codon features are included if `incl_stop` is set to `true`.
+    pub(crate) fn coding_segments(&self, incl_stop: bool) -> Vec<Coord<u64>> {
+        let mut segments = self.exons.iter()
+            .flat_map(|exon| exon.features.iter())
+            .filter(|fx| match fx.kind {
+                CDS { .. } => true,
+                StopCodon { .. } => incl_stop,
+                _ => false,
+            })
+            .map(|fx| (fx.start(), fx.end()))
+            .collect::<Vec<Coord<u64>>>();
+        segments.sort();
+        segments
+    }
+
+    /// Projects the given genomic coordinate onto the given genome-wise sorted segments.
+    ///
+    /// The projection is strand-aware and zero-based, starting from the transcript-wise 5'
+    /// end of the segments.
+    fn project_onto(&self, segments: Vec<Coord<u64>>, pos: u64) -> Option<u64> {
+        let mut offset = 0;
+        if self.strand == Strand::Reverse {
+            for &(start, end) in segments.iter().rev() {
+                if start <= pos && pos < end {
+                    return Some(offset + (end - 1 - pos));
+                }
+                offset += end - start;
+            }
+        } else {
+            for &(start, end) in segments.iter() {
+                if start <= pos && pos < end {
+                    return Some(offset + (pos - start));
+                }
+                offset += end - start;
+            }
+        }
+        None
+    }
+
     /// Returns the genome-wise 5'-most coordinate of the coding region.
     fn coding_start_coord(&self, incl_stop: bool) -> Option<u64> {
         match &self.strand {
@@ -496,11 +1487,11 @@ impl Transcript {
                 for exon in self.exons.iter() {
                     for fx in exon.features.iter() {
                         if let StartCodon { .. } = fx.kind {
-                            return Some(fx.interval.start);
+                            return Some(fx.start());
                         }
                     }
                 }
-                None
+                self.incomplete_coding_bound(self.cds_5p_incomplete, true)
             },
             &Strand::Reverse => {
                 let mut codon_rem = if incl_stop { 0 } else { 3 };
@@ -508,22 +1499,22 @@ impl Transcript {
                     for fx in exon.features.iter() {
                         if let StopCodon { .. } = fx.kind {
                             if incl_stop {
-                                return Some(fx.interval.start);
+                                return Some(fx.start());
                             }
                             codon_rem -= fx.span();
                             if codon_rem == 0 {
-                                return Some(fx.interval.end);
+                                return Some(fx.end());
                             }
                         }
                     }
                 }
-                None
+                self.incomplete_coding_bound(self.cds_3p_incomplete, true)
             },
             &Strand::Unknown if incl_stop => {
                 for exon in self.exons.iter() {
                     for fx in exon.features.iter() {
                         if let CDS { .. } = fx.kind {
-                            return Some(fx.interval.start)
+                            return Some(fx.start())
                         }
                     }
                 }
@@ -533,50 +1524,259 @@ impl Transcript {
         }
     }
 
-    /// Returns the genome-wise 3'-most coordinate of the coding region.
-    fn coding_end_coord(&self, incl_stop: bool) -> Option<u64> {
-        match &self.strand {
-            &Strand::Forward => {
-                let mut codon_rem = if incl_stop { 0 } else { 3 };
-                for exon in self.exons.iter().rev() {
-                    for fx in exon.features.iter().rev() {
-                        if let StopCodon { .. } = fx.kind {
-                            if incl_stop {
-                                return Some(fx.interval.end);
-                            }
-                            codon_rem -= fx.span();
-                            if codon_rem == 0 {
-                                return Some(fx.interval.start);
-                            }
-                        }
-                    }
-                }
-                None
-            },
-            &Strand::Reverse => {
-                for exon in self.exons.iter().rev() {
-                    for fx in exon.features.iter().rev() {
-                        if let StartCodon { .. } = fx.kind {
-                            return Some(fx.interval.end);
-                        }
-                    }
-                }
-                None
-            },
-            &Strand::Unknown if incl_stop => {
-                for exon in self.exons.iter().rev() {
-                    for fx in exon.features.iter().rev() {
-                        if let CDS { .. } = fx.kind {
-                            return Some(fx.interval.end);
-                        }
-                    }
-                }
-                None
-            },
-            _ => None,
-        }
+    /// Returns the genome-wise 3'-most coordinate of the coding region.
+    fn coding_end_coord(&self, incl_stop: bool) -> Option<u64> {
+        match &self.strand {
+            &Strand::Forward => {
+                let mut codon_rem = if incl_stop { 0 } else { 3 };
+                for exon in self.exons.iter().rev() {
+                    for fx in exon.features.iter().rev() {
+                        if let StopCodon { .. } = fx.kind {
+                            if incl_stop {
+                                return Some(fx.end());
+                            }
+                            codon_rem -= fx.span();
+                            if codon_rem == 0 {
+                                return Some(fx.start());
+                            }
+                        }
+                    }
+                }
+                self.incomplete_coding_bound(self.cds_3p_incomplete, false)
+            },
+            &Strand::Reverse => {
+                for exon in self.exons.iter().rev() {
+                    for fx in exon.features.iter().rev() {
+                        if let StartCodon { .. } = fx.kind {
+                            return Some(fx.end());
+                        }
+                    }
+                }
+                self.incomplete_coding_bound(self.cds_5p_incomplete, false)
+            },
+            &Strand::Unknown if incl_stop => {
+                for exon in self.exons.iter().rev() {
+                    for fx in exon.features.iter().rev() {
+                        if let CDS { .. } = fx.kind {
+                            return Some(fx.end());
+                        }
+                    }
+                }
+                None
+            },
+            _ => None,
+        }
+    }
+
+    /// Returns the genome-wise 5'-most (if `start` is `true`) or 3'-most coordinate of the CDS
+    /// features, if the coding region is incomplete at that end.
+    fn incomplete_coding_bound(&self, incomplete: bool, start: bool) -> Option<u64> {
+        if !incomplete {
+            return None;
+        }
+        let cds_coords = self.exons.iter()
+            .flat_map(|exon| exon.features.iter())
+            .filter_map(|fx| match fx.kind {
+                CDS { .. } => Some(if start { fx.start() } else { fx.end() }),
+                _ => None,
+            });
+        if start { cds_coords.min() } else { cds_coords.max() }
+    }
+}
+
+/// Iterator over the introns of a transcript.
+///
+/// This struct is created by the `introns` method of `Transcript`.
+pub struct Introns<'a> {
+    exons: &'a [Exon],
+    reverse: bool,
+    // Intron `i` lies between exon `i` and `i + 1`; these are the remaining gap indices.
+    front: usize,
+    back: usize,
+}
+
+impl<'a> Introns<'a> {
+
+    /// Creates the intron feature lying after the exon at the given index, if any.
+    #[inline]
+    fn intron_after(&self, idx: usize) -> Option<TranscriptFeature> {
+        let (start, end) = (self.exons[idx].end(), self.exons[idx + 1].start());
+        if start < end {
+            Some(Feature::new(Interval::new(start..end).unwrap(), TranscriptFeatureKind::Intron))
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a> Iterator for Introns<'a> {
+
+    type Item = TranscriptFeature;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.front < self.back {
+            let intron =
+                if self.reverse {
+                    self.back -= 1;
+                    self.intron_after(self.back)
+                } else {
+                    self.front += 1;
+                    self.intron_after(self.front - 1)
+                };
+            if intron.is_some() {
+                return intron;
+            }
+        }
+        None
+    }
+}
+
+/// Iterator over the exons of a transcript in the transcript direction.
+///
+/// This struct is created by the `exons_stranded` method of `Transcript`.
+pub struct ExonsStranded<'a> {
+    inner: slice::Iter<'a, Exon>,
+    reverse: bool,
+}
+
+impl<'a> Iterator for ExonsStranded<'a> {
+
+    type Item = &'a Exon;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.reverse { self.inner.next_back() } else { self.inner.next() }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a> DoubleEndedIterator for ExonsStranded<'a> {
+
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.reverse { self.inner.next() } else { self.inner.next_back() }
+    }
+}
+
+impl<'a> ExactSizeIterator for ExonsStranded<'a> {}
+
+impl IntoIterator for Exon {
+
+    type Item = ExonFeature;
+    type IntoIter = vec::IntoIter<ExonFeature>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.features.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Exon {
+
+    type Item = &'a ExonFeature;
+    type IntoIter = slice::Iter<'a, ExonFeature>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl IntoIterator for Transcript {
+
+    type Item = Exon;
+    type IntoIter = vec::IntoIter<Exon>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.exons.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Transcript {
+
+    type Item = &'a Exon;
+    type IntoIter = slice::Iter<'a, Exon>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterator over the transcripts of a gene, in the order they were added.
+///
+/// This struct is created by the `iter` method of `Gene`.
+pub struct Transcripts<'a> {
+    inner: linked_hash_map::Values<'a, String, Transcript>,
+}
+
+impl<'a> Iterator for Transcripts<'a> {
+
+    type Item = &'a Transcript;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a> DoubleEndedIterator for Transcripts<'a> {
+
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back()
+    }
+}
+
+impl<'a> ExactSizeIterator for Transcripts<'a> {}
+
+/// Owning iterator over the transcripts of a gene, in the order they were added.
+///
+/// This struct is created by the `into_iter` method of `Gene`.
+pub struct IntoTranscripts {
+    inner: linked_hash_map::IntoIter<String, Transcript>,
+}
+
+impl Iterator for IntoTranscripts {
+
+    type Item = Transcript;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(_, trx)| trx)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for IntoTranscripts {
+
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|(_, trx)| trx)
+    }
+}
+
+impl ExactSizeIterator for IntoTranscripts {}
+
+impl IntoIterator for Gene {
+
+    type Item = Transcript;
+    type IntoIter = IntoTranscripts;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoTranscripts { inner: self.transcripts.into_iter() }
     }
+}
 
+impl<'a> IntoIterator for &'a Gene {
+
+    type Item = &'a Transcript;
+    type IntoIter = Transcripts<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
 }
 
 /// Builder for transcripts.
@@ -585,14 +1785,17 @@ impl Transcript {
 /// via its `build` function.
 #[derive(Debug)]
 pub struct TBuilder {
-    seq_name: String,
+    seq_name: SeqName,
     start: u64,
     end: u64,
     strand: Option<Strand>,
     strand_char: Option<char>,
+    strand_policy: StrandPolicy,
     id: Option<String>,
     gene_id: Option<String>,
-    attributes: MultiMap<String, String>,
+    attributes: Attributes,
+    source: Option<String>,
+    score: Option<f64>,
     // Input can be a vector of pre-made features ...
     exons: Option<Vec<Exon>>,
     // Or exon coordinates, possibly coupled with cds coord
@@ -600,13 +1803,16 @@ pub struct TBuilder {
     exon_coords: Option<Vec<Coord<u64>>>,
     coding_coord: Option<Coord<u64>>,
     coding_incl_stop: bool,
+    cds_5p_incomplete: bool,
+    cds_3p_incomplete: bool,
+    stop_readthrough: bool,
 }
 
 impl TBuilder {
 
     /// Creates a new builder for a transcript with the given values.
     pub fn new<T>(seq_name: T, start: u64, end: u64) -> Self
-        where T: Into<String>
+        where T: Into<SeqName>
     {
         TBuilder {
             seq_name: seq_name.into(),
@@ -614,16 +1820,60 @@ impl TBuilder {
             end: end,
             strand: None,
             strand_char: None,
+            strand_policy: StrandPolicy::default(),
             id: None,
             gene_id: None,
-            attributes: MultiMap::new(),
+            attributes: Attributes::new(),
+            source: None,
+            score: None,
             exons: None,
             exon_coords: None,
             coding_coord: None,
             coding_incl_stop: false,
+            cds_5p_incomplete: false,
+            cds_3p_incomplete: false,
+            stop_readthrough: false,
         }
     }
 
+    /// Creates a new builder pre-populated with the values of the given transcript.
+    ///
+    /// The exons of the transcript are kept as exon coordinates, along with its coding
+    /// coordinate including the stop codon, so that they may be replaced using the `coords`
+    /// method. Exon-level attributes are not kept.
+    pub fn from_transcript(transcript: &Transcript) -> Self {
+        let exon_coords = transcript.exons().iter()
+            .map(|exn| (exn.interval().start, exn.interval().end))
+            .collect::<Vec<Coord<u64>>>();
+        TBuilder {
+            seq_name: transcript.seq_name.clone(),
+            start: transcript.interval().start,
+            end: transcript.interval().end,
+            strand: Some(*transcript.strand()),
+            strand_char: None,
+            strand_policy: StrandPolicy::default(),
+            id: transcript.id().map(|v| v.to_owned()),
+            gene_id: transcript.gene_id().map(|v| v.to_owned()),
+            attributes: transcript.attributes().clone(),
+            source: transcript.source().map(|v| v.to_owned()),
+            score: transcript.score(),
+            exons: None,
+            exon_coords: if exon_coords.is_empty() { None } else { Some(exon_coords) },
+            coding_coord: transcript.coding_coord(true),
+            coding_incl_stop: true,
+            cds_5p_incomplete: transcript.cds_5p_incomplete(),
+            cds_3p_incomplete: transcript.cds_3p_incomplete(),
+            stop_readthrough: transcript.stop_readthrough(),
+        }
+    }
+
+    /// Sets the start and end coordinates of the transcript.
+    pub fn coord(mut self, start: u64, end: u64) -> Self {
+        self.start = start;
+        self.end = end;
+        self
+    }
+
     /// Sets the strand of the transcript.
     pub fn strand(mut self, strand: Strand) -> Self {
         self.strand = Some(strand);
@@ -647,6 +1897,14 @@ impl TBuilder {
         self
     }
 
+    /// Sets the policy for when neither the `strand` nor the `strand_char` method is invoked.
+    ///
+    /// By default, the `build` method returns an error in this case.
+    pub fn strand_policy(mut self, policy: StrandPolicy) -> Self {
+        self.strand_policy = policy;
+        self
+    }
+
     /// Sets the identifier of the transcript.
     pub fn id<T>(mut self, id: T) -> Self
         where T: Into<String>
@@ -672,8 +1930,24 @@ impl TBuilder {
     }
 
     /// Sets the entire attribute of the transcript.
-    pub fn attributes(mut self, attributes: MultiMap<String, String>) -> Self {
-        self.attributes = attributes;
+    pub fn attributes<A>(mut self, attributes: A) -> Self
+        where A: Into<Attributes>
+    {
+        self.attributes = attributes.into();
+        self
+    }
+
+    /// Sets the source of the transcript.
+    pub fn source<T>(mut self, source: T) -> Self
+        where T: Into<String>
+    {
+        self.source = Some(source.into());
+        self
+    }
+
+    /// Sets the score of the transcript.
+    pub fn score(mut self, score: f64) -> Self {
+        self.score = Some(score);
         self
     }
 
@@ -721,17 +1995,49 @@ impl TBuilder {
         self
     }
 
+    /// Sets whether the coding region is incomplete at its transcript-wise 5' end.
+    ///
+    /// When set, no start codon feature is inferred from the coding coordinate of the `coords`
+    /// method.
+    pub fn cds_5p_incomplete(mut self, incomplete: bool) -> Self {
+        self.cds_5p_incomplete = incomplete;
+        self
+    }
+
+    /// Sets whether the coding region is incomplete at its transcript-wise 3' end.
+    ///
+    /// When set, no stop codon feature is inferred from the coding coordinate of the `coords`
+    /// method, which then ends at the last coding base regardless of `coding_incl_stop`.
+    pub fn cds_3p_incomplete(mut self, incomplete: bool) -> Self {
+        self.cds_3p_incomplete = incomplete;
+        self
+    }
+
+    /// Sets whether stop codons in the coding region of the transcript are read through.
+    ///
+    /// The flag is also set if the attributes of the transcript contain a `seleno` or
+    /// `stop_codon_readthrough` tag.
+    pub fn stop_readthrough(mut self, stop_readthrough: bool) -> Self {
+        self.stop_readthrough = stop_readthrough;
+        self
+    }
+
     /// Validates the input data and builds a transcript.
     pub fn build(self) -> ::Result<Transcript> {
         let interval = coord_to_interval(self.start, self.end)
             .map_err(::Error::Model)?;
-        let strand = resolve_strand_input(self.strand, self.strand_char)
+        let strand = resolve_strand_input(self.strand, self.strand_char, self.strand_policy)
             .map_err(::Error::Model)?;
         let exons = resolve_exons_input(
             &self.seq_name, &interval, &strand, self.id.as_deref(),
             self.gene_id.as_deref(), None, // TODO: allow for exon IDs here
             self.exons, self.exon_coords.as_ref(), self.coding_coord,
-            self.coding_incl_stop).map_err(::Error::Model)?;
+            self.coding_incl_stop, (self.cds_5p_incomplete, self.cds_3p_incomplete))
+            .map_err(::Error::Model)?;
+        let stop_readthrough = self.stop_readthrough ||
+            self.attributes.get_vec(TAG_KEY)
+                .map(|tags| tags.iter().any(|tag| is_readthrough_tag(tag)))
+                .unwrap_or(false);
 
         let transcript = Transcript {
             seq_name: self.seq_name,
@@ -740,7 +2046,12 @@ impl TBuilder {
             id: self.id,
             gene_id: self.gene_id,
             attributes: self.attributes,
+            source: self.source,
+            score: self.score,
             exons: exons,
+            cds_5p_incomplete: self.cds_5p_incomplete,
+            cds_3p_incomplete: self.cds_3p_incomplete,
+            stop_readthrough: stop_readthrough,
         };
         Ok(transcript)
     }
@@ -750,12 +2061,19 @@ impl TBuilder {
 ///
 /// To create a gene, a `GBuilder` needs to be used.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde_impl", derive(Serialize, Deserialize))]
 pub struct Gene {
-    seq_name: String,
+    seq_name: SeqName,
+    #[cfg_attr(feature = "serde_impl", serde(with = "::utils::serde_interval"))]
     interval: Interval<u64>,
+    #[cfg_attr(feature = "serde_impl", serde(with = "::utils::serde_strand"))]
     strand: Strand,
     id: Option<String>,
-    attributes: MultiMap<String, String>,
+    attributes: Attributes,
+    #[cfg_attr(feature = "serde_impl", serde(default))]
+    source: Option<String>,
+    #[cfg_attr(feature = "serde_impl", serde(default))]
+    score: Option<f64>,
     transcripts: LinkedHashMap<String, Transcript>,
 }
 
@@ -771,20 +2089,216 @@ impl Gene {
         where T: Into<String> + Clone
     {
         for (_, transcript) in self.transcripts.iter_mut() {
-            transcript.set_id(id.clone())
+            transcript.set_gene_id(id.clone())
         }
         self.id = id.map(|v| v.into())
     }
 
-    /// Sets the transcripts for the gene.
+    /// Strips the version suffixes from the identifiers of the gene, its transcripts, and their
+    /// exons, as in `strip_id_version`.
+    ///
+    /// Transcripts whose stripped identifiers collide keep only the first of them.
+    pub fn strip_id_versions(&mut self) {
+        let gid = self.id.as_ref().map(|id| strip_id_version(id));
+        self.id = gid;
+        let transcripts = mem::replace(&mut self.transcripts, LinkedHashMap::new());
+        for (_, mut transcript) in transcripts {
+            transcript.strip_id_versions();
+            let tid = transcript.id().map(|id| id.to_owned()).unwrap_or_default();
+            if !self.transcripts.contains_key(&tid) {
+                let _ = self.transcripts.insert(tid, transcript);
+            }
+        }
+    }
+
+    /// Parses the attributes of the gene, its transcripts, and their exons, if they were kept
+    /// unparsed by a reader.
+    pub fn parse_attributes(&mut self) {
+        self.attributes.parse();
+        for (_, transcript) in self.transcripts.iter_mut() {
+            transcript.parse_attributes();
+        }
+    }
+
+    /// Returns the transcripts of the gene, keyed by their identifiers.
+    ///
+    /// Transcripts are iterated in the order they were added to the gene, so repeated
+    /// conversions of the same input write them in the same order.
     pub fn transcripts(&self) -> &LinkedHashMap<String, Transcript> {
         &self.transcripts
     }
 
+    /// Returns the transcript with the given identifier, if it exists.
+    pub fn transcript(&self, id: &str) -> Option<&Transcript> {
+        self.transcripts.get(id)
+    }
+
+    /// Returns an iterator over the transcripts of the gene, in the order they were added.
+    pub fn iter(&self) -> Transcripts {
+        Transcripts { inner: self.transcripts.values() }
+    }
+
     /// Consumes the gene and returns its transcripts.
     pub fn take_transcripts(self) -> LinkedHashMap<String, Transcript> {
         self.transcripts
     }
+
+    /// Returns a mutable reference of the transcripts of the gene.
+    pub(crate) fn transcripts_mut(&mut self) -> &mut LinkedHashMap<String, Transcript> {
+        &mut self.transcripts
+    }
+
+    /// Moves the gene and its transcripts to the given sequence, shifting their coordinates by
+    /// the given offset.
+    pub(crate) fn relocate(&mut self, seq_name: &str, offset: i64) -> Result<(), ModelError> {
+        self.interval = shift_interval(&self.interval, offset, &self.seq_name)?;
+        for (_, transcript) in self.transcripts.iter_mut() {
+            transcript.relocate(seq_name, offset)?;
+        }
+        self.seq_name = seq_name.into();
+        Ok(())
+    }
+
+    /// Returns the name, or symbol, of the gene.
+    ///
+    /// The name is taken from the `gene_name` (GTF), `Name` (GFF3), or `gene` (RefSeq)
+    /// attribute of the gene, or, if the gene has none, from the `gene_name` or `gene`
+    /// attribute of its transcripts.
+    pub fn name(&self) -> Option<&str> {
+        find_attribute(&self.attributes, GENE_NAME_KEYS)
+            .or_else(|| {
+                self.transcripts.values()
+                    .filter_map(|trx| find_attribute(trx.attributes(), TRANSCRIPT_GENE_NAME_KEYS))
+                    .next()
+            })
+    }
+
+    /// Returns the biotype of the gene.
+    ///
+    /// The biotype is taken from the `gene_biotype` (Ensembl and RefSeq) or `gene_type`
+    /// (GENCODE) attribute of the gene. Since GTF files repeat gene attributes in the rows of
+    /// each transcript, the attributes of the gene's transcripts are used when the gene itself
+    /// has none.
+    pub fn biotype(&self) -> Option<Biotype> {
+        find_attribute(&self.attributes, GENE_BIOTYPE_KEYS)
+            .or_else(|| {
+                self.transcripts.values()
+                    .filter_map(|trx| find_attribute(trx.attributes(), GENE_BIOTYPE_KEYS))
+                    .next()
+            })
+            .map(Biotype::from)
+    }
+
+    /// Returns the database cross-references of the gene as database and accession pairs,
+    /// taken from its `Dbxref` (GFF3) or `db_xref` (GTF) attributes.
+    ///
+    /// Cross-references are split at the first colon, so that `HGNC:HGNC:5` is parsed as the
+    /// `HGNC` database with the `HGNC:5` accession.
+    pub fn xrefs(&self) -> Vec<(&str, &str)> {
+        find_xrefs(&self.attributes)
+    }
+
+    /// Returns the accession of the first cross-reference to the given database, e.g.
+    /// `gene.xref("HGNC")`.
+    pub fn xref(&self, database: &str) -> Option<&str> {
+        self.xrefs().into_iter().find(|&(db, _)| db == database).map(|(_, acc)| acc)
+    }
+
+    /// Returns the canonical transcript of the gene.
+    ///
+    /// Transcripts tagged as MANE Select are preferred, followed by those tagged as Ensembl
+    /// canonical and then those tagged as MANE Plus Clinical. Among transcripts with the same
+    /// tags, coding transcripts are preferred over non-coding ones, and then longer spliced
+    /// transcripts over shorter ones. Ties are resolved in favor of the earliest transcript.
+    pub fn canonical_transcript(&self) -> Option<&Transcript> {
+        let mut canonical: Option<(&Transcript, (bool, bool, bool, bool, u64))> = None;
+        for trx in self.transcripts.values() {
+            let rank = canonical_rank(trx);
+            let better = match canonical {
+                Some((_, ref best)) => rank > *best,
+                None => true,
+            };
+            if better {
+                canonical = Some((trx, rank));
+            }
+        }
+        canonical.map(|(trx, _)| trx)
+    }
+
+    /// Returns the flanking region of the given size on the given side of the gene.
+    ///
+    /// The side is relative to the gene strand, so the upstream flank of a reverse strand gene
+    /// lies after its genome-wise 3'-most coordinate. The region is clipped at zero when it would
+    /// extend past the start of the sequence. Genes with unknown strands are treated as
+    /// forward-stranded.
+    pub fn flank(&self, size: u64, side: FlankSide) -> GeneFeature {
+        let upstream = side == FlankSide::Upstream;
+        let kind = match side {
+            FlankSide::Upstream => GeneFeatureKind::UpstreamFlank,
+            FlankSide::Downstream => GeneFeatureKind::DownstreamFlank,
+        };
+        let (start, end) =
+            if upstream != (self.strand == Strand::Reverse) {
+                (self.start().saturating_sub(size), self.start())
+            } else {
+                (self.end(), self.end().saturating_add(size))
+            };
+        Feature::new(Interval::new(start..end).unwrap(), kind)
+    }
+
+    /// Returns a copy of the gene whose transcripts are merged into a single transcript.
+    ///
+    /// The exons of the merged transcript are the union of the exons of all the gene's
+    /// transcripts, with overlapping or adjacent exons merged into one. The merged transcript has
+    /// no coding region and uses the gene identifier as its own identifier, so it can be written
+    /// by the existing writers as a collapsed gene model for RNA-seq quantification. Genes
+    /// without transcripts are returned without any transcript.
+    pub fn collapse(&self) -> ::Result<Gene> {
+        let merged = self.exonic_coords();
+
+        let mut transcripts = LinkedHashMap::new();
+        if let (Some(&(start, _)), Some(&(_, end))) = (merged.first(), merged.last()) {
+            let mut builder = TBuilder::new(self.seq_name.clone(), start, end)
+                .strand(self.strand);
+            if let Some(ref gid) = self.id {
+                builder = builder.id(gid.as_str()).gene_id(gid.as_str());
+            }
+            let transcript = builder.coords(merged, None).build()?;
+            let _ = transcripts.insert(self.id.clone().unwrap_or(DEF_ID.to_owned()), transcript);
+        }
+
+        let mut gene = GBuilder::new(self.seq_name.clone(), self.start(), self.end())
+            .strand(self.strand)
+            .attributes(self.attributes.clone())
+            .transcripts(transcripts);
+        if let Some(ref gid) = self.id {
+            gene = gene.id(gid.as_str());
+        }
+        gene.build()
+    }
+
+    /// Returns the genome-wise sorted union of the exons of all the gene's transcripts.
+    ///
+    /// Overlapping or adjacent exons are merged into one interval, so that no two of the returned
+    /// intervals overlap.
+    pub fn exonic_intervals(&self) -> Vec<Interval<u64>> {
+        self.exonic_coords().into_iter()
+            .map(|(start, end)| Interval::new(start..end).unwrap())
+            .collect()
+    }
+
+    /// Returns the number of bases covered by the exons of any of the gene's transcripts.
+    ///
+    /// This is the total length of the intervals returned by `exonic_intervals`.
+    pub fn exonic_length(&self) -> u64 {
+        self.exonic_coords().iter().map(|&(start, end)| end - start).sum()
+    }
+
+    /// Returns the coordinates of the merged exons of all the gene's transcripts.
+    fn exonic_coords(&self) -> Vec<Coord<u64>> {
+        merged_coords(self.transcripts.values()
+            .flat_map(|trx| trx.exons().iter().map(|exn| exn.interval().clone())))
+    }
 }
 
 /// Builder for genes.
@@ -793,23 +2307,28 @@ impl Gene {
 /// via its `build` function.
 #[derive(Debug)]
 pub struct GBuilder {
-    seq_name: String,
+    seq_name: SeqName,
     start: u64,
     end: u64,
     strand: Option<Strand>,
     strand_char: Option<char>,
+    strand_policy: StrandPolicy,
     id: Option<String>,
-    attributes: MultiMap<String, String>,
+    attributes: Attributes,
+    source: Option<String>,
+    score: Option<f64>,
     transcripts: Option<LinkedHashMap<String, Transcript>>,
     transcript_coords: Option<LinkedHashMap<String, RawTrxCoords>>,
     transcript_coding_incl_stop: bool,
+    strand_conflict_policy: StrandConflictPolicy,
+    extend_bounds: bool,
 }
 
 impl GBuilder {
 
     /// Creates a new builder for a gene with the given values.
     pub fn new<T>(seq_name: T, start: u64, end: u64) -> Self
-        where T: Into<String>
+        where T: Into<SeqName>
     {
         GBuilder {
             seq_name: seq_name.into(),
@@ -817,14 +2336,48 @@ impl GBuilder {
             end: end,
             strand: None,
             strand_char: None,
+            strand_policy: StrandPolicy::default(),
             id: None,
-            attributes: MultiMap::new(),
+            attributes: Attributes::new(),
+            source: None,
+            score: None,
             transcripts: None,
             transcript_coords: None,
             transcript_coding_incl_stop: false,
+            strand_conflict_policy: StrandConflictPolicy::default(),
+            extend_bounds: false,
+        }
+    }
+
+    /// Creates a new builder pre-populated with the values of the given gene, including
+    /// copies of its transcripts.
+    pub fn from_gene(gene: &Gene) -> Self {
+        GBuilder {
+            seq_name: gene.seq_name.clone(),
+            start: gene.interval().start,
+            end: gene.interval().end,
+            strand: Some(*gene.strand()),
+            strand_char: None,
+            strand_policy: StrandPolicy::default(),
+            id: gene.id().map(|v| v.to_owned()),
+            attributes: gene.attributes().clone(),
+            source: gene.source().map(|v| v.to_owned()),
+            score: gene.score(),
+            transcripts: Some(gene.transcripts().clone()),
+            transcript_coords: None,
+            transcript_coding_incl_stop: false,
+            strand_conflict_policy: StrandConflictPolicy::default(),
+            extend_bounds: false,
         }
     }
 
+    /// Sets the start and end coordinates of the gene.
+    pub fn coord(mut self, start: u64, end: u64) -> Self {
+        self.start = start;
+        self.end = end;
+        self
+    }
+
     /// Sets the strand of the gene.
     pub fn strand(mut self, strand: Strand) -> Self {
         self.strand = Some(strand);
@@ -848,6 +2401,14 @@ impl GBuilder {
         self
     }
 
+    /// Sets the policy for when neither the `strand` nor the `strand_char` method is invoked.
+    ///
+    /// By default, the `build` method returns an error in this case.
+    pub fn strand_policy(mut self, policy: StrandPolicy) -> Self {
+        self.strand_policy = policy;
+        self
+    }
+
     /// Sets the identifier of the gene.
     pub fn id<T>(mut self, id: T) -> Self
         where T: Into<String>
@@ -865,8 +2426,24 @@ impl GBuilder {
     }
 
     /// Sets the entire attribute of the gene.
-    pub fn attributes(mut self, attributes: MultiMap<String, String>) -> Self {
-        self.attributes = attributes;
+    pub fn attributes<A>(mut self, attributes: A) -> Self
+        where A: Into<Attributes>
+    {
+        self.attributes = attributes.into();
+        self
+    }
+
+    /// Sets the source of the gene.
+    pub fn source<T>(mut self, source: T) -> Self
+        where T: Into<String>
+    {
+        self.source = Some(source.into());
+        self
+    }
+
+    /// Sets the score of the gene.
+    pub fn score(mut self, score: f64) -> Self {
+        self.score = Some(score);
         self
     }
 
@@ -901,26 +2478,203 @@ impl GBuilder {
         self
     }
 
+    /// Sets the policy for when the transcripts of the gene lie on different strands, or on
+    /// another strand than the gene.
+    ///
+    /// By default, the `build` and `build_genes` methods return an error in this case.
+    /// Transcripts and genes with unknown strands never conflict.
+    pub fn strand_conflict_policy(mut self, policy: StrandConflictPolicy) -> Self {
+        self.strand_conflict_policy = policy;
+        self
+    }
+
+    /// Sets whether the gene bounds are extended to cover transcripts that extend past them.
+    ///
+    /// By default, the `build` and `build_genes` methods return an error in this case. The
+    /// adjustments made when this is enabled can be retrieved with `build_with_warnings`.
+    pub fn extend_bounds(mut self, extend: bool) -> Self {
+        self.extend_bounds = extend;
+        self
+    }
+
     /// Validates the input data and builds a gene.
+    ///
+    /// With the `Split` strand conflict policy, an error is returned if the transcripts
+    /// conflict, since they can not be split into a single gene. Use `build_genes` instead.
     pub fn build(self) -> ::Result<Gene> {
+        self.build_with_warnings().map(|(gene, _)| gene)
+    }
+
+    /// Validates the input data and builds a gene, along with the adjustments made to its
+    /// bounds.
+    ///
+    /// The warnings are always empty unless automatic bound extension is enabled.
+    pub fn build_with_warnings(self) -> ::Result<(Gene, Vec<BoundsWarning>)> {
+        let gid = self.id.clone();
+        let (mut genes, warnings) = self.build_all()?;
+        if genes.len() == 1 {
+            Ok((genes.remove(0), warnings))
+        } else {
+            Err(::Error::Model(ModelError::StrandConflict(gid)))
+        }
+    }
+
+    /// Validates the input data and builds one gene, or, with the `Split` strand conflict
+    /// policy and conflicting transcripts, one gene per transcript strand.
+    ///
+    /// Split genes share the identifier and attributes of the original gene and span their
+    /// own transcripts. Transcripts with unknown strands are assigned to the gene with the
+    /// strand of the original gene, if any, or to the first gene otherwise.
+    pub fn build_genes(self) -> ::Result<Vec<Gene>> {
+        self.build_all().map(|(genes, _)| genes)
+    }
+
+    fn build_all(self) -> ::Result<(Vec<Gene>, Vec<BoundsWarning>)> {
         let interval = coord_to_interval(self.start, self.end)
             .map_err(::Error::Model)?;
-        let strand = resolve_strand_input(self.strand, self.strand_char)
+        let strand = resolve_strand_input(self.strand, self.strand_char, self.strand_policy)
             .map_err(::Error::Model)?;
         let transcripts = resolve_transcripts_input(
-            &self.seq_name, &interval, &strand, self.id.as_deref(),
+            &self.seq_name, &strand, self.id.as_deref(),
             self.transcripts, self.transcript_coords, self.transcript_coding_incl_stop)?;
+        let (interval, warnings) =
+            resolve_gene_bounds(interval, &transcripts, self.extend_bounds, self.id.as_deref())?;
+        // the gene shares the sequence name of its transcripts when they are equal
+        let seq_name = match transcripts.values().next() {
+            Some(trx) if trx.seq_name == self.seq_name => trx.seq_name.clone(),
+            _ => self.seq_name,
+        };
+
+        let strand = if !has_strand_conflict(&strand, &transcripts) {
+            strand
+        } else {
+            match self.strand_conflict_policy {
+                StrandConflictPolicy::Error =>
+                    return Err(::Error::Model(ModelError::StrandConflict(self.id))),
+                StrandConflictPolicy::Unknown => Strand::Unknown,
+                StrandConflictPolicy::Split => {
+                    let (id, attributes, source, score) =
+                        (&self.id, &self.attributes, &self.source, self.score);
+                    let genes = split_by_strand(&strand, transcripts).into_iter()
+                        .map(|(group_strand, group)| {
+                            let (start, end) = group.values()
+                                .fold(INIT_COORD, |(start, end), trx| {
+                                    (min(start, trx.start()), max(end, trx.end()))
+                                });
+                            Gene {
+                                seq_name: seq_name.clone(),
+                                interval: Interval::new(start..end).unwrap(),
+                                strand: group_strand,
+                                id: id.clone(),
+                                attributes: attributes.clone(),
+                                source: source.clone(),
+                                score: score,
+                                transcripts: group,
+                            }
+                        })
+                        .collect();
+                    return Ok((genes, warnings));
+                },
+            }
+        };
 
         let gene = Gene {
-            seq_name: self.seq_name,
+            seq_name: seq_name,
             interval: interval,
             strand: strand,
             id: self.id,
             attributes: self.attributes,
+            source: self.source,
+            score: self.score,
             transcripts: transcripts,
         };
-        Ok(gene)
+        Ok((vec![gene], warnings))
+    }
+}
+
+/// Checks that the given transcripts lie within the gene interval, extending the interval to
+/// cover them if allowed.
+fn resolve_gene_bounds(
+    interval: Interval<u64>,
+    transcripts: &LinkedHashMap<String, Transcript>,
+    extend: bool,
+    gene_id: Option<&str>,
+) -> ::Result<(Interval<u64>, Vec<BoundsWarning>)>
+{
+    let outside = transcripts.iter()
+        .filter(|&(_, trx)| trx.start() < interval.start || trx.end() > interval.end)
+        .map(|(tid, _)| tid.clone())
+        .collect::<Vec<String>>();
+    if outside.is_empty() {
+        return Ok((interval, vec![]));
+    }
+    if !extend {
+        let tid = outside.into_iter().next();
+        return Err(::Error::Model(ModelError::TranscriptNotFullyEnveloped(tid)));
+    }
+    let (start, end) = transcripts.values()
+        .fold((interval.start, interval.end), |(start, end), trx| {
+            (min(start, trx.start()), max(end, trx.end()))
+        });
+    let warning = BoundsWarning {
+        gene_id: gene_id.map(|v| v.to_owned()),
+        transcript_ids: outside,
+        declared: (interval.start, interval.end),
+        adjusted: (start, end),
+    };
+    Ok((Interval::new(start..end).unwrap(), vec![warning]))
+}
+
+/// Returns whether the given transcripts lie on different strands, or on another strand than
+/// the given gene strand, ignoring unknown strands.
+fn has_strand_conflict(gene_strand: &Strand, transcripts: &LinkedHashMap<String, Transcript>)
+    -> bool
+{
+    let mut known = match *gene_strand {
+        Strand::Unknown => None,
+        strand => Some(strand),
+    };
+    for trx in transcripts.values() {
+        match (*trx.strand(), known) {
+            (Strand::Unknown, _) => {},
+            (strand, None) => known = Some(strand),
+            (strand, Some(known_strand)) if strand != known_strand => return true,
+            _ => {},
+        }
     }
+    false
+}
+
+/// Groups the given transcripts by their strands, in order of first appearance.
+fn split_by_strand(gene_strand: &Strand, transcripts: LinkedHashMap<String, Transcript>)
+    -> Vec<(Strand, LinkedHashMap<String, Transcript>)>
+{
+    let mut groups: Vec<(Strand, LinkedHashMap<String, Transcript>)> = Vec::new();
+    let mut unknowns = Vec::new();
+    for (tid, trx) in transcripts {
+        let strand = *trx.strand();
+        if strand.is_unknown() {
+            unknowns.push((tid, trx));
+            continue;
+        }
+        match groups.iter().position(|&(group_strand, _)| group_strand == strand) {
+            Some(idx) => {
+                let _ = groups[idx].1.insert(tid, trx);
+            },
+            None => {
+                let mut group = LinkedHashMap::new();
+                let _ = group.insert(tid, trx);
+                groups.push((strand, group));
+            },
+        }
+    }
+    let idx = groups.iter()
+        .position(|&(group_strand, _)| group_strand == *gene_strand)
+        .unwrap_or(0);
+    for (tid, trx) in unknowns {
+        let _ = groups[idx].1.insert(tid, trx);
+    }
+    groups
 }
 
 quick_error! {
@@ -1008,17 +2762,39 @@ quick_error! {
             display(self_) -> ("{}, transcript ID: {}",
                                self_.description(), tid.as_deref().unwrap_or(DEF_ID))
         }
+        /// Occurs when the transcripts of a gene lie on conflicting strands.
+        StrandConflict(gid: Option<String>) {
+            description("transcripts of gene lie on conflicting strands")
+            display(self_) -> ("{}, gene ID: {}",
+                               self_.description(), gid.as_deref().unwrap_or(DEF_ID))
+        }
+        /// Occurs when multiple genes share the same identifier.
+        DuplicateGeneId(gid: String) {
+            description("multiple genes with the same identifier")
+            display(self_) -> ("{}, gene ID: {}", self_.description(), gid)
+        }
+        /// Occurs when an attribute value can not be parsed into the requested type.
+        InvalidAttribute(key: String, value: String) {
+            description("attribute value can not be parsed")
+            display(self_) -> ("{}: '{}={}'", self_.description(), key, value)
+        }
+        /// Occurs when shifting coordinates moves them before the start of their sequence.
+        NegativeShiftedCoord(seq_name: String, offset: i64) {
+            description("shifted coordinate lies before the start of its sequence")
+            display(self_) -> ("{}: '{}' shifted by {}", self_.description(), seq_name, offset)
+        }
     }
 }
 
 /// Resolves the `strand` and `strand_char` arguments given to builders.
 fn resolve_strand_input(
     strand: Option<Strand>,
-    strand_char: Option<char>)
+    strand_char: Option<char>,
+    policy: StrandPolicy)
 -> Result<Strand, ModelError>
 {
     match (strand, strand_char) {
-        (None, None) => Err(ModelError::UnspecifiedStrand),
+        (None, None) => policy.default_strand(),
         (Some(sv), None) => Ok(sv),
         (None, Some(ref scv)) => Strand::from_char(scv).map_err(ModelError::from),
         (Some(sv), Some(ref scv)) => {
@@ -1034,7 +2810,7 @@ fn resolve_strand_input(
 
 /// Resolves the exon inputs given to a `TBuilder`.
 fn resolve_exons_input(
-    transcript_seqname: &String,
+    transcript_seqname: &SeqName,
     transcript_interval: &Interval<u64>,
     transcript_strand: &Strand,
     transcript_id: Option<&str>,
@@ -1043,7 +2819,8 @@ fn resolve_exons_input(
     exons: Option<Vec<Exon>>,
     exon_coords: Option<&Vec<Coord<u64>>>,
     coding_coord: Option<Coord<u64>>,
-    coding_incl_stop: bool
+    coding_incl_stop: bool,
+    cds_incomplete: (bool, bool),
 ) -> Result<Vec<Exon>, ModelError>
 {
     match (exons, exon_coords, coding_coord) {
@@ -1062,14 +2839,14 @@ fn resolve_exons_input(
         // exon defined & coords possibly defined (refFlat input)
         (None, Some(raw_exon_coords), raw_coding_coord) =>
             infer_exons(transcript_seqname, transcript_interval, transcript_strand, transcript_id,
-                        gene_id, exon_id, raw_exon_coords, raw_coding_coord, coding_incl_stop),
+                        gene_id, exon_id, raw_exon_coords, raw_coding_coord, coding_incl_stop,
+                        cds_incomplete),
     }
 }
 
 /// Resolves the transcript inputs given to a `GBuilder`.
 fn resolve_transcripts_input(
-    gene_seqname: &String,
-    gene_interval: &Interval<u64>,
+    gene_seqname: &SeqName,
     gene_strand: &Strand,
     gene_id: Option<&str>,
     transcripts: Option<LinkedHashMap<String, Transcript>>,
@@ -1088,12 +2865,6 @@ fn resolve_transcripts_input(
         (None, Some(trxs_coords)) => {
             let mut trxs = LinkedHashMap::new();
             for (trx_id, (trx_coord, exon_coords, coding_coord)) in trxs_coords.into_iter() {
-
-                if trx_coord.0 < gene_interval.start || trx_coord.1 > gene_interval.end {
-                    let tid = Some(trx_id);
-                    return Err(::Error::Model(ModelError::TranscriptNotFullyEnveloped(tid)));
-                }
-
                 let btrx = TBuilder::new(gene_seqname.clone(), trx_coord.0, trx_coord.1)
                     .strand(*gene_strand)
                     .id(trx_id.clone())
@@ -1115,8 +2886,11 @@ fn resolve_transcripts_input(
 }
 
 /// Infers exons and their features given coordinate values and identifiers.
+///
+/// No start or stop codon features are inferred at the transcript-wise 5' or 3' ends of the
+/// coding region, respectively, if they are marked as incomplete in `cds_incomplete`.
 fn infer_exons(
-    transcript_seqname: &String,
+    transcript_seqname: &SeqName,
     transcript_interval: &Interval<u64>,
     transcript_strand: &Strand,
     transcript_id: Option<&str>,
@@ -1124,7 +2898,8 @@ fn infer_exons(
     exon_id: Option<&str>,
     exon_coords: &Vec<Coord<u64>>,
     coding_coord: Option<Coord<u64>>,
-    coding_incl_stop: bool
+    coding_incl_stop: bool,
+    cds_incomplete: (bool, bool),
 ) -> Result<Vec<Exon>, ModelError>
 {
 
@@ -1144,7 +2919,7 @@ fn infer_exons(
     m_exon_coords.sort();
 
     let adj_coding_coord =
-        if coding_incl_stop {
+        if coding_incl_stop && !cds_incomplete.1 {
             coding_coord.and_then(|(a, b)| {
                 adjust_coding_coord(a, b, &transcript_strand, &m_exon_coords)
             })
@@ -1179,7 +2954,7 @@ fn infer_exons(
                 return Err(ModelError::CodingInIntron(tid));
             }
             // There must be room for stop codons (which is not inclusive in coding_coord)
-            let stop_codon_ok = match transcript_strand {
+            let stop_codon_ok = cds_incomplete.1 || match transcript_strand {
                 &Strand::Forward => coding_r.1 + 3 <= exon_r.1,
                 &Strand::Reverse => coding_r.0 - 3 >= exon_r.0,
                 &Strand::Unknown =>
@@ -1188,8 +2963,11 @@ fn infer_exons(
             if !stop_codon_ok {
                 return Err(ModelError::CodingTooLarge(tid));
             }
-            infer_exon_features(&m_exon_coords, coding_r, &transcript_seqname, transcript_strand,
-                                transcript_id, gene_id, exon_id)
+            let mut exons = infer_exon_features(
+                &m_exon_coords, coding_r, &transcript_seqname, transcript_strand, transcript_id,
+                gene_id, exon_id)?;
+            strip_codon_features(&mut exons, cds_incomplete);
+            Ok(exons)
         }
 
         // No CDS intervals mean we just sort the coordinates and create the exons
@@ -1204,7 +2982,9 @@ fn infer_exons(
                         id: exon_id.map(|id| id.to_owned()),
                         transcript_id: tid.clone(),
                         gene_id: gene_id.map(|id| id.to_owned()),
-                        attributes: MultiMap::new(),
+                        attributes: Attributes::new(),
+                        source: None,
+                        score: None,
                         features: Vec::new(),
                     });
             }
@@ -1261,7 +3041,7 @@ fn coord_to_interval(start: u64, end: u64) -> Result<Interval<u64>, ModelError>
 fn infer_exon_features(
     exon_coords: &Vec<Coord<u64>>,
     coding_r: Coord<u64>,
-    transcript_seqname: &String,
+    transcript_seqname: &SeqName,
     transcript_strand: &Strand,
     transcript_id: Option<&str>,
     gene_id: Option<&str>,
@@ -1282,15 +3062,14 @@ fn infer_exon_features(
             id: exon_id.map(|v| v.to_owned()),
             transcript_id: transcript_id.map(|v| v.to_owned()),
             gene_id: gene_id.map(|v| v.to_owned()),
-            attributes: MultiMap::new(),
+            attributes: Attributes::new(),
+            source: None,
+            score: None,
             features: features,
         }
     };
     let feat = |start, end, kind| {
-        ExonFeature {
-            interval: Interval::new(start..end).unwrap(),
-            kind: kind,
-        }
+        ExonFeature::new(Interval::new(start..end).unwrap(), kind)
     };
 
     let tid = transcript_id.map(|id| id.to_owned());
@@ -1631,14 +3410,50 @@ fn infer_exon_features(
     }
 
     match transcript_strand {
-        &Strand::Forward => set_coding_frames(exons.iter_mut()),
-        &Strand::Reverse => set_coding_frames(exons.iter_mut().rev()),
+        &Strand::Forward => set_coding_frames(exons.iter_mut(), false),
+        &Strand::Reverse => set_coding_frames(exons.iter_mut().rev(), false),
         _ => {}
     }
 
     Ok(exons)
 }
 
+/// Helper function to remove the inferred codon features of the incomplete ends of a coding
+/// region.
+///
+/// Start codons are simply removed, while stop codons, which lie outside of the coding region,
+/// are turned into 3'UTR features.
+fn strip_codon_features(exons: &mut Vec<Exon>, cds_incomplete: (bool, bool)) {
+    if !cds_incomplete.0 && !cds_incomplete.1 {
+        return;
+    }
+    for exon in exons.iter_mut() {
+        let mut features: Vec<ExonFeature> = Vec::with_capacity(exon.features.len());
+        for mut fx in exon.features.drain(..) {
+            let (is_start, is_stop) = match fx.kind {
+                StartCodon { .. } => (true, false),
+                StopCodon { .. } => (false, true),
+                _ => (false, false),
+            };
+            if is_start && cds_incomplete.0 {
+                continue;
+            }
+            if is_stop && cds_incomplete.1 {
+                fx.kind = UTR3;
+            }
+            if let Some(prev) = features.last_mut() {
+                if prev.kind == UTR3 && fx.kind == UTR3 && prev.end() == fx.start() {
+                    let merged = Interval::new(prev.start()..fx.end()).unwrap();
+                    prev.set_interval(merged);
+                    continue;
+                }
+            }
+            features.push(fx);
+        }
+        exon.features = features;
+    }
+}
+
 /// Helper function for adding features when the transcript is on the reverse strand.
 fn backtrack_and_push<F>(
     exons: &mut Vec<Exon>,
@@ -1669,7 +3484,7 @@ where F: Fn(u64, u64, ExonFeatureKind) -> ExonFeature
             } else {
                 let n_fxs = exon.features.len();
                 let new_fxp_interval = Interval::new(fxp_start..adj_fxp_end).unwrap();
-                exon.features[n_fxs-1].interval = new_fxp_interval;
+                exon.features[n_fxs-1].set_interval(new_fxp_interval);
             }
         }
         exon.features.push(fx);
@@ -1678,24 +3493,37 @@ where F: Fn(u64, u64, ExonFeatureKind) -> ExonFeature
 }
 
 /// Helper function to set the frames of exon features in a transcript.
-fn set_coding_frames<'a, T>(exons_miter: T)
+///
+/// If `keep_known` is set to `true`, features with known frames keep their frames, and the
+/// frames of the subsequent features are computed from them.
+fn set_coding_frames<'a, T>(exons_miter: T, keep_known: bool)
 where T: Iterator<Item=&'a mut Exon>
 {
     let (mut startc_frame, mut cds_frame, mut stopc_frame) = (0, 0, 0);
     for mut exon in exons_miter {
         for coding_fx in exon.features.iter_mut() {
+            let frame = match coding_fx.kind.frame() {
+                Some(known) if keep_known => known,
+                _ => match coding_fx.kind {
+                    StartCodon { .. } => startc_frame,
+                    CDS { .. } => cds_frame,
+                    StopCodon { .. } => stopc_frame,
+                    _ => continue,
+                },
+            };
+            let next_frame = calc_next_frame(coding_fx.span(), frame);
             match coding_fx.kind {
                 StartCodon { .. } => {
-                    coding_fx.kind = StartCodon { frame: Some(startc_frame) };
-                    startc_frame = calc_next_frame(coding_fx.span(), startc_frame);
+                    coding_fx.kind = StartCodon { frame: Some(frame) };
+                    startc_frame = next_frame;
                 },
                 CDS { .. } => {
-                    coding_fx.kind = CDS { frame: Some(cds_frame) };
-                    cds_frame = calc_next_frame(coding_fx.span(), cds_frame);
+                    coding_fx.kind = CDS { frame: Some(frame) };
+                    cds_frame = next_frame;
                 },
                 StopCodon { .. } => {
-                    coding_fx.kind = StopCodon { frame: Some(stopc_frame) };
-                    stopc_frame = calc_next_frame(coding_fx.span(), stopc_frame);
+                    coding_fx.kind = StopCodon { frame: Some(frame) };
+                    stopc_frame = next_frame;
                 },
                 _ => {}
             };
@@ -1703,6 +3531,51 @@ where T: Iterator<Item=&'a mut Exon>
     }
 }
 
+/// Helper function to shift an interval by the given offset.
+fn shift_interval(interval: &Interval<u64>, offset: i64, seq_name: &str)
+    -> Result<Interval<u64>, ModelError>
+{
+    let shift = |coord: u64| {
+        let shifted = coord as i64 + offset;
+        if shifted < 0 {
+            Err(ModelError::NegativeShiftedCoord(seq_name.to_owned(), offset))
+        } else {
+            Ok(shifted as u64)
+        }
+    };
+    coord_to_interval(shift(interval.start)?, shift(interval.end)?)
+}
+
+/// Helper function to clip an exon and its features to the given window.
+///
+/// Returns `None` if the exon lies outside the window.
+fn clip_exon(exon: &Exon, start: u64, end: u64, reverse: bool) -> Option<Exon> {
+    let (exn_start, exn_end) = (max(exon.start(), start), min(exon.end(), end));
+    if exn_start >= exn_end {
+        return None;
+    }
+    let mut features = Vec::with_capacity(exon.features.len());
+    for fx in exon.features.iter() {
+        let (fx_start, fx_end) = (max(fx.start(), start), min(fx.end(), end));
+        if fx_start >= fx_end {
+            continue;
+        }
+        let trimmed_5p = if reverse { fx.end() - fx_end } else { fx_start - fx.start() };
+        let kind = match fx.kind {
+            StartCodon { .. } | StopCodon { .. } if fx_end - fx_start < fx.span() => continue,
+            CDS { frame } if trimmed_5p > 0 =>
+                CDS { frame: frame.map(|f| ((f as u64 + 3 - trimmed_5p % 3) % 3) as u8) },
+            ref other => other.clone(),
+        };
+        features.push(Feature::new(Interval::new(fx_start..fx_end).unwrap(), kind));
+    }
+    Some(Exon {
+        interval: Interval::new(exn_start..exn_end).unwrap(),
+        features: features,
+        ..exon.clone()
+    })
+}
+
 // Adapted from: http://mblab.wustl.edu/GTF22.html
 /// Helper function to calculate the frame of subsequent feature.
 #[inline(always)]
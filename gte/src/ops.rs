@@ -0,0 +1,106 @@
+/*! Set operations on collections of genomic intervals.
+
+The operations treat their inputs as sets of bases on a single sequence, so input intervals may
+be given in any order and may overlap each other. The returned intervals are always sorted by
+their coordinates and do not overlap each other, with adjacent intervals merged into one. Empty
+intervals are dropped.
+
+The exon sets of transcripts and genes can be used as inputs via their `exonic_intervals`
+methods.
+*/
+use std::cmp::{max, min};
+
+use bio::utils::Interval;
+
+use Coord;
+
+
+/// Returns the union of the given intervals.
+pub fn merge_intervals<T>(intervals: T) -> Vec<Interval<u64>>
+    where T: IntoIterator<Item=Interval<u64>>
+{
+    to_intervals(merged_coords(intervals))
+}
+
+/// Returns the bases covered by both of the given interval collections.
+pub fn intersect_intervals<A, B>(a: A, b: B) -> Vec<Interval<u64>>
+    where A: IntoIterator<Item=Interval<u64>>, B: IntoIterator<Item=Interval<u64>>
+{
+    let (a, b) = (merged_coords(a), merged_coords(b));
+    let mut res = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        let (start, end) = (max(a[i].0, b[j].0), min(a[i].1, b[j].1));
+        if start < end {
+            res.push((start, end));
+        }
+        if a[i].1 < b[j].1 {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    to_intervals(res)
+}
+
+/// Returns the bases covered by the first interval collection but not by the second.
+pub fn subtract_intervals<A, B>(a: A, b: B) -> Vec<Interval<u64>>
+    where A: IntoIterator<Item=Interval<u64>>, B: IntoIterator<Item=Interval<u64>>
+{
+    let (a, b) = (merged_coords(a), merged_coords(b));
+    let mut res = Vec::new();
+    let mut j = 0;
+    for (start, end) in a {
+        while j < b.len() && b[j].1 <= start {
+            j += 1;
+        }
+        let mut cursor = start;
+        for &(b_start, b_end) in b[j..].iter().take_while(|&&(b_start, _)| b_start < end) {
+            if b_start > cursor {
+                res.push((cursor, b_start));
+            }
+            cursor = max(cursor, b_end);
+        }
+        if cursor < end {
+            res.push((cursor, end));
+        }
+    }
+    to_intervals(res)
+}
+
+/// Returns the bases of the given region that are not covered by any of the given intervals.
+pub fn complement_intervals<T>(intervals: T, region: Interval<u64>) -> Vec<Interval<u64>>
+    where T: IntoIterator<Item=Interval<u64>>
+{
+    subtract_intervals(vec![region], intervals)
+}
+
+/// Returns the sorted, merged coordinates of the given intervals.
+pub(crate) fn merged_coords<T>(intervals: T) -> Vec<Coord<u64>>
+    where T: IntoIterator<Item=Interval<u64>>
+{
+    let mut coords = intervals.into_iter()
+        .map(|iv| (iv.start, iv.end))
+        .filter(|&(start, end)| start < end)
+        .collect::<Vec<Coord<u64>>>();
+    coords.sort();
+
+    let mut merged: Vec<Coord<u64>> = Vec::with_capacity(coords.len());
+    for (start, end) in coords {
+        if let Some(last) = merged.last_mut() {
+            if start <= last.1 {
+                last.1 = max(last.1, end);
+                continue;
+            }
+        }
+        merged.push((start, end));
+    }
+    merged
+}
+
+/// Helper function to create intervals from valid coordinates.
+fn to_intervals(coords: Vec<Coord<u64>>) -> Vec<Interval<u64>> {
+    coords.into_iter()
+        .map(|(start, end)| Interval::new(start..end).unwrap())
+        .collect()
+}
@@ -0,0 +1,169 @@
+/*! Open reading frames of transcripts.
+
+Transcripts without coding regions, such as those assembled from RNA-seq alignments, can be
+turned into coding transcripts by scanning their spliced sequences for open reading frames
+and setting the chosen frame as their coding region.
+
+*/
+use std::cmp::Ordering;
+
+use {CodonTable, Coord, ModelError, SequenceSource, Strand, TBuilder, Transcript};
+
+
+/// Open reading frame of a transcript.
+///
+/// Coordinates are zero-based, half-open positions along the spliced transcript, counted from
+/// the transcript-wise 5' end. The frame spans from the first base of its start codon to the
+/// last base of its stop codon.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Orf {
+    start: u64,
+    end: u64,
+}
+
+impl Orf {
+
+    /// Returns the position of the first base of the start codon.
+    pub fn start(&self) -> u64 {
+        self.start
+    }
+
+    /// Returns the position after the last base of the stop codon.
+    pub fn end(&self) -> u64 {
+        self.end
+    }
+
+    /// Returns the length of the frame, including its stop codon.
+    pub fn len(&self) -> u64 {
+        self.end - self.start
+    }
+
+    /// Returns the number of amino acids encoded by the frame.
+    pub fn protein_len(&self) -> u64 {
+        self.len() / 3 - 1
+    }
+}
+
+impl Transcript {
+
+    /// Returns the open reading frames of the spliced transcript sequence.
+    ///
+    /// All three frames are scanned from the transcript-wise 5' end. Each frame starts at the
+    /// first start codon of the given codon table after the previous stop codon, and ends at
+    /// the next stop codon, so that nested frames sharing the same stop codon are not returned.
+    /// Frames without stop codons and frames shorter than `min_len` bases, excluding their
+    /// stop codons, are skipped.
+    ///
+    /// Frames are sorted by their lengths in descending order, and then by their start
+    /// positions. Transcripts with unknown strands are scanned as forward-stranded.
+    pub fn find_orfs<S: SequenceSource>(&self, source: &mut S, table: &CodonTable,
+                                        min_len: u64) -> ::Result<Vec<Orf>>
+    {
+        let seq = self.spliced_sequence(source)?;
+        let mut orfs = Vec::new();
+        for frame in 0..3 {
+            if seq.len() < frame {
+                break;
+            }
+            let mut open: Option<usize> = None;
+            for (idx, codon) in seq[frame..].chunks(3).enumerate() {
+                if codon.len() < 3 {
+                    break;
+                }
+                let pos = frame + idx * 3;
+                match open {
+                    None if table.is_start(codon) => open = Some(pos),
+                    Some(start) if table.translate_codon(codon) == b'*' => {
+                        if (pos - start) as u64 >= min_len {
+                            orfs.push(Orf { start: start as u64, end: (pos + 3) as u64 });
+                        }
+                        open = None;
+                    },
+                    _ => {},
+                }
+            }
+        }
+        orfs.sort_by(|a, b| match b.len().cmp(&a.len()) {
+            Ordering::Equal => a.start.cmp(&b.start),
+            ord => ord,
+        });
+        Ok(orfs)
+    }
+
+    /// Sets the given open reading frame as the coding region of the transcript.
+    ///
+    /// The features of all exons are replaced with UTR, CDS, start codon, and stop codon
+    /// features inferred from the frame, as done by `TBuilder`. An error is returned if the
+    /// frame does not lie in the transcript or if the strand of the transcript is unknown.
+    pub fn set_orf(&mut self, orf: &Orf) -> ::Result<()> {
+        let bounds =
+            if orf.end > orf.start {
+                self.transcript_to_genomic(orf.start)
+                    .and_then(|f| self.transcript_to_genomic(orf.end - 1).map(|l| (f, l)))
+            } else {
+                None
+            };
+        let coding_coord = match (bounds, *self.strand()) {
+            (Some((first, last)), Strand::Reverse) => (last, first + 1),
+            (Some((first, last)), _) => (first, last + 1),
+            (None, _) => {
+                let err = ModelError::CodingNotFullyEnveloped(self.id().map(|v| v.to_owned()));
+                return Err(::Error::from(err));
+            },
+        };
+        let exon_coords = self.exons().iter()
+            .map(|exn| (exn.start(), exn.end()))
+            .collect::<Vec<Coord<u64>>>();
+        let rebuilt = TBuilder::new(self.seq_name(), self.start(), self.end())
+            .strand(*self.strand())
+            .coords(exon_coords, Some(coding_coord))
+            .coding_incl_stop(true)
+            .build()?;
+        for (exn, rebuilt_exn) in self.exons_mut().iter_mut().zip(rebuilt.exons().iter()) {
+            let _ = exn.set_features(rebuilt_exn.features().to_vec())?;
+        }
+        Ok(())
+    }
+
+    /// Finds the longest open reading frame and sets it as the coding region of the
+    /// transcript, returning the frame.
+    ///
+    /// Open reading frames are found as done by `find_orfs`. The transcript is left unchanged
+    /// and `None` is returned if no frame is found.
+    pub fn set_longest_orf<S: SequenceSource>(&mut self, source: &mut S, table: &CodonTable,
+                                              min_len: u64) -> ::Result<Option<Orf>>
+    {
+        let orf = match self.find_orfs(source, table, min_len)?.into_iter().next() {
+            Some(orf) => orf,
+            None => return Ok(None),
+        };
+        self.set_orf(&orf)?;
+        Ok(Some(orf))
+    }
+
+    /// Returns the genomic coordinate of the given position along the spliced transcript.
+    ///
+    /// This is the inverse of `genomic_to_transcript`.
+    fn transcript_to_genomic(&self, pos: u64) -> Option<u64> {
+        let mut offset = 0;
+        let exons = self.exons();
+        if *self.strand() == Strand::Reverse {
+            for exn in exons.iter().rev() {
+                let len = exn.end() - exn.start();
+                if pos < offset + len {
+                    return Some(exn.end() - 1 - (pos - offset));
+                }
+                offset += len;
+            }
+        } else {
+            for exn in exons.iter() {
+                let len = exn.end() - exn.start();
+                if pos < offset + len {
+                    return Some(exn.start() + (pos - offset));
+                }
+                offset += len;
+            }
+        }
+        None
+    }
+}
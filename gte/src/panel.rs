@@ -0,0 +1,219 @@
+/*! Creation of target regions of gene panels.
+
+A `PanelBuilder` looks up genes by their names or identifiers in a `GenomeAnnotation` and
+creates the regions covering their exons, or their whole loci, optionally limited to their
+coding regions and padded on both sides. Exon regions are the union of the exons of all
+transcripts of a gene, so that each targeted base is covered once. The regions can be written
+as a BED file for targeted sequencing, named after their genes (e.g. `BRCA1`) or after their
+genes and exon numbers (e.g. `BRCA1_exon3`), with exons numbered from the 5' end of the gene.
+*/
+use std::cmp::max;
+use std::error::Error;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
+use bio::utils::Interval;
+
+use {Gene, GenomeAnnotation, Strand};
+use ops::merged_coords;
+
+
+quick_error! {
+    /// Errors that occur when creating or writing panel regions.
+    #[derive(Debug)]
+    pub enum PanelError {
+        /// Occurs when a gene can not be found by its name or identifier.
+        UnknownGene(query: String) {
+            description("gene not found in annotation")
+            display(self_) -> ("{}: '{}'", self_.description(), query)
+        }
+        /// Errors propagated from writing the underlying stream.
+        Io(err: io::Error) {
+            description(err.description())
+            from()
+            cause(err)
+        }
+    }
+}
+
+/// Kinds of regions created for each gene of a panel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PanelRegionKind {
+    /// The union of the exons of all transcripts.
+    Exons,
+    /// The whole locus, from the start of the first transcript to the end of the last one.
+    Locus,
+}
+
+impl Default for PanelRegionKind {
+    fn default() -> Self {
+        PanelRegionKind::Exons
+    }
+}
+
+/// Target region of a gene panel.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PanelRegion {
+    /// Sequence name of the region.
+    pub seq_name: String,
+    /// Zero-based start coordinate of the region.
+    pub start: u64,
+    /// Zero-based, exclusive end coordinate of the region.
+    pub end: u64,
+    /// Name of the region.
+    pub name: String,
+}
+
+/// Builder of the target regions of gene panels.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PanelBuilder {
+    kind: PanelRegionKind,
+    padding: u64,
+    cds_only: bool,
+    exon_names: bool,
+}
+
+impl PanelBuilder {
+
+    /// Creates a builder of unpadded exon regions named after their genes.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the kind of regions created for each gene.
+    pub fn kind(&mut self, kind: PanelRegionKind) -> &mut Self {
+        self.kind = kind;
+        self
+    }
+
+    /// Sets the number of bases added to both sides of each region.
+    ///
+    /// Regions of the same gene that overlap after padding are merged.
+    pub fn padding(&mut self, padding: u64) -> &mut Self {
+        self.padding = padding;
+        self
+    }
+
+    /// Sets whether the regions are limited to the coding regions of the transcripts.
+    ///
+    /// Genes without coding transcripts have no regions.
+    pub fn cds_only(&mut self, cds_only: bool) -> &mut Self {
+        self.cds_only = cds_only;
+        self
+    }
+
+    /// Sets whether exon regions are named after their genes and exon numbers, as in
+    /// `GENE_exon3`.
+    pub fn exon_names(&mut self, exon_names: bool) -> &mut Self {
+        self.exon_names = exon_names;
+        self
+    }
+
+    /// Creates the regions of the genes with the given names or identifiers, in the order of
+    /// the genes and then of the coordinates of their regions.
+    ///
+    /// Each gene is looked up by its name first, as in `GenomeAnnotation::genes_by_name`, and
+    /// by its identifier otherwise. Names matching multiple genes, such as genes annotated on
+    /// both sex chromosomes, yield the regions of all of them. Regions are named after the
+    /// given names or identifiers. An error is returned if any gene can not be found.
+    pub fn regions<S>(&self, annotation: &GenomeAnnotation, genes: &[S])
+        -> ::Result<Vec<PanelRegion>>
+        where S: AsRef<str>
+    {
+        let mut regions = Vec::new();
+        for query in genes.iter().map(|q| q.as_ref()) {
+            let mut found = annotation.genes_by_name(query);
+            if found.is_empty() {
+                found.extend(annotation.gene(query));
+            }
+            if found.is_empty() {
+                return Err(::Error::from(PanelError::UnknownGene(query.to_owned())));
+            }
+            for gene in found {
+                regions.extend(self.gene_regions(gene, query));
+            }
+        }
+        Ok(regions)
+    }
+
+    /// Creates the regions of the given gene, named after the given name.
+    pub fn gene_regions(&self, gene: &Gene, name: &str) -> Vec<PanelRegion> {
+        let coords = self.padded(self.unpadded_coords(gene));
+        let num_coords = coords.len();
+        coords.into_iter()
+            .enumerate()
+            .map(|(idx, (start, end))| {
+                let region_name = if self.exon_names && self.kind == PanelRegionKind::Exons {
+                    let exon_num = match *gene.strand() {
+                        Strand::Reverse => num_coords - idx,
+                        _ => idx + 1,
+                    };
+                    format!("{}_exon{}", name, exon_num)
+                } else {
+                    name.to_owned()
+                };
+                PanelRegion {
+                    seq_name: gene.seq_name().to_owned(),
+                    start: start,
+                    end: end,
+                    name: region_name,
+                }
+            })
+            .collect()
+    }
+
+    fn unpadded_coords(&self, gene: &Gene) -> Vec<(u64, u64)> {
+        let coords = if self.cds_only {
+            merged_coords(gene.transcripts().values()
+                .flat_map(|trx| trx.coding_segments(true))
+                .map(|(start, end)| Interval::new(start..end).unwrap()))
+        } else {
+            gene.exonic_intervals().into_iter()
+                .map(|iv| (iv.start, iv.end))
+                .collect()
+        };
+        match self.kind {
+            PanelRegionKind::Exons => coords,
+            PanelRegionKind::Locus => {
+                let start = coords.iter().map(|&(start, _)| start).min();
+                let end = coords.iter().map(|&(_, end)| end).max();
+                match (start, end) {
+                    (Some(start), Some(end)) => vec![(start, end)],
+                    _ => vec![],
+                }
+            },
+        }
+    }
+
+    fn padded(&self, coords: Vec<(u64, u64)>) -> Vec<(u64, u64)> {
+        let mut padded: Vec<(u64, u64)> = Vec::with_capacity(coords.len());
+        for (start, end) in coords {
+            let (start, end) = (start.saturating_sub(self.padding),
+                                end.saturating_add(self.padding));
+            if let Some(last) = padded.last_mut() {
+                if start <= last.1 {
+                    last.1 = max(last.1, end);
+                    continue;
+                }
+            }
+            padded.push((start, end));
+        }
+        padded
+    }
+}
+
+/// Writes the given regions as BED4 rows.
+pub fn write_panel_bed<W: Write>(writer: &mut W, regions: &[PanelRegion]) -> ::Result<()> {
+    for region in regions {
+        writeln!(writer, "{}\t{}\t{}\t{}", region.seq_name, region.start, region.end, region.name)
+            .map_err(|e| ::Error::from(PanelError::from(e)))?;
+    }
+    writer.flush().map_err(|e| ::Error::from(PanelError::from(e)))
+}
+
+/// Writes the given regions as BED4 rows to a file at the given path.
+pub fn write_panel_bed_file<P: AsRef<Path>>(path: P, regions: &[PanelRegion]) -> ::Result<()> {
+    let file = fs::File::create(path).map_err(|e| ::Error::from(PanelError::from(e)))?;
+    write_panel_bed(&mut io::BufWriter::new(file), regions)
+}
@@ -0,0 +1,148 @@
+/*! Handling of genes duplicated in the pseudoautosomal regions of chromosome Y.
+
+GENCODE annotates the genes in the pseudoautosomal regions (PARs) on both chromosome X and
+chromosome Y, with the identifiers of the chromosome Y copies, and of their transcripts,
+suffixed with `_PAR_Y` (e.g. `ENSG00000182378.14_PAR_Y`). Depending on the `ParYPolicy`, these
+copies are kept as they are, filtered out, or linked to their chromosome X counterparts by the
+`par_x_id` attribute, which holds the identifier of the counterpart.
+*/
+use {Gene, Transcript};
+use model::PAR_Y_SUFFIX;
+
+
+/// Attribute of linked chromosome Y copies holding the identifier of their chromosome X
+/// counterparts.
+pub const PAR_X_ID_ATTR: &'static str = "par_x_id";
+
+/// Policies for handling the chromosome Y copies of pseudoautosomal genes and transcripts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParYPolicy {
+    /// Keeps the copies as they are.
+    Keep,
+    /// Discards the copies.
+    Filter,
+    /// Keeps the copies, linking them to their chromosome X counterparts.
+    Link,
+}
+
+impl Default for ParYPolicy {
+    fn default() -> Self {
+        ParYPolicy::Keep
+    }
+}
+
+/// Returns whether the given identifier is that of a chromosome Y copy of a pseudoautosomal
+/// feature.
+pub fn is_par_y_id(id: &str) -> bool {
+    id.ends_with(PAR_Y_SUFFIX) && id.len() > PAR_Y_SUFFIX.len()
+}
+
+/// Returns the identifier of the chromosome X counterpart of the feature with the given
+/// identifier, if it is a chromosome Y copy.
+pub fn par_x_id(id: &str) -> Option<&str> {
+    if is_par_y_id(id) {
+        Some(&id[..id.len() - PAR_Y_SUFFIX.len()])
+    } else {
+        None
+    }
+}
+
+/// Links the given transcript to its chromosome X counterpart, if it is a chromosome Y copy.
+fn link_transcript(transcript: &mut Transcript) {
+    let counterpart = transcript.id().and_then(par_x_id).map(|id| id.to_owned());
+    if let Some(counterpart) = counterpart {
+        transcript.attributes_mut().insert(PAR_X_ID_ATTR.to_owned(), counterpart);
+    }
+}
+
+/// Iterator over genes with their chromosome Y copies handled using a policy.
+///
+/// Genes are identified as copies by their own identifiers. Errors in the underlying gene
+/// stream are passed through as-is.
+pub struct ParYGenes<I> {
+    genes: I,
+    policy: ParYPolicy,
+}
+
+impl<I> ParYGenes<I> where I: Iterator<Item=::Result<Gene>> {
+
+    /// Creates an iterator over the given genes, handling their chromosome Y copies using the
+    /// given policy.
+    pub fn new<T>(genes: T, policy: ParYPolicy) -> Self
+        where T: IntoIterator<Item=::Result<Gene>, IntoIter=I>
+    {
+        ParYGenes {
+            genes: genes.into_iter(),
+            policy: policy,
+        }
+    }
+}
+
+impl<I> Iterator for ParYGenes<I> where I: Iterator<Item=::Result<Gene>> {
+
+    type Item = ::Result<Gene>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let mut gx = match self.genes.next() {
+                Some(Ok(gx)) => gx,
+                otherwise => return otherwise,
+            };
+            let counterpart = gx.id().and_then(par_x_id).map(|id| id.to_owned());
+            match (self.policy, counterpart) {
+                (ParYPolicy::Filter, Some(_)) => continue,
+                (ParYPolicy::Link, Some(counterpart)) => {
+                    gx.attributes_mut().insert(PAR_X_ID_ATTR.to_owned(), counterpart);
+                    for (_, transcript) in gx.transcripts_mut().iter_mut() {
+                        link_transcript(transcript);
+                    }
+                },
+                _ => {},
+            }
+            return Some(Ok(gx));
+        }
+    }
+}
+
+/// Iterator over transcripts with their chromosome Y copies handled using a policy.
+///
+/// Errors in the underlying transcript stream are passed through as-is.
+pub struct ParYTranscripts<I> {
+    transcripts: I,
+    policy: ParYPolicy,
+}
+
+impl<I> ParYTranscripts<I> where I: Iterator<Item=::Result<Transcript>> {
+
+    /// Creates an iterator over the given transcripts, handling their chromosome Y copies
+    /// using the given policy.
+    pub fn new<T>(transcripts: T, policy: ParYPolicy) -> Self
+        where T: IntoIterator<Item=::Result<Transcript>, IntoIter=I>
+    {
+        ParYTranscripts {
+            transcripts: transcripts.into_iter(),
+            policy: policy,
+        }
+    }
+}
+
+impl<I> Iterator for ParYTranscripts<I> where I: Iterator<Item=::Result<Transcript>> {
+
+    type Item = ::Result<Transcript>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let mut trx = match self.transcripts.next() {
+                Some(Ok(trx)) => trx,
+                otherwise => return otherwise,
+            };
+            let is_copy = trx.id().map_or(false, is_par_y_id);
+            match self.policy {
+                ParYPolicy::Filter if is_copy => continue,
+                ParYPolicy::Link => link_transcript(&mut trx),
+                _ => {},
+            }
+            return Some(Ok(trx));
+        }
+    }
+}
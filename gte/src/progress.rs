@@ -0,0 +1,146 @@
+/*! Progress reporting and cancellation of long-running reads.
+
+Readers accept an `Observer`, which is notified after every parsed record with the numbers of
+records parsed and bytes consumed so far, and a `CancelToken`, which can be used to stop reading
+from another thread. Once a token is cancelled, the next record or gene requested from a reader
+is an `Error::Cancelled` error, after which its iterators end.
+*/
+use std::io::{self, Read};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+
+/// Observer of the progress of reading an annotation file.
+pub trait Observer {
+    /// Called after a record is parsed, with the total numbers of records parsed and bytes
+    /// consumed from the input so far.
+    ///
+    /// Inputs are read in buffered chunks, so the number of bytes may run ahead of the parsed
+    /// records.
+    fn on_progress(&mut self, records: u64, bytes: u64);
+}
+
+impl<F: FnMut(u64, u64)> Observer for F {
+    fn on_progress(&mut self, records: u64, bytes: u64) {
+        self(records, bytes)
+    }
+}
+
+/// Token for cancelling reads, which may be shared among threads.
+///
+/// Clones of a token share their state, so cancelling any of them cancels all of them.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancelToken {
+
+    /// Creates a token that is not cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancels the token.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    /// Returns whether the token has been cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+}
+
+/// Helper struct for stopping iterators once their cancellation token is cancelled.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Cancellation {
+    token: Option<CancelToken>,
+    stopped: bool,
+}
+
+impl Cancellation {
+
+    pub fn new(token: Option<CancelToken>) -> Self {
+        Cancellation { token: token, stopped: false }
+    }
+
+    /// Returns whether reading may proceed.
+    ///
+    /// The cancellation error is returned the first time the cancellation is seen, and `false`
+    /// afterwards, so that iterators yield the error once before they end.
+    pub fn proceed(&mut self) -> ::Result<bool> {
+        if self.stopped {
+            return Ok(false);
+        }
+        if self.token.as_ref().map_or(false, |token| token.is_cancelled()) {
+            self.stopped = true;
+            return Err(::Error::Cancelled);
+        }
+        Ok(true)
+    }
+}
+
+/// Helper struct for the progress of a reader, which is shared with its iterators.
+#[derive(Default)]
+pub(crate) struct Tracker {
+    observer: Option<Box<Observer + Send>>,
+    cancellation: Cancellation,
+    bytes: Arc<AtomicUsize>,
+    records: u64,
+}
+
+impl Tracker {
+
+    /// Creates a tracker reporting the bytes counted by the given counter.
+    pub fn new(bytes: Arc<AtomicUsize>) -> Self {
+        Tracker { bytes: bytes, ..Tracker::default() }
+    }
+
+    pub fn set_observer(&mut self, observer: Box<Observer + Send>) {
+        self.observer = Some(observer);
+    }
+
+    pub fn set_cancel_token(&mut self, token: CancelToken) {
+        self.cancellation = Cancellation::new(Some(token));
+    }
+
+    /// Returns a cancellation for iterators that do not hold the tracker.
+    pub fn cancellation(&self) -> Cancellation {
+        Cancellation::new(self.cancellation.token.clone())
+    }
+
+    /// Returns whether reading may proceed, as in `Cancellation::proceed`.
+    pub fn proceed(&mut self) -> ::Result<bool> {
+        self.cancellation.proceed()
+    }
+
+    /// Counts a parsed record and notifies the observer, if any.
+    pub fn record(&mut self) {
+        self.records += 1;
+        if let Some(ref mut observer) = self.observer {
+            observer.on_progress(self.records, self.bytes.load(Ordering::SeqCst) as u64);
+        }
+    }
+}
+
+/// Reader that counts the bytes read from another reader.
+pub(crate) struct CountingReader<R> {
+    inner: R,
+    bytes: Arc<AtomicUsize>,
+}
+
+impl<R: Read> CountingReader<R> {
+
+    pub fn new(inner: R, bytes: Arc<AtomicUsize>) -> Self {
+        CountingReader { inner: inner, bytes: bytes }
+    }
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        let _ = self.bytes.fetch_add(n, Ordering::SeqCst);
+        Ok(n)
+    }
+}
@@ -0,0 +1,161 @@
+/*! Regulatory features.
+
+Regulatory builds, such as the Ensembl Regulatory Build, annotate features that are not
+transcribed, such as enhancers, promoters, and binding sites of CTCF and other transcription
+factors. These are read from the GFF reader as a separate stream of `RegulatoryFeature`s, so that
+they can be used alongside the genes of a gene build.
+
+*/
+use std::fmt;
+
+use {Attributes, Coord, Strand};
+
+
+/// Kinds of regulatory features.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RegulatoryKind {
+    /// `enhancer`
+    Enhancer,
+    /// `promoter`
+    Promoter,
+    /// `promoter_flanking_region`
+    PromoterFlank,
+    /// `CTCF_binding_site`
+    CtcfBindingSite,
+    /// `TF_binding_site`
+    TfBindingSite,
+    /// `open_chromatin_region`
+    OpenChromatin,
+}
+
+impl RegulatoryKind {
+
+    /// Returns the kind of the given feature type, if it is a regulatory feature type.
+    ///
+    /// Feature types are matched case-insensitively.
+    pub fn from_feature_type(feature_type: &str) -> Option<RegulatoryKind> {
+        match feature_type.to_lowercase().as_str() {
+            "enhancer" => Some(RegulatoryKind::Enhancer),
+            "promoter" => Some(RegulatoryKind::Promoter),
+            "promoter_flanking_region" => Some(RegulatoryKind::PromoterFlank),
+            "ctcf_binding_site" => Some(RegulatoryKind::CtcfBindingSite),
+            "tf_binding_site" => Some(RegulatoryKind::TfBindingSite),
+            "open_chromatin_region" => Some(RegulatoryKind::OpenChromatin),
+            _ => None,
+        }
+    }
+
+    /// Returns the feature type of the kind, as used in Ensembl GFF3 files.
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            RegulatoryKind::Enhancer => "enhancer",
+            RegulatoryKind::Promoter => "promoter",
+            RegulatoryKind::PromoterFlank => "promoter_flanking_region",
+            RegulatoryKind::CtcfBindingSite => "CTCF_binding_site",
+            RegulatoryKind::TfBindingSite => "TF_binding_site",
+            RegulatoryKind::OpenChromatin => "open_chromatin_region",
+        }
+    }
+}
+
+impl fmt::Display for RegulatoryKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Regulatory feature, such as an enhancer or a transcription factor binding site.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RegulatoryFeature {
+    seq_name: String,
+    start: u64,
+    end: u64,
+    strand: Strand,
+    kind: RegulatoryKind,
+    id: Option<String>,
+    source: Option<String>,
+    score: Option<f64>,
+    attributes: Attributes,
+}
+
+impl RegulatoryFeature {
+
+    /// Creates a regulatory feature from the values of a GFF row.
+    ///
+    /// Coordinates are zero-based and half-open, and the attributes exclude the `ID` key.
+    pub(crate) fn new(
+        seq_name: String,
+        coord: Coord<u64>,
+        strand: Strand,
+        kind: RegulatoryKind,
+        id: Option<String>,
+        source: Option<String>,
+        score: Option<f64>,
+        attributes: Attributes,
+    ) -> RegulatoryFeature {
+        RegulatoryFeature {
+            seq_name: seq_name,
+            start: coord.0,
+            end: coord.1,
+            strand: strand,
+            kind: kind,
+            id: id,
+            source: source,
+            score: score,
+            attributes: attributes,
+        }
+    }
+
+    /// Returns the sequence name.
+    pub fn seq_name(&self) -> &str {
+        self.seq_name.as_str()
+    }
+
+    /// Returns the 0-based start coordinate.
+    pub fn start(&self) -> u64 {
+        self.start
+    }
+
+    /// Returns the 0-based, exclusive end coordinate.
+    pub fn end(&self) -> u64 {
+        self.end
+    }
+
+    /// Returns the strand, which is usually unknown.
+    pub fn strand(&self) -> &Strand {
+        &self.strand
+    }
+
+    /// Returns the kind of the feature.
+    pub fn kind(&self) -> RegulatoryKind {
+        self.kind
+    }
+
+    /// Returns the identifier, such as `ENSR00000000001`.
+    ///
+    /// Identifiers are taken from the `ID` attribute, without the feature type prefixes used in
+    /// Ensembl GFF3 files, e.g. `enhancer:ENSR00000000001`.
+    pub fn id(&self) -> Option<&str> {
+        self.id.as_ref().map(|v| v.as_str())
+    }
+
+    /// Returns the source, if known.
+    pub fn source(&self) -> Option<&str> {
+        self.source.as_ref().map(|v| v.as_str())
+    }
+
+    /// Returns the score, if known.
+    pub fn score(&self) -> Option<f64> {
+        self.score
+    }
+
+    /// Returns the attributes, excluding the `ID` key.
+    pub fn attributes(&self) -> &Attributes {
+        &self.attributes
+    }
+
+    /// Returns the description, taken from the `description` attribute.
+    pub fn description(&self) -> Option<&str> {
+        self.attributes.get("description").map(|v| v.as_str())
+    }
+}
@@ -0,0 +1,201 @@
+/*! Rewriting of the sequence names and coordinates of genes and transcripts.
+
+A rewrite maps sequences to new names and shifts the coordinates of all features on them by a
+per-sequence offset, for example to convert annotations of contigs to the coordinates of their
+scaffolds or chromosomes. Rewrites are applied lazily to gene and transcript streams.
+*/
+use std::collections::HashMap;
+use std::error::Error;
+use std::io::{self, BufRead, BufReader};
+use std::path::Path;
+
+use {Gene, SeqAliases, Transcript};
+use utils::open_path;
+
+
+quick_error! {
+    /// Errors that occur when reading rewrite tables.
+    #[derive(Debug)]
+    pub enum RewriteError {
+        /// Occurs when a rewrite table line can not be parsed.
+        InvalidTableLine(line: String) {
+            description("invalid rewrite table line")
+            display(self_) -> ("{}: '{}'", self_.description(), line)
+        }
+        /// Errors propagated from reading the underlying stream.
+        Io(err: io::Error) {
+            description(err.description())
+            from()
+            cause(err)
+        }
+    }
+}
+
+/// Sequence name and coordinate rewrite.
+///
+/// Sequences without a new name are renamed using the aliases of the rewrite, if any, or
+/// otherwise keep their names. Sequences without an offset keep their coordinates.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SeqRewrite {
+    names: HashMap<String, String>,
+    offsets: HashMap<String, i64>,
+    aliases: Option<SeqAliases>,
+}
+
+impl SeqRewrite {
+
+    /// Creates an empty rewrite, which leaves all sequences unchanged.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a rewrite from a table.
+    ///
+    /// Each nonempty line of the table consists of the original sequence name, its new name,
+    /// and optionally the offset added to its coordinates, separated by tabs. Lines starting
+    /// with `#` are ignored.
+    pub fn from_reader<R: io::Read>(in_reader: R) -> ::Result<Self> {
+        let mut rewrite = SeqRewrite::new();
+        for line in BufReader::new(in_reader).lines() {
+            let line = line.map_err(|e| ::Error::from(RewriteError::from(e)))?;
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let cols = line.split('\t').collect::<Vec<&str>>();
+            let offset = match cols.get(2) {
+                Some(offset) => offset.trim().parse::<i64>().ok(),
+                None => Some(0),
+            };
+            match (cols.len(), offset) {
+                (2, Some(offset)) | (3, Some(offset)) if !cols[0].is_empty() => {
+                    let _ = rewrite.rename(cols[0], cols[1]).offset(cols[0], offset);
+                },
+                _ => return Err(::Error::from(RewriteError::InvalidTableLine(line.clone()))),
+            }
+        }
+        Ok(rewrite)
+    }
+
+    /// Creates a rewrite from a table file, which may be gzip-compressed.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> ::Result<Self> {
+        let file = open_path(path).map_err(|e| ::Error::from(RewriteError::from(e)))?;
+        SeqRewrite::from_reader(file)
+    }
+
+    /// Sets the new name of the given sequence.
+    pub fn rename<S, T>(&mut self, seq_name: S, new_name: T) -> &mut Self
+        where S: Into<String>, T: Into<String>
+    {
+        let _ = self.names.insert(seq_name.into(), new_name.into());
+        self
+    }
+
+    /// Sets the aliases used for renaming sequences without a new name.
+    pub fn aliases(&mut self, aliases: Option<SeqAliases>) -> &mut Self {
+        self.aliases = aliases;
+        self
+    }
+
+    /// Sets the offset added to the coordinates on the given sequence.
+    ///
+    /// The sequence is identified by its original name.
+    pub fn offset<S: Into<String>>(&mut self, seq_name: S, offset: i64) -> &mut Self {
+        let _ = self.offsets.insert(seq_name.into(), offset);
+        self
+    }
+
+    /// Rewrites the given gene, along with its transcripts and exons.
+    ///
+    /// An error is returned if any of the shifted coordinates becomes negative.
+    pub fn rewrite_gene(&self, mut gene: Gene) -> ::Result<Gene> {
+        if let Some((seq_name, offset)) = self.target(gene.seq_name()) {
+            gene.relocate(&seq_name, offset).map_err(::Error::from)?;
+        }
+        Ok(gene)
+    }
+
+    /// Rewrites the given transcript, along with its exons.
+    ///
+    /// An error is returned if any of the shifted coordinates becomes negative.
+    pub fn rewrite_transcript(&self, mut transcript: Transcript) -> ::Result<Transcript> {
+        if let Some((seq_name, offset)) = self.target(transcript.seq_name()) {
+            transcript.relocate(&seq_name, offset).map_err(::Error::from)?;
+        }
+        Ok(transcript)
+    }
+
+    /// Returns the new name and offset of the given sequence, if it is rewritten at all.
+    fn target(&self, seq_name: &str) -> Option<(String, i64)> {
+        let name = self.names.get(seq_name).cloned()
+            .or_else(|| self.aliases.as_ref().map(|aliases| aliases.resolve(seq_name)));
+        let offset = self.offsets.get(seq_name).cloned();
+        if name.is_none() && offset.is_none() {
+            return None;
+        }
+        Some((name.unwrap_or_else(|| seq_name.to_owned()), offset.unwrap_or(0)))
+    }
+}
+
+/// Iterator over the rewritten genes of a stream.
+///
+/// Errors in the underlying gene stream are passed through as-is.
+pub struct RewrittenGenes<I> {
+    genes: I,
+    rewrite: SeqRewrite,
+}
+
+impl<I> RewrittenGenes<I> where I: Iterator<Item=::Result<Gene>> {
+
+    /// Creates an iterator over the given genes, rewritten using the given rewrite.
+    pub fn new<T>(genes: T, rewrite: SeqRewrite) -> Self
+        where T: IntoIterator<Item=::Result<Gene>, IntoIter=I>
+    {
+        RewrittenGenes {
+            genes: genes.into_iter(),
+            rewrite: rewrite,
+        }
+    }
+}
+
+impl<I> Iterator for RewrittenGenes<I> where I: Iterator<Item=::Result<Gene>> {
+
+    type Item = ::Result<Gene>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let rewrite = &self.rewrite;
+        self.genes.next()
+            .map(|result| result.and_then(|gx| rewrite.rewrite_gene(gx)))
+    }
+}
+
+/// Iterator over the rewritten transcripts of a stream.
+///
+/// Errors in the underlying transcript stream are passed through as-is.
+pub struct RewrittenTranscripts<I> {
+    transcripts: I,
+    rewrite: SeqRewrite,
+}
+
+impl<I> RewrittenTranscripts<I> where I: Iterator<Item=::Result<Transcript>> {
+
+    /// Creates an iterator over the given transcripts, rewritten using the given rewrite.
+    pub fn new<T>(transcripts: T, rewrite: SeqRewrite) -> Self
+        where T: IntoIterator<Item=::Result<Transcript>, IntoIter=I>
+    {
+        RewrittenTranscripts {
+            transcripts: transcripts.into_iter(),
+            rewrite: rewrite,
+        }
+    }
+}
+
+impl<I> Iterator for RewrittenTranscripts<I> where I: Iterator<Item=::Result<Transcript>> {
+
+    type Item = ::Result<Transcript>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let rewrite = &self.rewrite;
+        self.transcripts.next()
+            .map(|result| result.and_then(|trx| rewrite.rewrite_transcript(trx)))
+    }
+}
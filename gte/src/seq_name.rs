@@ -0,0 +1,141 @@
+/*! Shared sequence names.
+
+Genes, transcripts, and exons all store the name of the sequence they are located on. Since an
+annotation usually has only a few distinct sequence names shared by millions of features, the
+names are stored as `SeqName`s, which share one allocation among all of their clones. A
+`SeqNamePool` can be used to make names created separately, such as those of different rows of
+an input file, share their allocations as well.
+
+*/
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::Deref;
+use std::sync::Arc;
+
+
+/// Sequence name whose clones share the same allocation.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SeqName(Arc<String>);
+
+impl SeqName {
+
+    /// Returns the name as a string slice.
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+
+    /// Returns whether the given name shares its allocation with this name.
+    pub fn ptr_eq(&self, other: &SeqName) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Deref for SeqName {
+
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl AsRef<str> for SeqName {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl fmt::Display for SeqName {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl From<String> for SeqName {
+    fn from(name: String) -> Self {
+        SeqName(Arc::new(name))
+    }
+}
+
+impl<'a> From<&'a str> for SeqName {
+    fn from(name: &'a str) -> Self {
+        SeqName(Arc::new(name.to_owned()))
+    }
+}
+
+impl<'a> From<&'a String> for SeqName {
+    fn from(name: &'a String) -> Self {
+        SeqName(Arc::new(name.clone()))
+    }
+}
+
+impl<'a> From<&'a SeqName> for SeqName {
+    fn from(name: &'a SeqName) -> Self {
+        name.clone()
+    }
+}
+
+impl PartialEq<str> for SeqName {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl<'a> PartialEq<&'a str> for SeqName {
+    fn eq(&self, other: &&'a str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+#[cfg(feature = "serde_impl")]
+impl ::serde::Serialize for SeqName {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where S: ::serde::Serializer
+    {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+#[cfg(feature = "serde_impl")]
+impl<'de> ::serde::Deserialize<'de> for SeqName {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where D: ::serde::Deserializer<'de>
+    {
+        let name: String = ::serde::Deserialize::deserialize(deserializer)?;
+        Ok(SeqName::from(name))
+    }
+}
+
+/// Pool of sequence names, which returns names sharing the same allocation for equal inputs.
+#[derive(Debug, Clone, Default)]
+pub struct SeqNamePool {
+    names: HashMap<String, SeqName>,
+}
+
+impl SeqNamePool {
+
+    /// Creates an empty pool.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the pooled name equal to the given name, adding it to the pool if needed.
+    pub fn intern(&mut self, name: &str) -> SeqName {
+        if let Some(pooled) = self.names.get(name) {
+            return pooled.clone();
+        }
+        let pooled = SeqName::from(name);
+        let _ = self.names.insert(name.to_owned(), pooled.clone());
+        pooled
+    }
+
+    /// Returns the number of distinct names in the pool.
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    /// Returns whether the pool is empty.
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+}
@@ -0,0 +1,319 @@
+/*! Extraction and translation of transcript sequences from genomic sequence sources.
+
+*/
+use std::collections::HashMap;
+use std::error::Error;
+use std::io;
+
+use bio::io::fasta;
+
+use {Coord, Strand, Transcript, DEF_ID};
+use utils::OptionDeref;
+
+
+quick_error! {
+    /// Errors that occur when extracting sequences.
+    #[derive(Debug)]
+    pub enum SequenceError {
+        /// Occurs when the sequence source does not contain the requested sequence.
+        MissingSequence(seq_name: String) {
+            description("sequence not found in source")
+            display(self_) -> ("{}: '{}'", self_.description(), seq_name)
+        }
+        /// Occurs when the requested region extends past the end of its sequence.
+        RegionOutOfBounds(seq_name: String, start: u64, end: u64) {
+            description("region extends past the end of its sequence")
+            display(self_) -> ("{}: {}:{}-{}", self_.description(), seq_name, start, end)
+        }
+        /// Occurs when a coding sequence is requested from a transcript with no coding region.
+        NoCodingRegion(tid: Option<String>) {
+            description("transcript has no coding region")
+            display(self_) -> ("{}, transcript ID: {}",
+                               self_.description(), tid.as_deref().unwrap_or(DEF_ID))
+        }
+        /// Occurs when a stop codon is found before the end of a coding sequence.
+        InternalStopCodon(codon_num: usize, tid: Option<String>) {
+            description("internal stop codon in coding sequence")
+            display(self_) -> ("{}, codon number: {}, transcript ID: {}",
+                               self_.description(), codon_num, tid.as_deref().unwrap_or(DEF_ID))
+        }
+        /// Errors propagated from reading the underlying sequence source.
+        Io(err: io::Error) {
+            description(err.description())
+            from()
+            cause(err)
+        }
+    }
+}
+
+/// Amino acids encoded by each codon of the standard code, in NCBI order.
+///
+/// Codons are ordered by their first, second, and then third base, using the `TCAG` base order.
+const STANDARD_AAS: &'static [u8; 64] =
+    b"FFLLSSSSYY**CC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG";
+
+/// Start codons of the standard code, in NCBI order.
+const STANDARD_STARTS: &'static [u8; 64] =
+    b"---M---------------M---------------M----------------------------";
+
+/// Amino acids encoded by each codon of the vertebrate mitochondrial code, in NCBI order.
+const VERT_MITO_AAS: &'static [u8; 64] =
+    b"FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIMMTTTTNNKKSS**VVVVAAAADDEEGGGG";
+
+/// Start codons of the vertebrate mitochondrial code, in NCBI order.
+const VERT_MITO_STARTS: &'static [u8; 64] =
+    b"--------------------------------MMMM---------------M------------";
+
+/// Start codons of the bacterial, archaeal, and plant plastid code, in NCBI order.
+const BACTERIAL_STARTS: &'static [u8; 64] =
+    b"---M---------------M------------MMMM---------------M------------";
+
+/// Genetic code used for translating coding sequences.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CodonTable {
+    amino_acids: &'static [u8; 64],
+    starts: &'static [u8; 64],
+}
+
+impl CodonTable {
+
+    /// Returns the standard code (NCBI table 1).
+    pub fn standard() -> Self {
+        CodonTable {
+            amino_acids: STANDARD_AAS,
+            starts: STANDARD_STARTS,
+        }
+    }
+
+    /// Returns the vertebrate mitochondrial code (NCBI table 2).
+    pub fn vertebrate_mitochondrial() -> Self {
+        CodonTable {
+            amino_acids: VERT_MITO_AAS,
+            starts: VERT_MITO_STARTS,
+        }
+    }
+
+    /// Returns the bacterial, archaeal, and plant plastid code (NCBI table 11).
+    pub fn bacterial() -> Self {
+        CodonTable {
+            amino_acids: STANDARD_AAS,
+            starts: BACTERIAL_STARTS,
+        }
+    }
+
+    /// Returns the code with the given NCBI translation table identifier, if supported.
+    pub fn from_ncbi_id(id: u8) -> Option<Self> {
+        match id {
+            1 => Some(Self::standard()),
+            2 => Some(Self::vertebrate_mitochondrial()),
+            11 => Some(Self::bacterial()),
+            _ => None,
+        }
+    }
+
+    /// Returns the amino acid encoded by the given codon.
+    ///
+    /// Stop codons are translated as `*`, while codons containing bases other than `A`, `C`,
+    /// `G`, `T`, or `U` (in any case) are translated as `X`.
+    pub fn translate_codon(&self, codon: &[u8]) -> u8 {
+        codon_index(codon).map(|idx| self.amino_acids[idx]).unwrap_or(b'X')
+    }
+
+    /// Returns whether the given codon is a start codon.
+    pub fn is_start(&self, codon: &[u8]) -> bool {
+        codon_index(codon).map(|idx| self.starts[idx] == b'M').unwrap_or(false)
+    }
+}
+
+impl Default for CodonTable {
+    fn default() -> Self {
+        Self::standard()
+    }
+}
+
+/// Returns the index of the given codon in the NCBI codon order.
+#[inline]
+fn codon_index(codon: &[u8]) -> Option<usize> {
+    if codon.len() != 3 {
+        return None;
+    }
+    let mut idx = 0;
+    for base in codon.iter() {
+        let value = match *base {
+            b'T' | b't' | b'U' | b'u' => 0,
+            b'C' | b'c' => 1,
+            b'A' | b'a' => 2,
+            b'G' | b'g' => 3,
+            _ => return None,
+        };
+        idx = idx * 4 + value;
+    }
+    Some(idx)
+}
+
+/// Trait for sources of genomic sequences.
+///
+/// This is implemented for indexed FASTA readers, and for in-memory maps of sequence names to
+/// their sequences.
+pub trait SequenceSource {
+
+    /// Reads the bases of the given zero-based, half-open region of a sequence into `seq`,
+    /// replacing its previous contents.
+    fn read_region(&mut self, seq_name: &str, start: u64, end: u64, seq: &mut Vec<u8>)
+        -> ::Result<()>;
+}
+
+impl<R: io::Read + io::Seek> SequenceSource for fasta::IndexedReader<R> {
+
+    fn read_region(&mut self, seq_name: &str, start: u64, end: u64, seq: &mut Vec<u8>)
+        -> ::Result<()>
+    {
+        self.read(seq_name, start, end, seq)
+            .map_err(|e| ::Error::from(SequenceError::from(e)))?;
+        if seq.len() as u64 != end - start {
+            let err = SequenceError::RegionOutOfBounds(seq_name.to_owned(), start, end);
+            return Err(::Error::from(err));
+        }
+        Ok(())
+    }
+}
+
+impl SequenceSource for HashMap<String, Vec<u8>> {
+
+    fn read_region(&mut self, seq_name: &str, start: u64, end: u64, seq: &mut Vec<u8>)
+        -> ::Result<()>
+    {
+        let full = self.get(seq_name)
+            .ok_or_else(|| SequenceError::MissingSequence(seq_name.to_owned()))?;
+        if start > end || end > full.len() as u64 {
+            let err = SequenceError::RegionOutOfBounds(seq_name.to_owned(), start, end);
+            return Err(::Error::from(err));
+        }
+        seq.clear();
+        seq.extend_from_slice(&full[start as usize..end as usize]);
+        Ok(())
+    }
+}
+
+impl Transcript {
+
+    /// Returns the sequence of the transcript with its introns spliced out.
+    ///
+    /// The sequence is read from the transcript-wise 5' end, so it is reverse complemented for
+    /// reverse strand transcripts.
+    pub fn spliced_sequence<S: SequenceSource>(&self, source: &mut S) -> ::Result<Vec<u8>> {
+        let segments = self.exons().iter()
+            .map(|exn| (exn.start(), exn.end()))
+            .collect();
+        self.read_segments(source, segments)
+    }
+
+    /// Returns the sequence of the coding region of the transcript.
+    ///
+    /// Whether the stop codon is included in the sequence is set by the `incl_stop` argument.
+    /// As with `spliced_sequence`, the sequence is reverse complemented for reverse strand
+    /// transcripts. An error is returned if the transcript has no coding region.
+    pub fn cds_sequence<S: SequenceSource>(&self, source: &mut S, incl_stop: bool)
+        -> ::Result<Vec<u8>>
+    {
+        let segments = self.coding_segments(incl_stop);
+        if segments.is_empty() {
+            let err = SequenceError::NoCodingRegion(self.id().map(|v| v.to_owned()));
+            return Err(::Error::from(err));
+        }
+        self.read_segments(source, segments)
+    }
+
+    /// Returns the amino acid sequence encoded by the coding region of the transcript.
+    ///
+    /// The coding sequence, excluding the stop codon, is translated using the given codon table.
+    /// Bases of a preceding partial codon, as set by the frame of the transcript-wise 5'-most
+    /// CDS, are skipped, and so are the bases of any trailing partial codon. A complete first
+    /// codon that is a start codon in the table is always translated as methionine.
+    ///
+    /// An error is returned if the transcript has no coding region or if the translation
+    /// contains a stop codon. Transcripts with stop codon readthrough, such as selenoproteins,
+    /// instead have their internal `UGA` codons translated as selenocysteine (`U`) and any other
+    /// internal stop codons translated as `X`.
+    pub fn translate<S: SequenceSource>(&self, source: &mut S, table: &CodonTable)
+        -> ::Result<Vec<u8>>
+    {
+        let phase = self.coding_phase() as usize;
+        let cds = self.cds_sequence(source, false)?;
+        let codons: &[u8] = if phase < cds.len() { &cds[phase..] } else { &[] };
+
+        let mut protein = Vec::with_capacity(codons.len() / 3);
+        for (idx, codon) in codons.chunks(3).filter(|c| c.len() == 3).enumerate() {
+            let aa =
+                if idx == 0 && phase == 0 && table.is_start(codon) {
+                    b'M'
+                } else {
+                    table.translate_codon(codon)
+                };
+            if aa == b'*' {
+                if self.stop_readthrough() {
+                    protein.push(if is_uga(codon) { b'U' } else { b'X' });
+                    continue;
+                }
+                let err = SequenceError::InternalStopCodon(
+                    idx + 1, self.id().map(|v| v.to_owned()));
+                return Err(::Error::from(err));
+            }
+            protein.push(aa);
+        }
+        Ok(protein)
+    }
+
+    /// Reads and concatenates the given genome-wise sorted segments of the transcript sequence.
+    fn read_segments<S: SequenceSource>(&self, source: &mut S, segments: Vec<Coord<u64>>)
+        -> ::Result<Vec<u8>>
+    {
+        let mut res = Vec::with_capacity(segments.iter().map(|&(s, e)| (e - s) as usize).sum());
+        let mut buf = Vec::new();
+        for (start, end) in segments {
+            source.read_region(self.seq_name(), start, end, &mut buf)?;
+            res.extend_from_slice(&buf);
+        }
+        if *self.strand() == Strand::Reverse {
+            res = reverse_complement(&res);
+        }
+        Ok(res)
+    }
+}
+
+/// Returns whether the given codon is `UGA` (or `TGA`), in any case.
+#[inline]
+fn is_uga(codon: &[u8]) -> bool {
+    // setting the sixth bit turns uppercase letters into lowercase ones
+    let lower = |base: u8| base | 0x20;
+    codon.len() == 3 && (lower(codon[0]) == b't' || lower(codon[0]) == b'u') &&
+        lower(codon[1]) == b'g' && lower(codon[2]) == b'a'
+}
+
+/// Returns the complement of the given nucleotide, preserving its case.
+///
+/// IUPAC ambiguity codes are complemented accordingly, while other characters are returned
+/// as-is.
+#[inline]
+pub(crate) fn complement(base: u8) -> u8 {
+    match base {
+        b'A' => b'T', b'a' => b't',
+        b'C' => b'G', b'c' => b'g',
+        b'G' => b'C', b'g' => b'c',
+        b'T' | b'U' => b'A', b't' | b'u' => b'a',
+        b'R' => b'Y', b'r' => b'y',
+        b'Y' => b'R', b'y' => b'r',
+        b'K' => b'M', b'k' => b'm',
+        b'M' => b'K', b'm' => b'k',
+        b'B' => b'V', b'b' => b'v',
+        b'V' => b'B', b'v' => b'b',
+        b'D' => b'H', b'd' => b'h',
+        b'H' => b'D', b'h' => b'd',
+        other => other,
+    }
+}
+
+/// Returns the reverse complement of the given nucleotide sequence.
+pub fn reverse_complement(seq: &[u8]) -> Vec<u8> {
+    seq.iter().rev().map(|&base| complement(base)).collect()
+}
@@ -0,0 +1,154 @@
+/*! Coordinate sorting of genes and transcripts with configurable sequence ordering.
+
+Features are sorted by their sequence names first, using one of the orderings of `SeqOrder`, and
+then by their start and end coordinates.
+*/
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use {Gene, SeqDict, Transcript};
+
+
+/// Ordering of sequence names.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SeqOrder {
+    /// Byte-wise ordering, so that `chr10` comes before `chr2`.
+    Lexicographic,
+    /// Natural ordering, in which runs of digits are compared by their numeric values, so that
+    /// `chr2` comes before `chr10`.
+    Natural,
+    /// Karyotype ordering, in which numbered chromosomes come first, followed by the X, Y, and
+    /// mitochondrial chromosomes, and then all other sequences in natural order. The `chr`
+    /// prefix is ignored.
+    Karyotype,
+    /// Ordering of the sequences of a sequence dictionary, such as those read from chrom.sizes
+    /// or Picard dictionary files. Sequences absent from the dictionary come last, in natural
+    /// order.
+    Custom(HashMap<String, usize>),
+}
+
+impl SeqOrder {
+
+    /// Creates an ordering from the sequences of the given dictionary.
+    pub fn from_seq_dict(dict: &SeqDict) -> Self {
+        let ranks = dict.seqs().iter()
+            .enumerate()
+            .map(|(idx, &(ref name, _))| (name.clone(), idx))
+            .collect();
+        SeqOrder::Custom(ranks)
+    }
+
+    /// Compares the given sequence names.
+    pub fn compare(&self, a: &str, b: &str) -> Ordering {
+        match *self {
+            SeqOrder::Lexicographic => a.cmp(b),
+            SeqOrder::Natural => natural_cmp(a, b),
+            SeqOrder::Karyotype => {
+                karyotype_rank(a).cmp(&karyotype_rank(b))
+                    .then_with(|| natural_cmp(a, b))
+            },
+            SeqOrder::Custom(ref ranks) => {
+                let rank = |name: &str| ranks.get(name).cloned().unwrap_or(usize::max_value());
+                rank(a).cmp(&rank(b))
+                    .then_with(|| natural_cmp(a, b))
+            },
+        }
+    }
+}
+
+impl Default for SeqOrder {
+    fn default() -> Self {
+        SeqOrder::Natural
+    }
+}
+
+/// Sorts the given genes by their sequence names and coordinates.
+pub fn sort_genes(genes: &mut [Gene], order: &SeqOrder) {
+    genes.sort_by(|a, b| {
+        order.compare(a.seq_name(), b.seq_name())
+            .then_with(|| (a.start(), a.end()).cmp(&(b.start(), b.end())))
+    })
+}
+
+/// Sorts the given transcripts by their sequence names and coordinates.
+pub fn sort_transcripts(transcripts: &mut [Transcript], order: &SeqOrder) {
+    transcripts.sort_by(|a, b| {
+        order.compare(a.seq_name(), b.seq_name())
+            .then_with(|| (a.start(), a.end()).cmp(&(b.start(), b.end())))
+    })
+}
+
+/// Collects the given gene stream into a vector of genes sorted by their sequence names and
+/// coordinates.
+///
+/// The first error of the stream, if any, is returned instead.
+pub fn sorted_genes<T>(genes: T, order: &SeqOrder) -> ::Result<Vec<Gene>>
+    where T: IntoIterator<Item=::Result<Gene>>
+{
+    let mut collected = genes.into_iter().collect::<::Result<Vec<Gene>>>()?;
+    sort_genes(&mut collected, order);
+    Ok(collected)
+}
+
+/// Compares the given strings, comparing runs of digits by their numeric values.
+fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let (mut ait, mut bit) = (a.as_bytes().iter().peekable(), b.as_bytes().iter().peekable());
+    loop {
+        match (ait.peek().cloned(), bit.peek().cloned()) {
+            (None, None) => return a.cmp(b),
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(&ac), Some(&bc)) if is_digit(ac) && is_digit(bc) => {
+                let mut anum = Vec::new();
+                while let Some(&&c) = ait.peek() {
+                    if !is_digit(c) { break; }
+                    anum.push(c);
+                    let _ = ait.next();
+                }
+                let mut bnum = Vec::new();
+                while let Some(&&c) = bit.peek() {
+                    if !is_digit(c) { break; }
+                    bnum.push(c);
+                    let _ = bit.next();
+                }
+                let (atrim, btrim) = (trim_zeros(&anum), trim_zeros(&bnum));
+                let ord = atrim.len().cmp(&btrim.len()).then_with(|| atrim.cmp(btrim));
+                if ord != Ordering::Equal {
+                    return ord;
+                }
+            },
+            (Some(ac), Some(bc)) => {
+                if ac != bc {
+                    return ac.cmp(bc);
+                }
+                let _ = ait.next();
+                let _ = bit.next();
+            },
+        }
+    }
+}
+
+/// Returns whether the given byte is an ASCII digit.
+#[inline]
+fn is_digit(c: u8) -> bool {
+    c >= b'0' && c <= b'9'
+}
+
+/// Removes the leading zeros of the given digits.
+fn trim_zeros(digits: &[u8]) -> &[u8] {
+    let start = digits.iter().position(|&d| d != b'0').unwrap_or(digits.len());
+    &digits[start..]
+}
+
+/// Returns the karyotype rank of the given sequence name.
+fn karyotype_rank(name: &str) -> u32 {
+    let bare = if name.starts_with("chr") { &name[3..] } else { name };
+    match bare {
+        "X" => 1001,
+        "Y" => 1002,
+        "M" | "MT" => 1003,
+        other => other.parse::<u32>().ok()
+            .and_then(|num| if num > 0 && num <= 1000 { Some(num) } else { None })
+            .unwrap_or(u32::max_value()),
+    }
+}
@@ -0,0 +1,471 @@
+/*! Summary statistics of gene annotations.
+
+`AnnotationStats` counts the genes, transcripts, and exons of an annotation, along with their
+biotypes and their totals per sequence, and collects the length distributions of exons,
+introns, and coding regions. Statistics are gathered one gene at a time, so they can be
+computed from gene streams of any reader.
+
+`GeneMetrics` and `TranscriptMetrics` hold the metrics of single genes and transcripts, such as
+their exonic, coding, and UTR lengths, which `MetricsWriter` writes as TSV rows. With the
+`serde_impl` feature enabled, the statistics and metrics types also implement serde's
+`Serialize` and `Deserialize` traits, for example for writing them as JSON.
+*/
+use std::cmp::{max, min};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
+use itertools::Itertools;
+use linked_hash_map::LinkedHashMap;
+
+use {Gene, Strand, Transcript};
+
+
+/// Placeholder of undefined values, also the key of features without biotypes in biotype
+/// counts.
+const UNK_STR: &'static str = ".";
+
+quick_error! {
+    /// Errors that occur when writing metrics.
+    #[derive(Debug)]
+    pub enum StatsError {
+        /// Errors propagated from writing the underlying stream.
+        Io(err: io::Error) {
+            description(err.description())
+            from()
+            cause(err)
+        }
+    }
+}
+
+
+/// Distribution of feature lengths.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde_impl", derive(Serialize, Deserialize))]
+pub struct LengthStats {
+    lengths: Vec<u64>,
+    total: u64,
+}
+
+impl LengthStats {
+
+    /// Creates an empty distribution.
+    pub fn new() -> Self {
+        LengthStats::default()
+    }
+
+    /// Adds the given length to the distribution.
+    pub fn push(&mut self, length: u64) {
+        self.lengths.push(length);
+        self.total += length;
+    }
+
+    /// Returns the number of lengths.
+    pub fn count(&self) -> usize {
+        self.lengths.len()
+    }
+
+    /// Returns the sum of all lengths.
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    /// Returns the smallest length.
+    pub fn min(&self) -> Option<u64> {
+        self.lengths.iter().cloned().min()
+    }
+
+    /// Returns the largest length.
+    pub fn max(&self) -> Option<u64> {
+        self.lengths.iter().cloned().max()
+    }
+
+    /// Returns the mean length.
+    pub fn mean(&self) -> Option<f64> {
+        if self.lengths.is_empty() {
+            None
+        } else {
+            Some(self.total as f64 / self.lengths.len() as f64)
+        }
+    }
+
+    /// Returns the median length.
+    pub fn median(&self) -> Option<u64> {
+        self.quantile(0.5)
+    }
+
+    /// Returns the length at the given quantile, which is clamped to the `[0, 1]` range, using
+    /// the nearest-rank method.
+    ///
+    /// The lengths are sorted on every call.
+    pub fn quantile(&self, q: f64) -> Option<u64> {
+        if self.lengths.is_empty() {
+            return None;
+        }
+        let mut sorted = self.lengths.clone();
+        sorted.sort();
+        let q = q.max(0.0).min(1.0);
+        let rank = (q * sorted.len() as f64).ceil() as usize;
+        Some(sorted[max(rank, 1) - 1])
+    }
+}
+
+/// Feature counts of a single sequence.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde_impl", derive(Serialize, Deserialize))]
+pub struct SeqStats {
+    /// Number of genes.
+    pub genes: u64,
+    /// Number of transcripts.
+    pub transcripts: u64,
+    /// Number of exons.
+    pub exons: u64,
+}
+
+/// Summary statistics of genes, their transcripts, and their exons.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde_impl", derive(Serialize, Deserialize))]
+pub struct AnnotationStats {
+    num_genes: u64,
+    num_transcripts: u64,
+    num_coding_transcripts: u64,
+    num_exons: u64,
+    gene_biotypes: BTreeMap<String, u64>,
+    transcript_biotypes: BTreeMap<String, u64>,
+    exon_lengths: LengthStats,
+    intron_lengths: LengthStats,
+    cds_lengths: LengthStats,
+    seqs: LinkedHashMap<String, SeqStats>,
+}
+
+impl AnnotationStats {
+
+    /// Creates empty statistics.
+    pub fn new() -> Self {
+        AnnotationStats::default()
+    }
+
+    /// Computes the statistics of the given gene stream.
+    ///
+    /// The first error of the stream, if any, is returned instead.
+    pub fn from_genes<T>(genes: T) -> ::Result<Self>
+        where T: IntoIterator<Item=::Result<Gene>>
+    {
+        let mut stats = AnnotationStats::new();
+        for gene in genes {
+            stats.add_gene(&gene?);
+        }
+        Ok(stats)
+    }
+
+    /// Adds the given gene, its transcripts, and their exons to the statistics.
+    pub fn add_gene(&mut self, gene: &Gene) {
+        self.num_genes += 1;
+        *self.gene_biotypes.entry(biotype_key(gene.biotype())).or_insert(0) += 1;
+        self.seq_stats(gene.seq_name()).genes += 1;
+        for transcript in gene.transcripts().values() {
+            self.add_transcript(transcript);
+        }
+    }
+
+    /// Adds the given transcript and its exons to the statistics.
+    pub fn add_transcript(&mut self, transcript: &Transcript) {
+        self.num_transcripts += 1;
+        self.num_exons += transcript.exons().len() as u64;
+        *self.transcript_biotypes.entry(biotype_key(transcript.biotype())).or_insert(0) += 1;
+        {
+            let seq_stats = self.seq_stats(transcript.seq_name());
+            seq_stats.transcripts += 1;
+            seq_stats.exons += transcript.exons().len() as u64;
+        }
+        for exon in transcript.exons() {
+            self.exon_lengths.push(exon.span());
+        }
+        for intron in transcript.introns() {
+            self.intron_lengths.push(intron.span());
+        }
+        let segments = transcript.coding_segments(true);
+        if !segments.is_empty() {
+            self.num_coding_transcripts += 1;
+            self.cds_lengths.push(segments.iter().map(|&(start, end)| end - start).sum::<u64>());
+        }
+    }
+
+    /// Returns the number of genes.
+    pub fn num_genes(&self) -> u64 {
+        self.num_genes
+    }
+
+    /// Returns the number of transcripts.
+    pub fn num_transcripts(&self) -> u64 {
+        self.num_transcripts
+    }
+
+    /// Returns the number of transcripts with coding regions.
+    pub fn num_coding_transcripts(&self) -> u64 {
+        self.num_coding_transcripts
+    }
+
+    /// Returns the number of exons.
+    pub fn num_exons(&self) -> u64 {
+        self.num_exons
+    }
+
+    /// Returns the number of genes of each biotype, with genes without biotypes counted under
+    /// `.`.
+    pub fn gene_biotypes(&self) -> &BTreeMap<String, u64> {
+        &self.gene_biotypes
+    }
+
+    /// Returns the number of transcripts of each biotype, with transcripts without biotypes
+    /// counted under `.`.
+    pub fn transcript_biotypes(&self) -> &BTreeMap<String, u64> {
+        &self.transcript_biotypes
+    }
+
+    /// Returns the distribution of exon lengths.
+    pub fn exon_lengths(&self) -> &LengthStats {
+        &self.exon_lengths
+    }
+
+    /// Returns the distribution of intron lengths.
+    pub fn intron_lengths(&self) -> &LengthStats {
+        &self.intron_lengths
+    }
+
+    /// Returns the distribution of the coding region lengths of transcripts, including their
+    /// stop codons.
+    pub fn cds_lengths(&self) -> &LengthStats {
+        &self.cds_lengths
+    }
+
+    /// Returns the feature counts of each sequence, in the order the sequences were first
+    /// seen.
+    pub fn seqs(&self) -> &LinkedHashMap<String, SeqStats> {
+        &self.seqs
+    }
+
+    fn seq_stats(&mut self, seq_name: &str) -> &mut SeqStats {
+        if !self.seqs.contains_key(seq_name) {
+            let _ = self.seqs.insert(seq_name.to_owned(), SeqStats::default());
+        }
+        self.seqs.get_mut(seq_name).unwrap()
+    }
+}
+
+/// Metrics of a single transcript.
+///
+/// Coding and UTR lengths include the stop codon in the coding region. UTR lengths are only
+/// defined for coding transcripts with known strands.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde_impl", derive(Serialize, Deserialize))]
+pub struct TranscriptMetrics {
+    /// Transcript identifier.
+    pub transcript_id: Option<String>,
+    /// Gene identifier.
+    pub gene_id: Option<String>,
+    /// Sequence name.
+    pub seq_name: String,
+    /// Zero-based start coordinate.
+    pub start: u64,
+    /// End coordinate.
+    pub end: u64,
+    /// Strand, as `+`, `-`, or `.`.
+    pub strand: char,
+    /// Number of exons.
+    pub num_exons: usize,
+    /// Number of bases covered by the exons.
+    pub exonic_length: u64,
+    /// Number of coding bases, if the transcript is coding.
+    pub cds_length: Option<u64>,
+    /// Number of exonic bases upstream of the coding region.
+    pub utr5_length: Option<u64>,
+    /// Number of exonic bases downstream of the coding region.
+    pub utr3_length: Option<u64>,
+    /// Lengths of the introns, from the transcript-wise 5' end.
+    pub intron_lengths: Vec<u64>,
+}
+
+impl TranscriptMetrics {
+
+    /// Computes the metrics of the given transcript.
+    pub fn from_transcript(transcript: &Transcript) -> Self {
+        let exon_coords = transcript.exons().iter()
+            .map(|exn| (exn.start(), exn.end()))
+            .collect::<Vec<(u64, u64)>>();
+        let segments = transcript.coding_segments(true);
+        let cds_length =
+            if segments.is_empty() {
+                None
+            } else {
+                Some(segments.iter().map(|&(start, end)| end - start).sum::<u64>())
+            };
+        let (utr5_length, utr3_length) =
+            match (transcript.coding_coord(true), transcript.strand()) {
+                (Some((cstart, cend)), &Strand::Forward) =>
+                    (Some(overlap(&exon_coords, 0, cstart)),
+                     Some(overlap(&exon_coords, cend, u64::max_value()))),
+                (Some((cstart, cend)), &Strand::Reverse) =>
+                    (Some(overlap(&exon_coords, cend, u64::max_value())),
+                     Some(overlap(&exon_coords, 0, cstart))),
+                _ => (None, None),
+            };
+        TranscriptMetrics {
+            transcript_id: transcript.id().map(|v| v.to_owned()),
+            gene_id: transcript.gene_id().map(|v| v.to_owned()),
+            seq_name: transcript.seq_name().to_owned(),
+            start: transcript.start(),
+            end: transcript.end(),
+            strand: strand_to_char(transcript.strand()),
+            num_exons: exon_coords.len(),
+            exonic_length: overlap(&exon_coords, 0, u64::max_value()),
+            cds_length: cds_length,
+            utr5_length: utr5_length,
+            utr3_length: utr3_length,
+            intron_lengths: transcript.introns().map(|intron| intron.span()).collect(),
+        }
+    }
+}
+
+/// Metrics of a single gene.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde_impl", derive(Serialize, Deserialize))]
+pub struct GeneMetrics {
+    /// Gene identifier.
+    pub gene_id: Option<String>,
+    /// Sequence name.
+    pub seq_name: String,
+    /// Zero-based start coordinate.
+    pub start: u64,
+    /// End coordinate.
+    pub end: u64,
+    /// Strand, as `+`, `-`, or `.`.
+    pub strand: char,
+    /// Number of transcripts.
+    pub num_transcripts: usize,
+    /// Number of coding transcripts.
+    pub num_coding_transcripts: usize,
+    /// Number of bases covered by the exons of any of the transcripts.
+    pub exonic_length: u64,
+    /// Largest number of coding bases of any of the transcripts, if any of them is coding.
+    pub max_cds_length: Option<u64>,
+    /// Metrics of the transcripts.
+    pub transcripts: Vec<TranscriptMetrics>,
+}
+
+impl GeneMetrics {
+
+    /// Computes the metrics of the given gene and its transcripts.
+    pub fn from_gene(gene: &Gene) -> Self {
+        let transcripts = gene.transcripts().values()
+            .map(TranscriptMetrics::from_transcript)
+            .collect::<Vec<TranscriptMetrics>>();
+        GeneMetrics {
+            gene_id: gene.id().map(|v| v.to_owned()),
+            seq_name: gene.seq_name().to_owned(),
+            start: gene.start(),
+            end: gene.end(),
+            strand: strand_to_char(gene.strand()),
+            num_transcripts: transcripts.len(),
+            num_coding_transcripts: transcripts.iter()
+                .filter(|trx| trx.cds_length.is_some())
+                .count(),
+            exonic_length: gene.exonic_length(),
+            max_cds_length: transcripts.iter().filter_map(|trx| trx.cds_length).max(),
+            transcripts: transcripts,
+        }
+    }
+}
+
+/// Writer of gene and transcript metrics as tab-separated rows.
+///
+/// Undefined values are written as `.`, and intron lengths as comma-separated lists.
+pub struct MetricsWriter<W: Write> {
+    inner: W,
+}
+
+impl<W: Write> MetricsWriter<W> {
+
+    /// Creates a metrics writer from another writer.
+    pub fn from_writer(in_writer: W) -> MetricsWriter<W> {
+        MetricsWriter { inner: in_writer }
+    }
+
+    /// Writes the header line of transcript metrics.
+    pub fn write_transcript_header(&mut self) -> ::Result<()> {
+        writeln!(self.inner, "transcript_id\tgene_id\tseq_name\tstart\tend\tstrand\t\
+                              num_exons\texonic_length\tcds_length\tutr5_length\t\
+                              utr3_length\tintron_lengths")
+            .map_err(|e| ::Error::from(StatsError::from(e)))
+    }
+
+    /// Writes the given transcript metrics.
+    pub fn write_transcript_metrics(&mut self, metrics: &TranscriptMetrics) -> ::Result<()> {
+        writeln!(self.inner, "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+                 opt_field(&metrics.transcript_id), opt_field(&metrics.gene_id),
+                 metrics.seq_name, metrics.start, metrics.end, metrics.strand,
+                 metrics.num_exons, metrics.exonic_length, opt_field(&metrics.cds_length),
+                 opt_field(&metrics.utr5_length), opt_field(&metrics.utr3_length),
+                 metrics.intron_lengths.iter().join(","))
+            .map_err(|e| ::Error::from(StatsError::from(e)))
+    }
+
+    /// Writes the header line of gene metrics.
+    pub fn write_gene_header(&mut self) -> ::Result<()> {
+        writeln!(self.inner, "gene_id\tseq_name\tstart\tend\tstrand\tnum_transcripts\t\
+                              num_coding_transcripts\texonic_length\tmax_cds_length")
+            .map_err(|e| ::Error::from(StatsError::from(e)))
+    }
+
+    /// Writes the given gene metrics, without the metrics of its transcripts.
+    pub fn write_gene_metrics(&mut self, metrics: &GeneMetrics) -> ::Result<()> {
+        writeln!(self.inner, "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+                 opt_field(&metrics.gene_id), metrics.seq_name, metrics.start, metrics.end,
+                 metrics.strand, metrics.num_transcripts, metrics.num_coding_transcripts,
+                 metrics.exonic_length, opt_field(&metrics.max_cds_length))
+            .map_err(|e| ::Error::from(StatsError::from(e)))
+    }
+
+    /// Flushes the underlying writer.
+    pub fn flush(&mut self) -> ::Result<()> {
+        self.inner.flush().map_err(|e| ::Error::from(StatsError::from(e)))
+    }
+}
+
+impl MetricsWriter<fs::File> {
+
+    /// Creates a metrics writer that writes to the given path.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let f = fs::File::create(path)?;
+        Ok(MetricsWriter::from_writer(f))
+    }
+}
+
+/// Returns the key of the given biotype in biotype counts.
+fn biotype_key<T: ToString>(biotype: Option<T>) -> String {
+    biotype.map_or_else(|| UNK_STR.to_owned(), |bt| bt.to_string())
+}
+
+/// Returns the number of bases of the given sorted, disjoint coordinates within the given
+/// range.
+fn overlap(coords: &[(u64, u64)], start: u64, end: u64) -> u64 {
+    coords.iter()
+        .map(|&(cstart, cend)| (max(cstart, start), min(cend, end)))
+        .filter(|&(ostart, oend)| ostart < oend)
+        .map(|(ostart, oend)| oend - ostart)
+        .sum()
+}
+
+fn strand_to_char(strand: &Strand) -> char {
+    match strand {
+        &Strand::Forward => '+',
+        &Strand::Reverse => '-',
+        &Strand::Unknown => '.',
+    }
+}
+
+fn opt_field<T: ToString>(value: &Option<T>) -> String {
+    value.as_ref().map_or_else(|| UNK_STR.to_owned(), |v| v.to_string())
+}
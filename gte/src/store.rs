@@ -0,0 +1,300 @@
+/*! Persistent SQLite stores of parsed annotations.
+
+Similar to [gffutils](https://gffutils.readthedocs.io), genes can be parsed once and written
+into a SQLite database, which can then be queried by gene identifier, gene name, transcript
+identifier, or region without parsing the annotation again or holding all of it in memory.
+
+Each gene is stored as a serialized blob, alongside tables of its transcripts and exons that
+are indexed by identifier and by coordinates. Like caches, stores record the format version of
+their serialized genes, so that incompatible stores are rejected instead of being misread.
+Creating and opening stores requires the `sqlite` feature.
+
+*/
+use std::error::Error;
+#[cfg(feature = "sqlite")]
+use std::ops::Range;
+#[cfg(feature = "sqlite")]
+use std::path::Path;
+
+#[cfg(feature = "sqlite")]
+use bincode::{self, Infinite};
+#[cfg(feature = "sqlite")]
+use rusqlite::{self, Connection};
+#[cfg(feature = "sqlite")]
+use rusqlite::types::ToSql;
+
+#[cfg(feature = "sqlite")]
+use {CACHE_VERSION, Exon, Gene, Transcript};
+
+
+quick_error! {
+    /// Errors that occur when writing or querying stores.
+    #[derive(Debug)]
+    pub enum StoreError {
+        /// Occurs when the database does not contain a gte store.
+        InvalidStore {
+            description("database is not a gte store")
+        }
+        /// Occurs when the store was written with an unsupported format version.
+        UnsupportedVersion(version: u32) {
+            description("unsupported store format version")
+            display(self_) -> ("{}: {}", self_.description(), version)
+        }
+        /// Occurs when a stored gene can not be encoded or decoded.
+        Encoding(msg: String) {
+            description("invalid stored gene")
+            display(self_) -> ("{}: {}", self_.description(), msg)
+        }
+        /// Errors propagated from the underlying database.
+        Database(msg: String) {
+            description("database error")
+            display(self_) -> ("{}: {}", self_.description(), msg)
+        }
+    }
+}
+
+/// Statements creating the tables and indices of a new store.
+#[cfg(feature = "sqlite")]
+const SCHEMA: &'static str = "
+    CREATE TABLE meta (key TEXT PRIMARY KEY, value TEXT NOT NULL);
+    CREATE TABLE genes (
+        id INTEGER PRIMARY KEY,
+        gene_id TEXT,
+        name TEXT,
+        seq_name TEXT NOT NULL,
+        start_pos INTEGER NOT NULL,
+        end_pos INTEGER NOT NULL,
+        data BLOB NOT NULL
+    );
+    CREATE TABLE transcripts (
+        id INTEGER PRIMARY KEY,
+        gene INTEGER NOT NULL REFERENCES genes(id),
+        key TEXT NOT NULL,
+        transcript_id TEXT
+    );
+    CREATE TABLE exons (
+        transcript INTEGER NOT NULL REFERENCES transcripts(id),
+        seq_name TEXT NOT NULL,
+        start_pos INTEGER NOT NULL,
+        end_pos INTEGER NOT NULL
+    );
+    CREATE INDEX genes_gene_id ON genes (gene_id);
+    CREATE INDEX genes_name ON genes (name);
+    CREATE INDEX genes_region ON genes (seq_name, start_pos);
+    CREATE INDEX transcripts_transcript_id ON transcripts (transcript_id);
+    CREATE INDEX exons_region ON exons (seq_name, start_pos);
+";
+
+/// SQLite store of genes, their transcripts, and their exons.
+#[cfg(feature = "sqlite")]
+pub struct GeneStore {
+    conn: Connection,
+}
+
+#[cfg(feature = "sqlite")]
+impl GeneStore {
+
+    /// Creates a new store at the given path.
+    ///
+    /// An error is returned if the file already contains a store.
+    pub fn create<P: AsRef<Path>>(path: P) -> ::Result<Self> {
+        let conn = Connection::open(path).map_err(db_error)?;
+        GeneStore::init(conn)
+    }
+
+    /// Creates a new store held in memory, which is discarded once dropped.
+    pub fn in_memory() -> ::Result<Self> {
+        let conn = Connection::open_in_memory().map_err(db_error)?;
+        GeneStore::init(conn)
+    }
+
+    /// Opens the existing store at the given path, after checking its format version.
+    pub fn open<P: AsRef<Path>>(path: P) -> ::Result<Self> {
+        let conn = Connection::open(path).map_err(db_error)?;
+        let version = conn
+            .query_row("SELECT value FROM meta WHERE key = 'version'", &[],
+                       |row| row.get::<_, String>(0))
+            .map_err(|_| ::Error::from(StoreError::InvalidStore))?;
+        let version = version.parse::<u32>()
+            .map_err(|_| ::Error::from(StoreError::InvalidStore))?;
+        if version != CACHE_VERSION {
+            return Err(::Error::from(StoreError::UnsupportedVersion(version)));
+        }
+        Ok(GeneStore { conn: conn })
+    }
+
+    /// Creates the tables of a new store in the given database.
+    fn init(conn: Connection) -> ::Result<Self> {
+        conn.execute_batch(SCHEMA).map_err(db_error)?;
+        let _ = conn
+            .execute("INSERT INTO meta (key, value) VALUES ('version', ?)",
+                     &[&CACHE_VERSION.to_string()])
+            .map_err(db_error)?;
+        Ok(GeneStore { conn: conn })
+    }
+
+    /// Writes the given gene into the store.
+    pub fn insert_gene(&mut self, gene: &Gene) -> ::Result<()> {
+        insert_gene(&self.conn, gene)
+    }
+
+    /// Writes all the given genes into the store in a single transaction, returning the
+    /// number of genes written.
+    ///
+    /// Nothing is written if any of the genes is an error, which is returned as-is.
+    pub fn insert_genes<T>(&mut self, genes: T) -> ::Result<usize>
+        where T: IntoIterator<Item=::Result<Gene>>
+    {
+        let tx = self.conn.transaction().map_err(db_error)?;
+        let mut num_genes = 0;
+        for gx in genes {
+            insert_gene(&tx, &gx?)?;
+            num_genes += 1;
+        }
+        tx.commit().map_err(db_error)?;
+        Ok(num_genes)
+    }
+
+    /// Returns the number of genes in the store.
+    pub fn num_genes(&self) -> ::Result<usize> {
+        self.conn
+            .query_row("SELECT COUNT(*) FROM genes", &[], |row| row.get::<_, i64>(0))
+            .map(|count| count as usize)
+            .map_err(db_error)
+    }
+
+    /// Returns the first stored gene with the given identifier.
+    pub fn gene(&self, gene_id: &str) -> ::Result<Option<Gene>> {
+        let genes = self.query_genes(
+            "SELECT data FROM genes WHERE gene_id = ? ORDER BY id LIMIT 1",
+            &[&gene_id.to_owned()])?;
+        Ok(genes.into_iter().next())
+    }
+
+    /// Returns the stored genes with the given name, in the order they were written.
+    ///
+    /// Genes are named as in `Gene::name`, and genes without names are named after their
+    /// identifiers.
+    pub fn genes_by_name(&self, name: &str) -> ::Result<Vec<Gene>> {
+        self.query_genes("SELECT data FROM genes WHERE name = ? ORDER BY id",
+                         &[&name.to_owned()])
+    }
+
+    /// Returns the stored genes that overlap the given region, sorted by their coordinates.
+    pub fn genes_in(&self, seq_name: &str, region: Range<u64>) -> ::Result<Vec<Gene>> {
+        self.query_genes(
+            "SELECT data FROM genes WHERE seq_name = ? AND start_pos < ? AND end_pos > ? \
+             ORDER BY start_pos, end_pos, id",
+            &[&seq_name.to_owned(), &(region.end as i64), &(region.start as i64)])
+    }
+
+    /// Returns the first stored transcript with the given identifier.
+    pub fn transcript(&self, transcript_id: &str) -> ::Result<Option<Transcript>> {
+        let transcripts = self.query_transcripts(
+            "SELECT genes.data, transcripts.key FROM transcripts \
+             JOIN genes ON genes.id = transcripts.gene \
+             WHERE transcripts.transcript_id = ? ORDER BY transcripts.id LIMIT 1",
+            &[&transcript_id.to_owned()])?;
+        Ok(transcripts.into_iter().next())
+    }
+
+    /// Returns the stored transcripts with any exons overlapping the given region, in the
+    /// order they were written.
+    pub fn transcripts_in(&self, seq_name: &str, region: Range<u64>)
+        -> ::Result<Vec<Transcript>>
+    {
+        self.query_transcripts(
+            "SELECT genes.data, transcripts.key FROM transcripts \
+             JOIN genes ON genes.id = transcripts.gene \
+             WHERE transcripts.id IN (SELECT transcript FROM exons \
+                                      WHERE seq_name = ? AND start_pos < ? AND end_pos > ?) \
+             ORDER BY transcripts.id",
+            &[&seq_name.to_owned(), &(region.end as i64), &(region.start as i64)])
+    }
+
+    /// Returns the stored exons that overlap the given region, grouped by their transcripts.
+    ///
+    /// Exons shared by several transcripts are returned once for each of them.
+    pub fn exons_in(&self, seq_name: &str, region: Range<u64>) -> ::Result<Vec<Exon>> {
+        let transcripts = self.transcripts_in(seq_name, region.clone())?;
+        Ok(transcripts.iter()
+            .flat_map(|trx| trx.exons_in(region.clone()).iter().cloned())
+            .collect())
+    }
+
+    /// Returns the genes decoded from the first column of the rows of the given query.
+    fn query_genes(&self, sql: &str, params: &[&ToSql])
+        -> ::Result<Vec<Gene>>
+    {
+        let mut stmt = self.conn.prepare(sql).map_err(db_error)?;
+        let rows = stmt.query_map(params, |row| row.get::<_, Vec<u8>>(0)).map_err(db_error)?;
+        let mut genes = Vec::new();
+        for row in rows {
+            genes.push(decode_gene(&row.map_err(db_error)?)?);
+        }
+        Ok(genes)
+    }
+
+    /// Returns the transcripts taken from the genes and transcript keys of the rows of the
+    /// given query.
+    fn query_transcripts(&self, sql: &str, params: &[&ToSql])
+        -> ::Result<Vec<Transcript>>
+    {
+        let mut stmt = self.conn.prepare(sql).map_err(db_error)?;
+        let rows = stmt
+            .query_map(params, |row| (row.get::<_, Vec<u8>>(0), row.get::<_, String>(1)))
+            .map_err(db_error)?;
+        let mut transcripts = Vec::new();
+        for row in rows {
+            let (data, key) = row.map_err(db_error)?;
+            let gene = decode_gene(&data)?;
+            if let Some(trx) = gene.transcripts().get(&key) {
+                transcripts.push(trx.clone());
+            }
+        }
+        Ok(transcripts)
+    }
+}
+
+/// Writes the given gene, its transcripts, and their exons into the given database.
+#[cfg(feature = "sqlite")]
+fn insert_gene(conn: &Connection, gene: &Gene) -> ::Result<()> {
+    let data = bincode::serialize(gene, Infinite)
+        .map_err(|e| ::Error::from(StoreError::Encoding(e.to_string())))?;
+    let name = gene.name().or(gene.id()).map(|v| v.to_owned());
+    let _ = conn
+        .execute("INSERT INTO genes (gene_id, name, seq_name, start_pos, end_pos, data) \
+                  VALUES (?, ?, ?, ?, ?, ?)",
+                 &[&gene.id().map(|v| v.to_owned()), &name, &gene.seq_name().to_owned(),
+                   &(gene.start() as i64), &(gene.end() as i64), &data])
+        .map_err(db_error)?;
+    let gene_row = conn.last_insert_rowid();
+    for (key, trx) in gene.transcripts().iter() {
+        let _ = conn
+            .execute("INSERT INTO transcripts (gene, key, transcript_id) VALUES (?, ?, ?)",
+                     &[&gene_row, key, &trx.id().map(|v| v.to_owned())])
+            .map_err(db_error)?;
+        let trx_row = conn.last_insert_rowid();
+        for exon in trx.exons() {
+            let _ = conn
+                .execute("INSERT INTO exons (transcript, seq_name, start_pos, end_pos) \
+                          VALUES (?, ?, ?, ?)",
+                         &[&trx_row, &exon.seq_name().to_owned(),
+                           &(exon.start() as i64), &(exon.end() as i64)])
+                .map_err(db_error)?;
+        }
+    }
+    Ok(())
+}
+
+/// Decodes a gene from its stored blob.
+#[cfg(feature = "sqlite")]
+fn decode_gene(data: &[u8]) -> ::Result<Gene> {
+    bincode::deserialize(data).map_err(|e| ::Error::from(StoreError::Encoding(e.to_string())))
+}
+
+/// Helper function for converting database errors.
+#[cfg(feature = "sqlite")]
+fn db_error(err: rusqlite::Error) -> ::Error {
+    ::Error::from(StoreError::Database(err.to_string()))
+}
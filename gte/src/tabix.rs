@@ -0,0 +1,209 @@
+/*! Reader for tabix indices of bgzip-compressed, coordinate-sorted text files.
+
+Only the parts of the index needed for finding where the rows of a region start are read. The
+index format is described in the
+[tabix specification](https://samtools.github.io/hts-specs/tabix.pdf).
+*/
+use std::collections::HashMap;
+use std::error::Error;
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom};
+use std::fs;
+use std::path::Path;
+
+use flate2::read::MultiGzDecoder;
+
+#[cfg(feature = "remote")]
+use utils::open_url_from;
+
+
+/// Magic bytes at the start of the decompressed tabix index.
+const TABIX_MAGIC: &'static [u8; 4] = b"TBI\x01";
+
+/// Number of bits of the smallest bins and the linear index windows.
+const MIN_SHIFT: u32 = 14;
+
+quick_error! {
+    /// Errors that occur when reading tabix indices.
+    #[derive(Debug)]
+    pub enum TabixError {
+        /// Occurs when the index is not a valid tabix index.
+        InvalidIndex(reason: &'static str) {
+            description("invalid tabix index")
+            display(self_) -> ("{}: {}", self_.description(), reason)
+        }
+        /// Errors propagated from reading the underlying index or data files.
+        Io(err: io::Error) {
+            description(err.description())
+            from()
+            cause(err)
+        }
+    }
+}
+
+/// Index of a single sequence.
+#[derive(Debug, Clone, Default)]
+struct RefIndex {
+    bins: HashMap<u32, Vec<(u64, u64)>>,
+    linear: Vec<u64>,
+}
+
+/// Tabix index of a bgzip-compressed file.
+#[derive(Debug, Clone)]
+pub(crate) struct TabixIndex {
+    refs: HashMap<String, RefIndex>,
+}
+
+impl TabixIndex {
+
+    /// Reads the index from the given path.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> ::Result<Self> {
+        let file = fs::File::open(path).map_err(tabix_io_error)?;
+        TabixIndex::from_reader(file)
+    }
+
+    /// Reads the index from the given URL.
+    #[cfg(feature = "remote")]
+    pub fn from_url(url: &str) -> ::Result<Self> {
+        let response = open_url_from(url, 0).map_err(tabix_io_error)?;
+        TabixIndex::from_reader(response)
+    }
+
+    /// Reads the index from its compressed contents.
+    fn from_reader<R: Read>(in_reader: R) -> ::Result<Self> {
+        let mut dec = MultiGzDecoder::new(BufReader::new(in_reader)).map_err(tabix_io_error)?;
+        let mut buf = Vec::new();
+        let _ = dec.read_to_end(&mut buf).map_err(tabix_io_error)?;
+        TabixIndex::from_bytes(&buf)
+    }
+
+    /// Parses the index from its decompressed contents.
+    fn from_bytes(buf: &[u8]) -> ::Result<Self> {
+        let mut cur = Cursor { buf: buf, pos: 0 };
+        if cur.take(4)? != &TABIX_MAGIC[..] {
+            return Err(::Error::from(TabixError::InvalidIndex("missing magic bytes")));
+        }
+        let n_ref = cur.i32()?;
+        // format, col_seq, col_beg, col_end, meta, and skip are not needed for queries
+        let _ = cur.take(6 * 4)?;
+        let l_nm = cur.i32()? as usize;
+        let names = cur.take(l_nm)?
+            .split(|&b| b == 0)
+            .filter(|name| !name.is_empty())
+            .map(|name| String::from_utf8_lossy(name).into_owned())
+            .collect::<Vec<String>>();
+        if names.len() != n_ref as usize {
+            return Err(::Error::from(TabixError::InvalidIndex("sequence name count mismatch")));
+        }
+
+        let mut refs = HashMap::new();
+        for name in names {
+            let mut index = RefIndex::default();
+            let n_bin = cur.i32()?;
+            for _ in 0..n_bin {
+                let bin = cur.u32()?;
+                let n_chunk = cur.i32()?;
+                let mut chunks = Vec::with_capacity(n_chunk as usize);
+                for _ in 0..n_chunk {
+                    chunks.push((cur.u64()?, cur.u64()?));
+                }
+                let _ = index.bins.insert(bin, chunks);
+            }
+            let n_intv = cur.i32()?;
+            for _ in 0..n_intv {
+                index.linear.push(cur.u64()?);
+            }
+            let _ = refs.insert(name, index);
+        }
+
+        Ok(TabixIndex { refs: refs })
+    }
+
+    /// Returns the virtual offset from which all rows overlapping the given zero-based,
+    /// half-open region can be found, or `None` if the region has no rows.
+    pub fn start_offset(&self, seq_name: &str, start: u64, end: u64) -> Option<u64> {
+        let index = match self.refs.get(seq_name) {
+            Some(index) => index,
+            None => return None,
+        };
+        let min_offset = index.linear.get((start >> MIN_SHIFT) as usize).cloned().unwrap_or(0);
+        reg_to_bins(start, end).iter()
+            .filter_map(|bin| index.bins.get(bin))
+            .flat_map(|chunks| chunks.iter())
+            .filter(|&&(_, cend)| cend > min_offset)
+            .map(|&(cbeg, _)| if cbeg > min_offset { cbeg } else { min_offset })
+            .min()
+    }
+}
+
+/// Returns the bins that may contain rows overlapping the given zero-based, half-open region.
+fn reg_to_bins(start: u64, end: u64) -> Vec<u32> {
+    let (start, end) = (start as u32, if end > start { (end - 1) as u32 } else { start as u32 });
+    let mut bins = vec![0];
+    for &(offset, shift) in &[(1, 26), (9, 23), (73, 20), (585, 17), (4681, 14)] {
+        bins.extend((offset + (start >> shift))..(offset + (end >> shift) + 1));
+    }
+    bins
+}
+
+/// Helper function for converting IO errors to crate errors.
+fn tabix_io_error(err: io::Error) -> ::Error {
+    ::Error::from(TabixError::from(err))
+}
+
+/// Helper struct for reading little-endian values from the index contents.
+struct Cursor<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Cursor<'a> {
+
+    fn take(&mut self, len: usize) -> ::Result<&'a [u8]> {
+        if self.pos + len > self.buf.len() {
+            return Err(::Error::from(TabixError::InvalidIndex("unexpected end of index")));
+        }
+        let bytes = &self.buf[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn u64(&mut self) -> ::Result<u64> {
+        self.take(8).map(|bytes| bytes.iter().rev().fold(0, |acc, &b| (acc << 8) | b as u64))
+    }
+
+    fn u32(&mut self) -> ::Result<u32> {
+        self.take(4).map(|bytes| bytes.iter().rev().fold(0, |acc, &b| (acc << 8) | b as u32))
+    }
+
+    fn i32(&mut self) -> ::Result<i32> {
+        self.u32().map(|value| value as i32)
+    }
+}
+
+/// Opens the bgzip-compressed file at the given path, positioned at the given virtual offset.
+///
+/// The upper 48 bits of a virtual offset are the offset of a compressed block in the file,
+/// while the lower 16 bits are the offset within the decompressed block.
+pub(crate) fn open_at<P: AsRef<Path>>(path: P, voffset: u64) -> ::Result<Box<BufRead>> {
+    let mut file = fs::File::open(path).map_err(tabix_io_error)?;
+    let _ = file.seek(SeekFrom::Start(voffset >> 16)).map_err(tabix_io_error)?;
+    decompress_at(file, voffset)
+}
+
+/// Opens the bgzip-compressed file at the given URL, positioned at the given virtual offset.
+///
+/// Only the part of the file from the compressed block onwards is requested.
+#[cfg(feature = "remote")]
+pub(crate) fn open_url_at(url: &str, voffset: u64) -> ::Result<Box<BufRead>> {
+    let response = open_url_from(url, voffset >> 16).map_err(tabix_io_error)?;
+    decompress_at(response, voffset)
+}
+
+/// Decompresses the given reader, which starts at the compressed block of the given virtual
+/// offset, skipping to the offset within the decompressed block.
+fn decompress_at<R: Read + 'static>(in_reader: R, voffset: u64) -> ::Result<Box<BufRead>> {
+    let mut dec = MultiGzDecoder::new(BufReader::new(in_reader)).map_err(tabix_io_error)?;
+    let _ = io::copy(&mut (&mut dec).take(voffset & 0xffff), &mut io::sink())
+        .map_err(tabix_io_error)?;
+    Ok(Box::new(BufReader::new(dec)))
+}
@@ -0,0 +1,370 @@
+/*! Validation of gene and transcript models.
+
+Validation does not fail on problematic models. Instead, it produces a report of all the issues
+found in each transcript, so that annotations from third-party sources can be audited.
+
+Checks that require the genomic sequence, such as the splice site check, are only performed when
+a sequence source is given.
+*/
+use std::fmt;
+
+use {reverse_complement, CodonTable, ExonFeatureKind, Gene, SequenceError, SequenceSource, Strand,
+     Transcript};
+use utils::OptionDeref;
+
+
+/// Checks that can be performed on transcripts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Check {
+    /// The length of the coding region, including the stop codon and excluding bases of any
+    /// preceding partial codon, is divisible by 3.
+    CdsLength,
+    /// Exons are sorted by their coordinates and do not overlap each other.
+    ExonOrder,
+    /// All coding region features lie within their exons.
+    CdsInExons,
+    /// Transcripts with a coding region have both start and stop codons.
+    Codons,
+    /// The transcript interval is equal to the span of its exons.
+    TranscriptSpan,
+    /// Introns start with a `GT` or `GC` donor site and end with an `AG` acceptor site, in the
+    /// transcript direction. This check requires a sequence source.
+    SpliceSites,
+    /// The coding region has no stop codons before its end, unless the transcript has stop
+    /// codon readthrough. This check requires a sequence source.
+    InternalStopCodons,
+}
+
+impl Check {
+
+    /// Returns all available checks.
+    pub fn all() -> Vec<Check> {
+        vec![Check::CdsLength, Check::ExonOrder, Check::CdsInExons, Check::Codons,
+             Check::TranscriptSpan, Check::SpliceSites, Check::InternalStopCodons]
+    }
+}
+
+/// An issue found by a check.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Issue {
+    check: Check,
+    message: String,
+}
+
+impl Issue {
+
+    /// Returns the check that found the issue.
+    pub fn check(&self) -> Check {
+        self.check
+    }
+
+    /// Returns the description of the issue.
+    pub fn message(&self) -> &str {
+        self.message.as_str()
+    }
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}: {}", self.check, self.message)
+    }
+}
+
+/// Validation report of a single transcript.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+    transcript_id: Option<String>,
+    issues: Vec<Issue>,
+}
+
+impl Report {
+
+    /// Returns the identifier of the validated transcript.
+    pub fn transcript_id(&self) -> Option<&str> {
+        self.transcript_id.as_deref()
+    }
+
+    /// Returns all issues found in the transcript.
+    pub fn issues(&self) -> &[Issue] {
+        self.issues.as_slice()
+    }
+
+    /// Returns whether no issues were found in the transcript.
+    pub fn is_valid(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// Adds an issue found by the given check.
+    fn add<T: Into<String>>(&mut self, check: Check, message: T) {
+        self.issues.push(Issue { check: check, message: message.into() });
+    }
+}
+
+/// Configurable validator of gene and transcript models.
+///
+/// All checks are enabled by default, and coding regions are translated using the standard code.
+#[derive(Debug, Clone)]
+pub struct Validator {
+    checks: Vec<Check>,
+    codon_table: CodonTable,
+}
+
+impl Default for Validator {
+    fn default() -> Self {
+        Validator {
+            checks: Check::all(),
+            codon_table: CodonTable::default(),
+        }
+    }
+}
+
+impl Validator {
+
+    /// Creates a validator with all checks enabled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets whether the given check is performed.
+    pub fn check(&mut self, check: Check, enabled: bool) -> &mut Self {
+        self.checks.retain(|c| *c != check);
+        if enabled {
+            self.checks.push(check);
+        }
+        self
+    }
+
+    /// Sets the codon table used for translating coding regions.
+    pub fn codon_table(&mut self, table: CodonTable) -> &mut Self {
+        self.codon_table = table;
+        self
+    }
+
+    /// Returns whether the given check is performed.
+    pub fn is_enabled(&self, check: Check) -> bool {
+        self.checks.contains(&check)
+    }
+
+    /// Validates the given transcript.
+    ///
+    /// Checks that require a sequence source are skipped.
+    pub fn validate_transcript(&self, transcript: &Transcript) -> Report {
+        let mut report = Report {
+            transcript_id: transcript.id().map(|v| v.to_owned()),
+            issues: vec![],
+        };
+        for check in Check::all() {
+            if !self.is_enabled(check) {
+                continue;
+            }
+            match check {
+                Check::CdsLength => check_cds_length(transcript, &mut report),
+                Check::ExonOrder => check_exon_order(transcript, &mut report),
+                Check::CdsInExons => check_cds_in_exons(transcript, &mut report),
+                Check::Codons => check_codons(transcript, &mut report),
+                Check::TranscriptSpan => check_transcript_span(transcript, &mut report),
+                Check::SpliceSites | Check::InternalStopCodons => {},
+            }
+        }
+        report
+    }
+
+    /// Validates the given transcript, reading its sequence from the given source.
+    ///
+    /// An error is returned if the sequence of the transcript can not be read.
+    pub fn validate_transcript_with_sequence<S: SequenceSource>(&self, transcript: &Transcript,
+                                                                source: &mut S)
+        -> ::Result<Report>
+    {
+        let mut report = self.validate_transcript(transcript);
+        if self.is_enabled(Check::SpliceSites) {
+            check_splice_sites(transcript, source, &mut report)?;
+        }
+        if self.is_enabled(Check::InternalStopCodons) {
+            check_internal_stop_codons(transcript, source, &self.codon_table, &mut report)?;
+        }
+        Ok(report)
+    }
+
+    /// Validates all transcripts of the given gene, returning one report per transcript.
+    pub fn validate_gene(&self, gene: &Gene) -> Vec<Report> {
+        gene.transcripts().values()
+            .map(|trx| self.validate_transcript(trx))
+            .collect()
+    }
+
+    /// Validates all transcripts of the given gene, reading their sequences from the given
+    /// source.
+    pub fn validate_gene_with_sequence<S: SequenceSource>(&self, gene: &Gene, source: &mut S)
+        -> ::Result<Vec<Report>>
+    {
+        gene.transcripts().values()
+            .map(|trx| self.validate_transcript_with_sequence(trx, source))
+            .collect()
+    }
+}
+
+/// Returns whether the given exon feature kind is part of the coding region.
+fn is_coding(kind: &ExonFeatureKind) -> bool {
+    match *kind {
+        ExonFeatureKind::CDS { .. } | ExonFeatureKind::StartCodon { .. } |
+        ExonFeatureKind::StopCodon { .. } => true,
+        _ => false,
+    }
+}
+
+/// Checks that the coding region length is divisible by 3.
+fn check_cds_length(transcript: &Transcript, report: &mut Report) {
+    let segments = transcript.coding_segments(true);
+    if segments.is_empty() {
+        return;
+    }
+    let length = segments.iter().map(|&(start, end)| end - start).sum::<u64>();
+    let phase = transcript.coding_phase() as u64;
+    if length < phase || (length - phase) % 3 != 0 {
+        report.add(Check::CdsLength,
+                   format!("coding region length {} with frame {} is not divisible by 3",
+                           length, phase));
+    }
+}
+
+/// Checks that exons are sorted and do not overlap.
+fn check_exon_order(transcript: &Transcript, report: &mut Report) {
+    for pair in transcript.exons().windows(2) {
+        let (prev, next) = (&pair[0], &pair[1]);
+        if next.start() < prev.start() {
+            report.add(Check::ExonOrder,
+                       format!("exon {}-{} is placed after exon {}-{}",
+                               next.start(), next.end(), prev.start(), prev.end()));
+        } else if next.start() < prev.end() {
+            report.add(Check::ExonOrder,
+                       format!("exon {}-{} overlaps exon {}-{}",
+                               next.start(), next.end(), prev.start(), prev.end()));
+        }
+    }
+}
+
+/// Checks that all coding region features lie within their exons.
+fn check_cds_in_exons(transcript: &Transcript, report: &mut Report) {
+    for exon in transcript.exons() {
+        for fx in exon.features().iter().filter(|fx| is_coding(fx.kind())) {
+            if fx.start() < exon.start() || fx.end() > exon.end() {
+                report.add(Check::CdsInExons,
+                           format!("{:?} feature {}-{} lies outside exon {}-{}",
+                                   fx.kind(), fx.start(), fx.end(), exon.start(), exon.end()));
+            }
+        }
+    }
+}
+
+/// Checks that coding transcripts have both start and stop codons.
+fn check_codons(transcript: &Transcript, report: &mut Report) {
+    let kinds = transcript.exons().iter()
+        .flat_map(|exon| exon.features().iter())
+        .map(|fx| fx.kind())
+        .collect::<Vec<&ExonFeatureKind>>();
+    if !kinds.iter().any(|kind| is_coding(kind)) {
+        return;
+    }
+    let has_start = kinds.iter()
+        .any(|kind| if let ExonFeatureKind::StartCodon { .. } = **kind { true } else { false });
+    let has_stop = kinds.iter()
+        .any(|kind| if let ExonFeatureKind::StopCodon { .. } = **kind { true } else { false });
+    if !has_start {
+        report.add(Check::Codons, "coding region has no start codon");
+    }
+    if !has_stop {
+        report.add(Check::Codons, "coding region has no stop codon");
+    }
+}
+
+/// Checks that the transcript interval is equal to the span of its exons.
+fn check_transcript_span(transcript: &Transcript, report: &mut Report) {
+    let exons = transcript.exons();
+    if exons.is_empty() {
+        report.add(Check::TranscriptSpan, "transcript has no exons");
+        return;
+    }
+    let start = exons.iter().map(|exon| exon.start()).min().unwrap();
+    let end = exons.iter().map(|exon| exon.end()).max().unwrap();
+    if (start, end) != (transcript.start(), transcript.end()) {
+        report.add(Check::TranscriptSpan,
+                   format!("transcript interval {}-{} differs from exon span {}-{}",
+                           transcript.start(), transcript.end(), start, end));
+    }
+}
+
+/// Length of the donor and acceptor splice site motifs.
+const SPLICE_MOTIF_LEN: u64 = 2;
+
+/// Checks that all introns have canonical donor and acceptor splice sites.
+///
+/// Transcripts with unknown strands pass if their introns are canonical in either direction.
+fn check_splice_sites<S: SequenceSource>(transcript: &Transcript, source: &mut S,
+                                         report: &mut Report)
+    -> ::Result<()>
+{
+    let mut head = Vec::with_capacity(SPLICE_MOTIF_LEN as usize);
+    let mut tail = Vec::with_capacity(SPLICE_MOTIF_LEN as usize);
+    for intron in transcript.introns() {
+        let (start, end) = (intron.start(), intron.end());
+        if end - start < SPLICE_MOTIF_LEN * 2 {
+            report.add(Check::SpliceSites,
+                       format!("intron {}-{} is too short for splice sites", start, end));
+            continue;
+        }
+        source.read_region(transcript.seq_name(), start, start + SPLICE_MOTIF_LEN, &mut head)?;
+        source.read_region(transcript.seq_name(), end - SPLICE_MOTIF_LEN, end, &mut tail)?;
+
+        let fwd = (head.clone(), tail.clone());
+        let rev = (reverse_complement(&tail), reverse_complement(&head));
+        let (donor, acceptor) = match *transcript.strand() {
+            Strand::Forward => fwd,
+            Strand::Reverse => rev,
+            Strand::Unknown =>
+                if is_canonical(&rev.0, &rev.1) && !is_canonical(&fwd.0, &fwd.1) {
+                    rev
+                } else {
+                    fwd
+                },
+        };
+        if !is_canonical(&donor, &acceptor) {
+            report.add(Check::SpliceSites,
+                       format!("intron {}-{} has non-canonical splice sites {}-{}",
+                               start, end, String::from_utf8_lossy(&donor),
+                               String::from_utf8_lossy(&acceptor)));
+        }
+    }
+    Ok(())
+}
+
+/// Checks that the coding region has no internal stop codons.
+///
+/// Transcripts with stop codon readthrough pass, as their internal stop codons are translated
+/// by `Transcript::translate`.
+fn check_internal_stop_codons<S: SequenceSource>(transcript: &Transcript, source: &mut S,
+                                                 table: &CodonTable, report: &mut Report)
+    -> ::Result<()>
+{
+    if transcript.coding_segments(false).is_empty() {
+        return Ok(());
+    }
+    match transcript.translate(source, table) {
+        Ok(_) => Ok(()),
+        Err(::Error::Sequence(SequenceError::InternalStopCodon(codon_num, _))) => {
+            report.add(Check::InternalStopCodons,
+                       format!("coding region has a stop codon at codon {}", codon_num));
+            Ok(())
+        },
+        Err(err) => Err(err),
+    }
+}
+
+/// Returns whether the given donor and acceptor sites form a canonical GT-AG or GC-AG pair.
+fn is_canonical(donor: &[u8], acceptor: &[u8]) -> bool {
+    let upper = |bases: &[u8]| bases.iter()
+        .map(|&b| if b >= b'a' && b <= b'z' { b - (b'a' - b'A') } else { b })
+        .collect::<Vec<u8>>();
+    let (donor, acceptor) = (upper(donor), upper(acceptor));
+    (donor == b"GT" || donor == b"GC") && acceptor == b"AG"
+}
@@ -0,0 +1,77 @@
+extern crate gte;
+
+use gte::{AliasError, GBuilder, NameStyle, SeqAliases, SeqRewrite, Strand};
+
+
+static ASSEMBLY_REPORT: &'static str = "\
+# Assembly name:  GRCh38.p13
+# Sequence-Name\tSequence-Role\tAssigned-Molecule\tAssigned-Molecule-Location/Type\t\
+GenBank-Accn\tRelationship\tRefSeq-Accn\tAssembly-Unit\tSequence-Length\tUCSC-style-name
+1\tassembled-molecule\t1\tChromosome\tCM000663.2\t=\tNC_000001.11\tPrimary Assembly\t\
+248956422\tchr1
+MT\tassembled-molecule\tMT\tMitochondrion\tJ01415.2\t=\tNC_012920.1\tnon-nuclear\t\
+16569\tchrM
+HSCHR1_CTG1_UNLOCALIZED\tunlocalized-scaffold\t1\tChromosome\tKI270706.1\t=\tNT_187361.1\t\
+Primary Assembly\t175055\tchr1_KI270706v1_random
+HG1_PATCH\tfix-patch\t1\tChromosome\tKN196472.1\t=\tNW_009646194.1\tPATCHES\t186494\tna
+";
+
+
+#[test]
+fn seq_aliases_builtin() {
+    let ucsc = SeqAliases::new(NameStyle::Ucsc);
+    assert_eq!(ucsc.resolve("1"), "chr1");
+    assert_eq!(ucsc.resolve("chr2"), "chr2");
+    assert_eq!(ucsc.resolve("X"), "chrX");
+    assert_eq!(ucsc.resolve("MT"), "chrM");
+    assert_eq!(ucsc.resolve("KI270706.1"), "KI270706.1");
+
+    let ensembl = SeqAliases::new(NameStyle::Ensembl);
+    assert_eq!(ensembl.resolve("chr1"), "1");
+    assert_eq!(ensembl.resolve("chrY"), "Y");
+    assert_eq!(ensembl.resolve("chrM"), "MT");
+    assert_eq!(ensembl.resolve("chr"), "chr");
+    assert_eq!(ensembl.resolve("chrUn_KI270302v1"), "chrUn_KI270302v1");
+}
+
+#[test]
+fn seq_aliases_assembly_report() {
+    let ensembl = SeqAliases::from_assembly_report(ASSEMBLY_REPORT.as_bytes(), NameStyle::Ensembl)
+        .expect("aliases");
+    assert_eq!(ensembl.resolve("chr1_KI270706v1_random"), "KI270706.1");
+    assert_eq!(ensembl.resolve("NC_012920.1"), "MT");
+    assert_eq!(ensembl.resolve("chr1"), "1");
+
+    let ucsc = SeqAliases::from_assembly_report(ASSEMBLY_REPORT.as_bytes(), NameStyle::Ucsc)
+        .expect("aliases");
+    assert_eq!(ucsc.resolve("KI270706.1"), "chr1_KI270706v1_random");
+    assert_eq!(ucsc.resolve("CM000663.2"), "chr1");
+    assert_eq!(ucsc.resolve("KN196472.1"), "KN196472.1");
+
+    let refseq = SeqAliases::from_assembly_report(ASSEMBLY_REPORT.as_bytes(), NameStyle::RefSeq)
+        .expect("aliases");
+    assert_eq!(refseq.resolve("chrM"), "NC_012920.1");
+    assert_eq!(refseq.resolve("HG1_PATCH"), "NW_009646194.1");
+}
+
+#[test]
+fn seq_aliases_invalid_report_line() {
+    match SeqAliases::from_assembly_report("1\tassembled-molecule\n".as_bytes(), NameStyle::Ucsc) {
+        Err(gte::Error::Alias(AliasError::InvalidReportLine(line))) => {
+            assert_eq!(line, "1\tassembled-molecule")
+        },
+        otherwise => panic!("unexpected result: {:?}", otherwise),
+    }
+}
+
+#[test]
+fn seq_rewrite_aliases() {
+    let mut rewrite = SeqRewrite::new();
+    let _ = rewrite
+        .aliases(Some(SeqAliases::new(NameStyle::Ucsc)))
+        .rename("GL000009.2", "chr14_GL000009v2_random");
+    let gx = GBuilder::new("MT", 100, 500).strand(Strand::Forward).build().unwrap();
+    assert_eq!(rewrite.rewrite_gene(gx).expect("a gene").seq_name(), "chrM");
+    let gx = GBuilder::new("GL000009.2", 100, 500).strand(Strand::Forward).build().unwrap();
+    assert_eq!(rewrite.rewrite_gene(gx).expect("a gene").seq_name(), "chr14_GL000009v2_random");
+}
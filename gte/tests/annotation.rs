@@ -0,0 +1,321 @@
+extern crate gte;
+extern crate linked_hash_map;
+
+use linked_hash_map::LinkedHashMap;
+
+use gte::{Biotype, DuplicatePolicy, FlankSide, Flanks, GBuilder, Gene, GeneFeatureKind,
+          GeneStreamExt, GenomeAnnotation, Promoters, RefFlatReader, SeqDict, Strand, TBuilder,
+          intergenic_regions};
+
+
+static MULT_ROWS_MULT_GENES_WITH_CDS: &'static str =
+    include_str!("data/mult_rows_mult_genes_with_cds.refFlat");
+
+
+fn make_annotation() -> GenomeAnnotation {
+    let mut reader = RefFlatReader::from_reader(MULT_ROWS_MULT_GENES_WITH_CDS.as_bytes());
+    GenomeAnnotation::from_genes_stream(reader.genes_stream()).expect("an annotation")
+}
+
+#[test]
+fn genome_annotation_basic() {
+    let ann = make_annotation();
+    assert_eq!(ann.len(), 2);
+    assert_eq!(ann.seq_names(), vec!["chr1"]);
+    assert_eq!(ann.genes("chr1").len(), 2);
+    assert!(ann.genes("chr2").is_empty());
+}
+
+#[test]
+fn genome_annotation_genes_by_name() {
+    let ann = make_annotation();
+    let genes = ann.genes_by_name("SMIM12");
+    assert_eq!(genes.len(), 1);
+    assert_eq!(genes[0].id(), Some("SMIM12"));
+    assert!(ann.genes_by_name("TP53").is_empty());
+}
+
+#[test]
+fn genome_annotation_genes_at() {
+    let ann = make_annotation();
+
+    let gxs = ann.genes_at("chr1", 2556000..34850400);
+    assert_eq!(gxs.iter().map(|gx| gx.id()).collect::<Vec<_>>(),
+               vec![Some("TNFRSF14"), Some("SMIM12")]);
+
+    assert_eq!(ann.genes_at("chr1", 2565622..2565700).len(), 0);
+    assert_eq!(ann.genes_at("chr1", 2565621..2565622).len(), 1);
+    assert_eq!(ann.genes_at("chr1", 2600000..2700000).len(), 0);
+    assert_eq!(ann.genes_at("chr2", 2556000..34850400).len(), 0);
+}
+
+#[test]
+fn genome_annotation_transcripts_at() {
+    let ann = make_annotation();
+
+    let trxs = ann.transcripts_at("chr1", 34859740..34859800);
+    assert_eq!(trxs.iter().map(|trx| trx.id()).collect::<Vec<_>>(), vec![Some("NM_138428")]);
+
+    assert_eq!(ann.transcripts_at("chr1", 34850361..34850362).len(), 3);
+}
+
+#[test]
+fn genome_annotation_exons_at() {
+    let ann = make_annotation();
+
+    let exns = ann.exons_at("chr1", 2556700..2557800);
+    assert_eq!(exns.iter().map(|exn| (exn.start(), exn.end())).collect::<Vec<_>>(),
+               vec![(2556364, 2556733), (2557725, 2557834),
+                    (2556364, 2556733), (2557725, 2557834)]);
+
+    assert_eq!(ann.exons_at("chr1", 2556733..2557725).len(), 0);
+}
+
+#[test]
+fn genome_annotation_nearest_gene() {
+    let ann = make_annotation();
+
+    let (gx1, dist1) = ann.nearest_gene("chr1", 2556000).expect("a nearest gene");
+    assert_eq!((gx1.id(), dist1), (Some("TNFRSF14"), -364));
+
+    let (gx2, dist2) = ann.nearest_gene("chr1", 2560000).expect("a nearest gene");
+    assert_eq!((gx2.id(), dist2), (Some("TNFRSF14"), 0));
+
+    let (gx3, dist3) = ann.nearest_gene("chr1", 2565700).expect("a nearest gene");
+    assert_eq!((gx3.id(), dist3), (Some("TNFRSF14"), 79));
+
+    let (gx4, dist4) = ann.nearest_gene("chr1", 34859900).expect("a nearest gene");
+    assert_eq!((gx4.id(), dist4), (Some("SMIM12"), -85));
+
+    let (gx5, dist5) = ann.nearest_gene("chr1", 34850000).expect("a nearest gene");
+    assert_eq!((gx5.id(), dist5), (Some("SMIM12"), 361));
+
+    assert!(ann.nearest_gene("chr2", 2556000).is_none());
+}
+
+#[test]
+fn genome_annotation_nested_genes() {
+    let ann = GenomeAnnotation::from_genes(
+        vec![("gx4", 20000, 20100), ("gx1", 100, 10000), ("gx3", 500, 600), ("gx2", 200, 300)]
+            .into_iter()
+            .map(|(id, start, end)| {
+                GBuilder::new("chrT", start, end).id(id).strand(Strand::Forward)
+                    .build().expect("a gene")
+            }));
+
+    fn ids(gxs: Vec<&Gene>) -> Vec<Option<&str>> {
+        gxs.into_iter().map(|gx| gx.id()).collect()
+    }
+    assert_eq!(ids(ann.genes_at("chrT", 550..560)), vec![Some("gx1"), Some("gx3")]);
+    assert_eq!(ids(ann.genes_at("chrT", 0..20001)),
+               vec![Some("gx1"), Some("gx2"), Some("gx3"), Some("gx4")]);
+    assert!(ann.genes_at("chrT", 10000..20000).is_empty());
+
+    let (gx1, dist1) = ann.nearest_gene("chrT", 250).expect("a nearest gene");
+    assert_eq!((gx1.id(), dist1), (Some("gx1"), 0));
+
+    let (gx2, dist2) = ann.nearest_gene("chrT", 15000).expect("a nearest gene");
+    assert_eq!((gx2.id(), dist2), (Some("gx4"), -5000));
+
+    let (gx3, dist3) = ann.nearest_gene("chrT", 14999).expect("a nearest gene");
+    assert_eq!((gx3.id(), dist3), (Some("gx1"), 5000));
+}
+
+#[test]
+fn genome_annotation_nearest_tss() {
+    let ann = make_annotation();
+
+    let (trx1, dist1) = ann.nearest_tss("chr1", 2556300).expect("a nearest transcript");
+    assert_eq!((trx1.id(), dist1), (Some("NM_001297605"), -64));
+
+    let (trx2, dist2) = ann.nearest_tss("chr1", 34859800).expect("a nearest transcript");
+    assert_eq!((trx2.id(), dist2), (Some("NM_138428"), 15));
+
+    let (trx3, dist3) = ann.nearest_tss("chr1", 34859740).expect("a nearest transcript");
+    assert_eq!((trx3.id(), dist3), (Some("NM_001164825"), -4));
+
+    assert!(ann.nearest_tss("chr2", 2556000).is_none());
+}
+
+#[test]
+fn promoters_mult_rows_mult_genes_with_cds() {
+    let mut reader = RefFlatReader::from_reader(MULT_ROWS_MULT_GENES_WITH_CDS.as_bytes());
+    let promoters = Promoters::new(reader.genes_stream(), 1000, 100)
+        .map(|res| res.expect("a promoter"))
+        .map(|(trx, fx)| (trx.id().unwrap().to_owned(), fx.start(), fx.end()))
+        .collect::<Vec<_>>();
+    assert_eq!(promoters, vec![
+        ("NM_001297605".to_owned(), 2555364, 2556464),
+        ("NM_003820".to_owned(), 2555364, 2556464),
+        ("NM_001164824".to_owned(), 34858945, 34860045),
+        ("NM_001164825".to_owned(), 34859637, 34860737),
+        ("NM_138428".to_owned(), 34859716, 34860816),
+    ]);
+}
+
+#[test]
+fn flanks_mult_rows_mult_genes_with_cds() {
+    let mut reader = RefFlatReader::from_reader(MULT_ROWS_MULT_GENES_WITH_CDS.as_bytes());
+    let flanks = Flanks::new(reader.genes_stream(), 500, FlankSide::Downstream)
+        .map(|res| res.expect("a flank"))
+        .map(|(gx, fx)| (gx.id().unwrap().to_owned(), fx.start(), fx.end()))
+        .collect::<Vec<_>>();
+    assert_eq!(flanks, vec![
+        ("TNFRSF14".to_owned(), 2565622, 2566122),
+        ("SMIM12".to_owned(), 34849861, 34850361),
+    ]);
+}
+
+fn biotype_genes() -> Vec<gte::Result<Gene>> {
+    let gx1 = GBuilder::new("chr1", 100, 200).id("gx1").strand(Strand::Forward)
+        .attribute("gene_biotype", "protein_coding")
+        .build();
+    let gx2 = GBuilder::new("chr2", 300, 400).id("gx2").strand(Strand::Forward)
+        .attribute("gene_type", "rRNA")
+        .build();
+    let gx3 = GBuilder::new("chr1", 500, 600).id("gx3").strand(Strand::Forward)
+        .build();
+    vec![gx1, gx2, gx3]
+}
+
+#[test]
+fn gene_stream_filter_biotype() {
+    let gxs = biotype_genes().into_iter()
+        .filter_biotype(&["rRNA", "Mt_rRNA"])
+        .map(|res| res.expect("a gene").id().map(|v| v.to_owned()))
+        .collect::<Vec<_>>();
+    assert_eq!(gxs, vec![Some("gx2".to_owned())]);
+}
+
+#[test]
+fn gene_stream_filter_biotype_from_transcripts() {
+    let trx = TBuilder::new("chr1", 100, 200).id("trx1").strand(Strand::Forward)
+        .attribute("gene_type", "protein_coding")
+        .attribute("transcript_type", "retained_intron")
+        .coords(vec![(100, 200)], None)
+        .build()
+        .expect("a transcript");
+    assert_eq!(trx.biotype(), Some(Biotype::RetainedIntron));
+    let mut transcripts = LinkedHashMap::new();
+    let _ = transcripts.insert("trx1".to_owned(), trx);
+    let gx = GBuilder::new("chr1", 100, 200).id("gx1").strand(Strand::Forward)
+        .transcripts(transcripts)
+        .build()
+        .expect("a gene");
+    assert_eq!(gx.biotype(), Some(Biotype::ProteinCoding));
+}
+
+#[test]
+fn biotype_other() {
+    let trx = TBuilder::new("chr1", 100, 200).id("trx1").strand(Strand::Forward)
+        .attribute("transcript_biotype", "polymorphic_pseudogene")
+        .coords(vec![(100, 200)], None)
+        .build()
+        .expect("a transcript");
+    let biotype = trx.biotype().expect("a biotype");
+    assert_eq!(biotype, Biotype::Other("polymorphic_pseudogene".to_owned()));
+    assert_eq!(biotype.as_str(), "polymorphic_pseudogene");
+    assert!(biotype.is_pseudogene());
+    assert_eq!(Biotype::from("Mt_rRNA"), Biotype::MtRRna);
+    assert_eq!(Biotype::MtRRna.to_string(), "Mt_rRNA");
+    assert!(!Biotype::ProteinCoding.is_pseudogene());
+}
+
+#[test]
+fn gene_stream_filter_seqname() {
+    let mut reader = RefFlatReader::from_reader(MULT_ROWS_MULT_GENES_WITH_CDS.as_bytes());
+    assert_eq!(reader.genes_stream().filter_seqname(&["chr2"]).count(), 0);
+
+    let gxs = biotype_genes().into_iter()
+        .filter_seqname(&["chr1".to_owned()])
+        .filter_biotype(&["protein_coding"])
+        .map(|res| res.expect("a gene").id().map(|v| v.to_owned()))
+        .collect::<Vec<_>>();
+    assert_eq!(gxs, vec![Some("gx1".to_owned())]);
+}
+
+fn duplicate_genes() -> Vec<gte::Result<Gene>> {
+    vec![GBuilder::new("chrX", 100, 200).id("gx1").strand(Strand::Forward).build(),
+         GBuilder::new("chr1", 100, 200).id("gx2").strand(Strand::Forward).build(),
+         GBuilder::new("chrY", 100, 200).id("gx1").strand(Strand::Reverse).build()]
+}
+
+fn dedup_genes(policy: DuplicatePolicy) -> Vec<(String, String)> {
+    duplicate_genes().into_iter()
+        .dedup_gene_ids(policy)
+        .map(|res| res.expect("a gene"))
+        .map(|gx| (gx.id().unwrap().to_owned(), gx.seq_name().to_owned()))
+        .collect()
+}
+
+#[test]
+fn gene_stream_dedup_gene_ids_error() {
+    let mut gxs = duplicate_genes().into_iter().dedup_gene_ids(DuplicatePolicy::Error);
+    assert!(gxs.next().expect("a gene result").is_ok());
+    assert!(gxs.next().expect("a gene result").is_ok());
+    assert!(gxs.next().expect("a gene result").is_err());
+    assert!(gxs.next().is_none());
+}
+
+#[test]
+fn gene_stream_dedup_gene_ids_keep_first() {
+    assert_eq!(dedup_genes(DuplicatePolicy::KeepFirst),
+               vec![("gx1".to_owned(), "chrX".to_owned()), ("gx2".to_owned(), "chr1".to_owned())]);
+}
+
+#[test]
+fn gene_stream_dedup_gene_ids_keep_last() {
+    assert_eq!(dedup_genes(DuplicatePolicy::KeepLast),
+               vec![("gx1".to_owned(), "chrY".to_owned()), ("gx2".to_owned(), "chr1".to_owned())]);
+}
+
+#[test]
+fn gene_stream_dedup_gene_ids_suffix() {
+    assert_eq!(dedup_genes(DuplicatePolicy::Suffix),
+               vec![("gx1".to_owned(), "chrX".to_owned()), ("gx2".to_owned(), "chr1".to_owned()),
+                    ("gx1_2".to_owned(), "chrY".to_owned())]);
+}
+
+fn intergenic_coords(flank: u64) -> Vec<(String, u64, u64)> {
+    let mut dict = SeqDict::new();
+    dict.push("chr1", 40000000);
+    dict.push("chr2", 1000);
+    let mut reader = RefFlatReader::from_reader(MULT_ROWS_MULT_GENES_WITH_CDS.as_bytes());
+    let regions = intergenic_regions(reader.genes_stream(), &dict, flank).expect("regions");
+    assert!(regions.iter().all(|&(_, ref fx)| fx.kind() == &GeneFeatureKind::Intergenic));
+    regions.into_iter()
+        .map(|(seq_name, fx)| (seq_name, fx.start(), fx.end()))
+        .collect()
+}
+
+#[test]
+fn intergenic_regions_mult_rows_mult_genes_with_cds() {
+    assert_eq!(intergenic_coords(0),
+               vec![("chr1".to_owned(), 0, 2556364),
+                    ("chr1".to_owned(), 2565622, 34850361),
+                    ("chr1".to_owned(), 34859816, 40000000),
+                    ("chr2".to_owned(), 0, 1000)]);
+}
+
+#[test]
+fn intergenic_regions_flank() {
+    assert_eq!(intergenic_coords(1000),
+               vec![("chr1".to_owned(), 0, 2555364),
+                    ("chr1".to_owned(), 2566622, 34849361),
+                    ("chr1".to_owned(), 34860816, 40000000),
+                    ("chr2".to_owned(), 0, 1000)]);
+}
+
+#[test]
+fn intergenic_regions_clipped() {
+    let mut dict = SeqDict::new();
+    dict.push("chrT", 1000);
+    let genes = vec![GBuilder::new("chrT", 200, 300).strand(Strand::Forward).build(),
+                     GBuilder::new("chrT", 400, 950).strand(Strand::Reverse).build(),
+                     GBuilder::new("chrU", 200, 900).strand(Strand::Reverse).build()];
+    let regions = intergenic_regions(genes, &dict, 100).expect("regions");
+    let coords = regions.iter()
+        .map(|&(ref seq_name, ref fx)| (seq_name.as_str(), fx.start(), fx.end()))
+        .collect::<Vec<_>>();
+    assert_eq!(coords, vec![("chrT", 0, 100)]);
+}
@@ -0,0 +1,115 @@
+extern crate gte;
+extern crate linked_hash_map;
+
+use linked_hash_map::LinkedHashMap;
+
+use gte::{Alignment, Assignment, AssignError, Gene, GenomeAnnotation, GBuilder, ReadAssigner,
+          Strand, Strandedness, TBuilder};
+use Strand::*;
+
+
+fn gene(id: &str, strand: Strand, transcripts: Vec<(&str, Vec<(u64, u64)>)>) -> Gene {
+    let mut trxs = LinkedHashMap::new();
+    for (tid, exon_coords) in transcripts {
+        let (start, end) = (exon_coords[0].0, exon_coords[exon_coords.len() - 1].1);
+        let trx = TBuilder::new("chr1", start, end)
+            .id(tid)
+            .strand(strand)
+            .coords(exon_coords, None)
+            .build()
+            .expect("a transcript");
+        let _ = trxs.insert(tid.to_owned(), trx);
+    }
+    let start = trxs.values().map(|trx| trx.start()).min().unwrap();
+    let end = trxs.values().map(|trx| trx.end()).max().unwrap();
+    GBuilder::new("chr1", start, end)
+        .id(id)
+        .strand(strand)
+        .transcripts(trxs)
+        .build()
+        .expect("a gene")
+}
+
+fn annotation() -> GenomeAnnotation {
+    GenomeAnnotation::from_genes(vec![
+        gene("g1", Forward, vec![("t1", vec![(100, 200), (300, 400)]),
+                                 ("t2", vec![(100, 200), (500, 600)])]),
+        gene("g2", Reverse, vec![("t3", vec![(220, 280)])]),
+        gene("g3", Forward, vec![("t4", vec![(350, 450)])]),
+    ])
+}
+
+fn summary(assignment: Assignment) -> (Vec<&str>, Vec<&str>) {
+    match assignment {
+        Assignment::Gene(gx, trxs) =>
+            (vec![gx.id().unwrap()], trxs.iter().map(|trx| trx.id().unwrap()).collect()),
+        Assignment::Ambiguous(gxs) =>
+            (gxs.iter().map(|gx| gx.id().unwrap()).collect(), vec![]),
+        Assignment::NoFeature => (vec![], vec![]),
+    }
+}
+
+fn aln(pos: u64, cigar: &str) -> Alignment {
+    Alignment::from_cigar("chr1", pos, cigar).expect("an alignment")
+}
+
+#[test]
+fn alignment_blocks() {
+    let aln = aln(100, "10S20M5D10M100N30M5I10M");
+    assert_eq!(aln.seq_name(), "chr1");
+    assert_eq!(aln.blocks(), &[(100, 135), (235, 275)]);
+}
+
+#[test]
+fn alignment_invalid_cigar() {
+    for cigar in vec!["", "10Q", "10M5", "M"] {
+        match Alignment::from_cigar("chr1", 100, cigar) {
+            Err(gte::Error::Assign(AssignError::InvalidCigar(ref c))) => assert_eq!(c, cigar),
+            otherwise => panic!("unexpected result for '{}': {:?}", cigar, otherwise),
+        }
+    }
+}
+
+#[test]
+fn assign_unstranded() {
+    let ann = annotation();
+    let assigner = ReadAssigner::new(&ann);
+    assert_eq!(summary(assigner.assign(&aln(150, "20M"))), (vec!["g1"], vec!["t1", "t2"]));
+    assert_eq!(summary(assigner.assign(&aln(180, "100M"))), (vec!["g1", "g2"], vec![]));
+    assert_eq!(summary(assigner.assign(&aln(370, "20M"))), (vec!["g1", "g3"], vec![]));
+    assert_eq!(summary(assigner.assign(&aln(700, "20M"))), (vec![], vec![]));
+    let other_seq = Alignment::from_cigar("chr2", 150, "20M").expect("an alignment");
+    assert_eq!(summary(assigner.assign(&other_seq)), (vec![], vec![]));
+}
+
+#[test]
+fn assign_spliced() {
+    let ann = annotation();
+    let assigner = ReadAssigner::new(&ann);
+    assert_eq!(summary(assigner.assign(&aln(180, "20M100N20M"))),
+               (vec!["g1"], vec!["t1", "t2"]));
+    assert_eq!(summary(assigner.assign(&aln(210, "80N20M"))), (vec!["g1"], vec!["t1"]));
+}
+
+#[test]
+fn assign_stranded_forward() {
+    let ann = annotation();
+    let mut assigner = ReadAssigner::new(&ann);
+    assigner.strandedness(Strandedness::Forward);
+    assert_eq!(summary(assigner.assign(&aln(230, "20M"))), (vec![], vec![]));
+    assert_eq!(summary(assigner.assign(&aln(230, "20M").reverse(true))),
+               (vec!["g2"], vec!["t3"]));
+    assert_eq!(summary(assigner.assign(&aln(180, "100M"))), (vec!["g1"], vec!["t1", "t2"]));
+}
+
+#[test]
+fn assign_stranded_reverse() {
+    let ann = annotation();
+    let mut assigner = ReadAssigner::new(&ann);
+    assigner.strandedness(Strandedness::Reverse);
+    assert_eq!(summary(assigner.assign(&aln(230, "20M"))), (vec!["g2"], vec!["t3"]));
+    assert_eq!(summary(assigner.assign(&aln(230, "20M").second_mate(true))),
+               (vec![], vec![]));
+    assert_eq!(summary(assigner.assign(&aln(180, "100M").reverse(true).second_mate(true))),
+               (vec!["g2"], vec!["t3"]));
+}
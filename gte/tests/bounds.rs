@@ -0,0 +1,73 @@
+extern crate gte;
+
+use gte::{BoundsAction, BoundsChecker, BoundsIssueKind, GeneStreamExt, RefFlatReader, SeqDict};
+
+
+static GENES: &'static str = "\
+gene1\ttrx1\tchr1\t+\t100\t500\t100\t100\t1\t100,\t500,
+gene2\ttrx2\tchr1\t+\t800\t1500\t800\t800\t2\t800,1100,\t900,1500,
+gene2\ttrx3\tchr1\t+\t800\t900\t800\t800\t1\t800,\t900,
+gene3\ttrx4\tchr1\t-\t1200\t1300\t1200\t1200\t1\t1200,\t1300,
+gene4\ttrx5\tchrUn\t+\t100\t200\t100\t100\t1\t100,\t200,
+";
+
+fn checked_ids(unknown: BoundsAction, exceeding: BoundsAction)
+    -> (Vec<(String, u64)>, Vec<(String, BoundsIssueKind, BoundsAction)>)
+{
+    let dict = SeqDict::from_reader("chr1\t1000\n".as_bytes()).expect("a dictionary");
+    let mut checker = BoundsChecker::new(&dict);
+    let _ = checker.unknown_action(unknown).exceeding_action(exceeding);
+    let mut reader = RefFlatReader::from_reader(GENES.as_bytes());
+    let mut genes = reader.genes_stream().check_bounds(checker);
+    let kept = genes.by_ref()
+        .map(|res| res.expect("a gene"))
+        .map(|gx| (gx.id().unwrap().to_owned(), gx.end()))
+        .collect::<Vec<_>>();
+    let issues = genes.issues().iter()
+        .map(|issue| (issue.gene_id.clone().unwrap(), issue.kind, issue.action))
+        .collect::<Vec<_>>();
+    (kept, issues)
+}
+
+#[test]
+fn bounds_flag() {
+    let (kept, issues) = checked_ids(BoundsAction::Flag, BoundsAction::Flag);
+    assert_eq!(kept.len(), 4);
+    assert_eq!(issues, vec![
+        ("gene2".to_owned(), BoundsIssueKind::ExceedsLength(1000), BoundsAction::Flag),
+        ("gene3".to_owned(), BoundsIssueKind::ExceedsLength(1000), BoundsAction::Flag),
+        ("gene4".to_owned(), BoundsIssueKind::UnknownSequence, BoundsAction::Flag),
+    ]);
+}
+
+#[test]
+fn bounds_clip() {
+    let (kept, issues) = checked_ids(BoundsAction::Clip, BoundsAction::Clip);
+    assert_eq!(kept, vec![("gene1".to_owned(), 500), ("gene2".to_owned(), 900)]);
+    let actions = issues.iter().map(|&(_, _, action)| action).collect::<Vec<_>>();
+    assert_eq!(actions, vec![BoundsAction::Clip, BoundsAction::Drop, BoundsAction::Drop]);
+}
+
+#[test]
+fn bounds_clip_transcripts() {
+    let dict = SeqDict::from_reader("chr1\t1000\n".as_bytes()).expect("a dictionary");
+    let mut checker = BoundsChecker::new(&dict);
+    let _ = checker.exceeding_action(BoundsAction::Clip);
+    let mut reader = RefFlatReader::from_reader(GENES.as_bytes());
+    let gx = reader.genes_stream()
+        .check_bounds(checker)
+        .map(|res| res.expect("a gene"))
+        .find(|gx| gx.id() == Some("gene2"))
+        .expect("a clipped gene");
+    let trx = gx.transcripts().get("trx2").expect("a clipped transcript");
+    assert_eq!(trx.end(), 900);
+    assert_eq!(trx.exons().iter().map(|exn| (exn.start(), exn.end())).collect::<Vec<_>>(),
+               vec![(800, 900)]);
+}
+
+#[test]
+fn bounds_drop() {
+    let (kept, issues) = checked_ids(BoundsAction::Drop, BoundsAction::Drop);
+    assert_eq!(kept, vec![("gene1".to_owned(), 500)]);
+    assert_eq!(issues.len(), 3);
+}
@@ -0,0 +1,74 @@
+#![cfg(feature = "cache")]
+extern crate gte;
+
+use std::env;
+use std::fs;
+
+use gte::{load_cache, read_cache, save_cache, write_cache, CacheError, Gene, GenomeAnnotation,
+          RefFlatReader, CACHE_MAGIC, CACHE_VERSION};
+
+
+static MULT_ROWS_MULT_GENES_WITH_CDS: &'static str =
+    include_str!("data/mult_rows_mult_genes_with_cds.refFlat");
+
+
+fn make_genes() -> Vec<Gene> {
+    let mut reader = RefFlatReader::from_reader(MULT_ROWS_MULT_GENES_WITH_CDS.as_bytes());
+    reader.genes_stream()
+        .collect::<Result<Vec<Gene>, _>>()
+        .expect("genes")
+}
+
+#[test]
+fn cache_annotation_round_trip() {
+    let ann = GenomeAnnotation::from_genes(make_genes());
+    let mut buf = Vec::new();
+    write_cache(&mut buf, &ann).expect("a written cache");
+    assert!(buf.starts_with(CACHE_MAGIC));
+
+    let loaded: GenomeAnnotation = read_cache(&mut buf.as_slice()).expect("a loaded cache");
+    assert_eq!(loaded.len(), ann.len());
+    assert_eq!(loaded.seq_names(), ann.seq_names());
+    let gxs = loaded.genes_at("chr1", 2556000..34850400);
+    assert_eq!(gxs.iter().map(|gx| gx.id()).collect::<Vec<_>>(),
+               vec![Some("TNFRSF14"), Some("SMIM12")]);
+}
+
+#[test]
+fn cache_genes_file_round_trip() {
+    let genes = make_genes();
+    let path = env::temp_dir().join("gte_cache_genes_file_round_trip.bin");
+    save_cache(&path, &genes).expect("a saved cache");
+    let loaded: Vec<Gene> = load_cache(&path).expect("a loaded cache");
+    let _ = fs::remove_file(&path);
+
+    assert_eq!(loaded.len(), genes.len());
+    for (lgx, gx) in loaded.iter().zip(genes.iter()) {
+        assert_eq!(lgx.id(), gx.id());
+        assert_eq!((lgx.start(), lgx.end()), (gx.start(), gx.end()));
+        assert_eq!(lgx.transcripts().len(), gx.transcripts().len());
+    }
+}
+
+#[test]
+fn cache_invalid_header() {
+    let res = read_cache::<_, Vec<Gene>>(&mut &b"chr1\t100\t200\n"[..]);
+    match res {
+        Err(gte::Error::Cache(CacheError::InvalidHeader)) => {},
+        otherwise => panic!("unexpected result: {:?}", otherwise),
+    }
+}
+
+#[test]
+fn cache_unsupported_version() {
+    let mut buf = Vec::new();
+    write_cache(&mut buf, &make_genes()).expect("a written cache");
+    buf[4] = (CACHE_VERSION + 1) as u8;
+    let res = read_cache::<_, Vec<Gene>>(&mut buf.as_slice());
+    match res {
+        Err(gte::Error::Cache(CacheError::UnsupportedVersion(v))) => {
+            assert_eq!(v, CACHE_VERSION + 1)
+        },
+        otherwise => panic!("unexpected result: {:?}", otherwise),
+    }
+}
@@ -0,0 +1,63 @@
+extern crate gte;
+
+use gte::{ClusteredGenes, Strand, TBuilder, Transcript, REF_GENE_ID_ATTR};
+
+
+fn transcript(id: &str, seq_name: &str, strand: Strand, exons: Vec<(u64, u64)>) -> Transcript {
+    let (start, end) = (exons[0].0, exons[exons.len() - 1].1);
+    TBuilder::new(seq_name, start, end)
+        .strand(strand)
+        .id(id)
+        .coords(exons, None)
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn cluster_transcripts() {
+    let transcripts = vec![
+        // shares no exons with trx01, but is linked to it through trx03
+        transcript("trx02", "chrT", Strand::Forward, vec![(300, 400), (700, 800)]),
+        transcript("trx01", "chrT", Strand::Forward, vec![(100, 200), (500, 600)]),
+        transcript("trx03", "chrT", Strand::Forward, vec![(150, 350)]),
+        // lies within the span of the cluster above, but in its intron
+        transcript("trx04", "chrT", Strand::Forward, vec![(420, 480)]),
+        transcript("trx05", "chrT", Strand::Reverse, vec![(100, 200)]),
+        transcript("trx06", "chrS", Strand::Forward, vec![(10, 20)]),
+    ];
+    let genes = ClusteredGenes::new(transcripts.into_iter().map(Ok), "MSTRG")
+        .map(|res| res.expect("a gene"))
+        .collect::<Vec<_>>();
+    let summary = genes.iter()
+        .map(|gx| (gx.id().unwrap(), gx.seq_name(), gx.start(), gx.end(),
+                   gx.transcripts().keys().map(|k| k.as_str()).collect::<Vec<_>>()))
+        .collect::<Vec<_>>();
+    assert_eq!(summary, vec![
+        ("MSTRG.1", "chrT", 100, 800, vec!["trx01", "trx03", "trx02"]),
+        ("MSTRG.2", "chrT", 100, 200, vec!["trx05"]),
+        ("MSTRG.3", "chrT", 420, 480, vec!["trx04"]),
+        ("MSTRG.4", "chrS", 10, 20, vec!["trx06"]),
+    ]);
+    for gx in genes.iter() {
+        for trx in gx.transcripts().values() {
+            assert_eq!(trx.gene_id(), gx.id());
+        }
+    }
+}
+
+#[test]
+fn cluster_transcripts_ref_gene_id() {
+    let trx = TBuilder::new("chrT", 100, 200)
+        .strand(Strand::Forward)
+        .id("trx01")
+        .gene_id("gene01")
+        .coords(vec![(100, 200)], None)
+        .build()
+        .unwrap();
+    let mut genes = ClusteredGenes::new(vec![Ok(trx)], "GENE");
+    let gx = genes.next().unwrap().unwrap();
+    assert!(genes.next().is_none());
+    let trx = gx.transcripts().values().next().unwrap();
+    assert_eq!(trx.gene_id(), Some("GENE.1"));
+    assert_eq!(trx.attributes().get(REF_GENE_ID_ATTR).map(|v| v.as_str()), Some("gene01"));
+}
@@ -0,0 +1,43 @@
+//! Fixtures shared by the sequence-based tests.
+//!
+//! Not every test crate uses every fixture.
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+
+use gte::{Strand, TBuilder, Transcript};
+use self::Strand::*;
+
+
+pub fn make_source() -> HashMap<String, Vec<u8>> {
+    let mut source = HashMap::new();
+    source.insert("chrF".to_owned(), b"GGCCATGGCCGTAGAAATTTTAACCCGGGG".to_vec());
+    source.insert("chrR".to_owned(), b"CCCCGGGTTAAAATTTCTACGGCCATGGCC".to_vec());
+    source
+}
+
+pub fn make_trx_fwd() -> Transcript {
+    trx_fwd(Some((4, 20)))
+}
+
+pub fn make_trx_rev() -> Transcript {
+    trx_rev(Some((10, 26)))
+}
+
+/// Creates the `chrF` transcript with the given coding region.
+pub fn trx_fwd(coding_coord: Option<(u64, u64)>) -> Transcript {
+    TBuilder::new("chrF", 2, 26)
+        .id("trxF")
+        .strand(Forward)
+        .coords(vec![(2, 10), (14, 26)], coding_coord)
+        .build().unwrap()
+}
+
+/// Creates the `chrR` transcript with the given coding region.
+pub fn trx_rev(coding_coord: Option<(u64, u64)>) -> Transcript {
+    TBuilder::new("chrR", 4, 28)
+        .id("trxR")
+        .strand(Reverse)
+        .coords(vec![(4, 16), (20, 28)], coding_coord)
+        .build().unwrap()
+}
@@ -0,0 +1,93 @@
+extern crate gte;
+extern crate linked_hash_map;
+
+mod common;
+
+use linked_hash_map::LinkedHashMap;
+
+use gte::{CodonTable, Consequence, GBuilder, GenomeAnnotation, Strand, TBuilder, Transcript,
+          Variant};
+use Consequence::*;
+use Strand::*;
+use common::{make_source, make_trx_fwd, make_trx_rev};
+
+
+fn consequence(trx: &Transcript, pos: u64, ref_allele: &[u8], alt_allele: &[u8])
+    -> Consequence
+{
+    let mut source = make_source();
+    let variant = Variant::new(trx.seq_name(), pos, ref_allele, alt_allele);
+    trx.variant_consequence(&variant, &mut source, &CodonTable::standard())
+        .expect("a consequence")
+}
+
+#[test]
+fn consequence_severity_order() {
+    assert!(SpliceSite < Nonsense);
+    assert!(Missense < Synonymous);
+    assert!(Intronic < Intergenic);
+}
+
+#[test]
+fn variant_consequence_fwd() {
+    let trx = make_trx_fwd();
+    assert_eq!(consequence(&trx, 0, b"G", b"A"), Intergenic);
+    assert_eq!(consequence(&trx, 27, b"G", b"A"), Intergenic);
+    assert_eq!(consequence(&trx, 3, b"C", b"A"), FivePrimeUtr);
+    assert_eq!(consequence(&trx, 24, b"C", b"A"), ThreePrimeUtr);
+    assert_eq!(consequence(&trx, 11, b"T", b"A"), SpliceSite);
+    assert_eq!(consequence(&trx, 9, b"CGTA", b"C"), SpliceSite);
+    assert_eq!(consequence(&trx, 9, b"C", b"T"), Synonymous);
+    assert_eq!(consequence(&trx, 7, b"G", b"A"), Missense);
+    assert_eq!(consequence(&trx, 14, b"A", b"T"), Nonsense);
+    assert_eq!(consequence(&trx, 20, b"T", b"C"), StopLost);
+    assert_eq!(consequence(&trx, 8, b"CC", b"TA"), Missense);
+    assert_eq!(consequence(&trx, 15, b"A", b"AT"), Frameshift);
+    assert_eq!(consequence(&trx, 15, b"A", b"AGCA"), InframeIndel);
+}
+
+#[test]
+fn variant_consequence_rev() {
+    let trx = make_trx_rev();
+    assert_eq!(consequence(&trx, 27, b"G", b"A"), FivePrimeUtr);
+    assert_eq!(consequence(&trx, 5, b"G", b"A"), ThreePrimeUtr);
+    assert_eq!(consequence(&trx, 20, b"G", b"A"), Synonymous);
+    assert_eq!(consequence(&trx, 22, b"C", b"T"), Missense);
+    assert_eq!(consequence(&trx, 15, b"T", b"A"), Nonsense);
+}
+
+#[test]
+fn variant_consequence_noncoding() {
+    let trx = TBuilder::new("chrF", 0, 30)
+        .strand(Forward)
+        .coords(vec![(0, 5), (15, 30)], None)
+        .build().unwrap();
+    assert_eq!(consequence(&trx, 2, b"C", b"A"), NonCodingExon);
+    assert_eq!(consequence(&trx, 6, b"T", b"A"), SpliceSite);
+    assert_eq!(consequence(&trx, 9, b"C", b"A"), Intronic);
+}
+
+#[test]
+fn annotation_variant_consequences() {
+    let mut trxs = LinkedHashMap::new();
+    let _ = trxs.insert("trxF".to_owned(), make_trx_fwd());
+    let gene = GBuilder::new("chrF", 2, 26)
+        .id("geneF")
+        .strand(Forward)
+        .transcripts(trxs)
+        .build().unwrap();
+    let ann = GenomeAnnotation::from_genes(vec![gene]);
+    let mut source = make_source();
+    let table = CodonTable::standard();
+
+    let variant = Variant::new("chrF", 7, b"G", b"A");
+    let csqs = ann.variant_consequences(&variant, &mut source, &table).expect("consequences");
+    assert_eq!(csqs.iter().map(|&(trx, csq)| (trx.and_then(|t| t.id()), csq)).collect::<Vec<_>>(),
+               vec![(Some("trxF"), Missense)]);
+
+    let variant = Variant::new("chrR", 7, b"G", b"A");
+    let csqs = ann.variant_consequences(&variant, &mut source, &table).expect("consequences");
+    assert_eq!(csqs.len(), 1);
+    assert!(csqs[0].0.is_none());
+    assert_eq!(csqs[0].1, Intergenic);
+}
@@ -0,0 +1,56 @@
+extern crate gte;
+
+use std::env;
+use std::fs;
+use std::io::Read;
+
+use gte::{AnnotationFormat, RefFlatReader, SeqOrder};
+use gte::convert::{convert, ConvertOptions};
+
+
+fn data_path(name: &str) -> String {
+    format!("{}/tests/data/{}", env!("CARGO_MANIFEST_DIR"), name)
+}
+
+#[test]
+fn convert_gtf_to_refflat() {
+    let path = env::temp_dir().join("gte_convert_gtf_to_refflat.refFlat");
+    let num_genes = convert(data_path("single_gene.gtf"), &path, &ConvertOptions::new())
+        .expect("a conversion");
+    let mut reader = RefFlatReader::from_file(&path).expect("a refFlat reader");
+    let trxs = reader.transcripts_stream()
+        .map(|res| res.expect("a transcript"))
+        .collect::<Vec<_>>();
+    let _ = fs::remove_file(&path);
+
+    assert_eq!(num_genes, 1);
+    assert_eq!(trxs.len(), 2);
+    assert_eq!(trxs[0].gene_id(), Some("ENSG00000128645.13"));
+}
+
+#[test]
+fn convert_filtered_sorted() {
+    let path = env::temp_dir().join("gte_convert_filtered_sorted.txt");
+    let opts = ConvertOptions::new()
+        .output_format(AnnotationFormat::Bed12)
+        .filter(|gene| gene.id() == Some("SMIM12"))
+        .sort(SeqOrder::Karyotype);
+    let num_genes = convert(data_path("mult_rows_mult_genes_with_cds.refFlat"), &path, &opts)
+        .expect("a conversion");
+    let mut contents = String::new();
+    let _ = fs::File::open(&path).and_then(|mut f| f.read_to_string(&mut contents))
+        .expect("the output");
+    let _ = fs::remove_file(&path);
+
+    assert_eq!(num_genes, 1);
+    assert_eq!(contents.lines().map(|line| line.split('\t').nth(3).unwrap()).collect::<Vec<_>>(),
+               vec!["NM_001164824", "NM_001164825", "NM_138428"]);
+}
+
+#[test]
+fn convert_unknown_output_format() {
+    let path = env::temp_dir().join("gte_convert_unknown_output_format.txt");
+    let result = convert(data_path("single_gene.gtf"), &path, &ConvertOptions::new());
+    assert!(result.is_err());
+    assert!(!path.exists());
+}
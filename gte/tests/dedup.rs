@@ -0,0 +1,70 @@
+extern crate gte;
+
+use gte::{Deduplicator, DuplicateAction, Gene, RefFlatReader, DUPLICATE_OF_ATTR,
+          MERGED_IDS_ATTR};
+
+
+static DUPLICATE_TRANSCRIPTS: &'static str = "\
+GA\tTA1\tchr1\t+\t100\t500\t150\t450\t2\t100,300,\t200,500,
+GA\tTA2\tchr1\t+\t100\t500\t150\t450\t2\t100,300,\t200,500,
+GA\tTA3\tchr1\t+\t90\t520\t150\t450\t2\t90,300,\t200,520,
+GB\tTB1\tchr1\t+\t100\t500\t100\t100\t2\t100,300,\t200,500,
+GC\tTC1\tchr1\t-\t100\t500\t100\t100\t2\t100,300,\t200,500,
+";
+
+fn read_genes() -> Vec<Gene> {
+    let mut reader = RefFlatReader::from_reader(DUPLICATE_TRANSCRIPTS.as_bytes());
+    reader.genes_stream().map(|res| res.expect("a gene")).collect()
+}
+
+fn transcript_ids(genes: &[Gene]) -> Vec<String> {
+    genes.iter()
+        .flat_map(|gx| gx.transcripts().keys().cloned().collect::<Vec<_>>())
+        .collect()
+}
+
+#[test]
+fn dedup_within_genes() {
+    let genes = Deduplicator::new().dedup_genes(read_genes());
+    assert_eq!(transcript_ids(&genes), vec!["TA1", "TA3", "TB1", "TC1"]);
+    let kept = genes[0].transcripts().get("TA1").expect("a transcript");
+    assert_eq!(kept.attributes().get_vec(MERGED_IDS_ATTR), Some(&vec!["TA2".to_owned()]));
+}
+
+#[test]
+fn dedup_ignoring_utr_ends() {
+    let genes = Deduplicator::new()
+        .ignore_utr_ends(true)
+        .dedup_genes(read_genes());
+    assert_eq!(transcript_ids(&genes), vec!["TA1", "TB1", "TC1"]);
+    let kept = genes[0].transcripts().get("TA1").expect("a transcript");
+    assert_eq!(kept.attributes().get_vec(MERGED_IDS_ATTR),
+               Some(&vec!["TA2".to_owned(), "TA3".to_owned()]));
+}
+
+#[test]
+fn dedup_across_genes() {
+    let genes = Deduplicator::new()
+        .across_genes(true)
+        .dedup_genes(read_genes());
+    assert_eq!(genes.len(), 2);
+    assert_eq!(transcript_ids(&genes), vec!["TA1", "TA3", "TC1"]);
+    let kept = genes[0].transcripts().get("TA1").expect("a transcript");
+    assert_eq!(kept.attributes().get_vec(MERGED_IDS_ATTR),
+               Some(&vec!["TA2".to_owned(), "TB1".to_owned()]));
+}
+
+#[test]
+fn dedup_flagging_duplicates() {
+    let genes = Deduplicator::new()
+        .across_genes(true)
+        .action(DuplicateAction::Flag)
+        .dedup_genes(read_genes());
+    assert_eq!(transcript_ids(&genes), vec!["TA1", "TA2", "TA3", "TB1", "TC1"]);
+    let flags = genes.iter()
+        .flat_map(|gx| gx.transcripts().values()
+            .map(|trx| trx.attributes().get(DUPLICATE_OF_ATTR).cloned())
+            .collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    assert_eq!(flags, vec![None, Some("TA1".to_owned()), None, Some("TA1".to_owned()), None]);
+}
@@ -0,0 +1,63 @@
+extern crate gte;
+extern crate linked_hash_map;
+
+use linked_hash_map::LinkedHashMap;
+
+use gte::{DexSeqWriter, Gene, GBuilder, Strand};
+use Strand::*;
+
+
+fn make_gene(strand: Strand) -> Gene {
+    let mut coords = LinkedHashMap::new();
+    coords.insert("trx01".to_owned(),
+                  ((100, 1000), vec![(100, 300), (400, 500), (700, 1000)], None));
+    coords.insert("trx02".to_owned(),
+                  ((200, 900), vec![(200, 300), (400, 550), (800, 900)], None));
+    GBuilder::new("chrT", 100, 1000)
+        .strand(strand)
+        .id("gene-1")
+        .transcript_coords(coords)
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn gene_exonic_parts() {
+    let parts = make_gene(Reverse).exonic_parts();
+    let summary = parts.iter()
+        .map(|p| (p.number(), p.start(), p.end(), p.transcript_ids().join("+")))
+        .collect::<Vec<_>>();
+    assert_eq!(summary, vec![(1, 100, 200, "trx01".to_owned()),
+                             (2, 200, 300, "trx01+trx02".to_owned()),
+                             (3, 400, 500, "trx01+trx02".to_owned()),
+                             (4, 500, 550, "trx02".to_owned()),
+                             (5, 700, 800, "trx01".to_owned()),
+                             (6, 800, 900, "trx01+trx02".to_owned()),
+                             (7, 900, 1000, "trx01".to_owned())]);
+}
+
+#[test]
+fn dexseq_writer_gene() {
+    let mut coords = LinkedHashMap::new();
+    coords.insert("trx01".to_owned(), ((100, 500), vec![(100, 300), (400, 500)], None));
+    coords.insert("trx02".to_owned(), ((200, 450), vec![(200, 300), (400, 450)], None));
+    let gx = GBuilder::new("chrT", 100, 500)
+        .strand(Forward)
+        .id("gene-1")
+        .transcript_coords(coords)
+        .build()
+        .unwrap();
+    let mut writer = DexSeqWriter::from_memory();
+    writer.write_gene(&gx).expect("flattened rows");
+    assert_eq!(writer.as_string(),
+               "chrT\tdexseq_prepare_annotation.py\taggregate_gene\t101\t500\t.\t+\t.\t\
+                gene_id \"gene-1\"\n\
+                chrT\tdexseq_prepare_annotation.py\texonic_part\t101\t200\t.\t+\t.\t\
+                transcripts \"trx01\"; exonic_part_number \"001\"; gene_id \"gene-1\"\n\
+                chrT\tdexseq_prepare_annotation.py\texonic_part\t201\t300\t.\t+\t.\t\
+                transcripts \"trx01+trx02\"; exonic_part_number \"002\"; gene_id \"gene-1\"\n\
+                chrT\tdexseq_prepare_annotation.py\texonic_part\t401\t450\t.\t+\t.\t\
+                transcripts \"trx01+trx02\"; exonic_part_number \"003\"; gene_id \"gene-1\"\n\
+                chrT\tdexseq_prepare_annotation.py\texonic_part\t451\t500\t.\t+\t.\t\
+                transcripts \"trx01\"; exonic_part_number \"004\"; gene_id \"gene-1\"\n");
+}
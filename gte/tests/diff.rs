@@ -0,0 +1,113 @@
+extern crate gte;
+
+use gte::{AnnotationDiff, DiffOptions, DiffStatus, DiffWriter, GffReader, GffType, RefFlatReader,
+          StructuralChange};
+
+
+static OLD_ANNOTATION: &'static str = "\
+G1\tT1\tchr1\t+\t100\t500\t150\t450\t2\t100,300,\t200,500,
+G1\tT2\tchr1\t+\t100\t500\t150\t450\t2\t100,300,\t200,500,
+G1\tT3\tchr1\t+\t100\t500\t100\t100\t2\t100,300,\t200,500,
+G2\tT4\tchr1\t+\t1000\t2000\t1000\t1000\t1\t1000,\t2000,
+";
+
+static NEW_ANNOTATION: &'static str = "\
+G1\tT1\tchr1\t+\t100\t700\t150\t450\t3\t100,300,600,\t200,500,700,
+G1\tT2\tchr1\t+\t100\t500\t160\t450\t2\t100,310,\t200,500,
+G1\tT3\tchr1\t+\t100\t500\t100\t100\t2\t100,300,\t200,500,
+G1\tT5\tchr1\t+\t100\t500\t100\t100\t1\t100,\t500,
+G3\tT6\tchr2\t-\t100\t500\t100\t100\t1\t100,\t500,
+";
+
+fn diff() -> AnnotationDiff {
+    let mut old_reader = RefFlatReader::from_reader(OLD_ANNOTATION.as_bytes());
+    let mut new_reader = RefFlatReader::from_reader(NEW_ANNOTATION.as_bytes());
+    AnnotationDiff::from_genes(old_reader.genes_stream(), new_reader.genes_stream())
+        .expect("a diff")
+}
+
+#[test]
+fn diff_genes() {
+    let diff = diff();
+    let genes = diff.genes().iter()
+        .map(|gd| (gd.gene_id.as_str(), gd.status, gd.changes.clone()))
+        .collect::<Vec<_>>();
+    assert_eq!(genes, vec![
+        ("G1", DiffStatus::Changed, vec![StructuralChange::BoundaryMoved]),
+        ("G2", DiffStatus::Removed, vec![]),
+        ("G3", DiffStatus::Added, vec![]),
+    ]);
+    assert_eq!(diff.num_genes(DiffStatus::Changed), 1);
+    assert_eq!(diff.num_genes(DiffStatus::Unchanged), 0);
+}
+
+#[test]
+fn diff_transcripts() {
+    let diff = diff();
+    let transcripts = diff.transcripts()
+        .map(|td| (td.transcript_id.as_str(), td.status, td.changes.clone()))
+        .collect::<Vec<_>>();
+    assert_eq!(transcripts, vec![
+        ("T1", DiffStatus::Changed,
+         vec![StructuralChange::BoundaryMoved, StructuralChange::ExonGained]),
+        ("T2", DiffStatus::Changed,
+         vec![StructuralChange::ExonBoundaryMoved, StructuralChange::CdsChanged]),
+        ("T3", DiffStatus::Unchanged, vec![]),
+        ("T5", DiffStatus::Added, vec![]),
+        ("T4", DiffStatus::Removed, vec![]),
+        ("T6", DiffStatus::Added, vec![]),
+    ]);
+    assert_eq!(diff.num_transcripts(DiffStatus::Added), 2);
+    assert_eq!(diff.num_transcript_changes(StructuralChange::CdsChanged), 1);
+}
+
+#[test]
+fn diff_writer() {
+    let mut buf = vec![];
+    DiffWriter::from_writer(&mut buf).write_diff(&diff()).expect("rows");
+    let output = String::from_utf8(buf).expect("a string");
+    assert_eq!(output, "\
+feature\tid\tgene_id\tstatus\tchanges
+gene\tG1\tG1\tchanged\tboundary_moved
+transcript\tT1\tG1\tchanged\tboundary_moved,exon_gained
+transcript\tT2\tG1\tchanged\texon_boundary_moved,cds_changed
+transcript\tT5\tG1\tadded\t.
+gene\tG2\tG2\tremoved\t.
+transcript\tT4\tG2\tremoved\t.
+gene\tG3\tG3\tadded\t.
+transcript\tT6\tG3\tadded\t.
+");
+}
+
+#[test]
+fn diff_ignoring_id_versions() {
+    let old = "G1.1\tT1.1\tchr1\t+\t100\t500\t100\t100\t1\t100,\t500,\n";
+    let new = "G1.2\tT1.3\tchr1\t+\t100\t500\t100\t100\t1\t100,\t500,\n";
+    let mut old_reader = RefFlatReader::from_reader(old.as_bytes());
+    let mut new_reader = RefFlatReader::from_reader(new.as_bytes());
+    let mut opts = DiffOptions::new();
+    let _ = opts.ignore_id_versions(true);
+    let diff = AnnotationDiff::from_genes_with(old_reader.genes_stream(),
+                                               new_reader.genes_stream(), &opts)
+        .expect("a diff");
+    assert_eq!(diff.genes().len(), 1);
+    assert_eq!(diff.genes()[0].gene_id, "G1");
+    assert_eq!(diff.num_transcripts(DiffStatus::Unchanged), 1);
+}
+
+#[test]
+fn diff_unknown_strands() {
+    let annotation = "\
+chr1\tsrc\texon\t101\t200\t.\t.\t.\tgene_id \"G1\"; transcript_id \"T1\";
+chr1\tsrc\texon\t301\t500\t.\t.\t.\tgene_id \"G1\"; transcript_id \"T1\";
+";
+    let mut old_reader = GffReader::from_reader(annotation.as_bytes(), GffType::GTF2);
+    let mut new_reader = GffReader::from_reader(annotation.as_bytes(), GffType::GTF2);
+    let diff = AnnotationDiff::from_genes(old_reader.genes().expect("genes"),
+                                          new_reader.genes().expect("genes"))
+        .expect("a diff");
+    assert_eq!(diff.num_genes(DiffStatus::Unchanged), 1);
+    assert_eq!(diff.num_genes(DiffStatus::Changed), 0);
+    assert_eq!(diff.num_transcripts(DiffStatus::Unchanged), 1);
+    assert_eq!(diff.genes()[0].changes, vec![]);
+}
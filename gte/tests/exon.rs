@@ -7,8 +7,8 @@ extern crate gte;
 use bio::utils::{self, Interval, Strand};
 use multimap::MultiMap;
 
-use gte::{EBuilder, ExonFeature, ExonFeatureKind, ModelError, Error};
-use ModelError::{InvalidInterval, InvalidStrandChar};
+use gte::{EBuilder, ExonFeature, ExonFeatureKind, ModelError, Error, StrandPolicy};
+use ModelError::{InvalidAttribute, InvalidInterval, InvalidStrandChar};
 use ExonFeatureKind::*;
 
 fn make_feat(start: u64, end: u64, kind: ExonFeatureKind) -> ExonFeature {
@@ -54,6 +54,80 @@ fn ebuilder_alt1() {
     assert_eq!(exon.features().len(), 0);
 }
 
+#[test]
+fn ebuilder_multi_valued_attribute() {
+    let exon = EBuilder::new("chrE", 10, 20)
+        .strand(Strand::Forward)
+        .attribute("tag", "basic")
+        .attribute("tag", "CCDS")
+        .build()
+        .unwrap();
+    assert_eq!(exon.attributes_all("tag"), &["basic", "CCDS"]);
+    assert_eq!(exon.attributes().get("tag"), Some(&"basic".to_owned()));
+    assert!(exon.attributes_all("name").is_empty());
+}
+
+#[test]
+fn ebuilder_attribute_order() {
+    let exon = EBuilder::new("chrE", 10, 20)
+        .strand(Strand::Forward)
+        .attribute("zeta", "1")
+        .attribute("alpha", "2")
+        .attribute("zeta", "3")
+        .attribute("mid", "4")
+        .build()
+        .unwrap();
+    assert_eq!(exon.attributes().keys().collect::<Vec<_>>(), vec!["zeta", "alpha", "mid"]);
+    assert_eq!(exon.attributes_all("zeta"), &["1", "3"]);
+
+    let mut attribs = MultiMap::new();
+    attribs.insert("zeta".to_owned(), "1".to_owned());
+    attribs.insert("alpha".to_owned(), "2".to_owned());
+    let exon = EBuilder::new("chrE", 10, 20)
+        .strand(Strand::Forward)
+        .attributes(attribs)
+        .build()
+        .unwrap();
+    assert_eq!(exon.attributes().keys().collect::<Vec<_>>(), vec!["alpha", "zeta"]);
+}
+
+#[test]
+fn exon_attributes_remove() {
+    let mut exon = EBuilder::new("chrE", 10, 20)
+        .strand(Strand::Forward)
+        .attribute("zeta", "1")
+        .attribute("alpha", "2")
+        .attribute("zeta", "3")
+        .build()
+        .unwrap();
+    let attribs = exon.attributes_mut();
+    assert_eq!(attribs.remove("zeta"), Some(vec!["1".to_owned(), "3".to_owned()]));
+    assert_eq!(attribs.remove("zeta"), None);
+    assert!(!attribs.contains_key("zeta"));
+    attribs.insert("zeta".to_owned(), "4".to_owned());
+    attribs.shrink_to_fit();
+    let entries = attribs.iter()
+        .map(|(key, values)| (key.as_str(), values.len()))
+        .collect::<Vec<_>>();
+    assert_eq!(entries, vec![("alpha", 1), ("zeta", 1)]);
+}
+
+#[test]
+fn ebuilder_source_score() {
+    let mut exon = EBuilder::new("chrE", 10, 20)
+        .strand(Strand::Forward)
+        .source("HAVANA")
+        .score(0.5)
+        .build()
+        .unwrap();
+    assert_eq!(exon.source(), Some("HAVANA"));
+    assert_eq!(exon.score(), Some(0.5));
+    exon.set_source(None::<String>);
+    exon.set_score(None);
+    assert_eq!(exon.source(), None);
+    assert_eq!(exon.score(), None);
+}
+
 #[test]
 fn ebuilder_interval_invalid() {
     let exonb = EBuilder::new("chrE", 20, 10).build();
@@ -69,6 +143,26 @@ fn ebuilder_strand_unspecified() {
     assert!(matches!(exonb.unwrap_err(), Error::Model(ModelError::UnspecifiedStrand)));
 }
 
+#[test]
+fn ebuilder_strand_policy() {
+    let exon = EBuilder::new("chrT", 20, 30)
+        .strand_policy(StrandPolicy::DefaultUnknown)
+        .build()
+        .expect("an exon");
+    assert!(exon.strand().is_unknown());
+    let exon = EBuilder::new("chrT", 20, 30)
+        .strand_policy(StrandPolicy::DefaultForward)
+        .build()
+        .expect("an exon");
+    assert_eq!(exon.strand(), &Strand::Forward);
+    let exon = EBuilder::new("chrT", 20, 30)
+        .strand_policy(StrandPolicy::DefaultForward)
+        .strand(Strand::Reverse)
+        .build()
+        .expect("an exon");
+    assert_eq!(exon.strand(), &Strand::Reverse);
+}
+
 #[test]
 fn ebuilder_strand_char_unexpected() {
     let exonb = EBuilder::new("chrE", 10, 20)
@@ -89,3 +183,23 @@ fn ebuilder_strand_char_conflicting() {
     let exon = exonb.unwrap();
     assert_eq!(exon.strand(), &Strand::Reverse);
 }
+
+#[test]
+fn exon_typed_attributes() {
+    let exon = EBuilder::new("chrE", 10, 20)
+        .strand(Strand::Forward)
+        .attribute("exon_number", "3")
+        .attribute("score_like", "0.25")
+        .attribute("constitutive", "1")
+        .attribute("level", "high")
+        .build()
+        .expect("an exon");
+    assert_eq!(exon.attribute_as::<u32>("exon_number").expect("a value"), Some(3));
+    assert_eq!(exon.attribute_int("exon_number").expect("a value"), Some(3));
+    assert_eq!(exon.attribute_float("score_like").expect("a value"), Some(0.25));
+    assert_eq!(exon.attribute_bool("constitutive").expect("a value"), Some(true));
+    assert_eq!(exon.attribute_int("missing").expect("no value"), None);
+    assert!(matches!(exon.attribute_int("level").unwrap_err(),
+                     Error::Model(InvalidAttribute(_, _))));
+    assert!(exon.attribute_bool("level").is_err());
+}
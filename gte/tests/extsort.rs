@@ -0,0 +1,120 @@
+extern crate gte;
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+use gte::{gff_lines, ExternalSorter, GffLine, RefFlatReader, RefFlatRecord, SeqOrder,
+          SortRecord};
+
+
+static MULT_ROWS_NO_CDS: &'static str = include_str!("data/mult_rows_no_cds.refFlat");
+static MULT_ROWS_MULT_GENES_WITH_CDS: &'static str =
+    include_str!("data/mult_rows_mult_genes_with_cds.refFlat");
+static SINGLE_GENE_GTF: &'static str = include_str!("data/single_gene.gtf");
+static WITH_FASTA_GFF3: &'static str = include_str!("data/with_fasta.gff3");
+
+
+fn tmp_dir(name: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("gte-test-extsort-{}", name));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).expect("a temporary directory");
+    dir
+}
+
+fn refflat_records() -> Vec<gte::Result<RefFlatRecord>> {
+    let input = format!("{}{}", MULT_ROWS_NO_CDS, MULT_ROWS_MULT_GENES_WITH_CDS);
+    let mut reader = RefFlatReader::from_reader(input.as_bytes());
+    let mut records = reader.records_stream().collect::<Vec<_>>();
+    records.reverse();
+    records
+}
+
+#[test]
+fn extsort_refflat_in_memory() {
+    let dir = tmp_dir("in-memory");
+    let sorted = ExternalSorter::new()
+        .tmp_dir(dir.clone())
+        .sort(refflat_records())
+        .expect("sorted records");
+    assert_eq!(sorted.num_spills(), 0);
+    let tids = sorted.map(|rec| rec.expect("a record").transcript_id().to_owned())
+        .collect::<Vec<String>>();
+    assert_eq!(tids, vec!["NR_046018", "NM_003820", "NM_001297605", "NM_001164824",
+                          "NM_001164825", "NM_138428", "NR_030296"]);
+    assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+}
+
+#[test]
+fn extsort_refflat_spilled() {
+    let dir = tmp_dir("spilled");
+    {
+        let sorted = ExternalSorter::new()
+            .chunk_size(2)
+            .tmp_dir(dir.clone())
+            .sort(refflat_records())
+            .expect("sorted records");
+        assert_eq!(sorted.num_spills(), 4);
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 4);
+        let records = sorted.collect::<gte::Result<Vec<RefFlatRecord>>>().expect("records");
+        let tids = records.iter().map(|rec| rec.transcript_id()).collect::<Vec<&str>>();
+        assert_eq!(tids, vec!["NR_046018", "NM_003820", "NM_001297605", "NM_001164824",
+                              "NM_001164825", "NM_138428", "NR_030296"]);
+        assert_eq!(records[0].exon_starts(), &[11873, 12612, 13220]);
+        assert_eq!(records[3].coding_start(), 34855698);
+    }
+    assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+}
+
+#[test]
+fn extsort_refflat_seq_order() {
+    let sorted = ExternalSorter::new()
+        .chunk_size(3)
+        .seq_order(SeqOrder::Custom(vec![("chr3".to_owned(), 0), ("chr1".to_owned(), 1)]
+                                    .into_iter().collect()))
+        .sort(refflat_records())
+        .expect("sorted records");
+    let seq_names = sorted.map(|rec| rec.expect("a record").seq_name().to_owned())
+        .collect::<Vec<String>>();
+    assert_eq!(seq_names, vec!["chr3", "chr1", "chr1", "chr1", "chr1", "chr1", "chr1"]);
+}
+
+#[test]
+fn extsort_refflat_input_error() {
+    let records = vec![Ok(refflat_records().remove(0).unwrap()),
+                       RefFlatReader::from_reader("a\tb\n".as_bytes()).records_stream()
+                           .next().unwrap()];
+    assert!(ExternalSorter::new().sort(records).is_err());
+}
+
+#[test]
+fn extsort_gff_lines() {
+    let mut lines = gff_lines(SINGLE_GENE_GTF.as_bytes()).collect::<Vec<_>>();
+    lines.reverse();
+    let num_lines = lines.len();
+    let sorted = ExternalSorter::new()
+        .chunk_size(4)
+        .sort(lines)
+        .expect("sorted lines")
+        .collect::<gte::Result<Vec<GffLine>>>()
+        .expect("lines");
+    assert_eq!(sorted.len(), num_lines);
+    assert_eq!(sorted[0].start(), 176188578);
+    assert!(sorted.windows(2).all(|w| w[0].start() <= w[1].start()));
+    assert!(sorted.iter().all(|line| SINGLE_GENE_GTF.contains(line.as_str())));
+}
+
+#[test]
+fn gff_lines_stop_at_fasta() {
+    let lines = gff_lines(WITH_FASTA_GFF3.as_bytes())
+        .collect::<gte::Result<Vec<GffLine>>>()
+        .expect("lines");
+    assert_eq!(lines.len(), 4);
+    assert_eq!(lines[3].start(), 700);
+}
+
+#[test]
+fn gff_line_invalid() {
+    assert!(GffLine::parse("chr1\tsrc\texon\tx\t10\t.\t+\t.\tgene_id \"g\";").is_err());
+    assert!(GffLine::parse("chr1\tsrc\texon\t10").is_err());
+}
@@ -0,0 +1,82 @@
+extern crate gte;
+
+use gte::{GffReader, GffType, Strand};
+
+
+static NESTED_GFF3: &'static str = "\
+chr1\tsrc\tgene\t101\t1000\t.\t+\t.\tID=g1;Name=A
+chr1\tsrc\tmRNA\t101\t1000\t.\t+\t.\tID=t1;Parent=g1
+chr1\tsrc\texon\t101\t300\t.\t+\t.\tParent=t1
+chr1\tsrc\texon\t701\t1000\t.\t+\t.\tParent=t1
+chr1\tsrc\tCDS\t201\t300\t.\t+\t0\tID=cds1;Parent=t1
+chr1\tsrc\tCDS\t701\t797\t.\t+\t2\tID=cds1;Parent=t1
+chr1\tsrc\tstop_codon\t798\t800\t.\t+\t0\tParent=t1
+chr1\tsrc\tpseudogene\t2001\t2500\t.\t-\t.\tID=p1
+chr1\tsrc\texon\t2001\t2100\t.\t-\t.\tParent=p1
+chr1\tsrc\texon\t2401\t2500\t.\t-\t.\tParent=p1
+chr1\tsrc\tgene\t3001\t3200\t.\t+\t.\tID=g2
+chr1\tsrc\tprimary_transcript\t3001\t3200\t.\t+\t.\tID=pt1;Parent=g2
+chr1\tsrc\tmiRNA\t3051\t3072\t.\t+\t.\tID=mir1;Parent=pt1
+chr1\tsrc\texon\t3051\t3072\t.\t+\t.\tParent=mir1
+chr1\tsrc\tenhancer\t4001\t4100\t.\t.\t.\tID=e1;function=activator
+";
+
+#[test]
+fn gff3_feature_tree() {
+    let mut reader = GffReader::from_reader(NESTED_GFF3.as_bytes(), GffType::GFF3);
+    let tree = reader.feature_tree().expect("a feature tree");
+    let roots = tree.roots().iter().map(|node| node.id()).collect::<Vec<_>>();
+    assert_eq!(roots, vec![Some("g1"), Some("p1"), Some("g2"), Some("e1")]);
+
+    let mrna = tree.find("t1").expect("a node");
+    assert_eq!(mrna.feature_type(), "mRNA");
+    assert_eq!(mrna.children().len(), 5);
+    assert!(mrna.attributes().get("Parent").is_none());
+
+    let mirna = tree.find("mir1").expect("a node");
+    assert_eq!((mirna.start(), mirna.end()), (3050, 3072));
+    assert!(mirna.is_transcript());
+
+    let enhancer = tree.find("e1").expect("a node");
+    assert!(enhancer.strand().is_unknown());
+    assert_eq!(enhancer.attributes().get("function"), Some(&"activator".to_owned()));
+    assert!(!enhancer.is_gene());
+}
+
+#[test]
+fn gff3_feature_tree_genes() {
+    let mut reader = GffReader::from_reader(NESTED_GFF3.as_bytes(), GffType::GFF3);
+    let tree = reader.feature_tree().expect("a feature tree");
+    let genes = tree.genes(false).into_iter()
+        .map(|res| res.expect("a gene"))
+        .collect::<Vec<_>>();
+    assert_eq!(genes.len(), 2);
+
+    let trx = genes[0].transcripts().get("t1").expect("a transcript");
+    assert_eq!(trx.gene_id(), Some("g1"));
+    assert_eq!(trx.exons().len(), 2);
+    assert_eq!(trx.coding_coord(true), Some((200, 800)));
+    assert_eq!(genes[0].attributes().get("Name"), Some(&"A".to_owned()));
+
+    let trx = genes[1].transcripts().get("p1").expect("a transcript");
+    assert_eq!(trx.strand(), &Strand::Reverse);
+    assert_eq!(trx.exons().len(), 2);
+    assert_eq!(trx.coding_coord(true), None);
+
+    let mirna = tree.find("mir1").expect("a node").to_transcript(Some("g2"), false)
+        .expect("a transcript");
+    assert_eq!((mirna.id(), mirna.gene_id()), (Some("mir1"), Some("g2")));
+}
+
+#[test]
+fn gff3_feature_tree_parent_cycle() {
+    let input = "\
+chr1\tsrc\tgene\t101\t1000\t.\t+\t.\tID=a;Parent=b
+chr1\tsrc\tmRNA\t101\t1000\t.\t+\t.\tID=b;Parent=a
+";
+    let mut reader = GffReader::from_reader(input.as_bytes(), GffType::GFF3);
+    assert!(reader.feature_tree().is_err());
+
+    let mut reader = GffReader::from_reader(NESTED_GFF3.as_bytes(), GffType::GTF2);
+    assert!(reader.feature_tree().is_err());
+}
@@ -0,0 +1,62 @@
+extern crate gte;
+
+use gte::{parse_region, FeatureFilter, FilteredGenes, FilteredTranscripts, GffReader, GffType,
+          RefFlatReader};
+
+
+static MULT_ROWS_MULT_GENES_WITH_CDS: &'static str =
+    include_str!("data/mult_rows_mult_genes_with_cds.refFlat");
+static SINGLE_GENE_GTF: &'static str = include_str!("data/single_gene.gtf");
+
+#[test]
+fn filtered_genes_by_location() {
+    let cases = vec![
+        ("chr1:1-3,000,000", vec!["TNFRSF14"]),
+        ("chr1:2565622-34850361", vec!["TNFRSF14"]),
+        ("chr1:2565623-34850361", vec![]),
+        ("chr1", vec!["TNFRSF14", "SMIM12"]),
+        ("chr2", vec![]),
+    ];
+    for (region, expected) in cases {
+        let (seq_name, start, end) = parse_region(region).expect("a region");
+        let mut filter = FeatureFilter::new();
+        let _ = filter.region(seq_name, start, end);
+        let mut reader = RefFlatReader::from_reader(MULT_ROWS_MULT_GENES_WITH_CDS.as_bytes());
+        let ids = FilteredGenes::new(reader.genes_stream(), filter)
+            .map(|res| res.expect("a gene").id().unwrap().to_owned())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, expected, "{}", region);
+    }
+}
+
+#[test]
+fn filtered_transcripts_by_attributes() {
+    let cases = vec![
+        (Some("basic"), None, vec!["ENST00000331462.5"]),
+        (None, Some("miRNA"), vec!["ENST00000610524.1"]),
+        (Some("basic"), Some("miRNA"), vec![]),
+    ];
+    for (tag, biotype, expected) in cases {
+        let mut filter = FeatureFilter::new();
+        if let Some(tag) = tag {
+            let _ = filter.tag(tag);
+        }
+        if let Some(biotype) = biotype {
+            let _ = filter.biotype(biotype);
+        }
+        let mut reader = GffReader::from_reader(SINGLE_GENE_GTF.as_bytes(), GffType::GTF2);
+        let _ = reader.keep_attributes(true);
+        let mut ids = FilteredTranscripts::new(reader.transcripts().expect("transcripts"), filter)
+            .map(|res| res.expect("a transcript").id().unwrap().to_owned())
+            .collect::<Vec<_>>();
+        ids.sort();
+        assert_eq!(ids, expected);
+    }
+}
+
+#[test]
+fn parse_region_invalid() {
+    for region in vec!["", ":1-10", "chr1:0-10", "chr1:10-1", "chr1:a-10", "chr1:10"] {
+        assert!(parse_region(region).is_err(), "{}", region);
+    }
+}
@@ -6,7 +6,8 @@ extern crate multimap;
 use linked_hash_map::LinkedHashMap;
 use multimap::MultiMap;
 
-use gte::{Strand, GBuilder};
+use gte::{Strand, GBuilder, TBuilder, FlankSide, GeneFeatureKind, RefFlatWriter,
+          StrandConflictPolicy, BoundsWarning, strip_id_version};
 use Strand::*;
 
 #[test]
@@ -38,3 +39,302 @@ fn gbuilder_basic() {
     assert_eq!(gx.attributes().len(), 2);
     assert_eq!(gx.transcripts().len(), 2);
 }
+
+#[test]
+fn gene_flank() {
+    let gx_fwd = GBuilder::new("chrT", 100, 1000).strand(Forward).build().unwrap();
+    let up_fwd = gx_fwd.flank(200, FlankSide::Upstream);
+    assert_eq!((up_fwd.start(), up_fwd.end()), (0, 100));
+    assert_eq!(up_fwd.kind(), &GeneFeatureKind::UpstreamFlank);
+    let down_fwd = gx_fwd.flank(200, FlankSide::Downstream);
+    assert_eq!((down_fwd.start(), down_fwd.end()), (1000, 1200));
+    assert_eq!(down_fwd.kind(), &GeneFeatureKind::DownstreamFlank);
+
+    let gx_rev = GBuilder::new("chrT", 100, 1000).strand(Reverse).build().unwrap();
+    let up_rev = gx_rev.flank(200, FlankSide::Upstream);
+    assert_eq!((up_rev.start(), up_rev.end()), (1000, 1200));
+    let down_rev = gx_rev.flank(50, FlankSide::Downstream);
+    assert_eq!((down_rev.start(), down_rev.end()), (50, 100));
+}
+
+#[test]
+fn gene_set_id() {
+    let mut coords = LinkedHashMap::new();
+    coords.insert("trx01".to_owned(), ((100, 1000), vec![(100, 300), (700, 1000)], None));
+    let mut gx = GBuilder::new("chrT", 100, 1000)
+        .strand(Forward)
+        .id("gene-1")
+        .transcript_coords(coords)
+        .build()
+        .unwrap();
+    gx.set_id(Some("gene-2"));
+    assert_eq!(gx.id(), Some("gene-2"));
+    let trx = gx.transcripts().get("trx01").unwrap();
+    assert_eq!(trx.id(), Some("trx01"));
+    assert_eq!(trx.gene_id(), Some("gene-2"));
+    assert!(trx.exons().iter().all(|exn| exn.gene_id() == Some("gene-2")));
+}
+
+#[test]
+fn gene_collapse() {
+    let mut coords = LinkedHashMap::new();
+    coords.insert("trx01".to_owned(),
+                  ((100, 1000), vec![(100, 300), (400, 500), (700, 1000)], Some((200, 800))));
+    coords.insert("trx02".to_owned(),
+                  ((150, 900), vec![(150, 350), (500, 550), (600, 700), (800, 900)], None));
+    let gx = GBuilder::new("chrT", 100, 1000)
+        .strand(Reverse)
+        .id("gene-1")
+        .transcript_coords(coords)
+        .build()
+        .unwrap();
+
+    let cgx = gx.collapse().unwrap();
+    assert_eq!((cgx.start(), cgx.end()), (100, 1000));
+    assert_eq!(cgx.id(), Some("gene-1"));
+    assert_eq!(cgx.transcripts().len(), 1);
+    let trx = cgx.transcripts().get("gene-1").unwrap();
+    assert_eq!(trx.id(), Some("gene-1"));
+    assert_eq!(trx.gene_id(), Some("gene-1"));
+    assert_eq!(trx.strand(), &Reverse);
+    assert_eq!(trx.coding_coord(true), None);
+    assert_eq!(trx.exons().iter().map(|exn| (exn.start(), exn.end())).collect::<Vec<_>>(),
+               vec![(100, 350), (400, 550), (600, 1000)]);
+
+    let mut writer = RefFlatWriter::from_memory();
+    writer.write_gene(&cgx).unwrap();
+    assert_eq!(writer.as_string(),
+               "gene-1\tgene-1\tchrT\t-\t100\t1000\t1000\t1000\t3\t100,400,600,\t350,550,1000,\n");
+}
+
+#[test]
+fn gene_collapse_no_transcripts() {
+    let gx = GBuilder::new("chrT", 100, 1000).id("gene-1").strand(Reverse).build().unwrap();
+    let cgx = gx.collapse().unwrap();
+    assert_eq!(cgx.id(), Some("gene-1"));
+    assert!(cgx.transcripts().is_empty());
+}
+
+#[test]
+fn gene_exonic_intervals() {
+    let mut coords = LinkedHashMap::new();
+    coords.insert("trx01".to_owned(),
+                  ((100, 1000), vec![(100, 300), (400, 500), (700, 1000)], Some((200, 800))));
+    coords.insert("trx02".to_owned(),
+                  ((150, 900), vec![(150, 350), (500, 550), (600, 650), (800, 900)], None));
+    let gx = GBuilder::new("chrT", 100, 1000)
+        .strand(Forward)
+        .id("gene-1")
+        .transcript_coords(coords)
+        .build()
+        .unwrap();
+    assert_eq!(gx.exonic_intervals().iter().map(|iv| (iv.start, iv.end)).collect::<Vec<_>>(),
+               vec![(100, 350), (400, 550), (600, 650), (700, 1000)]);
+    assert_eq!(gx.exonic_length(), 250 + 150 + 50 + 300);
+
+    let gx = GBuilder::new("chrT", 100, 1000).id("gene-2").strand(Forward).build().unwrap();
+    assert!(gx.exonic_intervals().is_empty());
+    assert_eq!(gx.exonic_length(), 0);
+}
+
+#[test]
+fn gene_canonical_transcript() {
+    let mut coords = LinkedHashMap::new();
+    coords.insert("trx01".to_owned(),
+                  ((150, 900), vec![(150, 350), (500, 550), (600, 700), (800, 900)], None));
+    coords.insert("trx02".to_owned(),
+                  ((100, 1000), vec![(100, 300), (400, 500), (700, 1000)], Some((200, 800))));
+    let gx = GBuilder::new("chrT", 100, 1000)
+        .strand(Forward)
+        .id("gene-1")
+        .transcript_coords(coords)
+        .build()
+        .unwrap();
+    assert_eq!(gx.canonical_transcript().and_then(|trx| trx.id()), Some("trx02"));
+
+    let mut transcripts = LinkedHashMap::new();
+    for &(id, tag) in &[("trx01", "basic"), ("trx02", "Ensembl_canonical"),
+                        ("trx03", "MANE_Select"), ("trx04", "MANE_Plus_Clinical")] {
+        let trx = TBuilder::new("chrT", 100, 1000)
+            .strand(Forward)
+            .id(id)
+            .attribute("tag", tag)
+            .coords(vec![(100, 1000)], None)
+            .build()
+            .unwrap();
+        transcripts.insert(id.to_owned(), trx);
+    }
+    let gx = GBuilder::new("chrT", 100, 1000)
+        .strand(Forward)
+        .id("gene-2")
+        .transcripts(transcripts)
+        .build()
+        .unwrap();
+    let trx = gx.canonical_transcript().unwrap();
+    assert_eq!(trx.id(), Some("trx03"));
+    assert!(trx.is_mane_select());
+    assert!(!trx.is_mane_plus_clinical());
+    assert!(!trx.is_ensembl_canonical());
+    let trx = gx.transcripts().get("trx02").unwrap();
+    assert!(trx.is_ensembl_canonical());
+    assert!(gx.transcripts().get("trx04").unwrap().is_mane_plus_clinical());
+
+    let renamed = GBuilder::from_gene(&gx).id("gene-3").build().unwrap();
+    assert_eq!(renamed.id(), Some("gene-3"));
+    assert_eq!(renamed.interval(), gx.interval());
+    assert_eq!(renamed.transcripts().len(), 4);
+    assert_eq!(renamed.canonical_transcript().and_then(|trx| trx.id()), Some("trx03"));
+
+    let gx = GBuilder::new("chrT", 100, 1000).id("gene-3").strand(Forward).build().unwrap();
+    assert!(gx.canonical_transcript().is_none());
+}
+
+#[test]
+fn gene_transcripts_insertion_order() {
+    let mut coords = LinkedHashMap::new();
+    for id in &["trx03", "trx01", "trx10", "trx02"] {
+        coords.insert(id.to_string(), ((100, 1000), vec![(100, 1000)], None));
+    }
+    let gx = GBuilder::new("chrT", 100, 1000)
+        .strand(Forward)
+        .id("gene-1")
+        .transcript_coords(coords)
+        .build()
+        .unwrap();
+    let ids = gx.transcripts().values().map(|trx| trx.id().unwrap()).collect::<Vec<_>>();
+    assert_eq!(ids, vec!["trx03", "trx01", "trx10", "trx02"]);
+    assert_eq!(gx.transcript("trx10").and_then(|trx| trx.id()), Some("trx10"));
+    assert!(gx.transcript("trx04").is_none());
+}
+
+#[test]
+fn gene_into_iter() {
+    let mut coords = LinkedHashMap::new();
+    coords.insert("trx02".to_owned(), ((100, 1000), vec![(100, 1000)], None));
+    coords.insert("trx01".to_owned(), ((200, 900), vec![(200, 900)], None));
+    let gx = GBuilder::new("chrT", 100, 1000)
+        .strand(Forward)
+        .transcript_coords(coords)
+        .build()
+        .unwrap();
+
+    let mut ids = Vec::new();
+    for trx in &gx {
+        ids.push(trx.id().unwrap());
+    }
+    assert_eq!(ids, vec!["trx02", "trx01"]);
+    assert_eq!(gx.iter().len(), 2);
+    assert_eq!(gx.iter().rev().next().and_then(|trx| trx.id()), Some("trx01"));
+    let spans = gx.clone().into_iter().map(|trx| trx.span()).collect::<Vec<_>>();
+    assert_eq!(spans, vec![900, 700]);
+}
+
+#[test]
+fn strip_id_versions() {
+    assert_eq!(strip_id_version("ENST00000367770.8"), "ENST00000367770");
+    assert_eq!(strip_id_version("ENSG00000182378.14_PAR_Y"), "ENSG00000182378_PAR_Y");
+    assert_eq!(strip_id_version("ENST00000367770"), "ENST00000367770");
+    assert_eq!(strip_id_version("NM_001297605.2"), "NM_001297605");
+    assert_eq!(strip_id_version("gene.a"), "gene.a");
+    assert_eq!(strip_id_version("gene."), "gene.");
+
+    let mut coords = LinkedHashMap::new();
+    coords.insert("ENST01.2".to_owned(), ((100, 1000), vec![(100, 1000)], None));
+    coords.insert("ENST01.3".to_owned(), ((100, 500), vec![(100, 500)], None));
+    coords.insert("ENST02".to_owned(), ((200, 800), vec![(200, 800)], None));
+    let mut gx = GBuilder::new("chrT", 100, 1000)
+        .strand(Forward)
+        .id("ENSG01.5")
+        .transcript_coords(coords)
+        .build()
+        .unwrap();
+    gx.strip_id_versions();
+    assert_eq!(gx.id(), Some("ENSG01"));
+    let tids = gx.transcripts().keys().cloned().collect::<Vec<_>>();
+    assert_eq!(tids, vec!["ENST01", "ENST02"]);
+    let trx = gx.transcripts().get("ENST01").unwrap();
+    assert_eq!((trx.id(), trx.gene_id()), (Some("ENST01"), Some("ENSG01")));
+    assert_eq!(trx.end(), 1000);
+    assert_eq!(trx.exons()[0].transcript_id(), Some("ENST01"));
+}
+
+fn stranded_transcripts() -> LinkedHashMap<String, gte::Transcript> {
+    let mut transcripts = LinkedHashMap::new();
+    for &(id, strand, start, end) in &[("trx01", Forward, 100, 500), ("trx02", Reverse, 600, 1000),
+                                       ("trx03", Unknown, 200, 400)] {
+        let trx = TBuilder::new("chrT", start, end)
+            .strand(strand)
+            .id(id)
+            .coords(vec![(start, end)], None)
+            .build()
+            .unwrap();
+        transcripts.insert(id.to_owned(), trx);
+    }
+    transcripts
+}
+
+#[test]
+fn gbuilder_strand_conflict() {
+    let builder = || {
+        GBuilder::new("chrT", 100, 1000)
+            .strand(Forward)
+            .id("gene-1")
+            .transcripts(stranded_transcripts())
+    };
+    assert!(builder().build().is_err());
+    assert!(builder().strand_conflict_policy(StrandConflictPolicy::Split).build().is_err());
+
+    let gx = builder().strand_conflict_policy(StrandConflictPolicy::Unknown).build().unwrap();
+    assert!(gx.strand().is_unknown());
+    assert_eq!(gx.transcripts().len(), 3);
+
+    let gxs = builder().strand_conflict_policy(StrandConflictPolicy::Split).build_genes().unwrap();
+    let summary = gxs.iter()
+        .map(|gx| (*gx.strand(), gx.start(), gx.end(), gx.id().unwrap(),
+                   gx.transcripts().keys().cloned().collect::<Vec<_>>()))
+        .collect::<Vec<_>>();
+    assert_eq!(summary, vec![
+        (Forward, 100, 500, "gene-1", vec!["trx01".to_owned(), "trx03".to_owned()]),
+        (Reverse, 600, 1000, "gene-1", vec!["trx02".to_owned()]),
+    ]);
+
+    let mut transcripts = stranded_transcripts();
+    let _ = transcripts.remove("trx02");
+    let gx = GBuilder::new("chrT", 100, 1000)
+        .strand(Forward)
+        .transcripts(transcripts)
+        .build();
+    assert!(gx.is_ok(), "{:?}", gx);
+}
+
+#[test]
+fn gbuilder_extend_bounds() {
+    let builder = || {
+        let mut transcripts = stranded_transcripts();
+        let _ = transcripts.remove("trx02");
+        GBuilder::new("chrT", 150, 450)
+            .strand(Forward)
+            .id("gene-1")
+            .transcripts(transcripts)
+    };
+    assert!(builder().build().is_err());
+
+    let (gx, warnings) = builder().extend_bounds(true).build_with_warnings().unwrap();
+    assert_eq!((gx.start(), gx.end()), (100, 500));
+    assert_eq!(warnings, vec![BoundsWarning {
+        gene_id: Some("gene-1".to_owned()),
+        transcript_ids: vec!["trx01".to_owned()],
+        declared: (150, 450),
+        adjusted: (100, 500),
+    }]);
+
+    let (gx, warnings) = GBuilder::new("chrT", 100, 500)
+        .strand(Forward)
+        .transcript_coords(vec![("trx01".to_owned(), ((100, 600), vec![(100, 600)], None))]
+                           .into_iter().collect())
+        .extend_bounds(true)
+        .build_with_warnings()
+        .unwrap();
+    assert_eq!(gx.end(), 600);
+    assert_eq!(warnings.len(), 1);
+}
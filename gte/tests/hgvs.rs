@@ -0,0 +1,88 @@
+extern crate gte;
+
+mod common;
+
+use std::collections::HashMap;
+
+use gte::{CodonTable, Strand, TBuilder, Transcript};
+use Strand::*;
+use common::{make_source, make_trx_fwd, make_trx_rev};
+
+
+fn hgvs_c(trx: &Transcript, positions: Vec<u64>) -> Vec<Option<String>> {
+    positions.into_iter().map(|pos| trx.hgvs_c(pos)).collect()
+}
+
+fn some(values: Vec<&str>) -> Vec<Option<String>> {
+    values.into_iter().map(|v| Some(v.to_owned())).collect()
+}
+
+#[test]
+fn hgvs_c_fwd() {
+    let trx = make_trx_fwd();
+    assert_eq!(hgvs_c(&trx, vec![2, 3, 4, 9, 14, 22, 23, 25]),
+               some(vec!["c.-2", "c.-1", "c.1", "c.6", "c.7", "c.15", "c.*1", "c.*3"]));
+    assert_eq!(hgvs_c(&trx, vec![10, 11, 12, 13]),
+               some(vec!["c.6+1", "c.6+2", "c.7-2", "c.7-1"]));
+    assert_eq!(hgvs_c(&trx, vec![1, 26]), vec![None, None]);
+}
+
+#[test]
+fn hgvs_c_rev() {
+    let trx = make_trx_rev();
+    assert_eq!(hgvs_c(&trx, vec![27, 25, 15, 7, 6, 4]),
+               some(vec!["c.-2", "c.1", "c.7", "c.15", "c.*1", "c.*3"]));
+    assert_eq!(hgvs_c(&trx, vec![19, 18, 17, 16]),
+               some(vec!["c.6+1", "c.6+2", "c.7-2", "c.7-1"]));
+}
+
+#[test]
+fn hgvs_c_odd_intron() {
+    let trx = TBuilder::new("chrF", 0, 30)
+        .strand(Forward)
+        .coords(vec![(0, 10), (15, 30)], Some((5, 20)))
+        .build().unwrap();
+    assert_eq!(hgvs_c(&trx, vec![11, 12, 13, 14]),
+               some(vec!["c.5+2", "c.5+3", "c.6-2", "c.6-1"]));
+}
+
+#[test]
+fn hgvs_n() {
+    let trx = TBuilder::new("chrF", 2, 26)
+        .strand(Forward)
+        .coords(vec![(2, 10), (14, 26)], None)
+        .build().unwrap();
+    assert_eq!(trx.hgvs_n(4), Some("n.3".to_owned()));
+    assert_eq!(trx.hgvs_n(11), Some("n.8+2".to_owned()));
+    assert_eq!(trx.hgvs_n(12), Some("n.9-2".to_owned()));
+    assert_eq!(trx.hgvs_n(13), Some("n.9-1".to_owned()));
+    assert_eq!(trx.hgvs_n(30), None);
+    assert_eq!(trx.hgvs_c(4), None);
+}
+
+#[test]
+fn hgvs_p_fwd() {
+    let mut source = make_source();
+    let table = CodonTable::standard();
+    let trx = make_trx_fwd();
+    let hgvs_p = |pos, source: &mut HashMap<String, Vec<u8>>| {
+        trx.hgvs_p(pos, source, &table).expect("a notation")
+    };
+    assert_eq!(hgvs_p(4, &mut source), Some("p.Met1".to_owned()));
+    assert_eq!(hgvs_p(8, &mut source), Some("p.Ala2".to_owned()));
+    assert_eq!(hgvs_p(16, &mut source), Some("p.Lys3".to_owned()));
+    assert_eq!(hgvs_p(21, &mut source), Some("p.Ter5".to_owned()));
+    assert_eq!(hgvs_p(3, &mut source), None);
+    assert_eq!(hgvs_p(12, &mut source), None);
+}
+
+#[test]
+fn hgvs_p_rev() {
+    let mut source = make_source();
+    let table = CodonTable::standard();
+    let trx = make_trx_rev();
+    assert_eq!(trx.hgvs_p(25, &mut source, &table).expect("a notation"),
+               Some("p.Met1".to_owned()));
+    assert_eq!(trx.hgvs_p(15, &mut source, &table).expect("a notation"),
+               Some("p.Lys3".to_owned()));
+}
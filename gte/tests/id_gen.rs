@@ -0,0 +1,86 @@
+extern crate gte;
+
+use gte::{AnnotationFormat, AnnotationReader, BedReader, GBuilder, IdGenerator, IdScheme,
+          TBuilder};
+
+
+static ANON_ROWS: &'static str = "\
+chr1\t100\t500\t.\t0\t+\t100\t100\t0\t2\t100,100,\t0,300,
+chr1\t1000\t2000\tnamed\t0\t-\t1000\t1000\t0\t1\t1000,\t0,
+chr2\t100\t500\t.\t0\t+\t100\t100\t0\t2\t100,100,\t0,300,
+";
+
+#[test]
+fn generate_ids_counter() {
+    let mut reader = BedReader::from_reader(ANON_ROWS.as_bytes());
+    let _ = reader.id_generator(IdGenerator::new(IdScheme::Counter));
+    let trxs = reader.transcripts()
+        .map(|res| res.expect("a transcript"))
+        .collect::<Vec<_>>();
+    let ids = trxs.iter()
+        .map(|trx| (trx.id().unwrap(), trx.gene_id().unwrap()))
+        .collect::<Vec<_>>();
+    assert_eq!(ids, vec![("TRX_1", "GENE_1"), ("named", "GENE_2"), ("TRX_2", "GENE_3")]);
+}
+
+#[test]
+fn generate_ids_hash() {
+    let mut gen = IdGenerator::new(IdScheme::Hash);
+    let _ = gen.transcript_prefix("T").gene_prefix("G");
+    let mut reader = BedReader::from_reader(ANON_ROWS.as_bytes());
+    let _ = reader.id_generator(gen);
+    let genes = reader.genes()
+        .map(|res| res.expect("a gene"))
+        .collect::<Vec<_>>();
+    let (tid1, gid1) = {
+        let trx = genes[0].transcripts().values().next().unwrap();
+        (trx.id().unwrap().to_owned(), trx.gene_id().unwrap().to_owned())
+    };
+    assert!(tid1.starts_with("T") && tid1.len() == 17, "{}", tid1);
+    assert!(gid1.starts_with("G") && gid1.len() == 17, "{}", gid1);
+    assert_eq!(genes[0].id(), Some(gid1.as_str()));
+    assert_eq!(genes[0].transcripts().keys().next(), Some(&tid1));
+    // identical exon chains on other sequences get other identifiers
+    assert!(genes[2].id() != genes[0].id());
+
+    // the same features get the same identifiers regardless of their order
+    let reversed = ANON_ROWS.lines().rev().collect::<Vec<_>>().join("\n");
+    let mut reader = BedReader::from_reader(reversed.as_bytes());
+    let mut gen = IdGenerator::new(IdScheme::Hash);
+    let _ = gen.transcript_prefix("T").gene_prefix("G");
+    let _ = reader.id_generator(gen);
+    let trx = reader.transcripts().last().unwrap().unwrap();
+    assert_eq!(trx.id(), Some(tid1.as_str()));
+    assert_eq!(trx.gene_id(), Some(gid1.as_str()));
+}
+
+#[test]
+fn generate_ids_fill_gene() {
+    let trx = TBuilder::new("chr1", 100, 500)
+        .coords(vec![(100, 200), (400, 500)], None)
+        .strand_char('+')
+        .build()
+        .unwrap();
+    let mut gene = GBuilder::new("chr1", 100, 500)
+        .strand_char('+')
+        .transcripts(vec![("<unknown>".to_owned(), trx)].into_iter().collect())
+        .build()
+        .unwrap();
+    let mut gen = IdGenerator::new(IdScheme::Counter);
+    gen.fill_gene(&mut gene);
+    assert_eq!(gene.id(), Some("GENE_1"));
+    let (key, trx) = gene.transcripts().iter().next().unwrap();
+    assert_eq!(key, "TRX_1");
+    assert_eq!(trx.id(), Some("TRX_1"));
+    assert_eq!(trx.gene_id(), Some("GENE_1"));
+}
+
+#[test]
+fn generate_ids_annotation_reader() {
+    let mut reader = AnnotationReader::from_reader(ANON_ROWS.as_bytes(), AnnotationFormat::Bed12);
+    let _ = reader.id_generator(IdGenerator::new(IdScheme::Counter));
+    let ids = reader.genes().unwrap()
+        .map(|res| res.expect("a gene").id().unwrap().to_owned())
+        .collect::<Vec<_>>();
+    assert_eq!(ids, vec!["GENE_1", "GENE_2", "GENE_3"]);
+}
@@ -0,0 +1,41 @@
+extern crate gte;
+
+use gte::{GffReader, GffType, IdMap};
+
+
+static SINGLE_GENE_GTF: &'static str = include_str!("data/single_gene.gtf");
+
+fn make_id_map() -> IdMap {
+    let mut reader = GffReader::from_reader(SINGLE_GENE_GTF.as_bytes(), GffType::GTF2);
+    let _ = reader.keep_attributes(true);
+    IdMap::from_genes(reader.genes().expect("genes")).expect("an ID map")
+}
+
+#[test]
+fn id_map_lookups() {
+    let map = make_id_map();
+    assert_eq!(map.len(), 1);
+    assert_eq!(map.gene_ids(), vec!["ENSG00000128645.13"]);
+    assert_eq!(map.gene_name("ENSG00000128645.13"), Some("HOXD1"));
+    assert_eq!(map.genes_by_name("HOXD1"), vec!["ENSG00000128645.13"]);
+    assert!(map.genes_by_name("TP53").is_empty());
+    let mut tids = map.transcript_ids("ENSG00000128645.13");
+    tids.sort();
+    assert_eq!(tids, vec!["ENST00000331462.5", "ENST00000610524.1"]);
+    assert_eq!(map.gene_of_transcript("ENST00000610524.1"), Some("ENSG00000128645.13"));
+    assert_eq!(map.gene_of_transcript("ENST00000000000.1"), None);
+}
+
+#[test]
+fn id_map_write_tsv() {
+    let mut buf = vec![];
+    make_id_map().write_tsv(&mut buf).expect("a table");
+    let table = String::from_utf8(buf).expect("a string");
+    let mut lines = table.lines().collect::<Vec<_>>();
+    lines[1..].sort();
+    assert_eq!(lines, vec![
+        "gene_id\tgene_name\ttranscript_id",
+        "ENSG00000128645.13\tHOXD1\tENST00000331462.5",
+        "ENSG00000128645.13\tHOXD1\tENST00000610524.1",
+    ]);
+}
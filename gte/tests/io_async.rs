@@ -0,0 +1,64 @@
+#![cfg(feature = "async")]
+extern crate futures;
+extern crate gte;
+
+use futures::Stream;
+
+use gte::{AsyncGffReader, AsyncRefFlatReader, GffType};
+
+
+static MULT_ROWS_MULT_GENES_WITH_CDS: &'static str =
+    include_str!("data/mult_rows_mult_genes_with_cds.refFlat");
+static SINGLE_GENE_GTF: &'static str = include_str!("data/single_gene.gtf");
+
+#[test]
+fn async_refflat_reader_records() {
+    let records = AsyncRefFlatReader::from_reader(MULT_ROWS_MULT_GENES_WITH_CDS.as_bytes())
+        .records()
+        .wait()
+        .map(|res| res.expect("a record"))
+        .collect::<Vec<_>>();
+    assert_eq!(records.len(), 5);
+    assert_eq!(records[0].transcript_id(), "NM_001297605");
+    assert_eq!(records[4].transcript_id(), "NM_138428");
+}
+
+#[test]
+fn async_refflat_reader_genes() {
+    let genes = AsyncRefFlatReader::from_reader(MULT_ROWS_MULT_GENES_WITH_CDS.as_bytes())
+        .genes()
+        .wait()
+        .map(|res| res.expect("a gene"))
+        .collect::<Vec<_>>();
+    assert_eq!(genes.iter().map(|gx| gx.id()).collect::<Vec<_>>(),
+               vec![Some("TNFRSF14"), Some("SMIM12")]);
+    assert_eq!(genes[0].transcripts().len(), 2);
+    assert_eq!(genes[1].transcripts().len(), 3);
+}
+
+#[test]
+fn async_refflat_reader_invalid_line() {
+    let input = "g1\tt1\tchr1\t+\t10\n";
+    let mut records = AsyncRefFlatReader::from_reader(input.as_bytes()).records().wait();
+    let err = records.next().expect("a record result").unwrap_err();
+    assert_eq!(err.line(), Some(1));
+}
+
+#[test]
+fn async_gff_reader_genes() {
+    let genes = AsyncGffReader::from_reader(SINGLE_GENE_GTF.as_bytes(), GffType::GTF2)
+        .genes()
+        .wait()
+        .map(|res| res.expect("a gene"))
+        .collect::<Vec<_>>();
+    assert_eq!(genes.len(), 1);
+    assert_eq!(genes[0].id(), Some("ENSG00000128645.13"));
+    assert_eq!(genes[0].transcripts().len(), 2);
+
+    let trxs = AsyncGffReader::from_reader(SINGLE_GENE_GTF.as_bytes(), GffType::GTF2)
+        .transcripts()
+        .wait()
+        .map(|res| res.expect("a transcript"))
+        .collect::<Vec<_>>();
+    assert_eq!(trxs.len(), 2);
+}
@@ -0,0 +1,119 @@
+extern crate gte;
+
+use gte::{convert, AnnotationFormat, AnnotationReader, BedReader, BedWriter, GffReader, GffType,
+          GffWriter, RefFlatWriter};
+
+
+fn data_path(name: &str) -> String {
+    format!("{}/tests/data/{}", env!("CARGO_MANIFEST_DIR"), name)
+}
+
+#[test]
+fn annotation_format_from_extension() {
+    assert_eq!(AnnotationFormat::from_extension("a.gtf.gz"), Some(AnnotationFormat::Gtf));
+    assert_eq!(AnnotationFormat::from_extension("a.GFF3"), Some(AnnotationFormat::Gff3));
+    assert_eq!(AnnotationFormat::from_extension("a.refFlat"), Some(AnnotationFormat::RefFlat));
+    assert_eq!(AnnotationFormat::from_extension("a.gp"), Some(AnnotationFormat::GenePred));
+    assert_eq!(AnnotationFormat::from_extension("dir/a.bed"), Some(AnnotationFormat::Bed12));
+    assert_eq!(AnnotationFormat::from_extension("a.txt"), None);
+    assert_eq!(AnnotationFormat::from_extension("gtf"), None);
+}
+
+#[test]
+fn annotation_format_from_contents() {
+    assert_eq!(AnnotationFormat::from_contents("##gff-version 3\n"),
+               Some(AnnotationFormat::Gff3));
+    assert_eq!(AnnotationFormat::from_contents("#!genome-build x\n\
+                                                chr1\ts\texon\t1\t10\t.\t+\t.\tgene_id \"g\";\n"),
+               Some(AnnotationFormat::Gtf));
+    assert_eq!(AnnotationFormat::from_contents("chr1\ts\texon\t1\t10\t.\t+\t.\tID=e1\n"),
+               Some(AnnotationFormat::Gff3));
+    assert_eq!(AnnotationFormat::from_contents("a\tb\tc\n"), None);
+    assert_eq!(AnnotationFormat::from_contents(""), None);
+}
+
+#[test]
+fn annotation_reader_open() {
+    let cases = vec![
+        ("mult_rows_mult_genes_with_cds.refFlat", AnnotationFormat::RefFlat, 5, Some(2)),
+        ("mult_rows_no_cds.refFlat.gz", AnnotationFormat::RefFlat, 2, Some(2)),
+        ("mult_rows_mult_genes_with_cds.genePred", AnnotationFormat::GenePred, 5, None),
+        ("mult_rows_mult_genes_with_cds.genePredExt", AnnotationFormat::GenePred, 5, Some(2)),
+        ("single_gene.gtf", AnnotationFormat::Gtf, 2, Some(1)),
+        ("indexed.gtf.gz", AnnotationFormat::Gtf, 4, None),
+        ("mult_rows_mult_genes_with_cds.bed", AnnotationFormat::Bed12, 5, Some(5)),
+    ];
+    for (name, format, num_transcripts, num_genes) in cases {
+        let mut reader = AnnotationReader::open(data_path(name)).expect("a reader");
+        assert_eq!(reader.format(), format, "{}", name);
+        let count = reader.transcripts().expect("transcripts")
+            .map(|res| res.expect("a transcript"))
+            .count();
+        assert_eq!(count, num_transcripts, "{}", name);
+        if let Some(num_genes) = num_genes {
+            let mut reader = AnnotationReader::open(data_path(name)).expect("a reader");
+            let count = reader.genes().expect("genes")
+                .map(|res| res.expect("a gene"))
+                .count();
+            assert_eq!(count, num_genes, "{}", name);
+        }
+    }
+}
+
+#[test]
+fn annotation_reader_gff3() {
+    let reader = AnnotationReader::open(data_path("with_fasta.gff3")).expect("a reader");
+    assert_eq!(reader.format(), AnnotationFormat::Gff3);
+
+    let input = "chr1\tsrc\texon\t1\t10\t.\t+\t.\tgene_id=g1;transcript_id=t1\n";
+    let mut reader = AnnotationReader::from_reader(input.as_bytes(), AnnotationFormat::Gff3);
+    let genes = reader.genes().expect("genes")
+        .map(|res| res.expect("a gene"))
+        .collect::<Vec<_>>();
+    assert_eq!(genes.len(), 1);
+    assert_eq!(genes[0].id(), Some("g1"));
+}
+
+#[test]
+fn convert_refflat_to_bed_and_gtf() {
+    let path = data_path("mult_rows_mult_genes_with_cds.refFlat");
+
+    let mut bed = Vec::new();
+    {
+        let mut reader = AnnotationReader::open(&path).expect("a reader");
+        let mut writer = BedWriter::from_writer(&mut bed);
+        assert_eq!(convert(&mut reader, &mut writer).expect("converted genes"), 2);
+    }
+    let bed = String::from_utf8(bed).expect("a BED string");
+    assert_eq!(bed.lines().next(),
+               Some("chr1\t2556364\t2565622\tNM_001297605\t0\t+\t2556664\t2562868\t0\t7\t\
+                     369,109,126,156,91,32,2475,\t0,1361,1978,3458,4259,6500,6783,"));
+    let trxs = BedReader::from_reader(bed.as_bytes()).transcripts()
+        .map(|res| res.expect("a transcript"))
+        .collect::<Vec<_>>();
+    assert_eq!(trxs.len(), 5);
+
+    let mut gtf = Vec::new();
+    {
+        let mut reader = AnnotationReader::open(&path).expect("a reader");
+        let mut writer = GffWriter::from_writer(&mut gtf, GffType::GTF2);
+        assert_eq!(convert(&mut reader, &mut writer).expect("converted genes"), 2);
+    }
+    let mut reader = GffReader::from_reader(gtf.as_slice(), GffType::GTF2);
+    let genes = reader.genes().expect("genes")
+        .map(|res| res.expect("a gene"))
+        .collect::<Vec<_>>();
+    assert_eq!(genes.len(), 2);
+    assert_eq!(genes.iter().map(|gx| gx.transcripts().len()).sum::<usize>(), 5);
+
+    // BED genes carry their transcript names as gene identifiers
+    let mut reader = BedReader::from_reader(bed.as_bytes());
+    let mut writer = RefFlatWriter::from_memory();
+    assert_eq!(convert(&mut reader, &mut writer).expect("converted genes"), 5);
+    assert!(writer.as_string().starts_with("NM_001297605\tNM_001297605\tchr1\t+\t"));
+}
+
+#[test]
+fn annotation_reader_unknown_format() {
+    assert!(AnnotationReader::open(data_path("indexed.gtf.gz.tbi")).is_err());
+}
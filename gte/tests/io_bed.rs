@@ -0,0 +1,52 @@
+extern crate gte;
+
+use gte::{BedReader, Strand};
+
+
+static MULT_ROWS_MULT_GENES_WITH_CDS: &'static str =
+    include_str!("data/mult_rows_mult_genes_with_cds.bed");
+
+#[test]
+fn bed_reader_transcripts() {
+    let mut reader = BedReader::from_reader(MULT_ROWS_MULT_GENES_WITH_CDS.as_bytes());
+    let trxs = reader.transcripts()
+        .map(|res| res.expect("a transcript"))
+        .collect::<Vec<_>>();
+    assert_eq!(trxs.len(), 5);
+
+    let trx = &trxs[0];
+    assert_eq!(trx.id(), Some("NM_001297605"));
+    assert_eq!(trx.seq_name(), "chr1");
+    assert_eq!(trx.strand(), &Strand::Forward);
+    assert_eq!((trx.start(), trx.end()), (2556364, 2565622));
+    assert_eq!(trx.exons().len(), 7);
+    assert_eq!((trx.exons()[0].start(), trx.exons()[0].end()), (2556364, 2556733));
+    assert_eq!(trx.coding_coord(true), Some((2556664, 2562868)));
+
+    assert_eq!(trxs[2].strand(), &Strand::Reverse);
+    assert_eq!(trxs[2].exons().len(), 3);
+}
+
+#[test]
+fn bed_reader_genes() {
+    let mut reader = BedReader::from_reader(MULT_ROWS_MULT_GENES_WITH_CDS.as_bytes());
+    let genes = reader.genes()
+        .map(|res| res.expect("a gene"))
+        .collect::<Vec<_>>();
+    assert_eq!(genes.len(), 5);
+    assert_eq!(genes[1].id(), Some("NM_003820"));
+    assert_eq!(genes[1].transcripts().len(), 1);
+}
+
+#[test]
+fn bed_reader_invalid_rows() {
+    let input = "chr1\t10\t20\tt1\t0\t+\t10\t10\t0\t2\t5,\t0,\n";
+    let mut reader = BedReader::from_reader(input.as_bytes());
+    let err = reader.transcripts().next().expect("a transcript result").unwrap_err();
+    assert_eq!(err.line(), Some(1));
+
+    let input = "track name=x\nchr1\t10\t20\tt1\t0\t+\n";
+    let mut reader = BedReader::from_reader(input.as_bytes());
+    let err = reader.transcripts().next().expect("a transcript result").unwrap_err();
+    assert_eq!(err.line(), Some(2));
+}
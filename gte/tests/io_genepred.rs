@@ -0,0 +1,262 @@
+extern crate bio;
+extern crate gte;
+
+use std::io;
+
+use gte::{GenePredReader, GenePredWriter, GenePredRecord, CdsStat, ExonFeatureKind,
+          GenePredRecordsStream, GenePredTranscriptsStream, GenePredGenesStream,
+          RefFlatReader, TBuilder, Transcript, Gene, Strand};
+
+
+static MULT_ROWS_MULT_GENES_WITH_CDS: &'static str =
+    include_str!("data/mult_rows_mult_genes_with_cds.genePred");
+static MULT_ROWS_MULT_GENES_WITH_CDS_EXT: &'static str =
+    include_str!("data/mult_rows_mult_genes_with_cds.genePredExt");
+static MULT_ROWS_MULT_GENES_WITH_CDS_REFFLAT: &'static str =
+    include_str!("data/mult_rows_mult_genes_with_cds.refFlat");
+
+
+fn next_rec<'a, R>(rr: &mut GenePredRecordsStream<'a, R>) -> GenePredRecord where R: io::Read {
+    rr.next().expect("a genePred record result").expect("a genePred record")
+}
+
+fn next_trx<'a, R>(rt: &mut GenePredTranscriptsStream<'a, R>) -> Transcript where R: io::Read {
+    rt.next().expect("a transcript result").expect("a transcript")
+}
+
+fn next_gx<'a, R>(rg: &mut GenePredGenesStream<'a, R>) -> Gene where R: io::Read {
+    rg.next().expect("a gene result").expect("a gene")
+}
+
+#[test]
+fn genepred_reader_records_mult_rows_mult_genes_with_cds() {
+    let mut reader = GenePredReader::from_reader(MULT_ROWS_MULT_GENES_WITH_CDS.as_bytes());
+    let mut records = reader.records_stream();
+
+    let rec1 = next_rec(&mut records);
+    assert_eq!(rec1.transcript_id(), "NM_001297605");
+    assert_eq!(rec1.gene_id(), None);
+    assert_eq!(rec1.num_exons(), 7);
+    assert!(rec1.ext().is_none());
+
+    let _rec2 = next_rec(&mut records);
+    let _rec3 = next_rec(&mut records);
+    let _rec4 = next_rec(&mut records);
+
+    let rec5 = next_rec(&mut records);
+    assert_eq!(rec5.transcript_id(), "NM_138428");
+    assert_eq!(rec5.strand(), '-');
+
+    assert!(records.next().is_none());
+}
+
+#[test]
+fn genepred_reader_records_ext_mult_rows_mult_genes_with_cds() {
+    let mut reader = GenePredReader::from_reader(MULT_ROWS_MULT_GENES_WITH_CDS_EXT.as_bytes());
+    let mut records = reader.records_stream();
+
+    let rec1 = next_rec(&mut records);
+    assert_eq!(rec1.transcript_id(), "NM_001297605");
+    assert_eq!(rec1.gene_id(), Some("TNFRSF14"));
+    let ext = rec1.ext().expect("extended values");
+    assert_eq!(ext.cds_start_stat(), CdsStat::Complete);
+    assert_eq!(ext.cds_end_stat(), CdsStat::Complete);
+    assert_eq!(ext.exon_frames().to_vec(), vec![0, 0, 1, 1, 1, 2, -1]);
+}
+
+#[test]
+fn genepred_reader_transcripts_mult_rows_mult_genes_with_cds() {
+    let mut reader = GenePredReader::from_reader(MULT_ROWS_MULT_GENES_WITH_CDS.as_bytes());
+    let mut transcripts = reader.transcripts_stream();
+
+    let mut refflat_reader =
+        RefFlatReader::from_reader(MULT_ROWS_MULT_GENES_WITH_CDS_REFFLAT.as_bytes());
+    let refflat_transcripts = refflat_reader.transcripts_stream()
+        .map(|res| res.expect("a transcript"))
+        .collect::<Vec<Transcript>>();
+
+    for rtrx in refflat_transcripts.iter() {
+        let trx = next_trx(&mut transcripts);
+        assert_eq!(trx.id(), rtrx.id());
+        assert_eq!(trx.gene_id(), None);
+        assert_eq!(trx.coding_coord(true), rtrx.coding_coord(true));
+        assert_eq!(trx.exons().len(), rtrx.exons().len());
+    }
+
+    assert!(transcripts.next().is_none());
+}
+
+#[test]
+fn genepred_reader_genes_ext_mult_rows_mult_genes_with_cds() {
+    let mut reader = GenePredReader::from_reader(MULT_ROWS_MULT_GENES_WITH_CDS_EXT.as_bytes());
+    let mut genes = reader.genes_stream();
+
+    let gx1 = next_gx(&mut genes);
+    assert_eq!(gx1.id(), Some("TNFRSF14"));
+    assert_eq!(gx1.transcripts().len(), 2);
+
+    let gx2 = next_gx(&mut genes);
+    assert_eq!(gx2.id(), Some("SMIM12"));
+    assert_eq!(gx2.transcripts().len(), 3);
+
+    assert!(genes.next().is_none());
+}
+
+#[test]
+fn genepred_reader_genes_mult_rows_mult_genes_with_cds() {
+    let mut reader = GenePredReader::from_reader(MULT_ROWS_MULT_GENES_WITH_CDS.as_bytes());
+    let mut genes = reader.genes_stream();
+
+    assert!(genes.next().expect("a gene result").is_err());
+}
+
+#[test]
+fn genepred_writer_records_ext_mult_rows_mult_genes_with_cds() {
+    let mut reader = GenePredReader::from_reader(MULT_ROWS_MULT_GENES_WITH_CDS_EXT.as_bytes());
+    let mut writer = GenePredWriter::from_memory();
+    writer.extended(true);
+    for rec in reader.records_stream() {
+        writer.write_record(&rec.expect("a genePred record")).expect("a successful write");
+    }
+    assert_eq!(writer.as_string(), MULT_ROWS_MULT_GENES_WITH_CDS_EXT);
+}
+
+#[test]
+fn genepred_writer_transcripts_mult_rows_mult_genes_with_cds() {
+    let mut reader = RefFlatReader::from_reader(MULT_ROWS_MULT_GENES_WITH_CDS_REFFLAT.as_bytes());
+    let mut writer = GenePredWriter::from_memory();
+    for trx in reader.transcripts_stream() {
+        writer.write_transcript(&trx.expect("a transcript")).expect("a successful write");
+    }
+    assert_eq!(writer.as_string(), MULT_ROWS_MULT_GENES_WITH_CDS);
+}
+
+#[test]
+fn genepred_writer_transcripts_coding_excl_stop() {
+    let trx = TBuilder::new("chr1", 100, 400)
+        .strand(Strand::Forward)
+        .id("t1")
+        .coords(vec![(100, 200), (300, 400)], Some((150, 360)))
+        .coding_incl_stop(true)
+        .build()
+        .expect("a transcript");
+    let mut writer = GenePredWriter::from_memory();
+    writer.coding_incl_stop(false);
+    writer.write_transcript(&trx).expect("a successful write");
+    assert_eq!(writer.as_string(), "t1\tchr1\t+\t100\t400\t150\t357\t2\t100,300,\t200,400,\n");
+}
+
+#[test]
+fn genepred_writer_transcripts_ext_mult_rows_mult_genes_with_cds() {
+    let mut reader = RefFlatReader::from_reader(MULT_ROWS_MULT_GENES_WITH_CDS_REFFLAT.as_bytes());
+    let mut writer = GenePredWriter::from_memory();
+    writer.extended(true);
+    for trx in reader.transcripts_stream() {
+        writer.write_transcript(&trx.expect("a transcript")).expect("a successful write");
+    }
+    assert_eq!(writer.as_string(), MULT_ROWS_MULT_GENES_WITH_CDS_EXT);
+}
+
+#[test]
+fn genepred_writer_genes_ext_mult_rows_mult_genes_with_cds() {
+    let mut reader = RefFlatReader::from_reader(MULT_ROWS_MULT_GENES_WITH_CDS_REFFLAT.as_bytes());
+    let mut writer = GenePredWriter::from_memory();
+    writer.extended(true);
+    for gx in reader.genes_stream() {
+        writer.write_gene(&gx.expect("a gene")).expect("a successful write");
+    }
+    assert_eq!(writer.as_string(), MULT_ROWS_MULT_GENES_WITH_CDS_EXT);
+}
+
+#[test]
+fn genepred_writer_transcripts_ext_unknown_frames() {
+    let mut reader = RefFlatReader::from_reader(MULT_ROWS_MULT_GENES_WITH_CDS_REFFLAT.as_bytes());
+    let mut writer = GenePredWriter::from_memory();
+    writer.extended(true);
+    for trx in reader.transcripts_stream() {
+        let trx = trx.expect("a transcript");
+        let mut exons = trx.exons().to_vec();
+        for exon in exons.iter_mut() {
+            for fx in exon.features_mut() {
+                if let ExonFeatureKind::CDS { .. } = *fx.kind() {
+                    fx.set_kind(ExonFeatureKind::CDS { frame: None });
+                }
+            }
+        }
+        let cleared = TBuilder::new(trx.seq_name(), trx.start(), trx.end())
+            .strand(*trx.strand())
+            .id(trx.id().expect("a transcript ID"))
+            .gene_id(trx.gene_id().expect("a gene ID"))
+            .exons(exons)
+            .build()
+            .expect("a transcript");
+        writer.write_transcript(&cleared).expect("a successful write");
+    }
+    assert_eq!(writer.as_string(), MULT_ROWS_MULT_GENES_WITH_CDS_EXT);
+}
+
+#[test]
+fn genepred_reader_records_invalid_number_line_context() {
+    let mut lines = MULT_ROWS_MULT_GENES_WITH_CDS.lines();
+    let data = format!("{}\n{}\n", lines.next().unwrap(),
+                       lines.next().unwrap().replacen("\t2556364\t", "\t2556364x\t", 1));
+    let mut reader = GenePredReader::from_reader(data.as_bytes());
+    let mut records = reader.records_stream();
+
+    let _rec1 = next_rec(&mut records);
+    let err = records.next().expect("a genePred record result").expect_err("a parse error");
+    assert_eq!(err.line(), Some(2));
+    assert!(err.to_string().starts_with("line 2: "));
+    assert!(err.to_string().contains("field: txStart"));
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn genepred_reader_par_genes_ext_mult_rows_mult_genes_with_cds() {
+    let mut reader = GenePredReader::from_reader(MULT_ROWS_MULT_GENES_WITH_CDS_EXT.as_bytes());
+    let genes = reader.par_genes().into_iter()
+        .map(|res| res.expect("a gene"))
+        .collect::<Vec<Gene>>();
+    assert_eq!(genes.len(), 2);
+    assert_eq!(genes[0].id(), Some("TNFRSF14"));
+    assert_eq!(genes[1].id(), Some("SMIM12"));
+    assert_eq!(genes[1].transcripts().len(), 3);
+}
+
+#[test]
+fn genepred_reader_transcripts_ext_incomplete_cds() {
+    let input = "\
+t1\tchr1\t+\t100\t400\t150\t400\t2\t100,300,\t200,400,\t0\tg1\tcmpl\tincmpl\t0,1,
+t2\tchr1\t-\t100\t400\t100\t360\t2\t100,300,\t200,400,\t0\tg1\tincmpl\tcmpl\t1,0,
+";
+    let mut reader = GenePredReader::from_reader(input.as_bytes());
+    let transcripts = reader.transcripts_stream()
+        .map(|res| res.expect("a transcript"))
+        .collect::<Vec<_>>();
+
+    assert_eq!((transcripts[0].cds_5p_incomplete(), transcripts[0].cds_3p_incomplete()),
+               (false, true));
+    assert_eq!(transcripts[0].coding_coord(true), Some((150, 400)));
+    assert_eq!((transcripts[1].cds_5p_incomplete(), transcripts[1].cds_3p_incomplete()),
+               (false, true));
+    assert_eq!(transcripts[1].coding_coord(true), Some((100, 360)));
+    for trx in transcripts.iter() {
+        let has_stop_codon = trx.exons().iter()
+            .flat_map(|exn| exn.features().iter())
+            .any(|fx| match *fx.kind() {
+                ExonFeatureKind::StopCodon { .. } => true,
+                _ => false,
+            });
+        assert!(!has_stop_codon);
+    }
+
+    let mut writer = GenePredWriter::from_memory();
+    writer.extended(true);
+    for trx in transcripts.iter() {
+        writer.write_transcript(trx).expect("a successful write");
+    }
+    let stats = writer.as_string().lines()
+        .map(|line| line.split('\t').skip(12).take(2).collect::<Vec<_>>().join(","))
+        .collect::<Vec<_>>();
+    assert_eq!(stats, vec!["cmpl,incmpl", "incmpl,cmpl"]);
+}
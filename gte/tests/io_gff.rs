@@ -1,12 +1,17 @@
 extern crate bio;
 extern crate gte;
 
-use gte::{GffType, GffReader,
-          ExonFeatureKind as EFK, Strand};
+use std::sync::{Arc, Mutex};
+
+use gte::{GffType, GffReader, GffIndexedReader, GffScanner, GffCodingOptions, TBuilder,
+          gff3_escape, gff3_unescape, ExonFeatureKind as EFK, RegulatoryKind, SeqName, SeqNamePool,
+          Strand, StrandPolicy, CancelToken, Error};
 use Strand::*;
 
 
 static SINGLE_GENE_GTF: &'static str = include_str!("data/single_gene.gtf");
+static EXONS_ONLY_GTF: &'static str = include_str!("data/exons_only.gtf");
+static WITH_FASTA_GFF3: &'static str = include_str!("data/with_fasta.gff3");
 
 
 #[test]
@@ -49,3 +54,773 @@ fn gtf_reader_multiple_transcripts() {
 
     assert!(transcripts.next().is_none());
 }
+
+#[test]
+fn gtf_reader_strand_policy() {
+    let input = "chr1\tsrc\texon\t101\t200\t.\t.\t.\tgene_id \"g1\"; transcript_id \"t1\";
+chr1\tsrc\texon\t301\t400\t.\t-\t.\tgene_id \"g2\"; transcript_id \"t2\";
+";
+    let strands = |policy: Option<StrandPolicy>| {
+        let mut reader = GffReader::from_reader(input.as_bytes(), GffType::GTF2);
+        if let Some(policy) = policy {
+            reader.strand_policy(policy);
+        }
+        reader.transcripts().expect("transcripts")
+            .map(|res| res.map(|trx| *trx.strand()))
+            .collect::<Result<Vec<Strand>, _>>()
+    };
+    let defaults = strands(None).expect("strands");
+    assert!(defaults[0].is_unknown());
+    assert_eq!(defaults[1], Reverse);
+    assert_eq!(strands(Some(StrandPolicy::DefaultForward)).expect("strands"),
+               vec![Forward, Reverse]);
+
+    let mut reader = GffReader::from_reader(input.as_bytes(), GffType::GTF2);
+    reader.strand_policy(StrandPolicy::Require);
+    assert!(reader.transcripts().is_err());
+    reader = GffReader::from_reader(input.as_bytes(), GffType::GTF2);
+    reader.strand_policy(StrandPolicy::Require).lenient(true);
+    assert_eq!(reader.transcripts().expect("transcripts").count(), 1);
+    assert_eq!(reader.warnings().len(), 1);
+
+    let input = "chr1\tsrc\texon\t101\t200\t.\t.\t.\tgene_id \"g1\"; transcript_id \"t1\";
+chr1\tsrc\texon\t301\t400\t.\t.\t.\tgene_id \"g1\"; transcript_id \"t1\";
+chr1\tsrc\texon\t101\t200\t.\t.\t.\tgene_id \"g1\"; transcript_id \"t2\";
+chr1\tsrc\texon\t501\t600\t.\t.\t.\tgene_id \"g1\"; transcript_id \"t2\";
+";
+    let mut reader = GffReader::from_reader(input.as_bytes(), GffType::GTF2);
+    let trxs = reader.transcripts().expect("transcripts")
+        .collect::<Result<Vec<_>, _>>().expect("transcripts");
+    assert_eq!(trxs.len(), 2);
+    assert_eq!((trxs[0].id(), trxs[0].exons().len()), (Some("t1"), 2));
+    assert_eq!((trxs[1].id(), trxs[1].exons().len()), (Some("t2"), 2));
+    assert!(trxs.iter().all(|trx| trx.strand().is_unknown()));
+
+    let mut reader = GffReader::from_reader(input.as_bytes(), GffType::GTF2);
+    let genes = reader.genes().expect("genes")
+        .collect::<Result<Vec<_>, _>>().expect("genes");
+    assert_eq!(genes.len(), 1);
+    assert_eq!(genes[0].id(), Some("g1"));
+    assert_eq!((genes[0].start(), genes[0].end()), (100, 600));
+    assert_eq!(genes[0].transcripts().len(), 2);
+    assert!(genes[0].strand().is_unknown());
+}
+
+#[test]
+fn gtf_reader_source_score() {
+    let input = "chr1\tsrc1\ttranscript\t101\t400\t12.5\t+\t.\tgene_id \"g1\"; transcript_id \"t1\";
+chr1\tsrc2\texon\t101\t200\t.\t+\t.\tgene_id \"g1\"; transcript_id \"t1\";
+chr1\t.\texon\t301\t400\t3\t+\t.\tgene_id \"g1\"; transcript_id \"t1\";
+";
+    let mut reader = GffReader::from_reader(input.as_bytes(), GffType::GTF2);
+    let trx = reader.transcripts().expect("transcripts")
+        .next().expect("a transcript result").expect("a transcript");
+    assert_eq!((trx.source(), trx.score()), (Some("src1"), Some(12.5)));
+    assert_eq!((trx.exons()[0].source(), trx.exons()[0].score()), (Some("src2"), None));
+    assert_eq!((trx.exons()[1].source(), trx.exons()[1].score()), (None, Some(3.0)));
+    assert!(trx.attributes().is_empty());
+
+    let recs = trx.into_gff_records().expect("records");
+    assert_eq!(recs[0].source(), "src1");
+    assert_eq!(recs[1].source(), "src2");
+    assert_eq!(recs[2].source(), ".");
+}
+
+#[test]
+fn transcript_gff_records_exon_numbers() {
+    let mut trx = TBuilder::new("chr1", 100, 1000)
+        .id("t1")
+        .gene_id("g1")
+        .strand(Reverse)
+        .coords(vec![(100, 300), (400, 500), (700, 1000)], None)
+        .build()
+        .expect("a transcript");
+    let numbers = trx.clone().into_gff_records().expect("records").into_iter()
+        .filter(|rec| rec.feature_type() == "exon")
+        .map(|rec| rec.attributes().get("exon_number").cloned())
+        .collect::<Vec<_>>();
+    assert_eq!(numbers, vec![Some("3".to_owned()), Some("2".to_owned()), Some("1".to_owned())]);
+
+    trx.exons_mut()[0].attributes_mut().insert("exon_number".to_owned(), "1".to_owned());
+    assert!(trx.into_gff_records().is_err());
+}
+
+#[test]
+fn gtf_reader_keep_attributes() {
+    let mut reader = GffReader::from_reader(SINGLE_GENE_GTF.as_bytes(), GffType::GTF2);
+    reader.keep_attributes(true);
+    let mut transcripts = reader.transcripts().expect("transcripts");
+
+    let trx = transcripts.next().expect("a transcript result").expect("a transcript");
+    assert_eq!(trx.attributes().get("source"), Some(&"HAVANA".to_owned()));
+    assert_eq!(trx.attributes().get("transcript_name"), Some(&"HOXD1-001".to_owned()));
+    assert_eq!(trx.attributes_all("tag"), &["basic", "appris_principal_1", "CCDS"]);
+    assert!(trx.attributes_all("missing").is_empty());
+    assert_eq!(trx.attributes().keys().take(5).collect::<Vec<_>>(),
+               vec!["source", "gene_type", "gene_status", "gene_name", "transcript_type"]);
+    assert_eq!(trx.attributes().get("transcript_id"), None);
+    assert_eq!(trx.exons()[0].attributes().get("exon_id"),
+               Some(&"ENSE00000882927.4".to_owned()));
+
+    let recs = trx.into_gff_records().expect("records");
+    assert_eq!(recs[0].feature_type(), "transcript");
+    assert_eq!(recs[0].source(), "HAVANA");
+    assert_eq!(recs[0].attributes().get("transcript_id"),
+               Some(&"ENST00000331462.5".to_owned()));
+    assert_eq!(recs[0].attributes().get("source"), None);
+    assert_eq!(recs[1].feature_type(), "exon");
+    assert_eq!(recs[1].source(), "HAVANA");
+    assert_eq!(recs[1].attributes().get("exon_number"), Some(&"1".to_owned()));
+}
+
+#[test]
+fn gtf_reader_lazy_attributes() {
+    let mut reader = GffReader::from_reader(SINGLE_GENE_GTF.as_bytes(), GffType::GTF2);
+    reader.keep_attributes(true).lazy_attributes(true);
+    let mut transcripts = reader.transcripts().expect("transcripts");
+
+    let mut trx = transcripts.next().expect("a transcript result").expect("a transcript");
+    assert!(!trx.attributes().is_parsed());
+    assert!(trx.attributes().raw().unwrap().contains("transcript_name \"HOXD1-001\""));
+    assert_eq!(trx.attributes().get("transcript_name"), None);
+
+    trx.parse_attributes();
+    assert!(trx.attributes().is_parsed());
+    assert!(trx.exons()[0].attributes().is_parsed());
+    assert_eq!(trx.attributes().get("transcript_name"), Some(&"HOXD1-001".to_owned()));
+    assert_eq!(trx.attributes().get("transcript_id"), None);
+    assert_eq!(trx.attributes().get("source"), None);
+    assert_eq!(trx.exons()[0].attributes().get("exon_id"),
+               Some(&"ENSE00000882927.4".to_owned()));
+
+    let trx = transcripts.next().expect("a transcript result").expect("a transcript");
+    let recs = trx.into_gff_records().expect("records");
+    assert_eq!(recs[0].source(), "ENSEMBL");
+    assert_eq!(recs[0].attributes().get_vec("transcript_id").map(|v| v.len()), Some(1));
+    assert!(recs[0].attributes().get("transcript_name").is_some());
+}
+
+#[test]
+fn gtf_reader_default_attributes() {
+    let mut reader = GffReader::from_reader(SINGLE_GENE_GTF.as_bytes(), GffType::GTF2);
+    let mut transcripts = reader.transcripts().expect("transcripts");
+
+    let trx = transcripts.next().expect("a transcript result").expect("a transcript");
+    assert!(trx.attributes().is_empty());
+    assert!(trx.exons()[0].attributes().is_empty());
+}
+
+#[test]
+fn gtf_reader_exons_only_transcripts() {
+    let mut reader = GffReader::from_reader(EXONS_ONLY_GTF.as_bytes(), GffType::GTF2);
+    let transcripts = reader.transcripts().expect("transcripts")
+        .map(|res| res.expect("a transcript"))
+        .map(|trx| (trx.id().unwrap().to_owned(), trx.start(), trx.end(), trx.exons().len()))
+        .collect::<Vec<_>>();
+    assert_eq!(transcripts, vec![
+        ("STRG.1.1".to_owned(), 1000, 1800, 2),
+        ("STRG.1.2".to_owned(), 1100, 1900, 2),
+        ("STRG.2.1".to_owned(), 3000, 3500, 1),
+    ]);
+}
+
+#[test]
+fn gtf_reader_exons_only_genes() {
+    let mut reader = GffReader::from_reader(EXONS_ONLY_GTF.as_bytes(), GffType::GTF2);
+    let mut genes = reader.genes().expect("genes");
+
+    let gx1 = genes.next().expect("a gene result").expect("a gene");
+    assert_eq!(gx1.id(), Some("STRG.1"));
+    assert_eq!((gx1.start(), gx1.end()), (1000, 1900));
+    assert_eq!(gx1.strand(), &Forward);
+    assert_eq!(gx1.transcripts().keys().collect::<Vec<_>>(), vec!["STRG.1.1", "STRG.1.2"]);
+
+    let gx2 = genes.next().expect("a gene result").expect("a gene");
+    assert_eq!(gx2.id(), Some("STRG.2"));
+    assert_eq!((gx2.start(), gx2.end()), (3000, 3500));
+    assert_eq!(gx2.transcripts().len(), 1);
+
+    assert!(genes.next().is_none());
+}
+
+#[test]
+fn gtf_reader_genes() {
+    let mut reader = GffReader::from_reader(SINGLE_GENE_GTF.as_bytes(), GffType::GTF2);
+    let genes = reader.genes().expect("genes")
+        .map(|res| res.expect("a gene"))
+        .collect::<Vec<_>>();
+    assert_eq!(genes.len(), 1);
+    assert_eq!((genes[0].start(), genes[0].end()), (176188578, 176190907));
+    assert_eq!(genes[0].transcripts().len(), 2);
+}
+
+static CUSTOM_KEYS_GFF3: &'static str = "\
+chr\tRefSeq\texon\t101\t200\t.\t+\t.\told_locus_tag=OLD1;locus_tag=b0001;Parent=rna0
+chr\tRefSeq\texon\t301\t400\t.\t+\t.\told_locus_tag=OLD1;locus_tag=b0001;Parent=rna0
+chr\tRefSeq\texon\t501\t600\t.\t-\t.\tgene=thrA;Parent=rna1
+";
+
+#[test]
+fn gff3_reader_custom_id_attrs() {
+    let mut reader = GffReader::from_reader(CUSTOM_KEYS_GFF3.as_bytes(), GffType::GFF3);
+    reader.gene_id_attrs(&["locus_tag", "gene"])
+        .transcript_id_attr("Parent")
+        .keep_attributes(true);
+    let transcripts = reader.transcripts().expect("transcripts")
+        .map(|res| res.expect("a transcript"))
+        .collect::<Vec<_>>();
+    assert_eq!(transcripts.len(), 2);
+
+    assert_eq!(transcripts[0].id(), Some("rna0"));
+    assert_eq!(transcripts[0].gene_id(), Some("b0001"));
+    assert_eq!((transcripts[0].start(), transcripts[0].end()), (100, 400));
+    let exon_attributes = transcripts[0].exons()[0].attributes();
+    assert_eq!(exon_attributes.get("old_locus_tag"), Some(&"OLD1".to_owned()));
+    assert_eq!(exon_attributes.get("locus_tag"), None);
+    assert_eq!(exon_attributes.get("Parent"), None);
+
+    assert_eq!(transcripts[1].id(), Some("rna1"));
+    assert_eq!(transcripts[1].gene_id(), Some("thrA"));
+    assert_eq!(transcripts[1].strand(), &Reverse);
+}
+
+#[test]
+fn gff3_reader_missing_id_attrs() {
+    let mut reader = GffReader::from_reader(CUSTOM_KEYS_GFF3.as_bytes(), GffType::GFF3);
+    reader.gene_id_attrs(&["locus_tag"]).transcript_id_attr("Parent");
+    assert!(reader.transcripts().is_err());
+}
+
+static PARTIAL_CDS_GTF: &'static str = "\
+chr1\tsrc\texon\t101\t200\t.\t+\t.\tgene_id \"g1\"; transcript_id \"t1\";
+chr1\tsrc\tCDS\t101\t200\t.\t+\t2\tgene_id \"g1\"; transcript_id \"t1\";
+chr1\tsrc\texon\t301\t400\t.\t+\t.\tgene_id \"g1\"; transcript_id \"t1\";
+chr1\tsrc\tCDS\t301\t397\t.\t+\t1\tgene_id \"g1\"; transcript_id \"t1\";
+chr1\tsrc\tstop_codon\t398\t400\t.\t+\t0\tgene_id \"g1\"; transcript_id \"t1\";
+";
+
+#[test]
+fn gtf_reader_cds_frames() {
+    let mut reader = GffReader::from_reader(PARTIAL_CDS_GTF.as_bytes(), GffType::GTF2);
+    reader.loose_codons(true);
+    let mut trx = reader.transcripts().expect("transcripts")
+        .next().expect("a transcript result").expect("a transcript");
+    assert_eq!(trx.exons()[0].features()[1].kind(), &EFK::CDS { frame: Some(2) });
+    assert_eq!(trx.exons()[1].features()[0].kind(), &EFK::CDS { frame: Some(1) });
+
+    trx.exons_mut()[1].features_mut()[0].set_kind(EFK::CDS { frame: None });
+    let frames = trx.into_gff_records().expect("records").into_iter()
+        .filter(|rec| rec.feature_type() == "CDS")
+        .map(|rec| rec.frame().to_owned())
+        .collect::<Vec<_>>();
+    assert_eq!(frames, vec!["2", "1"]);
+}
+
+#[test]
+fn transcript_gff_records_stop_codon_options() {
+    let trx = TBuilder::new("chr1", 100, 400)
+        .id("t1")
+        .gene_id("g1")
+        .strand(Forward)
+        .coords(vec![(100, 200), (300, 400)], Some((150, 350)))
+        .coding_incl_stop(true)
+        .build()
+        .expect("a transcript");
+    let coding_recs = |opts: GffCodingOptions| {
+        trx.clone().into_gff_records_with(opts).expect("records").into_iter()
+            .filter(|rec| rec.feature_type() == "CDS" || rec.feature_type() == "stop_codon")
+            .map(|rec| (rec.feature_type().to_owned(), *rec.start(), *rec.end()))
+            .collect::<Vec<_>>()
+    };
+
+    let default = coding_recs(GffCodingOptions::new());
+    assert_eq!(default.iter().map(|r| r.0.as_str()).collect::<Vec<_>>(),
+               vec!["CDS", "CDS", "stop_codon"]);
+
+    let no_stops = coding_recs(GffCodingOptions::new().stop_codon_records(false));
+    assert_eq!(no_stops, default[..2].to_vec());
+
+    let incl_stop = coding_recs(GffCodingOptions::new()
+        .cds_incl_stop(true)
+        .stop_codon_records(false));
+    assert_eq!(incl_stop, vec![default[0].clone(),
+                               ("CDS".to_owned(), default[1].1, default[2].2)]);
+
+    let both = coding_recs(GffCodingOptions::new().cds_incl_stop(true));
+    assert_eq!(both.len(), 3);
+}
+
+#[test]
+fn gtf_reader_stop_readthrough_tag() {
+    let input = "\
+chr1\tsrc\ttranscript\t101\t400\t.\t+\t.\tgene_id \"g1\"; transcript_id \"t1\"; tag \"seleno\";
+chr1\tsrc\texon\t101\t400\t.\t+\t.\tgene_id \"g1\"; transcript_id \"t1\"; tag \"seleno\";
+chr1\tsrc\ttranscript\t101\t400\t.\t+\t.\tgene_id \"g1\"; transcript_id \"t2\"; tag \"basic\";
+chr1\tsrc\texon\t101\t400\t.\t+\t.\tgene_id \"g1\"; transcript_id \"t2\"; tag \"basic\";
+";
+    let mut reader = GffReader::from_reader(input.as_bytes(), GffType::GTF2);
+    let readthrough = reader.transcripts().expect("transcripts")
+        .map(|res| res.expect("a transcript").stop_readthrough())
+        .collect::<Vec<_>>();
+    assert_eq!(readthrough, vec![true, false]);
+}
+
+#[test]
+fn gff3_reader_partial_cds() {
+    let input = "\
+chr1\tsrc\ttranscript\t101\t400\t.\t+\t.\tgene_id=g1;transcript_id=t1
+chr1\tsrc\texon\t101\t200\t.\t+\t.\tgene_id=g1;transcript_id=t1
+chr1\tsrc\texon\t301\t400\t.\t+\t.\tgene_id=g1;transcript_id=t1
+chr1\tsrc\tstart_codon\t151\t153\t.\t+\t0\tgene_id=g1;transcript_id=t1
+chr1\tsrc\tCDS\t151\t200\t.\t+\t0\tgene_id=g1;transcript_id=t1
+chr1\tsrc\tCDS\t301\t400\t.\t+\t1\tgene_id=g1;transcript_id=t1;partial=true;end_range=400,.
+";
+    let mut reader = GffReader::from_reader(input.as_bytes(), GffType::GFF3);
+    let trx = reader.transcripts().expect("transcripts")
+        .next().expect("a transcript result").expect("a transcript");
+    assert!(!trx.cds_5p_incomplete());
+    assert!(trx.cds_3p_incomplete());
+    assert_eq!(trx.coding_coord(true), Some((150, 400)));
+    let kinds = trx.exons()[1].features().iter().map(|fx| fx.kind().clone()).collect::<Vec<_>>();
+    assert_eq!(kinds, vec![EFK::CDS { frame: Some(1) }]);
+}
+
+#[test]
+fn gff3_reader_directives_and_fasta() {
+    let mut reader = GffReader::from_reader(WITH_FASTA_GFF3.as_bytes(), GffType::GFF3);
+    reader.gene_id_attr("locus_tag")
+        .transcript_id_attr("Parent")
+        .seq_name_lstrip(Some("contig_"))
+        .read_fasta(true);
+    let transcripts = reader.transcripts().expect("transcripts")
+        .map(|res| res.expect("a transcript"))
+        .collect::<Vec<_>>();
+    assert_eq!(transcripts.len(), 2);
+    assert_eq!(transcripts[0].id(), Some("PROKKA_00001_mRNA"));
+    assert_eq!(transcripts[1].id(), Some("PROKKA_00002_mRNA"));
+    assert_eq!((transcripts[1].start(), transcripts[1].end()), (700, 1000));
+
+    assert_eq!(reader.sequence_regions(),
+               vec![("1".to_owned(), 0..1200), ("2".to_owned(), 0..300)]);
+    let sequences = reader.take_sequences();
+    assert_eq!(sequences.len(), 2);
+    assert_eq!(sequences.get("1"), Some(&b"ACGTACGTACGTACGTAC".to_vec()));
+    assert_eq!(sequences.get("2"), Some(&b"TTTTGGGG".to_vec()));
+    assert!(reader.take_sequences().is_empty());
+}
+
+#[test]
+fn gff3_reader_skip_fasta() {
+    let mut reader = GffReader::from_reader(WITH_FASTA_GFF3.as_bytes(), GffType::GFF3);
+    reader.gene_id_attr("locus_tag").transcript_id_attr("Parent");
+    assert_eq!(reader.transcripts().expect("transcripts").count(), 2);
+    assert!(reader.take_sequences().is_empty());
+
+    let implicit = "chr1\tsrc\texon\t1\t10\t.\t+\t.\tgene_id=g1;transcript_id=t1\n\
+                    >chr1\nACGT\n";
+    let mut reader = GffReader::from_reader(implicit.as_bytes(), GffType::GFF3);
+    reader.read_fasta(true);
+    assert_eq!(reader.transcripts().expect("transcripts").count(), 1);
+    assert_eq!(reader.take_sequences().get("chr1"), Some(&b"ACGT".to_vec()));
+}
+
+#[test]
+fn gff3_reader_percent_decoding() {
+    let input = "chr1\tsrc\texon\t101\t200\t.\t+\t.\t\
+                 gene_id=NKX2-1%2Dlike;transcript_id=t%3B1;Note=a%2Cb,c;Alias=x%3Dy\n";
+    let mut reader = GffReader::from_reader(input.as_bytes(), GffType::GFF3);
+    reader.keep_attributes(true);
+    let trx = reader.transcripts().expect("transcripts")
+        .next().expect("a transcript result").expect("a transcript");
+    assert_eq!(trx.gene_id(), Some("NKX2-1-like"));
+    assert_eq!(trx.id(), Some("t;1"));
+    assert_eq!(trx.exons()[0].attributes_all("Note"), &["a,b", "c"]);
+    assert_eq!(trx.exons()[0].attributes().get("Alias"), Some(&"x=y".to_owned()));
+}
+
+#[test]
+fn gff3_reader_refseq() {
+    let input = "\
+chr1\tBestRefSeq%2CGnomon\tgene\t101\t1000\t.\t+\t.\tID=gene-GENE1;Name=GENE1;gene=GENE1
+chr1\tBestRefSeq%2CGnomon\tmRNA\t101\t1000\t.\t+\t.\t\
+ID=rna-NM_1.1;Parent=gene-GENE1;gene=GENE1;transcript_id=NM_1.1
+chr1\tBestRefSeq\texon\t101\t300\t.\t+\t.\tID=exon-NM_1.1-1;Parent=rna-NM_1.1;gene=GENE1
+chr1\tBestRefSeq\texon\t401\t500\t.\t+\t.\tID=exon-NM_1.1-2;Parent=rna-NM_1.1;gene=GENE1
+chr1\tBestRefSeq\texon\t701\t1000\t.\t+\t.\tID=exon-NM_1.1-3;Parent=rna-NM_1.1;gene=GENE1
+chr1\tBestRefSeq\tCDS\t201\t300\t.\t+\t0\tID=cds-NP_1.1;Parent=rna-NM_1.1;gene=GENE1
+chr1\tBestRefSeq\tCDS\t401\t500\t.\t+\t2\tID=cds-NP_1.1;Parent=rna-NM_1.1;gene=GENE1
+chr1\tBestRefSeq\tCDS\t701\t800\t.\t+\t1\tID=cds-NP_1.1;Parent=rna-NM_1.1;gene=GENE1
+chr1\tGnomon\tlnc_RNA\t2001\t2500\t.\t-\t.\tID=rna-XR_2.1;Parent=gene-LINC1;gene=LINC1
+chr1\tGnomon\texon\t2001\t2100\t.\t-\t.\tID=exon-XR_2.1-2;Parent=rna-XR_2.1;gene=LINC1
+chr1\tGnomon\texon\t2401\t2500\t.\t-\t.\tID=exon-XR_2.1-1;Parent=rna-XR_2.1;gene=LINC1
+";
+    let mut reader = GffReader::from_reader(input.as_bytes(), GffType::GFF3);
+    reader.refseq(true);
+    let transcripts = reader.transcripts().expect("transcripts")
+        .map(|res| res.expect("a transcript"))
+        .collect::<Vec<_>>();
+    assert_eq!(transcripts.len(), 2);
+
+    let trx = &transcripts[0];
+    assert_eq!((trx.id(), trx.gene_id()), (Some("NM_1.1"), Some("GENE1")));
+    assert_eq!(trx.source(), Some("BestRefSeq,Gnomon"));
+    assert_eq!((trx.start(), trx.end(), trx.exons().len()), (100, 1000, 3));
+    assert_eq!(trx.coding_coord(true), Some((200, 800)));
+    assert_eq!(trx.coding_coord(false), Some((200, 797)));
+
+    let trx = &transcripts[1];
+    assert_eq!((trx.id(), trx.gene_id()), (Some("XR_2.1"), Some("LINC1")));
+    assert_eq!((trx.start(), trx.end(), trx.exons().len()), (2000, 2500, 2));
+    assert_eq!(trx.coding_coord(true), None);
+
+    let mut reader = GffReader::from_reader(input.as_bytes(), GffType::GTF2);
+    reader.refseq(true);
+    assert!(reader.transcripts().is_err());
+}
+
+#[test]
+fn gff3_reader_prokaryotic() {
+    let input = "\
+contig_1\tProkka\tgene\t101\t400\t.\t+\t.\tID=P_00001_gene;locus_tag=P_00001
+contig_1\tProkka\tCDS\t101\t400\t.\t+\t0\t\
+ID=P_00001;Parent=P_00001_gene;locus_tag=P_00001;product=thrA
+contig_1\tAragorn\ttRNA\t501\t576\t.\t+\t.\tID=P_00002;locus_tag=P_00002
+contig_1\tProtein Homology\tCDS\t701\t850\t.\t-\t0\tID=cds-b0003;locus_tag=b0003
+contig_1\tProtein Homology\tCDS\t850\t1000\t.\t-\t0\tID=cds-b0003;locus_tag=b0003
+";
+    let mut reader = GffReader::from_reader(input.as_bytes(), GffType::GFF3);
+    reader.prokaryotic(true).keep_attributes(true);
+    let transcripts = reader.transcripts().expect("transcripts")
+        .map(|res| res.expect("a transcript"))
+        .collect::<Vec<_>>();
+    assert_eq!(transcripts.len(), 2);
+
+    let trx = &transcripts[0];
+    assert_eq!((trx.id(), trx.gene_id()), (Some("P_00001"), Some("P_00001")));
+    assert_eq!((trx.start(), trx.end(), trx.exons().len()), (100, 400, 1));
+    assert_eq!(trx.coding_coord(true), Some((100, 400)));
+    assert_eq!(trx.source(), Some("Prokka"));
+    assert_eq!(trx.attributes().get("product"), Some(&"thrA".to_owned()));
+
+    let trx = &transcripts[1];
+    assert_eq!((trx.id(), trx.gene_id()), (Some("cds-b0003"), Some("b0003")));
+    assert_eq!(trx.strand(), &Reverse);
+    assert_eq!((trx.start(), trx.end(), trx.exons().len()), (700, 1000, 1));
+    assert_eq!(trx.coding_coord(true), Some((700, 1000)));
+
+    let mut reader = GffReader::from_reader(input.as_bytes(), GffType::GFF3);
+    reader.prokaryotic(true);
+    let genes = reader.genes().expect("genes")
+        .map(|res| res.expect("a gene"))
+        .collect::<Vec<_>>();
+    assert_eq!(genes.iter().map(|gx| gx.id()).collect::<Vec<_>>(),
+               vec![Some("P_00001"), Some("b0003")]);
+}
+
+#[test]
+fn gff3_reader_dbxrefs() {
+    let input = "\
+chr1\tsrc\ttranscript\t101\t200\t.\t+\t.\t\
+gene_id=g1;transcript_id=t1;Dbxref=GeneID:944742,Genbank:NP_414542.1,HGNC:HGNC:5
+chr1\tsrc\texon\t101\t200\t.\t+\t.\tgene_id=g1;transcript_id=t1
+";
+    let mut reader = GffReader::from_reader(input.as_bytes(), GffType::GFF3);
+    reader.keep_attributes(true);
+    let trx = reader.transcripts().expect("transcripts")
+        .next().expect("a transcript result").expect("a transcript");
+    assert_eq!(trx.xrefs(),
+               vec![("GeneID", "944742"), ("Genbank", "NP_414542.1"), ("HGNC", "HGNC:5")]);
+    assert_eq!(trx.xref("HGNC"), Some("HGNC:5"));
+    assert_eq!(trx.xref("GeneID"), Some("944742"));
+    assert_eq!(trx.xref("MIM"), None);
+}
+
+#[test]
+fn gtf_reader_shared_seq_names() {
+    let mut reader = GffReader::from_reader(EXONS_ONLY_GTF.as_bytes(), GffType::GTF2);
+    let genes = reader.genes().expect("genes")
+        .map(|res| res.expect("a gene"))
+        .collect::<Vec<_>>();
+    assert!(genes.len() > 1);
+    let first = genes[0].shared_seq_name();
+    assert_eq!(first.as_str(), "chr1");
+    for gx in genes.iter() {
+        assert!(gx.shared_seq_name().ptr_eq(first));
+        for trx in gx.transcripts().values() {
+            assert!(trx.shared_seq_name().ptr_eq(first));
+            assert!(trx.exons().iter().all(|exn| exn.shared_seq_name().ptr_eq(first)));
+        }
+    }
+
+    let mut pool = SeqNamePool::new();
+    let name = pool.intern("chr2");
+    assert!(pool.intern("chr2").ptr_eq(&name));
+    assert!(!SeqName::from("chr2").ptr_eq(&name));
+    assert_eq!(name, SeqName::from("chr2"));
+    assert_eq!(pool.len(), 1);
+}
+
+#[test]
+fn gff3_scanner() {
+    let mut scanner = GffScanner::from_reader(WITH_FASTA_GFF3.as_bytes(), GffType::GFF3);
+    let mut num_records = 0;
+    let mut last_end = 0;
+    while let Some(result) = scanner.read_record() {
+        let rec = result.expect("a record");
+        assert!(rec.start() < rec.end());
+        last_end = rec.end();
+        num_records += 1;
+    }
+    assert!(num_records > 0);
+    assert!(last_end > 0);
+
+    let input = "##gff-version 3\n#comment\n\
+                 chr1\tsrc\tgene\t101\t200\t.\t-\t.\tID=g1;Name=A%3BB\n\
+                 chr1\tsrc\tgene\tx\t200\t.\t-\t.\tID=g2\n";
+    let mut scanner = GffScanner::from_reader(input.as_bytes(), GffType::GFF3);
+    let rec = scanner.read_record().expect("a record result").expect("a record");
+    assert_eq!((rec.seq_name(), rec.feature_type()), ("chr1", "gene"));
+    assert_eq!((rec.start(), rec.end()), (100, 200));
+    assert_eq!((rec.strand(), rec.score(), rec.frame()), (Reverse, None, None));
+    assert_eq!(rec.raw_attributes(), "ID=g1;Name=A%3BB");
+    assert_eq!(rec.attributes().get("Name"), Some(&"A;B".to_owned()));
+    let err = scanner.read_record().expect("a record result").unwrap_err();
+    assert_eq!(err.line(), Some(4));
+    assert!(scanner.read_record().is_none());
+}
+
+static HEADER_GTF: &'static str = "#!genome-build GRCh38
+#!genome-version GRCh38
+#!genebuild-last-updated 2019-06
+chr1\tsrc\texon\t101\t200\t.\t+\t.\tgene_id \"g1\"; transcript_id \"t1\";
+#comment
+chr1\tsrc\texon\tx\t400\t.\t+\t.\tgene_id \"g1\"; transcript_id \"t1\";
+chr1\tsrc\texon\t501\t600\t.\t+\t.\tgene_id \"g2\"; transcript_id \"t2\";
+";
+
+#[test]
+fn gtf_reader_error_lines() {
+    let mut reader = GffReader::from_reader(HEADER_GTF.as_bytes(), GffType::GTF2);
+    let err = reader.transcripts().err().expect("an error");
+    assert_eq!(err.line(), Some(6));
+
+    let mut reader = GffReader::from_reader(HEADER_GTF.as_bytes(), GffType::GTF2);
+    reader.lenient(true);
+    assert_eq!(reader.transcripts().expect("transcripts").count(), 2);
+    assert_eq!(reader.warnings().len(), 1);
+    assert_eq!(reader.warnings()[0].line(), 6);
+
+    let input = HEADER_GTF.replace("gene_id \"g1\"; transcript_id \"t1\"", "ID=t1")
+        .replace("gene_id \"g2\"; transcript_id \"t2\"", "ID=t2");
+    let mut reader = GffReader::from_reader(input.as_bytes(), GffType::GFF3);
+    let err = reader.feature_tree().err().expect("an error");
+    assert_eq!(err.line(), Some(6));
+}
+
+#[test]
+fn gtf_reader_progress() {
+    let progress = Arc::new(Mutex::new(Vec::new()));
+    let observed = progress.clone();
+    let mut reader = GffReader::from_reader(SINGLE_GENE_GTF.as_bytes(), GffType::GTF2);
+    reader.observer(move |records, bytes| observed.lock().unwrap().push((records, bytes)));
+    assert_eq!(reader.transcripts().expect("transcripts").count(), 2);
+    let progress = progress.lock().unwrap();
+    assert_eq!(progress.len(), 12);
+    assert_eq!(progress.last(), Some(&(12, SINGLE_GENE_GTF.len() as u64)));
+    assert!(progress.windows(2).all(|w| w[0].0 < w[1].0 && w[0].1 <= w[1].1));
+}
+
+#[test]
+fn gtf_reader_cancelled() {
+    let token = CancelToken::new();
+    let mut reader = GffReader::from_reader(SINGLE_GENE_GTF.as_bytes(), GffType::GTF2);
+    reader.cancel_token(token.clone());
+    let mut transcripts = reader.transcripts().expect("transcripts");
+    assert!(transcripts.next().expect("a transcript result").is_ok());
+    token.cancel();
+    match transcripts.next() {
+        Some(Err(Error::Cancelled)) => {},
+        other => panic!("expected a cancellation error, got {:?}", other),
+    }
+    assert!(transcripts.next().is_none());
+
+    let mut reader = GffReader::from_reader(SINGLE_GENE_GTF.as_bytes(), GffType::GTF2);
+    reader.cancel_token(token);
+    assert!(reader.genes().is_err());
+}
+
+#[test]
+fn gtf_reader_quoted_semicolons() {
+    let input = "chr1\tsrc\texon\t101\t200\t.\t+\t.\t\
+                 gene_id \"g;1\"; transcript_id \"t1\"; note \"a; b\"; level 2;\n";
+    let mut reader = GffReader::from_reader(input.as_bytes(), GffType::GTF2);
+    reader.keep_attributes(true);
+    let trx = reader.transcripts().expect("transcripts")
+        .next().expect("a transcript result").expect("a transcript");
+    assert_eq!(trx.gene_id(), Some("g;1"));
+    assert_eq!(trx.id(), Some("t1"));
+    let exon_attributes = trx.exons()[0].attributes();
+    assert_eq!(exon_attributes.get("note"), Some(&"a; b".to_owned()));
+    assert_eq!(exon_attributes.get("level"), Some(&"2".to_owned()));
+}
+
+#[test]
+fn gff3_escape_unescape() {
+    assert_eq!(gff3_escape("a;b=c,d&e%f\tg"), "a%3Bb%3Dc%2Cd%26e%25f%09g");
+    assert_eq!(gff3_escape("NKX2-1 like"), "NKX2-1 like");
+    assert_eq!(gff3_unescape("a%3Bb%3dc%2Cd%26e%25f%09g"), "a;b=c,d&e%f\tg");
+    assert_eq!(gff3_unescape("100%"), "100%");
+    assert_eq!(gff3_unescape("%zz%4"), "%zz%4");
+    assert_eq!(gff3_unescape(&gff3_escape("x=1;y=2")), "x=1;y=2");
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn gtf_reader_par_transcripts() {
+    let mut reader = GffReader::from_reader(SINGLE_GENE_GTF.as_bytes(), GffType::GTF2);
+    let expected = reader.transcripts().expect("transcripts")
+        .map(|res| res.expect("a transcript"))
+        .map(|trx| (trx.id().map(|v| v.to_owned()), trx.start(), trx.end(), trx.exons().len()))
+        .collect::<Vec<_>>();
+
+    let mut par_reader = GffReader::from_reader(SINGLE_GENE_GTF.as_bytes(), GffType::GTF2);
+    let par = par_reader.par_transcripts().expect("transcripts").into_iter()
+        .map(|res| res.expect("a transcript"))
+        .map(|trx| (trx.id().map(|v| v.to_owned()), trx.start(), trx.end(), trx.exons().len()))
+        .collect::<Vec<_>>();
+
+    assert_eq!(par, expected);
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn gtf_reader_par_genes() {
+    fn gene_values(gx: gte::Gene) -> (Option<String>, u64, u64, Vec<String>) {
+        (gx.id().map(|v| v.to_owned()), gx.start(), gx.end(),
+         gx.transcripts().keys().cloned().collect())
+    }
+    for input in &[SINGLE_GENE_GTF, EXONS_ONLY_GTF] {
+        let mut reader = GffReader::from_reader(input.as_bytes(), GffType::GTF2);
+        let expected = reader.genes().expect("genes")
+            .map(|res| gene_values(res.expect("a gene")))
+            .collect::<Vec<_>>();
+
+        let mut par_reader = GffReader::from_reader(input.as_bytes(), GffType::GTF2);
+        let par = par_reader.par_genes().expect("genes").into_iter()
+            .map(|res| gene_values(res.expect("a gene")))
+            .collect::<Vec<_>>();
+
+        assert!(!par.is_empty());
+        assert_eq!(par, expected);
+    }
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn gtf_reader_par_transcripts_error_lines() {
+    let mut reader = GffReader::from_reader(HEADER_GTF.as_bytes(), GffType::GTF2);
+    let err = reader.par_transcripts().err().expect("an error");
+    assert_eq!(err.line(), Some(6));
+
+    let mut reader = GffReader::from_reader(HEADER_GTF.as_bytes(), GffType::GTF2);
+    reader.lenient(true);
+    assert_eq!(reader.par_transcripts().expect("transcripts").len(), 2);
+    assert_eq!(reader.warnings()[0].line(), 6);
+}
+
+fn indexed_reader() -> GffIndexedReader {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/indexed.gtf.gz");
+    GffIndexedReader::from_path(path, GffType::GTF2).expect("an indexed reader")
+}
+
+fn transcript_ids(transcripts: &[gte::Transcript]) -> Vec<&str> {
+    let mut ids = transcripts.iter().filter_map(|trx| trx.id()).collect::<Vec<&str>>();
+    ids.sort();
+    ids
+}
+
+#[test]
+fn gtf_indexed_reader_query() {
+    let mut reader = indexed_reader();
+
+    let trxs = reader.query("chr1", 2500..2600).expect("transcripts");
+    assert_eq!(transcript_ids(&trxs), vec!["tA1", "tA2"]);
+    let ta1 = trxs.iter().find(|trx| trx.id() == Some("tA1")).unwrap();
+    assert_eq!(ta1.exons().iter().map(|exn| (exn.start(), exn.end())).collect::<Vec<_>>(),
+               vec![(1000, 1200), (4000, 5000)]);
+
+    let trxs = reader.query("chr1", 41000..41100).expect("transcripts");
+    assert_eq!(transcript_ids(&trxs), vec!["tB1"]);
+    assert_eq!(trxs[0].exons().len(), 2);
+
+    let trxs = reader.query("chr1", 4500..40001).expect("transcripts");
+    assert_eq!(transcript_ids(&trxs), vec!["tA1", "tB1"]);
+
+    let trxs = reader.query("chr2", 0..600).expect("transcripts");
+    assert_eq!(transcript_ids(&trxs), vec!["tC1"]);
+}
+
+#[test]
+fn gtf_indexed_reader_query_empty() {
+    let mut reader = indexed_reader();
+    assert!(reader.query("chr1", 10000..20000).expect("transcripts").is_empty());
+    assert!(reader.query("chr3", 0..1000).expect("transcripts").is_empty());
+}
+
+#[test]
+fn gtf_indexed_reader_fetch() {
+    let mut reader = indexed_reader();
+    let mut fetched = reader.fetch("chr1", 4500..4600).expect("a reader");
+    let ids = fetched.transcripts().expect("transcripts")
+        .map(|res| res.expect("a transcript").id().map(|v| v.to_owned()))
+        .collect::<Vec<_>>();
+    assert_eq!(ids.len(), 2);
+}
+
+#[test]
+fn gtf_indexed_reader_missing_index() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/single_gene.gtf");
+    assert!(GffIndexedReader::from_path(path, GffType::GTF2).is_err());
+}
+
+#[test]
+fn gtf_reader_from_path_bgzip_threads() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/indexed.gtf.gz");
+    for &threads in &[1, 4] {
+        let mut reader = GffReader::from_path(path, GffType::GTF2).expect("a GFF reader");
+        let trxs = reader.threads(threads)
+            .transcripts().expect("transcripts")
+            .map(|res| res.expect("a transcript"))
+            .collect::<Vec<_>>();
+        assert_eq!(transcript_ids(&trxs), vec!["tA1", "tA2", "tB1", "tC1"]);
+    }
+}
+
+#[test]
+fn gff3_reader_regulatory_features() {
+    let input = "\
+1\tRegulatory_Build\tenhancer\t10001\t10600\t.\t.\t.\t\
+ID=enhancer:ENSR00000000001;description=Predicted enhancer region
+1\thavana\tgene\t11869\t14409\t.\t+\t.\tID=gene:ENSG00000223972
+1\tRegulatory_Build\tCTCF_binding_site\t15001\t15200\t.\t.\t.\tID=CTCF_binding_site:ENSR2
+1\tRegulatory_Build\tpromoter\t16001\t17000\t.\t.\t.\tID=ENSR3
+";
+    let mut reader = GffReader::from_reader(input.as_bytes(), GffType::GFF3);
+    reader.seq_name_prefix(Some("chr"));
+    let features = reader.regulatory_features()
+        .map(|res| res.expect("a regulatory feature"))
+        .collect::<Vec<_>>();
+    assert_eq!(features.len(), 3);
+    assert_eq!(features[0].kind(), RegulatoryKind::Enhancer);
+    assert_eq!(features[0].id(), Some("ENSR00000000001"));
+    assert_eq!(features[0].seq_name(), "chr1");
+    assert_eq!((features[0].start(), features[0].end()), (10000, 10600));
+    assert!(features[0].strand().is_unknown());
+    assert_eq!(features[0].source(), Some("Regulatory_Build"));
+    assert_eq!(features[0].description(), Some("Predicted enhancer region"));
+    assert_eq!(features[1].kind(), RegulatoryKind::CtcfBindingSite);
+    assert_eq!(features[1].id(), Some("ENSR2"));
+    assert_eq!(features[2].kind(), RegulatoryKind::Promoter);
+    assert_eq!(features[2].id(), Some("ENSR3"));
+    assert_eq!(features[2].kind().to_string(), "promoter");
+}
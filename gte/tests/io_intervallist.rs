@@ -0,0 +1,82 @@
+extern crate gte;
+
+use gte::{IntervalListError, IntervalListWriter, RefFlatReader, SeqDict};
+
+
+static MULT_ROWS_MULT_GENES_WITH_CDS: &'static str =
+    include_str!("data/mult_rows_mult_genes_with_cds.refFlat");
+
+static CHROM_SIZES: &'static str = "chr1\t248956422\nchr2\t242193529\n";
+
+static SEQ_DICT: &'static str = "@HD\tVN:1.5\n\
+                                 @SQ\tSN:chr1\tLN:248956422\tM5:2648ae1bacce4ec4b6cf337dcae37816\n\
+                                 @SQ\tSN:chr2\tLN:242193529\n";
+
+
+#[test]
+fn seq_dict_from_chrom_sizes() {
+    let dict = SeqDict::from_reader(CHROM_SIZES.as_bytes()).expect("a dictionary");
+    assert_eq!(dict.seqs(), &[("chr1".to_owned(), 248956422), ("chr2".to_owned(), 242193529)]);
+    assert_eq!(dict.seq_len("chr2"), Some(242193529));
+    assert_eq!(dict.seq_len("chrM"), None);
+}
+
+#[test]
+fn seq_dict_from_sam_dict() {
+    let dict = SeqDict::from_reader(SEQ_DICT.as_bytes()).expect("a dictionary");
+    let exp = SeqDict::from_reader(CHROM_SIZES.as_bytes()).expect("a dictionary");
+    assert_eq!(dict, exp);
+}
+
+#[test]
+fn seq_dict_from_fasta_index() {
+    let fai = "chr1\t248956422\t112\t70\t71\nchr2\t242193529\t252513167\t70\t71\n";
+    let dict = SeqDict::from_reader(fai.as_bytes()).expect("a dictionary");
+    let exp = SeqDict::from_reader(CHROM_SIZES.as_bytes()).expect("a dictionary");
+    assert_eq!(dict, exp);
+}
+
+#[test]
+fn seq_dict_invalid_line() {
+    let res = SeqDict::from_reader("chr1\tlong\n".as_bytes());
+    match res {
+        Err(gte::Error::IntervalList(IntervalListError::InvalidDictLine(line))) => {
+            assert_eq!(line, "chr1\tlong")
+        },
+        otherwise => panic!("unexpected result: {:?}", otherwise),
+    }
+}
+
+#[test]
+fn interval_list_writer_genes() {
+    let dict = SeqDict::from_reader(CHROM_SIZES.as_bytes()).expect("a dictionary");
+    let mut reader = RefFlatReader::from_reader(MULT_ROWS_MULT_GENES_WITH_CDS.as_bytes());
+    let mut writer = IntervalListWriter::from_memory();
+    writer.write_header(&dict).expect("a header");
+    for gene in reader.genes_stream() {
+        writer.write_gene(&gene.expect("a gene")).expect("an interval");
+    }
+    assert_eq!(writer.as_string(),
+               "@HD\tVN:1.5\tSO:coordinate\n\
+                @SQ\tSN:chr1\tLN:248956422\n\
+                @SQ\tSN:chr2\tLN:242193529\n\
+                chr1\t2556365\t2565622\t+\tTNFRSF14\n\
+                chr1\t34850362\t34859816\t-\tSMIM12\n");
+}
+
+#[test]
+fn interval_list_writer_missing_sequence() {
+    let mut dict = SeqDict::new();
+    dict.push("chr2", 242193529);
+    let mut reader = RefFlatReader::from_reader(MULT_ROWS_MULT_GENES_WITH_CDS.as_bytes());
+    let trx = reader.transcripts_stream().next()
+        .expect("a transcript result").expect("a transcript");
+    let mut writer = IntervalListWriter::from_memory();
+    writer.write_header(&dict).expect("a header");
+    match writer.write_transcript(&trx) {
+        Err(gte::Error::IntervalList(IntervalListError::MissingSequence(name))) => {
+            assert_eq!(name, "chr1")
+        },
+        otherwise => panic!("unexpected result: {:?}", otherwise),
+    }
+}
@@ -3,12 +3,14 @@ extern crate linked_hash_map;
 extern crate gte;
 
 use std::io;
+use std::sync::{Arc, Mutex};
 
 use linked_hash_map::LinkedHashMap;
 
-use gte::{RefFlatReader, RefFlatWriter, RefFlatRecord,
+use gte::{RefFlatReader, RefFlatWriter, RefFlatRecord, RefFlatIndex, RefFlatIndexedReader,
+          RefFlatScanner,
           RefFlatRecordsStream, RefFlatTranscriptsStream, RefFlatGenesStream,
-          Transcript, TBuilder, Gene, GBuilder, Strand};
+          Transcript, TBuilder, Gene, GBuilder, Strand, DuplicatePolicy, CancelToken, Error};
 
 
 static SINGLE_ROW_NO_CDS: &'static str = include_str!("data/single_row_no_cds.refFlat");
@@ -154,6 +156,109 @@ fn refflat_reader_genes_mult_rows_mult_genes_with_cds() {
     assert!(genes.next().is_none());
 }
 
+fn interleaved_rows() -> String {
+    let lines = MULT_ROWS_MULT_GENES_WITH_CDS.lines().collect::<Vec<&str>>();
+    [lines[2], lines[0], lines[3], lines[1], lines[4]].iter()
+        .map(|line| format!("{}\n", line))
+        .collect()
+}
+
+#[test]
+fn refflat_reader_genes_interleaved_rows() {
+    let input = interleaved_rows();
+    let mut reader = RefFlatReader::from_reader(input.as_bytes());
+    assert_eq!(reader.genes_stream().count(), 5);
+}
+
+#[test]
+fn refflat_reader_genes_unsorted_interleaved_rows() {
+    let input = interleaved_rows();
+    let mut reader = RefFlatReader::from_reader(input.as_bytes());
+    let mut genes = reader.genes_unsorted().expect("unsorted genes");
+
+    let gx1 = genes.next().expect("a gene result").expect("a gene");
+    assert_eq!(gx1.id(), Some("SMIM12"));
+    assert_eq!(gx1.transcripts().keys().collect::<Vec<_>>(),
+               vec!["NM_001164824", "NM_001164825", "NM_138428"]);
+    assert_eq!((gx1.start(), gx1.end()), (34850361, 34859816));
+
+    let gx2 = genes.next().expect("a gene result").expect("a gene");
+    assert_eq!(gx2.id(), Some("TNFRSF14"));
+    assert_eq!(gx2.transcripts().len(), 2);
+
+    assert!(genes.next().is_none());
+}
+
+#[test]
+fn refflat_reader_genes_unsorted_bounded_interleaved_rows() {
+    let input = interleaved_rows();
+    let mut reader = RefFlatReader::from_reader(input.as_bytes());
+    let mut genes = reader.genes_unsorted_bounded(4).expect("unsorted genes")
+        .map(|gx| gx.expect("a gene"))
+        .map(|gx| (gx.id().unwrap().to_owned(), gx.transcripts().len()))
+        .collect::<Vec<(String, usize)>>();
+    genes.sort();
+    assert_eq!(genes, vec![("SMIM12".to_owned(), 3), ("TNFRSF14".to_owned(), 2)]);
+}
+
+fn duplicate_transcript_rows() -> String {
+    let lines = MULT_ROWS_MULT_GENES_WITH_CDS.lines().collect::<Vec<&str>>();
+    let dup = lines[0].replace("\t2556364\t2565622\t", "\t2556000\t2565622\t")
+        .replace("\t2556364,", "\t2556000,");
+    format!("{}\n{}\n{}\n", lines[0], dup, lines[1])
+}
+
+fn duplicate_transcript_gene(policy: Option<DuplicatePolicy>) -> gte::Result<Gene> {
+    let input = duplicate_transcript_rows();
+    let mut reader = RefFlatReader::from_reader(input.as_bytes());
+    if let Some(policy) = policy {
+        let _ = reader.duplicate_transcripts(policy);
+    }
+    let mut genes = reader.genes_stream();
+    genes.next().expect("a gene result")
+}
+
+#[test]
+fn refflat_reader_genes_duplicate_transcript_error() {
+    assert!(duplicate_transcript_gene(None).is_err());
+    assert!(duplicate_transcript_gene(Some(DuplicatePolicy::Error)).is_err());
+}
+
+#[test]
+fn refflat_reader_genes_duplicate_transcript_keep_first() {
+    let gx = duplicate_transcript_gene(Some(DuplicatePolicy::KeepFirst)).expect("a gene");
+    assert_eq!(gx.transcripts().keys().collect::<Vec<_>>(), vec!["NM_001297605", "NM_003820"]);
+    assert_eq!(gx.transcripts()["NM_001297605"].start(), 2556364);
+    assert_eq!(gx.start(), 2556364);
+}
+
+#[test]
+fn refflat_reader_genes_duplicate_transcript_keep_last() {
+    let gx = duplicate_transcript_gene(Some(DuplicatePolicy::KeepLast)).expect("a gene");
+    assert_eq!(gx.transcripts().keys().collect::<Vec<_>>(), vec!["NM_001297605", "NM_003820"]);
+    assert_eq!(gx.transcripts()["NM_001297605"].start(), 2556000);
+    assert_eq!(gx.start(), 2556000);
+}
+
+#[test]
+fn refflat_reader_genes_duplicate_transcript_suffix() {
+    let gx = duplicate_transcript_gene(Some(DuplicatePolicy::Suffix)).expect("a gene");
+    assert_eq!(gx.transcripts().keys().collect::<Vec<_>>(),
+               vec!["NM_001297605", "NM_001297605_2", "NM_003820"]);
+    let trx = &gx.transcripts()["NM_001297605_2"];
+    assert_eq!(trx.id(), Some("NM_001297605_2"));
+    assert!(trx.exons().iter().all(|exon| exon.transcript_id() == Some("NM_001297605_2")));
+    assert_eq!(gx.start(), 2556000);
+}
+
+#[test]
+fn refflat_reader_genes_unsorted_duplicate_transcript() {
+    let input = format!("{}{}", MULT_ROWS_NO_CDS, MULT_ROWS_NO_CDS);
+    let mut reader = RefFlatReader::from_reader(input.as_bytes());
+    let mut genes = reader.genes_unsorted().expect("unsorted genes");
+    assert!(genes.next().expect("a gene result").is_err());
+}
+
 #[test]
 fn refflat_writer_rows_single_row_no_cds() {
     let row =
@@ -223,6 +328,31 @@ fn refflat_writer_genes_single_row_no_cds() {
     assert_eq!(writer.as_string(), SINGLE_ROW_NO_CDS);
 }
 
+#[test]
+fn refflat_writer_transcripts_coding_excl_stop() {
+    let build = |strand| {
+        TBuilder::new("chr1", 100, 400)
+            .strand(strand)
+            .id("t1")
+            .gene_id("g1")
+            .coords(vec![(100, 200), (300, 400)], Some((150, 360)))
+            .coding_incl_stop(true)
+            .build()
+            .expect("a transcript")
+    };
+
+    let mut writer = RefFlatWriter::from_memory();
+    writer.coding_incl_stop(false);
+    writer.write_transcript(&build(Strand::Forward)).expect("a successful write");
+    writer.write_transcript(&build(Strand::Reverse)).expect("a successful write");
+    writer.write_transcript(&build(Strand::Unknown)).expect("a successful write");
+    assert_eq!(writer.as_string(), "\
+g1\tt1\tchr1\t+\t100\t400\t150\t357\t2\t100,300,\t200,400,
+g1\tt1\tchr1\t-\t100\t400\t153\t360\t2\t100,300,\t200,400,
+g1\tt1\tchr1\t.\t100\t400\t150\t360\t2\t100,300,\t200,400,
+");
+}
+
 #[test]
 fn refflat_writer_records_mult_rows_mult_genes_with_cds() {
     let recs = [
@@ -407,3 +537,189 @@ fn refflat_writer_genes_mult_rows_mult_genes_with_cds() {
     }
     assert_eq!(writer.as_string(), MULT_ROWS_MULT_GENES_WITH_CDS);
 }
+
+#[test]
+fn refflat_reader_from_path_gzip_mult_rows_no_cds() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/mult_rows_no_cds.refFlat.gz");
+    let mut reader = RefFlatReader::from_path(path).expect("a refFlat reader");
+    let mut transcripts = reader.transcripts_stream();
+
+    let trx1 = next_trx(&mut transcripts);
+    assert_eq!(trx1.id(), Some("NR_046018"));
+
+    let trx2 = next_trx(&mut transcripts);
+    assert_eq!(trx2.id(), Some("NR_030296"));
+
+    assert!(transcripts.next().is_none());
+}
+
+#[test]
+fn refflat_reader_from_path_plain_mult_rows_no_cds() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/mult_rows_no_cds.refFlat");
+    let mut reader = RefFlatReader::from_path(path).expect("a refFlat reader");
+    let mut genes = reader.genes_stream();
+
+    let gx1 = next_gx(&mut genes);
+    assert_eq!(gx1.id(), Some("DDX11L1"));
+
+    let gx2 = next_gx(&mut genes);
+    assert_eq!(gx2.id(), Some("MIR570"));
+
+    assert!(genes.next().is_none());
+}
+
+#[test]
+fn refflat_reader_lenient_mult_rows_no_cds() {
+    let mut lines = MULT_ROWS_NO_CDS.lines();
+    let data = format!("{}\nDDX11L1\tNR_000000\tchr1\t+\tbad\n{}\n",
+                       lines.next().unwrap(), lines.next().unwrap());
+    let mut reader = RefFlatReader::from_reader(data.as_bytes());
+    reader.lenient(true);
+    {
+        let mut transcripts = reader.transcripts_stream();
+
+        let trx1 = next_trx(&mut transcripts);
+        assert_eq!(trx1.id(), Some("NR_046018"));
+
+        let trx2 = next_trx(&mut transcripts);
+        assert_eq!(trx2.id(), Some("NR_030296"));
+
+        assert!(transcripts.next().is_none());
+    }
+    assert_eq!(reader.warnings().len(), 1);
+    assert_eq!(reader.warnings()[0].line(), 2);
+}
+
+#[test]
+fn refflat_reader_strict_mult_rows_no_cds() {
+    let data = format!("{}DDX11L1\tNR_000000\tchr1\t+\tbad\n", MULT_ROWS_NO_CDS);
+    let mut reader = RefFlatReader::from_reader(data.as_bytes());
+    let results = reader.transcripts_stream().collect::<Vec<_>>();
+    assert_eq!(results.len(), 3);
+    let err = results[2].as_ref().expect_err("a parse error");
+    assert_eq!(err.line(), Some(3));
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn refflat_reader_par_genes_mult_rows_mult_genes_with_cds() {
+    let mut reader = RefFlatReader::from_reader(MULT_ROWS_MULT_GENES_WITH_CDS.as_bytes());
+    let genes = reader.par_genes().into_iter()
+        .map(|res| res.expect("a gene"))
+        .collect::<Vec<Gene>>();
+    assert_eq!(genes.len(), 2);
+    assert_eq!(genes[0].id(), Some("TNFRSF14"));
+    assert_eq!(genes[0].transcripts().len(), 2);
+    assert_eq!(genes[1].id(), Some("SMIM12"));
+    assert_eq!(genes[1].transcripts().len(), 3);
+}
+
+#[test]
+fn refflat_indexed_reader_seek_gene() {
+    let data = io::Cursor::new(MULT_ROWS_MULT_GENES_WITH_CDS.as_bytes());
+    let mut reader = RefFlatIndexedReader::from_reader(data).expect("an indexed reader");
+    assert_eq!(reader.index().len(), 5);
+
+    let gx = reader.seek_gene("SMIM12").expect("a gene result").expect("a gene");
+    assert_eq!(gx.id(), Some("SMIM12"));
+    assert_eq!((gx.start(), gx.end()), (34850361, 34859816));
+    assert_eq!(gx.strand(), &Strand::Reverse);
+    assert_eq!(gx.transcripts().len(), 3);
+
+    let gx = reader.seek_gene("TNFRSF14").expect("a gene result").expect("a gene");
+    assert_eq!(gx.transcripts().keys().collect::<Vec<_>>(), vec!["NM_001297605", "NM_003820"]);
+
+    assert!(reader.seek_gene("DDX11L1").expect("a gene result").is_none());
+}
+
+#[test]
+fn refflat_indexed_reader_query() {
+    let data = io::Cursor::new(MULT_ROWS_MULT_GENES_WITH_CDS.as_bytes());
+    let mut reader = RefFlatIndexedReader::from_reader(data).expect("an indexed reader");
+
+    let gxs = reader.query("chr1", 2560000..34850400).expect("genes");
+    assert_eq!(gxs.iter().map(|gx| gx.id()).collect::<Vec<_>>(),
+               vec![Some("TNFRSF14"), Some("SMIM12")]);
+    let gxs = reader.query("chr1", 34859737..34860000).expect("genes");
+    assert_eq!(gxs.len(), 1);
+    assert_eq!(gxs[0].transcripts().len(), 3);
+    assert!(reader.query("chr1", 0..1000).expect("genes").is_empty());
+    assert!(reader.query("chr2", 2560000..34850400).expect("genes").is_empty());
+}
+
+#[test]
+fn refflat_index_write_and_load_non_contiguous() {
+    let mut lines = MULT_ROWS_MULT_GENES_WITH_CDS.lines().collect::<Vec<&str>>();
+    lines.swap(1, 2);
+    let data = lines.join("\n") + "\n";
+
+    let index = RefFlatIndex::build(data.as_bytes()).expect("an index");
+    let mut saved = Vec::new();
+    index.write(&mut saved).expect("a saved index");
+    let loaded = RefFlatIndex::from_reader(saved.as_slice()).expect("a loaded index");
+    assert_eq!(loaded, index);
+    assert!(loaded.contains_gene("TNFRSF14"));
+
+    let mut reader = RefFlatIndexedReader::new(io::Cursor::new(data.as_bytes()), loaded);
+    let gx = reader.seek_gene("TNFRSF14").expect("a gene result").expect("a gene");
+    assert_eq!(gx.transcripts().len(), 2);
+}
+
+#[test]
+fn refflat_index_invalid_line() {
+    assert!(RefFlatIndex::from_reader("SMIM12\tchr1\tx\t1\t0\t10\n".as_bytes()).is_err());
+}
+
+#[test]
+fn scanner_mult_rows() {
+    let mut scanner = RefFlatScanner::from_reader(MULT_ROWS_MULT_GENES_WITH_CDS.as_bytes());
+    let mut tids = Vec::new();
+    while let Some(result) = scanner.read_record() {
+        let rec = result.expect("a record");
+        if rec.strand() == '-' {
+            tids.push(rec.transcript_id().to_owned());
+        }
+    }
+    assert_eq!(tids[..3].to_vec(), vec!["NM_001164824", "NM_001164825", "NM_138428"]);
+
+    let mut scanner = RefFlatScanner::from_reader(MULT_ROWS_MULT_GENES_WITH_CDS.as_bytes());
+    let rec = scanner.read_record().expect("a record result").expect("a record");
+    assert_eq!((rec.gene_id(), rec.seq_name()), ("TNFRSF14", "chr1"));
+    assert_eq!((rec.transcript_start(), rec.transcript_end()), (2556364, 2565622));
+    assert_eq!((rec.coding_start(), rec.coding_end()), (2556664, 2562868));
+    assert_eq!(rec.num_exons(), 7);
+    let coords = rec.exon_coords().expect("exon coordinates");
+    assert_eq!(coords[0], (2556364, 2556733));
+    assert_eq!(rec.to_record().expect("a record").exon_ends().len(), 7);
+
+    let mut scanner = RefFlatScanner::from_reader("\ng1\tt1\tchr1\t+\tx\n".as_bytes());
+    let err = scanner.read_record().expect("a record result").unwrap_err();
+    assert_eq!(err.line(), Some(2));
+}
+
+#[test]
+fn refflat_reader_progress_and_cancel() {
+    let progress = Arc::new(Mutex::new(Vec::new()));
+    let token = CancelToken::new();
+    let (observed, cancel) = (progress.clone(), token.clone());
+    let mut reader = RefFlatReader::from_reader(MULT_ROWS_MULT_GENES_WITH_CDS.as_bytes());
+    reader
+        .observer(move |records, bytes| {
+            observed.lock().unwrap().push((records, bytes));
+            if records == 2 {
+                cancel.cancel();
+            }
+        })
+        .cancel_token(token);
+
+    let genes = reader.genes_stream().collect::<Vec<_>>();
+    assert_eq!(genes.len(), 2);
+    assert_eq!(genes[0].as_ref().expect("a gene").transcripts().len(), 2);
+    match genes[1] {
+        Err(Error::Cancelled) => {},
+        ref other => panic!("expected a cancellation error, got {:?}", other),
+    }
+    let progress = progress.lock().unwrap();
+    assert_eq!(progress.iter().map(|&(records, _)| records).collect::<Vec<_>>(), vec![1, 2]);
+    assert!(progress[0].1 > 0);
+}
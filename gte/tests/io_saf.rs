@@ -0,0 +1,63 @@
+extern crate gte;
+extern crate linked_hash_map;
+
+use linked_hash_map::LinkedHashMap;
+
+use gte::{GBuilder, RefFlatReader, SafError, SafWriter, Strand};
+
+
+static MULT_ROWS_MULT_GENES_WITH_CDS: &'static str =
+    include_str!("data/mult_rows_mult_genes_with_cds.refFlat");
+
+
+#[test]
+fn saf_writer_gene() {
+    let mut coords = LinkedHashMap::new();
+    coords.insert("trx01".to_owned(), ((100, 500), vec![(100, 300), (400, 500)], None));
+    coords.insert("trx02".to_owned(), ((100, 500), vec![(100, 300), (450, 500)], None));
+    let gx = GBuilder::new("chrT", 100, 500)
+        .strand(Strand::Reverse)
+        .id("gene-1")
+        .transcript_coords(coords)
+        .build()
+        .unwrap();
+    let mut writer = SafWriter::from_memory();
+    writer.write_header().expect("a header");
+    writer.write_gene(&gx).expect("SAF rows");
+    assert_eq!(writer.as_string(),
+               "GeneID\tChr\tStart\tEnd\tStrand\n\
+                gene-1\tchrT\t101\t300\t-\n\
+                gene-1\tchrT\t401\t500\t-\n\
+                gene-1\tchrT\t101\t300\t-\n\
+                gene-1\tchrT\t451\t500\t-\n");
+}
+
+#[test]
+fn saf_writer_collapsed_genes() {
+    let mut reader = RefFlatReader::from_reader(MULT_ROWS_MULT_GENES_WITH_CDS.as_bytes());
+    let mut writer = SafWriter::from_memory();
+    writer.collapse(true);
+    for gene in reader.genes_stream() {
+        let gene = gene.expect("a gene");
+        if gene.id() == Some("SMIM12") {
+            writer.write_gene(&gene).expect("SAF rows");
+        }
+    }
+    assert_eq!(writer.as_string(),
+               "SMIM12\tchr1\t34850362\t34855982\t-\n\
+                SMIM12\tchr1\t34856556\t34856739\t-\n\
+                SMIM12\tchr1\t34858840\t34859045\t-\n\
+                SMIM12\tchr1\t34859455\t34859816\t-\n");
+}
+
+#[test]
+fn saf_writer_missing_gene_id() {
+    let gx = GBuilder::new("chrT", 100, 500).strand(Strand::Forward).build().unwrap();
+    let mut writer = SafWriter::from_memory();
+    match writer.write_gene(&gx) {
+        Err(gte::Error::Saf(SafError::MissingGeneId(seq_name, start, end))) => {
+            assert_eq!((seq_name.as_str(), start, end), ("chrT", 100, 500))
+        },
+        otherwise => panic!("unexpected result: {:?}", otherwise),
+    }
+}
@@ -0,0 +1,94 @@
+extern crate gte;
+extern crate linked_hash_map;
+
+use linked_hash_map::LinkedHashMap;
+
+use gte::{GBuilder, JunctionFormat, JunctionSet, JunctionWriter, SeqOrder, Strand, TBuilder,
+          Transcript};
+use Strand::*;
+
+
+fn transcript(seq_name: &str, id: &str, strand: Strand, exon_coords: Vec<(u64, u64)>)
+    -> Transcript
+{
+    let start = exon_coords.first().unwrap().0;
+    let end = exon_coords.last().unwrap().1;
+    TBuilder::new(seq_name, start, end)
+        .id(id)
+        .strand(strand)
+        .coords(exon_coords, None)
+        .build()
+        .expect("a transcript")
+}
+
+fn transcripts() -> Vec<gte::Result<Transcript>> {
+    vec![Ok(transcript("chr2", "t1", Forward, vec![(100, 200), (300, 400), (500, 600)])),
+         Ok(transcript("chr2", "t2", Forward, vec![(150, 200), (300, 450)])),
+         Ok(transcript("chr10", "t3", Reverse, vec![(100, 200), (300, 400)])),
+         Ok(transcript("chr2", "t4", Reverse, vec![(100, 200), (300, 400)]))]
+}
+
+#[test]
+fn transcript_junctions() {
+    let trx = transcript("chr2", "t1", Reverse, vec![(100, 200), (200, 300), (400, 500)]);
+    let junctions = trx.junctions();
+    assert_eq!(junctions.len(), 1);
+    assert_eq!(junctions[0].seq_name(), "chr2");
+    assert_eq!((junctions[0].start(), junctions[0].end()), (300, 400));
+    assert_eq!(junctions[0].strand(), &Reverse);
+    assert_eq!(junctions[0].transcript_ids(), &["t1".to_owned()]);
+}
+
+#[test]
+fn junction_set_unique() {
+    let set = JunctionSet::from_transcripts(transcripts()).expect("junctions");
+    assert_eq!(set.len(), 4);
+    let junctions = set.into_sorted(&SeqOrder::Natural);
+    let summary = junctions.iter()
+        .map(|j| (j.seq_name(), j.start(), j.end(), *j.strand(), j.transcript_ids().len()))
+        .collect::<Vec<_>>();
+    assert_eq!(summary, vec![("chr2", 200, 300, Forward, 2),
+                             ("chr2", 200, 300, Reverse, 1),
+                             ("chr2", 400, 500, Forward, 1),
+                             ("chr10", 200, 300, Reverse, 1)]);
+    assert_eq!(junctions[0].transcript_ids(), &["t1".to_owned(), "t2".to_owned()]);
+}
+
+#[test]
+fn junction_set_from_genes() {
+    let mut trxs = LinkedHashMap::new();
+    let _ = trxs.insert("t1".to_owned(),
+                        transcript("chr1", "t1", Forward, vec![(100, 200), (300, 400)]));
+    let _ = trxs.insert("t2".to_owned(),
+                        transcript("chr1", "t2", Forward, vec![(100, 250), (300, 400)]));
+    let gene = GBuilder::new("chr1", 100, 400)
+        .id("g1")
+        .strand(Forward)
+        .transcripts(trxs)
+        .build();
+    let set = JunctionSet::from_genes(vec![gene]).expect("junctions");
+    assert_eq!(set.len(), 2);
+}
+
+#[test]
+fn junction_writer_bed() {
+    let set = JunctionSet::from_transcripts(transcripts()).expect("junctions");
+    let mut writer = JunctionWriter::from_memory();
+    writer.write_set(set, &SeqOrder::Natural).expect("written junctions");
+    assert_eq!(writer.as_string(),
+               "chr2\t200\t300\tt1,t2\t2\t+\n\
+                chr2\t200\t300\tt4\t1\t-\n\
+                chr2\t400\t500\tt1\t1\t+\n\
+                chr10\t200\t300\tt3\t1\t-\n");
+}
+
+#[test]
+fn junction_writer_star() {
+    let trx = transcript("chr1", "t1", Reverse, vec![(100, 200), (300, 400)]);
+    let mut writer = JunctionWriter::from_memory();
+    writer.format(JunctionFormat::StarSj);
+    for junction in trx.junctions() {
+        writer.write_junction(&junction).expect("a written junction");
+    }
+    assert_eq!(writer.as_string(), "chr1\t201\t300\t2\t0\t1\t0\t0\t0\n");
+}
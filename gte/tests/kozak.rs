@@ -0,0 +1,46 @@
+extern crate gte;
+
+mod common;
+
+use gte::{KozakContext, KozakStrength};
+use common::{make_source, make_trx_fwd, make_trx_rev, trx_fwd};
+
+
+#[test]
+fn kozak_context_strong() {
+    let ctx = KozakContext::from_spliced(b"TTgccaccATGGCC", 8);
+    assert_eq!(ctx.seq(), b"GCCACCATGG");
+    assert_eq!(ctx.strength(), KozakStrength::Strong);
+    assert_eq!(ctx.score(), 7);
+}
+
+#[test]
+fn kozak_context_adequate_and_weak() {
+    let ctx = KozakContext::from_spliced(b"TTTATTATGC", 6);
+    assert_eq!(ctx.strength(), KozakStrength::Adequate);
+    assert_eq!(ctx.score(), 1);
+    let ctx = KozakContext::from_spliced(b"CCATGC", 2);
+    assert_eq!(ctx.seq(), b"NNNNCCATGC");
+    assert_eq!(ctx.strength(), KozakStrength::Weak);
+    assert_eq!(ctx.score(), 2);
+    assert!(KozakStrength::Strong < KozakStrength::Weak);
+}
+
+#[test]
+fn transcript_kozak_context() {
+    let mut source = make_source();
+    for trx in vec![make_trx_fwd(), make_trx_rev()] {
+        let ctx = trx.kozak_context(&mut source).expect("a result").expect("a context");
+        assert_eq!(ctx.seq(), b"NNNNCCATGG");
+        assert_eq!(ctx.strength(), KozakStrength::Adequate);
+        assert_eq!(ctx.score(), 3);
+        assert_eq!(trx.kozak_context_at(&mut source, 2).expect("a context"), ctx);
+    }
+}
+
+#[test]
+fn transcript_kozak_context_no_coding() {
+    let mut source = make_source();
+    let trx = trx_fwd(None);
+    assert_eq!(trx.kozak_context(&mut source).expect("a result"), None);
+}
@@ -0,0 +1,114 @@
+extern crate gte;
+extern crate linked_hash_map;
+
+use linked_hash_map::LinkedHashMap;
+
+use gte::{ChainMap, GBuilder, LiftOverError, LiftStatus, Strand, TBuilder, Transcript};
+use Strand::*;
+
+
+static CHAINS: &'static str = "\
+chain 1000 chrA 10000 + 0 1000 chrB 20000 + 5000 6090 1
+300\t10\t0
+200\t0\t100
+490
+
+chain 500 chrA 10000 + 2000 2300 chrC 1000 - 100 400 2
+300
+";
+
+fn make_trx(id: &str, strand: Strand, exon_coords: Vec<(u64, u64)>,
+            coding_coord: Option<(u64, u64)>) -> Transcript {
+    let (start, end) = (exon_coords[0].0, exon_coords[exon_coords.len() - 1].1);
+    TBuilder::new("chrA", start, end)
+        .id(id)
+        .strand(strand)
+        .coords(exon_coords, coding_coord)
+        .build()
+        .unwrap()
+}
+
+fn exon_coords(transcript: &Transcript) -> Vec<(u64, u64)> {
+    transcript.exons().iter().map(|exn| (exn.start(), exn.end())).collect()
+}
+
+#[test]
+fn chain_map_from_reader() {
+    let chains = ChainMap::from_reader(CHAINS.as_bytes()).expect("chains");
+    assert_eq!(chains.len(), 2);
+}
+
+#[test]
+fn chain_map_invalid_line() {
+    match ChainMap::from_reader("300\n".as_bytes()) {
+        Err(gte::Error::LiftOver(LiftOverError::InvalidChainLine(line))) => {
+            assert_eq!(line, "300")
+        },
+        otherwise => panic!("unexpected result: {:?}", otherwise),
+    }
+}
+
+#[test]
+fn lift_transcript_split_exons() {
+    let chains = ChainMap::from_reader(CHAINS.as_bytes()).expect("chains");
+    let trx = make_trx("trx01", Forward, vec![(100, 200), (250, 400), (450, 600)], None);
+    let (lifted, report) = chains.lift_transcript(&trx).expect("a lift result");
+    let lifted = lifted.expect("a lifted transcript");
+    assert_eq!((lifted.seq_name(), lifted.start(), lifted.end()), ("chrB", 5100, 5690));
+    assert_eq!(lifted.strand(), &Forward);
+    assert_eq!(exon_coords(&lifted), vec![(5100, 5200), (5250, 5390), (5440, 5500), (5600, 5690)]);
+    assert_eq!(report.transcript_id(), Some("trx01"));
+    assert_eq!(report.exon_statuses(),
+               &[LiftStatus::Mapped, LiftStatus::Partial(1), LiftStatus::Partial(2)]);
+    assert!(report.is_lifted());
+    assert!(!report.is_complete());
+}
+
+#[test]
+fn lift_transcript_reverse_chain() {
+    let chains = ChainMap::from_reader(CHAINS.as_bytes()).expect("chains");
+    let trx = make_trx("trx01", Forward, vec![(2000, 2050), (2100, 2200)], Some((2020, 2150)));
+    let (lifted, report) = chains.lift_transcript(&trx).expect("a lift result");
+    let lifted = lifted.expect("a lifted transcript");
+    assert_eq!((lifted.seq_name(), lifted.start(), lifted.end()), ("chrC", 700, 900));
+    assert_eq!(lifted.strand(), &Reverse);
+    assert_eq!(exon_coords(&lifted), vec![(700, 800), (850, 900)]);
+    assert_eq!(lifted.coding_coord(true), Some((747, 880)));
+    assert!(report.is_complete());
+}
+
+#[test]
+fn lift_transcript_unmapped() {
+    let chains = ChainMap::from_reader(CHAINS.as_bytes()).expect("chains");
+    let trx = make_trx("trx01", Forward, vec![(3000, 3100)], None);
+    let (lifted, report) = chains.lift_transcript(&trx).expect("a lift result");
+    assert!(lifted.is_none());
+    assert_eq!(report.exon_statuses(), &[LiftStatus::Dropped]);
+    assert!(!report.is_lifted());
+}
+
+#[test]
+fn lift_gene() {
+    let chains = ChainMap::from_reader(CHAINS.as_bytes()).expect("chains");
+    let mut trxs = LinkedHashMap::new();
+    let _ = trxs.insert("trx01".to_owned(),
+                        make_trx("trx01", Reverse, vec![(100, 200), (700, 900)], None));
+    let _ = trxs.insert("trx02".to_owned(),
+                        make_trx("trx02", Reverse, vec![(2000, 2050)], None));
+    let _ = trxs.insert("trx03".to_owned(),
+                        make_trx("trx03", Reverse, vec![(600, 800)], None));
+    let gx = GBuilder::new("chrA", 100, 2050)
+        .id("gene-1")
+        .strand(Reverse)
+        .transcripts(trxs)
+        .build()
+        .unwrap();
+
+    let (lifted, reports) = chains.lift_gene(&gx).expect("a lift result");
+    let lifted = lifted.expect("a lifted gene");
+    assert_eq!((lifted.seq_name(), lifted.start(), lifted.end()), ("chrB", 5100, 5990));
+    assert_eq!(lifted.strand(), &Reverse);
+    assert_eq!(lifted.transcripts().keys().collect::<Vec<_>>(), vec!["trx01", "trx03"]);
+    assert_eq!(reports.iter().map(|r| r.is_lifted()).collect::<Vec<_>>(),
+               vec![true, false, true]);
+}
@@ -0,0 +1,94 @@
+extern crate gte;
+
+use gte::{DuplicatePolicy, Gene, MergedGenes, MergeOptions, RefFlatReader, SOURCE_ATTR};
+
+
+static REFERENCE: &'static str = "\
+G1\tT1\tchr1\t+\t100\t500\t150\t450\t2\t100,300,\t200,500,
+G2\tT2\tchr1\t+\t1000\t2000\t1000\t1000\t1\t1000,\t2000,
+";
+
+static CUSTOM: &'static str = "\
+G3\tT3\tchrT\t+\t0\t900\t0\t0\t1\t0,\t900,
+G1\tT4\tchrT\t+\t0\t900\t0\t0\t1\t0,\t900,
+G4\tT2\tchrT\t+\t0\t900\t0\t0\t1\t0,\t900,
+";
+
+fn merged(opts: MergeOptions) -> Vec<gte::Result<Gene>> {
+    let mut ref_reader = RefFlatReader::from_reader(REFERENCE.as_bytes());
+    let mut custom_reader = RefFlatReader::from_reader(CUSTOM.as_bytes());
+    let mut genes = MergedGenes::new(opts);
+    let _ = genes.add_source("ref", ref_reader.genes_stream())
+        .add_source("custom", custom_reader.genes_stream());
+    genes.collect()
+}
+
+fn ids(genes: Vec<gte::Result<Gene>>) -> Vec<(String, Vec<String>)> {
+    genes.into_iter()
+        .map(|res| {
+            let gx = res.expect("a gene");
+            (gx.id().unwrap().to_owned(), gx.transcripts().keys().cloned().collect())
+        })
+        .collect()
+}
+
+#[test]
+fn merged_genes_renamed() {
+    let genes = merged(MergeOptions::new());
+    let sources = genes.iter()
+        .map(|res| res.as_ref().unwrap().attributes().get(SOURCE_ATTR).cloned().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(sources, vec!["ref", "ref", "custom", "custom", "custom"]);
+    let gx = genes[3].as_ref().unwrap();
+    let trx = gx.transcripts().get("T4").expect("a transcript");
+    assert_eq!(trx.gene_id(), Some("G1_custom"));
+    assert_eq!(trx.attributes().get(SOURCE_ATTR), Some(&"custom".to_owned()));
+    assert_eq!(ids(genes), vec![
+        ("G1".to_owned(), vec!["T1".to_owned()]),
+        ("G2".to_owned(), vec!["T2".to_owned()]),
+        ("G3".to_owned(), vec!["T3".to_owned()]),
+        ("G1_custom".to_owned(), vec!["T4".to_owned()]),
+        ("G4".to_owned(), vec!["T2_custom".to_owned()]),
+    ]);
+}
+
+#[test]
+fn merged_genes_numeric_suffix() {
+    let mut opts = MergeOptions::new();
+    let _ = opts.rename_with_source(false);
+    let genes = ids(merged(opts));
+    assert_eq!(genes[3].0, "G1_2");
+    assert_eq!(genes[4].1, vec!["T2_2".to_owned()]);
+}
+
+#[test]
+fn merged_genes_keep_first() {
+    let mut opts = MergeOptions::new();
+    let _ = opts.duplicate_policy(DuplicatePolicy::KeepFirst);
+    let genes = ids(merged(opts));
+    let gids = genes.into_iter().map(|(gid, _)| gid).collect::<Vec<_>>();
+    assert_eq!(gids, vec!["G1", "G2", "G3"]);
+}
+
+#[test]
+fn merged_genes_keep_last() {
+    let mut opts = MergeOptions::new();
+    let _ = opts.duplicate_policy(DuplicatePolicy::KeepLast);
+    let genes = merged(opts);
+    let sources = genes.iter()
+        .map(|res| res.as_ref().unwrap().attributes().get(SOURCE_ATTR).cloned().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(sources, vec!["custom", "custom", "custom"]);
+    let gids = ids(genes).into_iter().map(|(gid, _)| gid).collect::<Vec<_>>();
+    assert_eq!(gids, vec!["G3", "G1", "G4"]);
+}
+
+#[test]
+fn merged_genes_error() {
+    let mut opts = MergeOptions::new();
+    let _ = opts.duplicate_policy(DuplicatePolicy::Error);
+    let genes = merged(opts);
+    assert_eq!(genes.len(), 5);
+    assert!(genes[3].is_err());
+    assert!(genes[4].is_err());
+}
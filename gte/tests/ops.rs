@@ -0,0 +1,62 @@
+extern crate bio;
+extern crate gte;
+
+use bio::utils::Interval;
+
+use gte::{complement_intervals, intersect_intervals, merge_intervals, subtract_intervals,
+          Strand, TBuilder};
+
+
+fn ivs(coords: Vec<(u64, u64)>) -> Vec<Interval<u64>> {
+    coords.into_iter().map(|(start, end)| Interval::new(start..end).unwrap()).collect()
+}
+
+fn coords(intervals: Vec<Interval<u64>>) -> Vec<(u64, u64)> {
+    intervals.iter().map(|iv| (iv.start, iv.end)).collect()
+}
+
+#[test]
+fn merge_unsorted() {
+    let merged = merge_intervals(ivs(vec![(50, 60), (10, 20), (15, 30), (30, 40), (45, 45)]));
+    assert_eq!(coords(merged), vec![(10, 40), (50, 60)]);
+}
+
+#[test]
+fn intersect_overlapping() {
+    let a = ivs(vec![(10, 40), (50, 60), (70, 80)]);
+    let b = ivs(vec![(5, 15), (20, 25), (35, 55), (80, 90)]);
+    assert_eq!(coords(intersect_intervals(a, b)), vec![(10, 15), (20, 25), (35, 40), (50, 55)]);
+    assert!(intersect_intervals(ivs(vec![(10, 20)]), vec![]).is_empty());
+}
+
+#[test]
+fn subtract_overlapping() {
+    let a = ivs(vec![(10, 40), (50, 60), (70, 80)]);
+    let b = ivs(vec![(5, 15), (20, 25), (35, 55), (72, 74)]);
+    assert_eq!(coords(subtract_intervals(a, b)),
+               vec![(15, 20), (25, 35), (55, 60), (70, 72), (74, 80)]);
+    assert_eq!(coords(subtract_intervals(ivs(vec![(10, 20)]), ivs(vec![(0, 30)]))), vec![]);
+}
+
+#[test]
+fn complement_region() {
+    let region = Interval::new(0..100).unwrap();
+    assert_eq!(coords(complement_intervals(ivs(vec![(30, 40), (0, 10), (35, 50)]), region)),
+               vec![(10, 30), (50, 100)]);
+}
+
+#[test]
+fn transcript_exonic_intervals_ops() {
+    let trx1 = TBuilder::new("chrT", 100, 1000)
+        .strand(Strand::Forward)
+        .coords(vec![(100, 300), (400, 500), (700, 1000)], None)
+        .build().unwrap();
+    let trx2 = TBuilder::new("chrT", 200, 900)
+        .strand(Strand::Forward)
+        .coords(vec![(200, 450), (800, 900)], None)
+        .build().unwrap();
+    assert_eq!(coords(intersect_intervals(trx1.exonic_intervals(), trx2.exonic_intervals())),
+               vec![(200, 300), (400, 450), (800, 900)]);
+    assert_eq!(coords(subtract_intervals(trx1.exonic_intervals(), trx2.exonic_intervals())),
+               vec![(100, 200), (450, 500), (700, 800), (900, 1000)]);
+}
@@ -0,0 +1,55 @@
+extern crate gte;
+
+mod common;
+
+use gte::CodonTable;
+use common::{make_source, trx_fwd, trx_rev};
+
+
+#[test]
+fn find_orfs() {
+    let mut source = make_source();
+    let table = CodonTable::standard();
+    let trx = trx_fwd(None);
+    let orfs = trx.find_orfs(&mut source, &table, 12).expect("open reading frames");
+    assert_eq!(orfs.len(), 1);
+    assert_eq!((orfs[0].start(), orfs[0].end()), (2, 17));
+    assert_eq!(orfs[0].len(), 15);
+    assert_eq!(orfs[0].protein_len(), 4);
+    assert!(trx.find_orfs(&mut source, &table, 13).expect("open reading frames").is_empty());
+}
+
+#[test]
+fn set_orf_fwd() {
+    let mut source = make_source();
+    let table = CodonTable::standard();
+    let mut trx = trx_fwd(None);
+    let orf = trx.set_longest_orf(&mut source, &table, 0).expect("a result");
+    assert_eq!(orf.map(|o| (o.start(), o.end())), Some((2, 17)));
+    assert_eq!(trx.coding_coord(true), Some((4, 23)));
+    assert_eq!(trx.coding_coord(false), Some((4, 20)));
+    assert_eq!(trx.cds_sequence(&mut source, true).expect("a sequence"),
+               b"ATGGCCAAATTTTAA".to_vec());
+    assert_eq!(trx.exons().len(), 2);
+}
+
+#[test]
+fn set_orf_rev() {
+    let mut source = make_source();
+    let table = CodonTable::standard();
+    let mut trx = trx_rev(None);
+    let orfs = trx.find_orfs(&mut source, &table, 0).expect("open reading frames");
+    assert_eq!(orfs.len(), 1);
+    trx.set_orf(&orfs[0]).expect("a set frame");
+    assert_eq!(trx.coding_coord(true), Some((7, 26)));
+    assert_eq!(trx.translate(&mut source, &table).expect("a protein"), b"MAKF".to_vec());
+}
+
+#[test]
+fn set_longest_orf_none() {
+    let mut source = make_source();
+    let table = CodonTable::standard();
+    let mut trx = trx_fwd(None);
+    assert_eq!(trx.set_longest_orf(&mut source, &table, 100).expect("a result"), None);
+    assert_eq!(trx.coding_coord(true), None);
+}
@@ -0,0 +1,58 @@
+extern crate gte;
+
+use gte::{GenomeAnnotation, PanelBuilder, PanelRegionKind, RefFlatReader, write_panel_bed};
+
+
+static MULT_ROWS_MULT_GENES_WITH_CDS: &'static str =
+    include_str!("data/mult_rows_mult_genes_with_cds.refFlat");
+
+fn make_annotation() -> GenomeAnnotation {
+    let mut reader = RefFlatReader::from_reader(MULT_ROWS_MULT_GENES_WITH_CDS.as_bytes());
+    GenomeAnnotation::from_genes_stream(reader.genes_stream()).expect("an annotation")
+}
+
+fn region_coords(builder: &PanelBuilder, genes: &[&str]) -> Vec<(u64, u64, String)> {
+    builder.regions(&make_annotation(), genes)
+        .expect("regions")
+        .into_iter()
+        .map(|region| (region.start, region.end, region.name))
+        .collect()
+}
+
+#[test]
+fn panel_exon_regions() {
+    let mut builder = PanelBuilder::new();
+    let _ = builder.padding(10).exon_names(true);
+    assert_eq!(region_coords(&builder, &["SMIM12"]), vec![
+        (34850351, 34855992, "SMIM12_exon4".to_owned()),
+        (34856545, 34856749, "SMIM12_exon3".to_owned()),
+        (34858829, 34859055, "SMIM12_exon2".to_owned()),
+        (34859444, 34859826, "SMIM12_exon1".to_owned()),
+    ]);
+}
+
+#[test]
+fn panel_locus_regions() {
+    let mut builder = PanelBuilder::new();
+    let _ = builder.kind(PanelRegionKind::Locus);
+    assert_eq!(region_coords(&builder, &["SMIM12"]),
+               vec![(34850361, 34859816, "SMIM12".to_owned())]);
+    let _ = builder.cds_only(true);
+    assert_eq!(region_coords(&builder, &["SMIM12"]),
+               vec![(34855698, 34855977, "SMIM12".to_owned())]);
+}
+
+#[test]
+fn panel_unknown_gene() {
+    assert!(PanelBuilder::new().regions(&make_annotation(), &["SMIM12", "TP53"]).is_err());
+}
+
+#[test]
+fn panel_write_bed() {
+    let mut builder = PanelBuilder::new();
+    let _ = builder.kind(PanelRegionKind::Locus);
+    let regions = builder.regions(&make_annotation(), &["SMIM12"]).expect("regions");
+    let mut buf = vec![];
+    write_panel_bed(&mut buf, &regions).expect("BED rows");
+    assert_eq!(String::from_utf8(buf).expect("a string"), "chr1\t34850361\t34859816\tSMIM12\n");
+}
@@ -0,0 +1,58 @@
+extern crate gte;
+
+use gte::{GeneStreamExt, ParYPolicy, ParYTranscripts, RefFlatReader, PAR_X_ID_ATTR,
+          is_par_y_id, par_x_id};
+
+
+static PAR_GENES: &'static str = "\
+ENSG01.2\tENST01.3\tchrX\t+\t100\t500\t100\t100\t1\t100,\t500,
+ENSG01.2_PAR_Y\tENST01.3_PAR_Y\tchrY\t+\t100\t500\t100\t100\t1\t100,\t500,
+ENSG02.1\tENST02.1\tchrY\t+\t1000\t2000\t1000\t1000\t1\t1000,\t2000,
+";
+
+#[test]
+fn par_y_ids() {
+    assert!(is_par_y_id("ENSG00000182378.14_PAR_Y"));
+    assert!(!is_par_y_id("ENSG00000182378.14"));
+    assert!(!is_par_y_id("_PAR_Y"));
+    assert_eq!(par_x_id("ENST00000381192.10_PAR_Y"), Some("ENST00000381192.10"));
+    assert_eq!(par_x_id("ENST00000381192.10"), None);
+}
+
+#[test]
+fn par_y_genes() {
+    let cases = vec![
+        (ParYPolicy::Keep, vec!["ENSG01.2", "ENSG01.2_PAR_Y", "ENSG02.1"]),
+        (ParYPolicy::Filter, vec!["ENSG01.2", "ENSG02.1"]),
+        (ParYPolicy::Link, vec!["ENSG01.2", "ENSG01.2_PAR_Y", "ENSG02.1"]),
+    ];
+    for (policy, expected) in cases {
+        let mut reader = RefFlatReader::from_reader(PAR_GENES.as_bytes());
+        let genes = reader.genes_stream()
+            .par_y(policy)
+            .map(|res| res.expect("a gene"))
+            .collect::<Vec<_>>();
+        let ids = genes.iter().map(|gx| gx.id().unwrap()).collect::<Vec<_>>();
+        assert_eq!(ids, expected, "{:?}", policy);
+        let linked = genes.iter()
+            .filter(|gx| gx.attributes().contains_key(PAR_X_ID_ATTR))
+            .collect::<Vec<_>>();
+        if policy == ParYPolicy::Link {
+            assert_eq!(linked.len(), 1);
+            assert_eq!(linked[0].attributes().get(PAR_X_ID_ATTR), Some(&"ENSG01.2".to_owned()));
+            let trx = linked[0].transcripts().get("ENST01.3_PAR_Y").expect("a transcript");
+            assert_eq!(trx.attributes().get(PAR_X_ID_ATTR), Some(&"ENST01.3".to_owned()));
+        } else {
+            assert!(linked.is_empty());
+        }
+    }
+}
+
+#[test]
+fn par_y_transcripts() {
+    let mut reader = RefFlatReader::from_reader(PAR_GENES.as_bytes());
+    let ids = ParYTranscripts::new(reader.transcripts_stream(), ParYPolicy::Filter)
+        .map(|res| res.expect("a transcript").id().unwrap().to_owned())
+        .collect::<Vec<_>>();
+    assert_eq!(ids, vec!["ENST01.3", "ENST02.1"]);
+}
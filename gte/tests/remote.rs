@@ -0,0 +1,86 @@
+#![cfg(feature = "remote")]
+extern crate gte;
+
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+
+use gte::{GffIndexedReader, GffReader, GffType};
+
+
+/// Serves files of the test data directory over HTTP, honoring open-ended range requests.
+fn serve_data() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("a listener");
+    let addr = listener.local_addr().expect("an address");
+    let _ = thread::spawn(move || {
+        for stream in listener.incoming() {
+            if let Ok(stream) = stream {
+                respond(stream);
+            }
+        }
+    });
+    format!("http://{}", addr)
+}
+
+fn respond(mut stream: TcpStream) {
+    let (name, offset) = {
+        let mut reader = BufReader::new(&mut stream);
+        let mut line = String::new();
+        let _ = reader.read_line(&mut line).expect("a request line");
+        let name = line.split(' ').nth(1).unwrap_or("/").trim_left_matches('/').to_owned();
+        let mut offset = None;
+        loop {
+            line.clear();
+            let _ = reader.read_line(&mut line).expect("a header line");
+            if line.trim().is_empty() {
+                break;
+            }
+            if line.to_lowercase().starts_with("range: bytes=") {
+                offset = line[13..].trim().trim_right_matches('-').parse::<usize>().ok();
+            }
+        }
+        (name, offset)
+    };
+    let path = format!("{}/tests/data/{}", env!("CARGO_MANIFEST_DIR"), name);
+    let mut contents = Vec::new();
+    let status = match fs::File::open(path) {
+        Ok(mut file) => {
+            let _ = file.read_to_end(&mut contents).expect("file contents");
+            match offset {
+                Some(offset) => {
+                    contents = contents.split_off(offset);
+                    "206 Partial Content"
+                },
+                None => "200 OK",
+            }
+        },
+        Err(_) => "404 Not Found",
+    };
+    let header = format!("HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                         status, contents.len());
+    let _ = stream.write_all(header.as_bytes()).and_then(|_| stream.write_all(&contents));
+}
+
+#[test]
+fn gtf_indexed_reader_from_url() {
+    let base = serve_data();
+    let url = format!("{}/indexed.gtf.gz", base);
+    let mut reader = GffIndexedReader::from_url(&url, GffType::GTF2).expect("an indexed reader");
+    let mut ids = reader.query("chr2", 0..600).expect("transcripts").iter()
+        .filter_map(|trx| trx.id().map(|id| id.to_owned()))
+        .collect::<Vec<_>>();
+    ids.sort();
+    assert_eq!(ids, vec!["tC1"]);
+
+    let missing = format!("{}/missing.gtf.gz", base);
+    assert!(GffIndexedReader::from_url(&missing, GffType::GTF2).is_err());
+}
+
+#[test]
+fn gtf_reader_from_url() {
+    let url = format!("{}/single_gene.gtf", serve_data());
+    let mut reader = GffReader::from_url(&url, GffType::GTF2).expect("a GFF reader");
+    let num_transcripts = reader.transcripts().expect("transcripts").count();
+    assert_eq!(num_transcripts, 2);
+}
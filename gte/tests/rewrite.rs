@@ -0,0 +1,89 @@
+extern crate gte;
+extern crate linked_hash_map;
+
+use linked_hash_map::LinkedHashMap;
+
+use gte::{GBuilder, Gene, GeneStreamExt, ModelError, RewriteError, RewrittenTranscripts,
+          SeqRewrite, Strand, TBuilder};
+
+
+fn make_gene(seq_name: &str) -> Gene {
+    let mut coords = LinkedHashMap::new();
+    coords.insert("trx01".to_owned(),
+                  ((100, 500), vec![(100, 200), (300, 500)], Some((150, 400))));
+    GBuilder::new(seq_name, 100, 500)
+        .strand(Strand::Forward)
+        .id("gene-1")
+        .transcript_coords(coords)
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn seq_rewrite_gene() {
+    let mut rewrite = SeqRewrite::new();
+    let _ = rewrite.rename("ctg1", "chr1").offset("ctg1", 1000);
+    let gx = rewrite.rewrite_gene(make_gene("ctg1")).expect("a rewritten gene");
+    assert_eq!((gx.seq_name(), gx.start(), gx.end()), ("chr1", 1100, 1500));
+
+    let trx = gx.transcripts().get("trx01").unwrap();
+    assert_eq!((trx.seq_name(), trx.start(), trx.end()), ("chr1", 1100, 1500));
+    assert_eq!(trx.coding_coord(true), Some((1150, 1403)));
+    assert!(trx.exons().iter().all(|exn| exn.seq_name() == "chr1"));
+    assert_eq!(trx.exons().iter().map(|exn| (exn.start(), exn.end())).collect::<Vec<_>>(),
+               vec![(1100, 1200), (1300, 1500)]);
+
+    let gx = rewrite.rewrite_gene(make_gene("ctg2")).expect("an unchanged gene");
+    assert_eq!((gx.seq_name(), gx.start(), gx.end()), ("ctg2", 100, 500));
+}
+
+#[test]
+fn seq_rewrite_negative_coord() {
+    let mut rewrite = SeqRewrite::new();
+    let _ = rewrite.offset("ctg1", -200);
+    match rewrite.rewrite_gene(make_gene("ctg1")) {
+        Err(gte::Error::Model(ModelError::NegativeShiftedCoord(seq_name, offset))) => {
+            assert_eq!((seq_name.as_str(), offset), ("ctg1", -200))
+        },
+        otherwise => panic!("unexpected result: {:?}", otherwise),
+    }
+}
+
+#[test]
+fn seq_rewrite_from_table() {
+    let rewrite = SeqRewrite::from_reader("# contigs\nctg1\tchr1\t1000\nctg2\tchr2\n".as_bytes())
+        .expect("a rewrite");
+    let mut exp = SeqRewrite::new();
+    let _ = exp
+        .rename("ctg1", "chr1").offset("ctg1", 1000)
+        .rename("ctg2", "chr2").offset("ctg2", 0);
+    assert_eq!(rewrite, exp);
+
+    match SeqRewrite::from_reader("ctg1\tchr1\tfar\n".as_bytes()) {
+        Err(gte::Error::Rewrite(RewriteError::InvalidTableLine(line))) => {
+            assert_eq!(line, "ctg1\tchr1\tfar")
+        },
+        otherwise => panic!("unexpected result: {:?}", otherwise),
+    }
+}
+
+#[test]
+fn seq_rewrite_streams() {
+    let mut rewrite = SeqRewrite::new();
+    let _ = rewrite.rename("ctg1", "chr1").offset("ctg1", 1000);
+
+    let genes = vec![Ok(make_gene("ctg1")), Ok(make_gene("ctg2"))];
+    let coords = genes.into_iter().rewrite_seqs(rewrite.clone())
+        .map(|gx| gx.map(|gx| (gx.seq_name().to_owned(), gx.start())))
+        .collect::<gte::Result<Vec<_>>>()
+        .expect("rewritten genes");
+    assert_eq!(coords, vec![("chr1".to_owned(), 1100), ("ctg2".to_owned(), 100)]);
+
+    let trx = TBuilder::new("ctg1", 10, 20)
+        .strand(Strand::Reverse)
+        .coords(vec![(10, 20)], None)
+        .build();
+    let rewritten = RewrittenTranscripts::new(vec![trx], rewrite).next()
+        .expect("a transcript result").expect("a transcript");
+    assert_eq!((rewritten.seq_name(), rewritten.start(), rewritten.end()), ("chr1", 1010, 1020));
+}
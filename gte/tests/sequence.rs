@@ -0,0 +1,127 @@
+extern crate gte;
+
+mod common;
+
+use gte::{reverse_complement, CodonTable, Strand, TBuilder};
+use Strand::*;
+use common::{make_source, make_trx_fwd, make_trx_rev};
+
+
+#[test]
+fn reverse_complement_basic() {
+    assert_eq!(reverse_complement(b"ACGTNacgtn"), b"nacgtNACGT".to_vec());
+}
+
+#[test]
+fn transcript_spliced_sequence_fwd() {
+    let mut source = make_source();
+    let seq = make_trx_fwd().spliced_sequence(&mut source).expect("a sequence");
+    assert_eq!(seq, b"CCATGGCCAAATTTTAACCC".to_vec());
+}
+
+#[test]
+fn transcript_spliced_sequence_rev() {
+    let mut source = make_source();
+    let seq = make_trx_rev().spliced_sequence(&mut source).expect("a sequence");
+    assert_eq!(seq, b"CCATGGCCAAATTTTAACCC".to_vec());
+}
+
+#[test]
+fn transcript_cds_sequence_fwd() {
+    let mut source = make_source();
+    let trx = make_trx_fwd();
+    assert_eq!(trx.cds_sequence(&mut source, false).expect("a sequence"),
+               b"ATGGCCAAATTT".to_vec());
+    assert_eq!(trx.cds_sequence(&mut source, true).expect("a sequence"),
+               b"ATGGCCAAATTTTAA".to_vec());
+}
+
+#[test]
+fn transcript_cds_sequence_rev() {
+    let mut source = make_source();
+    let trx = make_trx_rev();
+    assert_eq!(trx.cds_sequence(&mut source, false).expect("a sequence"),
+               b"ATGGCCAAATTT".to_vec());
+    assert_eq!(trx.cds_sequence(&mut source, true).expect("a sequence"),
+               b"ATGGCCAAATTTTAA".to_vec());
+}
+
+#[test]
+fn transcript_cds_sequence_no_cds() {
+    let mut source = make_source();
+    let trx = TBuilder::new("chrF", 2, 26)
+        .strand(Forward)
+        .coords(vec![(2, 10), (14, 26)], None)
+        .build().unwrap();
+    assert!(trx.cds_sequence(&mut source, true).is_err());
+}
+
+#[test]
+fn transcript_spliced_sequence_missing_seq() {
+    let mut source = make_source();
+    let trx = TBuilder::new("chrX", 2, 26)
+        .strand(Forward)
+        .coords(vec![(2, 10), (14, 26)], None)
+        .build().unwrap();
+    assert!(trx.spliced_sequence(&mut source).is_err());
+}
+
+#[test]
+fn codon_table_translate_codon() {
+    let standard = CodonTable::standard();
+    assert_eq!(standard.translate_codon(b"ATG"), b'M');
+    assert_eq!(standard.translate_codon(b"tga"), b'*');
+    assert_eq!(standard.translate_codon(b"AGA"), b'R');
+    assert_eq!(standard.translate_codon(b"ANA"), b'X');
+    assert!(!standard.is_start(b"ATA"));
+
+    let mito = CodonTable::from_ncbi_id(2).expect("a codon table");
+    assert_eq!(mito, CodonTable::vertebrate_mitochondrial());
+    assert_eq!(mito.translate_codon(b"TGA"), b'W');
+    assert_eq!(mito.translate_codon(b"AGA"), b'*');
+    assert!(mito.is_start(b"ATA"));
+
+    assert!(CodonTable::from_ncbi_id(99).is_none());
+}
+
+#[test]
+fn transcript_translate_fwd() {
+    let mut source = make_source();
+    let protein = make_trx_fwd().translate(&mut source, &CodonTable::standard())
+        .expect("a protein sequence");
+    assert_eq!(protein, b"MAKF".to_vec());
+}
+
+#[test]
+fn transcript_translate_rev() {
+    let mut source = make_source();
+    let protein = make_trx_rev().translate(&mut source, &CodonTable::standard())
+        .expect("a protein sequence");
+    assert_eq!(protein, b"MAKF".to_vec());
+}
+
+#[test]
+fn transcript_translate_internal_stop() {
+    let mut source = make_source();
+    let trx = TBuilder::new("chrF", 2, 26)
+        .strand(Forward)
+        .coords(vec![(2, 10), (14, 26)], Some((4, 23)))
+        .build().unwrap();
+    let err = trx.translate(&mut source, &CodonTable::standard())
+        .expect_err("an internal stop codon error");
+    assert!(err.to_string().contains("codon number: 5"));
+}
+
+#[test]
+fn transcript_translate_stop_readthrough() {
+    let mut source = make_source();
+    let trx = TBuilder::new("chrF", 2, 26)
+        .strand(Forward)
+        .attribute("tag", "seleno")
+        .coords(vec![(2, 10), (14, 26)], Some((4, 23)))
+        .build().unwrap();
+    assert!(trx.stop_readthrough());
+    let protein = trx.translate(&mut source, &CodonTable::standard())
+        .expect("a protein sequence");
+    assert_eq!(protein, b"MAKFX".to_vec());
+}
@@ -0,0 +1,82 @@
+#![cfg(feature = "serde_impl")]
+extern crate gte;
+extern crate linked_hash_map;
+extern crate serde_json;
+
+use linked_hash_map::LinkedHashMap;
+
+use gte::{ExonFeatureKind, GBuilder, Gene, RefFlatReader, RefFlatRecord, Strand, TBuilder,
+          Transcript};
+
+
+static SINGLE_ROW_NO_CDS: &'static str = include_str!("data/single_row_no_cds.refFlat");
+
+fn transcript() -> Transcript {
+    TBuilder::new("chrT", 100, 1000)
+        .id("trx01")
+        .gene_id("gene01")
+        .strand(Strand::Reverse)
+        .coords(vec![(100, 300), (400, 1000)], Some((200, 500)))
+        .attribute("name", "TRX1")
+        .build()
+        .expect("a transcript")
+}
+
+#[test]
+fn serde_transcript_round_trip() {
+    let trx = transcript();
+    let json = serde_json::to_string(&trx).expect("a JSON string");
+    let rt: Transcript = serde_json::from_str(&json).expect("a transcript");
+
+    assert_eq!(rt.seq_name(), "chrT");
+    assert_eq!((rt.start(), rt.end()), (100, 1000));
+    assert_eq!(rt.strand(), &Strand::Reverse);
+    assert_eq!(rt.id(), Some("trx01"));
+    assert_eq!(rt.gene_id(), Some("gene01"));
+    assert_eq!(rt.attributes().get("name"), Some(&"TRX1".to_owned()));
+    assert_eq!(rt.exons().len(), trx.exons().len());
+    for (rexn, exn) in rt.exons().iter().zip(trx.exons().iter()) {
+        assert_eq!((rexn.start(), rexn.end()), (exn.start(), exn.end()));
+        assert_eq!(rexn.features(), exn.features());
+    }
+    let has_cds = rt.exons().iter()
+        .flat_map(|exn| exn.features().iter())
+        .any(|fx| if let ExonFeatureKind::CDS { .. } = *fx.kind() { true } else { false });
+    assert!(has_cds);
+}
+
+#[test]
+fn serde_gene_round_trip() {
+    let mut transcripts = LinkedHashMap::new();
+    let _ = transcripts.insert("trx01".to_owned(), transcript());
+    let gene = GBuilder::new("chrT", 100, 1000)
+        .id("gene01")
+        .strand(Strand::Reverse)
+        .transcripts(transcripts)
+        .build()
+        .expect("a gene");
+    let json = serde_json::to_string(&gene).expect("a JSON string");
+    let rt: Gene = serde_json::from_str(&json).expect("a gene");
+
+    assert_eq!(rt.id(), Some("gene01"));
+    assert_eq!(rt.strand(), &Strand::Reverse);
+    assert_eq!(rt.transcripts().keys().collect::<Vec<&String>>(), vec!["trx01"]);
+}
+
+#[test]
+fn serde_transcript_invalid_strand() {
+    let json = serde_json::to_string(&transcript()).expect("a JSON string")
+        .replace(r#""strand":"-""#, r#""strand":"x""#);
+    assert!(serde_json::from_str::<Transcript>(&json).is_err());
+}
+
+#[test]
+fn serde_refflat_record_round_trip() {
+    let mut reader = RefFlatReader::from_reader(SINGLE_ROW_NO_CDS.as_bytes());
+    let rec = reader.records_stream().next()
+        .expect("a record result").expect("a record");
+    let json = serde_json::to_string(&rec).expect("a JSON string");
+    let rt: RefFlatRecord = serde_json::from_str(&json).expect("a record");
+
+    assert_eq!(rt, rec);
+}
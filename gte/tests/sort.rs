@@ -0,0 +1,74 @@
+extern crate gte;
+
+use std::cmp::Ordering;
+
+use gte::{sort_genes, sort_transcripts, sorted_genes, GBuilder, Gene, SeqDict, SeqOrder, Strand,
+          TBuilder};
+
+
+fn seq_names(order: &SeqOrder, names: &[&str]) -> Vec<String> {
+    let mut names = names.iter().map(|v| v.to_string()).collect::<Vec<String>>();
+    names.sort_by(|a, b| order.compare(a, b));
+    names
+}
+
+fn gene(seq_name: &str, start: u64, end: u64) -> Gene {
+    GBuilder::new(seq_name, start, end).strand(Strand::Forward).build().expect("a gene")
+}
+
+#[test]
+fn seq_order_lexicographic() {
+    assert_eq!(seq_names(&SeqOrder::Lexicographic, &["chr2", "chr10", "chr1"]),
+               vec!["chr1", "chr10", "chr2"]);
+}
+
+#[test]
+fn seq_order_natural() {
+    assert_eq!(seq_names(&SeqOrder::Natural, &["chr2", "chr10", "chrX", "chr1", "chr1_alt"]),
+               vec!["chr1", "chr1_alt", "chr2", "chr10", "chrX"]);
+    assert_eq!(SeqOrder::Natural.compare("scaffold_007", "scaffold_7"), Ordering::Less);
+}
+
+#[test]
+fn seq_order_karyotype() {
+    let names = ["chrUn_gl000220", "chrM", "chrY", "chr10", "chrX", "chr2", "chr1"];
+    assert_eq!(seq_names(&SeqOrder::Karyotype, &names),
+               vec!["chr1", "chr2", "chr10", "chrX", "chrY", "chrM", "chrUn_gl000220"]);
+    assert_eq!(seq_names(&SeqOrder::Karyotype, &["MT", "X", "22"]), vec!["22", "X", "MT"]);
+}
+
+#[test]
+fn seq_order_from_seq_dict() {
+    let dict = SeqDict::from_reader("chrM\t16569\nchr2\t100\nchr1\t200\n".as_bytes())
+        .expect("a dictionary");
+    let order = SeqOrder::from_seq_dict(&dict);
+    assert_eq!(seq_names(&order, &["chr10", "chr1", "chr3", "chr2", "chrM"]),
+               vec!["chrM", "chr2", "chr1", "chr3", "chr10"]);
+}
+
+#[test]
+fn sort_genes_by_coord() {
+    let mut genes = vec![gene("chr10", 5, 10), gene("chr2", 50, 60), gene("chr2", 10, 30),
+                         gene("chr2", 10, 20)];
+    sort_genes(&mut genes, &SeqOrder::Natural);
+    assert_eq!(genes.iter().map(|gx| (gx.seq_name(), gx.start(), gx.end())).collect::<Vec<_>>(),
+               vec![("chr2", 10, 20), ("chr2", 10, 30), ("chr2", 50, 60), ("chr10", 5, 10)]);
+}
+
+#[test]
+fn sort_transcripts_by_coord() {
+    let mut trxs = ["chrY", "chr3", "chrX"].iter()
+        .map(|sn| TBuilder::new(*sn, 10, 20).strand(Strand::Forward)
+                 .coords(vec![(10, 20)], None).build().unwrap())
+        .collect::<Vec<_>>();
+    sort_transcripts(&mut trxs, &SeqOrder::Karyotype);
+    assert_eq!(trxs.iter().map(|trx| trx.seq_name()).collect::<Vec<_>>(),
+               vec!["chr3", "chrX", "chrY"]);
+}
+
+#[test]
+fn sorted_genes_stream() {
+    let genes = vec![Ok(gene("chrX", 1, 2)), Ok(gene("chr9", 1, 2))];
+    let sorted = sorted_genes(genes, &SeqOrder::Karyotype).expect("sorted genes");
+    assert_eq!(sorted.iter().map(|gx| gx.seq_name()).collect::<Vec<_>>(), vec!["chr9", "chrX"]);
+}
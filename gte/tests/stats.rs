@@ -0,0 +1,73 @@
+extern crate gte;
+
+use gte::{AnnotationStats, GeneMetrics, LengthStats, MetricsWriter, RefFlatReader,
+          TranscriptMetrics};
+
+
+static MULT_ROWS_MULT_GENES_WITH_CDS: &'static str =
+    include_str!("data/mult_rows_mult_genes_with_cds.refFlat");
+
+#[test]
+fn annotation_stats_from_genes() {
+    let mut reader = RefFlatReader::from_reader(MULT_ROWS_MULT_GENES_WITH_CDS.as_bytes());
+    let stats = AnnotationStats::from_genes(reader.genes_stream()).expect("stats");
+    assert_eq!(stats.num_genes(), 2);
+    assert_eq!(stats.num_transcripts(), 5);
+    assert_eq!(stats.num_coding_transcripts(), 5);
+    assert_eq!(stats.num_exons(), 22);
+    assert_eq!(stats.gene_biotypes().get("."), Some(&2));
+    assert_eq!(stats.exon_lengths().count(), 22);
+    assert_eq!(stats.exon_lengths().min(), Some(32));
+    assert_eq!(stats.exon_lengths().max(), Some(5621));
+    assert_eq!(stats.intron_lengths().count(), 17);
+    assert_eq!(stats.cds_lengths().count(), 5);
+
+    let seqs = stats.seqs().iter()
+        .map(|(name, seq)| (name.as_str(), seq.genes, seq.transcripts, seq.exons))
+        .collect::<Vec<_>>();
+    assert_eq!(seqs, vec![("chr1", 2, 5, 22)]);
+}
+
+#[test]
+fn length_stats_quantiles() {
+    let mut lengths = LengthStats::new();
+    assert_eq!(lengths.median(), None);
+    for length in vec![40, 10, 30, 20] {
+        lengths.push(length);
+    }
+    assert_eq!(lengths.total(), 100);
+    assert_eq!(lengths.mean(), Some(25.0));
+    assert_eq!(lengths.median(), Some(20));
+    assert_eq!(lengths.quantile(0.0), Some(10));
+    assert_eq!(lengths.quantile(0.9), Some(40));
+}
+
+#[test]
+fn transcript_metrics_from_transcript() {
+    let mut reader = RefFlatReader::from_reader(MULT_ROWS_MULT_GENES_WITH_CDS.as_bytes());
+    let trx = reader.transcripts_stream().next().expect("a result").expect("a transcript");
+    let metrics = TranscriptMetrics::from_transcript(&trx);
+    assert_eq!(metrics.transcript_id, Some("NM_001297605".to_owned()));
+    assert_eq!(metrics.num_exons, 7);
+    assert_eq!(metrics.exonic_length, 3358);
+    assert_eq!(metrics.cds_length, Some(555));
+    assert_eq!((metrics.utr5_length, metrics.utr3_length), (Some(300), Some(2503)));
+    assert_eq!(metrics.intron_lengths, vec![992, 508, 1354, 645, 2150, 251]);
+
+    let mut buf = Vec::new();
+    MetricsWriter::from_writer(&mut buf).write_transcript_metrics(&metrics).expect("a row");
+    let row = String::from_utf8(buf).expect("a row string");
+    assert_eq!(row, "NM_001297605\tTNFRSF14\tchr1\t2556364\t2565622\t+\t7\t3358\t555\t300\t\
+                     2503\t992,508,1354,645,2150,251\n");
+}
+
+#[test]
+fn gene_metrics_from_gene() {
+    let mut reader = RefFlatReader::from_reader(MULT_ROWS_MULT_GENES_WITH_CDS.as_bytes());
+    let genes = reader.genes_stream().collect::<Result<Vec<_>, _>>().expect("genes");
+    let metrics = GeneMetrics::from_gene(&genes[1]);
+    assert_eq!(metrics.gene_id, Some("SMIM12".to_owned()));
+    assert_eq!((metrics.num_transcripts, metrics.num_coding_transcripts), (3, 3));
+    assert_eq!(metrics.transcripts[0].strand, '-');
+    assert_eq!(metrics.transcripts[0].utr3_length, Some(34855698 - 34850361));
+}
@@ -0,0 +1,75 @@
+#![cfg(feature = "sqlite")]
+extern crate gte;
+
+use std::env;
+use std::fs;
+
+use gte::{GeneStore, RefFlatReader, StoreError};
+
+
+static MULT_ROWS_MULT_GENES_WITH_CDS: &'static str =
+    include_str!("data/mult_rows_mult_genes_with_cds.refFlat");
+
+fn make_store() -> GeneStore {
+    let mut store = GeneStore::in_memory().expect("a store");
+    let mut reader = RefFlatReader::from_reader(MULT_ROWS_MULT_GENES_WITH_CDS.as_bytes());
+    let num_genes = store.insert_genes(reader.genes_stream()).expect("written genes");
+    assert_eq!(store.num_genes().unwrap(), num_genes);
+    store
+}
+
+#[test]
+fn store_gene_queries() {
+    let store = make_store();
+    let gx = store.gene("SMIM12").unwrap().expect("a gene");
+    assert_eq!(gx.transcripts().len(), 3);
+    assert!(store.gene("NONEXISTENT").unwrap().is_none());
+    assert_eq!(store.genes_by_name("TNFRSF14").unwrap().len(), 1);
+
+    let gxs = store.genes_in("chr1", 2556000..34850400).unwrap();
+    assert_eq!(gxs.iter().map(|gx| gx.id()).collect::<Vec<_>>(),
+               vec![Some("TNFRSF14"), Some("SMIM12")]);
+    assert!(store.genes_in("chr1", 0..100).unwrap().is_empty());
+}
+
+#[test]
+fn store_transcript_queries() {
+    let store = make_store();
+    let trx = store.transcript("NM_138428").unwrap().expect("a transcript");
+    assert_eq!(trx.gene_id(), Some("SMIM12"));
+    assert_eq!((trx.start(), trx.end()), (34850361, 34859816));
+
+    let trxs = store.transcripts_in("chr1", 34859700..34859710).unwrap();
+    assert_eq!(trxs.iter().map(|trx| trx.id()).collect::<Vec<_>>(),
+               vec![Some("NM_001164825"), Some("NM_138428")]);
+    let exons = store.exons_in("chr1", 34859700..34859710).unwrap();
+    assert_eq!(exons.len(), 2);
+}
+
+#[test]
+fn store_file_round_trip() {
+    let path = env::temp_dir().join("gte_store_file_round_trip.db");
+    let _ = fs::remove_file(&path);
+    {
+        let mut store = GeneStore::create(&path).expect("a store");
+        let mut reader = RefFlatReader::from_reader(MULT_ROWS_MULT_GENES_WITH_CDS.as_bytes());
+        let _ = store.insert_genes(reader.genes_stream()).expect("written genes");
+    }
+    assert!(GeneStore::create(&path).is_err());
+    let num_genes = GeneStore::open(&path).and_then(|store| store.num_genes());
+    let _ = fs::remove_file(&path);
+    assert_eq!(num_genes.unwrap(), 2);
+}
+
+#[test]
+fn store_invalid() {
+    let path = env::temp_dir().join("gte_store_invalid.db");
+    let _ = fs::remove_file(&path);
+    let res = GeneStore::open(&path);
+    let _ = fs::remove_file(&path);
+    match res {
+        Err(gte::Error::Store(StoreError::InvalidStore)) => {},
+        Err(err) => panic!("unexpected error: {:?}", err),
+        Ok(_) => panic!("unexpected store"),
+    }
+}
@@ -2,9 +2,10 @@ extern crate bio;
 extern crate gte;
 extern crate multimap;
 
+use bio::utils::Interval;
 use multimap::MultiMap;
 
-use gte::{ExonFeatureKind, Strand, TBuilder, Transcript};
+use gte::{ExonFeatureKind, Strand, TBuilder, Transcript, TranscriptFeatureKind};
 use ExonFeatureKind::*;
 use Strand::*;
 
@@ -1403,3 +1404,454 @@ fn tbuilder_coords_unk_coding_incl_stop() {
         .build();
     assert!(btrx.is_err());
 }
+
+fn intron_coords(transcript: &Transcript) -> Vec<(u64, u64)> {
+    transcript.introns()
+        .map(|intron| (intron.start(), intron.end()))
+        .collect()
+}
+
+#[test]
+fn transcript_introns_fwd() {
+    let (trx, _) = trx_fxs(100, 1000, Forward, vec![(100, 300), (400, 500), (700, 1000)], None);
+    assert_eq!(intron_coords(&trx), vec![(300, 400), (500, 700)]);
+    assert!(trx.introns().all(|intron| intron.kind() == &TranscriptFeatureKind::Intron));
+}
+
+#[test]
+fn transcript_introns_rev() {
+    let (trx, _) = trx_fxs(100, 1000, Reverse, vec![(100, 300), (400, 500), (700, 1000)], None);
+    assert_eq!(intron_coords(&trx), vec![(500, 700), (300, 400)]);
+}
+
+#[test]
+fn transcript_introns_adjacent_exons() {
+    let (trx, _) = trx_fxs(100, 1000, Forward, vec![(100, 300), (300, 500), (700, 1000)], None);
+    assert_eq!(intron_coords(&trx), vec![(500, 700)]);
+}
+
+#[test]
+fn transcript_introns_single_exon() {
+    let (trx, _) = trx_fxs(100, 1000, Reverse, vec![(100, 1000)], None);
+    assert!(intron_coords(&trx).is_empty());
+}
+
+#[test]
+fn transcript_genomic_to_transcript_rev() {
+    let (trx, _) = trx_fxs(100, 1000, Reverse, vec![(100, 300), (400, 500), (700, 1000)],
+                           Some((200, 450)));
+    assert_eq!(trx.genomic_to_transcript(999), Some(0));
+    assert_eq!(trx.genomic_to_transcript(700), Some(299));
+    assert_eq!(trx.genomic_to_transcript(499), Some(300));
+    assert_eq!(trx.genomic_to_transcript(100), Some(599));
+    assert_eq!(trx.genomic_to_transcript(600), None);
+    assert_eq!(trx.genomic_to_transcript(1000), None);
+}
+
+#[test]
+fn transcript_genomic_to_cds_fwd() {
+    let (trx, _) = trx_fxs(100, 1000, Forward, vec![(100, 300), (400, 500), (700, 1000)],
+                           Some((150, 210)));
+    assert_eq!(trx.genomic_to_cds(149, false), None);
+    assert_eq!(trx.genomic_to_cds(150, false), Some(0));
+    assert_eq!(trx.genomic_to_cds(209, false), Some(59));
+    assert_eq!(trx.genomic_to_cds(210, false), None);
+    assert_eq!(trx.genomic_to_cds(212, true), Some(62));
+    assert_eq!(trx.genomic_to_protein(150, false), Some(0));
+    assert_eq!(trx.genomic_to_protein(153, false), Some(1));
+    assert_eq!(trx.genomic_to_protein(209, false), Some(19));
+    assert_eq!(trx.genomic_to_protein(210, false), None);
+    assert_eq!(trx.genomic_to_protein(210, true), Some(20));
+}
+
+#[test]
+fn transcript_genomic_to_cds_rev() {
+    let (trx, _) = trx_fxs(100, 1000, Reverse, vec![(100, 300), (400, 500), (700, 1000)],
+                           Some((200, 450)));
+    assert_eq!(trx.genomic_to_cds(449, false), Some(0));
+    assert_eq!(trx.genomic_to_cds(400, false), Some(49));
+    assert_eq!(trx.genomic_to_cds(350, false), None);
+    assert_eq!(trx.genomic_to_cds(299, false), Some(50));
+    assert_eq!(trx.genomic_to_cds(200, false), Some(149));
+    assert_eq!(trx.genomic_to_cds(199, false), None);
+    assert_eq!(trx.genomic_to_cds(199, true), Some(150));
+    assert_eq!(trx.genomic_to_cds(197, true), Some(152));
+    assert_eq!(trx.genomic_to_protein(449, false), Some(0));
+    assert_eq!(trx.genomic_to_protein(446, false), Some(1));
+    assert_eq!(trx.genomic_to_protein(299, false), Some(16));
+    assert_eq!(trx.genomic_to_protein(200, false), Some(49));
+}
+
+#[test]
+fn transcript_genomic_to_cds_no_coding() {
+    let (trx, _) = trx_fxs(100, 1000, Forward, vec![(100, 300), (400, 500), (700, 1000)], None);
+    assert_eq!(trx.genomic_to_cds(150, true), None);
+    assert_eq!(trx.genomic_to_protein(150, true), None);
+}
+
+#[test]
+fn transcript_promoter_fwd() {
+    let (trx, _) = trx_fxs(100, 1000, Forward, vec![(100, 300), (400, 500), (700, 1000)], None);
+    let promoter = trx.promoter(50, 20);
+    assert_eq!((promoter.start(), promoter.end()), (50, 120));
+    assert_eq!(promoter.kind(), &TranscriptFeatureKind::Promoter);
+}
+
+#[test]
+fn transcript_promoter_rev() {
+    let (trx, _) = trx_fxs(100, 1000, Reverse, vec![(100, 300), (400, 500), (700, 1000)], None);
+    let promoter = trx.promoter(50, 20);
+    assert_eq!((promoter.start(), promoter.end()), (980, 1050));
+}
+
+#[test]
+fn transcript_promoter_clipped() {
+    let (trx, _) = trx_fxs(100, 1000, Forward, vec![(100, 300), (400, 500), (700, 1000)], None);
+    let promoter = trx.promoter(500, 0);
+    assert_eq!((promoter.start(), promoter.end()), (0, 100));
+}
+
+fn clear_coding_frames(transcript: &mut Transcript) {
+    for exon in transcript.exons_mut() {
+        for fx in exon.features_mut() {
+            let kind = match *fx.kind() {
+                CDS { .. } => CDS { frame: None },
+                StartCodon { .. } => StartCodon { frame: None },
+                StopCodon { .. } => StopCodon { frame: None },
+                ref other => other.clone(),
+            };
+            fx.set_kind(kind);
+        }
+    }
+}
+
+#[test]
+fn transcript_fill_coding_frames_rev() {
+    let (mut trx, fxs) = trx_fxs(100, 1000, Reverse, vec![(100, 300), (400, 500), (700, 1000)],
+                                 Some((250, 750)));
+    clear_coding_frames(&mut trx);
+    assert_eq!(trx.exons()[1].features()[0].kind(), &CDS { frame: None });
+    trx.fill_coding_frames();
+    assert_eq!(exon_fxs_coords(&trx), fxs);
+}
+
+#[test]
+fn transcript_fill_coding_frames_keep_known() {
+    let (mut trx, _) = trx_fxs(100, 1000, Forward, vec![(100, 300), (400, 500), (700, 1000)],
+                               Some((200, 460)));
+    clear_coding_frames(&mut trx);
+    trx.exons_mut()[0].features_mut()[2].set_kind(CDS { frame: Some(2) });
+    trx.fill_coding_frames();
+    assert_eq!(trx.exons()[0].features()[1].kind(), &StartCodon { frame: Some(0) });
+    assert_eq!(trx.exons()[0].features()[2].kind().frame(), Some(2));
+    assert_eq!(trx.exons()[1].features()[0].kind(), &CDS { frame: Some(1) });
+    assert_eq!(trx.exons()[1].features()[1].kind(), &StopCodon { frame: Some(0) });
+}
+
+#[test]
+fn transcript_fill_coding_frames_unknown_strand() {
+    let (mut trx, _) = trx_fxs(100, 1000, Unknown, vec![(100, 300), (400, 500), (700, 1000)],
+                               Some((200, 460)));
+    clear_coding_frames(&mut trx);
+    trx.fill_coding_frames();
+    assert!(trx.exons().iter()
+        .flat_map(|exon| exon.features().iter())
+        .all(|fx| fx.kind().frame().is_none()));
+}
+
+#[test]
+fn transcript_exons_stranded_fwd() {
+    let (trx, _) = trx_fxs(100, 1000, Forward, vec![(100, 300), (400, 500), (700, 1000)], None);
+    let starts = trx.exons_stranded().map(|exon| exon.start()).collect::<Vec<_>>();
+    assert_eq!(starts, vec![100, 400, 700]);
+    assert_eq!(trx.first_exon().map(|exon| exon.start()), Some(100));
+    assert_eq!(trx.last_exon().map(|exon| exon.start()), Some(700));
+    assert_eq!(trx.exon_number(2).map(|exon| exon.start()), Some(400));
+}
+
+#[test]
+fn transcript_exons_stranded_rev() {
+    let (trx, _) = trx_fxs(100, 1000, Reverse, vec![(100, 300), (400, 500), (700, 1000)], None);
+    let starts = trx.exons_stranded().map(|exon| exon.start()).collect::<Vec<_>>();
+    assert_eq!(starts, vec![700, 400, 100]);
+    assert_eq!(trx.exons_stranded().rev().next().map(|exon| exon.start()), Some(100));
+    assert_eq!(trx.exons_stranded().len(), 3);
+    assert_eq!(trx.first_exon().map(|exon| exon.start()), Some(700));
+    assert_eq!(trx.last_exon().map(|exon| exon.start()), Some(100));
+    assert_eq!(trx.exon_number(1).map(|exon| exon.start()), Some(700));
+    assert_eq!(trx.exon_number(3).map(|exon| exon.start()), Some(100));
+    assert!(trx.exon_number(0).is_none());
+    assert!(trx.exon_number(4).is_none());
+}
+
+#[test]
+fn transcript_exon_lookup() {
+    let (trx, _) = trx_fxs(100, 1000, Reverse, vec![(100, 300), (400, 500), (700, 1000)], None);
+    assert_eq!(trx.exon_at(100).map(|exon| exon.start()), Some(100));
+    assert_eq!(trx.exon_at(499).map(|exon| exon.start()), Some(400));
+    assert_eq!(trx.exon_at(999).map(|exon| exon.start()), Some(700));
+    assert!(trx.exon_at(300).is_none());
+    assert!(trx.exon_at(99).is_none());
+    assert!(trx.exon_at(1000).is_none());
+
+    let starts = |range| trx.exons_in(range).iter().map(|exon| exon.start()).collect::<Vec<_>>();
+    assert_eq!(starts(0..2000), vec![100, 400, 700]);
+    assert_eq!(starts(299..401), vec![100, 400]);
+    assert_eq!(starts(450..700), vec![400]);
+    assert_eq!(starts(500..700), Vec::<u64>::new());
+    assert_eq!(starts(1000..1200), Vec::<u64>::new());
+    assert_eq!(starts(450..450), Vec::<u64>::new());
+}
+
+fn utr_coords(intervals: Vec<Interval<u64>>) -> Vec<(u64, u64)> {
+    intervals.iter().map(|iv| (iv.start, iv.end)).collect()
+}
+
+#[test]
+fn transcript_utrs_fwd() {
+    let (trx, _) = trx_fxs(100, 1000, Forward, vec![(100, 300), (400, 500), (700, 1000)],
+                           Some((150, 210)));
+    assert_eq!(utr_coords(trx.utr5()), vec![(100, 150)]);
+    assert_eq!(utr_coords(trx.utr3()), vec![(213, 300), (400, 500), (700, 1000)]);
+    assert_eq!(trx.utr5_len(), 50);
+    assert_eq!(trx.utr3_len(), 487);
+}
+
+#[test]
+fn transcript_utrs_rev() {
+    let (trx, _) = trx_fxs(100, 1000, Reverse, vec![(100, 300), (400, 500), (700, 1000)],
+                           Some((150, 210)));
+    assert_eq!(utr_coords(trx.utr5()), vec![(700, 1000), (400, 500), (210, 300)]);
+    assert_eq!(utr_coords(trx.utr3()), vec![(100, 147)]);
+    assert_eq!(trx.utr5_len(), 490);
+    assert_eq!(trx.utr3_len(), 47);
+}
+
+#[test]
+fn transcript_utrs_unstranded_kind() {
+    let (mut trx, _) = trx_fxs(100, 1000, Reverse, vec![(100, 300), (400, 500), (700, 1000)],
+                               Some((150, 210)));
+    for exon in trx.exons_mut() {
+        for fx in exon.features_mut() {
+            let is_utr = match *fx.kind() { UTR5 | UTR3 => true, _ => false };
+            if is_utr {
+                fx.set_kind(UTR);
+            }
+        }
+    }
+    assert_eq!(utr_coords(trx.utr5()), vec![(700, 1000), (400, 500), (210, 300)]);
+    assert_eq!(utr_coords(trx.utr3()), vec![(100, 147)]);
+}
+
+#[test]
+fn transcript_utrs_unknown_strand() {
+    let (trx, _) = trx_fxs(100, 1000, Unknown, vec![(100, 300), (400, 500), (700, 1000)],
+                           Some((150, 210)));
+    assert!(trx.utr5().is_empty());
+    assert_eq!(trx.utr3_len(), 0);
+}
+
+#[test]
+fn transcript_clip_fwd() {
+    let (trx, fxs) = trx_fxs(100, 1000, Forward, vec![(100, 300), (400, 500), (700, 1000)],
+                             Some((200, 800)));
+    assert_eq!(fxs[1], vec![(400, 500, CDS { frame: Some(2) })]);
+
+    let clipped = trx.clip(450, 750).expect("a clipped transcript");
+    assert_eq!((clipped.start(), clipped.end()), (450, 750));
+    assert_eq!(exon_coords(&clipped), vec![(450, 500), (700, 750)]);
+    let cfxs = exon_fxs_coords(&clipped);
+    assert_eq!(cfxs[0], vec![(450, 500, CDS { frame: Some(0) })]);
+    assert_eq!(cfxs[1], vec![(700, 750, CDS { frame: fxs[2][0].2.frame() })]);
+
+    let clipped = trx.clip(201, 250).expect("a clipped transcript");
+    assert_eq!(exon_fxs_coords(&clipped), vec![vec![(201, 250, CDS { frame: Some(2) })]]);
+
+    assert!(trx.clip(300, 400).is_none());
+}
+
+#[test]
+fn transcript_clip_rev() {
+    let (trx, fxs) = trx_fxs(100, 1000, Reverse, vec![(100, 300), (400, 500), (700, 1000)],
+                             Some((200, 800)));
+    assert_eq!(fxs[1], vec![(400, 500, CDS { frame: Some(2) })]);
+
+    let clipped = trx.clip(0, 450).expect("a clipped transcript");
+    assert_eq!((clipped.start(), clipped.end()), (100, 450));
+    assert_eq!(exon_coords(&clipped), vec![(100, 300), (400, 450)]);
+    assert_eq!(exon_fxs_coords(&clipped)[0], fxs[0]);
+    assert_eq!(exon_fxs_coords(&clipped)[1], vec![(400, 450, CDS { frame: Some(0) })]);
+}
+
+#[test]
+fn transcript_cds_3p_incomplete_fwd() {
+    let trx = TBuilder::new("chrT", 100, 400)
+        .strand(Forward)
+        .coords(vec![(100, 200), (300, 400)], Some((150, 200)))
+        .cds_3p_incomplete(true)
+        .build()
+        .expect("a transcript");
+    assert!(!trx.cds_5p_incomplete());
+    assert!(trx.cds_3p_incomplete());
+    let fxs = exon_fxs_coords(&trx);
+    assert_eq!(fxs[0].last(), Some(&(150, 200, CDS { frame: Some(0) })));
+    assert_eq!(fxs[1], vec![(300, 400, UTR3)]);
+    assert_eq!(trx.coding_coord(true), Some((150, 200)));
+    assert_eq!(trx.coding_coord(false), Some((150, 200)));
+}
+
+#[test]
+fn transcript_cds_5p_incomplete_rev() {
+    let trx = TBuilder::new("chrT", 100, 400)
+        .strand(Reverse)
+        .coords(vec![(100, 200), (300, 400)], Some((150, 360)))
+        .cds_5p_incomplete(true)
+        .build()
+        .expect("a transcript");
+    let has_start_codon = exon_fxs_coords(&trx).into_iter()
+        .flat_map(|fxs| fxs.into_iter())
+        .any(|(_, _, kind)| match kind { StartCodon { .. } => true, _ => false });
+    assert!(!has_start_codon);
+    assert_eq!(trx.coding_coord(true), Some((147, 360)));
+    assert_eq!(trx.coding_coord(false), Some((150, 360)));
+}
+
+#[test]
+fn transcript_support_level_and_tags() {
+    let mut attribs = MultiMap::new();
+    attribs.insert("transcript_support_level".to_owned(),
+                   "1 (assigned to previous version 3)".to_owned());
+    attribs.insert("level".to_owned(), "2".to_owned());
+    attribs.insert("tag".to_owned(), "basic".to_owned());
+    attribs.insert("tag".to_owned(), "CCDS".to_owned());
+    attribs.insert("ccds_id".to_owned(), "CCDS2289.1".to_owned());
+    let trx = TBuilder::new("chrT", 100, 400)
+        .strand(Forward)
+        .attributes(attribs)
+        .coords(vec![(100, 400)], None)
+        .build()
+        .expect("a transcript");
+    assert_eq!(trx.support_level(), Some(1));
+    assert_eq!(trx.level(), Some(2));
+    assert_eq!(trx.tags(), &["basic", "CCDS"]);
+    assert!(trx.has_tag("basic"));
+    assert!(!trx.has_tag("MANE_Select"));
+    assert_eq!(trx.ccds_id(), Some("CCDS2289.1"));
+
+    let trx = TBuilder::new("chrT", 100, 400)
+        .strand(Forward)
+        .attribute("transcript_support_level", "NA")
+        .coords(vec![(100, 400)], None)
+        .build()
+        .expect("a transcript");
+    assert_eq!(trx.support_level(), None);
+    assert_eq!(trx.level(), None);
+    assert!(trx.tags().is_empty());
+    assert_eq!(trx.ccds_id(), None);
+}
+
+#[test]
+fn transcript_semantic_eq_and_hash() {
+    use std::collections::HashSet;
+
+    let trx1 = TBuilder::new("chrT", 100, 400)
+        .id("trx1")
+        .strand(Forward)
+        .coords(vec![(100, 200), (300, 400)], None)
+        .build()
+        .expect("a transcript");
+    let mut trx2 = TBuilder::new("chrT", 100, 400)
+        .id("trx1")
+        .strand(Forward)
+        .attribute("tag", "basic")
+        .coords(vec![(100, 400)], None)
+        .build()
+        .expect("a transcript");
+    assert_eq!(trx1, trx2);
+    assert_eq!(trx1.exons()[0].features(), trx1.clone().exons()[0].features());
+
+    let mut set = HashSet::new();
+    assert!(set.insert(trx1.clone()));
+    assert!(!set.insert(trx2.clone()));
+    trx2.set_strand(Reverse);
+    assert!(trx1 != trx2);
+    assert!(set.insert(trx2));
+    assert_eq!(set.len(), 2);
+
+    let introns = trx1.introns().collect::<HashSet<_>>();
+    assert_eq!(introns.len(), 1);
+}
+
+#[test]
+fn transcript_semantic_eq_and_hash_unknown_strand() {
+    use std::collections::HashSet;
+
+    let trx = TBuilder::new("chrT", 100, 400)
+        .id("trx1")
+        .strand(Unknown)
+        .coords(vec![(100, 400)], None)
+        .build()
+        .expect("a transcript");
+    assert!(trx.strand().is_unknown());
+    assert_eq!(trx, trx.clone());
+    assert_eq!(trx.exons()[0], trx.clone().exons()[0]);
+
+    let mut set = HashSet::new();
+    assert!(set.insert(trx.clone()));
+    assert!(!set.insert(trx.clone()));
+    let mut trx_fwd = trx.clone();
+    trx_fwd.set_strand(Forward);
+    assert!(trx != trx_fwd);
+    assert!(set.insert(trx_fwd));
+    assert_eq!(set.len(), 2);
+}
+
+#[test]
+fn transcript_builder_from_transcript() {
+    let trx = TBuilder::new("chrT", 100, 1000)
+        .id("trx01")
+        .gene_id("gene01")
+        .strand(Forward)
+        .attribute("tag", "basic")
+        .coords(vec![(100, 300), (400, 500), (700, 1000)], Some((200, 800)))
+        .coding_incl_stop(true)
+        .build()
+        .expect("a transcript");
+
+    let copy = TBuilder::from_transcript(&trx).build().expect("a transcript");
+    assert_eq!(copy, trx);
+    assert_eq!(exon_coords(&copy), exon_coords(&trx));
+    assert_eq!(copy.coding_coord(true), Some((200, 800)));
+    assert_eq!(copy.gene_id(), Some("gene01"));
+    assert_eq!(copy.attributes().get("tag"), Some(&"basic".to_owned()));
+
+    let extended = TBuilder::from_transcript(&trx)
+        .id("trx02")
+        .coord(100, 1200)
+        .coords(vec![(100, 300), (400, 500), (700, 1200)], trx.coding_coord(true))
+        .build()
+        .expect("a transcript");
+    assert_eq!(extended.id(), Some("trx02"));
+    assert_eq!(extended.exons()[2].transcript_id(), Some("trx02"));
+    assert_eq!(extended.interval(), &Interval::new(100..1200).unwrap());
+    assert_eq!(extended.coding_coord(true), Some((200, 800)));
+}
+
+#[test]
+fn transcript_into_iter() {
+    let trx = TBuilder::new("chrT", 100, 1000)
+        .strand(Forward)
+        .coords(vec![(100, 300), (400, 500), (700, 1000)], Some((200, 800)))
+        .build()
+        .expect("a transcript");
+
+    let mut starts = Vec::new();
+    for exon in &trx {
+        starts.push(exon.interval().start);
+    }
+    assert_eq!(starts, vec![100, 400, 700]);
+    let n_features = trx.iter().map(|exon| exon.iter().count()).collect::<Vec<_>>();
+    assert_eq!(n_features, vec![3, 1, 3]);
+    let exons = trx.into_iter().collect::<Vec<_>>();
+    assert_eq!(exons.len(), 3);
+}
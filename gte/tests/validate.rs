@@ -0,0 +1,152 @@
+extern crate bio;
+extern crate gte;
+
+use std::collections::HashMap;
+
+use bio::utils::Interval;
+
+use gte::{EBuilder, Exon, ExonFeature, ExonFeatureKind, Strand, TBuilder, Transcript,
+          ValidationCheck, Validator};
+use ExonFeatureKind::*;
+use Strand::*;
+
+
+fn make_feature(start: u64, end: u64, kind: ExonFeatureKind) -> ExonFeature {
+    ExonFeature::new(Interval::new(start..end).unwrap(), kind)
+}
+
+fn make_exon(start: u64, end: u64, features: Vec<ExonFeature>) -> Exon {
+    EBuilder::new("chrT", start, end)
+        .strand(Forward)
+        .features(features)
+        .build().unwrap()
+}
+
+fn make_trx(start: u64, end: u64, exons: Vec<Exon>) -> Transcript {
+    TBuilder::new("chrT", start, end)
+        .strand(Forward)
+        .id("trx01")
+        .exons(exons)
+        .build().unwrap()
+}
+
+fn checks(trx: &Transcript, validator: &Validator) -> Vec<ValidationCheck> {
+    validator.validate_transcript(trx).issues().iter()
+        .map(|issue| issue.check())
+        .collect()
+}
+
+#[test]
+fn validator_valid_transcript() {
+    let trx = TBuilder::new("chrT", 100, 1000)
+        .strand(Forward)
+        .id("trx01")
+        .coords(vec![(100, 300), (400, 500), (700, 1000)], Some((200, 800)))
+        .build().unwrap();
+    let report = Validator::new().validate_transcript(&trx);
+    assert_eq!(report.transcript_id(), Some("trx01"));
+    assert!(report.is_valid(), "{:?}", report);
+}
+
+#[test]
+fn validator_cds_length() {
+    let trx = TBuilder::new("chrT", 100, 1000)
+        .strand(Forward)
+        .coords(vec![(100, 300), (400, 500), (700, 1000)], Some((200, 801)))
+        .build().unwrap();
+    assert_eq!(checks(&trx, &Validator::new()), vec![ValidationCheck::CdsLength]);
+}
+
+#[test]
+fn validator_exon_order_and_span() {
+    let trx = make_trx(100, 1000, vec![make_exon(400, 500, vec![]), make_exon(100, 300, vec![])]);
+    assert_eq!(checks(&trx, &Validator::new()),
+               vec![ValidationCheck::ExonOrder, ValidationCheck::TranscriptSpan]);
+}
+
+#[test]
+fn validator_cds_in_exons_and_codons() {
+    let exon = make_exon(100, 300, vec![make_feature(250, 350, CDS { frame: Some(0) })]);
+    let trx = make_trx(100, 300, vec![exon]);
+    assert_eq!(checks(&trx, &Validator::new()),
+               vec![ValidationCheck::CdsLength, ValidationCheck::CdsInExons,
+                    ValidationCheck::Codons, ValidationCheck::Codons]);
+
+    let mut validator = Validator::new();
+    let _ = validator
+        .check(ValidationCheck::CdsLength, false)
+        .check(ValidationCheck::Codons, false);
+    assert_eq!(checks(&trx, &validator), vec![ValidationCheck::CdsInExons]);
+}
+
+fn make_splice_source() -> HashMap<String, Vec<u8>> {
+    let mut source = HashMap::new();
+    source.insert("chrS".to_owned(), b"AAAAGTCCAGAAAAAACTCCCCACAAAAAA".to_vec());
+    source
+}
+
+fn make_splice_trx(strand: Strand, exon_coords: Vec<(u64, u64)>) -> Transcript {
+    let (start, end) = (exon_coords[0].0, exon_coords[exon_coords.len() - 1].1);
+    TBuilder::new("chrS", start, end)
+        .strand(strand)
+        .coords(exon_coords, None)
+        .build().unwrap()
+}
+
+#[test]
+fn validator_splice_sites_canonical() {
+    let mut source = make_splice_source();
+    let validator = Validator::new();
+    for trx in vec![make_splice_trx(Forward, vec![(0, 4), (10, 16)]),
+                    make_splice_trx(Reverse, vec![(10, 16), (24, 30)])] {
+        let report = validator.validate_transcript_with_sequence(&trx, &mut source)
+            .expect("a report");
+        assert!(report.is_valid(), "{:?}", report);
+    }
+}
+
+#[test]
+fn validator_splice_sites_non_canonical() {
+    let mut source = make_splice_source();
+    let trx = make_splice_trx(Forward, vec![(10, 16), (24, 30)]);
+    let report = Validator::new().validate_transcript_with_sequence(&trx, &mut source)
+        .expect("a report");
+    assert_eq!(report.issues().len(), 1);
+    assert_eq!(report.issues()[0].check(), ValidationCheck::SpliceSites);
+    assert_eq!(report.issues()[0].message(),
+               "intron 16-24 has non-canonical splice sites CT-AC");
+    // Splice sites are not checked without a sequence source.
+    assert!(Validator::new().validate_transcript(&trx).is_valid());
+
+    let mut validator = Validator::new();
+    let _ = validator.check(ValidationCheck::SpliceSites, false);
+    assert!(validator.validate_transcript_with_sequence(&trx, &mut source)
+                .expect("a report").is_valid());
+}
+
+#[test]
+fn validator_splice_sites_missing_sequence() {
+    let mut source: HashMap<String, Vec<u8>> = HashMap::new();
+    let trx = make_splice_trx(Forward, vec![(0, 4), (10, 16)]);
+    assert!(Validator::new().validate_transcript_with_sequence(&trx, &mut source).is_err());
+}
+
+#[test]
+fn validator_internal_stop_codons() {
+    let mut source = HashMap::new();
+    source.insert("chrF".to_owned(), b"GGCCATGGCCGTAGAAATTTTAACCCGGGG".to_vec());
+    let mut trx = TBuilder::new("chrF", 2, 26)
+        .strand(Forward)
+        .coords(vec![(2, 10), (14, 26)], Some((4, 23)))
+        .build().unwrap();
+    let validator = Validator::new();
+    let report = validator.validate_transcript_with_sequence(&trx, &mut source)
+        .expect("a report");
+    assert_eq!(report.issues().len(), 1);
+    assert_eq!(report.issues()[0].check(), ValidationCheck::InternalStopCodons);
+    assert_eq!(report.issues()[0].message(), "coding region has a stop codon at codon 5");
+
+    trx.set_stop_readthrough(true);
+    assert!(validator.validate_transcript_with_sequence(&trx, &mut source)
+                .expect("a report").is_valid());
+}
@@ -1,5 +1,7 @@
+extern crate bio;
 #[macro_use]
 extern crate clap;
+extern crate flate2;
 extern crate gte;
 #[macro_use]
 extern crate quick_error;
@@ -47,6 +49,10 @@ quick_error! {
             from()
             cause(err)
         }
+        Fasta(msg: String) {
+            description("could not open the indexed FASTA file")
+            display("could not open the indexed FASTA file: {}", msg)
+        }
         Other(msg: &'static str) {
             description(msg)
         }
@@ -68,7 +74,12 @@ fn build_cli<'a, 'b>() -> App<'a, 'b> {
                     AppSettings::SubcommandRequiredElseHelp,
                     AppSettings::DisableHelpSubcommand,
                     AppSettings::VersionlessSubcommands])
+        .subcommand(tools::convert::build_cli::<'a, 'b>())
+        .subcommand(tools::diff::build_cli::<'a, 'b>())
+        .subcommand(tools::extract::build_cli::<'a, 'b>())
+        .subcommand(tools::filter::build_cli::<'a, 'b>())
         .subcommand(tools::gff_to_refflat::build_cli::<'a, 'b>())
+        .subcommand(tools::panel::build_cli::<'a, 'b>())
         .subcommand(tools::stats::build_cli::<'a, 'b>())
 }
 
@@ -77,6 +88,11 @@ fn run(matches: ArgMatches) -> ::Result<()> {
     match matches.subcommand() {
         (tools::stats::NAME, Some(m)) => tools::stats::run(m),
         (tools::gff_to_refflat::NAME, Some(m)) => tools::gff_to_refflat::run(m),
+        (tools::convert::NAME, Some(m)) => tools::convert::run(m),
+        (tools::filter::NAME, Some(m)) => tools::filter::run(m),
+        (tools::extract::NAME, Some(m)) => tools::extract::run(m),
+        (tools::diff::NAME, Some(m)) => tools::diff::run(m),
+        (tools::panel::NAME, Some(m)) => tools::panel::run(m),
         // We should not reach this point since we already require
         // that subcommands must be present in the app settings.
         _ => Err(Error::Other("unexpected command line parsing error")),
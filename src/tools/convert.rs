@@ -0,0 +1,107 @@
+use clap::{App, Arg, ArgMatches, SubCommand};
+use flate2::Compression;
+use flate2::write::GzEncoder;
+use gte::{AnnotationFormat, IdGenerator, IdScheme};
+use gte::convert::{self, ConvertOptions};
+
+use tools::TEMPLATE_SUBCMD;
+use utils;
+use Error;
+
+pub const NAME: &'static str = "convert";
+
+
+pub fn build_cli<'a, 'b>() -> App<'a, 'b> {
+    SubCommand::with_name(NAME)
+        .about("Converts between annotation formats")
+        .template(TEMPLATE_SUBCMD)
+        .arg(Arg::with_name("input")
+                .required(true)
+                .takes_value(true)
+                .help("Path to input annotation file or '-' for stdin"))
+        .arg(Arg::with_name("output")
+                .required(true)
+                .takes_value(true)
+                .help("Path to output annotation file or '-' for stdout"))
+        .arg(Arg::with_name("from")
+                .short("-f")
+                .long("--from")
+                .takes_value(true)
+                .value_name("FORMAT")
+                .possible_values(utils::FORMATS)
+                .display_order(1)
+                .long_help(
+                    "Input format. If not specified, the format is detected from the contents \
+                     or the extension of the input file. Required when reading from stdin."))
+        .arg(Arg::with_name("to")
+                .short("-t")
+                .long("--to")
+                .takes_value(true)
+                .value_name("FORMAT")
+                .possible_values(utils::FORMATS)
+                .display_order(2)
+                .long_help(
+                    "Output format. If not specified, the format is inferred from the extension \
+                     of the output file. Required when writing to stdout."))
+        .arg(Arg::with_name("gzip")
+                .short("-z")
+                .long("--gzip")
+                .takes_value(false)
+                .display_order(3)
+                .help("Compresses the output with gzip, implied by a '.gz' output extension"))
+        .arg(Arg::with_name("sort")
+                .long("--sort")
+                .takes_value(true)
+                .value_name("ORDER")
+                .possible_values(&["natural", "karyotype", "lexicographic"])
+                .display_order(4)
+                .help("Sorts genes by sequence name, in the given order, and coordinates"))
+        .arg(Arg::with_name("strip_versions")
+                .long("--strip-versions")
+                .takes_value(false)
+                .display_order(5)
+                .help("Strips version suffixes from gene and transcript IDs"))
+        .arg(Arg::with_name("generate_ids")
+                .long("--generate-ids")
+                .takes_value(true)
+                .value_name("SCHEME")
+                .possible_values(&["hash", "counter"])
+                .display_order(6)
+                .long_help(
+                    "Generates IDs for genes and transcripts without any, either from a hash of \
+                     their coordinates or from a running number."))
+}
+
+pub fn run(args: &ArgMatches) -> ::Result<()> {
+
+    let output = args.value_of("output").unwrap();
+
+    let mut reader = utils::resolve_annotation_reader(args.value_of("input").unwrap(),
+                                                      args.value_of("from"))?;
+    if let Some(raw) = args.value_of("generate_ids") {
+        let scheme = if raw == "counter" { IdScheme::Counter } else { IdScheme::Hash };
+        let _ = reader.id_generator(IdGenerator::new(scheme));
+    }
+
+    let to = match args.value_of("to") {
+        Some(raw) => utils::resolve_format(raw)?,
+        None => AnnotationFormat::from_extension(output)
+            .ok_or(Error::Other("output format can not be inferred from the output path"))?,
+    };
+    let mut opts = ConvertOptions::new().strip_id_versions(args.is_present("strip_versions"));
+    if let Some(raw) = args.value_of("sort") {
+        opts = opts.sort(utils::resolve_seq_order(raw)?);
+    }
+
+    let out_writer = utils::resolve_writer(output)?;
+    let mut writer =
+        if args.is_present("gzip") || output.ends_with(".gz") {
+            convert::create_writer(GzEncoder::new(out_writer, Compression::Default), to)
+        } else {
+            convert::create_writer(out_writer, to)
+        };
+
+    let _ = convert::write_genes(&mut reader, &mut *writer, &opts)?;
+
+    Ok(())
+}
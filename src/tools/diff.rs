@@ -0,0 +1,59 @@
+use clap::{App, Arg, ArgMatches, SubCommand};
+use gte::{AnnotationDiff, DiffOptions, DiffWriter};
+
+use tools::TEMPLATE_SUBCMD;
+use utils;
+
+pub const NAME: &'static str = "diff";
+
+
+pub fn build_cli<'a, 'b>() -> App<'a, 'b> {
+    SubCommand::with_name(NAME)
+        .about("Reports added, removed, and changed genes and transcripts")
+        .template(TEMPLATE_SUBCMD)
+        .arg(Arg::with_name("old")
+                .required(true)
+                .takes_value(true)
+                .help("Path to the old annotation file"))
+        .arg(Arg::with_name("new")
+                .required(true)
+                .takes_value(true)
+                .help("Path to the new annotation file"))
+        .arg(Arg::with_name("output")
+                .short("-o")
+                .long("--output")
+                .takes_value(true)
+                .value_name("PATH")
+                .default_value(utils::STREAM_ARG)
+                .display_order(1)
+                .help("Path to the output TSV report or '-' for stdout"))
+        .arg(Arg::with_name("from")
+                .short("-f")
+                .long("--from")
+                .takes_value(true)
+                .value_name("FORMAT")
+                .possible_values(utils::FORMATS)
+                .display_order(2)
+                .help("Format of both inputs, detected if not specified"))
+        .arg(Arg::with_name("ignore_versions")
+                .long("--ignore-versions")
+                .takes_value(false)
+                .display_order(3)
+                .help("Matches gene and transcript IDs without their version suffixes"))
+}
+
+pub fn run(args: &ArgMatches) -> ::Result<()> {
+
+    let format = args.value_of("from");
+    let mut old_reader = utils::resolve_annotation_reader(args.value_of("old").unwrap(), format)?;
+    let mut new_reader = utils::resolve_annotation_reader(args.value_of("new").unwrap(), format)?;
+
+    let mut opts = DiffOptions::new();
+    let _ = opts.ignore_id_versions(args.is_present("ignore_versions"));
+    let diff = AnnotationDiff::from_genes_with(old_reader.genes()?, new_reader.genes()?, &opts)?;
+
+    let mut writer = DiffWriter::from_writer(
+        utils::resolve_writer(args.value_of("output").unwrap())?);
+    writer.write_diff(&diff)?;
+    writer.flush().map_err(::Error::from)
+}
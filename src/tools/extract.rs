@@ -0,0 +1,103 @@
+use bio::io::fasta;
+use clap::{App, Arg, ArgMatches, SubCommand};
+use gte::{self, CodonTable, SequenceError, Transcript};
+
+use tools::TEMPLATE_SUBCMD;
+use utils;
+use Error;
+
+pub const NAME: &'static str = "extract";
+
+
+pub fn build_cli<'a, 'b>() -> App<'a, 'b> {
+    SubCommand::with_name(NAME)
+        .about("Extracts transcript, coding, or protein sequences")
+        .template(TEMPLATE_SUBCMD)
+        .arg(Arg::with_name("input")
+                .required(true)
+                .takes_value(true)
+                .help("Path to input annotation file or '-' for stdin"))
+        .arg(Arg::with_name("output")
+                .required(true)
+                .takes_value(true)
+                .help("Path to output FASTA file or '-' for stdout"))
+        .arg(Arg::with_name("fasta")
+                .long("--fasta")
+                .required(true)
+                .takes_value(true)
+                .value_name("PATH")
+                .display_order(1)
+                .help("Path to genome FASTA file, indexed with 'samtools faidx'"))
+        .arg(Arg::with_name("kind")
+                .short("-k")
+                .long("--kind")
+                .takes_value(true)
+                .value_name("KIND")
+                .possible_values(&["cdna", "cds", "protein"])
+                .default_value("cdna")
+                .display_order(2)
+                .long_help(
+                    "Kind of sequences to extract: spliced transcripts (cdna), coding regions \
+                     including their stop codons (cds), or translated proteins (protein). \
+                     Noncoding transcripts are skipped for the cds and protein kinds."))
+        .arg(Arg::with_name("from")
+                .short("-f")
+                .long("--from")
+                .takes_value(true)
+                .value_name("FORMAT")
+                .possible_values(utils::FORMATS)
+                .display_order(3)
+                .help("Input format, detected if not specified; required for stdin"))
+        .arg(Arg::with_name("codon_table")
+                .long("--codon-table")
+                .takes_value(true)
+                .value_name("ID")
+                .default_value("1")
+                .display_order(4)
+                .help("NCBI identifier of the codon table used for translation"))
+}
+
+pub fn run(args: &ArgMatches) -> ::Result<()> {
+
+    let table = args.value_of("codon_table").unwrap().parse::<u8>().ok()
+        .and_then(CodonTable::from_ncbi_id)
+        .ok_or(Error::Other("unsupported codon table"))?;
+    let kind = args.value_of("kind").unwrap();
+
+    let mut source = fasta::IndexedReader::from_file(&args.value_of("fasta").unwrap())
+        .map_err(|e| Error::Fasta(e.to_string()))?;
+    let mut reader = utils::resolve_annotation_reader(args.value_of("input").unwrap(),
+                                                      args.value_of("from"))?;
+    let mut writer = utils::resolve_writer(args.value_of("output").unwrap())
+        .map(fasta::Writer::new)?;
+
+    for result in reader.transcripts()? {
+        let trx = result?;
+        if kind != "cdna" && trx.coding_coord(true).is_none() {
+            continue;
+        }
+        let seq = match kind {
+            "cds" => trx.cds_sequence(&mut source, true),
+            "protein" => trx.translate(&mut source, &table),
+            _ => trx.spliced_sequence(&mut source),
+        };
+        match seq {
+            Ok(seq) => {
+                let desc = header(&trx);
+                writer.write(trx.id().unwrap_or("."), desc.as_ref().map(|d| d.as_str()), &seq)?;
+            },
+            Err(e) => match *e.inner() {
+                gte::Error::Sequence(SequenceError::InternalStopCodon(..)) =>
+                    eprintln!("skipping: {}", e),
+                _ => return Err(::Error::from(e)),
+            },
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the description of the FASTA record of the given transcript.
+fn header(transcript: &Transcript) -> Option<String> {
+    transcript.gene_id().map(|gid| format!("gene_id={}", gid))
+}
@@ -0,0 +1,119 @@
+use clap::{App, Arg, ArgMatches, SubCommand};
+use gte::{FeatureFilter, FilteredGenes, FilteredTranscripts, parse_region};
+use gte::convert;
+
+use tools::TEMPLATE_SUBCMD;
+use utils;
+
+pub const NAME: &'static str = "filter";
+
+
+pub fn build_cli<'a, 'b>() -> App<'a, 'b> {
+    SubCommand::with_name(NAME)
+        .about("Filters genes or transcripts by their attributes and coordinates")
+        .template(TEMPLATE_SUBCMD)
+        .arg(Arg::with_name("input")
+                .required(true)
+                .takes_value(true)
+                .help("Path to input annotation file or '-' for stdin"))
+        .arg(Arg::with_name("output")
+                .required(true)
+                .takes_value(true)
+                .help("Path to output annotation file or '-' for stdout"))
+        .arg(Arg::with_name("from")
+                .short("-f")
+                .long("--from")
+                .takes_value(true)
+                .value_name("FORMAT")
+                .possible_values(utils::FORMATS)
+                .display_order(1)
+                .help("Input format, detected if not specified; required for stdin"))
+        .arg(Arg::with_name("to")
+                .short("-t")
+                .long("--to")
+                .takes_value(true)
+                .value_name("FORMAT")
+                .possible_values(utils::FORMATS)
+                .display_order(2)
+                .help("Output format, the input format if not specified"))
+        .arg(Arg::with_name("biotype")
+                .long("--biotype")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .value_name("VALUE")
+                .display_order(3)
+                .help("Biotype to keep, may be given multiple times"))
+        .arg(Arg::with_name("chrom")
+                .long("--chrom")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .value_name("NAME")
+                .display_order(4)
+                .help("Sequence name to keep, may be given multiple times"))
+        .arg(Arg::with_name("region")
+                .long("--region")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .value_name("SEQ:START-END")
+                .display_order(5)
+                .help("One-based, inclusive region to overlap, may be given multiple times"))
+        .arg(Arg::with_name("tag")
+                .long("--tag")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .value_name("VALUE")
+                .display_order(6)
+                .help("Transcript tag to keep, may be given multiple times"))
+        .arg(Arg::with_name("transcripts")
+                .long("--transcripts")
+                .takes_value(false)
+                .display_order(7)
+                .long_help(
+                    "If specified, transcripts are filtered and written individually. \
+                     Otherwise, genes are filtered by their own biotypes and coordinates and \
+                     are written with all their transcripts if any of them has any of the \
+                     given tags."))
+}
+
+pub fn run(args: &ArgMatches) -> ::Result<()> {
+
+    let mut filter = FeatureFilter::new();
+    for biotype in args.values_of("biotype").into_iter().flat_map(|values| values) {
+        let _ = filter.biotype(biotype);
+    }
+    for seq_name in args.values_of("chrom").into_iter().flat_map(|values| values) {
+        let _ = filter.seq_name(seq_name);
+    }
+    for region in args.values_of("region").into_iter().flat_map(|values| values) {
+        let (seq_name, start, end) = parse_region(region)?;
+        let _ = filter.region(seq_name, start, end);
+    }
+    for tag in args.values_of("tag").into_iter().flat_map(|values| values) {
+        let _ = filter.tag(tag);
+    }
+
+    let mut reader = utils::resolve_annotation_reader(args.value_of("input").unwrap(),
+                                                      args.value_of("from"))?;
+    let to = match args.value_of("to") {
+        Some(raw) => utils::resolve_format(raw)?,
+        None => reader.format(),
+    };
+    let mut writer = utils::resolve_writer(args.value_of("output").unwrap())
+        .map(|w| convert::create_writer(w, to))?;
+
+    if args.is_present("transcripts") {
+        for transcript in FilteredTranscripts::new(reader.transcripts()?, filter) {
+            writer.write_transcript(&transcript?)?;
+        }
+    } else {
+        for gene in FilteredGenes::new(reader.genes()?, filter) {
+            writer.write_gene(&gene?)?;
+        }
+    }
+
+    Ok(())
+}
@@ -62,6 +62,22 @@ pub fn build_cli<'a, 'b>() -> App<'a, 'b> {
                     "If not specified, only GFF transcripts with start and stop codons will be \
                      created. If specified, GFF transcripts without start and/or stop codons \
                      will be created using the min/max coordinates of all their CDS."))
+        .arg(Arg::with_name("refseq")
+                .long("--refseq")
+                .display_order(7)
+                .takes_value(false)
+                .long_help(
+                    "If specified, the input is read as an NCBI RefSeq GFF3 file, whose mRNA, \
+                     ncRNA, and other RNA rows are read as transcripts. The --gid and --tid \
+                     options are ignored in this mode."))
+        .arg(Arg::with_name("prokaryotic")
+                .long("--prokaryotic")
+                .display_order(8)
+                .takes_value(false)
+                .long_help(
+                    "If specified, the input is read as a prokaryotic GFF3 file, such as those \
+                     created by Prokka or PGAP, and each CDS is read as a single-exon \
+                     transcript. The --gid and --tid options are ignored in this mode."))
 }
 
 pub fn run(args: &ArgMatches) -> ::Result<()> {
@@ -76,7 +92,9 @@ pub fn run(args: &ArgMatches) -> ::Result<()> {
         .transcript_id_attr(args.value_of("transcript_id_attr").unwrap())
         .seq_name_prefix(args.value_of("seq_prefix"))
         .seq_name_lstrip(args.value_of("seq_lstrip"))
-        .loose_codons(args.is_present("loose_codons"));
+        .loose_codons(args.is_present("loose_codons"))
+        .refseq(args.is_present("refseq"))
+        .prokaryotic(args.is_present("prokaryotic"));
 
     let mut writer = utils::resolve_writer(args.value_of("output").unwrap())
         .map(|w| RefFlatWriter::from_writer(w))?;
@@ -85,8 +103,8 @@ pub fn run(args: &ArgMatches) -> ::Result<()> {
         let wresult = result
             .and_then(|ref trx| writer.write_transcript(trx));
         if let Err(e) = wresult {
-            if let gte::Error::Gff(gffe) = e {
-                eprintln!("skipping: {}", gffe);
+            if let gte::Error::Gff(_) = *e.inner() {
+                eprintln!("skipping: {}", e);
             } else {
                 return Err(::Error::from(e));
             }
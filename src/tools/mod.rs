@@ -1,5 +1,10 @@
 //! Functions invoked by the subcommands.
 
+pub mod convert;
+pub mod diff;
+pub mod extract;
+pub mod filter;
+pub mod panel;
 pub mod stats;
 pub mod gff_to_refflat;
 
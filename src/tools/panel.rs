@@ -0,0 +1,98 @@
+use std::io::{BufRead, BufReader};
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+use gte::{GenomeAnnotation, PanelBuilder, PanelRegionKind, write_panel_bed};
+
+use tools::TEMPLATE_SUBCMD;
+use utils;
+use Error;
+
+pub const NAME: &'static str = "panel";
+
+
+pub fn build_cli<'a, 'b>() -> App<'a, 'b> {
+    SubCommand::with_name(NAME)
+        .about("Creates a BED file of the target regions of a gene panel")
+        .template(TEMPLATE_SUBCMD)
+        .arg(Arg::with_name("input")
+                .required(true)
+                .takes_value(true)
+                .help("Path to input annotation file or '-' for stdin"))
+        .arg(Arg::with_name("genes")
+                .required(true)
+                .takes_value(true)
+                .help("Path to a file of gene names or IDs, one per line, or '-' for stdin"))
+        .arg(Arg::with_name("output")
+                .short("-o")
+                .long("--output")
+                .takes_value(true)
+                .value_name("PATH")
+                .default_value(utils::STREAM_ARG)
+                .display_order(1)
+                .help("Path to the output BED file or '-' for stdout"))
+        .arg(Arg::with_name("from")
+                .short("-f")
+                .long("--from")
+                .takes_value(true)
+                .value_name("FORMAT")
+                .possible_values(utils::FORMATS)
+                .display_order(2)
+                .help("Input format, detected if not specified; required for stdin"))
+        .arg(Arg::with_name("padding")
+                .long("--padding")
+                .takes_value(true)
+                .value_name("BASES")
+                .default_value("0")
+                .display_order(3)
+                .help("Number of bases added to both sides of each region"))
+        .arg(Arg::with_name("locus")
+                .long("--locus")
+                .takes_value(false)
+                .display_order(4)
+                .help("Creates one region per gene locus instead of per exon"))
+        .arg(Arg::with_name("cds_only")
+                .long("--cds-only")
+                .takes_value(false)
+                .display_order(5)
+                .help("Limits regions to coding regions"))
+        .arg(Arg::with_name("exon_names")
+                .long("--exon-names")
+                .takes_value(false)
+                .display_order(6)
+                .help("Names exon regions after their genes and exon numbers, e.g. GENE_exon3"))
+}
+
+pub fn run(args: &ArgMatches) -> ::Result<()> {
+
+    let input = args.value_of("input").unwrap();
+    let genes_arg = args.value_of("genes").unwrap();
+    if input == utils::STREAM_ARG && genes_arg == utils::STREAM_ARG {
+        return Err(Error::Other("annotation and genes can not both be read from stdin"));
+    }
+    let padding = args.value_of("padding").unwrap().parse::<u64>()
+        .map_err(|_| Error::Other("padding must be a nonnegative integer"))?;
+
+    let mut genes = Vec::new();
+    for line in BufReader::new(utils::resolve_reader(genes_arg)?).lines() {
+        let line = line?;
+        let query = line.trim();
+        if !query.is_empty() && !query.starts_with('#') {
+            genes.push(query.to_owned());
+        }
+    }
+
+    let mut reader = utils::resolve_annotation_reader(input, args.value_of("from"))?;
+    let annotation = GenomeAnnotation::from_genes_stream(reader.genes()?)?;
+
+    let mut builder = PanelBuilder::new();
+    let _ = builder.padding(padding)
+        .cds_only(args.is_present("cds_only"))
+        .exon_names(args.is_present("exon_names"));
+    if args.is_present("locus") {
+        let _ = builder.kind(PanelRegionKind::Locus);
+    }
+    let regions = builder.regions(&annotation, &genes)?;
+
+    let mut out = utils::resolve_writer(args.value_of("output").unwrap())?;
+    write_panel_bed(&mut out, &regions).map_err(Error::from)
+}
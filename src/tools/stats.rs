@@ -1,6 +1,11 @@
+use std::io::{self, Write};
+
 use clap::{App, Arg, ArgMatches, SubCommand};
+use gte::{AnnotationStats, LengthStats};
 
 use tools::TEMPLATE_SUBCMD;
+use utils;
+use Error;
 
 pub const NAME: &'static str = "stats";
 
@@ -14,8 +19,59 @@ pub fn build_cli<'a, 'b>() -> App<'a, 'b> {
                     .help("Path to input annotation file or '-' for stdin")
                     .takes_value(true)
                     .required(true))
+        .arg(Arg::with_name("from")
+                    .short("-f")
+                    .long("--from")
+                    .takes_value(true)
+                    .value_name("FORMAT")
+                    .possible_values(utils::FORMATS)
+                    .long_help(
+                        "Input format. If not specified, the format is detected from the \
+                         contents or the extension of the input file. Required when reading \
+                         from stdin."))
 }
 
 pub fn run(args: &ArgMatches) -> ::Result<()> {
-    unimplemented!()
+
+    let mut reader = utils::resolve_annotation_reader(args.value_of("input").unwrap(),
+                                                      args.value_of("from"))?;
+
+    let stats = AnnotationStats::from_genes(reader.genes()?)?;
+
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    writeln!(out, "genes\t{}", stats.num_genes())?;
+    writeln!(out, "transcripts\t{}", stats.num_transcripts())?;
+    writeln!(out, "coding_transcripts\t{}", stats.num_coding_transcripts())?;
+    writeln!(out, "exons\t{}", stats.num_exons())?;
+
+    writeln!(out, "\n#length\tcount\tmin\tmedian\tmean\tmax\ttotal")?;
+    write_lengths(&mut out, "exon", stats.exon_lengths())?;
+    write_lengths(&mut out, "intron", stats.intron_lengths())?;
+    write_lengths(&mut out, "cds", stats.cds_lengths())?;
+
+    writeln!(out, "\n#gene_biotype\tcount")?;
+    for (biotype, count) in stats.gene_biotypes() {
+        writeln!(out, "{}\t{}", biotype, count)?;
+    }
+    writeln!(out, "\n#transcript_biotype\tcount")?;
+    for (biotype, count) in stats.transcript_biotypes() {
+        writeln!(out, "{}\t{}", biotype, count)?;
+    }
+
+    writeln!(out, "\n#seq_name\tgenes\ttranscripts\texons")?;
+    for (seq_name, seq) in stats.seqs().iter() {
+        writeln!(out, "{}\t{}\t{}\t{}", seq_name, seq.genes, seq.transcripts, seq.exons)?;
+    }
+
+    Ok(())
+}
+
+fn write_lengths<W: Write>(out: &mut W, name: &str, lengths: &LengthStats) -> ::Result<()> {
+    let field = |value: Option<u64>| value.map_or(".".to_owned(), |v| v.to_string());
+    writeln!(out, "{}\t{}\t{}\t{}\t{}\t{}\t{}",
+             name, lengths.count(), field(lengths.min()), field(lengths.median()),
+             lengths.mean().map_or(".".to_owned(), |v| format!("{:.1}", v)),
+             field(lengths.max()), lengths.total())
+        .map_err(Error::from)
 }
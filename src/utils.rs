@@ -1,12 +1,14 @@
 use std::fs;
 use std::io::{self, BufReader, BufWriter, Read, Write};
 
-use gte::GffType;
+use gte::{AnnotationFormat, AnnotationReader, GffType, SeqOrder};
 
 use Error;
 
 
-const STREAM_ARG: &'static str = "-";
+pub const STREAM_ARG: &'static str = "-";
+
+pub const FORMATS: &'static [&'static str] = &["refflat", "genepred", "gtf", "gff3", "bed"];
 
 
 pub fn resolve_reader(raw_arg: &str) -> ::Result<Box<Read>>
@@ -38,3 +40,42 @@ pub fn resolve_gff_type(raw_arg: &str) -> ::Result<GffType> {
         _ => Err(Error::Other("invalid gff type")),
     }
 }
+
+pub fn resolve_annotation_reader(raw_arg: &str, raw_format: Option<&str>)
+    -> ::Result<AnnotationReader>
+{
+    let format = match raw_format {
+        Some(raw) => Some(resolve_format(raw)?),
+        None => None,
+    };
+    match (raw_arg, format) {
+        (STREAM_ARG, Some(format)) =>
+            Ok(AnnotationReader::from_reader(io::stdin(), format)),
+        (STREAM_ARG, None) =>
+            Err(Error::Other("input format must be specified when reading from stdin")),
+        (path, Some(format)) => AnnotationReader::open_as(path, format).map_err(::Error::from),
+        (path, None) => AnnotationReader::open(path).map_err(::Error::from),
+    }
+}
+
+pub fn resolve_format(raw_arg: &str) -> ::Result<AnnotationFormat> {
+
+    match raw_arg.to_owned().to_lowercase().as_str() {
+        "refflat" => Ok(AnnotationFormat::RefFlat),
+        "genepred" | "gp" => Ok(AnnotationFormat::GenePred),
+        "gtf" | "gtf2" => Ok(AnnotationFormat::Gtf),
+        "gff3" | "gff" => Ok(AnnotationFormat::Gff3),
+        "bed" | "bed12" => Ok(AnnotationFormat::Bed12),
+        _ => Err(Error::Other("invalid annotation format")),
+    }
+}
+
+pub fn resolve_seq_order(raw_arg: &str) -> ::Result<SeqOrder> {
+
+    match raw_arg.to_owned().to_lowercase().as_str() {
+        "natural" => Ok(SeqOrder::Natural),
+        "karyotype" => Ok(SeqOrder::Karyotype),
+        "lexicographic" => Ok(SeqOrder::Lexicographic),
+        _ => Err(Error::Other("invalid sequence order")),
+    }
+}